@@ -1,3 +1,14 @@
+use std::path::PathBuf;
+
+#[path = "build/tsgen.rs"]
+mod tsgen;
+
 fn main() {
+    let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR"));
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=build");
+    if let Err(e) = tsgen::generate(&manifest_dir.join("src"), &manifest_dir.join("../src/types/generated.ts")) {
+        println!("cargo:warning=TypeScript bindings not generated: {}", e);
+    }
     tauri_build::build()
 }
@@ -0,0 +1,586 @@
+//! TypeScript bindings
+//!
+//! Generates `src/types/generated.ts` from the backend sources at build
+//! time, so the frontend's view of the API cannot drift from the Rust side:
+//!
+//! - an interface or union for every type deriving `Serialize` or
+//!   `Deserialize`, following its `rename_all`, `rename`, `tag`, `flatten`,
+//!   `skip` and `default` attributes; fields a Deserialize-only type can
+//!   leave out (`Option`s, and any under a container `default`) are optional
+//! - `Commands`, mapping each `#[tauri::command]` to its camelCase
+//!   arguments (injected state, app and window parameters left out) and its
+//!   result (the `Ok` type of a `Result`)
+//! - `Events`, mapping each topic to its payload: the types documented as
+//!   "Payload of `topic`", struct literals passed to `events::publish`, and
+//!   `unknown` for other published topics
+//!
+//! The file is only rewritten when its contents change.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use syn::visit::Visit;
+use syn::{Attribute, Expr, Fields, FnArg, GenericArgument, Item, Lit, Pat, PathArguments, ReturnType, Type};
+
+/// Command parameters Tauri injects rather than reading from the arguments
+const INJECTED: &[&str] = &["State", "AppHandle", "Window", "WebviewWindow", "Webview"];
+
+const HEADER: &str = "// Generated from the backend sources by src-tauri/build/tsgen.rs; do not edit.\n";
+
+// ============================================================================
+// Attributes
+// ============================================================================
+
+#[derive(Default)]
+struct Serde {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    tag: Option<String>,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+    optional: bool,
+}
+
+fn serde_attrs(attrs: &[Attribute]) -> Serde {
+    let mut serde = Serde::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            let key = meta.path.get_ident().map(|ident| ident.to_string()).unwrap_or_default();
+            let value = if meta.input.peek(syn::Token![=]) {
+                let expr: Expr = meta.value()?.parse()?;
+                match expr {
+                    Expr::Lit(syn::ExprLit { lit: Lit::Str(text), .. }) => Some(text.value()),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+            match key.as_str() {
+                "rename" => serde.rename = value,
+                "rename_all" => serde.rename_all = value,
+                "tag" => serde.tag = value,
+                "default" => serde.default = true,
+                "skip" => serde.skip = true,
+                "skip_serializing" | "skip_serializing_if" => serde.optional = true,
+                "flatten" => serde.flatten = true,
+                _ => {}
+            }
+            Ok(())
+        });
+    }
+    serde
+}
+
+fn derives(attrs: &[Attribute]) -> BTreeSet<String> {
+    let mut derives = BTreeSet::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if let Some(last) = meta.path.segments.last() {
+                derives.insert(last.ident.to_string());
+            }
+            Ok(())
+        });
+    }
+    derives
+}
+
+fn docs(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: Expr::Lit(syn::ExprLit { lit: Lit::Str(text), .. }),
+                ..
+            }) => Some(text.value().trim().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn doc_comment(lines: &[String], indent: &str) -> String {
+    match lines {
+        [] => String::new(),
+        [line] => format!("{}/** {} */\n", indent, line),
+        lines => {
+            let body: String = lines.iter().map(|line| format!("{} * {}\n", indent, line).replace(" * \n", " *\n")).collect();
+            format!("{}/**\n{}{} */\n", indent, body, indent)
+        }
+    }
+}
+
+/// Topics named in a "Payload of `topic`" doc comment
+fn payload_topics(lines: &[String]) -> Vec<String> {
+    let text = lines.join(" ");
+    let Some(rest) = text.strip_prefix("Payload of") else {
+        return Vec::new();
+    };
+    rest.split('`').skip(1).step_by(2).map(str::to_string).collect()
+}
+
+// ============================================================================
+// Names
+// ============================================================================
+
+fn split_words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let mut word = String::new();
+        for c in part.chars() {
+            if c.is_uppercase() && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        words.push(word);
+    }
+    words.into_iter().map(|word| word.to_lowercase()).collect()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map(|first| first.to_uppercase().chain(chars).collect()).unwrap_or_default()
+}
+
+/// Apply a serde `rename_all` rule to a field or variant name
+fn rename(name: &str, rule: Option<&str>) -> String {
+    let words = split_words(name);
+    match rule {
+        Some("lowercase") => words.concat(),
+        Some("UPPERCASE") => words.concat().to_uppercase(),
+        Some("snake_case") => words.join("_"),
+        Some("SCREAMING_SNAKE_CASE") => words.join("_").to_uppercase(),
+        Some("kebab-case") => words.join("-"),
+        Some("camelCase") => {
+            let rest: String = words.iter().skip(1).map(|word| capitalize(word)).collect();
+            format!("{}{}", words.first().cloned().unwrap_or_default(), rest)
+        }
+        Some("PascalCase") => words.iter().map(|word| capitalize(word)).collect(),
+        _ => name.to_string(),
+    }
+}
+
+fn property(name: &str) -> String {
+    if name.chars().all(|c| c.is_alphanumeric() || c == '_') && !name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
+
+// ============================================================================
+// Types
+// ============================================================================
+
+fn type_args(arguments: &PathArguments) -> Vec<&Type> {
+    match arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn array(inner: String) -> String {
+    if inner.contains(' ') {
+        format!("({})[]", inner)
+    } else {
+        format!("{}[]", inner)
+    }
+}
+
+fn ts_type(ty: &Type, known: &BTreeSet<String>) -> String {
+    match ty {
+        Type::Reference(reference) => ts_type(&reference.elem, known),
+        Type::Paren(paren) => ts_type(&paren.elem, known),
+        Type::Group(group) => ts_type(&group.elem, known),
+        Type::Slice(slice) => array(ts_type(&slice.elem, known)),
+        Type::Array(items) => array(ts_type(&items.elem, known)),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => "null".to_string(),
+        Type::Tuple(tuple) => {
+            let items: Vec<String> = tuple.elems.iter().map(|ty| ts_type(ty, known)).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Type::Path(path) => {
+            let Some(segment) = path.path.segments.last() else {
+                return "unknown".to_string();
+            };
+            let args = type_args(&segment.arguments);
+            let arg = |index: usize| args.get(index).map_or("unknown".to_string(), |ty| ts_type(ty, known));
+            let name = segment.ident.to_string();
+            match name.as_str() {
+                "String" | "str" | "PathBuf" | "Path" | "OsString" | "char" | "Decimal" => "string".to_string(),
+                "bool" => "boolean".to_string(),
+                "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+                | "f32" | "f64" => "number".to_string(),
+                "Option" => match arg(0).as_str() {
+                    "unknown" => "unknown".to_string(),
+                    inner => format!("{} | null", inner),
+                },
+                "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => array(arg(0)),
+                "HashMap" | "BTreeMap" => format!("Record<string, {}>", arg(1)),
+                "Box" | "Arc" | "Rc" | "Cow" | "Result" => arg(0),
+                _ if known.contains(&name) => name,
+                _ => "unknown".to_string(),
+            }
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+fn is_option(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|segment| segment.ident == "Option"))
+}
+
+/// How a type's fields may be left out
+#[derive(Clone, Copy)]
+struct Presence {
+    /// Only ever sent to the backend, so a missing `Option` is `None`
+    input: bool,
+    /// Every field has a default (container `#[serde(default)]`)
+    defaults: bool,
+}
+
+/// Object members for named fields, and the flattened types to extend
+fn members(
+    fields: &syn::FieldsNamed,
+    rule: Option<&str>,
+    presence: Presence,
+    known: &BTreeSet<String>,
+    indent: &str,
+) -> (String, Vec<String>) {
+    let mut body = String::new();
+    let mut extends = Vec::new();
+    for field in &fields.named {
+        let serde = serde_attrs(&field.attrs);
+        let Some(ident) = &field.ident else { continue };
+        if serde.skip {
+            continue;
+        }
+        if serde.flatten {
+            extends.push(ts_type(&field.ty, known));
+            continue;
+        }
+        let name = serde.rename.unwrap_or_else(|| rename(&ident.to_string(), rule));
+        let omittable = presence.input && (presence.defaults || is_option(&field.ty));
+        let optional = if serde.default || serde.optional || omittable { "?" } else { "" };
+        body.push_str(&doc_comment(&docs(&field.attrs), indent));
+        body.push_str(&format!("{}{}{}: {};\n", indent, property(&name), optional, ts_type(&field.ty, known)));
+    }
+    (body, extends)
+}
+
+/// Enum variants are taken as they are written
+const OUTPUT: Presence = Presence {
+    input: false,
+    defaults: false,
+};
+
+fn struct_ts(item: &syn::ItemStruct, known: &BTreeSet<String>) -> String {
+    let serde = serde_attrs(&item.attrs);
+    let name = item.ident.to_string();
+    let derives = derives(&item.attrs);
+    let presence = Presence {
+        input: derives.contains("Deserialize") && !derives.contains("Serialize"),
+        defaults: serde.default,
+    };
+    match &item.fields {
+        Fields::Named(fields) => {
+            let (body, extends) = members(fields, serde.rename_all.as_deref(), presence, known, "  ");
+            let extends = extends.into_iter().filter(|ty| ty != "unknown").collect::<Vec<_>>();
+            let extends = if extends.is_empty() { String::new() } else { format!(" extends {}", extends.join(", ")) };
+            format!("export interface {}{} {{\n{}}}\n", name, extends, body)
+        }
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            format!("export type {} = {};\n", name, ts_type(&fields.unnamed[0].ty, known))
+        }
+        Fields::Unnamed(fields) => {
+            let items: Vec<String> = fields.unnamed.iter().map(|field| ts_type(&field.ty, known)).collect();
+            format!("export type {} = [{}];\n", name, items.join(", "))
+        }
+        Fields::Unit => format!("export type {} = null;\n", name),
+    }
+}
+
+fn enum_ts(item: &syn::ItemEnum, known: &BTreeSet<String>) -> String {
+    let serde = serde_attrs(&item.attrs);
+    let mut variants = Vec::new();
+    for variant in &item.variants {
+        let attrs = serde_attrs(&variant.attrs);
+        if attrs.skip {
+            continue;
+        }
+        let tag = attrs.rename.unwrap_or_else(|| rename(&variant.ident.to_string(), serde.rename_all.as_deref()));
+        let ts = match (&serde.tag, &variant.fields) {
+            (None, Fields::Unit) => format!("'{}'", tag),
+            (None, Fields::Unnamed(fields)) if fields.unnamed.len() == 1 => {
+                format!("{{ {}: {} }}", property(&tag), ts_type(&fields.unnamed[0].ty, known))
+            }
+            (None, Fields::Named(fields)) => {
+                let (body, _) = members(fields, None, OUTPUT, known, "");
+                format!("{{ {}: {{ {} }} }}", property(&tag), body.trim_end().replace('\n', " "))
+            }
+            (Some(key), Fields::Unit) => format!("{{ {}: '{}' }}", property(key), tag),
+            (Some(key), Fields::Unnamed(fields)) if fields.unnamed.len() == 1 => {
+                format!("({{ {}: '{}' }} & {})", property(key), tag, ts_type(&fields.unnamed[0].ty, known))
+            }
+            (Some(key), Fields::Named(fields)) => {
+                let (body, _) = members(fields, None, OUTPUT, known, "");
+                let body = body.lines().filter(|line| !line.starts_with("/**")).collect::<Vec<_>>().join(" ");
+                format!("{{ {}: '{}'; {} }}", property(key), tag, body)
+            }
+            (_, Fields::Unnamed(_)) => "unknown".to_string(),
+        };
+        variants.push(ts);
+    }
+    if variants.is_empty() {
+        variants.push("never".to_string());
+    }
+    format!("export type {} =\n  | {};\n", item.ident, variants.join("\n  | "))
+}
+
+// ============================================================================
+// Commands and events
+// ============================================================================
+
+fn is_command(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let segments: Vec<String> = attr.path().segments.iter().map(|segment| segment.ident.to_string()).collect();
+        segments == ["tauri", "command"] || segments == ["command"]
+    })
+}
+
+fn injected(ty: &Type) -> bool {
+    let ty = match ty {
+        Type::Reference(reference) => &reference.elem,
+        ty => ty,
+    };
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|segment| INJECTED.contains(&segment.ident.to_string().as_str())))
+}
+
+fn command_ts(item: &syn::ItemFn, known: &BTreeSet<String>) -> String {
+    let mut args = Vec::new();
+    for input in &item.sig.inputs {
+        let FnArg::Typed(typed) = input else { continue };
+        let Pat::Ident(pat) = &*typed.pat else { continue };
+        if injected(&typed.ty) {
+            continue;
+        }
+        let name = rename(pat.ident.to_string().trim_start_matches("r#"), Some("camelCase"));
+        let optional = if is_option(&typed.ty) { "?" } else { "" };
+        args.push(format!("{}{}: {}", property(&name), optional, ts_type(&typed.ty, known)));
+    }
+    let result = match &item.sig.output {
+        ReturnType::Default => "void".to_string(),
+        ReturnType::Type(_, ty) => match ts_type(ty, known).as_str() {
+            "null" => "void".to_string(),
+            ty => ty.to_string(),
+        },
+    };
+    let args = if args.is_empty() { "Record<string, never>".to_string() } else { format!("{{ {} }}", args.join("; ")) };
+    format!("  {}: {{ args: {}; result: {} }};\n", item.sig.ident, args, result)
+}
+
+/// Topics passed to `events::publish` and `publish_progress`, with the
+/// payload type when it can be told from the call site: a struct or string
+/// literal, or a variable bound to one, to a typed parameter or to the result
+/// of a function with a known return type
+struct Publishes<'a> {
+    known: &'a BTreeSet<String>,
+    /// Free function name -> TypeScript type it returns
+    returns: &'a BTreeMap<String, String>,
+    /// Variables in scope in the current function
+    bindings: BTreeMap<String, String>,
+    topics: BTreeMap<String, Option<String>>,
+}
+
+impl Publishes<'_> {
+    fn known_type(&self, ty: &Type) -> Option<String> {
+        Some(ts_type(ty, self.known)).filter(|ty| !ty.contains("unknown"))
+    }
+
+    fn expr_type(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Reference(reference) => self.expr_type(&reference.expr),
+            Expr::Try(attempt) => self.expr_type(&attempt.expr),
+            Expr::Struct(literal) => {
+                let name = literal.path.segments.last()?.ident.to_string();
+                self.known.contains(&name).then_some(name)
+            }
+            Expr::Lit(syn::ExprLit { lit: Lit::Str(_), .. }) => Some("string".to_string()),
+            Expr::Path(path) => self.bindings.get(&path.path.get_ident()?.to_string()).cloned(),
+            Expr::Call(call) => match &*call.func {
+                Expr::Path(path) => self.returns.get(&path.path.segments.last()?.ident.to_string()).cloned(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Publishes<'_> {
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        let outer = std::mem::take(&mut self.bindings);
+        for input in &item.sig.inputs {
+            if let FnArg::Typed(typed) = input {
+                if let (Pat::Ident(pat), Some(ty)) = (&*typed.pat, self.known_type(&typed.ty)) {
+                    self.bindings.insert(pat.ident.to_string(), ty);
+                }
+            }
+        }
+        syn::visit::visit_item_fn(self, item);
+        self.bindings = outer;
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        let ty = match &local.pat {
+            Pat::Type(typed) => self.known_type(&typed.ty).map(|ty| (&*typed.pat, ty)),
+            pat => local.init.as_ref().and_then(|init| self.expr_type(&init.expr)).map(|ty| (pat, ty)),
+        };
+        if let Some((Pat::Ident(pat), ty)) = ty {
+            self.bindings.insert(pat.ident.to_string(), ty);
+        }
+        syn::visit::visit_local(self, local);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+        let publishes = match &*call.func {
+            Expr::Path(path) => path.path.segments.last().is_some_and(|segment| {
+                segment.ident == "publish" || segment.ident == "publish_progress"
+            }),
+            _ => false,
+        };
+        let args: Vec<&Expr> = call.args.iter().collect();
+        if let (true, Some(Expr::Lit(syn::ExprLit { lit: Lit::Str(topic), .. }))) = (publishes, args.get(1)) {
+            let ty = args.get(2).and_then(|payload| self.expr_type(payload));
+            let entry = self.topics.entry(topic.value()).or_insert(None);
+            if entry.is_none() {
+                *entry = ty;
+            }
+        }
+        syn::visit::visit_expr_call(self, call);
+    }
+}
+
+// ============================================================================
+// Generation
+// ============================================================================
+
+fn serde_items(file: &syn::File) -> impl Iterator<Item = &Item> {
+    file.items.iter().filter(|item| {
+        let attrs = match item {
+            Item::Struct(item) => &item.attrs,
+            Item::Enum(item) => &item.attrs,
+            _ => return false,
+        };
+        let derives = derives(attrs);
+        derives.contains("Serialize") || derives.contains("Deserialize")
+    })
+}
+
+/// Write the bindings for the Rust files in `src` to `out`
+pub fn generate(src: &Path, out: &Path) -> Result<(), String> {
+    let mut paths: Vec<_> = fs::read_dir(src)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    paths.sort();
+    let mut files = Vec::new();
+    for path in &paths {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let file = syn::parse_file(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let module = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        files.push((module, file));
+    }
+
+    let mut known = BTreeSet::new();
+    for (module, file) in &files {
+        for item in serde_items(file) {
+            let name = match item {
+                Item::Struct(item) => item.ident.to_string(),
+                Item::Enum(item) => item.ident.to_string(),
+                _ => continue,
+            };
+            if !known.insert(name.clone()) {
+                println!("cargo:warning=TypeScript bindings: {} in {} duplicates another type's name", name, module);
+            }
+        }
+    }
+
+    let mut types = String::new();
+    let mut commands = String::new();
+    let mut documented: BTreeMap<String, String> = BTreeMap::new();
+    // Return types of free functions whose name is not reused elsewhere
+    let mut returns: BTreeMap<String, Option<String>> = BTreeMap::new();
+    for (_, file) in &files {
+        for item in &file.items {
+            let Item::Fn(item) = item else { continue };
+            let ReturnType::Type(_, ty) = &item.sig.output else { continue };
+            let ty = Some(ts_type(ty, &known)).filter(|ty| !ty.contains("unknown"));
+            returns
+                .entry(item.sig.ident.to_string())
+                .and_modify(|existing| *existing = None)
+                .or_insert(ty);
+        }
+    }
+    let returns: BTreeMap<String, String> =
+        returns.into_iter().filter_map(|(name, ty)| Some((name, ty?))).collect();
+    let mut publishes = Publishes {
+        known: &known,
+        returns: &returns,
+        bindings: BTreeMap::new(),
+        topics: BTreeMap::new(),
+    };
+    let mut emitted = BTreeSet::new();
+    for (module, file) in &files {
+        let mut section = String::new();
+        for item in serde_items(file) {
+            let (name, attrs, ts) = match item {
+                Item::Struct(item) => (item.ident.to_string(), &item.attrs, struct_ts(item, &known)),
+                Item::Enum(item) => (item.ident.to_string(), &item.attrs, enum_ts(item, &known)),
+                _ => continue,
+            };
+            if !emitted.insert(name.clone()) {
+                continue;
+            }
+            let lines = docs(attrs);
+            for topic in payload_topics(&lines) {
+                documented.insert(topic, name.clone());
+            }
+            section.push_str(&format!("\n{}{}", doc_comment(&lines, ""), ts));
+        }
+        if !section.is_empty() {
+            types.push_str(&format!("\n// {}\n{}", module, section));
+        }
+        for item in &file.items {
+            if let Item::Fn(item) = item {
+                if is_command(&item.attrs) {
+                    commands.push_str(&command_ts(item, &known));
+                }
+            }
+        }
+        publishes.visit_file(file);
+    }
+
+    let mut topics: BTreeMap<String, String> =
+        publishes.topics.into_iter().map(|(topic, ty)| (topic, ty.unwrap_or_else(|| "unknown".to_string()))).collect();
+    topics.extend(documented);
+    let events: String = topics.iter().map(|(topic, ty)| format!("  '{}': {};\n", topic, ty)).collect();
+
+    let output = format!(
+        "{}{}\n/** Every backend command: its arguments and the value it resolves to */\nexport interface Commands {{\n{}}}\n\n/** Every event topic the backend publishes, with its payload */\nexport interface Events {{\n{}}}\n",
+        HEADER, types, commands, events
+    );
+    if fs::read_to_string(out).ok().as_deref() != Some(output.as_str()) {
+        fs::write(out, output).map_err(|e| format!("{}: {}", out.display(), e))?;
+    }
+    Ok(())
+}
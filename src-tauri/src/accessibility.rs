@@ -0,0 +1,1100 @@
+//! Keyboard accessibility
+//!
+//! Sticky keys, filter keys, toggle keys and mouse keys, done at the evdev
+//! level so they work in every app the kiosk launches, not just its own
+//! window. While a feature that changes input is on, the keyboards in
+//! /dev/input are grabbed and their keys are passed on, changed as needed,
+//! through a virtual keyboard and mouse made with /dev/uinput:
+//!
+//! - Sticky keys: a modifier pressed on its own stays down for the next
+//!   key; pressed twice it stays down until pressed again.
+//! - Filter keys: keys must be held a while to count (slow keys), and a key
+//!   pressed again too soon after it was released is ignored (bounce keys).
+//! - Toggle keys: Caps Lock, Num Lock and Scroll Lock are announced as
+//!   `toggle-key` so the frontend can play the tone.
+//! - Mouse keys: the numeric keypad moves the pointer and clicks.
+//!
+//! The Windows shortcuts turn the features on and off: Shift five times
+//! for sticky keys, right Shift held eight seconds for filter keys and left
+//! Alt + left Shift + Num Lock for mouse keys. Settings are saved in
+//! `accessibility.json`. Needs read access to /dev/input and write access
+//! to /dev/uinput (the `input` group and a udev rule for uinput).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{events, store};
+
+const ACCESSIBILITY_FILE: &str = "accessibility.json";
+
+const INPUT_DEVICES: &str = "/sys/class/input";
+
+const UINPUT: &str = "/dev/uinput";
+
+/// Name of the virtual device, so it is never grabbed itself
+const DEVICE_NAME: &str = "Kiosk accessibility input";
+
+const EVENT_SIZE: usize = std::mem::size_of::<libc::input_event>();
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How often new keyboards are looked for
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Longest wait for keys to be released before grabbing, so none is left
+/// pressed in the session
+const GRAB_WAIT: Duration = Duration::from_secs(2);
+
+/// Longest gap between the Shift presses of the sticky keys shortcut
+const SHORTCUT_GAP: Duration = Duration::from_secs(1);
+const SHORTCUT_PRESSES: u32 = 5;
+/// How long right Shift is held to turn filter keys on or off
+const FILTER_HOLD: Duration = Duration::from_secs(8);
+
+/// Mouse keys start at this speed, in pixels per second
+const MOUSE_MIN_SPEED: f64 = 40.0;
+
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_REL: u16 = 0x02;
+const EV_REP: u16 = 0x14;
+const REL_X: u16 = 0x00;
+const REL_Y: u16 = 0x01;
+const KEY_A: u16 = 30;
+const KEY_MAX: u16 = 0x2ff;
+const BTN_MISC: u16 = 0x100;
+const BTN_LEFT: u16 = 0x110;
+const BTN_RIGHT: u16 = 0x111;
+const BTN_MIDDLE: u16 = 0x112;
+/// Joystick and gamepad buttons, left off the virtual device so it is not
+/// taken for one
+const BTN_TRIGGER_HAPPY: u16 = 0x2c0;
+const BUS_VIRTUAL: u16 = 0x06;
+
+const KEY_LEFTCTRL: u16 = 29;
+const KEY_LEFTSHIFT: u16 = 42;
+const KEY_RIGHTSHIFT: u16 = 54;
+const KEY_LEFTALT: u16 = 56;
+const KEY_CAPSLOCK: u16 = 58;
+const KEY_NUMLOCK: u16 = 69;
+const KEY_SCROLLLOCK: u16 = 70;
+const KEY_RIGHTCTRL: u16 = 97;
+const KEY_RIGHTALT: u16 = 100;
+const KEY_LEFTMETA: u16 = 125;
+const KEY_RIGHTMETA: u16 = 126;
+
+const KEY_KP7: u16 = 71;
+const KEY_KP8: u16 = 72;
+const KEY_KP9: u16 = 73;
+const KEY_KPMINUS: u16 = 74;
+const KEY_KP4: u16 = 75;
+const KEY_KP5: u16 = 76;
+const KEY_KP6: u16 = 77;
+const KEY_KPPLUS: u16 = 78;
+const KEY_KP1: u16 = 79;
+const KEY_KP2: u16 = 80;
+const KEY_KP3: u16 = 81;
+const KEY_KP0: u16 = 82;
+const KEY_KPDOT: u16 = 83;
+const KEY_KPASTERISK: u16 = 55;
+const KEY_KPSLASH: u16 = 98;
+
+/// Modifiers sticky keys hold, with the names announced for them
+const MODIFIERS: &[(u16, &str)] = &[
+    (KEY_LEFTSHIFT, "shift"),
+    (KEY_RIGHTSHIFT, "shift"),
+    (KEY_LEFTCTRL, "ctrl"),
+    (KEY_RIGHTCTRL, "ctrl"),
+    (KEY_LEFTALT, "alt"),
+    (KEY_RIGHTALT, "altgr"),
+    (KEY_LEFTMETA, "super"),
+    (KEY_RIGHTMETA, "super"),
+];
+
+const LOCK_KEYS: &[(u16, &str)] = &[
+    (KEY_CAPSLOCK, "caps_lock"),
+    (KEY_NUMLOCK, "num_lock"),
+    (KEY_SCROLLLOCK, "scroll_lock"),
+];
+
+/// Keypad keys that move the pointer, and which way
+const MOUSE_DIRECTIONS: &[(u16, f64, f64)] = &[
+    (KEY_KP7, -1.0, -1.0),
+    (KEY_KP8, 0.0, -1.0),
+    (KEY_KP9, 1.0, -1.0),
+    (KEY_KP4, -1.0, 0.0),
+    (KEY_KP6, 1.0, 0.0),
+    (KEY_KP1, -1.0, 1.0),
+    (KEY_KP2, 0.0, 1.0),
+    (KEY_KP3, 1.0, 1.0),
+];
+
+const MOUSE_KEYS: &[u16] = &[
+    KEY_KP7, KEY_KP8, KEY_KP9, KEY_KP4, KEY_KP6, KEY_KP1, KEY_KP2, KEY_KP3, KEY_KP5, KEY_KPPLUS, KEY_KP0, KEY_KPDOT,
+    KEY_KPSLASH, KEY_KPASTERISK, KEY_KPMINUS,
+];
+
+const fn ioc(direction: libc::c_ulong, kind: u8, number: u8, size: usize) -> libc::c_ulong {
+    (direction << 30) | ((size as libc::c_ulong) << 16) | ((kind as libc::c_ulong) << 8) | number as libc::c_ulong
+}
+
+const IOC_WRITE: libc::c_ulong = 1;
+const IOC_READ: libc::c_ulong = 2;
+const EVIOCGRAB: libc::c_ulong = ioc(IOC_WRITE, b'E', 0x90, std::mem::size_of::<libc::c_int>());
+/// Bitmap of the keys held down
+const EVIOCGKEY: libc::c_ulong = ioc(IOC_READ, b'E', 0x18, KEY_BITMAP_SIZE);
+const KEY_BITMAP_SIZE: usize = (KEY_MAX as usize + 8) / 8;
+const UI_SET_EVBIT: libc::c_ulong = ioc(IOC_WRITE, b'U', 100, std::mem::size_of::<libc::c_int>());
+const UI_SET_KEYBIT: libc::c_ulong = ioc(IOC_WRITE, b'U', 101, std::mem::size_of::<libc::c_int>());
+const UI_SET_RELBIT: libc::c_ulong = ioc(IOC_WRITE, b'U', 102, std::mem::size_of::<libc::c_int>());
+const UI_DEV_SETUP: libc::c_ulong = ioc(IOC_WRITE, b'U', 3, std::mem::size_of::<libc::uinput_setup>());
+const UI_DEV_CREATE: libc::c_ulong = ioc(0, b'U', 1, 0);
+const UI_DEV_DESTROY: libc::c_ulong = ioc(0, b'U', 2, 0);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StickyKeys {
+    pub enabled: bool,
+    /// A modifier pressed twice stays down until pressed again
+    pub lock_on_double_press: bool,
+    /// Pressing a modifier together with another key turns sticky keys off
+    pub off_when_two_pressed: bool,
+}
+
+impl Default for StickyKeys {
+    fn default() -> Self {
+        StickyKeys {
+            enabled: false,
+            lock_on_double_press: true,
+            off_when_two_pressed: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterKeys {
+    pub enabled: bool,
+    /// How long a key is held before it counts; 0 for no wait
+    pub slow_keys_ms: u32,
+    /// A key pressed again within this long of its release is ignored; 0
+    /// to take every press
+    pub bounce_keys_ms: u32,
+}
+
+impl Default for FilterKeys {
+    fn default() -> Self {
+        FilterKeys {
+            enabled: false,
+            slow_keys_ms: 500,
+            bounce_keys_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MouseKeys {
+    pub enabled: bool,
+    /// Top pointer speed in pixels per second
+    pub max_speed: u32,
+    /// How long a key is held before the pointer reaches top speed
+    pub acceleration_ms: u32,
+}
+
+impl Default for MouseKeys {
+    fn default() -> Self {
+        MouseKeys {
+            enabled: false,
+            max_speed: 400,
+            acceleration_ms: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    pub sticky_keys: StickyKeys,
+    pub filter_keys: FilterKeys,
+    /// Announce Caps Lock, Num Lock and Scroll Lock
+    pub toggle_keys: bool,
+    pub mouse_keys: MouseKeys,
+    /// The keyboard shortcuts that turn the features on and off
+    pub shortcuts: bool,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        AccessibilitySettings {
+            sticky_keys: StickyKeys::default(),
+            filter_keys: FilterKeys::default(),
+            toggle_keys: false,
+            mouse_keys: MouseKeys::default(),
+            shortcuts: true,
+        }
+    }
+}
+
+impl AccessibilitySettings {
+    /// Whether input has to go through the virtual device
+    fn changes_input(&self) -> bool {
+        self.sticky_keys.enabled || self.filter_keys.enabled || self.mouse_keys.enabled
+    }
+
+    /// Whether the keyboards need watching at all
+    fn watches_input(&self) -> bool {
+        self.changes_input() || self.toggle_keys || self.shortcuts
+    }
+}
+
+/// Payload of `sticky-keys`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StickyModifiers {
+    /// Held for the next key
+    pub latched: Vec<String>,
+    /// Held until pressed again
+    pub locked: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AccessibilityStatus {
+    /// Keyboards are being watched
+    pub running: bool,
+    /// Keyboards grabbed and passed through the virtual device
+    pub grabbed: Vec<String>,
+    pub sticky: StickyModifiers,
+    /// Why the keyboards can't be watched or grabbed
+    pub error: Option<String>,
+}
+
+pub struct AccessibilityState {
+    settings: Arc<Mutex<AccessibilitySettings>>,
+    status: Arc<Mutex<AccessibilityStatus>>,
+    worker: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl AccessibilityState {
+    pub fn load(app: &AppHandle) -> Self {
+        AccessibilityState {
+            settings: Arc::new(Mutex::new(store::load(app, ACCESSIBILITY_FILE))),
+            status: Arc::new(Mutex::new(AccessibilityStatus::default())),
+            worker: Mutex::new(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sticky {
+    Latched,
+    Locked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Feature {
+    Sticky,
+    Filter,
+    Mouse,
+}
+
+/// What the engine wants done
+#[derive(Debug, Clone, PartialEq)]
+enum Output {
+    Key(u16, i32),
+    Move(i32, i32),
+    Sticky(StickyModifiers),
+    Toggle(&'static str, bool),
+    Feature(Feature, bool),
+}
+
+/// The features' state, fed key events and the passing of time
+struct Engine {
+    settings: AccessibilitySettings,
+    /// Keys physically down, before any filtering
+    raw_down: BTreeSet<u16>,
+    shift_presses: (u32, Option<Instant>),
+    right_shift_since: Option<Instant>,
+    /// Lock key states, as the lights show them
+    locks: BTreeMap<u16, bool>,
+    /// Slow keys: presses waiting to count, and since when
+    pending: BTreeMap<u16, Instant>,
+    /// Bounce keys: presses being ignored until released
+    bounced: BTreeSet<u16>,
+    released_at: BTreeMap<u16, Instant>,
+    sticky: BTreeMap<u16, Sticky>,
+    /// Modifiers down, and those with no other key pressed since
+    held: BTreeSet<u16>,
+    alone: BTreeSet<u16>,
+    /// Modifier releases to drop, for presses that only changed sticky state
+    swallow: BTreeSet<u16>,
+    /// Mouse keys: direction keys down, and since when
+    moving: BTreeMap<u16, Instant>,
+    remainder: (f64, f64),
+    last_tick: Option<Instant>,
+    button: u16,
+    button_down: bool,
+}
+
+// ============================================================================
+// Engine
+// ============================================================================
+
+fn is_modifier(code: u16) -> bool {
+    MODIFIERS.iter().any(|&(modifier, _)| modifier == code)
+}
+
+fn modifier_names(codes: impl Iterator<Item = u16>) -> Vec<String> {
+    let mut names: Vec<String> = codes
+        .filter_map(|code| MODIFIERS.iter().find(|&&(modifier, _)| modifier == code))
+        .map(|(_, name)| name.to_string())
+        .collect();
+    names.dedup();
+    names
+}
+
+impl Engine {
+    fn new(settings: AccessibilitySettings, locks: BTreeMap<u16, bool>) -> Self {
+        Engine {
+            settings,
+            raw_down: BTreeSet::new(),
+            shift_presses: (0, None),
+            right_shift_since: None,
+            locks,
+            pending: BTreeMap::new(),
+            bounced: BTreeSet::new(),
+            released_at: BTreeMap::new(),
+            sticky: BTreeMap::new(),
+            held: BTreeSet::new(),
+            alone: BTreeSet::new(),
+            swallow: BTreeSet::new(),
+            moving: BTreeMap::new(),
+            remainder: (0.0, 0.0),
+            last_tick: None,
+            button: BTN_LEFT,
+            button_down: false,
+        }
+    }
+
+    fn sticky_state(&self) -> StickyModifiers {
+        let with = |state: Sticky| self.sticky.iter().filter(move |(_, s)| **s == state).map(|(code, _)| *code);
+        StickyModifiers {
+            latched: modifier_names(with(Sticky::Latched)),
+            locked: modifier_names(with(Sticky::Locked)),
+        }
+    }
+
+    /// Take new settings, letting go of whatever a feature turned off holds
+    fn set_settings(&mut self, settings: AccessibilitySettings, out: &mut Vec<Output>) {
+        if !settings.sticky_keys.enabled && !self.sticky.is_empty() {
+            for (code, _) in std::mem::take(&mut self.sticky) {
+                out.push(Output::Key(code, 0));
+            }
+            out.push(Output::Sticky(StickyModifiers::default()));
+        }
+        if !settings.sticky_keys.enabled {
+            self.held.clear();
+            self.alone.clear();
+            self.swallow.clear();
+        }
+        if !settings.filter_keys.enabled {
+            self.pending.clear();
+            self.bounced.clear();
+        }
+        if !settings.mouse_keys.enabled {
+            self.moving.clear();
+            if self.button_down {
+                self.button_down = false;
+                out.push(Output::Key(self.button, 0));
+            }
+        }
+        self.settings = settings;
+    }
+
+    /// Turn a feature on or off from its shortcut
+    fn toggle(&mut self, feature: Feature, out: &mut Vec<Output>) {
+        let mut settings = self.settings.clone();
+        let enabled = match feature {
+            Feature::Sticky => &mut settings.sticky_keys.enabled,
+            Feature::Filter => &mut settings.filter_keys.enabled,
+            Feature::Mouse => &mut settings.mouse_keys.enabled,
+        };
+        *enabled = !*enabled;
+        let enabled = *enabled;
+        self.set_settings(settings, out);
+        out.push(Output::Feature(feature, enabled));
+    }
+
+    /// Release everything held, before the virtual device goes away
+    fn release_all(&mut self, out: &mut Vec<Output>) {
+        let settings = self.settings.clone();
+        let mut off = settings.clone();
+        off.sticky_keys.enabled = false;
+        off.filter_keys.enabled = false;
+        off.mouse_keys.enabled = false;
+        self.set_settings(off, out);
+        self.settings = settings;
+    }
+
+    fn shortcuts(&mut self, code: u16, value: i32, now: Instant, out: &mut Vec<Output>) {
+        if value != 1 {
+            if value == 0 && code == KEY_RIGHTSHIFT {
+                self.right_shift_since = None;
+            }
+            return;
+        }
+        if code == KEY_LEFTSHIFT || code == KEY_RIGHTSHIFT {
+            let (count, last) = self.shift_presses;
+            let count = if last.is_some_and(|last| now.duration_since(last) <= SHORTCUT_GAP) { count + 1 } else { 1 };
+            self.shift_presses = (count, Some(now));
+            if count == SHORTCUT_PRESSES {
+                self.shift_presses = (0, None);
+                self.toggle(Feature::Sticky, out);
+            }
+        } else {
+            self.shift_presses = (0, None);
+        }
+        if code == KEY_RIGHTSHIFT {
+            self.right_shift_since = Some(now);
+        }
+        let mouse_chord = [KEY_LEFTALT, KEY_LEFTSHIFT].iter().all(|key| self.raw_down.contains(key));
+        if code == KEY_NUMLOCK && mouse_chord {
+            self.toggle(Feature::Mouse, out);
+        }
+    }
+
+    /// A key event from a keyboard
+    fn key(&mut self, code: u16, value: i32, now: Instant, out: &mut Vec<Output>) {
+        match value {
+            1 => {
+                self.raw_down.insert(code);
+            }
+            0 => {
+                self.raw_down.remove(&code);
+            }
+            _ => {}
+        }
+        if self.settings.shortcuts {
+            self.shortcuts(code, value, now, out);
+        }
+        if value == 1 && self.settings.toggle_keys {
+            if let Some(&(_, name)) = LOCK_KEYS.iter().find(|(key, _)| *key == code) {
+                let on = self.locks.entry(code).or_insert(false);
+                *on = !*on;
+                out.push(Output::Toggle(name, *on));
+            }
+        }
+
+        if self.settings.filter_keys.enabled {
+            let filter = &self.settings.filter_keys;
+            match value {
+                1 => {
+                    let bounce = Duration::from_millis(filter.bounce_keys_ms.into());
+                    let released = self.released_at.get(&code);
+                    if filter.bounce_keys_ms > 0 && released.is_some_and(|at| now.duration_since(*at) < bounce) {
+                        self.bounced.insert(code);
+                        return;
+                    }
+                    if filter.slow_keys_ms > 0 {
+                        self.pending.insert(code, now);
+                        return;
+                    }
+                }
+                0 => {
+                    self.released_at.insert(code, now);
+                    if self.bounced.remove(&code) || self.pending.remove(&code).is_some() {
+                        return;
+                    }
+                }
+                _ => {
+                    if self.bounced.contains(&code) || self.pending.contains_key(&code) {
+                        return;
+                    }
+                }
+            }
+        }
+        self.accepted(code, value, now, out);
+    }
+
+    /// A key event that got through filter keys
+    fn accepted(&mut self, code: u16, value: i32, now: Instant, out: &mut Vec<Output>) {
+        if self.settings.mouse_keys.enabled && MOUSE_KEYS.contains(&code) {
+            self.mouse_key(code, value, now, out);
+        } else if self.settings.sticky_keys.enabled {
+            self.sticky_key(code, value, out);
+        } else {
+            out.push(Output::Key(code, value));
+        }
+    }
+
+    fn sticky_key(&mut self, code: u16, value: i32, out: &mut Vec<Output>) {
+        let before = self.sticky_state();
+        if is_modifier(code) {
+            match value {
+                1 => match self.sticky.get(&code) {
+                    Some(Sticky::Latched) if self.settings.sticky_keys.lock_on_double_press => {
+                        self.sticky.insert(code, Sticky::Locked);
+                        self.swallow.insert(code);
+                    }
+                    Some(_) => {
+                        self.sticky.remove(&code);
+                        self.swallow.insert(code);
+                        out.push(Output::Key(code, 0));
+                    }
+                    None => {
+                        self.held.insert(code);
+                        self.alone.insert(code);
+                        out.push(Output::Key(code, 1));
+                    }
+                },
+                0 => {
+                    if !self.swallow.remove(&code) {
+                        self.held.remove(&code);
+                        if self.alone.remove(&code) {
+                            self.sticky.insert(code, Sticky::Latched);
+                        } else {
+                            out.push(Output::Key(code, 0));
+                        }
+                    }
+                }
+                _ => {
+                    if !self.swallow.contains(&code) && !self.sticky.contains_key(&code) {
+                        out.push(Output::Key(code, value));
+                    }
+                }
+            }
+        } else {
+            if value == 1 {
+                self.alone.clear();
+                if self.settings.sticky_keys.off_when_two_pressed && !self.held.is_empty() {
+                    // Modifiers still down are released as usual
+                    self.toggle(Feature::Sticky, out);
+                    out.push(Output::Key(code, value));
+                    return;
+                }
+            }
+            out.push(Output::Key(code, value));
+            if value == 0 {
+                let latched: Vec<u16> =
+                    self.sticky.iter().filter(|(_, state)| **state == Sticky::Latched).map(|(code, _)| *code).collect();
+                for modifier in latched {
+                    self.sticky.remove(&modifier);
+                    out.push(Output::Key(modifier, 0));
+                }
+            }
+        }
+        let after = self.sticky_state();
+        if after != before {
+            out.push(Output::Sticky(after));
+        }
+    }
+
+    fn click(&self, out: &mut Vec<Output>) {
+        out.push(Output::Key(self.button, 1));
+        out.push(Output::Key(self.button, 0));
+    }
+
+    fn mouse_key(&mut self, code: u16, value: i32, now: Instant, out: &mut Vec<Output>) {
+        let direction = MOUSE_DIRECTIONS.iter().any(|&(key, _, _)| key == code);
+        match (value, code) {
+            (0, _) if direction => {
+                self.moving.remove(&code);
+            }
+            (1, _) if direction => {
+                if self.moving.is_empty() {
+                    self.last_tick = Some(now);
+                    self.remainder = (0.0, 0.0);
+                }
+                self.moving.insert(code, now);
+            }
+            (1, KEY_KP5) => self.click(out),
+            (1, KEY_KPPLUS) => {
+                self.click(out);
+                self.click(out);
+            }
+            (1, KEY_KP0) if !self.button_down => {
+                self.button_down = true;
+                out.push(Output::Key(self.button, 1));
+            }
+            (1, KEY_KPDOT) if self.button_down => {
+                self.button_down = false;
+                out.push(Output::Key(self.button, 0));
+            }
+            (1, KEY_KPSLASH) => self.button = BTN_LEFT,
+            (1, KEY_KPASTERISK) => self.button = BTN_MIDDLE,
+            (1, KEY_KPMINUS) => self.button = BTN_RIGHT,
+            _ => {}
+        }
+    }
+
+    /// Let time pass: slow keys come due, the pointer moves, right Shift
+    /// is timed
+    fn tick(&mut self, now: Instant, out: &mut Vec<Output>) {
+        let held = self.right_shift_since.is_some_and(|since| now.duration_since(since) >= FILTER_HOLD);
+        if self.settings.shortcuts && held {
+            self.right_shift_since = None;
+            self.toggle(Feature::Filter, out);
+        }
+
+        let wait = Duration::from_millis(self.settings.filter_keys.slow_keys_ms.into());
+        let due: Vec<u16> =
+            self.pending.iter().filter(|(_, at)| now.duration_since(**at) >= wait).map(|(code, _)| *code).collect();
+        for code in due {
+            self.pending.remove(&code);
+            self.accepted(code, 1, now, out);
+        }
+
+        let Some(since) = self.moving.values().min().copied() else {
+            return;
+        };
+        let elapsed = self.last_tick.map_or(Duration::ZERO, |last| now.duration_since(last));
+        self.last_tick = Some(now);
+        let (x, y) = MOUSE_DIRECTIONS
+            .iter()
+            .filter(|(key, _, _)| self.moving.contains_key(key))
+            .fold((0.0, 0.0), |(x, y), &(_, dx, dy)| (x + dx, y + dy));
+        let length = f64::hypot(x, y);
+        if length == 0.0 {
+            return;
+        }
+        let mouse = &self.settings.mouse_keys;
+        let ramp = now.duration_since(since).as_secs_f64() / (f64::from(mouse.acceleration_ms.max(1)) / 1000.0);
+        let top = f64::from(mouse.max_speed).max(MOUSE_MIN_SPEED);
+        let speed = MOUSE_MIN_SPEED + (top - MOUSE_MIN_SPEED) * ramp.min(1.0);
+        let distance = speed * elapsed.as_secs_f64() / length;
+        self.remainder.0 += x * distance;
+        self.remainder.1 += y * distance;
+        let (dx, dy) = (self.remainder.0.trunc(), self.remainder.1.trunc());
+        if dx != 0.0 || dy != 0.0 {
+            self.remainder.0 -= dx;
+            self.remainder.1 -= dy;
+            out.push(Output::Move(dx as i32, dy as i32));
+        }
+    }
+}
+
+// ============================================================================
+// Devices
+// ============================================================================
+
+fn decode(bytes: &[u8]) -> libc::input_event {
+    // SAFETY: `bytes` holds EVENT_SIZE bytes and input_event is plain data
+    unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const libc::input_event) }
+}
+
+fn encode(kind: u16, code: u16, value: i32) -> Vec<u8> {
+    // SAFETY: input_event is plain data, valid when zeroed
+    let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+    event.type_ = kind;
+    event.code = code;
+    event.value = value;
+    // SAFETY: the pointer covers exactly one input_event
+    unsafe { std::slice::from_raw_parts(&event as *const libc::input_event as *const u8, EVENT_SIZE) }.to_vec()
+}
+
+fn ioctl_int(file: &File, request: libc::c_ulong, value: libc::c_int) -> Result<(), String> {
+    // SAFETY: the request takes an int argument
+    if unsafe { libc::ioctl(file.as_raw_fd(), request as _, value) } < 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+/// Whether a hex bitmap from sysfs (most significant word first) has `bit`
+fn has_bit(bitmap: &str, bit: usize) -> bool {
+    let word_bits = usize::BITS as usize;
+    let words: Vec<&str> = bitmap.split_whitespace().rev().collect();
+    words
+        .get(bit / word_bits)
+        .and_then(|word| usize::from_str_radix(word, 16).ok())
+        .is_some_and(|word| word & (1 << (bit % word_bits)) != 0)
+}
+
+/// Event nodes of keyboards, leaving out the virtual device
+fn keyboard_nodes() -> Vec<String> {
+    let mut nodes: Vec<String> = fs::read_dir(INPUT_DEVICES)
+        .map(|entries| entries.flatten().map(|entry| entry.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+    nodes.retain(|name| {
+        let device = Path::new(INPUT_DEVICES).join(name).join("device");
+        let read = |file: &str| fs::read_to_string(device.join(file)).unwrap_or_default();
+        name.starts_with("event")
+            && read("name").trim() != DEVICE_NAME
+            && has_bit(&read("capabilities/ev"), EV_REP.into())
+            && has_bit(&read("capabilities/key"), KEY_A.into())
+    });
+    nodes.sort();
+    nodes.into_iter().map(|name| format!("/dev/input/{}", name)).collect()
+}
+
+/// Lock key states from the keyboard lights
+fn lock_states() -> BTreeMap<u16, bool> {
+    let leds: Vec<(String, bool)> = fs::read_dir("/sys/class/leds")
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| {
+                    let on = fs::read_to_string(entry.path().join("brightness")).is_ok_and(|value| value.trim() != "0");
+                    (entry.file_name().to_string_lossy().to_string(), on)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    LOCK_KEYS
+        .iter()
+        .map(|&(code, name)| {
+            let suffix = format!("::{}", name.replace('_', ""));
+            (code, leds.iter().any(|(led, on)| *on && led.starts_with("input") && led.ends_with(&suffix)))
+        })
+        .collect()
+}
+
+/// Whether any key on the keyboard is down
+fn keys_held(file: &File) -> bool {
+    let mut bitmap = [0u8; KEY_BITMAP_SIZE];
+    // SAFETY: the buffer is the size the request says
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), EVIOCGKEY as _, bitmap.as_mut_ptr()) };
+    result >= 0 && bitmap.iter().any(|byte| *byte != 0)
+}
+
+/// The virtual keyboard and mouse
+struct VirtualDevice(File);
+
+impl VirtualDevice {
+    fn create() -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(UINPUT)
+            .map_err(|e| format!("{}: {}", UINPUT, e))?;
+        for kind in [EV_SYN, EV_KEY, EV_REL] {
+            ioctl_int(&file, UI_SET_EVBIT, kind.into())?;
+        }
+        let keys = (1..BTN_MISC).chain([BTN_LEFT, BTN_RIGHT, BTN_MIDDLE]).chain(0x160..BTN_TRIGGER_HAPPY);
+        for key in keys {
+            ioctl_int(&file, UI_SET_KEYBIT, key.into())?;
+        }
+        for axis in [REL_X, REL_Y] {
+            ioctl_int(&file, UI_SET_RELBIT, axis.into())?;
+        }
+
+        // SAFETY: uinput_setup is plain data, valid when zeroed
+        let mut setup: libc::uinput_setup = unsafe { std::mem::zeroed() };
+        setup.id.bustype = BUS_VIRTUAL;
+        setup.id.vendor = 0x4b49;
+        setup.id.product = 0x0001;
+        for (slot, byte) in setup.name.iter_mut().zip(DEVICE_NAME.bytes()) {
+            *slot = byte as libc::c_char;
+        }
+        // SAFETY: the request takes a pointer to a uinput_setup
+        if unsafe { libc::ioctl(file.as_raw_fd(), UI_DEV_SETUP as _, &setup) } < 0 {
+            return Err(format!("{}: {}", UINPUT, std::io::Error::last_os_error()));
+        }
+        // SAFETY: the request takes no argument
+        if unsafe { libc::ioctl(file.as_raw_fd(), UI_DEV_CREATE as _) } < 0 {
+            return Err(format!("{}: {}", UINPUT, std::io::Error::last_os_error()));
+        }
+        Ok(VirtualDevice(file))
+    }
+
+    fn send(&mut self, outputs: &[Output]) {
+        let mut bytes = Vec::new();
+        for output in outputs {
+            match *output {
+                Output::Key(code, value) => bytes.extend(encode(EV_KEY, code, value)),
+                Output::Move(dx, dy) => {
+                    if dx != 0 {
+                        bytes.extend(encode(EV_REL, REL_X, dx));
+                    }
+                    if dy != 0 {
+                        bytes.extend(encode(EV_REL, REL_Y, dy));
+                    }
+                }
+                _ => continue,
+            }
+            bytes.extend(encode(EV_SYN, 0, 0));
+        }
+        if !bytes.is_empty() {
+            let _ = self.0.write_all(&bytes);
+        }
+    }
+}
+
+impl Drop for VirtualDevice {
+    fn drop(&mut self) {
+        // SAFETY: the request takes no argument
+        unsafe { libc::ioctl(self.0.as_raw_fd(), UI_DEV_DESTROY as _) };
+    }
+}
+
+struct Keyboard {
+    node: String,
+    file: File,
+    grabbed: bool,
+}
+
+impl Keyboard {
+    fn open(node: &str) -> Option<Self> {
+        let file = OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(node).ok()?;
+        Some(Keyboard {
+            node: node.to_string(),
+            file,
+            grabbed: false,
+        })
+    }
+
+    /// Take the keyboard for ourselves once its keys are up
+    fn grab(&mut self) -> Result<(), String> {
+        let started = Instant::now();
+        while keys_held(&self.file) && started.elapsed() < GRAB_WAIT {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        // Whatever was pressed while waiting has already been seen
+        let mut buffer = [0u8; EVENT_SIZE * 64];
+        while self.file.read(&mut buffer).is_ok_and(|read| read > 0) {}
+        ioctl_int(&self.file, EVIOCGRAB, 1).map_err(|e| format!("{}: {}", self.node, e))?;
+        self.grabbed = true;
+        Ok(())
+    }
+
+    fn release(&mut self) {
+        if self.grabbed && ioctl_int(&self.file, EVIOCGRAB, 0).is_ok() {
+            self.grabbed = false;
+        }
+    }
+}
+
+// ============================================================================
+// Worker
+// ============================================================================
+
+fn announce(app: &AppHandle, status: &Mutex<AccessibilityStatus>, output: &Output) {
+    match output {
+        Output::Sticky(sticky) => {
+            status.lock().expect("accessibility status lock").sticky = sticky.clone();
+            events::publish(app, "sticky-keys", sticky);
+        }
+        Output::Toggle(key, on) => events::publish(app, "toggle-key", serde_json::json!({ "key": key, "on": on })),
+        _ => {}
+    }
+}
+
+/// Save a feature turned on or off by its shortcut
+fn save_toggle(app: &AppHandle, shared: &Mutex<AccessibilitySettings>, engine: &Engine, feature: Feature) {
+    let settings = {
+        let mut settings = shared.lock().expect("accessibility lock");
+        match feature {
+            Feature::Sticky => settings.sticky_keys.enabled = engine.settings.sticky_keys.enabled,
+            Feature::Filter => settings.filter_keys.enabled = engine.settings.filter_keys.enabled,
+            Feature::Mouse => settings.mouse_keys.enabled = engine.settings.mouse_keys.enabled,
+        }
+        settings.clone()
+    };
+    let _ = store::save(app, ACCESSIBILITY_FILE, &settings);
+    events::publish(app, "accessibility-changed", &settings);
+}
+
+/// Watch the keyboards until stopped, grabbing them while a feature
+/// changes input
+fn watch(
+    app: AppHandle,
+    shared: Arc<Mutex<AccessibilitySettings>>,
+    status: Arc<Mutex<AccessibilityStatus>>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut engine = Engine::new(shared.lock().expect("accessibility lock").clone(), lock_states());
+    let mut keyboards: Vec<Keyboard> = Vec::new();
+    let mut device: Option<VirtualDevice> = None;
+    let mut scanned: Option<Instant> = None;
+    let mut buffer = [0u8; EVENT_SIZE * 64];
+    let mut out = Vec::new();
+    let mut error = None;
+    let mut grabbing = false;
+
+    while !stop.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        let settings = shared.lock().expect("accessibility lock").clone();
+        if settings != engine.settings {
+            engine.set_settings(settings, &mut out);
+        }
+
+        let rescan = scanned.map_or(true, |at| now.duration_since(at) >= RESCAN_INTERVAL);
+        if rescan {
+            scanned = Some(now);
+            error = None;
+            let nodes = keyboard_nodes();
+            keyboards.retain(|keyboard| nodes.contains(&keyboard.node));
+            for node in nodes {
+                if !keyboards.iter().any(|keyboard| keyboard.node == node) {
+                    keyboards.extend(Keyboard::open(&node));
+                }
+            }
+            if keyboards.is_empty() {
+                error = Some("No readable keyboards (is the kiosk user in the input group?)".to_string());
+            }
+        }
+
+        // Grab while input is changed, through a virtual device made first;
+        // a failure is retried with the next scan
+        let grab = engine.settings.changes_input();
+        if grab && device.is_none() && (rescan || !grabbing) {
+            match VirtualDevice::create() {
+                Ok(created) => device = Some(created),
+                Err(e) => error = Some(e),
+            }
+        }
+        for keyboard in keyboards.iter_mut() {
+            if grab && device.is_some() && !keyboard.grabbed && (rescan || !grabbing) {
+                if let Err(e) = keyboard.grab() {
+                    error = Some(e);
+                }
+            } else if !grab && keyboard.grabbed {
+                keyboard.release();
+            }
+        }
+
+        let mut idle = true;
+        let mut lost = Vec::new();
+        for (index, keyboard) in keyboards.iter_mut().enumerate() {
+            match keyboard.file.read(&mut buffer) {
+                Ok(read) => {
+                    idle = false;
+                    for chunk in buffer[..read].chunks_exact(EVENT_SIZE) {
+                        let event = decode(chunk);
+                        if event.type_ == EV_KEY {
+                            engine.key(event.code, event.value, now, &mut out);
+                        }
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                // Unplugged
+                Err(_) => lost.push(index),
+            }
+        }
+        for index in lost.into_iter().rev() {
+            keyboards.remove(index);
+        }
+        engine.tick(now, &mut out);
+
+        for output in &out {
+            match output {
+                Output::Feature(feature, _) => save_toggle(&app, &shared, &engine, *feature),
+                output => announce(&app, &status, output),
+            }
+        }
+        if let Some(device) = device.as_mut().filter(|_| keyboards.iter().any(|keyboard| keyboard.grabbed)) {
+            device.send(&out);
+        }
+        out.clear();
+        if !grab {
+            device = None;
+        }
+        grabbing = grab;
+
+        {
+            let mut status = status.lock().expect("accessibility status lock");
+            status.running = true;
+            status.grabbed = keyboards.iter().filter(|keyboard| keyboard.grabbed).map(|k| k.node.clone()).collect();
+            status.error = error.clone();
+        }
+        if idle {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    engine.release_all(&mut out);
+    if let Some(device) = device.as_mut() {
+        device.send(&out);
+    }
+    for keyboard in keyboards.iter_mut() {
+        keyboard.release();
+    }
+    *status.lock().expect("accessibility status lock") = AccessibilityStatus::default();
+}
+
+/// Start or stop watching the keyboards to suit the settings
+fn update_worker(app: &AppHandle, state: &AccessibilityState) {
+    let wanted = state.settings.lock().expect("accessibility lock").watches_input();
+    let mut worker = state.worker.lock().expect("accessibility worker lock");
+    match (wanted, worker.as_ref()) {
+        (true, None) => {
+            let stop = Arc::new(AtomicBool::new(false));
+            let (app, shared, status) = (app.clone(), state.settings.clone(), state.status.clone());
+            let running = stop.clone();
+            std::thread::spawn(move || watch(app, shared, status, running));
+            *worker = Some(stop);
+        }
+        (false, Some(stop)) => {
+            stop.store(true, Ordering::Relaxed);
+            *worker = None;
+        }
+        _ => {}
+    }
+}
+
+/// Start watching the keyboards if a feature or the shortcuts are on
+pub fn start_accessibility(app: AppHandle) {
+    update_worker(&app, &app.state::<AccessibilityState>());
+}
+
+fn validate(settings: &AccessibilitySettings) -> Result<(), String> {
+    if settings.filter_keys.slow_keys_ms > 20_000 || settings.filter_keys.bounce_keys_ms > 20_000 {
+        return Err("Filter keys times must be at most 20 seconds".into());
+    }
+    if !(10..=5000).contains(&settings.mouse_keys.max_speed) {
+        return Err("The mouse keys speed must be 10-5000 pixels per second".into());
+    }
+    if settings.mouse_keys.acceleration_ms > 10_000 {
+        return Err("The mouse keys acceleration must be at most 10 seconds".into());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_accessibility_settings(state: State<'_, AccessibilityState>) -> AccessibilitySettings {
+    state.settings.lock().expect("accessibility lock").clone()
+}
+
+/// Save the settings and start or stop watching the keyboards to suit
+#[tauri::command]
+pub fn set_accessibility_settings(
+    app: AppHandle,
+    state: State<'_, AccessibilityState>,
+    auth: State<'_, AuthState>,
+    settings: AccessibilitySettings,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    validate(&settings).map_err(KioskError::invalid)?;
+    {
+        let mut current = state.settings.lock().expect("accessibility lock");
+        store::save(&app, ACCESSIBILITY_FILE, &settings)?;
+        *current = settings.clone();
+    }
+    update_worker(&app, &state);
+    events::publish(&app, "accessibility-changed", &settings);
+    Ok(())
+}
+
+/// Whether the keyboards are watched and grabbed, and the sticky modifiers
+#[tauri::command]
+pub fn get_accessibility_status(state: State<'_, AccessibilityState>) -> AccessibilityStatus {
+    state.status.lock().expect("accessibility status lock").clone()
+}
@@ -0,0 +1,381 @@
+//! Two-person admin actions
+//!
+//! Some retail compliance rules require a second admin to sign off on
+//! destructive changes. With dual authorization on, the actions it covers
+//! (factory reset, turning off bundle lockdown) need an approval: the admin
+//! asks with `request_approval`, a different admin enters their own
+//! credentials, or a one-time code from support, into `approve_action` on
+//! the same kiosk, and the requester passes the approval's id to the
+//! protected command within a few minutes.
+//! Each approval is used once. Requests, approvals, rejections and uses are
+//! kept in an audit trail in `dual-auth-audit.json`.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthProvider, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{events, store};
+
+const DUAL_AUTH_FILE: &str = "dual-auth.json";
+
+const AUDIT_FILE: &str = "dual-auth-audit.json";
+
+/// Entries kept in the audit trail
+const AUDIT_KEPT: usize = 1000;
+
+/// Seconds an approval request stays open, and an approval usable
+const APPROVAL_LIFETIME_SECS: i64 = 300;
+
+/// Wrong approver credentials before the request is withdrawn
+const MAX_APPROVAL_FAILURES: u32 = 3;
+
+/// Actions dual authorization can cover, with their descriptions
+const ACTIONS: &[(&str, &str)] = &[
+    ("factory_reset", "Factory reset"),
+    ("disable_lockdown", "Turn off bundle signature lockdown"),
+    ("disable_dual_auth", "Turn off or narrow dual authorization"),
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DualAuthConfig {
+    pub enabled: bool,
+    /// Actions from `list_protected_actions` that need a second admin
+    pub actions: Vec<String>,
+}
+
+impl Default for DualAuthConfig {
+    fn default() -> Self {
+        DualAuthConfig {
+            enabled: false,
+            actions: ACTIONS.iter().map(|(action, _)| action.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProtectedAction {
+    pub action: String,
+    pub description: String,
+    /// Needs a second admin under the current settings
+    pub protected: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Approval {
+    pub id: String,
+    pub action: String,
+    pub requested_by: String,
+    pub requested_at: i64,
+    /// Unix time the request, or the approval, lapses
+    pub expires_at: i64,
+    pub approved_by: Option<String>,
+    #[serde(skip)]
+    failures: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditEvent {
+    Requested,
+    Approved,
+    Rejected,
+    Cancelled,
+    Used,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: i64,
+    pub event: AuditEvent,
+    pub action: String,
+    pub approval_id: String,
+    /// Operator who did this step
+    pub operator: String,
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+pub struct ApprovalsState {
+    config: Mutex<DualAuthConfig>,
+    pending: Mutex<Vec<Approval>>,
+    audit: Mutex<Vec<AuditEntry>>,
+}
+
+impl ApprovalsState {
+    pub fn load(app: &AppHandle) -> Self {
+        ApprovalsState {
+            config: Mutex::new(store::load(app, DUAL_AUTH_FILE)),
+            pending: Mutex::new(Vec::new()),
+            audit: Mutex::new(store::load(app, AUDIT_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn description(action: &str) -> Option<&'static str> {
+    ACTIONS.iter().find(|(name, _)| *name == action).map(|(_, description)| *description)
+}
+
+fn required(state: &ApprovalsState, action: &str) -> bool {
+    let config = state.config.lock().expect("dual auth config lock");
+    config.enabled && config.actions.iter().any(|protected| protected == action)
+}
+
+fn audit(app: &AppHandle, state: &ApprovalsState, approval: &Approval, event: AuditEvent, operator: &str, detail: Option<String>) {
+    let entry = AuditEntry {
+        time: Local::now().timestamp(),
+        event,
+        action: approval.action.clone(),
+        approval_id: approval.id.clone(),
+        operator: operator.to_string(),
+        detail,
+    };
+    let mut trail = state.audit.lock().expect("dual auth audit lock");
+    trail.push(entry.clone());
+    let excess = trail.len().saturating_sub(AUDIT_KEPT);
+    trail.drain(..excess);
+    let _ = store::save(app, AUDIT_FILE, &*trail);
+    drop(trail);
+    events::publish(app, "dual-auth-audit", entry);
+}
+
+fn drop_expired(pending: &mut Vec<Approval>) {
+    let now = Local::now().timestamp();
+    pending.retain(|approval| approval.expires_at > now);
+}
+
+/// Let a protected action through: always when dual authorization does not
+/// cover it, otherwise only with an approval the signed-in admin requested
+/// and a second admin granted. The approval is used up.
+pub(crate) fn authorize(app: &AppHandle, action: &str, approval_id: Option<&str>) -> Result<(), KioskError> {
+    let state = app.state::<ApprovalsState>();
+    if !required(&state, action) {
+        return Ok(());
+    }
+    let operator = auth::require(&app.state::<AuthState>(), Role::Admin)?.username;
+    let denied = || {
+        KioskError::new(
+            ErrorKind::Denied,
+            format!("{} needs a second admin's approval", description(action).unwrap_or(action)),
+        )
+    };
+    let approval_id = approval_id.ok_or_else(denied)?;
+
+    let mut pending = state.pending.lock().expect("pending approvals lock");
+    drop_expired(&mut pending);
+    let index = pending
+        .iter()
+        .position(|approval| {
+            approval.id == approval_id
+                && approval.action == action
+                && approval.requested_by == operator
+                && approval.approved_by.is_some()
+        })
+        .ok_or_else(denied)?;
+    let approval = pending.remove(index);
+    drop(pending);
+    audit(app, &state, &approval, AuditEvent::Used, &operator, None);
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Actions dual authorization can cover and whether each is covered now
+#[tauri::command]
+pub fn list_protected_actions(state: State<'_, ApprovalsState>) -> Vec<ProtectedAction> {
+    ACTIONS
+        .iter()
+        .map(|&(action, description)| ProtectedAction {
+            action: action.to_string(),
+            description: description.to_string(),
+            protected: required(&state, action),
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_dual_auth_config(state: State<'_, ApprovalsState>) -> DualAuthConfig {
+    state.config.lock().expect("dual auth config lock").clone()
+}
+
+/// Update dual authorization (admin). Turning it off or dropping actions
+/// needs an approval for `disable_dual_auth` while it is on.
+#[tauri::command]
+pub fn set_dual_auth_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, ApprovalsState>,
+    config: DualAuthConfig,
+    approval_id: Option<String>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if let Some(unknown) = config.actions.iter().find(|action| description(action).is_none()) {
+        return Err(KioskError::invalid(format!("Unknown protected action: {}", unknown)));
+    }
+    let weakened = {
+        let current = state.config.lock().expect("dual auth config lock");
+        current.enabled
+            && (!config.enabled || current.actions.iter().any(|action| !config.actions.contains(action)))
+    };
+    if weakened {
+        authorize(&app, "disable_dual_auth", approval_id.as_deref())?;
+    }
+    store::save(&app, DUAL_AUTH_FILE, &config)?;
+    *state.config.lock().expect("dual auth config lock") = config;
+    Ok(())
+}
+
+/// Ask for a second admin's approval of `action` (admin)
+#[tauri::command]
+pub fn request_approval(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, ApprovalsState>,
+    action: String,
+) -> Result<Approval, KioskError> {
+    let operator = auth::require(&auth, Role::Admin)?.username;
+    if description(&action).is_none() {
+        return Err(KioskError::invalid(format!("Unknown protected action: {}", action)));
+    }
+    let now = Local::now().timestamp();
+    let approval = Approval {
+        id: uuid::Uuid::new_v4().to_string(),
+        action,
+        requested_by: operator.clone(),
+        requested_at: now,
+        expires_at: now + APPROVAL_LIFETIME_SECS,
+        approved_by: None,
+        failures: 0,
+    };
+    {
+        let mut pending = state.pending.lock().expect("pending approvals lock");
+        drop_expired(&mut pending);
+        pending.push(approval.clone());
+    }
+    audit(&app, &state, &approval, AuditEvent::Requested, &operator, None);
+    Ok(approval)
+}
+
+/// Approve a request as a second admin, with that admin's own credentials
+/// or a one-time code from support
+#[tauri::command]
+pub fn approve_action(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, ApprovalsState>,
+    id: String,
+    username: String,
+    secret: String,
+    provider: Option<AuthProvider>,
+) -> Result<Approval, KioskError> {
+    let find = |pending: &mut Vec<Approval>| {
+        drop_expired(pending);
+        pending
+            .iter()
+            .position(|approval| approval.id == id && approval.approved_by.is_none())
+            .ok_or_else(|| KioskError::not_found(format!("Approval request not found: {}", id)))
+    };
+    let requested_by = {
+        let mut pending = state.pending.lock().expect("pending approvals lock");
+        let index = find(&mut pending)?;
+        pending[index].requested_by.clone()
+    };
+
+    // Checked without the lock, as a directory login can take a while
+    let approver = auth::check_credentials(&app, &auth, username.trim(), &secret, provider).and_then(|session| {
+        if session.role < Role::Admin {
+            Err("Only an admin can approve".to_string())
+        } else if session.username.eq_ignore_ascii_case(&requested_by) {
+            Err("A second admin must approve".to_string())
+        } else {
+            Ok(session.username)
+        }
+    });
+
+    let mut pending = state.pending.lock().expect("pending approvals lock");
+    let index = find(&mut pending)?;
+    let (approval, result) = match approver {
+        Ok(approver) => {
+            let approval = &mut pending[index];
+            approval.approved_by = Some(approver);
+            approval.expires_at = Local::now().timestamp() + APPROVAL_LIFETIME_SECS;
+            (approval.clone(), Ok(()))
+        }
+        Err(e) => {
+            pending[index].failures += 1;
+            let approval = if pending[index].failures >= MAX_APPROVAL_FAILURES {
+                pending.remove(index)
+            } else {
+                pending[index].clone()
+            };
+            (approval, Err(e))
+        }
+    };
+    drop(pending);
+
+    match result {
+        Ok(()) => {
+            let approver = approval.approved_by.clone().unwrap_or_default();
+            audit(&app, &state, &approval, AuditEvent::Approved, &approver, None);
+            Ok(approval)
+        }
+        Err(e) => {
+            audit(&app, &state, &approval, AuditEvent::Rejected, username.trim(), Some(e.clone()));
+            Err(KioskError::new(ErrorKind::Denied, e))
+        }
+    }
+}
+
+/// Withdraw a request or an unused approval (admin)
+#[tauri::command]
+pub fn cancel_approval(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, ApprovalsState>,
+    id: String,
+) -> Result<(), KioskError> {
+    let operator = auth::require(&auth, Role::Admin)?.username;
+    let approval = {
+        let mut pending = state.pending.lock().expect("pending approvals lock");
+        let index = pending
+            .iter()
+            .position(|approval| approval.id == id)
+            .ok_or_else(|| KioskError::not_found(format!("Approval request not found: {}", id)))?;
+        pending.remove(index)
+    };
+    audit(&app, &state, &approval, AuditEvent::Cancelled, &operator, None);
+    Ok(())
+}
+
+/// Open requests and unused approvals
+#[tauri::command]
+pub fn list_pending_approvals(state: State<'_, ApprovalsState>) -> Vec<Approval> {
+    let mut pending = state.pending.lock().expect("pending approvals lock");
+    drop_expired(&mut pending);
+    pending.clone()
+}
+
+/// The audit trail, newest first (admin)
+#[tauri::command]
+pub fn get_dual_auth_audit(
+    auth: State<'_, AuthState>,
+    state: State<'_, ApprovalsState>,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let trail = state.audit.lock().expect("dual auth audit lock");
+    Ok(trail.iter().rev().take(limit.unwrap_or(usize::MAX)).cloned().collect())
+}
@@ -0,0 +1,228 @@
+//! Attract loop
+//!
+//! Once the kiosk has sat idle for the configured time, cycles through a
+//! playlist of images, videos and web pages, each shown for its dwell time.
+//! The frontend reports touches and key presses through `report_activity`;
+//! any activity ends the loop and tells the frontend to return home.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::{config, events, store};
+
+const ATTRACT_FILE: &str = "attract.json";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttractKind {
+    Image,
+    Video,
+    Url,
+}
+
+/// One entry in the attract playlist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttractItem {
+    pub kind: AttractKind,
+    /// File path or URL
+    pub source: String,
+    /// Seconds on screen before moving on
+    pub dwell_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttractConfig {
+    pub enabled: bool,
+    /// Seconds without activity before the loop starts
+    pub idle_timeout_secs: u64,
+    pub items: Vec<AttractItem>,
+}
+
+impl Default for AttractConfig {
+    fn default() -> Self {
+        AttractConfig {
+            enabled: true,
+            idle_timeout_secs: 120,
+            items: Vec::new(),
+        }
+    }
+}
+
+/// Payload of the `attract-state` event
+#[derive(Debug, Clone, Serialize)]
+pub struct AttractStatus {
+    pub active: bool,
+    pub index: usize,
+    pub item: Option<AttractItem>,
+    /// Set when the loop was interrupted and the home screen should show
+    pub return_home: bool,
+}
+
+struct Playback {
+    last_activity: Instant,
+    active: bool,
+    index: usize,
+    item_started: Instant,
+}
+
+pub struct AttractState {
+    config: Mutex<AttractConfig>,
+    playback: Mutex<Playback>,
+}
+
+impl AttractState {
+    pub fn load(app: &AppHandle) -> Self {
+        AttractState {
+            config: Mutex::new(store::load(app, ATTRACT_FILE)),
+            playback: Mutex::new(Playback {
+                last_activity: Instant::now(),
+                active: false,
+                index: 0,
+                item_started: Instant::now(),
+            }),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn status(config: &AttractConfig, playback: &Playback, return_home: bool) -> AttractStatus {
+    AttractStatus {
+        active: playback.active,
+        index: playback.index,
+        item: playback
+            .active
+            .then(|| config.items.get(playback.index).cloned())
+            .flatten(),
+        return_home,
+    }
+}
+
+/// Leave attract mode, telling the frontend to go home
+fn stop(app: &AppHandle, config: &AttractConfig, playback: &mut Playback) {
+    if playback.active {
+        playback.active = false;
+        events::publish(app, "attract-state", status(config, playback, true));
+    }
+}
+
+fn tick(app: &AppHandle, state: &AttractState) {
+    let config = state.config.lock().expect("attract config lock");
+    let mut playback = state.playback.lock().expect("attract playback lock");
+
+    if !config.enabled || config.items.is_empty() {
+        stop(app, &config, &mut playback);
+        return;
+    }
+
+    if !playback.active {
+        if playback.last_activity.elapsed() >= Duration::from_secs(config.idle_timeout_secs) {
+            playback.active = true;
+            playback.index = 0;
+            playback.item_started = Instant::now();
+            events::publish(app, "attract-state", status(&config, &playback, false));
+        }
+        return;
+    }
+
+    // Playlist may have shrunk while playing
+    if playback.index >= config.items.len() {
+        playback.index = 0;
+    }
+    let dwell = Duration::from_secs(config.items[playback.index].dwell_secs.max(1));
+    if playback.item_started.elapsed() >= dwell {
+        playback.index = (playback.index + 1) % config.items.len();
+        playback.item_started = Instant::now();
+        events::publish(app, "attract-state", status(&config, &playback, false));
+    }
+}
+
+/// Time since the frontend last reported activity
+pub(crate) fn idle_time(app: &AppHandle) -> Duration {
+    app.state::<AttractState>()
+        .playback
+        .lock()
+        .expect("attract playback lock")
+        .last_activity
+        .elapsed()
+}
+
+/// Reset the idle timer and end the loop if it is playing, as a touch would
+pub(crate) fn record_activity(app: &AppHandle) {
+    let state = app.state::<AttractState>();
+    let config = state.config.lock().expect("attract config lock");
+    let mut playback = state.playback.lock().expect("attract playback lock");
+    playback.last_activity = Instant::now();
+    stop(app, &config, &mut playback);
+}
+
+/// Spawn the idle watcher and playlist timer
+pub fn start_attract(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        tick(&app, &app.state::<AttractState>());
+        std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the attract loop settings
+#[tauri::command]
+pub fn get_attract_config(state: State<'_, AttractState>) -> AttractConfig {
+    state.config.lock().expect("attract config lock").clone()
+}
+
+/// Set the attract playlist, and optionally the idle timeout and enabled flag
+#[tauri::command]
+pub fn configure_attract_loop(
+    app: AppHandle,
+    state: State<'_, AttractState>,
+    items: Vec<AttractItem>,
+    idle_timeout_secs: Option<u64>,
+    enabled: Option<bool>,
+) -> Result<(), KioskError> {
+    if items.iter().any(|item| item.source.trim().is_empty()) {
+        return Err(KioskError::invalid("Attract items need a source"));
+    }
+
+    let mut config = state.config.lock().expect("attract config lock");
+    let mut updated = config.clone();
+    updated.items = items;
+    if let Some(timeout) = idle_timeout_secs {
+        updated.idle_timeout_secs = timeout.max(5);
+    }
+    if let Some(enabled) = enabled {
+        updated.enabled = enabled;
+    }
+
+    store::save(&app, ATTRACT_FILE, &updated)?;
+    *config = updated;
+    Ok(())
+}
+
+/// Record user activity; interrupts the attract loop if it is playing
+#[tauri::command]
+pub fn report_activity(app: AppHandle) {
+    record_activity(&app);
+}
+
+/// Get whether the attract loop is playing and what is on screen
+#[tauri::command]
+pub fn get_attract_state(state: State<'_, AttractState>) -> AttractStatus {
+    let config = state.config.lock().expect("attract config lock");
+    let playback = state.playback.lock().expect("attract playback lock");
+    status(&config, &playback, false)
+}
@@ -0,0 +1,622 @@
+//! Operator authentication
+//!
+//! Operator logins for maintenance and staff functions, checked against
+//! local PIN accounts or an LDAP/Active Directory server. Directory groups
+//! map to kiosk roles, and successful directory logins are cached (as salted
+//! hashes) so operators can still sign in while the directory is unreachable.
+//!
+//! Until the first local account exists, creating one takes the setup token
+//! written to `setup-token` in the data directory, which whoever provisions
+//! the kiosk reads with `kiosk-cli setup-token`; walk-up users cannot.
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::{ErrorKind, KioskError};
+use crate::ldap::{LdapConnection, LdapEntry, LdapError, LdapSecurity};
+use crate::{events, keyring, store, totp};
+
+const ACCOUNTS_FILE: &str = "operators.json";
+const LDAP_FILE: &str = "ldap.json";
+const CACHE_FILE: &str = "operator_cache.json";
+
+/// One-time token for creating the first account, readable by the owner only
+pub(crate) const SETUP_TOKEN_FILE: &str = "setup-token";
+
+/// Keyring entry holding the LDAP service account password
+const LDAP_PASSWORD_KEY: &str = "auth/ldap";
+
+const HASH_ROUNDS: u32 = 100_000;
+
+/// Failed attempts allowed before a username is locked out
+const MAX_FAILURES: u32 = 5;
+const LOCKOUT: Duration = Duration::from_secs(60);
+
+/// Characters that would change the meaning of a DN built from a username
+const FORBIDDEN_USERNAME_CHARS: &[char] = &[',', '=', '+', '<', '>', '#', ';', '\\', '"', '*', '(', ')'];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Operator roles, lowest to highest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Operator,
+    Supervisor,
+    Admin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthProvider {
+    Local,
+    Ldap,
+    /// Directory login verified against the offline cache
+    Cached,
+    /// One-time code from support; see `totp`
+    Totp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LocalAccount {
+    username: String,
+    display_name: String,
+    role: Role,
+    pin_hash: String,
+    salt: String,
+}
+
+/// A local account as shown to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperatorAccount {
+    pub username: String,
+    pub display_name: String,
+    pub role: Role,
+    /// New PIN; omitted when editing keeps the current one
+    #[serde(default, skip_serializing)]
+    pub pin: Option<String>,
+}
+
+/// Directory group granting a role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupRole {
+    /// Group DN or common name
+    pub group: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LdapConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub security: LdapSecurity,
+    /// Search base, e.g. "dc=corp,dc=example,dc=com"
+    pub base_dn: String,
+    /// Attribute holding the login name
+    pub user_attribute: String,
+    /// Bind name built from the login, e.g. "{username}@corp.example.com";
+    /// used when no service account is configured
+    pub bind_template: String,
+    /// Service account used to look users up before binding as them
+    #[serde(default)]
+    pub bind_dn: String,
+    #[serde(default)]
+    pub bind_password: String,
+    pub group_roles: Vec<GroupRole>,
+    /// Days a cached directory login stays valid offline; 0 disables caching
+    pub offline_cache_days: u32,
+}
+
+impl Default for LdapConfig {
+    fn default() -> Self {
+        LdapConfig {
+            enabled: false,
+            host: String::new(),
+            port: 636,
+            security: LdapSecurity::Tls,
+            base_dn: String::new(),
+            user_attribute: "sAMAccountName".to_string(),
+            bind_template: "{username}".to_string(),
+            bind_dn: String::new(),
+            bind_password: String::new(),
+            group_roles: Vec::new(),
+            offline_cache_days: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedLogin {
+    username: String,
+    display_name: String,
+    role: Role,
+    hash: String,
+    salt: String,
+    cached_at: i64,
+}
+
+/// The signed-in operator
+#[derive(Debug, Clone, Serialize)]
+pub struct OperatorSession {
+    pub username: String,
+    pub display_name: String,
+    pub role: Role,
+    pub provider: AuthProvider,
+    pub logged_in_at: i64,
+}
+
+pub struct AuthState {
+    accounts: Mutex<Vec<LocalAccount>>,
+    ldap: Mutex<LdapConfig>,
+    cache: Mutex<Vec<CachedLogin>>,
+    session: Mutex<Option<OperatorSession>>,
+    failures: Mutex<HashMap<String, (u32, Instant)>>,
+    /// Set while no local account exists
+    setup_token: Mutex<Option<String>>,
+}
+
+impl AuthState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut ldap: LdapConfig = store::load(app, LDAP_FILE);
+        ldap.bind_password = keyring::get(app, LDAP_PASSWORD_KEY).ok().flatten().unwrap_or_default();
+        let accounts: Vec<LocalAccount> = store::load(app, ACCOUNTS_FILE);
+        let setup_token = if accounts.is_empty() {
+            setup_token(app).ok()
+        } else {
+            forget_setup_token(app);
+            None
+        };
+        AuthState {
+            accounts: Mutex::new(accounts),
+            ldap: Mutex::new(ldap),
+            cache: Mutex::new(store::load(app, CACHE_FILE)),
+            session: Mutex::new(None),
+            failures: Mutex::new(HashMap::new()),
+            setup_token: Mutex::new(setup_token),
+        }
+    }
+}
+
+/// The setup token, created on first use
+fn setup_token(app: &AppHandle) -> Result<String, String> {
+    let path = store::data_path(app, SETUP_TOKEN_FILE)?;
+    if let Ok(token) = fs::read_to_string(&path) {
+        return Ok(token.trim().to_string());
+    }
+
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    let token = to_hex(&bytes);
+    fs::write(&path, &token).map_err(|e| e.to_string())?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+fn forget_setup_token(app: &AppHandle) {
+    if let Ok(path) = store::data_path(app, SETUP_TOKEN_FILE) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+// ============================================================================
+// Hashing
+// ============================================================================
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub(crate) fn new_salt() -> String {
+    to_hex(uuid::Uuid::new_v4().as_bytes())
+}
+
+pub(crate) fn hash_secret(secret: &str, salt: &str) -> String {
+    let mut out = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(secret.as_bytes(), salt.as_bytes(), HASH_ROUNDS, &mut out);
+    to_hex(&out)
+}
+
+/// Compare hashes without leaking where they differ
+pub(crate) fn verify_secret(secret: &str, salt: &str, expected: &str) -> bool {
+    let actual = hash_secret(secret, salt);
+    actual.len() == expected.len() && actual.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+// ============================================================================
+// Directory
+// ============================================================================
+
+/// Highest role granted by the user's groups
+fn map_role(config: &LdapConfig, groups: &[String]) -> Option<Role> {
+    let matches = |group: &str, mapping: &str| {
+        if group.eq_ignore_ascii_case(mapping) {
+            return true;
+        }
+        // "CN=Kiosk Admins,OU=Groups,..." matches a mapping of "Kiosk Admins"
+        group
+            .split(',')
+            .next()
+            .and_then(|rdn| rdn.split_once('='))
+            .is_some_and(|(_, name)| name.trim().eq_ignore_ascii_case(mapping))
+    };
+    config
+        .group_roles
+        .iter()
+        .filter(|mapping| groups.iter().any(|group| matches(group, &mapping.group)))
+        .map(|mapping| mapping.role)
+        .max()
+}
+
+fn find_user(connection: &mut LdapConnection, config: &LdapConfig, username: &str) -> Result<LdapEntry, LdapError> {
+    connection
+        .search_eq(
+            &config.base_dn,
+            &config.user_attribute,
+            username,
+            &["displayName", "cn", "memberOf"],
+        )?
+        .into_iter()
+        .next()
+        .ok_or_else(|| LdapError::Rejected("Invalid username or password".to_string()))
+}
+
+/// Bind as the user and return their display name and role
+fn ldap_authenticate(config: &LdapConfig, username: &str, password: &str) -> Result<(String, Role), LdapError> {
+    let connect = || LdapConnection::connect(&config.host, config.port, config.security);
+
+    let entry = if config.bind_dn.is_empty() {
+        let mut connection = connect()?;
+        connection.bind(&config.bind_template.replace("{username}", username), password)?;
+        find_user(&mut connection, config, username)?
+    } else {
+        let entry = {
+            let mut service = connect()?;
+            service.bind(&config.bind_dn, &config.bind_password)?;
+            find_user(&mut service, config, username)?
+        };
+        connect()?.bind(&entry.dn, password)?;
+        entry
+    };
+
+    let role = map_role(config, &entry.values("memberOf"))
+        .ok_or_else(|| LdapError::Rejected("Account is not in any kiosk operator group".to_string()))?;
+    let display_name = entry
+        .first("displayName")
+        .or_else(|| entry.first("cn"))
+        .unwrap_or_else(|| username.to_string());
+    Ok((display_name, role))
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn session(username: &str, display_name: String, role: Role, provider: AuthProvider) -> OperatorSession {
+    OperatorSession {
+        username: username.to_string(),
+        display_name,
+        role,
+        provider,
+        logged_in_at: Local::now().timestamp(),
+    }
+}
+
+fn local_login(state: &AuthState, username: &str, pin: &str) -> Result<OperatorSession, String> {
+    let accounts = state.accounts.lock().expect("operator accounts lock");
+    let account = accounts
+        .iter()
+        .find(|account| account.username.eq_ignore_ascii_case(username))
+        .filter(|account| verify_secret(pin, &account.salt, &account.pin_hash))
+        .ok_or("Invalid username or PIN")?;
+    Ok(session(
+        &account.username,
+        account.display_name.clone(),
+        account.role,
+        AuthProvider::Local,
+    ))
+}
+
+fn ldap_login(app: &AppHandle, state: &AuthState, username: &str, password: &str) -> Result<OperatorSession, String> {
+    let config = state.ldap.lock().expect("ldap config lock").clone();
+    if !config.enabled {
+        return Err("Directory login is not enabled".to_string());
+    }
+    if username.is_empty() || username.contains(FORBIDDEN_USERNAME_CHARS) {
+        return Err("Invalid username or password".to_string());
+    }
+
+    match ldap_authenticate(&config, username, password) {
+        Ok((display_name, role)) => {
+            if config.offline_cache_days > 0 {
+                let salt = new_salt();
+                let mut cache = state.cache.lock().expect("operator cache lock");
+                cache.retain(|entry| !entry.username.eq_ignore_ascii_case(username));
+                cache.push(CachedLogin {
+                    username: username.to_string(),
+                    display_name: display_name.clone(),
+                    role,
+                    hash: hash_secret(password, &salt),
+                    salt,
+                    cached_at: Local::now().timestamp(),
+                });
+                let _ = store::save(app, CACHE_FILE, &*cache);
+            }
+            Ok(session(username, display_name, role, AuthProvider::Ldap))
+        }
+        Err(LdapError::Unreachable(reason)) => {
+            let max_age = config.offline_cache_days as i64 * 86_400;
+            let now = Local::now().timestamp();
+            let cache = state.cache.lock().expect("operator cache lock");
+            cache
+                .iter()
+                .find(|entry| entry.username.eq_ignore_ascii_case(username))
+                .filter(|entry| now - entry.cached_at <= max_age)
+                .filter(|entry| verify_secret(password, &entry.salt, &entry.hash))
+                .map(|entry| session(&entry.username, entry.display_name.clone(), entry.role, AuthProvider::Cached))
+                .ok_or_else(|| format!("Directory unreachable and no cached login: {}", reason))
+        }
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Fail unless the signed-in operator has at least `role`
+pub(crate) fn require_role(state: &AuthState, role: Role) -> Result<OperatorSession, String> {
+    state
+        .session
+        .lock()
+        .expect("operator session lock")
+        .clone()
+        .filter(|session| session.role >= role)
+        .ok_or_else(|| "Operator login required".to_string())
+}
+
+/// `require_role` for commands: fails with `Denied`
+pub(crate) fn require(state: &AuthState, role: Role) -> Result<OperatorSession, KioskError> {
+    require_role(state, role).map_err(|e| KioskError::new(ErrorKind::Denied, e))
+}
+
+/// Role of the signed-in operator, if any
+pub(crate) fn current_role(state: &AuthState) -> Option<Role> {
+    state.session.lock().expect("operator session lock").as_ref().map(|session| session.role)
+}
+
+/// An admin, or before the first local account exists, the setup token
+fn require_setup_or_admin(state: &AuthState, setup_token: Option<&str>) -> Result<(), KioskError> {
+    if !state.accounts.lock().expect("operator accounts lock").is_empty() {
+        return require(state, Role::Admin).map(|_| ());
+    }
+    let expected = state.setup_token.lock().expect("setup token lock").clone();
+    match (expected, setup_token) {
+        (Some(expected), Some(given)) if given.trim() == expected => Ok(()),
+        _ => Err(KioskError::new(
+            ErrorKind::Denied,
+            "The setup token from `kiosk-cli setup-token` is needed to create the first account",
+        )),
+    }
+}
+
+/// Check an operator's credentials without signing them in
+pub(crate) fn check_credentials(
+    app: &AppHandle,
+    state: &AuthState,
+    username: &str,
+    secret: &str,
+    provider: Option<AuthProvider>,
+) -> Result<OperatorSession, String> {
+    let is_local = state
+        .accounts
+        .lock()
+        .expect("operator accounts lock")
+        .iter()
+        .any(|account| account.username.eq_ignore_ascii_case(username));
+    match provider.unwrap_or(if is_local { AuthProvider::Local } else { AuthProvider::Ldap }) {
+        AuthProvider::Local => local_login(state, username, secret),
+        AuthProvider::Ldap | AuthProvider::Cached => ldap_login(app, state, username, secret),
+        AuthProvider::Totp => {
+            let role = totp::verify(app, secret)?;
+            let username = if username.is_empty() { "support" } else { username };
+            Ok(session(username, "Remote support".to_string(), role, AuthProvider::Totp))
+        }
+    }
+}
+
+fn set_session(app: &AppHandle, state: &AuthState, session: Option<OperatorSession>) {
+    *state.session.lock().expect("operator session lock") = session.clone();
+    events::publish(app, "operator-changed", session);
+}
+
+/// Sign out whoever is signed in
+pub(crate) fn sign_out(app: &AppHandle) {
+    set_session(app, &app.state::<AuthState>(), None);
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Sign an operator in. Without a provider, local accounts are tried first
+/// and other usernames go to the directory.
+#[tauri::command(async)]
+pub fn login(
+    app: AppHandle,
+    state: State<'_, AuthState>,
+    username: String,
+    secret: String,
+    provider: Option<AuthProvider>,
+) -> Result<OperatorSession, KioskError> {
+    // Codes are not tied to a username, so their failures are counted together
+    let key = match provider {
+        Some(AuthProvider::Totp) => "#totp".to_string(),
+        _ => username.trim().to_lowercase(),
+    };
+    {
+        let failures = state.failures.lock().expect("login failures lock");
+        if let Some((count, since)) = failures.get(&key) {
+            if *count >= MAX_FAILURES && since.elapsed() < LOCKOUT {
+                return Err(KioskError::new(ErrorKind::RateLimited, "Too many failed attempts; try again later"));
+            }
+        }
+    }
+
+    let result = check_credentials(&app, &state, username.trim(), &secret, provider);
+
+    let mut failures = state.failures.lock().expect("login failures lock");
+    match result {
+        Ok(session) => {
+            failures.remove(&key);
+            drop(failures);
+            set_session(&app, &state, Some(session.clone()));
+            Ok(session)
+        }
+        Err(e) => {
+            let entry = failures.entry(key).or_insert((0, Instant::now()));
+            if entry.1.elapsed() >= LOCKOUT {
+                *entry = (0, Instant::now());
+            }
+            entry.0 += 1;
+            entry.1 = Instant::now();
+            Err(e.into())
+        }
+    }
+}
+
+/// Sign the current operator out
+#[tauri::command]
+pub fn logout(app: AppHandle, state: State<'_, AuthState>) {
+    set_session(&app, &state, None);
+}
+
+/// Get the signed-in operator, if any
+#[tauri::command]
+pub fn get_current_operator(state: State<'_, AuthState>) -> Option<OperatorSession> {
+    state.session.lock().expect("operator session lock").clone()
+}
+
+/// List local PIN accounts
+#[tauri::command]
+pub fn list_operator_accounts(state: State<'_, AuthState>) -> Vec<OperatorAccount> {
+    state
+        .accounts
+        .lock()
+        .expect("operator accounts lock")
+        .iter()
+        .map(|account| OperatorAccount {
+            username: account.username.clone(),
+            display_name: account.display_name.clone(),
+            role: account.role,
+            pin: None,
+        })
+        .collect()
+}
+
+/// Create or update a local PIN account (admin; the first one takes the
+/// setup token instead)
+#[tauri::command]
+pub fn save_operator_account(
+    app: AppHandle,
+    state: State<'_, AuthState>,
+    account: OperatorAccount,
+    setup_token: Option<String>,
+) -> Result<(), KioskError> {
+    require_setup_or_admin(&state, setup_token.as_deref())?;
+    let username = account.username.trim().to_string();
+    if username.is_empty() {
+        return Err(KioskError::invalid("Username is required"));
+    }
+    if let Some(pin) = &account.pin {
+        if !(4..=12).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+            return Err(KioskError::invalid("PIN must be 4 to 12 digits"));
+        }
+    }
+
+    let mut accounts = state.accounts.lock().expect("operator accounts lock");
+    let position = accounts
+        .iter()
+        .position(|existing| existing.username.eq_ignore_ascii_case(&username));
+    let (pin_hash, salt) = match (&account.pin, position) {
+        (Some(pin), _) => {
+            let salt = new_salt();
+            (hash_secret(pin, &salt), salt)
+        }
+        (None, Some(index)) => (accounts[index].pin_hash.clone(), accounts[index].salt.clone()),
+        (None, None) => return Err(KioskError::invalid("A PIN is required for new accounts")),
+    };
+    // The first account must be able to manage the others
+    let role = if accounts.is_empty() { Role::Admin } else { account.role };
+
+    let updated = LocalAccount {
+        username,
+        display_name: account.display_name,
+        role,
+        pin_hash,
+        salt,
+    };
+    match position {
+        Some(index) => accounts[index] = updated,
+        None => accounts.push(updated),
+    }
+    store::save(&app, ACCOUNTS_FILE, &*accounts)?;
+    if state.setup_token.lock().expect("setup token lock").take().is_some() {
+        forget_setup_token(&app);
+    }
+    Ok(())
+}
+
+/// Delete a local PIN account (admin only)
+#[tauri::command]
+pub fn delete_operator_account(app: AppHandle, state: State<'_, AuthState>, username: String) -> Result<(), KioskError> {
+    require(&state, Role::Admin)?;
+    let mut accounts = state.accounts.lock().expect("operator accounts lock");
+    accounts.retain(|account| !account.username.eq_ignore_ascii_case(&username));
+    store::save(&app, ACCOUNTS_FILE, &*accounts)?;
+    if accounts.is_empty() {
+        *state.setup_token.lock().expect("setup token lock") = Some(setup_token(&app)?);
+    }
+    Ok(())
+}
+
+/// Get the directory settings (service password blanked)
+#[tauri::command]
+pub fn get_ldap_config(state: State<'_, AuthState>) -> LdapConfig {
+    LdapConfig {
+        bind_password: String::new(),
+        ..state.ldap.lock().expect("ldap config lock").clone()
+    }
+}
+
+/// Update the directory settings. An empty service password keeps the stored one.
+/// Needs a signed-in admin even before the first local account exists, since
+/// whoever points the kiosk at a directory decides who can sign in.
+#[tauri::command]
+pub fn set_ldap_config(app: AppHandle, state: State<'_, AuthState>, mut config: LdapConfig) -> Result<(), KioskError> {
+    require(&state, Role::Admin)?;
+    let mut current = state.ldap.lock().expect("ldap config lock");
+    if config.bind_password.is_empty() {
+        config.bind_password = current.bind_password.clone();
+    } else {
+        keyring::put(&app, LDAP_PASSWORD_KEY, &config.bind_password)?;
+    }
+
+    store::save(
+        &app,
+        LDAP_FILE,
+        &LdapConfig {
+            bind_password: String::new(),
+            ..config.clone()
+        },
+    )?;
+    *current = config;
+    Ok(())
+}
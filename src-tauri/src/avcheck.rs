@@ -0,0 +1,433 @@
+//! Meeting-room AV self-test
+//!
+//! `test_av_devices` checks the camera, microphone and speaker end to end
+//! as a job, so an operator can trust a meeting-room kiosk before a call.
+//! The camera is read for a few seconds through ffmpeg and judged on frame
+//! rate, brightness and whether the picture changes at all. The
+//! microphone's background level is measured, then a 1 kHz tone is played
+//! through the speaker while it listens: hearing the tone proves the whole
+//! chain from playback, through the room, to capture. Each part comes back
+//! with its measurements and the problems found, worded for the operator.
+
+use chrono::Local;
+use serde::Serialize;
+use std::f64::consts::PI;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State, Window};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::jobs::{self, JobHandle};
+use crate::microphone::{self, SAMPLE_RATE};
+
+const VIDEO_DEVICES: &str = "/sys/class/video4linux";
+
+/// Frames are scaled down to this, in 8-bit gray, for measuring
+const FRAME_WIDTH: usize = 64;
+const FRAME_HEIGHT: usize = 48;
+
+const CAMERA_SECONDS: u32 = 3;
+
+/// Below this the camera is too slow for a call
+const MIN_FPS: f64 = 10.0;
+
+/// Mean gray level below which the picture counts as black
+const DARK_LEVEL: f64 = 8.0;
+
+/// Mean change between frames, in gray levels, below which the picture is
+/// frozen; sensor noise alone is well above it
+const FROZEN_MOTION: f64 = 0.05;
+
+/// Background listened to before the tone
+const AMBIENT: Duration = Duration::from_secs(1);
+
+const TONE_HZ: f64 = 1000.0;
+const TONE: Duration = Duration::from_secs(2);
+const TONE_AMPLITUDE: f64 = 0.25;
+
+/// Listening while the tone plays, with room for playback latency
+const LISTEN: Duration = Duration::from_millis(2500);
+
+/// How far above the background the tone has to be heard
+const TONE_MARGIN_DB: f64 = 10.0;
+
+/// Peak level that counts as clipping
+const CLIPPING: f64 = 0.99;
+
+/// Processes that hang are killed after this
+const DEVICE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CameraCheck {
+    pub device: Option<String>,
+    pub passed: bool,
+    pub frames: u32,
+    pub fps: f64,
+    /// Time to the first frame
+    pub startup_ms: Option<u64>,
+    /// Mean gray level, 0 to 255
+    pub brightness: f64,
+    /// Mean change between frames, in gray levels
+    pub motion: f64,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MicrophoneCheck {
+    pub device: Option<String>,
+    pub passed: bool,
+    /// Background level, in dBFS
+    pub ambient_db: Option<f64>,
+    /// Loudest sample during the test, in dBFS
+    pub peak_db: Option<f64>,
+    pub clipping: bool,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeakerCheck {
+    pub device: Option<String>,
+    pub passed: bool,
+    /// The test tone's level at the microphone, in dBFS
+    pub tone_db: Option<f64>,
+    /// How far the tone stood out from the background
+    pub above_ambient_db: Option<f64>,
+    pub issues: Vec<String>,
+}
+
+/// Result of a `test_av_devices` job
+#[derive(Debug, Clone, Serialize)]
+pub struct AvReport {
+    pub camera: CameraCheck,
+    pub microphone: MicrophoneCheck,
+    pub speaker: SpeakerCheck,
+    pub passed: bool,
+    pub tested_at: i64,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Kills a process that has not finished by the deadline
+struct Watchdog(Arc<Mutex<bool>>);
+
+impl Watchdog {
+    fn start(child: &Child) -> Self {
+        let finished = Arc::new(Mutex::new(false));
+        let (pid, watched) = (child.id(), finished.clone());
+        std::thread::spawn(move || {
+            std::thread::sleep(DEVICE_TIMEOUT);
+            let finished = watched.lock().expect("watchdog lock");
+            if !*finished {
+                // SAFETY: kill has no memory safety requirements; the
+                // process is not reaped until `finish`, so the pid is its own
+                unsafe { libc::kill(pid as i32, libc::SIGKILL) };
+            }
+        });
+        Watchdog(finished)
+    }
+
+    /// Stop watching and end the process
+    fn finish(self, child: &mut Child) {
+        *self.0.lock().expect("watchdog lock") = true;
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+fn db(level: f64) -> f64 {
+    (20.0 * level.max(1e-6).log10() * 10.0).round() / 10.0
+}
+
+fn samples(bytes: &[u8]) -> Vec<f64> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| f64::from(i16::from_le_bytes([pair[0], pair[1]])) / 32768.0)
+        .collect()
+}
+
+/// Amplitude of the test tone in a block of samples (Goertzel)
+fn tone_amplitude(samples: &[f64]) -> f64 {
+    let coefficient = 2.0 * (2.0 * PI * TONE_HZ / f64::from(SAMPLE_RATE)).cos();
+    let (mut previous, mut before) = (0.0, 0.0);
+    for sample in samples {
+        let current = sample + coefficient * previous - before;
+        before = previous;
+        previous = current;
+    }
+    let power = previous * previous + before * before - coefficient * previous * before;
+    2.0 * power.max(0.0).sqrt() / samples.len().max(1) as f64
+}
+
+/// The tone's strongest showing over 100 ms blocks
+fn strongest_tone(samples: &[f64]) -> f64 {
+    samples
+        .chunks(SAMPLE_RATE as usize / 10)
+        .map(tone_amplitude)
+        .fold(0.0, f64::max)
+}
+
+fn tone_pcm() -> Vec<u8> {
+    let count = (f64::from(SAMPLE_RATE) * TONE.as_secs_f64()) as usize;
+    (0..count)
+        .flat_map(|i| {
+            let phase = 2.0 * PI * TONE_HZ * i as f64 / f64::from(SAMPLE_RATE);
+            ((phase.sin() * TONE_AMPLITUDE * 32767.0) as i16).to_le_bytes()
+        })
+        .collect()
+}
+
+fn bytes_for(duration: Duration) -> usize {
+    (f64::from(SAMPLE_RATE) * duration.as_secs_f64()) as usize * 2
+}
+
+// ============================================================================
+// Camera
+// ============================================================================
+
+/// The first capture node, e.g. `/dev/video0`; a camera's metadata nodes
+/// have a non-zero index
+fn first_camera() -> Option<String> {
+    let mut nodes: Vec<String> = fs::read_dir(VIDEO_DEVICES)
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            fs::read_to_string(entry.path().join("index")).map_or(true, |index| index.trim() == "0")
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    nodes.sort_by_key(|name| name.trim_start_matches("video").parse::<u32>().unwrap_or(u32::MAX));
+    nodes.first().map(|name| format!("/dev/{}", name))
+}
+
+fn check_camera(device: Option<String>) -> CameraCheck {
+    let mut check = CameraCheck {
+        device: device.or_else(first_camera),
+        passed: false,
+        frames: 0,
+        fps: 0.0,
+        startup_ms: None,
+        brightness: 0.0,
+        motion: 0.0,
+        issues: Vec::new(),
+    };
+    let Some(device) = check.device.clone() else {
+        check.issues.push("No camera was found".to_string());
+        return check;
+    };
+    if !device.starts_with("/dev/video") || !Path::new(&device).exists() {
+        check.issues.push(format!("{} is not a camera", device));
+        return check;
+    }
+    let started = Instant::now();
+    let child = Command::new("ffmpeg")
+        .args(["-loglevel", "error", "-f", "v4l2", "-i", &device, "-t", &CAMERA_SECONDS.to_string()])
+        .args(["-vf", &format!("scale={}:{}", FRAME_WIDTH, FRAME_HEIGHT), "-pix_fmt", "gray", "-f", "rawvideo", "-"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            check.issues.push(format!("Failed to run ffmpeg: {}", e));
+            return check;
+        }
+    };
+    let watchdog = Watchdog::start(&child);
+    let mut frame = vec![0u8; FRAME_WIDTH * FRAME_HEIGHT];
+    let mut previous: Option<Vec<u8>> = None;
+    let (mut first_at, mut last_at) = (None, started.elapsed());
+    let (mut brightness, mut motion) = (0.0, 0.0);
+    if let Some(mut video) = child.stdout.take() {
+        while video.read_exact(&mut frame).is_ok() {
+            last_at = started.elapsed();
+            first_at.get_or_insert(last_at);
+            check.frames += 1;
+            brightness += frame.iter().map(|&level| f64::from(level)).sum::<f64>() / frame.len() as f64;
+            if let Some(previous) = &previous {
+                let change: u64 = frame.iter().zip(previous).map(|(a, b)| u64::from(a.abs_diff(*b))).sum();
+                motion += change as f64 / frame.len() as f64;
+            }
+            previous = Some(frame.clone());
+        }
+    }
+    watchdog.finish(&mut child);
+
+    let Some(first_at) = first_at else {
+        check.issues.push("The camera gave no picture: it may be unplugged or in use by another app".to_string());
+        return check;
+    };
+    check.startup_ms = Some(first_at.as_millis() as u64);
+    check.brightness = (brightness / f64::from(check.frames) * 10.0).round() / 10.0;
+    if check.frames > 1 {
+        check.fps = (f64::from(check.frames - 1) / (last_at - first_at).as_secs_f64().max(0.001) * 10.0).round() / 10.0;
+        check.motion = (motion / f64::from(check.frames - 1) * 100.0).round() / 100.0;
+    }
+    if check.fps < MIN_FPS {
+        check.issues.push(format!("The camera only managed {:.1} frames a second", check.fps));
+    }
+    if check.brightness < DARK_LEVEL {
+        check.issues.push("The picture is black: the lens may be covered or the room dark".to_string());
+    } else if check.frames > 1 && check.motion < FROZEN_MOTION {
+        check.issues.push("The picture never changes: the camera may be frozen".to_string());
+    }
+    check.passed = check.issues.is_empty();
+    check
+}
+
+// ============================================================================
+// Audio
+// ============================================================================
+
+/// What the microphone picked up: the background, then the listening
+/// window while the tone played, or why it could not be played
+struct Recording {
+    ambient: Vec<f64>,
+    heard: Result<Vec<f64>, String>,
+}
+
+fn listen_for_tone(input: Option<&str>, output: Option<&str>) -> Result<Recording, String> {
+    let mut capture = microphone::capture(input)?;
+    let watchdog = Watchdog::start(&capture);
+    let mut audio = capture.stdout.take().ok_or("The capture has no output")?;
+    let mut ambient = vec![0u8; bytes_for(AMBIENT)];
+    if let Err(e) = audio.read_exact(&mut ambient) {
+        watchdog.finish(&mut capture);
+        return Err(format!("The microphone gave no sound: {}", e));
+    }
+
+    let mut player = match microphone::playback(output) {
+        Ok(player) => player,
+        Err(e) => {
+            watchdog.finish(&mut capture);
+            return Ok(Recording { ambient: samples(&ambient), heard: Err(e) });
+        }
+    };
+    let player_watchdog = Watchdog::start(&player);
+    let writer = player.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(&tone_pcm());
+        })
+    });
+    let mut heard = vec![0u8; bytes_for(LISTEN)];
+    let result = audio.read_exact(&mut heard);
+    watchdog.finish(&mut capture);
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    player_watchdog.finish(&mut player);
+    match result {
+        Ok(()) => Ok(Recording { ambient: samples(&ambient), heard: Ok(samples(&heard)) }),
+        Err(e) => Err(format!("The microphone stopped during the test: {}", e)),
+    }
+}
+
+fn check_audio(input: Option<String>, output: Option<String>) -> (MicrophoneCheck, SpeakerCheck) {
+    let mut microphone = MicrophoneCheck {
+        device: input.clone(),
+        passed: false,
+        ambient_db: None,
+        peak_db: None,
+        clipping: false,
+        issues: Vec::new(),
+    };
+    let mut speaker = SpeakerCheck {
+        device: output.clone(),
+        passed: false,
+        tone_db: None,
+        above_ambient_db: None,
+        issues: Vec::new(),
+    };
+    let Recording { ambient, heard } = match listen_for_tone(input.as_deref(), output.as_deref()) {
+        Ok(result) => result,
+        Err(e) => {
+            microphone.issues.push(e);
+            speaker.issues.push("The speaker could not be checked without a working microphone".to_string());
+            return (microphone, speaker);
+        }
+    };
+
+    let all = ambient.iter().chain(heard.iter().flatten());
+    let peak = all.clone().fold(0.0f64, |peak, sample| peak.max(sample.abs()));
+    let rms = (ambient.iter().map(|sample| sample * sample).sum::<f64>() / ambient.len().max(1) as f64).sqrt();
+    microphone.ambient_db = Some(db(rms));
+    microphone.peak_db = Some(db(peak));
+    microphone.clipping = peak >= CLIPPING;
+    if all.clone().all(|sample| *sample == 0.0) {
+        microphone.issues.push("The microphone gives only silence: it may be muted".to_string());
+    }
+    if microphone.clipping {
+        microphone.issues.push("The microphone is clipping: its gain is too high".to_string());
+    }
+    microphone.passed = microphone.issues.is_empty();
+
+    let heard = match heard {
+        Ok(heard) => heard,
+        Err(e) => {
+            speaker.issues.push(e);
+            return (microphone, speaker);
+        }
+    };
+    let (background, tone) = (strongest_tone(&ambient), strongest_tone(&heard));
+    let margin = ((db(tone) - db(background)) * 10.0).round() / 10.0;
+    speaker.tone_db = Some(db(tone));
+    speaker.above_ambient_db = Some(margin);
+    if !microphone.passed {
+        speaker.issues.push("The speaker could not be checked without a working microphone".to_string());
+    } else if margin < TONE_MARGIN_DB {
+        speaker.issues.push("The test tone was not heard: check the speaker is on and turned up".to_string());
+    }
+    speaker.passed = speaker.issues.is_empty();
+    (microphone, speaker)
+}
+
+fn test(
+    job: &JobHandle,
+    camera: Option<String>,
+    input: Option<String>,
+    output: Option<String>,
+) -> Result<AvReport, String> {
+    job.progress(0.0, Some("Camera".to_string()));
+    let camera = check_camera(camera);
+    job.check()?;
+    job.progress(0.5, Some("Microphone and speaker".to_string()));
+    let (microphone, speaker) = check_audio(input, output);
+    Ok(AvReport {
+        passed: camera.passed && microphone.passed && speaker.passed,
+        camera,
+        microphone,
+        speaker,
+        tested_at: Local::now().timestamp(),
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Check the camera (a `/dev/video` node), microphone and speaker (ids from
+/// the audio device lists) as a job; the defaults are used for any not
+/// given. The job's result is an `AvReport`.
+#[tauri::command]
+pub fn test_av_devices(
+    app: AppHandle,
+    window: Window,
+    auth: State<'_, AuthState>,
+    camera: Option<String>,
+    input: Option<String>,
+    output: Option<String>,
+) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    Ok(jobs::spawn(&app, Some(window.label()), "av-test", move |job| test(job, camera, input, output)))
+}
@@ -0,0 +1,375 @@
+//! Backup and restore
+//!
+//! Snapshots the app data directory (module settings, databases, downloaded
+//! content) into a timestamped zip on a USB drive, so a kiosk can be moved
+//! to new hardware. The archive carries a manifest with the SHA-256 of every
+//! file and a `.sha256` file beside it; both are checked after writing and
+//! again before a restore touches anything. The keyring files are sealed to
+//! the device that wrote them and are never copied; given a passphrase, a
+//! backup carries the secrets re-sealed under it, and a restore with the
+//! same passphrase seals them to the new device. Without one, secrets have
+//! to be entered again after moving. A restore replaces the data directory
+//! and restarts the kiosk.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State, Window};
+
+use crate::auth::{self, AuthState, Role};
+use crate::checksum::to_hex;
+use crate::error::KioskError;
+use crate::jobs::{self, JobHandle};
+use crate::keyring::{self, SecretsExport};
+use crate::vfs::{self, Access};
+use crate::zip::{self, ZipWriter};
+use crate::{events, overlay};
+
+const MANIFEST: &str = "manifest.json";
+
+/// Passphrase-sealed keyring export, beside the manifest
+const SECRETS_ENTRY: &str = "secrets.json";
+
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Folder in the archive holding the data directory
+const DATA_PREFIX: &str = "data/";
+
+/// Sealed to this device, and support bundles are not worth carrying over
+const EXCLUDED: &[&str] = &["secrets.json", "keyring.key", "support"];
+
+/// Time for the frontend to show the result before the restart
+const RESTART_DELAY: Duration = Duration::from_secs(3);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Archive contents as (name, contents)
+type Files = Vec<(String, Vec<u8>)>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    /// Path relative to the data directory, `/` separated
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub created_at: String,
+    pub host: String,
+    pub version: String,
+    pub files: Vec<BackupEntry>,
+    /// The keyring export, when the backup was given a passphrase
+    #[serde(default)]
+    pub secrets: Option<BackupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupResult {
+    pub path: String,
+    pub size: u64,
+    pub files: usize,
+    /// Keyring secrets carried under the passphrase
+    pub secrets: usize,
+    /// SHA-256 of the archive, also written beside it
+    pub sha256: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub files: usize,
+    /// Keyring secrets sealed to this device
+    pub secrets: usize,
+    /// Host and time the backup was taken on
+    pub host: String,
+    pub created_at: String,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn sha256(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+/// Files under `dir` as paths relative to `root`, skipping symlinks and exclusions
+fn collect(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Ok(relative) = path.strip_prefix(root) else { continue };
+        if EXCLUDED.iter().any(|excluded| relative == Path::new(excluded)) {
+            continue;
+        }
+        let Ok(metadata) = fs::symlink_metadata(&path) else { continue };
+        if metadata.is_dir() {
+            collect(root, &path, files);
+        } else if metadata.is_file() && path.extension().map_or(true, |ext| ext != "tmp") {
+            files.push(relative.to_path_buf());
+        }
+    }
+}
+
+fn sidecar(archive: &Path) -> PathBuf {
+    let mut name = archive.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// Check the archive against its `.sha256` file (when present) and every
+/// file against the manifest, returning the manifest, the data entries and
+/// the keyring export
+fn verify(archive: &Path, data: &[u8]) -> Result<(BackupManifest, Files, Option<Vec<u8>>), String> {
+    if let Ok(expected) = fs::read_to_string(sidecar(archive)) {
+        let expected = expected.split_whitespace().next().unwrap_or_default().to_lowercase();
+        if expected != sha256(data) {
+            return Err("The backup does not match its .sha256 file".to_string());
+        }
+    }
+
+    let mut entries = zip::read_entries(data)?;
+    let at = entries
+        .iter()
+        .position(|(name, _)| name == MANIFEST)
+        .ok_or_else(|| "Not a kiosk backup: no manifest".to_string())?;
+    let (_, manifest) = entries.swap_remove(at);
+    let manifest: BackupManifest = serde_json::from_slice(&manifest).map_err(|e| format!("Bad manifest: {}", e))?;
+
+    let secrets = match &manifest.secrets {
+        Some(entry) => {
+            let (_, contents) = entries
+                .iter()
+                .find(|(name, _)| *name == SECRETS_ENTRY)
+                .ok_or_else(|| "The exported secrets are missing from the backup".to_string())?;
+            if contents.len() as u64 != entry.size || sha256(contents) != entry.sha256 {
+                return Err("The exported secrets are damaged".to_string());
+            }
+            Some(contents.clone())
+        }
+        None => None,
+    };
+
+    let files: Files = entries
+        .into_iter()
+        .filter_map(|(name, contents)| Some((name.strip_prefix(DATA_PREFIX)?.to_string(), contents)))
+        .collect();
+    if files.len() != manifest.files.len() {
+        return Err("The backup is missing files listed in its manifest".to_string());
+    }
+    for entry in &manifest.files {
+        let (_, contents) = files
+            .iter()
+            .find(|(name, _)| *name == entry.name)
+            .ok_or_else(|| format!("{} is missing from the backup", entry.name))?;
+        if contents.len() as u64 != entry.size || sha256(contents) != entry.sha256 {
+            return Err(format!("{} is damaged", entry.name));
+        }
+    }
+    Ok((manifest, files, secrets))
+}
+
+/// Whether an archive name is a plain relative path that stays inside the data directory
+fn safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('/')
+        && name.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+}
+
+fn backup(
+    app: &AppHandle,
+    job: &JobHandle,
+    destination: &str,
+    passphrase: Option<&str>,
+) -> Result<BackupResult, String> {
+    let dir = vfs::resolve(app, destination, Access::Write).map_err(|e| e.to_string())?;
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let mut files = Vec::new();
+    collect(&data_dir, &data_dir, &mut files);
+    files.sort();
+
+    let now = Local::now();
+    let host = sysinfo::System::host_name().unwrap_or_else(|| "kiosk".to_string());
+    let path = dir.join(format!("kiosk-backup-{}-{}.zip", host, now.format("%Y%m%d-%H%M%S")));
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(BufWriter::new(file));
+
+    let result: Result<(String, u64, usize, usize), String> = (|| {
+        let mut manifest = BackupManifest {
+            created_at: now.to_rfc3339(),
+            host: host.clone(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            files: Vec::new(),
+            secrets: None,
+        };
+        for (index, relative) in files.iter().enumerate() {
+            job.check()?;
+            job.progress(index as f64 / files.len() as f64 * 0.9, Some(relative.display().to_string()));
+            let contents = fs::read(data_dir.join(relative)).map_err(|e| format!("{}: {}", relative.display(), e))?;
+            let name = relative.to_string_lossy().replace('\\', "/");
+            zip.add(&format!("{}{}", DATA_PREFIX, name), &contents)?;
+            manifest.files.push(BackupEntry {
+                name,
+                size: contents.len() as u64,
+                sha256: sha256(&contents),
+            });
+        }
+        let mut secrets = 0;
+        if let Some(passphrase) = passphrase {
+            job.progress(0.9, Some("Exporting secrets".to_string()));
+            let export = keyring::export(app, passphrase)?;
+            let contents = serde_json::to_vec(&export).map_err(|e| e.to_string())?;
+            zip.add(SECRETS_ENTRY, &contents)?;
+            secrets = export.secrets.len();
+            manifest.secrets = Some(BackupEntry {
+                name: SECRETS_ENTRY.to_string(),
+                size: contents.len() as u64,
+                sha256: sha256(&contents),
+            });
+        }
+        let text = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+        zip.add(MANIFEST, &text)?;
+        zip.finish()?;
+
+        // Read the archive back so a bad drive is caught now, not at restore time
+        job.progress(0.9, Some("Verifying".to_string()));
+        let written = fs::read(&path).map_err(|e| e.to_string())?;
+        let digest = sha256(&written);
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        fs::write(sidecar(&path), format!("{}  {}\n", digest, name)).map_err(|e| e.to_string())?;
+        verify(&path, &written)?;
+        Ok((digest, written.len() as u64, manifest.files.len(), secrets))
+    })();
+
+    let (digest, size, count, secrets) = result.map_err(|e| {
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(sidecar(&path));
+        e
+    })?;
+    let result = BackupResult {
+        path: vfs::to_virtual(app, &path).unwrap_or_else(|| path.display().to_string()),
+        size,
+        files: count,
+        secrets,
+        sha256: digest,
+        created_at: now.timestamp(),
+    };
+    events::publish(app, "backup-created", &result);
+    Ok(result)
+}
+
+fn restore(app: &AppHandle, job: &JobHandle, archive: &str, passphrase: Option<&str>) -> Result<RestoreResult, String> {
+    let path = vfs::resolve(app, archive, Access::Read).map_err(|e| e.to_string())?;
+    job.progress(0.0, Some("Verifying".to_string()));
+    let data = fs::read(&path).map_err(|e| e.to_string())?;
+    let (manifest, files, export) = verify(&path, &data)?;
+    if let Some((name, _)) = files.iter().find(|(name, _)| !safe_name(name)) {
+        return Err(format!("The backup contains an unsafe path: {}", name));
+    }
+    // Opened now, so a wrong passphrase fails before the data is replaced
+    let secrets = match (export, passphrase) {
+        (Some(export), Some(passphrase)) => {
+            let export: SecretsExport =
+                serde_json::from_slice(&export).map_err(|e| format!("Bad secrets export: {}", e))?;
+            keyring::open_export(&export, passphrase)?
+        }
+        _ => BTreeMap::new(),
+    };
+    job.check()?;
+
+    // Nothing is touched until the whole backup has checked out
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    job.progress(0.3, Some("Removing current data".to_string()));
+    let Ok(entries) = fs::read_dir(&data_dir) else {
+        return Err(format!("Cannot read {}", data_dir.display()));
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let keep = path.file_name().is_some_and(|name| EXCLUDED.iter().any(|excluded| name == *excluded));
+        if keep {
+            continue;
+        }
+        let is_dir = fs::symlink_metadata(&path).map(|m| m.is_dir()).unwrap_or(false);
+        let removed = if is_dir { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        removed.map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    for (index, (name, contents)) in files.iter().enumerate() {
+        job.progress(0.4 + index as f64 / files.len() as f64 * 0.5, Some(name.clone()));
+        let target = data_dir.join(name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&target, contents).map_err(|e| format!("{}: {}", name, e))?;
+    }
+    keyring::import(app, &secrets)?;
+    overlay::persist(app, Vec::new())?;
+
+    let result = RestoreResult {
+        files: files.len(),
+        secrets: secrets.len(),
+        host: manifest.host,
+        created_at: manifest.created_at,
+    };
+    events::publish(app, "backup-restored", &result);
+    Ok(result)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Write a backup of the app data to a drive or folder (a virtual path such
+/// as a removable root) as a job; the job result is a `BackupResult`. With a
+/// passphrase, the keyring secrets go along sealed under it (admin)
+#[tauri::command]
+pub fn backup_to_drive(
+    app: AppHandle,
+    window: Window,
+    auth: State<'_, AuthState>,
+    mount_point: String,
+    passphrase: Option<String>,
+) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if passphrase.as_ref().is_some_and(|passphrase| passphrase.chars().count() < MIN_PASSPHRASE_LEN) {
+        return Err(KioskError::invalid(format!(
+            "A backup passphrase is at least {} characters",
+            MIN_PASSPHRASE_LEN
+        )));
+    }
+    let worker = app.clone();
+    Ok(jobs::spawn(&app, Some(window.label()), "backup", move |job| {
+        backup(&worker, job, &mount_point, passphrase.as_deref())
+    }))
+}
+
+/// Verify a backup archive, replace the app data with it and restart the
+/// kiosk, as a job; the job result is a `RestoreResult`. The backup's
+/// passphrase brings its secrets along; without it they are skipped (admin)
+#[tauri::command]
+pub fn restore_from_drive(
+    app: AppHandle,
+    window: Window,
+    auth: State<'_, AuthState>,
+    path: String,
+    passphrase: Option<String>,
+) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let worker = app.clone();
+    Ok(jobs::spawn(&app, Some(window.label()), "restore", move |job| {
+        let result = restore(&worker, job, &path, passphrase.as_deref())?;
+        // Restart so no module writes its old in-memory state over the restored files
+        std::thread::spawn(move || {
+            std::thread::sleep(RESTART_DELAY);
+            worker.restart();
+        });
+        Ok(result)
+    }))
+}
@@ -0,0 +1,395 @@
+//! Visitor badges
+//!
+//! Renders a visitor badge (header band, photo, name, company and a QR code)
+//! to a PNG or a one-page PDF at label-printer resolution, for on-screen
+//! preview during check-in and for printing through CUPS on a label printer.
+//! A photo is a `data:` URL or a virtual path.
+
+use ab_glyph::{point, Font, FontVec, PxScale, ScaleFont};
+use base64::Engine;
+use chrono::Local;
+use image::{imageops, DynamicImage, ImageFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Cursor, Write};
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::fonts;
+use crate::i18n::{self, DateStyle};
+use crate::vfs::{self, Access};
+#[cfg(feature = "printing")]
+use crate::{lazy, printing};
+
+const MM_PER_INCH: f64 = 25.4;
+
+const POINTS_PER_INCH: f64 = 72.0;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Badge size and styling. Defaults to a landscape CR80 card.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BadgeTemplate {
+    pub width_mm: f64,
+    pub height_mm: f64,
+    pub dpi: u32,
+    pub header_text: String,
+    /// Header band colour as "#rrggbb"
+    pub accent_color: String,
+    /// Label printer queue; the system default when unset
+    pub printer: Option<String>,
+}
+
+impl Default for BadgeTemplate {
+    fn default() -> Self {
+        BadgeTemplate {
+            width_mm: 86.0,
+            height_mm: 54.0,
+            dpi: 300,
+            header_text: "VISITOR".to_string(),
+            accent_color: "#000080".to_string(),
+            printer: None,
+        }
+    }
+}
+
+/// Visitor details printed on the badge
+#[derive(Debug, Clone, Deserialize)]
+pub struct BadgeData {
+    pub name: String,
+    #[serde(default)]
+    pub subtitle: String,
+    /// Image file path or `data:` URL (e.g. a webcam capture)
+    pub photo: Option<String>,
+    /// Text encoded in the QR code, e.g. a visit id
+    pub qr_data: Option<String>,
+    #[serde(default)]
+    pub template: BadgeTemplate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BadgeFormat {
+    #[default]
+    Png,
+    /// One page the size of the badge
+    Pdf,
+}
+
+/// A rendered badge
+#[derive(Debug, Clone, Serialize)]
+pub struct BadgePreview {
+    /// PNG or PDF as a `data:` URL
+    pub data_url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct BadgeFonts {
+    bold: FontVec,
+    regular: FontVec,
+}
+
+// ============================================================================
+// Drawing
+// ============================================================================
+
+fn load_font(pattern: &str) -> Result<FontVec, String> {
+    let path = fonts::fc_match(pattern)?;
+    let data = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    FontVec::try_from_vec(data).map_err(|_| format!("Unsupported font: {}", path.display()))
+}
+
+pub(crate) fn parse_color(value: &str) -> Result<Rgba<u8>, String> {
+    let hex = value.trim_start_matches('#');
+    let channel = |range: std::ops::Range<usize>| {
+        hex.get(range)
+            .and_then(|part| u8::from_str_radix(part, 16).ok())
+            .ok_or_else(|| format!("Invalid colour: {}", value))
+    };
+    if hex.len() != 6 {
+        return Err(format!("Invalid colour: {}", value));
+    }
+    Ok(Rgba([channel(0..2)?, channel(2..4)?, channel(4..6)?, 255]))
+}
+
+fn load_photo(app: &AppHandle, source: &str) -> Result<DynamicImage, String> {
+    if let Some(data) = source.strip_prefix("data:") {
+        let (_, encoded) = data.split_once(',').ok_or("Invalid photo data URL")?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| e.to_string())?;
+        image::load_from_memory(&bytes).map_err(|e| format!("Invalid photo: {}", e))
+    } else {
+        let path = vfs::resolve(app, source, Access::Read).map_err(|e| e.to_string())?;
+        image::open(path).map_err(|e| format!("Failed to open photo: {}", e))
+    }
+}
+
+fn fill_rect(image: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, color: Rgba<u8>) {
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+fn text_width(font: &FontVec, size: f32, text: &str) -> f32 {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            width += scaled.kern(previous, id);
+        }
+        width += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Largest size up to `size` at which the text fits in `max_width`
+fn fit_size(font: &FontVec, size: f32, text: &str, max_width: f32) -> f32 {
+    let width = text_width(font, size, text);
+    if width <= max_width || width == 0.0 {
+        size
+    } else {
+        size * max_width / width
+    }
+}
+
+/// Draw a line of text with its top edge at `y`, blending anti-aliased edges
+fn draw_text(image: &mut RgbaImage, font: &FontVec, size: f32, x: f32, y: f32, color: Rgba<u8>, text: &str) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let baseline = y + scaled.ascent();
+    let mut caret = x;
+    let mut previous = None;
+
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        previous = Some(id);
+
+        let glyph = id.with_scale_and_position(size, point(caret, baseline));
+        caret += scaled.h_advance(id);
+        let Some(outline) = font.outline_glyph(glyph) else { continue };
+
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let px = bounds.min.x as i64 + gx as i64;
+            let py = bounds.min.y as i64 + gy as i64;
+            if px < 0 || py < 0 || px >= image.width() as i64 || py >= image.height() as i64 {
+                return;
+            }
+            let pixel = image.get_pixel_mut(px as u32, py as u32);
+            for channel in 0..3 {
+                let under = pixel[channel] as f32;
+                pixel[channel] = (under + (color[channel] as f32 - under) * coverage.min(1.0)).round() as u8;
+            }
+        });
+    }
+}
+
+/// Draw a QR code with a two-module quiet zone into a square of at most `side` pixels
+fn draw_qr(image: &mut RgbaImage, data: &str, x: u32, y: u32, side: u32) -> Result<u32, String> {
+    let code = qrcode::QrCode::new(data.as_bytes()).map_err(|e| format!("Cannot encode QR code: {}", e))?;
+    let modules = code.width() as u32;
+    let module = (side / (modules + 4)).max(1);
+    let drawn = module * (modules + 4);
+    // Anchor to the bottom-right of the allotted square
+    let (x, y) = (x + side.saturating_sub(drawn), y + side.saturating_sub(drawn));
+
+    fill_rect(image, x, y, drawn, drawn, Rgba([255, 255, 255, 255]));
+    for (index, color) in code.to_colors().into_iter().enumerate() {
+        if color == qrcode::Color::Dark {
+            let (column, row) = (index as u32 % modules, index as u32 / modules);
+            fill_rect(image, x + (column + 2) * module, y + (row + 2) * module, module, module, Rgba([0, 0, 0, 255]));
+        }
+    }
+    Ok(drawn)
+}
+
+fn render(app: &AppHandle, data: &BadgeData, fonts: &BadgeFonts) -> Result<RgbaImage, String> {
+    let template = &data.template;
+    if data.name.trim().is_empty() {
+        return Err("Badge needs a name".to_string());
+    }
+    let to_px = |mm: f64| (mm / MM_PER_INCH * template.dpi as f64).round() as u32;
+    let (width, height) = (to_px(template.width_mm), to_px(template.height_mm));
+    if width < 100 || height < 100 || width > 10_000 || height > 10_000 {
+        return Err("Badge size out of range".to_string());
+    }
+
+    let black = Rgba([0, 0, 0, 255]);
+    let white = Rgba([255, 255, 255, 255]);
+    let mut image = RgbaImage::from_pixel(width, height, white);
+
+    let margin = height / 16;
+    let band = height / 5;
+    fill_rect(&mut image, 0, 0, width, band, parse_color(&template.accent_color)?);
+    let header_size = fit_size(&fonts.bold, band as f32 * 0.6, &template.header_text, (width - 2 * margin) as f32);
+    let header_y = (band as f32 - header_size) / 2.0;
+    draw_text(&mut image, &fonts.bold, header_size, margin as f32, header_y, white, &template.header_text);
+
+    let top = band + margin;
+    let content = height - top - margin;
+    let mut left = margin;
+    let mut right = width - margin;
+
+    if let Some(photo) = data.photo.as_deref().filter(|photo| !photo.is_empty()) {
+        let photo = load_photo(app, photo)?
+            .resize_to_fill(content * 3 / 4, content, imageops::FilterType::Lanczos3)
+            .to_rgba8();
+        imageops::overlay(&mut image, &photo, left as i64, top as i64);
+        left += photo.width() + margin;
+    }
+    if let Some(qr) = data.qr_data.as_deref().filter(|qr| !qr.is_empty()) {
+        let side = content * 3 / 4;
+        let drawn = draw_qr(&mut image, qr, right.saturating_sub(side), height - margin - side, side)?;
+        right = right.saturating_sub(drawn + margin / 2);
+    }
+    if right <= left + margin {
+        return Err("Badge is too small for its contents".to_string());
+    }
+
+    let text_width = (right - left) as f32;
+    let name_size = fit_size(&fonts.bold, content as f32 * 0.28, &data.name, text_width);
+    draw_text(&mut image, &fonts.bold, name_size, left as f32, top as f32, black, &data.name);
+
+    let subtitle_size = fit_size(&fonts.regular, content as f32 * 0.16, &data.subtitle, text_width);
+    let subtitle_y = top as f32 + name_size * 1.25;
+    draw_text(&mut image, &fonts.regular, subtitle_size, left as f32, subtitle_y, black, &data.subtitle);
+
+    let date = i18n::format_date(&Local::now(), DateStyle::Medium);
+    let date_size = content as f32 * 0.12;
+    let date_y = (height - margin) as f32 - date_size * 1.2;
+    draw_text(&mut image, &fonts.regular, date_size, left as f32, date_y, Rgba([96, 96, 96, 255]), &date);
+
+    Ok(image)
+}
+
+/// A single page the size of the badge holding the image
+fn pdf_page(image: &RgbaImage, template: &BadgeTemplate) -> Result<Vec<u8>, String> {
+    let rgb = DynamicImage::ImageRgba8(image.clone()).to_rgb8();
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(rgb.as_raw()).map_err(|e| e.to_string())?;
+    let pixels = encoder.finish().map_err(|e| e.to_string())?;
+
+    let to_points = |mm: f64| mm / MM_PER_INCH * POINTS_PER_INCH;
+    let (width, height) = (to_points(template.width_mm), to_points(template.height_mm));
+    let contents = format!("q {:.2} 0 0 {:.2} 0 0 cm /Im0 Do Q", width, height);
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, head: String, stream: Option<&[u8]>| {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{} 0 obj\n{}\n", offsets.len(), head).into_bytes());
+        if let Some(stream) = stream {
+            pdf.extend(b"stream\n");
+            pdf.extend(stream);
+            pdf.extend(b"\nendstream\n");
+        }
+        pdf.extend(b"endobj\n");
+    };
+    object(&mut pdf, "<< /Type /Catalog /Pages 2 0 R >>".into(), None);
+    object(&mut pdf, "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".into(), None);
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources << /XObject << /Im0 4 0 R >> >> \
+             /Contents 5 0 R >>",
+            width, height
+        ),
+        None,
+    );
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8 \
+             /Filter /FlateDecode /Length {} >>",
+            image.width(),
+            image.height(),
+            pixels.len()
+        ),
+        Some(&pixels),
+    );
+    object(&mut pdf, format!("<< /Length {} >>", contents.len()), Some(contents.as_bytes()));
+
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).into_bytes());
+    for offset in &offsets {
+        pdf.extend(format!("{:010} 00000 n \n", offset).into_bytes());
+    }
+    pdf.extend(
+        format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", offsets.len() + 1, xref).into_bytes(),
+    );
+    Ok(pdf)
+}
+
+/// Render a badge as PNG or PDF bytes, with its size in pixels
+fn render_file(app: &AppHandle, data: &BadgeData, format: BadgeFormat) -> Result<(Vec<u8>, u32, u32), String> {
+    let fonts = BadgeFonts {
+        bold: load_font("sans-serif:bold")?,
+        regular: load_font("sans-serif")?,
+    };
+    let image = render(app, data, &fonts)?;
+
+    let bytes = match format {
+        BadgeFormat::Png => {
+            let mut png = Vec::new();
+            image
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .map_err(|e| e.to_string())?;
+            png
+        }
+        BadgeFormat::Pdf => pdf_page(&image, &data.template)?,
+    };
+    Ok((bytes, image.width(), image.height()))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Render a badge for on-screen preview, as a PNG unless a PDF is asked for
+#[tauri::command]
+pub fn preview_badge(
+    app: AppHandle,
+    data: BadgeData,
+    format: Option<BadgeFormat>,
+) -> Result<BadgePreview, KioskError> {
+    let format = format.unwrap_or_default();
+    let (bytes, width, height) = render_file(&app, &data, format)?;
+    let mime = match format {
+        BadgeFormat::Png => "image/png",
+        BadgeFormat::Pdf => "application/pdf",
+    };
+    Ok(BadgePreview {
+        data_url: format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(bytes)),
+        width,
+        height,
+    })
+}
+
+/// Render a badge and print it on the template's label printer
+#[cfg(feature = "printing")]
+#[tauri::command]
+pub fn print_badge(app: AppHandle, data: BadgeData) -> Result<Option<String>, KioskError> {
+    lazy::require(&app, "printing")?;
+    let (png, _, _) = render_file(&app, &data, BadgeFormat::Png)?;
+    let template = &data.template;
+    let media = format!("media=Custom.{}x{}mm", template.width_mm, template.height_mm);
+    Ok(printing::submit(
+        template.printer.as_deref(),
+        &format!("Badge - {}", data.name),
+        &png,
+        &[media, "fit-to-page".to_string()],
+        1,
+    )?)
+}
@@ -0,0 +1,199 @@
+//! Bandwidth shaping
+//!
+//! One token bucket shared by every backend HTTP transfer, so content syncs
+//! and downloads cannot crowd out interactive browsing. The limit applies
+//! always, during the operating hours, or in set time windows; outside
+//! them transfers run at full speed. Response bodies are charged chunk by
+//! chunk as they are read, through `http::ResponseExt`. Changes in the
+//! limit in force are published as `bandwidth-limit-changed`.
+
+use chrono::{Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{events, hours, store};
+
+const BANDWIDTH_FILE: &str = "bandwidth.json";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+/// Seconds of traffic the bucket holds when idle
+const BURST_SECS: f64 = 1.0;
+
+/// Rate in force, bytes per second; `None` when unlimited
+static RATE: Mutex<Option<f64>> = Mutex::new(None);
+
+static BUCKET: Mutex<Bucket> = Mutex::new(Bucket {
+    tokens: 0.0,
+    updated: None,
+});
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// "HH:MM" to "HH:MM"; an end at or before the start runs past midnight
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeWindow {
+    pub start: String,
+    pub end: String,
+}
+
+/// When the limit applies
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BandwidthSchedule {
+    #[default]
+    Always,
+    /// While the operating hours have the kiosk open
+    OpenHours,
+    Windows { windows: Vec<TimeWindow> },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BandwidthConfig {
+    /// Kilobits per second; 0 for no limit
+    pub kbps: u32,
+    pub schedule: BandwidthSchedule,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BandwidthStatus {
+    pub config: BandwidthConfig,
+    /// Limit in force now, in kilobits per second
+    pub active_kbps: Option<u32>,
+}
+
+/// Payload of `bandwidth-limit-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct BandwidthLimitChanged {
+    pub active_kbps: Option<u32>,
+}
+
+struct Bucket {
+    /// Bytes that may go now; negative when transfers are ahead of the rate
+    tokens: f64,
+    updated: Option<Instant>,
+}
+
+pub struct BandwidthState(Mutex<BandwidthConfig>);
+
+impl BandwidthState {
+    pub fn load(app: &AppHandle) -> Self {
+        BandwidthState(Mutex::new(store::load(app, BANDWIDTH_FILE)))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Not a time (HH:MM): {}", time))
+}
+
+fn in_window(window: &TimeWindow, now: NaiveTime) -> bool {
+    let (Ok(start), Ok(end)) = (parse_time(&window.start), parse_time(&window.end)) else { return false };
+    if end > start {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// The limit the configuration puts in force now
+fn active_kbps(app: &AppHandle, config: &BandwidthConfig) -> Option<u32> {
+    let applies = match &config.schedule {
+        BandwidthSchedule::Always => true,
+        BandwidthSchedule::OpenHours => hours::is_open(app),
+        BandwidthSchedule::Windows { windows } => {
+            let now = Local::now().time();
+            windows.iter().any(|window| in_window(window, now))
+        }
+    };
+    (config.kbps > 0 && applies).then_some(config.kbps)
+}
+
+/// Put the configured limit in force, publishing a change
+fn apply(app: &AppHandle) -> Option<u32> {
+    let config = app.state::<BandwidthState>().0.lock().expect("bandwidth lock").clone();
+    let kbps = active_kbps(app, &config);
+    let rate = kbps.map(|kbps| kbps as f64 * 1000.0 / 8.0);
+    let changed = {
+        let mut current = RATE.lock().expect("bandwidth rate lock");
+        let changed = *current != rate;
+        *current = rate;
+        changed
+    };
+    if changed {
+        events::publish(app, "bandwidth-limit-changed", BandwidthLimitChanged { active_kbps: kbps });
+    }
+    kbps
+}
+
+/// Take `bytes` from the shared bucket, sleeping until the rate allows them
+pub(crate) fn throttle(bytes: u64) {
+    let Some(rate) = *RATE.lock().expect("bandwidth rate lock") else { return };
+    let wait = {
+        let mut bucket = BUCKET.lock().expect("bandwidth bucket lock");
+        let now = Instant::now();
+        let elapsed = bucket.updated.map_or(0.0, |updated| now.duration_since(updated).as_secs_f64());
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate * BURST_SECS) - bytes as f64;
+        bucket.updated = Some(now);
+        // Later callers queue behind the debt, which keeps the total rate fair
+        (bucket.tokens < 0.0).then(|| Duration::from_secs_f64(-bucket.tokens / rate))
+    };
+    if let Some(wait) = wait {
+        std::thread::sleep(wait);
+    }
+}
+
+/// Follow the schedule in the background
+pub fn start_bandwidth(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        apply(&app);
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_bandwidth_limit(app: AppHandle, state: State<'_, BandwidthState>) -> BandwidthStatus {
+    let config = state.0.lock().expect("bandwidth lock").clone();
+    BandwidthStatus {
+        active_kbps: active_kbps(&app, &config),
+        config,
+    }
+}
+
+/// Limit backend transfers to `kbps` (0 for no limit) when the schedule
+/// says so (admin)
+#[tauri::command]
+pub fn set_bandwidth_limit(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, BandwidthState>,
+    kbps: u32,
+    schedule: BandwidthSchedule,
+) -> Result<BandwidthStatus, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if let BandwidthSchedule::Windows { windows } = &schedule {
+        for window in windows {
+            parse_time(&window.start).map_err(KioskError::invalid)?;
+            parse_time(&window.end).map_err(KioskError::invalid)?;
+        }
+    }
+    let config = BandwidthConfig { kbps, schedule };
+    store::save(&app, BANDWIDTH_FILE, &config)?;
+    *state.0.lock().expect("bandwidth lock") = config.clone();
+    let active_kbps = apply(&app);
+    Ok(BandwidthStatus { config, active_kbps })
+}
@@ -0,0 +1,263 @@
+//! Performance self-test
+//!
+//! `run_benchmark` measures CPU hashing throughput (one core and all cores),
+//! memory copy bandwidth and disk write/read speed in the data directory,
+//! then combines them into a score where a Raspberry Pi 4 is about 1000.
+//! The webview's frame rate is measured by the frontend while the test runs
+//! and reported back with `report_frame_rate`. Results are kept in
+//! `benchmarks.json` so deployments can be compared.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Window};
+
+use crate::error::KioskError;
+use crate::jobs::{self, JobHandle};
+use crate::{events, store};
+
+const RESULTS_FILE: &str = "benchmarks.json";
+
+const SCRATCH_FILE: &str = "benchmark.tmp";
+
+/// Results kept in history
+const RESULTS_KEPT: usize = 20;
+
+/// How long each CPU and memory test runs
+const TEST_DURATION: Duration = Duration::from_secs(2);
+
+const BLOCK_SIZE: usize = 1024 * 1024;
+
+const MEMORY_BUFFER: usize = 32 * 1024 * 1024;
+
+const DISK_FILE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Raspberry Pi 4 reference throughput in MB/s: (cpu single, cpu multi, memory, disk write, disk read)
+const REFERENCE: [f64; 5] = [110.0, 420.0, 1800.0, 20.0, 40.0];
+
+const REFERENCE_FPS: f64 = 60.0;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameRate {
+    pub average_fps: f64,
+    pub min_fps: f64,
+    pub frames: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    /// Job id the result was produced by
+    pub id: String,
+    pub model: String,
+    pub cpu_single_mbps: f64,
+    pub cpu_multi_mbps: f64,
+    pub threads: usize,
+    pub memory_mbps: f64,
+    pub disk_write_mbps: f64,
+    pub disk_read_mbps: f64,
+    #[serde(default)]
+    pub frame_rate: Option<FrameRate>,
+    pub score: u32,
+    pub run_at: i64,
+}
+
+/// Frame rates reported before their benchmark finished
+static PENDING_FRAMES: Mutex<Vec<(String, FrameRate)>> = Mutex::new(Vec::new());
+
+// ============================================================================
+// Measurements
+// ============================================================================
+
+fn mbps(bytes: usize, elapsed: Duration) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+/// SHA-256 throughput of one thread over the test duration
+fn hash_throughput(job: Option<&JobHandle>) -> f64 {
+    let block = vec![0x5au8; BLOCK_SIZE];
+    let mut hasher = Sha256::new();
+    let started = Instant::now();
+    let mut bytes = 0;
+    while started.elapsed() < TEST_DURATION && !job.is_some_and(JobHandle::is_cancelled) {
+        hasher.update(&block);
+        bytes += block.len();
+    }
+    std::hint::black_box(hasher.finalize());
+    mbps(bytes, started.elapsed())
+}
+
+fn cpu_multi(threads: usize) -> f64 {
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads).map(|_| scope.spawn(|| hash_throughput(None))).collect();
+        workers.into_iter().map(|worker| worker.join().unwrap_or(0.0)).sum()
+    })
+}
+
+fn memory_copy(job: &JobHandle) -> f64 {
+    let source = vec![0xa5u8; MEMORY_BUFFER];
+    let mut target = vec![0u8; MEMORY_BUFFER];
+    let started = Instant::now();
+    let mut bytes = 0;
+    while started.elapsed() < TEST_DURATION && !job.is_cancelled() {
+        target.copy_from_slice(&source);
+        std::hint::black_box(&mut target);
+        bytes += MEMORY_BUFFER;
+    }
+    mbps(bytes, started.elapsed())
+}
+
+/// Sequential write with fsync, then read back with the page cache dropped
+fn disk(app: &AppHandle, job: &JobHandle) -> Result<(f64, f64), String> {
+    let path = store::data_path(app, SCRATCH_FILE)?;
+    let block = vec![0x3cu8; BLOCK_SIZE];
+
+    let result = (|| {
+        let started = Instant::now();
+        let mut file = File::create(&path).map_err(|e| e.to_string())?;
+        for _ in 0..DISK_FILE_SIZE / BLOCK_SIZE {
+            job.check()?;
+            file.write_all(&block).map_err(|e| e.to_string())?;
+        }
+        file.sync_all().map_err(|e| e.to_string())?;
+        let write = mbps(DISK_FILE_SIZE, started.elapsed());
+
+        // SAFETY: the descriptor is valid for the lifetime of `file`
+        unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+        drop(file);
+
+        let started = Instant::now();
+        let mut file = File::open(&path).map_err(|e| e.to_string())?;
+        let mut buffer = vec![0u8; BLOCK_SIZE];
+        let mut bytes = 0;
+        loop {
+            let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+            if read == 0 {
+                break;
+            }
+            bytes += read;
+        }
+        Ok((write, mbps(bytes, started.elapsed())))
+    })();
+
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/// 1000 times the geometric mean of each measurement relative to the reference
+fn score(result: &BenchmarkResult) -> u32 {
+    let mut ratios = vec![
+        result.cpu_single_mbps / REFERENCE[0],
+        result.cpu_multi_mbps / REFERENCE[1],
+        result.memory_mbps / REFERENCE[2],
+        result.disk_write_mbps / REFERENCE[3],
+        result.disk_read_mbps / REFERENCE[4],
+    ];
+    if let Some(frame_rate) = &result.frame_rate {
+        ratios.push(frame_rate.average_fps / REFERENCE_FPS);
+    }
+    let log_mean = ratios.iter().map(|ratio| ratio.max(0.001).ln()).sum::<f64>() / ratios.len() as f64;
+    (log_mean.exp() * 1000.0).round() as u32
+}
+
+fn model() -> String {
+    fs::read_to_string("/sys/firmware/devicetree/base/model")
+        .map(|model| model.trim_matches('\0').trim().to_string())
+        .unwrap_or_else(|_| "Unknown".to_string())
+}
+
+fn save_result(app: &AppHandle, result: BenchmarkResult) -> Result<(), String> {
+    let mut results: Vec<BenchmarkResult> = store::load(app, RESULTS_FILE);
+    results.retain(|existing| existing.id != result.id);
+    results.push(result);
+    let excess = results.len().saturating_sub(RESULTS_KEPT);
+    results.drain(..excess);
+    store::save(app, RESULTS_FILE, &results)
+}
+
+fn benchmark(app: &AppHandle, job: &JobHandle) -> Result<BenchmarkResult, String> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    job.progress(0.0, Some("CPU (single core)".to_string()));
+    let cpu_single_mbps = hash_throughput(Some(job));
+    job.check()?;
+    job.progress(0.2, Some("CPU (all cores)".to_string()));
+    let cpu_multi_mbps = cpu_multi(threads);
+    job.check()?;
+    job.progress(0.4, Some("Memory".to_string()));
+    let memory_mbps = memory_copy(job);
+    job.check()?;
+    job.progress(0.6, Some("Disk".to_string()));
+    let (disk_write_mbps, disk_read_mbps) = disk(app, job)?;
+
+    // Held until the result is saved so a report cannot slip in between
+    let mut pending = PENDING_FRAMES.lock().expect("pending frames lock");
+    let id = job.id();
+    let frame_rate = pending
+        .iter()
+        .position(|(pending, _)| *pending == id)
+        .map(|index| pending.remove(index).1);
+    let mut result = BenchmarkResult {
+        id,
+        model: model(),
+        cpu_single_mbps,
+        cpu_multi_mbps,
+        threads,
+        memory_mbps,
+        disk_write_mbps,
+        disk_read_mbps,
+        frame_rate,
+        score: 0,
+        run_at: Local::now().timestamp(),
+    };
+    result.score = score(&result);
+    save_result(app, result.clone())?;
+    Ok(result)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start the benchmark as a job. `benchmark-started` asks the frontend to
+/// measure its frame rate and report it with `report_frame_rate`.
+#[tauri::command]
+pub fn run_benchmark(app: AppHandle, window: Window) -> String {
+    let worker = app.clone();
+    let id = jobs::spawn(&app, Some(window.label()), "benchmark", move |job| benchmark(&worker, job));
+    events::publish(&app, "benchmark-started", &id);
+    id
+}
+
+/// Attach the frontend's frame rate measurement to a benchmark. Reports for
+/// a run still in progress are held until it finishes.
+#[tauri::command]
+pub fn report_frame_rate(app: AppHandle, id: String, frame_rate: FrameRate) -> Result<(), KioskError> {
+    let mut pending = PENDING_FRAMES.lock().expect("pending frames lock");
+    let results: Vec<BenchmarkResult> = store::load(&app, RESULTS_FILE);
+    let Some(mut result) = results.into_iter().find(|result| result.id == id) else {
+        pending.retain(|(pending, _)| *pending != id);
+        pending.push((id, frame_rate));
+        // Reports for runs that never finish should not pile up
+        let excess = pending.len().saturating_sub(RESULTS_KEPT);
+        pending.drain(..excess);
+        return Ok(());
+    };
+    result.frame_rate = Some(frame_rate);
+    result.score = score(&result);
+    Ok(save_result(&app, result)?)
+}
+
+/// Saved benchmark results, oldest first
+#[tauri::command]
+pub fn list_benchmark_results(app: AppHandle) -> Vec<BenchmarkResult> {
+    store::load(&app, RESULTS_FILE)
+}
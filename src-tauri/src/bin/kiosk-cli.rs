@@ -0,0 +1,5 @@
+// Headless command line for scripting a kiosk over SSH; see `kiosk_lib::cli`
+
+fn main() {
+    std::process::exit(kiosk_lib::cli::main())
+}
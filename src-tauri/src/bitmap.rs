@@ -0,0 +1,968 @@
+//! Classic bitmap formats
+//!
+//! Reads and writes Windows BMP, ICO, CUR and ANI files, so Paint can open
+//! and save them and old icon and cursor packs can be imported. Images
+//! reach the frontend as PNG `data:` URLs with the palette and bit depth
+//! they were stored with.
+//!
+//! BMP files may be 1, 4 or 8-bit indexed (raw or RLE), 16, 24 or 32-bit,
+//! with any of the classic header versions. ICO and CUR entries may be
+//! bitmaps with a transparency mask or PNG. Pixels a mask marks to invert
+//! the screen, as in text cursors, are read as black. An ANI is a series
+//! of cursor frames with a display time for each step in jiffies (1/60 s).
+//!
+//! When writing, an indexed depth uses the image's own colours if they
+//! fit, or else the given palette or the standard Windows one, with each
+//! colour matched to the nearest entry. BMPs below 32 bits are flattened
+//! onto white; icons and cursors keep transparency in their mask.
+
+use base64::Engine;
+use image::{ImageFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::{badges, recents};
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+
+const MAX_FILE_BYTES: u64 = 16 * 1024 * 1024;
+
+const MAX_SIDE: u32 = 4096;
+
+/// Largest icon or cursor image; 256 is stored as 0
+const MAX_ICON_SIDE: u32 = 256;
+
+/// Step length of an animation that gives none, in jiffies
+const DEFAULT_JIFFIES: u32 = 10;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// ANI frames are icon or cursor files
+const AF_ICON: u32 = 1;
+/// ANI has a `seq ` chunk ordering its frames
+const AF_SEQUENCE: u32 = 2;
+
+/// The 16 colours of the standard Windows palette
+const VGA_COLORS: [[u8; 3]; 16] = [
+    [0x00, 0x00, 0x00],
+    [0x80, 0x00, 0x00],
+    [0x00, 0x80, 0x00],
+    [0x80, 0x80, 0x00],
+    [0x00, 0x00, 0x80],
+    [0x80, 0x00, 0x80],
+    [0x00, 0x80, 0x80],
+    [0xc0, 0xc0, 0xc0],
+    [0x80, 0x80, 0x80],
+    [0xff, 0x00, 0x00],
+    [0x00, 0xff, 0x00],
+    [0xff, 0xff, 0x00],
+    [0x00, 0x00, 0xff],
+    [0xff, 0x00, 0xff],
+    [0x00, 0xff, 0xff],
+    [0xff, 0xff, 0xff],
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BitmapFormat {
+    Bmp,
+    Ico,
+    Cur,
+    Ani,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Hotspot {
+    pub x: u16,
+    pub y: u16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BitmapImage {
+    pub width: u32,
+    pub height: u32,
+    /// Bits per pixel as stored
+    pub bits: u16,
+    /// "#rrggbb" colours of an indexed image
+    pub palette: Vec<String>,
+    pub hotspot: Option<Hotspot>,
+    /// PNG as a `data:` URL
+    pub data_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AniStep {
+    /// Index into the frames
+    pub frame: usize,
+    pub jiffies: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Animation {
+    /// Frames in the order shown; when writing, each frame once at the
+    /// default rate if empty
+    pub steps: Vec<AniStep>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BitmapFile {
+    pub format: BitmapFormat,
+    /// The BMP, each size of an icon, or each frame of an animation
+    pub images: Vec<BitmapImage>,
+    pub animation: Option<Animation>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitmapSource {
+    /// PNG or JPEG as a `data:` URL
+    pub data_url: String,
+    /// Cursor hotspot; the top-left corner when unset
+    #[serde(default)]
+    pub hotspot: Option<Hotspot>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BitmapWriteOptions {
+    /// Taken from the file extension when unset
+    pub format: Option<BitmapFormat>,
+    /// 1, 4, 8, 24 or 32; the fewest that keep every colour when unset
+    pub bits: Option<u16>,
+    /// "#rrggbb" colours for an indexed image
+    pub palette: Vec<String>,
+    /// Steps and details of an ANI
+    pub animation: Option<Animation>,
+}
+
+/// A decoded image
+pub(crate) struct Bitmap {
+    pub(crate) image: RgbaImage,
+    pub(crate) bits: u16,
+    pub(crate) palette: Vec<[u8; 3]>,
+    pub(crate) hotspot: Option<Hotspot>,
+}
+
+// ============================================================================
+// Reading
+// ============================================================================
+
+fn u16_at(data: &[u8], at: usize) -> Result<u16, String> {
+    data.get(at..at + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| "The file is cut short".to_string())
+}
+
+fn u32_at(data: &[u8], at: usize) -> Result<u32, String> {
+    data.get(at..at + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| "The file is cut short".to_string())
+}
+
+/// Scale the bits of `value` under `mask` to 0-255
+fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = (mask >> shift) as u64;
+    let value = ((value & mask) >> shift) as u64;
+    ((value * 255 + max / 2) / max) as u8
+}
+
+/// Expand RLE8 or RLE4 data to one palette index per pixel, in stored row
+/// order
+fn decode_rle(data: &[u8], width: usize, height: usize, four_bit: bool) -> Vec<u8> {
+    let mut indices = vec![0u8; width * height];
+    let mut set = |x: usize, y: usize, value: u8| {
+        if x < width && y < height {
+            indices[y * width + x] = value;
+        }
+    };
+    let nibble = |byte: u8, n: usize| if n % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+    let (mut x, mut y, mut at) = (0usize, 0usize, 0usize);
+    while at + 1 < data.len() {
+        let (count, value) = (data[at] as usize, data[at + 1]);
+        at += 2;
+        if count > 0 {
+            for n in 0..count {
+                set(x, y, if four_bit { nibble(value, n) } else { value });
+                x += 1;
+            }
+            continue;
+        }
+        match value {
+            0 => {
+                x = 0;
+                y += 1;
+            }
+            1 => break,
+            2 => {
+                let (Some(&dx), Some(&dy)) = (data.get(at), data.get(at + 1)) else {
+                    break;
+                };
+                x += dx as usize;
+                y += dy as usize;
+                at += 2;
+            }
+            count => {
+                let count = count as usize;
+                for n in 0..count {
+                    let byte = data.get(at + if four_bit { n / 2 } else { n }).copied().unwrap_or(0);
+                    set(x, y, if four_bit { nibble(byte, n) } else { byte });
+                    x += 1;
+                }
+                let bytes = if four_bit { (count + 1) / 2 } else { count };
+                // Absolute runs are padded to a whole word
+                at += bytes + bytes % 2;
+            }
+        }
+    }
+    indices
+}
+
+/// Read a device-independent bitmap: the header, palette and pixels. In
+/// icons the height covers the image and its mask, and the mask follows
+/// the pixels. `pixels_at` is where a BMP's file header puts the pixels.
+fn read_dib(data: &[u8], pixels_at: Option<usize>, icon: bool) -> Result<Bitmap, String> {
+    let header = u32_at(data, 0)? as usize;
+    let core = header == 12;
+    let (width, height, bits, compression, colors_used) = if core {
+        (u16_at(data, 4)? as i64, u16_at(data, 6)? as i64, u16_at(data, 10)?, BI_RGB, 0)
+    } else if header >= 40 {
+        (
+            u32_at(data, 4)? as i32 as i64,
+            u32_at(data, 8)? as i32 as i64,
+            u16_at(data, 14)?,
+            u32_at(data, 16)?,
+            u32_at(data, 32)? as usize,
+        )
+    } else {
+        return Err(format!("Unsupported bitmap header of {} bytes", header));
+    };
+    let height = if icon { height / 2 } else { height };
+    let top_down = height < 0;
+    let (width, height) = (width.unsigned_abs() as u32, height.unsigned_abs() as u32);
+    if width == 0 || height == 0 || width > MAX_SIDE || height > MAX_SIDE {
+        return Err(format!("Unsupported bitmap size {} x {}", width, height));
+    }
+
+    let mut at = header;
+    let masks = match compression {
+        BI_BITFIELDS | BI_ALPHABITFIELDS => {
+            // Older headers are followed by the masks, newer ones hold them
+            if header < 52 {
+                at += if compression == BI_ALPHABITFIELDS { 16 } else { 12 };
+            }
+            let alpha = if compression == BI_ALPHABITFIELDS || header >= 56 {
+                u32_at(data, 52)?
+            } else {
+                0
+            };
+            [u32_at(data, 40)?, u32_at(data, 44)?, u32_at(data, 48)?, alpha]
+        }
+        _ if bits == 16 => [0x7c00, 0x03e0, 0x001f, 0],
+        _ => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0xff00_0000],
+    };
+
+    let entry = if core { 3 } else { 4 };
+    let palette: Vec<[u8; 3]> = if bits <= 8 {
+        let count = if colors_used > 0 { colors_used.min(256) } else { 1 << bits };
+        let palette = (0..count)
+            .map(|i| {
+                let bgr = data.get(at + i * entry..at + i * entry + 3).ok_or("The palette is cut short")?;
+                Ok([bgr[2], bgr[1], bgr[0]])
+            })
+            .collect::<Result<_, String>>()?;
+        at += count * entry;
+        palette
+    } else {
+        // A palette may still be given as a hint for display
+        at += colors_used * entry;
+        Vec::new()
+    };
+    let start = pixels_at.unwrap_or(at);
+    let pixels = data.get(start..).ok_or("The pixel data is missing")?;
+
+    let (w, h) = (width as usize, height as usize);
+    let stride = (w * bits as usize + 31) / 32 * 4;
+    let row_bytes = (w * bits as usize + 7) / 8;
+    let row = |stored: usize| -> Result<&[u8], String> {
+        pixels
+            .get(stored * stride..stored * stride + row_bytes)
+            .ok_or_else(|| "The pixel data is cut short".to_string())
+    };
+    let image_row = |stored: usize| if top_down { stored } else { h - 1 - stored } as u32;
+
+    let mut image = RgbaImage::new(width, height);
+    match (compression, bits) {
+        (BI_RGB | BI_RLE8 | BI_RLE4, 1 | 4 | 8) => {
+            let indices = match compression {
+                BI_RLE8 if bits == 8 => decode_rle(pixels, w, h, false),
+                BI_RLE4 if bits == 4 => decode_rle(pixels, w, h, true),
+                BI_RGB => {
+                    let mut indices = Vec::with_capacity(w * h);
+                    for stored in 0..h {
+                        let line = row(stored)?;
+                        indices.extend((0..w).map(|x| match bits {
+                            1 => (line[x / 8] >> (7 - x % 8)) & 1,
+                            4 => (line[x / 2] >> if x % 2 == 0 { 4 } else { 0 }) & 0x0f,
+                            _ => line[x],
+                        }));
+                    }
+                    indices
+                }
+                _ => return Err(format!("Unsupported {}-bit compression {}", bits, compression)),
+            };
+            for (i, &index) in indices.iter().enumerate() {
+                let [r, g, b] = palette.get(index as usize).copied().unwrap_or([0, 0, 0]);
+                image.put_pixel((i % w) as u32, image_row(i / w), Rgba([r, g, b, 255]));
+            }
+        }
+        (BI_RGB, 24) => {
+            for stored in 0..h {
+                let line = row(stored)?;
+                for x in 0..w {
+                    let bgr = &line[x * 3..x * 3 + 3];
+                    image.put_pixel(x as u32, image_row(stored), Rgba([bgr[2], bgr[1], bgr[0], 255]));
+                }
+            }
+        }
+        (BI_RGB | BI_BITFIELDS | BI_ALPHABITFIELDS, 16 | 32) => {
+            let size = bits as usize / 8;
+            for stored in 0..h {
+                let line = row(stored)?;
+                for x in 0..w {
+                    let mut bytes = [0u8; 4];
+                    bytes[..size].copy_from_slice(&line[x * size..x * size + size]);
+                    let value = u32::from_le_bytes(bytes);
+                    let alpha = if masks[3] == 0 { 255 } else { channel(value, masks[3]) };
+                    let pixel = Rgba([
+                        channel(value, masks[0]),
+                        channel(value, masks[1]),
+                        channel(value, masks[2]),
+                        alpha,
+                    ]);
+                    image.put_pixel(x as u32, image_row(stored), pixel);
+                }
+            }
+            // Plain 32-bit bitmaps often leave the alpha byte at zero
+            if masks[3] != 0 && image.pixels().all(|pixel| pixel[3] == 0) {
+                image.pixels_mut().for_each(|pixel| pixel[3] = 255);
+            }
+        }
+        _ => return Err(format!("Unsupported {}-bit bitmap with compression {}", bits, compression)),
+    }
+
+    // The mask marks transparent pixels; over a coloured pixel it inverts
+    // the screen instead, which is shown as black
+    let has_alpha = bits == 32 && image.pixels().any(|pixel| pixel[3] != 255);
+    if icon && !has_alpha {
+        let mask_stride = (w + 31) / 32 * 4;
+        let mask_at = stride * h;
+        for stored in 0..h {
+            let Some(line) = pixels.get(mask_at + stored * mask_stride..mask_at + (stored + 1) * mask_stride) else {
+                break;
+            };
+            for x in 0..w {
+                if (line[x / 8] >> (7 - x % 8)) & 1 == 1 {
+                    let pixel = image.get_pixel_mut(x as u32, image_row(stored));
+                    let inverts = pixel[0] != 0 || pixel[1] != 0 || pixel[2] != 0;
+                    *pixel = if inverts { Rgba([0, 0, 0, 255]) } else { Rgba([0, 0, 0, 0]) };
+                }
+            }
+        }
+    }
+    Ok(Bitmap {
+        image,
+        bits,
+        palette,
+        hotspot: None,
+    })
+}
+
+fn read_bmp(data: &[u8]) -> Result<Bitmap, String> {
+    if !data.starts_with(b"BM") {
+        return Err("Not a BMP file".into());
+    }
+    let offset = u32_at(data, 10)? as usize;
+    let dib = data.get(14..).ok_or("The file is cut short")?;
+    read_dib(dib, offset.checked_sub(14), false)
+}
+
+/// Read every image of an ICO or CUR file, with hotspots for cursors
+pub(crate) fn read_icon(data: &[u8]) -> Result<Vec<Bitmap>, String> {
+    let kind = u16_at(data, 2)?;
+    if u16_at(data, 0)? != 0 || !(kind == 1 || kind == 2) {
+        return Err("Not an icon or cursor file".into());
+    }
+    let count = u16_at(data, 4)? as usize;
+    let mut images = Vec::with_capacity(count);
+    for index in 0..count {
+        let entry = 6 + index * 16;
+        let size = u32_at(data, entry + 8)? as usize;
+        let offset = u32_at(data, entry + 12)? as usize;
+        let bytes = data
+            .get(offset..offset.saturating_add(size))
+            .ok_or("An icon image is cut short")?;
+        let mut bitmap = if bytes.starts_with(PNG_SIGNATURE) {
+            let image = image::load_from_memory_with_format(bytes, ImageFormat::Png)
+                .map_err(|e| format!("Invalid PNG icon image: {}", e))?
+                .to_rgba8();
+            Bitmap {
+                image,
+                bits: 32,
+                palette: Vec::new(),
+                hotspot: None,
+            }
+        } else {
+            read_dib(bytes, None, true)?
+        };
+        if kind == 2 {
+            bitmap.hotspot = Some(Hotspot {
+                x: u16_at(data, entry + 4)?,
+                y: u16_at(data, entry + 6)?,
+            });
+        }
+        images.push(bitmap);
+    }
+    if images.is_empty() {
+        return Err("The file has no images".into());
+    }
+    Ok(images)
+}
+
+/// RIFF chunks in `data` as (id, body) pairs
+fn riff_chunks(data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut chunks = Vec::new();
+    let mut at = 0;
+    while at + 8 <= data.len() {
+        let size = u32::from_le_bytes([data[at + 4], data[at + 5], data[at + 6], data[at + 7]]) as usize;
+        let end = (at + 8).saturating_add(size).min(data.len());
+        chunks.push((&data[at..at + 4], &data[at + 8..end]));
+        at = end + size % 2;
+    }
+    chunks
+}
+
+fn riff_text(body: &[u8]) -> String {
+    String::from_utf8_lossy(body.split(|&byte| byte == 0).next().unwrap_or_default())
+        .trim()
+        .to_string()
+}
+
+fn read_ani(data: &[u8]) -> Result<(Vec<Bitmap>, Animation), String> {
+    if !data.starts_with(b"RIFF") || data.get(8..12) != Some(b"ACON".as_slice()) {
+        return Err("Not an animated cursor".into());
+    }
+    let mut frames = Vec::new();
+    let (mut rates, mut sequence) = (Vec::new(), Vec::new());
+    let (mut default_jiffies, mut flags) = (DEFAULT_JIFFIES, AF_ICON);
+    let mut animation = Animation::default();
+    let words = |body: &[u8]| body.chunks_exact(4).map(|word| u32_at(word, 0).unwrap_or(0)).collect::<Vec<_>>();
+
+    for (id, body) in riff_chunks(&data[12..]) {
+        match id {
+            b"anih" => {
+                let jiffies = u32_at(body, 28)?;
+                if jiffies > 0 {
+                    default_jiffies = jiffies;
+                }
+                flags = u32_at(body, 32)?;
+            }
+            b"rate" => rates = words(body),
+            b"seq " => sequence = words(body),
+            b"LIST" if body.starts_with(b"fram") => {
+                if flags & AF_ICON == 0 {
+                    return Err("Animated cursors with raw bitmap frames are not supported".into());
+                }
+                for (id, frame) in riff_chunks(&body[4..]) {
+                    if id == b"icon" {
+                        frames.push(read_icon(frame)?.remove(0));
+                    }
+                }
+            }
+            b"LIST" if body.starts_with(b"INFO") => {
+                for (id, text) in riff_chunks(&body[4..]) {
+                    match id {
+                        b"INAM" => animation.title = Some(riff_text(text)),
+                        b"IART" => animation.author = Some(riff_text(text)),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if frames.is_empty() {
+        return Err("The animation has no frames".into());
+    }
+    let steps = if flags & AF_SEQUENCE != 0 && !sequence.is_empty() {
+        sequence.len()
+    } else {
+        frames.len()
+    };
+    animation.steps = (0..steps)
+        .map(|step| AniStep {
+            frame: sequence.get(step).map_or(step, |&frame| frame as usize).min(frames.len() - 1),
+            jiffies: rates.get(step).copied().filter(|&rate| rate > 0).unwrap_or(default_jiffies),
+        })
+        .collect();
+    Ok((frames, animation))
+}
+
+// ============================================================================
+// Writing
+// ============================================================================
+
+/// The standard Windows palette for a depth: black and white, the 16
+/// colours, or those with a 6 x 6 x 6 colour cube and greys
+fn standard_palette(bits: u16) -> Vec<[u8; 3]> {
+    match bits {
+        1 => vec![[0, 0, 0], [0xff, 0xff, 0xff]],
+        4 => VGA_COLORS.to_vec(),
+        _ => {
+            let mut palette = VGA_COLORS.to_vec();
+            let levels = [0x00, 0x33, 0x66, 0x99, 0xcc, 0xff];
+            for r in levels {
+                for g in levels {
+                    for b in levels {
+                        palette.push([r, g, b]);
+                    }
+                }
+            }
+            palette.extend((1..=24).map(|step| [(step * 10) as u8; 3]));
+            palette
+        }
+    }
+}
+
+/// Colours of the visible pixels, in the order first seen, up to `limit`
+/// and one over
+fn colors_of(image: &RgbaImage, limit: usize) -> Vec<[u8; 3]> {
+    let mut colors = Vec::new();
+    for pixel in image.pixels().filter(|pixel| pixel[3] >= 128) {
+        let color = [pixel[0], pixel[1], pixel[2]];
+        if !colors.contains(&color) {
+            colors.push(color);
+            if colors.len() > limit {
+                break;
+            }
+        }
+    }
+    colors
+}
+
+/// The fewest bits that keep every colour, using a mask for icons and
+/// alpha when some pixel is partly transparent
+fn auto_bits(image: &RgbaImage, icon: bool) -> u16 {
+    let partial = image.pixels().any(|pixel| pixel[3] != 255 && (pixel[3] != 0 || !icon));
+    if partial {
+        return 32;
+    }
+    match colors_of(image, 256).len() {
+        0..=2 => 1,
+        3..=16 => 4,
+        17..=256 => 8,
+        _ => 24,
+    }
+}
+
+fn nearest(palette: &[[u8; 3]], color: [u8; 3]) -> u8 {
+    let distance = |entry: &[u8; 3]| {
+        (0..3)
+            .map(|i| (entry[i] as i32 - color[i] as i32).pow(2))
+            .sum::<i32>()
+    };
+    (0..palette.len()).min_by_key(|&i| distance(&palette[i])).unwrap_or(0) as u8
+}
+
+/// Composite onto white, for formats without transparency
+fn flatten(image: &RgbaImage) -> RgbaImage {
+    let mut flat = image.clone();
+    for pixel in flat.pixels_mut() {
+        let alpha = pixel[3] as u32;
+        for i in 0..3 {
+            pixel[i] = ((pixel[i] as u32 * alpha + 255 * (255 - alpha) + 127) / 255) as u8;
+        }
+        pixel[3] = 255;
+    }
+    flat
+}
+
+/// A device-independent bitmap of `image`, with a mask after the pixels for
+/// icons
+fn write_dib(image: &RgbaImage, bits: u16, given: &[[u8; 3]], icon: bool) -> Vec<u8> {
+    let (w, h) = (image.width() as usize, image.height() as usize);
+    let palette = if bits > 8 {
+        Vec::new()
+    } else if !given.is_empty() {
+        given.iter().copied().take(1 << bits).collect()
+    } else {
+        let colors = colors_of(image, 1 << bits);
+        if colors.len() <= 1 << bits {
+            colors
+        } else {
+            standard_palette(bits)
+        }
+    };
+    let stride = (w * bits as usize + 31) / 32 * 4;
+    let mask_stride = (w + 31) / 32 * 4;
+    let mask_size = if icon { mask_stride * h } else { 0 };
+
+    let mut out = Vec::with_capacity(40 + palette.len() * 4 + stride * h + mask_size);
+    out.extend(40u32.to_le_bytes());
+    out.extend((w as u32).to_le_bytes());
+    out.extend((if icon { h * 2 } else { h } as u32).to_le_bytes());
+    out.extend(1u16.to_le_bytes());
+    out.extend(bits.to_le_bytes());
+    out.extend(BI_RGB.to_le_bytes());
+    out.extend(((stride * h + mask_size) as u32).to_le_bytes());
+    // 72 dpi
+    out.extend(2835u32.to_le_bytes());
+    out.extend(2835u32.to_le_bytes());
+    out.extend((palette.len() as u32).to_le_bytes());
+    out.extend(0u32.to_le_bytes());
+    for [r, g, b] in &palette {
+        out.extend([*b, *g, *r, 0]);
+    }
+
+    let mut lookup: HashMap<[u8; 3], u8> = HashMap::new();
+    for y in (0..h).rev() {
+        let mut line = vec![0u8; stride];
+        for x in 0..w {
+            let pixel = image.get_pixel(x as u32, y as u32);
+            // Masked pixels are black so the mask alone decides them
+            let color = if icon && pixel[3] < 128 && bits != 32 {
+                [0, 0, 0]
+            } else {
+                [pixel[0], pixel[1], pixel[2]]
+            };
+            match bits {
+                1 | 4 | 8 => {
+                    let index = *lookup.entry(color).or_insert_with(|| nearest(&palette, color));
+                    match bits {
+                        1 => line[x / 8] |= index << (7 - x % 8),
+                        4 => line[x / 2] |= index << if x % 2 == 0 { 4 } else { 0 },
+                        _ => line[x] = index,
+                    }
+                }
+                24 => line[x * 3..x * 3 + 3].copy_from_slice(&[color[2], color[1], color[0]]),
+                _ => line[x * 4..x * 4 + 4].copy_from_slice(&[color[2], color[1], color[0], pixel[3]]),
+            }
+        }
+        out.extend(line);
+    }
+    if icon {
+        for y in (0..h).rev() {
+            let mut line = vec![0u8; mask_stride];
+            for x in 0..w {
+                if image.get_pixel(x as u32, y as u32)[3] < 128 {
+                    line[x / 8] |= 1 << (7 - x % 8);
+                }
+            }
+            out.extend(line);
+        }
+    }
+    out
+}
+
+fn write_bmp(image: &RgbaImage, bits: Option<u16>, palette: &[[u8; 3]]) -> Vec<u8> {
+    let bits = bits.unwrap_or_else(|| auto_bits(image, false));
+    let flat;
+    let image = if bits == 32 {
+        image
+    } else {
+        flat = flatten(image);
+        &flat
+    };
+    let dib = write_dib(image, bits, palette, false);
+    let colors = u32_at(&dib, 32).unwrap_or(0);
+    let mut out = Vec::with_capacity(14 + dib.len());
+    out.extend(b"BM");
+    out.extend(((14 + dib.len()) as u32).to_le_bytes());
+    out.extend([0u8; 4]);
+    out.extend((14 + 40 + colors * 4).to_le_bytes());
+    out.extend(dib);
+    out
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+/// An ICO or CUR file holding each image; 256-pixel images are stored as PNG
+fn write_icon(
+    images: &[(RgbaImage, Option<Hotspot>)],
+    bits: Option<u16>,
+    palette: &[[u8; 3]],
+    cursor: bool,
+) -> Result<Vec<u8>, String> {
+    let mut entries = Vec::new();
+    let mut bodies: Vec<Vec<u8>> = Vec::new();
+    let mut offset = 6 + 16 * images.len();
+    for (image, hotspot) in images {
+        let (width, height) = image.dimensions();
+        if width > MAX_ICON_SIDE || height > MAX_ICON_SIDE {
+            return Err(format!("Icons are at most {0} x {0} pixels", MAX_ICON_SIDE));
+        }
+        let large = width == MAX_ICON_SIDE || height == MAX_ICON_SIDE;
+        let bits = if large { 32 } else { bits.unwrap_or_else(|| auto_bits(image, true)) };
+        let body = if large {
+            encode_png(image)?
+        } else {
+            write_dib(image, bits, palette, true)
+        };
+        let hotspot = hotspot.unwrap_or_default();
+        entries.push((width % 256) as u8);
+        entries.push((height % 256) as u8);
+        entries.push(if bits < 8 { 1u8 << bits } else { 0 });
+        entries.push(0);
+        if cursor {
+            entries.extend(hotspot.x.to_le_bytes());
+            entries.extend(hotspot.y.to_le_bytes());
+        } else {
+            entries.extend(1u16.to_le_bytes());
+            entries.extend(bits.to_le_bytes());
+        }
+        entries.extend((body.len() as u32).to_le_bytes());
+        entries.extend((offset as u32).to_le_bytes());
+        offset += body.len();
+        bodies.push(body);
+    }
+    let mut out = Vec::with_capacity(offset);
+    out.extend(0u16.to_le_bytes());
+    out.extend((if cursor { 2u16 } else { 1 }).to_le_bytes());
+    out.extend((images.len() as u16).to_le_bytes());
+    out.extend(entries);
+    bodies.into_iter().for_each(|body| out.extend(body));
+    Ok(out)
+}
+
+fn riff_chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend(id);
+    out.extend((body.len() as u32).to_le_bytes());
+    out.extend(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+/// An animated cursor with one single-image cursor per frame
+fn write_ani(
+    frames: &[(RgbaImage, Option<Hotspot>)],
+    bits: Option<u16>,
+    palette: &[[u8; 3]],
+    animation: &Animation,
+) -> Result<Vec<u8>, String> {
+    if let Some(step) = animation.steps.iter().find(|step| step.frame >= frames.len()) {
+        return Err(format!("Step shows frame {} of {}", step.frame, frames.len()));
+    }
+    let sequenced = !animation.steps.is_empty();
+    let steps = if sequenced { animation.steps.len() } else { frames.len() };
+
+    let mut body = b"ACON".to_vec();
+    let mut info = b"INFO".to_vec();
+    for (id, text) in [(b"INAM", &animation.title), (b"IART", &animation.author)] {
+        if let Some(text) = text {
+            riff_chunk(&mut info, id, format!("{}\0", text).as_bytes());
+        }
+    }
+    if info.len() > 4 {
+        riff_chunk(&mut body, b"LIST", &info);
+    }
+
+    let mut header = Vec::with_capacity(36);
+    header.extend(36u32.to_le_bytes());
+    header.extend((frames.len() as u32).to_le_bytes());
+    header.extend((steps as u32).to_le_bytes());
+    // Size, depth and planes only apply to raw bitmap frames
+    header.extend([0u8; 16]);
+    header.extend(DEFAULT_JIFFIES.to_le_bytes());
+    header.extend((AF_ICON | if sequenced { AF_SEQUENCE } else { 0 }).to_le_bytes());
+    riff_chunk(&mut body, b"anih", &header);
+    if sequenced {
+        let rates: Vec<u8> = animation.steps.iter().flat_map(|step| step.jiffies.to_le_bytes()).collect();
+        riff_chunk(&mut body, b"rate", &rates);
+        let sequence: Vec<u8> = animation
+            .steps
+            .iter()
+            .flat_map(|step| (step.frame as u32).to_le_bytes())
+            .collect();
+        riff_chunk(&mut body, b"seq ", &sequence);
+    }
+
+    let mut list = b"fram".to_vec();
+    for frame in frames {
+        riff_chunk(&mut list, b"icon", &write_icon(std::slice::from_ref(frame), bits, palette, true)?);
+    }
+    riff_chunk(&mut body, b"LIST", &list);
+
+    let mut out = Vec::with_capacity(body.len() + 8);
+    riff_chunk(&mut out, b"RIFF", &body);
+    Ok(out)
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn format_of(path: &str, format: Option<BitmapFormat>) -> Result<BitmapFormat, KioskError> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("bmp") | Some("dib") => Ok(BitmapFormat::Bmp),
+        Some("ico") => Ok(BitmapFormat::Ico),
+        Some("cur") => Ok(BitmapFormat::Cur),
+        Some("ani") => Ok(BitmapFormat::Ani),
+        _ => Err(KioskError::invalid(format!("Unknown bitmap file type: {}", path))),
+    }
+}
+
+/// The format a file's contents are in, whatever its name
+fn sniff(data: &[u8]) -> Option<BitmapFormat> {
+    match data.get(..4)? {
+        [b'B', b'M', ..] => Some(BitmapFormat::Bmp),
+        [0, 0, 1, 0] => Some(BitmapFormat::Ico),
+        [0, 0, 2, 0] => Some(BitmapFormat::Cur),
+        b"RIFF" => Some(BitmapFormat::Ani),
+        _ => None,
+    }
+}
+
+/// Decode a BMP, ICO, CUR or ANI file
+pub(crate) fn decode(data: &[u8]) -> Result<(BitmapFormat, Vec<Bitmap>, Option<Animation>), String> {
+    let format = sniff(data).ok_or("Not a BMP, ICO, CUR or ANI file")?;
+    Ok(match format {
+        BitmapFormat::Bmp => (format, vec![read_bmp(data)?], None),
+        BitmapFormat::Ico | BitmapFormat::Cur => (format, read_icon(data)?, None),
+        BitmapFormat::Ani => {
+            let (frames, animation) = read_ani(data)?;
+            (format, frames, Some(animation))
+        }
+    })
+}
+
+fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn to_view(bitmap: Bitmap) -> Result<BitmapImage, String> {
+    let png = encode_png(&bitmap.image)?;
+    Ok(BitmapImage {
+        width: bitmap.image.width(),
+        height: bitmap.image.height(),
+        bits: bitmap.bits,
+        palette: bitmap.palette.into_iter().map(hex).collect(),
+        hotspot: bitmap.hotspot,
+        data_url: format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png)),
+    })
+}
+
+fn load_source(source: &BitmapSource) -> Result<(RgbaImage, Option<Hotspot>), String> {
+    let (_, encoded) = source
+        .data_url
+        .strip_prefix("data:")
+        .and_then(|data| data.split_once(','))
+        .ok_or("Images are given as data URLs")?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| e.to_string())?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Invalid image: {}", e))?
+        .to_rgba8();
+    if image.width() > MAX_SIDE || image.height() > MAX_SIDE {
+        return Err(format!("Images are at most {0} x {0} pixels", MAX_SIDE));
+    }
+    Ok((image, source.hotspot))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Read a BMP, ICO, CUR or ANI file at a virtual path
+#[tauri::command]
+pub fn read_bitmap_file(app: AppHandle, path: String) -> Result<BitmapFile, KioskError> {
+    let real = vfs::resolve(&app, &path, Access::Read)?;
+    if fs::metadata(&real)?.len() > MAX_FILE_BYTES {
+        return Err(KioskError::invalid(format!(
+            "The file is over the {} MB limit",
+            MAX_FILE_BYTES / (1024 * 1024)
+        )));
+    }
+    let (format, bitmaps, animation) = decode(&fs::read(&real)?).map_err(KioskError::invalid)?;
+    let images = bitmaps.into_iter().map(to_view).collect::<Result<_, _>>()?;
+    let _ = recents::record(&app, "paint", &path);
+    Ok(BitmapFile {
+        format,
+        images,
+        animation,
+    })
+}
+
+/// Write images to a virtual path as a BMP (the first image), an icon or
+/// cursor with every image as a size, or an animated cursor with every
+/// image as a frame; returns the bytes written
+#[tauri::command]
+pub fn write_bitmap_file(
+    app: AppHandle,
+    path: String,
+    images: Vec<BitmapSource>,
+    options: Option<BitmapWriteOptions>,
+) -> Result<u64, KioskError> {
+    let options = options.unwrap_or_default();
+    let format = format_of(&path, options.format)?;
+    if let Some(bits) = options.bits.filter(|bits| ![1, 4, 8, 24, 32].contains(bits)) {
+        return Err(KioskError::invalid(format!("Unsupported bit depth: {}", bits)));
+    }
+    let palette = options
+        .palette
+        .iter()
+        .map(|color| badges::parse_color(color).map(|color| [color[0], color[1], color[2]]))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(KioskError::invalid)?;
+    let images = images
+        .iter()
+        .map(load_source)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(KioskError::invalid)?;
+    let Some((first, _)) = images.first() else {
+        return Err(KioskError::invalid("There are no images to write"));
+    };
+
+    let bytes = match format {
+        BitmapFormat::Bmp => write_bmp(first, options.bits, &palette),
+        BitmapFormat::Ico => write_icon(&images, options.bits, &palette, false)?,
+        BitmapFormat::Cur => write_icon(&images, options.bits, &palette, true)?,
+        BitmapFormat::Ani => write_ani(&images, options.bits, &palette, &options.animation.unwrap_or_default())?,
+    };
+    fs::write(vfs::resolve_write(&app, &path, bytes.len() as u64)?, &bytes)?;
+    let _ = recents::record(&app, "paint", &path);
+    Ok(bytes.len() as u64)
+}
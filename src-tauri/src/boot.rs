@@ -0,0 +1,213 @@
+//! Startup profiling
+//!
+//! `mark` records named points during startup (backend init, plugins, state
+//! loading, page load) in milliseconds since the process was exec'd, and the
+//! frontend closes the timeline with `report_first_paint`. Each boot's total
+//! is kept in `boot.json`; a boot over the budget, or well over the recent
+//! median, is flagged and published as `boot-regression`.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::{events, store};
+
+const BOOT_FILE: &str = "boot.json";
+
+/// Boots kept for the median
+const HISTORY_KEPT: usize = 30;
+
+/// A boot this much slower than the median counts as a regression
+const REGRESSION_FACTOR: f64 = 1.25;
+
+/// ...unless it is only slower by less than this
+const REGRESSION_MIN_MS: f64 = 250.0;
+
+const FIRST_PAINT: &str = "first-paint";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BootMark {
+    pub name: String,
+    /// Milliseconds since the process started
+    pub at_ms: f64,
+    /// Milliseconds since the previous mark
+    pub delta_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootRecord {
+    pub time: i64,
+    pub total_ms: f64,
+    #[serde(default)]
+    pub regression: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootConfig {
+    /// Target for process start to first paint
+    pub budget_ms: f64,
+    #[serde(default)]
+    pub history: Vec<BootRecord>,
+}
+
+impl Default for BootConfig {
+    fn default() -> Self {
+        BootConfig {
+            budget_ms: 5000.0,
+            history: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BootTimeline {
+    pub marks: Vec<BootMark>,
+    /// Start to first paint, once the frontend has reported it
+    pub total_ms: Option<f64>,
+    pub budget_ms: f64,
+    pub median_ms: Option<f64>,
+    pub history: Vec<BootRecord>,
+}
+
+/// When the first mark was taken, and how long the process had been running by then
+static START: OnceLock<(Instant, f64)> = OnceLock::new();
+
+static MARKS: Mutex<Vec<BootMark>> = Mutex::new(Vec::new());
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Time since exec from /proc, covering the dynamic loader and runtime startup
+fn process_age_ms() -> Option<f64> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the parenthesised command name start at field 3; starttime is field 22
+    let start_ticks: f64 = stat.rsplit_once(')')?.1.split_whitespace().nth(19)?.parse().ok()?;
+    let uptime: f64 = fs::read_to_string("/proc/uptime").ok()?.split_whitespace().next()?.parse().ok()?;
+    // SAFETY: sysconf has no memory safety requirements
+    let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) } as f64;
+    if ticks_per_second <= 0.0 {
+        return None;
+    }
+    Some(((uptime - start_ticks / ticks_per_second) * 1000.0).max(0.0))
+}
+
+fn push_mark(marks: &mut Vec<BootMark>, name: &str) {
+    let (start, offset) = START.get_or_init(|| (Instant::now(), process_age_ms().unwrap_or(0.0)));
+    let at_ms = offset + start.elapsed().as_secs_f64() * 1000.0;
+    let delta_ms = at_ms - marks.last().map_or(0.0, |last| last.at_ms);
+    marks.push(BootMark {
+        name: name.to_string(),
+        at_ms,
+        delta_ms,
+    });
+}
+
+/// Record a named point in the startup timeline
+pub fn mark(name: &str) {
+    push_mark(&mut MARKS.lock().expect("boot marks lock"), name);
+}
+
+/// Record a point only the first time it is reached (page reloads repeat
+/// some), returning its time if this was the first
+pub fn mark_once(name: &str) -> Option<f64> {
+    let mut marks = MARKS.lock().expect("boot marks lock");
+    if marks.iter().any(|mark| mark.name == name) {
+        return None;
+    }
+    push_mark(&mut marks, name);
+    marks.last().map(|mark| mark.at_ms)
+}
+
+fn median(history: &[BootRecord]) -> Option<f64> {
+    let mut totals: Vec<f64> = history.iter().map(|record| record.total_ms).collect();
+    if totals.is_empty() {
+        return None;
+    }
+    totals.sort_by(f64::total_cmp);
+    Some(totals[totals.len() / 2])
+}
+
+fn regression(config: &BootConfig, total_ms: f64) -> Option<String> {
+    if total_ms > config.budget_ms {
+        return Some(format!("{:.0} ms is over the {:.0} ms budget", total_ms, config.budget_ms));
+    }
+    let median = median(&config.history)?;
+    if total_ms > median * REGRESSION_FACTOR && total_ms - median > REGRESSION_MIN_MS {
+        return Some(format!("{:.0} ms against a recent median of {:.0} ms", total_ms, median));
+    }
+    None
+}
+
+fn first_paint_ms() -> Option<f64> {
+    let marks = MARKS.lock().expect("boot marks lock");
+    marks.iter().find(|mark| mark.name == FIRST_PAINT).map(|mark| mark.at_ms)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Called by the frontend once the desktop has painted; only the first
+/// report of a process counts
+#[tauri::command]
+pub fn report_first_paint(app: AppHandle) -> Result<(), KioskError> {
+    let Some(total_ms) = mark_once(FIRST_PAINT) else {
+        return Ok(());
+    };
+
+    let mut config: BootConfig = store::load(&app, BOOT_FILE);
+    let regression = regression(&config, total_ms);
+    if let Some(reason) = &regression {
+        events::publish(
+            &app,
+            "boot-regression",
+            serde_json::json!({
+                "total_ms": total_ms,
+                "budget_ms": config.budget_ms,
+                "median_ms": median(&config.history),
+                "reason": reason,
+            }),
+        );
+    }
+    config.history.push(BootRecord {
+        time: Local::now().timestamp(),
+        total_ms,
+        regression,
+    });
+    let excess = config.history.len().saturating_sub(HISTORY_KEPT);
+    config.history.drain(..excess);
+    Ok(store::save(&app, BOOT_FILE, &config)?)
+}
+
+/// This boot's marks with recent boot totals
+#[tauri::command]
+pub fn get_boot_timeline(app: AppHandle) -> BootTimeline {
+    let config: BootConfig = store::load(&app, BOOT_FILE);
+    BootTimeline {
+        marks: MARKS.lock().expect("boot marks lock").clone(),
+        total_ms: first_paint_ms(),
+        budget_ms: config.budget_ms,
+        median_ms: median(&config.history),
+        history: config.history,
+    }
+}
+
+/// Set the start-to-first-paint budget
+#[tauri::command]
+pub fn set_boot_budget(app: AppHandle, budget_ms: f64) -> Result<(), KioskError> {
+    if !budget_ms.is_finite() || budget_ms <= 0.0 {
+        return Err(KioskError::invalid("Budget must be a positive number of milliseconds"));
+    }
+    let mut config: BootConfig = store::load(&app, BOOT_FILE);
+    config.budget_ms = budget_ms;
+    Ok(store::save(&app, BOOT_FILE, &config)?)
+}
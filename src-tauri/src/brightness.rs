@@ -0,0 +1,376 @@
+//! Ambient light and auto-brightness
+//!
+//! Reads an ambient light sensor, either through the IIO subsystem or a
+//! BH1750 on an I2C bus, and sets the panel backlight from a configurable
+//! curve so outdoor and window-facing kiosks stay readable in sun and are
+//! not glaring at night. Readings are smoothed and the backlight only moves
+//! when the target changes noticeably, so passing shadows do not make it
+//! flicker. Changes are published as `brightness-changed`.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{config, events, store};
+
+const BRIGHTNESS_FILE: &str = "brightness.json";
+
+const IIO_DIR: &str = "/sys/bus/iio/devices";
+
+const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+
+/// ioctl selecting the I2C slave address
+const I2C_SLAVE: libc::c_ulong = 0x0703;
+
+/// BH1750 continuous high-resolution mode, 1 lx per 1.2 counts
+const BH1750_CONTINUOUS_HIGH_RES: u8 = 0x10;
+const BH1750_COUNTS_PER_LUX: f64 = 1.2;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Weight of a new reading in the smoothed light level
+const SMOOTHING: f64 = 0.3;
+
+/// Smallest change in percent worth moving the backlight for
+const HYSTERESIS: f64 = 3.0;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LightSensor {
+    /// The first IIO device with an illuminance channel
+    Auto,
+    /// An IIO device by name, e.g. "iio:device0"
+    Iio { device: String },
+    /// A BH1750 on /dev/i2c-<bus>, usually at 0x23
+    Bh1750 { bus: u8, address: u16 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurvePoint {
+    pub lux: f64,
+    /// Backlight level in percent
+    pub brightness: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrightnessConfig {
+    pub auto: bool,
+    pub sensor: LightSensor,
+    /// Interpolated on a log lux scale, flat beyond the ends
+    pub curve: Vec<CurvePoint>,
+    /// Floor so the screen never goes fully dark
+    pub min_brightness: f64,
+}
+
+impl Default for BrightnessConfig {
+    fn default() -> Self {
+        let point = |lux, brightness| CurvePoint { lux, brightness };
+        BrightnessConfig {
+            auto: false,
+            sensor: LightSensor::Auto,
+            curve: vec![point(1.0, 15.0), point(50.0, 35.0), point(400.0, 65.0), point(10_000.0, 100.0)],
+            min_brightness: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LightLevel {
+    /// Smoothed illuminance; `None` without a working sensor
+    pub lux: Option<f64>,
+    pub sensor: Option<String>,
+    /// Current backlight level in percent
+    pub brightness: Option<f64>,
+    pub auto: bool,
+    pub error: Option<String>,
+}
+
+/// Payload of `brightness-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct BrightnessChanged {
+    pub brightness: f64,
+    pub lux: Option<f64>,
+    pub auto: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Reading {
+    /// Smoothed illuminance
+    lux: Option<f64>,
+    sensor: Option<String>,
+    error: Option<String>,
+}
+
+pub struct BrightnessState {
+    config: Mutex<BrightnessConfig>,
+    reading: Mutex<Reading>,
+}
+
+impl BrightnessState {
+    pub fn load(app: &AppHandle) -> Self {
+        BrightnessState {
+            config: Mutex::new(store::load(app, BRIGHTNESS_FILE)),
+            reading: Mutex::new(Reading::default()),
+        }
+    }
+}
+
+// ============================================================================
+// Sensors
+// ============================================================================
+
+fn read_number(path: &Path) -> Option<f64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Lux from an IIO device, from the processed channel or raw * scale
+fn read_iio(dir: &Path) -> Option<f64> {
+    if let Some(lux) = read_number(&dir.join("in_illuminance_input")) {
+        return Some(lux);
+    }
+    let raw = read_number(&dir.join("in_illuminance_raw"))?;
+    let offset = read_number(&dir.join("in_illuminance_offset")).unwrap_or(0.0);
+    let scale = read_number(&dir.join("in_illuminance_scale")).unwrap_or(1.0);
+    Some((raw + offset) * scale)
+}
+
+fn iio_devices() -> Vec<PathBuf> {
+    let mut devices: Vec<PathBuf> = fs::read_dir(IIO_DIR)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    devices.sort();
+    devices
+}
+
+fn read_bh1750(bus: u8, address: u16) -> Result<f64, String> {
+    let path = format!("/dev/i2c-{}", bus);
+    let mut device = File::options()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    // SAFETY: the fd is open for the duration of the call and I2C_SLAVE takes an integer
+    if unsafe { libc::ioctl(device.as_raw_fd(), I2C_SLAVE, libc::c_ulong::from(address)) } < 0 {
+        return Err(format!("No I2C device at 0x{:02x}: {}", address, std::io::Error::last_os_error()));
+    }
+    device.write_all(&[BH1750_CONTINUOUS_HIGH_RES]).map_err(|e| e.to_string())?;
+    let mut counts = [0u8; 2];
+    device.read_exact(&mut counts).map_err(|e| e.to_string())?;
+    Ok(u16::from_be_bytes(counts) as f64 / BH1750_COUNTS_PER_LUX)
+}
+
+/// One lux reading and the sensor it came from
+fn read_lux(sensor: &LightSensor) -> Result<(f64, String), String> {
+    match sensor {
+        LightSensor::Auto => iio_devices()
+            .iter()
+            .find_map(|dir| Some((read_iio(dir)?, dir.file_name()?.to_string_lossy().to_string())))
+            .ok_or_else(|| "No ambient light sensor found".to_string()),
+        LightSensor::Iio { device } => {
+            if device.contains('/') {
+                return Err(format!("Not an IIO device name: {}", device));
+            }
+            let lux = read_iio(&Path::new(IIO_DIR).join(device))
+                .ok_or_else(|| format!("{} has no illuminance channel", device))?;
+            Ok((lux, device.clone()))
+        }
+        LightSensor::Bh1750 { bus, address } => {
+            let lux = read_bh1750(*bus, *address)?;
+            Ok((lux, format!("bh1750@i2c-{}:0x{:02x}", bus, address)))
+        }
+    }
+}
+
+// ============================================================================
+// Backlight
+// ============================================================================
+
+fn backlight() -> Option<PathBuf> {
+    let mut panels: Vec<PathBuf> = fs::read_dir(BACKLIGHT_DIR).ok()?.flatten().map(|entry| entry.path()).collect();
+    panels.sort();
+    panels.into_iter().next()
+}
+
+fn get_backlight() -> Option<f64> {
+    let panel = backlight()?;
+    let max = read_number(&panel.join("max_brightness"))?;
+    let current = read_number(&panel.join("brightness"))?;
+    (max > 0.0).then(|| current / max * 100.0)
+}
+
+fn set_backlight(percent: f64) -> Result<(), String> {
+    let panel = backlight().ok_or_else(|| "No backlight to control".to_string())?;
+    let max = read_number(&panel.join("max_brightness")).ok_or_else(|| "Cannot read max_brightness".to_string())?;
+    let value = (percent.clamp(0.0, 100.0) / 100.0 * max).round() as u64;
+    let path = panel.join("brightness");
+    fs::write(&path, value.to_string()).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Backlight level for a light level from the curve
+fn curve_brightness(config: &BrightnessConfig, lux: f64) -> f64 {
+    let curve = &config.curve;
+    let level = match (curve.first(), curve.last()) {
+        (Some(first), _) if lux <= first.lux => first.brightness,
+        (_, Some(last)) if lux >= last.lux => last.brightness,
+        (Some(_), Some(_)) => {
+            let log = |lux: f64| lux.max(0.01).log10();
+            let upper = curve.iter().position(|point| point.lux >= lux).unwrap_or(curve.len() - 1).max(1);
+            let (a, b) = (curve[upper - 1], curve[upper]);
+            let t = (log(lux) - log(a.lux)) / (log(b.lux) - log(a.lux)).max(f64::EPSILON);
+            a.brightness + (b.brightness - a.brightness) * t.clamp(0.0, 1.0)
+        }
+        _ => 100.0,
+    };
+    level.clamp(config.min_brightness, 100.0)
+}
+
+fn validate(config: &BrightnessConfig) -> Result<(), String> {
+    if config.curve.is_empty() {
+        return Err("The curve needs at least one point".to_string());
+    }
+    if config.curve.windows(2).any(|pair| pair[1].lux <= pair[0].lux) {
+        return Err("Curve points must be in increasing lux order".to_string());
+    }
+    let in_range = |percent: f64| (0.0..=100.0).contains(&percent);
+    let points_valid = config.curve.iter().all(|point| point.lux >= 0.0 && in_range(point.brightness));
+    if !points_valid || !in_range(config.min_brightness) {
+        return Err("Brightness must be 0-100 and lux non-negative".to_string());
+    }
+    Ok(())
+}
+
+fn publish_change(app: &AppHandle, brightness: f64, lux: Option<f64>, auto: bool) {
+    events::publish(app, "brightness-changed", &BrightnessChanged { brightness, lux, auto });
+}
+
+/// Read the sensor and, in auto mode, follow the curve
+fn tick(app: &AppHandle) {
+    let state = app.state::<BrightnessState>();
+    let config = state.config.lock().expect("brightness lock").clone();
+    let lux = {
+        let mut reading = state.reading.lock().expect("brightness lock");
+        match read_lux(&config.sensor) {
+            Ok((lux, sensor)) => {
+                let smoothed = reading.lux.map_or(lux, |previous| previous + (lux - previous) * SMOOTHING);
+                *reading = Reading {
+                    lux: Some(smoothed),
+                    sensor: Some(sensor),
+                    error: None,
+                };
+            }
+            Err(e) => reading.error = Some(e),
+        }
+        reading.lux
+    };
+    let (Some(lux), true) = (lux, config.auto) else { return };
+
+    let target = curve_brightness(&config, lux);
+    let current = get_backlight();
+    if current.map_or(true, |current| (current - target).abs() >= HYSTERESIS) && set_backlight(target).is_ok() {
+        publish_change(app, target, Some(lux), true);
+    }
+}
+
+/// Poll the light sensor in the background
+pub fn start_brightness(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        tick(&app);
+        std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Smoothed ambient light, the sensor and the backlight level
+#[tauri::command]
+pub fn get_light_level(state: State<'_, BrightnessState>) -> LightLevel {
+    let auto = state.config.lock().expect("brightness lock").auto;
+    let reading = state.reading.lock().expect("brightness lock").clone();
+    LightLevel {
+        lux: reading.lux,
+        sensor: reading.sensor,
+        brightness: get_backlight(),
+        auto,
+        error: reading.error,
+    }
+}
+
+#[tauri::command]
+pub fn get_brightness_config(state: State<'_, BrightnessState>) -> BrightnessConfig {
+    state.config.lock().expect("brightness lock").clone()
+}
+
+/// Replace the sensor, curve and floor (admin)
+#[tauri::command]
+pub fn set_brightness_config(
+    app: AppHandle,
+    state: State<'_, BrightnessState>,
+    auth: State<'_, AuthState>,
+    config: BrightnessConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    validate(&config).map_err(KioskError::invalid)?;
+    let mut current = state.config.lock().expect("brightness lock");
+    store::save(&app, BRIGHTNESS_FILE, &config)?;
+    *current = config;
+    Ok(())
+}
+
+/// Turn the auto-brightness controller on or off
+#[tauri::command]
+pub fn set_auto_brightness(
+    app: AppHandle,
+    state: State<'_, BrightnessState>,
+    auth: State<'_, AuthState>,
+    enabled: bool,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    {
+        let mut config = state.config.lock().expect("brightness lock");
+        config.auto = enabled;
+        store::save(&app, BRIGHTNESS_FILE, &*config)?;
+    }
+    if enabled {
+        tick(&app);
+    }
+    Ok(())
+}
+
+/// Set the backlight by hand, in percent; turns auto-brightness off
+#[tauri::command]
+pub fn set_brightness(
+    app: AppHandle,
+    state: State<'_, BrightnessState>,
+    auth: State<'_, AuthState>,
+    brightness: f64,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    if !(0.0..=100.0).contains(&brightness) {
+        return Err(KioskError::invalid("Brightness must be 0-100"));
+    }
+    {
+        let mut config = state.config.lock().expect("brightness lock");
+        if config.auto {
+            config.auto = false;
+            store::save(&app, BRIGHTNESS_FILE, &*config)?;
+        }
+    }
+    set_backlight(brightness)?;
+    let lux = state.reading.lock().expect("brightness lock").lux;
+    publish_change(&app, brightness, lux, false);
+    Ok(())
+}
@@ -0,0 +1,189 @@
+//! Signed content bundles
+//!
+//! Ed25519 verification for downloaded update and content bundles. A bundle
+//! `content.zip` is accompanied by `content.zip.sig`, a 64-byte signature
+//! (raw or base64) over the SHA-256 digest of the bundle. In lockdown mode
+//! unsigned bundles are rejected outright.
+
+use base64::Engine;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, Window};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+use crate::{approvals, jobs, store};
+
+const BUNDLES_FILE: &str = "bundles.json";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BundleConfig {
+    /// Refuse bundles without a valid signature
+    pub lockdown: bool,
+    /// Trusted public keys (32 bytes, base64 or hex)
+    pub trusted_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleVerification {
+    /// A signature file was present and verified
+    pub signed: bool,
+    /// Fingerprint of the key that verified the signature
+    pub key_fingerprint: Option<String>,
+    /// SHA-256 of the bundle, hex
+    pub digest: String,
+}
+
+pub struct BundleState(Mutex<BundleConfig>);
+
+impl BundleState {
+    pub fn load(app: &AppHandle) -> Self {
+        BundleState(Mutex::new(store::load(app, BUNDLES_FILE)))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn decode_bytes(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    let is_hex = text.len() % 2 == 0 && text.chars().all(|c| c.is_ascii_hexdigit());
+    if is_hex {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+            .collect()
+    } else {
+        base64::engine::general_purpose::STANDARD.decode(text).ok()
+    }
+}
+
+fn parse_key(text: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = decode_bytes(text)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("Public key must be 32 bytes in base64 or hex")?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid public key: {}", e))
+}
+
+/// Short identifier for a key: first 8 bytes of its SHA-256, hex
+fn fingerprint(key: &VerifyingKey) -> String {
+    Sha256::digest(key.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+fn read_signature(path: &Path) -> Result<Option<Signature>, String> {
+    let Ok(data) = std::fs::read(path) else { return Ok(None) };
+    let bytes = if data.len() == Signature::BYTE_SIZE {
+        data
+    } else {
+        decode_bytes(&String::from_utf8_lossy(&data)).ok_or("Unreadable signature file")?
+    };
+    let bytes: [u8; Signature::BYTE_SIZE] = bytes.try_into().map_err(|_| "Signature must be 64 bytes")?;
+    Ok(Some(Signature::from_bytes(&bytes)))
+}
+
+fn digest_file(path: &Path) -> Result<[u8; 32], String> {
+    let mut file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hasher.finalize().into())
+}
+
+/// Verify a bundle against the given keys, applying the lockdown policy.
+///
+/// Other modules call this before applying downloaded content.
+pub(crate) fn check_bundle(app: &AppHandle, path: &Path, keys: &[String]) -> Result<BundleVerification, String> {
+    let lockdown = app.state::<BundleState>().0.lock().expect("bundle config lock").lockdown;
+    let digest = digest_file(path)?;
+    let digest_hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+
+    let Some(signature) = read_signature(&signature_path(path))? else {
+        if lockdown {
+            return Err("Unsigned bundle rejected in lockdown mode".to_string());
+        }
+        return Ok(BundleVerification {
+            signed: false,
+            key_fingerprint: None,
+            digest: digest_hex,
+        });
+    };
+
+    let keys = keys.iter().map(|key| parse_key(key)).collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err("No trusted keys configured".to_string());
+    }
+    let key = keys
+        .iter()
+        .find(|key| key.verify(&digest, &signature).is_ok())
+        .ok_or("Bundle signature is not valid for any trusted key")?;
+
+    Ok(BundleVerification {
+        signed: true,
+        key_fingerprint: Some(fingerprint(key)),
+        digest: digest_hex,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start verifying a bundle's signature, against `pubkey` or the trusted keys.
+/// The job result is a `BundleVerification`.
+#[tauri::command]
+pub fn verify_bundle(app: AppHandle, window: Window, path: String, pubkey: Option<String>) -> String {
+    let keys = match pubkey {
+        Some(key) => vec![key],
+        None => app.state::<BundleState>().0.lock().expect("bundle config lock").trusted_keys.clone(),
+    };
+    let worker = app.clone();
+    jobs::spawn(&app, Some(window.label()), "verify_bundle", move |_| {
+        check_bundle(&worker, &vfs::resolve(&worker, &path, Access::Read).map_err(|e| e.to_string())?, &keys)
+    })
+}
+
+/// Get the bundle signing policy
+#[tauri::command]
+pub fn get_bundle_config(state: State<'_, BundleState>) -> BundleConfig {
+    state.0.lock().expect("bundle config lock").clone()
+}
+
+/// Update the bundle signing policy. Turning lockdown off needs a second
+/// admin's approval when dual authorization covers it.
+#[tauri::command]
+pub fn set_bundle_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, BundleState>,
+    config: BundleConfig,
+    approval_id: Option<String>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    for key in &config.trusted_keys {
+        parse_key(key)?;
+    }
+    if state.0.lock().expect("bundle config lock").lockdown && !config.lockdown {
+        approvals::authorize(&app, "disable_lockdown", approval_id.as_deref())?;
+    }
+    store::save(&app, BUNDLES_FILE, &config)?;
+    *state.0.lock().expect("bundle config lock") = config;
+    Ok(())
+}
@@ -0,0 +1,565 @@
+//! Business hours and public holidays
+//!
+//! Knows when the premises are open, so the lobby kiosk can say "We're
+//! closed" and when it opens again without anyone updating it. Opening
+//! periods are set per weekday (several a day for a lunch break; a close at
+//! or before the open runs past midnight). Public holidays come from the
+//! configured region's rules (`holidays/*.json`, or `holidays/<region>.json`
+//! in the data directory to add or correct a region), and closures or
+//! special hours for single dates take precedence over both.
+//!
+//! A holiday rule's date is `MM-DD`, `easter+N` / `easter-N`, or
+//! `MM-<weekday>-<n>` such as `11-thu-4` or `05-mon-last`. A watcher
+//! publishes `business-open-changed` when the premises open or close.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{config, events, store};
+
+const BUSINESS_FILE: &str = "business-hours.json";
+
+/// Data directory subdirectory for added or corrected regions
+const REGION_DIR: &str = "holidays";
+
+const BUNDLED: &[(&str, &str)] = &[
+    ("de", include_str!("../holidays/de.json")),
+    ("es", include_str!("../holidays/es.json")),
+    ("fr", include_str!("../holidays/fr.json")),
+    ("gb", include_str!("../holidays/gb.json")),
+    ("us", include_str!("../holidays/us.json")),
+];
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How far ahead to look for the next opening
+const LOOKAHEAD_DAYS: i64 = 60;
+
+const DAYS: usize = 7;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Open from `open` to `close`, as "HH:MM"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Period {
+    pub open: String,
+    pub close: String,
+}
+
+/// Different hours on one date, or closed when `hours` is empty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpecialDay {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub hours: Vec<Period>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BusinessHours {
+    /// Monday first; an empty day is closed
+    pub days: Vec<Vec<Period>>,
+    /// Holiday region such as `de`; no public holidays when unset
+    pub region: Option<String>,
+    pub open_on_holidays: bool,
+    pub special_days: Vec<SpecialDay>,
+}
+
+impl Default for BusinessHours {
+    fn default() -> Self {
+        let weekday = vec![Period {
+            open: "09:00".to_string(),
+            close: "17:00".to_string(),
+        }];
+        BusinessHours {
+            days: vec![weekday.clone(), weekday.clone(), weekday.clone(), weekday.clone(), weekday, vec![], vec![]],
+            region: None,
+            open_on_holidays: false,
+            special_days: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Region {
+    name: String,
+    holidays: Vec<HolidayRule>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Observed {
+    /// Kept on its date
+    #[default]
+    Actual,
+    /// Saturday moves to Friday, Sunday to Monday (US federal)
+    NearestWeekday,
+    /// A weekend day moves to the next free weekday (UK bank holidays)
+    NextWeekday,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct HolidayRule {
+    name: String,
+    date: String,
+    #[serde(default)]
+    observed: Observed,
+    /// First year the holiday was kept
+    #[serde(default)]
+    since: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HolidayDate {
+    /// "YYYY-MM-DD" the holiday is kept on
+    pub date: String,
+    pub name: String,
+    /// Moved off a weekend
+    pub observed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HolidayRegion {
+    pub code: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenStatus {
+    pub open: bool,
+    /// Holiday or special day name when today is one
+    pub day_name: Option<String>,
+    /// Unix time the current opening period ends
+    pub closes_at: Option<i64>,
+    /// Unix time of the next opening when closed
+    pub opens_at: Option<i64>,
+}
+
+/// Payload of `business-open-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct BusinessOpenChanged {
+    pub open: bool,
+    pub opens_at: Option<i64>,
+}
+
+pub struct BusinessState {
+    hours: Mutex<BusinessHours>,
+    /// Open state last published
+    open: Mutex<Option<bool>>,
+}
+
+impl BusinessState {
+    pub fn load(app: &AppHandle) -> Self {
+        BusinessState {
+            hours: Mutex::new(store::load(app, BUSINESS_FILE)),
+            open: Mutex::new(None),
+        }
+    }
+}
+
+// ============================================================================
+// Holiday Rules
+// ============================================================================
+
+/// Easter Sunday (Gregorian, anonymous algorithm)
+fn easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+fn parse_weekday(name: &str) -> Option<Weekday> {
+    name.parse().ok()
+}
+
+/// The date a rule falls on in `year`, before any weekend move
+fn rule_date(rule: &str, year: i32) -> Result<NaiveDate, String> {
+    let invalid = || format!("Invalid holiday date: {}", rule);
+    if let Some(offset) = rule.strip_prefix("easter") {
+        let days: i64 = if offset.is_empty() { 0 } else { offset.parse().map_err(|_| invalid())? };
+        return easter(year).map(|date| date + ChronoDuration::days(days)).ok_or_else(invalid);
+    }
+    let parts: Vec<&str> = rule.split('-').collect();
+    let month: u32 = parts.first().and_then(|month| month.parse().ok()).ok_or_else(invalid)?;
+    match parts[1..] {
+        [day] => NaiveDate::from_ymd_opt(year, month, day.parse().map_err(|_| invalid())?).ok_or_else(invalid),
+        [weekday, "last"] => {
+            let weekday = parse_weekday(weekday).ok_or_else(invalid)?;
+            let next_month = if month == 12 {
+                NaiveDate::from_ymd_opt(year + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(year, month + 1, 1)
+            };
+            let mut date = next_month.and_then(|date| date.pred_opt()).ok_or_else(invalid)?;
+            while date.weekday() != weekday {
+                date = date.pred_opt().ok_or_else(invalid)?;
+            }
+            Ok(date)
+        }
+        [weekday, nth] => {
+            let weekday = parse_weekday(weekday).ok_or_else(invalid)?;
+            let nth: u8 = nth.parse().map_err(|_| invalid())?;
+            NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth).ok_or_else(invalid)
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn bundled_region(code: &str) -> Option<&'static str> {
+    BUNDLED.iter().find(|(bundled, _)| *bundled == code).map(|(_, text)| *text)
+}
+
+fn valid_region_code(code: &str) -> bool {
+    !code.is_empty() && code.len() <= 16 && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// A region's rules, from the data directory first
+fn load_region(app: &AppHandle, code: &str) -> Result<Region, String> {
+    let code = code.trim().to_lowercase();
+    if !valid_region_code(&code) {
+        return Err(format!("Invalid holiday region: {}", code));
+    }
+    let path = store::data_path(app, &format!("{}/{}.json", REGION_DIR, code))?;
+    if path.exists() {
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        return serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+    let text = bundled_region(&code).ok_or_else(|| format!("No holiday data for {}", code))?;
+    serde_json::from_str(text).map_err(|e| format!("Holiday data for {}: {}", code, e))
+}
+
+/// A region's holidays in `year`, in date order, with weekend holidays moved
+/// as the region does
+fn region_holidays(region: &Region, year: i32) -> Result<Vec<HolidayDate>, String> {
+    let mut actual: Vec<(NaiveDate, &HolidayRule)> = Vec::new();
+    for rule in &region.holidays {
+        if rule.since.is_some_and(|since| year < since) {
+            continue;
+        }
+        actual.push((rule_date(&rule.date, year)?, rule));
+    }
+    actual.sort_by_key(|(date, _)| *date);
+
+    let mut kept: Vec<(NaiveDate, HolidayDate)> = Vec::new();
+    for (date, rule) in actual {
+        let moved = match rule.observed {
+            Observed::Actual => date,
+            Observed::NearestWeekday => match date.weekday() {
+                Weekday::Sat => date.pred_opt().unwrap_or(date),
+                Weekday::Sun => date.succ_opt().unwrap_or(date),
+                _ => date,
+            },
+            Observed::NextWeekday => {
+                let mut moved = date;
+                // Skip weekends and days another holiday already has
+                while is_weekend(moved) || kept.iter().any(|(taken, _)| *taken == moved) {
+                    match moved.succ_opt() {
+                        Some(next) => moved = next,
+                        None => break,
+                    }
+                }
+                moved
+            }
+        };
+        kept.push((
+            moved,
+            HolidayDate {
+                date: moved.format("%Y-%m-%d").to_string(),
+                name: rule.name.clone(),
+                observed: moved != date,
+            },
+        ));
+    }
+    kept.sort_by_key(|(date, _)| *date);
+    Ok(kept.into_iter().map(|(_, holiday)| holiday).collect())
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Not a time (HH:MM): {}", time))
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Not a date (YYYY-MM-DD): {}", date))
+}
+
+fn validate(app: &AppHandle, hours: &BusinessHours) -> Result<(), String> {
+    if hours.days.len() != DAYS {
+        return Err(format!("Expected hours for {} days, got {}", DAYS, hours.days.len()));
+    }
+    let periods = hours.days.iter().flatten().chain(hours.special_days.iter().flat_map(|day| &day.hours));
+    for period in periods {
+        parse_time(&period.open)?;
+        parse_time(&period.close)?;
+    }
+    for day in &hours.special_days {
+        parse_date(&day.date)?;
+    }
+    if let Some(region) = &hours.region {
+        region_holidays(&load_region(app, region)?, Local::now().year())?;
+    }
+    Ok(())
+}
+
+/// Holidays by date for the years a lookup touches
+struct Calendar<'a> {
+    hours: &'a BusinessHours,
+    region: Option<Region>,
+    years: BTreeMap<i32, BTreeMap<NaiveDate, String>>,
+}
+
+impl<'a> Calendar<'a> {
+    fn new(app: &AppHandle, hours: &'a BusinessHours) -> Self {
+        Calendar {
+            hours,
+            region: hours.region.as_deref().and_then(|region| load_region(app, region).ok()),
+            years: BTreeMap::new(),
+        }
+    }
+
+    fn holiday(&mut self, date: NaiveDate) -> Option<String> {
+        let region = self.region.as_ref()?;
+        let holidays = self.years.entry(date.year()).or_insert_with(|| {
+            region_holidays(region, date.year())
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|holiday| Some((parse_date(&holiday.date).ok()?, holiday.name)))
+                .collect()
+        });
+        holidays.get(&date).cloned()
+    }
+
+    /// Opening periods on a date and the name of the day if it is special
+    fn periods(&mut self, date: NaiveDate) -> (Vec<(NaiveTime, NaiveTime)>, Option<String>) {
+        let special = self.hours.special_days.iter().find(|day| parse_date(&day.date).ok() == Some(date));
+        let (periods, name) = match special {
+            Some(day) => (day.hours.clone(), Some(day.name.clone()).filter(|name| !name.is_empty())),
+            None => match self.holiday(date) {
+                Some(name) if !self.hours.open_on_holidays => (Vec::new(), Some(name)),
+                name => {
+                    let weekday = date.weekday().num_days_from_monday() as usize;
+                    (self.hours.days.get(weekday).cloned().unwrap_or_default(), name)
+                }
+            },
+        };
+        let periods = periods
+            .iter()
+            .filter_map(|period| Some((parse_time(&period.open).ok()?, parse_time(&period.close).ok()?)))
+            .collect();
+        (periods, name)
+    }
+
+    /// Opening periods as local times, for the days around `from`
+    fn intervals(&mut self, from: NaiveDate, days: i64) -> Vec<(DateTime<Local>, DateTime<Local>)> {
+        let mut intervals = Vec::new();
+        for offset in -1..days {
+            let date = from + ChronoDuration::days(offset);
+            for (open, close) in self.periods(date).0 {
+                let end_date = if close <= open { date + ChronoDuration::days(1) } else { date };
+                let start = Local.from_local_datetime(&date.and_time(open)).earliest();
+                let end = Local.from_local_datetime(&end_date.and_time(close)).earliest();
+                if let (Some(start), Some(end)) = (start, end) {
+                    intervals.push((start, end));
+                }
+            }
+        }
+        intervals.sort();
+        intervals
+    }
+}
+
+fn status(app: &AppHandle, hours: &BusinessHours, now: DateTime<Local>) -> OpenStatus {
+    let mut calendar = Calendar::new(app, hours);
+    let day_name = calendar.periods(now.date_naive()).1;
+    let intervals = calendar.intervals(now.date_naive(), LOOKAHEAD_DAYS);
+
+    let mut closes_at = None;
+    for (start, end) in &intervals {
+        // Back-to-back periods count as one stretch of opening
+        match closes_at {
+            None if *start <= now && now < *end => closes_at = Some(*end),
+            Some(close) if *start <= close && *end > close => closes_at = Some(*end),
+            _ => {}
+        }
+    }
+    let opens_at = match closes_at {
+        Some(_) => None,
+        None => intervals.iter().map(|(start, _)| *start).find(|start| *start > now),
+    };
+    OpenStatus {
+        open: closes_at.is_some(),
+        day_name,
+        closes_at: closes_at.map(|at| at.timestamp()),
+        opens_at: opens_at.map(|at| at.timestamp()),
+    }
+}
+
+fn current_status(app: &AppHandle) -> OpenStatus {
+    let hours = app.state::<BusinessState>().hours.lock().expect("business hours lock").clone();
+    status(app, &hours, Local::now())
+}
+
+/// Publish `business-open-changed` when the premises open or close
+fn check(app: &AppHandle) {
+    let status = current_status(app);
+    let state = app.state::<BusinessState>();
+    let mut published = state.open.lock().expect("business hours lock");
+    if *published == Some(status.open) {
+        return;
+    }
+    let first = published.is_none();
+    *published = Some(status.open);
+    drop(published);
+    if !first {
+        events::publish(
+            app,
+            "business-open-changed",
+            &BusinessOpenChanged {
+                open: status.open,
+                opens_at: status.opens_at,
+            },
+        );
+    }
+}
+
+/// Watch for opening and closing in the background
+pub fn start_business(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        check(&app);
+        std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_business_hours(state: State<'_, BusinessState>) -> BusinessHours {
+    state.hours.lock().expect("business hours lock").clone()
+}
+
+/// Replace the business hours, region and special days (admin)
+#[tauri::command]
+pub fn set_business_hours(
+    app: AppHandle,
+    state: State<'_, BusinessState>,
+    auth: State<'_, AuthState>,
+    hours: BusinessHours,
+) -> Result<OpenStatus, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    validate(&app, &hours).map_err(KioskError::invalid)?;
+    {
+        let mut current = state.hours.lock().expect("business hours lock");
+        store::save(&app, BUSINESS_FILE, &hours)?;
+        *current = hours;
+    }
+    check(&app);
+    Ok(current_status(&app))
+}
+
+/// Whether the premises are open, and until or from when
+#[tauri::command]
+pub fn is_open_now(app: AppHandle) -> OpenStatus {
+    current_status(&app)
+}
+
+/// Unix time of the next opening, or `None` if nothing opens in the next
+/// two months; while open, the opening after the current period
+#[tauri::command]
+pub fn next_opening(app: AppHandle) -> Option<i64> {
+    let now = Local::now();
+    let hours = app.state::<BusinessState>().hours.lock().expect("business hours lock").clone();
+    let current = status(&app, &hours, now);
+    match current.closes_at {
+        Some(close) => {
+            let after = Local.timestamp_opt(close, 0).single()?;
+            status(&app, &hours, after).opens_at
+        }
+        None => current.opens_at,
+    }
+}
+
+/// Public holidays of `region`, or the configured region, in `year`, with
+/// special days added
+#[tauri::command]
+pub fn get_holidays(
+    app: AppHandle,
+    state: State<'_, BusinessState>,
+    year: i32,
+    region: Option<String>,
+) -> Result<Vec<HolidayDate>, KioskError> {
+    let hours = state.hours.lock().expect("business hours lock").clone();
+    // Special days belong to this kiosk, not to another region asked about
+    let own = region.is_none();
+    let mut holidays = match region.or_else(|| hours.region.clone()) {
+        Some(region) => region_holidays(&load_region(&app, &region).map_err(KioskError::not_found)?, year)?,
+        None => Vec::new(),
+    };
+    if own {
+        for day in hours.special_days.iter().filter(|day| day.date.starts_with(&format!("{:04}-", year))) {
+            holidays.retain(|holiday| holiday.date != day.date);
+            holidays.push(HolidayDate {
+                date: day.date.clone(),
+                name: day.name.clone(),
+                observed: false,
+            });
+        }
+        holidays.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+    Ok(holidays)
+}
+
+/// Holiday regions with bundled or added rules
+#[tauri::command]
+pub fn list_holiday_regions(app: AppHandle) -> Vec<HolidayRegion> {
+    let mut codes: Vec<String> = BUNDLED.iter().map(|(code, _)| code.to_string()).collect();
+    let added = store::data_path(&app, REGION_DIR).ok().and_then(|dir| std::fs::read_dir(dir).ok());
+    for path in added.into_iter().flatten().flatten().map(|entry| entry.path()) {
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+        if let Some(code) = path.file_stem().and_then(|stem| stem.to_str()) {
+            codes.push(code.to_lowercase());
+        }
+    }
+    codes.sort();
+    codes.dedup();
+    codes
+        .into_iter()
+        .filter_map(|code| {
+            let name = load_region(&app, &code).ok()?.name;
+            Some(HolidayRegion { code, name })
+        })
+        .collect()
+}
@@ -0,0 +1,650 @@
+//! Calculator expression engine
+//!
+//! Parses and evaluates expressions for the Calculator accessory. Standard and
+//! scientific modes use 28-digit decimal arithmetic so `0.1 + 0.2` is exactly
+//! `0.3`; programmer mode works on 64-bit integers with bitwise operators.
+//! Any mode accepts a trailing unit conversion such as `12 km to mi`.
+
+use rust_decimal::prelude::*;
+use rust_decimal::MathematicalOps;
+use serde::{Deserialize, Serialize};
+
+use crate::error::KioskError;
+
+/// Longest expression accepted, to keep parsing cheap
+const MAX_EXPRESSION_LEN: usize = 1024;
+
+/// Decimal places shown; hides rounding noise from series-based functions
+const DISPLAY_DECIMALS: u32 = 24;
+
+/// Unit table: (symbol, category, factor to the category's base unit)
+const UNITS: &[(&str, &str, &str)] = &[
+    ("mm", "length", "0.001"),
+    ("cm", "length", "0.01"),
+    ("m", "length", "1"),
+    ("km", "length", "1000"),
+    ("in", "length", "0.0254"),
+    ("ft", "length", "0.3048"),
+    ("yd", "length", "0.9144"),
+    ("mi", "length", "1609.344"),
+    ("mg", "mass", "0.000001"),
+    ("g", "mass", "0.001"),
+    ("kg", "mass", "1"),
+    ("t", "mass", "1000"),
+    ("oz", "mass", "0.028349523125"),
+    ("lb", "mass", "0.45359237"),
+    ("ml", "volume", "0.001"),
+    ("l", "volume", "1"),
+    ("floz", "volume", "0.0295735295625"),
+    ("gal", "volume", "3.785411784"),
+    ("ms", "time", "0.001"),
+    ("s", "time", "1"),
+    ("min", "time", "60"),
+    ("h", "time", "3600"),
+    ("day", "time", "86400"),
+    ("week", "time", "604800"),
+    ("b", "data", "1"),
+    ("kb", "data", "1024"),
+    ("mb", "data", "1048576"),
+    ("gb", "data", "1073741824"),
+    ("tb", "data", "1099511627776"),
+    ("c", "temperature", "1"),
+    ("f", "temperature", "1"),
+    ("k", "temperature", "1"),
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Calculator mode, matching the View menu of the accessory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CalcMode {
+    Standard,
+    /// Scientific functions, trigonometry in degrees
+    Scientific,
+    /// 64-bit integers, `^` is XOR and `0x`/`0b`/`0o` literals are accepted
+    Programmer,
+}
+
+/// Result of evaluating an expression
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalcResult {
+    pub value: String,
+    /// Unit of the result when a conversion was requested
+    pub unit: Option<String>,
+    /// Radix views, filled in programmer mode
+    pub hex: Option<String>,
+    pub oct: Option<String>,
+    pub bin: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Number(String),
+    Constant(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+const OPERATORS: &[&str] = &["<<", ">>", "+", "-", "*", "/", "%", "^", "&", "|", "~", "!"];
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                // Allow signed exponents like 1e-5
+                if (chars[i] == 'e' || chars[i] == 'E')
+                    && !chars[start..i].iter().any(|c| c.is_ascii_alphabetic())
+                    && matches!(chars.get(i + 1), Some('+') | Some('-'))
+                {
+                    i += 1;
+                }
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect::<String>().to_lowercase()));
+        } else if c == '(' {
+            tokens.push(Token::LParen);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(Token::RParen);
+            i += 1;
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            i += 1;
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("Unexpected character '{}'", c))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    mode: CalcMode,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Binding power of a binary operator, or None if it isn't one in this mode
+    fn precedence(&self, op: &str) -> Option<(u8, bool)> {
+        let right_assoc = self.mode != CalcMode::Programmer && op == "^";
+        let level = match (self.mode, op) {
+            (CalcMode::Programmer, "|") => 1,
+            (CalcMode::Programmer, "^") => 2,
+            (CalcMode::Programmer, "&") => 3,
+            (CalcMode::Programmer, "<<" | ">>") => 4,
+            (_, "+" | "-") => 5,
+            (_, "*" | "/" | "%") => 6,
+            (CalcMode::Standard | CalcMode::Scientific, "^") => 8,
+            _ => return None,
+        };
+        Some((level, right_assoc))
+    }
+
+    fn expression(&mut self, min_level: u8) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            let Some((level, right_assoc)) = self.precedence(op) else { break };
+            if level < min_level {
+                break;
+            }
+
+            self.next();
+            let rhs = self.expression(if right_assoc { level } else { level + 1 })?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Op(op @ ("-" | "+"))) => {
+                let op = *op;
+                self.next();
+                // Unary minus binds looser than ^ so -2^2 is -4
+                Ok(Expr::Unary(op, Box::new(self.expression(7)?)))
+            }
+            Some(Token::Op("~")) if self.mode == CalcMode::Programmer => {
+                self.next();
+                Ok(Expr::Unary("~", Box::new(self.unary()?)))
+            }
+            _ => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+
+        while self.peek() == Some(&Token::Op("!")) {
+            self.next();
+            expr = Expr::Call("fact".to_string(), vec![expr]);
+        }
+
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::LParen) => {
+                let expr = self.expression(0)?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Missing closing parenthesis".to_string()),
+                }
+            }
+            Some(Token::Ident(name)) => {
+                if self.peek() != Some(&Token::LParen) {
+                    return Ok(Expr::Constant(name));
+                }
+
+                self.next();
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::RParen) {
+                    loop {
+                        args.push(self.expression(0)?);
+                        if self.peek() != Some(&Token::Comma) {
+                            break;
+                        }
+                        self.next();
+                    }
+                }
+
+                match self.next() {
+                    Some(Token::RParen) => Ok(Expr::Call(name, args)),
+                    _ => Err(format!("Missing closing parenthesis after {}(", name)),
+                }
+            }
+            Some(token) => Err(format!("Unexpected {:?}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn parse(input: &str, mode: CalcMode) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+        mode,
+    };
+
+    let expr = parser.expression(0)?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("Unexpected {:?}", token)),
+    }
+}
+
+// ============================================================================
+// Decimal Evaluation (Standard / Scientific)
+// ============================================================================
+
+fn overflow() -> String {
+    "Overflow".to_string()
+}
+
+fn to_radians(degrees: Decimal) -> Result<Decimal, String> {
+    degrees
+        .checked_mul(Decimal::PI)
+        .and_then(|d| d.checked_div(Decimal::from(180)))
+        .ok_or_else(overflow)
+}
+
+/// Evaluate a function through f64 where rust_decimal has no implementation
+fn via_f64(value: Decimal, f: fn(f64) -> f64) -> Result<Decimal, String> {
+    let result = f(value.to_f64().ok_or_else(overflow)?);
+    Decimal::from_f64(result).ok_or_else(|| "Invalid input for function".to_string())
+}
+
+fn eval_decimal(expr: &Expr, mode: CalcMode) -> Result<Decimal, String> {
+    match expr {
+        Expr::Number(number) => {
+            let parsed = if number.contains(['e', 'E']) {
+                Decimal::from_scientific(number)
+            } else {
+                Decimal::from_str(number)
+            };
+            parsed.map_err(|_| format!("Invalid number: {}", number))
+        }
+        Expr::Constant(name) => match name.as_str() {
+            "pi" => Ok(Decimal::PI),
+            "e" => Ok(Decimal::E),
+            _ => Err(format!("Unknown constant: {}", name)),
+        },
+        Expr::Unary(op, operand) => {
+            let value = eval_decimal(operand, mode)?;
+            Ok(if *op == "-" { -value } else { value })
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let a = eval_decimal(lhs, mode)?;
+            let b = eval_decimal(rhs, mode)?;
+            match *op {
+                "+" => a.checked_add(b).ok_or_else(overflow),
+                "-" => a.checked_sub(b).ok_or_else(overflow),
+                "*" => a.checked_mul(b).ok_or_else(overflow),
+                "/" if b.is_zero() => Err("Cannot divide by zero".to_string()),
+                "/" => a.checked_div(b).ok_or_else(overflow),
+                "%" if b.is_zero() => Err("Cannot divide by zero".to_string()),
+                "%" => a.checked_rem(b).ok_or_else(overflow),
+                "^" => a.checked_powd(b).ok_or_else(overflow),
+                _ => Err(format!("Operator {} is not available in this mode", op)),
+            }
+        }
+        Expr::Call(name, args) => {
+            if mode == CalcMode::Standard && !matches!(name.as_str(), "sqrt" | "fact" | "abs") {
+                return Err(format!("{}() requires scientific mode", name));
+            }
+
+            let [arg] = args.as_slice() else {
+                return Err(format!("{}() takes one argument", name));
+            };
+            let x = eval_decimal(arg, mode)?;
+
+            match name.as_str() {
+                "sqrt" => x.sqrt().ok_or_else(|| "Invalid input for function".to_string()),
+                "abs" => Ok(x.abs()),
+                "fact" => {
+                    if x.is_sign_negative() || !x.fract().is_zero() || x > Decimal::from(27) {
+                        return Err("Invalid input for function".to_string());
+                    }
+                    let n = x.to_u64().unwrap_or(0);
+                    (1..=n).try_fold(Decimal::ONE, |acc, k| acc.checked_mul(Decimal::from(k)).ok_or_else(overflow))
+                }
+                "sin" => to_radians(x)?.checked_sin().ok_or_else(overflow),
+                "cos" => to_radians(x)?.checked_cos().ok_or_else(overflow),
+                "tan" => to_radians(x)?.checked_tan().ok_or_else(overflow),
+                "asin" => via_f64(x, |v| v.asin().to_degrees()),
+                "acos" => via_f64(x, |v| v.acos().to_degrees()),
+                "atan" => via_f64(x, |v| v.atan().to_degrees()),
+                "ln" => x.checked_ln().ok_or_else(|| "Invalid input for function".to_string()),
+                "log" => x.checked_log10().ok_or_else(|| "Invalid input for function".to_string()),
+                "exp" => x.checked_exp().ok_or_else(overflow),
+                _ => Err(format!("Unknown function: {}", name)),
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Integer Evaluation (Programmer)
+// ============================================================================
+
+fn parse_integer(number: &str) -> Result<i64, String> {
+    let lower = number.to_lowercase();
+    let (digits, radix) = if let Some(hex) = lower.strip_prefix("0x") {
+        (hex, 16)
+    } else if let Some(bin) = lower.strip_prefix("0b") {
+        (bin, 2)
+    } else if let Some(oct) = lower.strip_prefix("0o") {
+        (oct, 8)
+    } else {
+        (lower.as_str(), 10)
+    };
+
+    // Parse as unsigned so 0xFFFFFFFFFFFFFFFF is -1, like the Qword view
+    u64::from_str_radix(digits, radix)
+        .map(|value| value as i64)
+        .map_err(|_| format!("Invalid number: {}", number))
+}
+
+fn eval_integer(expr: &Expr) -> Result<i64, String> {
+    match expr {
+        Expr::Number(number) => parse_integer(number),
+        Expr::Constant(name) => Err(format!("Unknown constant: {}", name)),
+        Expr::Unary(op, operand) => {
+            let value = eval_integer(operand)?;
+            Ok(match *op {
+                "-" => value.wrapping_neg(),
+                "~" => !value,
+                _ => value,
+            })
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let a = eval_integer(lhs)?;
+            let b = eval_integer(rhs)?;
+            match *op {
+                "+" => Ok(a.wrapping_add(b)),
+                "-" => Ok(a.wrapping_sub(b)),
+                "*" => Ok(a.wrapping_mul(b)),
+                "/" | "%" if b == 0 => Err("Cannot divide by zero".to_string()),
+                "/" => Ok(a.wrapping_div(b)),
+                "%" => Ok(a.wrapping_rem(b)),
+                "&" => Ok(a & b),
+                "|" => Ok(a | b),
+                "^" => Ok(a ^ b),
+                "<<" => Ok(a.wrapping_shl(b as u32)),
+                ">>" => Ok(a.wrapping_shr(b as u32)),
+                _ => Err(format!("Operator {} is not available in this mode", op)),
+            }
+        }
+        Expr::Call(name, args) => {
+            let values = args.iter().map(eval_integer).collect::<Result<Vec<_>, _>>()?;
+            match (name.as_str(), values.as_slice()) {
+                ("and", [a, b]) => Ok(a & b),
+                ("or", [a, b]) => Ok(a | b),
+                ("xor", [a, b]) => Ok(a ^ b),
+                ("not", [a]) => Ok(!a),
+                ("rol", [a, b]) => Ok(a.rotate_left(*b as u32)),
+                ("ror", [a, b]) => Ok(a.rotate_right(*b as u32)),
+                _ => Err(format!("Unknown function: {}", name)),
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Unit Conversion
+// ============================================================================
+
+fn find_unit(symbol: &str) -> Option<(&'static str, &'static str, Decimal)> {
+    UNITS
+        .iter()
+        .find(|(unit, _, _)| *unit == symbol)
+        .map(|&(unit, category, factor)| (unit, category, Decimal::from_str(factor).unwrap_or(Decimal::ONE)))
+}
+
+/// Split `"<expr> <unit> to <unit>"` into its parts, if it is a conversion
+fn split_conversion(expr: &str) -> Option<(&str, &'static str, &'static str)> {
+    let lower_pos = |sep: &str| expr.to_ascii_lowercase().rfind(sep);
+    let sep_pos = lower_pos(" to ").or_else(|| lower_pos(" in "))?;
+    let (lhs, rhs) = (&expr[..sep_pos], &expr[sep_pos + 4..]);
+
+    let (value, from) = lhs.trim_end().rsplit_once(char::is_whitespace)?;
+    let (from, _, _) = find_unit(&from.to_lowercase())?;
+    let (to, _, _) = find_unit(&rhs.trim().to_lowercase())?;
+    Some((value, from, to))
+}
+
+fn convert(value: Decimal, from: &str, to: &str) -> Result<Decimal, String> {
+    let (_, from_category, from_factor) = find_unit(from).ok_or("Unknown unit")?;
+    let (_, to_category, to_factor) = find_unit(to).ok_or("Unknown unit")?;
+    if from_category != to_category {
+        return Err(format!("Cannot convert {} to {}", from_category, to_category));
+    }
+
+    if from_category == "temperature" {
+        let nine_fifths = Decimal::new(18, 1);
+        let freezing = Decimal::from(32);
+        let kelvin_offset = Decimal::new(27315, 2);
+        let celsius = match from {
+            "f" => value.checked_sub(freezing).and_then(|d| d.checked_div(nine_fifths)),
+            "k" => value.checked_sub(kelvin_offset),
+            _ => Some(value),
+        };
+        return match to {
+            "f" => celsius.and_then(|c| c.checked_mul(nine_fifths)).and_then(|d| d.checked_add(freezing)),
+            "k" => celsius.and_then(|c| c.checked_add(kelvin_offset)),
+            _ => celsius,
+        }
+        .ok_or_else(overflow);
+    }
+
+    value
+        .checked_mul(from_factor)
+        .and_then(|base| base.checked_div(to_factor))
+        .ok_or_else(overflow)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Evaluate a calculator expression in the given mode
+#[tauri::command]
+pub fn evaluate_expression(expr: String, mode: CalcMode) -> Result<CalcResult, KioskError> {
+    if expr.len() > MAX_EXPRESSION_LEN {
+        return Err(KioskError::invalid("Expression is too long"));
+    }
+
+    let conversion = split_conversion(&expr);
+    let source = conversion.map(|(value, _, _)| value).unwrap_or(&expr);
+    let ast = parse(source, mode)?;
+
+    if mode == CalcMode::Programmer && conversion.is_none() {
+        let value = eval_integer(&ast)?;
+        return Ok(CalcResult {
+            value: value.to_string(),
+            unit: None,
+            hex: Some(format!("{:X}", value)),
+            oct: Some(format!("{:o}", value)),
+            bin: Some(format!("{:b}", value)),
+        });
+    }
+
+    let mut value = match mode {
+        CalcMode::Programmer => Decimal::from(eval_integer(&ast)?),
+        _ => eval_decimal(&ast, mode)?,
+    };
+
+    let unit = match conversion {
+        Some((_, from, to)) => {
+            value = convert(value, from, to)?;
+            Some(to.to_string())
+        }
+        None => None,
+    };
+
+    Ok(CalcResult {
+        value: value.round_dp(DISPLAY_DECIMALS).normalize().to_string(),
+        unit,
+        hex: None,
+        oct: None,
+        bin: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expr: &str, mode: CalcMode) -> Result<String, String> {
+        evaluate_expression(expr.to_string(), mode)
+            .map(|result| result.value)
+            .map_err(|e| e.to_string())
+    }
+
+    fn standard(expr: &str) -> String {
+        eval(expr, CalcMode::Standard).unwrap()
+    }
+
+    #[test]
+    fn tokenizes_numbers_operators_and_names() {
+        let tokens = tokenize("1.5e-3 << Pi(2,x)").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number("1.5e-3".to_string()),
+                Token::Op("<<"),
+                Token::Ident("pi".to_string()),
+                Token::LParen,
+                Token::Number("2".to_string()),
+                Token::Comma,
+                Token::Ident("x".to_string()),
+                Token::RParen,
+            ]
+        );
+        assert!(tokenize("2 $ 3").is_err());
+    }
+
+    #[test]
+    fn follows_precedence_and_associativity() {
+        assert_eq!(standard("1 + 2 * 3"), "7");
+        assert_eq!(standard("(1 + 2) * 3"), "9");
+        assert_eq!(standard("10 - 4 - 3"), "3");
+        assert_eq!(standard("2 ^ 3 ^ 2"), "512");
+        assert_eq!(standard("-2 ^ 2"), "-4");
+        assert_eq!(standard("(-2) ^ 2"), "4");
+        assert_eq!(standard("7 % 4 * 2"), "6");
+    }
+
+    #[test]
+    fn uses_exact_decimals() {
+        assert_eq!(standard("0.1 + 0.2"), "0.3");
+        assert_eq!(standard("1e-3 * 1000"), "1");
+        assert_eq!(standard("5!"), "120");
+        assert_eq!(standard("abs(-2.5)"), "2.5");
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for expr in ["(1 + 2", "1 +", "1 2", "sqrt(4", "", "1..2 + 1", "sqrt(1, 2)"] {
+            assert!(eval(expr, CalcMode::Standard).is_err(), "{:?} should fail", expr);
+        }
+        assert!(eval("1 / 0", CalcMode::Standard).is_err());
+        assert!(eval("foo", CalcMode::Standard).is_err());
+        assert!(eval("sin(30)", CalcMode::Standard).is_err());
+        assert!(eval("1 & 2", CalcMode::Standard).is_err());
+        assert!(eval(&"1+".repeat(MAX_EXPRESSION_LEN), CalcMode::Standard).is_err());
+    }
+
+    #[test]
+    fn overflows_are_errors() {
+        assert!(eval("79228162514264337593543950335 * 2", CalcMode::Standard).is_err());
+        assert!(eval("28!", CalcMode::Standard).is_err());
+    }
+
+    #[test]
+    fn programmer_mode_uses_integers() {
+        let result = evaluate_expression("0xFF & 0b1111 | 1 << 4".to_string(), CalcMode::Programmer).unwrap();
+        assert_eq!(result.value, "31");
+        assert_eq!(result.hex.as_deref(), Some("1F"));
+        assert_eq!(result.bin.as_deref(), Some("11111"));
+        assert_eq!(eval("5 ^ 3", CalcMode::Programmer).unwrap(), "6");
+        assert_eq!(eval("0xFFFFFFFFFFFFFFFF", CalcMode::Programmer).unwrap(), "-1");
+        assert_eq!(eval("~0", CalcMode::Programmer).unwrap(), "-1");
+        assert_eq!(eval("rol(1, 3)", CalcMode::Programmer).unwrap(), "8");
+        assert!(eval("7 / 0", CalcMode::Programmer).is_err());
+        assert!(eval("1.5", CalcMode::Programmer).is_err());
+    }
+
+    #[test]
+    fn converts_units() {
+        let result = evaluate_expression("1 mi to m".to_string(), CalcMode::Standard).unwrap();
+        assert_eq!(result.value, "1609.344");
+        assert_eq!(result.unit.as_deref(), Some("m"));
+        assert_eq!(standard("2 * 1.5 ft in in"), "36");
+        assert!(eval("1 kg to m", CalcMode::Standard).is_err());
+    }
+
+    #[test]
+    fn converts_temperatures() {
+        assert_eq!(standard("100 c to f"), "212");
+        assert_eq!(standard("32 f to c"), "0");
+        assert_eq!(standard("0 k to c"), "-273.15");
+        assert_eq!(standard("-40 f to k"), "233.15");
+        assert!(eval("79228162514264337593543950335 c to f", CalcMode::Standard).is_err());
+        assert!(eval("-79228162514264337593543950335 k to c", CalcMode::Standard).is_err());
+    }
+}
@@ -0,0 +1,674 @@
+//! Calendar and appointments
+//!
+//! Event storage with recurrence, iCalendar (.ics) import/export, and a
+//! background reminder loop that emits `calendar-reminder` events, for
+//! meeting-room and lobby kiosks showing schedules.
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+use crate::{config, events, recents, store};
+
+const CALENDAR_FILE: &str = "calendar.json";
+
+/// How often the reminder loop wakes up
+const REMINDER_POLL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Upper bound on generated occurrences per event, guarding runaway rules
+const MAX_OCCURRENCES: usize = 5000;
+
+/// Largest recurrence interval accepted from the frontend or an .ics file
+const MAX_INTERVAL: u32 = 1000;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A recurrence rule, a subset of iCalendar RRULE
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// Stop after this many occurrences
+    pub count: Option<u32>,
+    /// Stop after this timestamp
+    pub until: Option<i64>,
+    /// Weekdays for weekly rules ("MO", "TU", ...)
+    #[serde(default)]
+    pub by_day: Vec<String>,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+/// Editable event fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventInput {
+    pub title: String,
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub description: String,
+    pub start: i64,
+    pub end: i64,
+    #[serde(default)]
+    pub all_day: bool,
+    pub recurrence: Option<Recurrence>,
+    /// Minutes before the start to raise a reminder
+    pub reminder_minutes: Option<i64>,
+}
+
+/// A stored event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub id: String,
+    /// iCalendar UID, preserved across import/export
+    pub uid: String,
+    #[serde(flatten)]
+    pub fields: EventInput,
+}
+
+/// One concrete occurrence of an event within a queried range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventOccurrence {
+    pub event_id: String,
+    pub title: String,
+    pub location: String,
+    pub start: i64,
+    pub end: i64,
+    pub all_day: bool,
+}
+
+/// Payload of the `calendar-reminder` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderPayload {
+    pub event_id: String,
+    pub title: String,
+    pub location: String,
+    pub start: i64,
+    pub minutes_before: i64,
+}
+
+pub struct CalendarState(Mutex<Vec<CalendarEvent>>);
+
+impl CalendarState {
+    pub fn load(app: &AppHandle) -> Self {
+        CalendarState(Mutex::new(store::load(app, CALENDAR_FILE)))
+    }
+}
+
+// ============================================================================
+// Recurrence Expansion
+// ============================================================================
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    match day.to_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn local(timestamp: i64) -> Option<DateTime<Local>> {
+    Local.timestamp_opt(timestamp, 0).single()
+}
+
+/// Start times of every occurrence of `event` that begins before `to`
+fn occurrence_starts(event: &EventInput, to: i64) -> Vec<i64> {
+    let Some(rule) = &event.recurrence else {
+        return vec![event.start];
+    };
+    let Some(first) = local(event.start) else {
+        return Vec::new();
+    };
+
+    let interval = rule.interval.max(1);
+    let limit = rule.until.unwrap_or(i64::MAX).min(to);
+    let max = rule.count.map(|count| count as usize).unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+    let by_day: Vec<Weekday> = rule.by_day.iter().filter_map(|day| parse_weekday(day)).collect();
+    let weekly_by_day = rule.frequency == Frequency::Weekly && !by_day.is_empty();
+
+    let mut starts = Vec::new();
+    let mut period = 0u32;
+
+    while starts.len() < max {
+        // Stored events predate the interval bound, so anything out of range just ends the series
+        let Some(step) = period.checked_mul(interval) else { break };
+        let anchor = match rule.frequency {
+            Frequency::Daily => Duration::try_days(step as i64).and_then(|days| first.checked_add_signed(days)),
+            Frequency::Weekly => Duration::try_weeks(step as i64).and_then(|weeks| first.checked_add_signed(weeks)),
+            Frequency::Monthly => first.checked_add_months(Months::new(step)),
+            Frequency::Yearly => step.checked_mul(12).and_then(|months| first.checked_add_months(Months::new(months))),
+        };
+        let Some(anchor) = anchor else { break };
+
+        if weekly_by_day {
+            // Expand the anchor's Monday-based week into the selected weekdays
+            let offset = Duration::days(anchor.weekday().num_days_from_monday() as i64);
+            let Some(monday) = anchor.checked_sub_signed(offset) else { break };
+            if monday.timestamp() > limit {
+                break;
+            }
+
+            let mut week: Vec<i64> = by_day
+                .iter()
+                .filter_map(|day| monday.checked_add_signed(Duration::days(day.num_days_from_monday() as i64)))
+                .map(|start| start.timestamp())
+                .filter(|&start| start >= event.start && start <= limit)
+                .collect();
+            week.sort_unstable();
+            starts.extend(week.into_iter().take(max - starts.len()));
+        } else {
+            if anchor.timestamp() > limit {
+                break;
+            }
+            starts.push(anchor.timestamp());
+        }
+
+        period += 1;
+    }
+
+    starts
+}
+
+pub(crate) fn occurrences_in(event: &CalendarEvent, from: i64, to: i64) -> impl Iterator<Item = EventOccurrence> + '_ {
+    let duration = event.fields.end - event.fields.start;
+
+    occurrence_starts(&event.fields, to)
+        .into_iter()
+        .filter(move |&start| start < to && start + duration.max(1) > from)
+        .map(move |start| EventOccurrence {
+            event_id: event.id.clone(),
+            title: event.fields.title.clone(),
+            location: event.fields.location.clone(),
+            start,
+            end: start + duration,
+            all_day: event.fields.all_day,
+        })
+}
+
+// ============================================================================
+// iCalendar
+// ============================================================================
+
+fn ics_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+fn ics_unescape(value: &str) -> String {
+    value
+        .replace("\\n", "\n")
+        .replace("\\N", "\n")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+fn ics_utc(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.format("%Y%m%dT%H%M%SZ").to_string())
+        .unwrap_or_default()
+}
+
+/// Parse DTSTART/DTEND style values: UTC, floating local time, or a DATE
+fn parse_ics_time(value: &str) -> Option<(i64, bool)> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((naive.and_utc().timestamp(), false));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Some((Local.from_local_datetime(&naive).earliest()?.timestamp(), false));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+    Some((Local.from_local_datetime(&date.and_hms_opt(0, 0, 0)?).earliest()?.timestamp(), true))
+}
+
+/// Parse an RFC 5545 duration such as `-PT15M` or `-P1D` into minutes before
+fn parse_trigger_minutes(value: &str) -> Option<i64> {
+    let body = value.strip_prefix('-')?.strip_prefix('P')?;
+    let mut minutes = 0;
+    let mut number = String::new();
+
+    for c in body.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n: i64 = number.parse().ok()?;
+                minutes += match c {
+                    'W' => n * 7 * 24 * 60,
+                    'D' => n * 24 * 60,
+                    'H' => n * 60,
+                    'M' => n,
+                    _ => 0,
+                };
+                number.clear();
+            }
+            _ => {}
+        }
+    }
+
+    Some(minutes)
+}
+
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut rule = Recurrence {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+    };
+    let mut has_frequency = false;
+
+    for part in value.split(';') {
+        let (key, val) = part.split_once('=')?;
+        match key {
+            "FREQ" => {
+                rule.frequency = match val {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return None,
+                };
+                has_frequency = true;
+            }
+            "INTERVAL" => rule.interval = val.parse().ok().filter(|interval| (1..=MAX_INTERVAL).contains(interval))?,
+            "COUNT" => rule.count = val.parse().ok(),
+            "UNTIL" => rule.until = parse_ics_time(val).map(|(ts, _)| ts),
+            "BYDAY" => rule.by_day = val.split(',').map(str::to_string).collect(),
+            _ => {}
+        }
+    }
+
+    has_frequency.then_some(rule)
+}
+
+fn to_rrule(rule: &Recurrence) -> String {
+    let frequency = match rule.frequency {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+        Frequency::Yearly => "YEARLY",
+    };
+    let mut parts = vec![format!("FREQ={}", frequency), format!("INTERVAL={}", rule.interval)];
+    if let Some(count) = rule.count {
+        parts.push(format!("COUNT={}", count));
+    }
+    if let Some(until) = rule.until {
+        parts.push(format!("UNTIL={}", ics_utc(until)));
+    }
+    if !rule.by_day.is_empty() {
+        parts.push(format!("BYDAY={}", rule.by_day.join(",")));
+    }
+    parts.join(";")
+}
+
+pub(crate) fn parse_ics(text: &str) -> Vec<(String, EventInput)> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(previous)) => previous.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<(String, EventInput)> = None;
+    let mut in_alarm = false;
+
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let property = name.split(';').next().unwrap_or_default().to_uppercase();
+
+        match (property.as_str(), value) {
+            ("BEGIN", "VEVENT") => {
+                current = Some((
+                    String::new(),
+                    EventInput {
+                        title: String::new(),
+                        location: String::new(),
+                        description: String::new(),
+                        start: 0,
+                        end: 0,
+                        all_day: false,
+                        recurrence: None,
+                        reminder_minutes: None,
+                    },
+                ))
+            }
+            ("BEGIN", "VALARM") => in_alarm = true,
+            ("END", "VALARM") => in_alarm = false,
+            ("END", "VEVENT") => {
+                if let Some((uid, mut event)) = current.take() {
+                    if event.start != 0 {
+                        if event.end <= event.start {
+                            event.end = event.start + if event.all_day { 86400 } else { 3600 };
+                        }
+                        events.push((uid, event));
+                    }
+                }
+            }
+            (_, _) => {
+                let Some((uid, event)) = current.as_mut() else { continue };
+                match property.as_str() {
+                    "TRIGGER" if in_alarm => event.reminder_minutes = parse_trigger_minutes(value),
+                    "UID" => *uid = value.to_string(),
+                    "SUMMARY" => event.title = ics_unescape(value),
+                    "LOCATION" => event.location = ics_unescape(value),
+                    "DESCRIPTION" => event.description = ics_unescape(value),
+                    "DTSTART" => {
+                        if let Some((start, all_day)) = parse_ics_time(value) {
+                            event.start = start;
+                            event.all_day = all_day;
+                        }
+                    }
+                    "DTEND" => event.end = parse_ics_time(value).map(|(end, _)| end).unwrap_or_default(),
+                    "RRULE" => event.recurrence = parse_rrule(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    events
+}
+
+pub(crate) fn to_ics(events: &[CalendarEvent]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Kiosk//Calendar//EN".to_string(),
+    ];
+
+    for event in events {
+        let fields = &event.fields;
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", ics_utc(Local::now().timestamp())));
+        if fields.all_day {
+            let date = |ts: i64| local(ts).map(|dt| dt.format("%Y%m%d").to_string()).unwrap_or_default();
+            lines.push(format!("DTSTART;VALUE=DATE:{}", date(fields.start)));
+            lines.push(format!("DTEND;VALUE=DATE:{}", date(fields.end)));
+        } else {
+            lines.push(format!("DTSTART:{}", ics_utc(fields.start)));
+            lines.push(format!("DTEND:{}", ics_utc(fields.end)));
+        }
+        lines.push(format!("SUMMARY:{}", ics_escape(&fields.title)));
+        if !fields.location.is_empty() {
+            lines.push(format!("LOCATION:{}", ics_escape(&fields.location)));
+        }
+        if !fields.description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", ics_escape(&fields.description)));
+        }
+        if let Some(rule) = &fields.recurrence {
+            lines.push(format!("RRULE:{}", to_rrule(rule)));
+        }
+        if let Some(minutes) = fields.reminder_minutes {
+            lines.push("BEGIN:VALARM".to_string());
+            lines.push("ACTION:DISPLAY".to_string());
+            lines.push(format!("DESCRIPTION:{}", ics_escape(&fields.title)));
+            lines.push(format!("TRIGGER:-PT{}M", minutes));
+            lines.push("END:VALARM".to_string());
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+// ============================================================================
+// Reminders
+// ============================================================================
+
+/// Spawn the background loop that emits `calendar-reminder` events
+pub fn start_reminders(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_check = Local::now().timestamp();
+
+        loop {
+            std::thread::sleep(config::poll_interval(&app, REMINDER_POLL));
+            let now = Local::now().timestamp();
+            let state = app.state::<CalendarState>();
+            let events = state.0.lock().expect("calendar lock").clone();
+
+            for event in &events {
+                let Some(minutes) = event.fields.reminder_minutes else { continue };
+                let lead = minutes * 60;
+
+                // Occurrences whose reminder time fell inside (last_check, now]
+                for occurrence in occurrences_in(event, last_check + lead, now + lead + 1) {
+                    let remind_at = occurrence.start - lead;
+                    if remind_at > last_check && remind_at <= now {
+                        events::publish(
+                            &app,
+                            "calendar-reminder",
+                            ReminderPayload {
+                                event_id: event.id.clone(),
+                                title: occurrence.title,
+                                location: occurrence.location,
+                                start: occurrence.start,
+                                minutes_before: minutes,
+                            },
+                        );
+                    }
+                }
+            }
+
+            last_check = now;
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+fn validate(event: &EventInput) -> Result<(), String> {
+    if event.title.trim().is_empty() {
+        return Err("Event needs a title".to_string());
+    }
+    if event.end < event.start {
+        return Err("Event ends before it starts".to_string());
+    }
+    if event.recurrence.as_ref().is_some_and(|rule| !(1..=MAX_INTERVAL).contains(&rule.interval)) {
+        return Err(format!("A recurrence interval is 1 to {}", MAX_INTERVAL));
+    }
+    Ok(())
+}
+
+/// List stored events (not expanded)
+#[tauri::command]
+pub fn list_events(state: State<'_, CalendarState>) -> Vec<CalendarEvent> {
+    state.0.lock().expect("calendar lock").clone()
+}
+
+/// Get every occurrence overlapping `[from, to)`, recurring events expanded
+#[tauri::command]
+pub fn get_events(state: State<'_, CalendarState>, from: i64, to: i64) -> Vec<EventOccurrence> {
+    let events = state.0.lock().expect("calendar lock");
+    let mut occurrences: Vec<EventOccurrence> = events
+        .iter()
+        .flat_map(|event| occurrences_in(event, from, to))
+        .collect();
+
+    occurrences.sort_by_key(|occurrence| occurrence.start);
+    occurrences
+}
+
+/// Create an event
+#[tauri::command]
+pub fn create_event(
+    app: AppHandle,
+    state: State<'_, CalendarState>,
+    event: EventInput,
+) -> Result<CalendarEvent, KioskError> {
+    validate(&event)?;
+    let id = uuid::Uuid::new_v4().to_string();
+    let event = CalendarEvent {
+        uid: format!("{}@kiosk", id),
+        id,
+        fields: event,
+    };
+
+    let mut events = state.0.lock().expect("calendar lock");
+    events.push(event.clone());
+    store::save(&app, CALENDAR_FILE, &*events)?;
+    Ok(event)
+}
+
+/// Replace an event's fields
+#[tauri::command]
+pub fn update_event(
+    app: AppHandle,
+    state: State<'_, CalendarState>,
+    id: String,
+    event: EventInput,
+) -> Result<CalendarEvent, KioskError> {
+    validate(&event)?;
+    let mut events = state.0.lock().expect("calendar lock");
+    let existing = events
+        .iter_mut()
+        .find(|existing| existing.id == id)
+        .ok_or_else(|| format!("Event not found: {}", id))?;
+
+    existing.fields = event;
+    let updated = existing.clone();
+    store::save(&app, CALENDAR_FILE, &*events)?;
+    Ok(updated)
+}
+
+/// Delete an event and all of its occurrences
+#[tauri::command]
+pub fn delete_event(app: AppHandle, state: State<'_, CalendarState>, id: String) -> Result<(), KioskError> {
+    let mut events = state.0.lock().expect("calendar lock");
+    let before = events.len();
+    events.retain(|event| event.id != id);
+    if events.len() == before {
+        return Err(KioskError::not_found(format!("Event not found: {}", id)));
+    }
+    Ok(store::save(&app, CALENDAR_FILE, &*events)?)
+}
+
+/// Import events from an .ics file. Events with a known UID are updated.
+#[tauri::command]
+pub fn import_ics(app: AppHandle, state: State<'_, CalendarState>, path: String) -> Result<usize, KioskError> {
+    let text = fs::read_to_string(vfs::resolve(&app, &path, Access::Read)?).map_err(|e| e.to_string())?;
+    let parsed = parse_ics(&text);
+    let count = parsed.len();
+
+    let mut events = state.0.lock().expect("calendar lock");
+    for (uid, fields) in parsed {
+        match events.iter_mut().find(|event| !uid.is_empty() && event.uid == uid) {
+            Some(existing) => existing.fields = fields,
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                events.push(CalendarEvent {
+                    uid: if uid.is_empty() { format!("{}@kiosk", id) } else { uid },
+                    id,
+                    fields,
+                });
+            }
+        }
+    }
+
+    store::save(&app, CALENDAR_FILE, &*events)?;
+    let _ = recents::record(&app, "calendar", &path);
+    Ok(count)
+}
+
+/// Export all events to an .ics file
+#[tauri::command]
+pub fn export_ics(app: AppHandle, state: State<'_, CalendarState>, path: String) -> Result<usize, KioskError> {
+    let events = state.0.lock().expect("calendar lock");
+    let text = to_ics(&events);
+    fs::write(vfs::resolve_write(&app, &path, text.len() as u64)?, text).map_err(|e| e.to_string())?;
+    let _ = recents::record(&app, "calendar", &path);
+    Ok(events.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recurring(frequency: Frequency, interval: u32, by_day: &[&str]) -> EventInput {
+        EventInput {
+            title: "Standup".to_string(),
+            location: String::new(),
+            description: String::new(),
+            start: 1_700_000_000,
+            end: 1_700_001_800,
+            all_day: false,
+            recurrence: Some(Recurrence {
+                frequency,
+                interval,
+                count: None,
+                until: None,
+                by_day: by_day.iter().map(|day| day.to_string()).collect(),
+            }),
+            reminder_minutes: None,
+        }
+    }
+
+    #[test]
+    fn rrule_interval_is_bounded() {
+        assert_eq!(parse_rrule("FREQ=DAILY;INTERVAL=2").map(|rule| rule.interval), Some(2));
+        assert!(parse_rrule("FREQ=DAILY;INTERVAL=0").is_none());
+        assert!(parse_rrule("FREQ=DAILY;INTERVAL=1001").is_none());
+        assert!(parse_rrule("FREQ=DAILY;INTERVAL=4294967295").is_none());
+    }
+
+    #[test]
+    fn validate_bounds_the_interval() {
+        assert!(validate(&recurring(Frequency::Weekly, 1, &[])).is_ok());
+        assert!(validate(&recurring(Frequency::Weekly, MAX_INTERVAL, &[])).is_ok());
+        assert!(validate(&recurring(Frequency::Weekly, 0, &[])).is_err());
+        assert!(validate(&recurring(Frequency::Weekly, MAX_INTERVAL + 1, &[])).is_err());
+    }
+
+    #[test]
+    fn expansion_follows_the_interval() {
+        let mut event = recurring(Frequency::Daily, 2, &[]);
+        event.recurrence.as_mut().expect("recurring").count = Some(3);
+        let starts = occurrence_starts(&event, i64::MAX);
+        assert_eq!(starts, vec![event.start, event.start + 2 * 86_400, event.start + 4 * 86_400]);
+    }
+
+    #[test]
+    fn out_of_range_intervals_end_the_series() {
+        let frequencies = [Frequency::Daily, Frequency::Weekly, Frequency::Monthly, Frequency::Yearly];
+        for frequency in frequencies {
+            let event = recurring(frequency, u32::MAX, &[]);
+            assert_eq!(occurrence_starts(&event, i64::MAX), vec![event.start]);
+        }
+        let event = recurring(Frequency::Weekly, u32::MAX, &["MO", "TU", "WE", "TH", "FR", "SA", "SU"]);
+        assert!(occurrence_starts(&event, i64::MAX).len() <= 7);
+    }
+}
@@ -0,0 +1,571 @@
+//! Cash handling
+//!
+//! ccTalk drivers for coin acceptors, bill validators and payout hoppers on
+//! a shared serial bus. A background poller credits inserted cash (emitting
+//! `cash-inserted`), holds bills in escrow until the kiosk accepts or returns
+//! them (`cash-escrow`), and hoppers pay out change.
+//!
+//! Change is owed out of the session: the money inserted, less the sale
+//! total and change already paid. Paying out more than that needs an
+//! operator, and configuring the hardware, switching acceptance and
+//! closing a session need a supervisor.
+//!
+//! Only ccTalk is implemented. MDB peripherals are left to a separate
+//! driver: they need a 9-bit bus adapter rather than a plain serial port,
+//! and none is supported here.
+
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::lazy::{self, Subsystem};
+use crate::{events, store};
+
+const CONFIG_FILE: &str = "cash.json";
+
+/// ccTalk address of the kiosk (the bus master)
+const HOST_ADDRESS: u8 = 1;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const REPLY_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Longest a hopper may take to finish a payout
+const PAYOUT_TIMEOUT: Duration = Duration::from_secs(60);
+
+// ccTalk headers
+const HEADER_ACK: u8 = 0;
+const HEADER_SIMPLE_POLL: u8 = 254;
+const HEADER_REQUEST_SERIAL: u8 = 242;
+const HEADER_MODIFY_INHIBITS: u8 = 231;
+const HEADER_READ_COIN_CREDITS: u8 = 229;
+const HEADER_MODIFY_MASTER_INHIBIT: u8 = 228;
+const HEADER_REQUEST_COIN_ID: u8 = 184;
+const HEADER_DISPENSE_COINS: u8 = 167;
+const HEADER_HOPPER_STATUS: u8 = 166;
+const HEADER_ENABLE_HOPPER: u8 = 164;
+const HEADER_READ_BILL_EVENTS: u8 = 159;
+const HEADER_REQUEST_BILL_ID: u8 = 157;
+const HEADER_ROUTE_BILL: u8 = 154;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HopperConfig {
+    pub address: u8,
+    /// Value of each coin the hopper pays out, in minor units
+    pub coin_value: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CashConfig {
+    pub port: String,
+    pub baud_rate: u32,
+    /// Most ccTalk adapters echo transmitted bytes back on the shared line
+    pub echo: bool,
+    pub coin_acceptor: Option<u8>,
+    pub bill_validator: Option<u8>,
+    /// Hold bills in escrow until `accept_escrow`/`return_escrow`
+    pub escrow_bills: bool,
+    /// Value of each coin/bill position (position 1 first), in minor units.
+    /// Empty lists are filled from the device's coin/bill ids.
+    pub coin_values: Vec<i64>,
+    pub bill_values: Vec<i64>,
+    pub hoppers: Vec<HopperConfig>,
+}
+
+impl Default for CashConfig {
+    fn default() -> Self {
+        CashConfig {
+            port: "/dev/ttyUSB0".to_string(),
+            baud_rate: 9600,
+            echo: true,
+            coin_acceptor: Some(2),
+            bill_validator: Some(40),
+            escrow_bills: true,
+            coin_values: Vec::new(),
+            bill_values: Vec::new(),
+            hoppers: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CashKind {
+    Coin,
+    Bill,
+}
+
+/// Emitted as `cash-inserted` when money is credited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashInserted {
+    pub kind: CashKind,
+    pub value: i64,
+    pub total: i64,
+}
+
+/// Money credited in the current session, plus any bill held in escrow
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CashSession {
+    pub accepting: bool,
+    pub inserted: i64,
+    pub escrow: Option<i64>,
+    /// Price of the current sale, set with `set_sale_total`
+    pub sale_total: i64,
+    /// Change already paid out this session
+    pub dispensed: i64,
+}
+
+impl CashSession {
+    /// Change the customer is still owed
+    fn change_due(&self) -> i64 {
+        (self.inserted - self.sale_total - self.dispensed).max(0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeResult {
+    pub requested: i64,
+    pub dispensed: i64,
+    pub shortfall: i64,
+    /// Why the payout stopped early, when the bus failed partway
+    pub error: Option<String>,
+}
+
+/// Last seen event counters and resolved position values
+#[derive(Default)]
+struct DeviceCache {
+    coin_counter: Option<u8>,
+    bill_counter: Option<u8>,
+    coin_values: Vec<i64>,
+    bill_values: Vec<i64>,
+    /// Bill type currently held in escrow
+    escrow_type: Option<u8>,
+}
+
+pub struct CashState {
+    config: Mutex<CashConfig>,
+    session: Mutex<CashSession>,
+    bus: Mutex<Option<CcTalkBus>>,
+    devices: Mutex<DeviceCache>,
+    accepting: AtomicBool,
+}
+
+impl Subsystem for CashState {
+    const NAME: &'static str = "cash";
+
+    fn load(app: &AppHandle) -> Self {
+        CashState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            session: Mutex::new(CashSession::default()),
+            bus: Mutex::new(None),
+            devices: Mutex::new(DeviceCache::default()),
+            accepting: AtomicBool::new(false),
+        }
+    }
+
+    fn start(app: AppHandle) {
+        start_cash(app);
+    }
+}
+
+impl CashState {
+    /// Run `f` against the bus, opening the serial port on first use
+    fn with_bus<T>(&self, f: impl FnOnce(&mut CcTalkBus) -> Result<T, String>) -> Result<T, String> {
+        let mut bus = self.bus.lock().expect("cash bus lock");
+        if bus.is_none() {
+            let config = self.config.lock().expect("cash config lock").clone();
+            *bus = Some(CcTalkBus::open(&config)?);
+        }
+        let result = f(bus.as_mut().expect("bus opened"));
+        if result.is_err() {
+            // Reopen next time in case the adapter was unplugged
+            *bus = None;
+        }
+        result
+    }
+}
+
+// ============================================================================
+// ccTalk
+// ============================================================================
+
+struct CcTalkBus {
+    port: Box<dyn serialport::SerialPort>,
+    echo: bool,
+}
+
+impl CcTalkBus {
+    fn open(config: &CashConfig) -> Result<Self, String> {
+        let port = serialport::new(&config.port, config.baud_rate)
+            .timeout(REPLY_TIMEOUT)
+            .open()
+            .map_err(|e| format!("Cannot open {}: {}", config.port, e))?;
+        Ok(CcTalkBus { port, echo: config.echo })
+    }
+
+    fn read_exact(&mut self, length: usize) -> Result<Vec<u8>, String> {
+        let mut buffer = vec![0u8; length];
+        self.port.read_exact(&mut buffer).map_err(|e| match e.kind() {
+            ErrorKind::TimedOut | ErrorKind::WouldBlock => "Cash device did not respond".to_string(),
+            _ => e.to_string(),
+        })?;
+        Ok(buffer)
+    }
+
+    /// Send a command and return the reply data
+    fn request(&mut self, address: u8, header: u8, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut packet = vec![address, data.len() as u8, HOST_ADDRESS, header];
+        packet.extend(data);
+        let sum = packet.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        packet.push(0u8.wrapping_sub(sum));
+
+        let _ = self.port.clear(serialport::ClearBuffer::Input);
+        self.port.write_all(&packet).map_err(|e| e.to_string())?;
+        if self.echo {
+            self.read_exact(packet.len())?;
+        }
+
+        let head = self.read_exact(2)?;
+        let rest = self.read_exact(head[1] as usize + 3)?;
+        let reply: Vec<u8> = head.iter().chain(rest.iter()).copied().collect();
+        if reply.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) != 0 {
+            return Err("ccTalk checksum error".to_string());
+        }
+        if reply[3] != HEADER_ACK {
+            return Err(format!("Device {} rejected command {} (NAK)", address, header));
+        }
+        Ok(reply[4..reply.len() - 1].to_vec())
+    }
+
+    fn set_accepting(&mut self, address: u8, enabled: bool) -> Result<(), String> {
+        let mask = if enabled { 0xFF } else { 0x00 };
+        self.request(address, HEADER_MODIFY_INHIBITS, &[mask, mask])?;
+        self.request(address, HEADER_MODIFY_MASTER_INHIBIT, &[enabled as u8])?;
+        Ok(())
+    }
+}
+
+/// Pull the value digits out of a coin or bill id such as "EU200A"
+fn id_value(id: &[u8]) -> i64 {
+    let digits: String = id.iter().map(|b| *b as char).filter(char::is_ascii_digit).collect();
+    digits.parse().unwrap_or(0)
+}
+
+/// Resolve position values from the device ids where not configured
+fn resolve_values(bus: &mut CcTalkBus, address: u8, header: u8, scale: i64) -> Vec<i64> {
+    (1..=16u8)
+        .map(|position| {
+            bus.request(address, header, &[position])
+                .map(|id| id_value(&id) * scale)
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+/// New events in a buffered-event reply since `last` (oldest first)
+fn new_events(reply: &[u8], last: &mut Option<u8>) -> Vec<(u8, u8)> {
+    let Some(&counter) = reply.first() else { return Vec::new() };
+    let previous = last.replace(counter);
+    let Some(previous) = previous else { return Vec::new() };
+    if counter == previous || counter == 0 {
+        return Vec::new();
+    }
+
+    // The counter runs 1..=255 and skips 0 when it wraps
+    let mut count = (counter as i32 - previous as i32).rem_euclid(256) as usize;
+    if counter < previous {
+        count -= 1;
+    }
+    let pairs: Vec<(u8, u8)> = reply[1..].chunks(2).filter(|pair| pair.len() == 2).map(|pair| (pair[0], pair[1])).collect();
+    pairs.into_iter().take(count.min(5)).rev().collect()
+}
+
+fn credit(app: &AppHandle, state: &CashState, kind: CashKind, value: i64) {
+    if value <= 0 {
+        return;
+    }
+    let mut session = state.session.lock().expect("cash session lock");
+    session.inserted += value;
+    events::publish(
+        app,
+        "cash-inserted",
+        CashInserted {
+            kind,
+            value,
+            total: session.inserted,
+        },
+    );
+}
+
+fn poll(app: &AppHandle, state: &CashState) -> Result<(), String> {
+    let config = state.config.lock().expect("cash config lock").clone();
+    let mut coins = Vec::new();
+    let mut bills = Vec::new();
+
+    state.with_bus(|bus| {
+        let mut devices = state.devices.lock().expect("cash devices lock");
+        if let Some(address) = config.coin_acceptor {
+            let reply = bus.request(address, HEADER_READ_COIN_CREDITS, &[])?;
+            coins = new_events(&reply, &mut devices.coin_counter);
+        }
+        if let Some(address) = config.bill_validator {
+            let reply = bus.request(address, HEADER_READ_BILL_EVENTS, &[])?;
+            bills = new_events(&reply, &mut devices.bill_counter);
+        }
+        Ok(())
+    })?;
+
+    let devices = state.devices.lock().expect("cash devices lock");
+    let coin_values = if config.coin_values.is_empty() { &devices.coin_values } else { &config.coin_values };
+    let bill_values = if config.bill_values.is_empty() { &devices.bill_values } else { &config.bill_values };
+    let value_of = |values: &Vec<i64>, position: u8| values.get(position as usize - 1).copied().unwrap_or(0);
+
+    // Result A is the coin position; 0 means B holds an error code
+    let coin_credits: Vec<i64> = coins
+        .iter()
+        .filter(|(position, _)| *position > 0)
+        .map(|(position, _)| value_of(coin_values, *position))
+        .collect();
+
+    let mut bill_credits = Vec::new();
+    let mut escrowed = None;
+    for (bill_type, status) in bills.iter().filter(|(bill_type, _)| *bill_type > 0) {
+        match status {
+            0 => bill_credits.push(value_of(bill_values, *bill_type)),
+            1 => escrowed = Some((*bill_type, value_of(bill_values, *bill_type))),
+            _ => {}
+        }
+    }
+    drop(devices);
+
+    for value in coin_credits {
+        credit(app, state, CashKind::Coin, value);
+    }
+    for value in bill_credits {
+        credit(app, state, CashKind::Bill, value);
+    }
+
+    if let Some((bill_type, value)) = escrowed {
+        if config.escrow_bills {
+            state.devices.lock().expect("cash devices lock").escrow_type = Some(bill_type);
+            state.session.lock().expect("cash session lock").escrow = Some(value);
+            events::publish(app, "cash-escrow", value);
+        } else {
+            route_bill(state, true)?;
+        }
+    }
+    Ok(())
+}
+
+/// Stack (accept) or return the bill held in escrow
+fn route_bill(state: &CashState, accept: bool) -> Result<Option<i64>, String> {
+    let address = state
+        .config
+        .lock()
+        .expect("cash config lock")
+        .bill_validator
+        .ok_or("No bill validator configured")?;
+    state.with_bus(|bus| bus.request(address, HEADER_ROUTE_BILL, &[accept as u8]))?;
+
+    state.devices.lock().expect("cash devices lock").escrow_type = None;
+    // An accepted bill is credited when the validator reports it stacked
+    Ok(state.session.lock().expect("cash session lock").escrow.take())
+}
+
+/// Pay `count` coins from one hopper, keeping `paid` at how many have come
+/// out so far, so a failure partway still accounts for them
+fn payout(bus: &mut CcTalkBus, address: u8, count: u8, paid: &mut u8) -> Result<(), String> {
+    bus.request(address, HEADER_ENABLE_HOPPER, &[165])?;
+    let serial = bus.request(address, HEADER_REQUEST_SERIAL, &[])?;
+    let mut data = serial.into_iter().take(3).collect::<Vec<_>>();
+    data.push(count);
+    bus.request(address, HEADER_DISPENSE_COINS, &data)?;
+
+    let started = Instant::now();
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        // [event counter, coins remaining, paid, unpaid]
+        let status = bus.request(address, HEADER_HOPPER_STATUS, &[])?;
+        let remaining = status.get(1).copied().unwrap_or(0);
+        *paid = status.get(2).copied().unwrap_or(0);
+        if remaining == 0 || started.elapsed() > PAYOUT_TIMEOUT {
+            return Ok(());
+        }
+    }
+}
+
+/// Spawn the background poller that credits inserted cash
+fn start_cash(app: AppHandle) {
+    std::thread::spawn(move || {
+        let Ok(state) = lazy::get::<CashState>(&app) else {
+            return;
+        };
+        loop {
+            if state.accepting.load(Ordering::SeqCst) {
+                let _ = poll(&app, state);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the cash hardware settings
+#[tauri::command]
+pub fn get_cash_config(app: AppHandle) -> Result<CashConfig, KioskError> {
+    let state = lazy::get::<CashState>(&app)?;
+    Ok(state.config.lock().expect("cash config lock").clone())
+}
+
+/// Update the cash hardware settings (the bus is reopened on next use; supervisor)
+#[tauri::command]
+pub fn set_cash_config(app: AppHandle, auth: State<'_, AuthState>, config: CashConfig) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let state = lazy::get::<CashState>(&app)?;
+    let mut current = state.config.lock().expect("cash config lock");
+    *current = config;
+    store::save(&app, CONFIG_FILE, &*current)?;
+    drop(current);
+
+    *state.bus.lock().expect("cash bus lock") = None;
+    *state.devices.lock().expect("cash devices lock") = DeviceCache::default();
+    Ok(())
+}
+
+/// Start or stop accepting coins and bills (supervisor)
+#[tauri::command]
+pub fn set_cash_accepting(app: AppHandle, auth: State<'_, AuthState>, enabled: bool) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let state = lazy::get::<CashState>(&app)?;
+    let config = state.config.lock().expect("cash config lock").clone();
+
+    state.with_bus(|bus| {
+        let mut devices = state.devices.lock().expect("cash devices lock");
+        for address in config.coin_acceptor.iter().chain(config.bill_validator.iter()) {
+            bus.request(*address, HEADER_SIMPLE_POLL, &[])?;
+        }
+        if enabled {
+            if let (Some(address), true) = (config.coin_acceptor, devices.coin_values.is_empty()) {
+                devices.coin_values = resolve_values(bus, address, HEADER_REQUEST_COIN_ID, 1);
+            }
+            // Bill ids carry whole currency units
+            if let (Some(address), true) = (config.bill_validator, devices.bill_values.is_empty()) {
+                devices.bill_values = resolve_values(bus, address, HEADER_REQUEST_BILL_ID, 100);
+            }
+        }
+        for address in config.coin_acceptor.iter().chain(config.bill_validator.iter()) {
+            bus.set_accepting(*address, enabled)?;
+        }
+        Ok(())
+    })?;
+
+    state.accepting.store(enabled, Ordering::SeqCst);
+    state.session.lock().expect("cash session lock").accepting = enabled;
+    Ok(())
+}
+
+/// Get the money inserted so far and any bill in escrow
+#[tauri::command]
+pub fn get_inserted_amount(app: AppHandle) -> Result<CashSession, KioskError> {
+    let state = lazy::get::<CashState>(&app)?;
+    Ok(state.session.lock().expect("cash session lock").clone())
+}
+
+/// Set the price of the current sale, which change is worked out against
+#[tauri::command]
+pub fn set_sale_total(app: AppHandle, total: i64) -> Result<CashSession, KioskError> {
+    let state = lazy::get::<CashState>(&app)?;
+    if total < 0 {
+        return Err(KioskError::invalid("Sale total cannot be negative"));
+    }
+    let mut session = state.session.lock().expect("cash session lock");
+    session.sale_total = total;
+    Ok(session.clone())
+}
+
+/// Finish a sale: return the inserted total and start counting from zero
+/// (supervisor)
+#[tauri::command]
+pub fn reset_inserted_amount(app: AppHandle, auth: State<'_, AuthState>) -> Result<i64, KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let state = lazy::get::<CashState>(&app)?;
+    let mut session = state.session.lock().expect("cash session lock");
+    let inserted = std::mem::take(&mut session.inserted);
+    session.sale_total = 0;
+    session.dispensed = 0;
+    Ok(inserted)
+}
+
+/// Stack the bill held in escrow
+#[tauri::command]
+pub fn accept_escrow(app: AppHandle) -> Result<Option<i64>, KioskError> {
+    let state = lazy::get::<CashState>(&app)?;
+    Ok(route_bill(state, true)?)
+}
+
+/// Hand the bill held in escrow back to the customer
+#[tauri::command]
+pub fn return_escrow(app: AppHandle) -> Result<Option<i64>, KioskError> {
+    let state = lazy::get::<CashState>(&app)?;
+    Ok(route_bill(state, false)?)
+}
+
+/// Pay out change from the hoppers, largest coins first. More than the
+/// change due in the session needs an operator. A bus failure partway
+/// returns what was paid so far, with the error.
+#[tauri::command(async)]
+pub fn dispense_change(app: AppHandle, auth: State<'_, AuthState>, amount: i64) -> Result<ChangeResult, KioskError> {
+    let state = lazy::get::<CashState>(&app)?;
+    if amount <= 0 {
+        return Err(KioskError::invalid("Amount must be positive"));
+    }
+    if amount > state.session.lock().expect("cash session lock").change_due() {
+        auth::require(&auth, Role::Operator)?;
+    }
+    let mut hoppers = state.config.lock().expect("cash config lock").hoppers.clone();
+    if hoppers.is_empty() {
+        return Err("No hoppers configured".into());
+    }
+    hoppers.sort_by_key(|hopper| std::cmp::Reverse(hopper.coin_value));
+
+    let mut remaining = amount;
+    let mut error = None;
+    'hoppers: for hopper in hoppers.iter().filter(|hopper| hopper.coin_value > 0) {
+        let mut wanted = remaining / hopper.coin_value;
+        while wanted > 0 {
+            let batch = wanted.min(255) as u8;
+            let mut paid = 0;
+            let result = state.with_bus(|bus| payout(bus, hopper.address, batch, &mut paid));
+            remaining -= paid as i64 * hopper.coin_value;
+            if let Err(e) = result {
+                error = Some(e);
+                break 'hoppers;
+            }
+            // A short payout means the hopper is empty or jammed; try the next one
+            if paid < batch {
+                break;
+            }
+            wanted -= batch as i64;
+        }
+    }
+
+    state.session.lock().expect("cash session lock").dispensed += amount - remaining;
+    Ok(ChangeResult {
+        requested: amount,
+        dispensed: amount - remaining,
+        shortfall: remaining,
+        error,
+    })
+}
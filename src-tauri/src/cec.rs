@@ -0,0 +1,224 @@
+//! HDMI-CEC TV control
+//!
+//! Turns the attached television on and off and switches its input over
+//! HDMI-CEC, so Pi-driven signage can follow a schedule. Commands go through
+//! libcec's `cec-client` in single-command mode. A watcher polls the TV's
+//! power status and publishes `tv-state` when it changes, including when
+//! someone uses the TV's own remote.
+
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{config, events};
+
+const CEC_CLIENT: &str = "cec-client";
+
+/// Logical address of the TV
+const TV: &str = "0";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Highest HDMI input number a TV is likely to have
+const MAX_INPUT: u8 = 15;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TvPower {
+    On,
+    Standby,
+    TurningOn,
+    TurningOff,
+    Unknown,
+}
+
+/// Payload of `tv-state`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TvState {
+    /// A CEC adapter was found
+    pub available: bool,
+    pub power: TvPower,
+    /// HDMI input last selected through the kiosk; 0 means the kiosk itself
+    pub input: Option<u8>,
+    pub error: Option<String>,
+}
+
+pub struct CecState {
+    tv: Mutex<TvState>,
+    /// cec-client holds the adapter exclusively, so calls take turns
+    adapter: Mutex<()>,
+}
+
+impl Default for CecState {
+    fn default() -> Self {
+        CecState {
+            tv: Mutex::new(TvState {
+                available: false,
+                power: TvPower::Unknown,
+                input: None,
+                error: None,
+            }),
+            adapter: Mutex::new(()),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Send one command to the adapter and return cec-client's output
+fn cec(state: &CecState, command: &str) -> Result<String, String> {
+    let _adapter = state.adapter.lock().expect("cec adapter lock");
+    // -s: single command from stdin, -d 1: errors only
+    let mut child = Command::new(CEC_CLIENT)
+        .args(["-s", "-d", "1"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {} (install libcec): {}", CEC_CLIENT, e))?;
+    child
+        .stdin
+        .take()
+        .ok_or("cec-client stdin unavailable")?
+        .write_all(format!("{}\n", command).as_bytes())
+        .map_err(|e| e.to_string())?;
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    let text = String::from_utf8_lossy(&output.stdout).to_string();
+    if text.contains("could not open a connection") || text.contains("no serial port given") {
+        return Err("No CEC adapter found".to_string());
+    }
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", CEC_CLIENT, text.trim()));
+    }
+    Ok(text)
+}
+
+fn parse_power(output: &str) -> TvPower {
+    let Some(status) = output.lines().find_map(|line| line.trim().strip_prefix("power status:")) else {
+        return TvPower::Unknown;
+    };
+    match status.trim() {
+        "on" => TvPower::On,
+        "standby" => TvPower::Standby,
+        "in transition from standby to on" => TvPower::TurningOn,
+        "in transition from on to standby" => TvPower::TurningOff,
+        _ => TvPower::Unknown,
+    }
+}
+
+/// Record the TV's state, publishing `tv-state` when it changed
+fn update(app: &AppHandle, change: impl FnOnce(&mut TvState)) -> TvState {
+    let state = app.state::<CecState>();
+    let mut tv = state.tv.lock().expect("tv state lock");
+    let before = tv.clone();
+    change(&mut tv);
+    let after = tv.clone();
+    drop(tv);
+    if after != before {
+        events::publish(app, "tv-state", &after);
+    }
+    after
+}
+
+fn refresh(app: &AppHandle) -> TvState {
+    let result = cec(&app.state::<CecState>(), &format!("pow {}", TV));
+    update(app, |tv| match result {
+        Ok(output) => {
+            tv.available = true;
+            tv.power = parse_power(&output);
+            tv.error = None;
+        }
+        Err(e) => {
+            tv.available = false;
+            tv.power = TvPower::Unknown;
+            tv.error = Some(e);
+        }
+    })
+}
+
+/// Run a TV command, then record the expected state
+fn control(app: &AppHandle, command: &str, change: impl FnOnce(&mut TvState)) -> Result<TvState, KioskError> {
+    if let Err(e) = cec(&app.state::<CecState>(), command) {
+        update(app, |tv| tv.error = Some(e.clone()));
+        return Err(KioskError::new(ErrorKind::Failed, e));
+    }
+    Ok(update(app, |tv| {
+        tv.available = true;
+        tv.error = None;
+        change(tv);
+    }))
+}
+
+/// Wake the TV as the kiosk's display, or put it into standby
+pub(crate) fn set_power(app: &AppHandle, on: bool) -> Result<TvState, KioskError> {
+    if on {
+        control(app, &format!("on {}", TV), |tv| tv.power = TvPower::TurningOn)?;
+        control(app, "as", |tv| tv.input = Some(0))
+    } else {
+        control(app, &format!("standby {}", TV), |tv| tv.power = TvPower::TurningOff)
+    }
+}
+
+/// Poll the TV's power status in the background
+pub fn start_cec(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = refresh(&app);
+        if !state.available && state.error.as_deref().is_some_and(|e| e.starts_with("Failed to run")) {
+            // libcec is not installed; nothing will change until a restart
+            return;
+        }
+        std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Wake the TV and make the kiosk its active source
+#[tauri::command]
+pub fn tv_power_on(app: AppHandle, auth: State<'_, AuthState>) -> Result<TvState, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    set_power(&app, true)
+}
+
+/// Put the TV into standby
+#[tauri::command]
+pub fn tv_power_off(app: AppHandle, auth: State<'_, AuthState>) -> Result<TvState, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    set_power(&app, false)
+}
+
+/// Switch the TV to HDMI input `input` (1-15), or to the kiosk itself when
+/// `input` is 0
+#[tauri::command]
+pub fn set_tv_input(app: AppHandle, auth: State<'_, AuthState>, input: u8) -> Result<TvState, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    if input > MAX_INPUT {
+        return Err(KioskError::invalid(format!("No HDMI input {}", input)));
+    }
+    let command = if input == 0 {
+        "as".to_string()
+    } else {
+        // Set Stream Path to the input's physical address, X.0.0.0
+        format!("tx 1F:86:{:X}0:00", input)
+    };
+    control(&app, &command, |tv| tv.input = Some(input))
+}
+
+/// Ask the TV for its power status now
+#[tauri::command]
+pub fn get_tv_state(app: AppHandle) -> TvState {
+    refresh(&app)
+}
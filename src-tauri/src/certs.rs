@@ -0,0 +1,263 @@
+//! TLS certificates
+//!
+//! Custom CA bundles and a client certificate for mutual TLS, applied to every
+//! backend HTTP and IMAP connection through the shared TLS configuration.
+//! Certificates are kept as PEM in the data directory; client private keys
+//! are sealed in the keyring.
+
+use base64::Engine;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+use crate::{http, keyring, store};
+
+const CERTS_FILE: &str = "certificates.json";
+
+const CERTS_DIR: &str = "certificates";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CertKind {
+    /// Extra trusted root or intermediate
+    Ca,
+    /// Certificate with a private key, presented to servers
+    Client,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    pub id: String,
+    pub kind: CertKind,
+    pub subject: String,
+    pub issuer: String,
+    pub not_before: i64,
+    pub not_after: i64,
+    /// SHA-256 of the leaf certificate, colon-separated hex
+    pub fingerprint: String,
+    /// Number of certificates in the file (chain or bundle)
+    pub count: usize,
+    /// The client certificate currently presented to servers
+    #[serde(default)]
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CertStore {
+    certificates: Vec<CertificateInfo>,
+}
+
+pub struct CertState(Mutex<CertStore>);
+
+impl CertState {
+    pub fn load(app: &AppHandle) -> Self {
+        let certs: CertStore = store::load(app, CERTS_FILE);
+        // A broken client identity must not take down plain HTTPS
+        let _ = apply(app, &certs);
+        CertState(Mutex::new(certs))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn cert_file(id: &str) -> String {
+    format!("{}/{}.pem", CERTS_DIR, id)
+}
+
+fn key_name(id: &str) -> String {
+    format!("certs/{}", id)
+}
+
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn to_pem(certs: &[CertificateDer<'_>]) -> String {
+    let mut pem = String::new();
+    for cert in certs {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(cert.as_ref());
+        pem.push_str("-----BEGIN CERTIFICATE-----\n");
+        for line in encoded.as_bytes().chunks(64) {
+            pem.push_str(&String::from_utf8_lossy(line));
+            pem.push('\n');
+        }
+        pem.push_str("-----END CERTIFICATE-----\n");
+    }
+    pem
+}
+
+/// Certificates from PEM, or a single DER certificate
+fn parse_certs(data: &[u8]) -> Vec<CertificateDer<'static>> {
+    let certs: Vec<CertificateDer<'static>> = CertificateDer::pem_slice_iter(data).flatten().collect();
+    if certs.is_empty() && x509_parser::parse_x509_certificate(data).is_ok() {
+        return vec![CertificateDer::from(data.to_vec())];
+    }
+    certs
+}
+
+fn load_certs(app: &AppHandle, id: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let data = fs::read(store::data_path(app, &cert_file(id))?).map_err(|e| e.to_string())?;
+    Ok(parse_certs(&data))
+}
+
+/// Build and install the shared TLS configuration
+fn apply(app: &AppHandle, certs: &CertStore) -> Result<(), String> {
+    let mut roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    for info in certs.certificates.iter().filter(|info| info.kind == CertKind::Ca) {
+        for cert in load_certs(app, &info.id)? {
+            roots.add(cert).map_err(|e| format!("{}: {}", info.subject, e))?;
+        }
+    }
+
+    let builder = rustls::ClientConfig::builder().with_root_certificates(roots);
+    let config = match certs.certificates.iter().find(|info| info.active) {
+        Some(info) => {
+            let pem = keyring::get(app, &key_name(&info.id))?.ok_or("Client certificate key is missing")?;
+            let key = PrivateKeyDer::from_pem_slice(pem.as_bytes()).map_err(|e| e.to_string())?;
+            builder
+                .with_client_auth_cert(load_certs(app, &info.id)?, key)
+                .map_err(|e| e.to_string())?
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    http::set_tls_config(config);
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Import a CA bundle or a client certificate (PEM with its private key)
+#[tauri::command]
+pub fn import_certificate(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, CertState>,
+    path: String,
+) -> Result<CertificateInfo, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let data = fs::read(vfs::resolve(&app, &path, Access::Read)?).map_err(|e| e.to_string())?;
+    let certs = parse_certs(&data);
+    let leaf = certs.first().ok_or("No certificate found in file")?;
+    let (_, parsed) = x509_parser::parse_x509_certificate(leaf).map_err(|e| e.to_string())?;
+
+    let text = String::from_utf8_lossy(&data);
+    if text.contains("ENCRYPTED PRIVATE KEY") {
+        return Err(KioskError::invalid("Encrypted private keys are not supported; export the key without a passphrase"));
+    }
+    let has_key = PrivateKeyDer::from_pem_slice(&data).is_ok();
+
+    let mut current = state.0.lock().expect("cert state lock");
+    let fingerprint = fingerprint(leaf);
+    if current.certificates.iter().any(|info| info.fingerprint == fingerprint) {
+        return Err("Certificate is already installed".into());
+    }
+
+    let kind = if has_key { CertKind::Client } else { CertKind::Ca };
+    let info = CertificateInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        subject: parsed.subject().to_string(),
+        issuer: parsed.issuer().to_string(),
+        not_before: parsed.validity().not_before.timestamp(),
+        not_after: parsed.validity().not_after.timestamp(),
+        fingerprint,
+        count: certs.len(),
+        // The first client certificate becomes the active one
+        active: kind == CertKind::Client && !current.certificates.iter().any(|info| info.active),
+    };
+
+    fs::create_dir_all(store::data_path(&app, CERTS_DIR)?).map_err(|e| e.to_string())?;
+    fs::write(store::data_path(&app, &cert_file(&info.id))?, to_pem(&certs)).map_err(|e| e.to_string())?;
+    if has_key {
+        keyring::put(&app, &key_name(&info.id), &text)?;
+    }
+
+    let mut updated = current.clone();
+    updated.certificates.push(info.clone());
+    if let Err(e) = apply(&app, &updated) {
+        let _ = fs::remove_file(store::data_path(&app, &cert_file(&info.id))?);
+        let _ = keyring::remove(&app, &key_name(&info.id));
+        return Err(e.into());
+    }
+    store::save(&app, CERTS_FILE, &updated)?;
+    *current = updated;
+    Ok(info)
+}
+
+/// List installed CA and client certificates
+#[tauri::command]
+pub fn list_certificates(state: State<'_, CertState>) -> Vec<CertificateInfo> {
+    state.0.lock().expect("cert state lock").certificates.clone()
+}
+
+/// Remove an installed certificate and its key
+#[tauri::command]
+pub fn remove_certificate(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, CertState>,
+    id: String,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut current = state.0.lock().expect("cert state lock");
+    let mut updated = current.clone();
+    updated.certificates.retain(|info| info.id != id);
+    apply(&app, &updated)?;
+    store::save(&app, CERTS_FILE, &updated)?;
+    *current = updated;
+
+    let _ = fs::remove_file(store::data_path(&app, &cert_file(&id))?);
+    Ok(keyring::remove(&app, &key_name(&id))?)
+}
+
+/// Choose which client certificate to present, or none
+#[tauri::command]
+pub fn set_client_certificate(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, CertState>,
+    id: Option<String>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut current = state.0.lock().expect("cert state lock");
+    if let Some(id) = &id {
+        if !current
+            .certificates
+            .iter()
+            .any(|info| &info.id == id && info.kind == CertKind::Client)
+        {
+            return Err(KioskError::invalid("Not a client certificate"));
+        }
+    }
+
+    let mut updated = current.clone();
+    for info in &mut updated.certificates {
+        info.active = id.as_ref() == Some(&info.id);
+    }
+    apply(&app, &updated)?;
+    store::save(&app, CERTS_FILE, &updated)?;
+    *current = updated;
+    Ok(())
+}
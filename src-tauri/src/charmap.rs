@@ -0,0 +1,150 @@
+//! Character map data
+//!
+//! Reports Unicode blocks and which code points an installed font actually
+//! covers (from the fontconfig charset), so the Character Map accessory only
+//! shows glyphs the font can render.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::KioskError;
+use crate::fonts;
+
+/// Unicode blocks offered by the Character Map, as (name, first, last)
+const UNICODE_BLOCKS: &[(&str, u32, u32)] = &[
+    ("Basic Latin", 0x0000, 0x007F),
+    ("Latin-1 Supplement", 0x0080, 0x00FF),
+    ("Latin Extended-A", 0x0100, 0x017F),
+    ("Latin Extended-B", 0x0180, 0x024F),
+    ("IPA Extensions", 0x0250, 0x02AF),
+    ("Spacing Modifier Letters", 0x02B0, 0x02FF),
+    ("Combining Diacritical Marks", 0x0300, 0x036F),
+    ("Greek and Coptic", 0x0370, 0x03FF),
+    ("Cyrillic", 0x0400, 0x04FF),
+    ("Cyrillic Supplement", 0x0500, 0x052F),
+    ("Armenian", 0x0530, 0x058F),
+    ("Hebrew", 0x0590, 0x05FF),
+    ("Arabic", 0x0600, 0x06FF),
+    ("Devanagari", 0x0900, 0x097F),
+    ("Thai", 0x0E00, 0x0E7F),
+    ("Georgian", 0x10A0, 0x10FF),
+    ("Hangul Jamo", 0x1100, 0x11FF),
+    ("Latin Extended Additional", 0x1E00, 0x1EFF),
+    ("Greek Extended", 0x1F00, 0x1FFF),
+    ("General Punctuation", 0x2000, 0x206F),
+    ("Superscripts and Subscripts", 0x2070, 0x209F),
+    ("Currency Symbols", 0x20A0, 0x20CF),
+    ("Letterlike Symbols", 0x2100, 0x214F),
+    ("Number Forms", 0x2150, 0x218F),
+    ("Arrows", 0x2190, 0x21FF),
+    ("Mathematical Operators", 0x2200, 0x22FF),
+    ("Miscellaneous Technical", 0x2300, 0x23FF),
+    ("Enclosed Alphanumerics", 0x2460, 0x24FF),
+    ("Box Drawing", 0x2500, 0x257F),
+    ("Block Elements", 0x2580, 0x259F),
+    ("Geometric Shapes", 0x25A0, 0x25FF),
+    ("Miscellaneous Symbols", 0x2600, 0x26FF),
+    ("Dingbats", 0x2700, 0x27BF),
+    ("Braille Patterns", 0x2800, 0x28FF),
+    ("CJK Symbols and Punctuation", 0x3000, 0x303F),
+    ("Hiragana", 0x3040, 0x309F),
+    ("Katakana", 0x30A0, 0x30FF),
+    ("CJK Unified Ideographs", 0x4E00, 0x9FFF),
+    ("Hangul Syllables", 0xAC00, 0xD7AF),
+    ("Private Use Area", 0xE000, 0xF8FF),
+    ("Alphabetic Presentation Forms", 0xFB00, 0xFB4F),
+    ("Halfwidth and Fullwidth Forms", 0xFF00, 0xFFEF),
+    ("Specials", 0xFFF0, 0xFFFF),
+    ("Mathematical Alphanumeric Symbols", 0x1D400, 0x1D7FF),
+    ("Emoticons", 0x1F600, 0x1F64F),
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// A named range of code points
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnicodeBlock {
+    pub name: String,
+    pub first: u32,
+    pub last: u32,
+}
+
+/// A code point the font can render
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Glyph {
+    pub code_point: u32,
+    pub character: String,
+    pub label: String,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Parse a fontconfig charset ("20-7e a0 a1-17f ...") into inclusive ranges
+fn parse_charset(charset: &str) -> Vec<(u32, u32)> {
+    charset
+        .split_whitespace()
+        .filter_map(|range| match range.split_once('-') {
+            Some((first, last)) => Some((
+                u32::from_str_radix(first, 16).ok()?,
+                u32::from_str_radix(last, 16).ok()?,
+            )),
+            None => u32::from_str_radix(range, 16).ok().map(|cp| (cp, cp)),
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List the Unicode blocks shown in the Character Map subset dropdown
+#[tauri::command]
+pub fn get_unicode_blocks() -> Vec<UnicodeBlock> {
+    UNICODE_BLOCKS
+        .iter()
+        .map(|&(name, first, last)| UnicodeBlock {
+            name: name.to_string(),
+            first,
+            last,
+        })
+        .collect()
+}
+
+/// Get the code points in `block` that `font` covers
+#[tauri::command]
+pub fn get_glyphs(block: String, font: String) -> Result<Vec<Glyph>, KioskError> {
+    let &(_, first, last) = UNICODE_BLOCKS
+        .iter()
+        .find(|(name, _, _)| *name == block)
+        .ok_or_else(|| format!("Unknown Unicode block: {}", block))?;
+
+    // Fontconfig patterns treat '-' and ':' specially, so escape them in family names
+    let pattern = font.replace('\\', "\\\\").replace('-', "\\-").replace(':', "\\:");
+    let charset = fonts::fc_list(&pattern, "%{charset}\n")?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Font not found: {}", font))?;
+
+    let glyphs = parse_charset(&charset)
+        .into_iter()
+        .filter(|&(start, end)| end >= first && start <= last)
+        .flat_map(|(start, end)| start.max(first)..=end.min(last))
+        .filter_map(|code_point| {
+            let character = char::from_u32(code_point)?;
+            if character.is_control() {
+                return None;
+            }
+
+            Some(Glyph {
+                code_point,
+                character: character.to_string(),
+                label: format!("U+{:04X}", code_point),
+            })
+        })
+        .collect();
+
+    Ok(glyphs)
+}
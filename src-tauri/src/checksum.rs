@@ -0,0 +1,280 @@
+//! File checksums
+//!
+//! Streamed MD5/SHA-1/SHA-256 hashing with `hash-progress` events, and
+//! verification of checksum manifests for content bundles before they are
+//! applied. Manifests are either `sha256sum`-style text files or JSON. Both
+//! run as cancellable background jobs.
+
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::digest::DynDigest;
+use sha2::Sha256;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Window};
+
+use crate::events;
+use crate::jobs::{self, JobHandle};
+use crate::vfs::{self, Access};
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Emit progress at most once per this many bytes
+const PROGRESS_STEP: u64 = 16 * 1024 * 1024;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    fn hasher(self) -> Box<dyn DynDigest> {
+        match self {
+            HashAlgorithm::Md5 => Box::new(Md5::default()),
+            HashAlgorithm::Sha1 => Box::new(Sha1::default()),
+            HashAlgorithm::Sha256 => Box::new(Sha256::default()),
+        }
+    }
+
+    /// Guess the algorithm from the length of a hex digest
+    fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            32 => Some(HashAlgorithm::Md5),
+            40 => Some(HashAlgorithm::Sha1),
+            64 => Some(HashAlgorithm::Sha256),
+            _ => None,
+        }
+    }
+}
+
+/// Payload of `hash-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct HashProgress {
+    pub path: String,
+    pub processed: u64,
+    pub total: u64,
+}
+
+/// One file listed in a JSON manifest
+#[derive(Debug, Clone, Deserialize)]
+struct ManifestFile {
+    path: String,
+    hash: String,
+    #[serde(default)]
+    algorithm: Option<HashAlgorithm>,
+    #[serde(default)]
+    size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct JsonManifest {
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryStatus {
+    Ok,
+    Mismatch,
+    SizeMismatch,
+    Missing,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub algorithm: HashAlgorithm,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub status: EntryStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestReport {
+    /// True when every listed file is present and matches
+    pub ok: bool,
+    pub entries: Vec<ManifestEntry>,
+}
+
+// ============================================================================
+// Hashing
+// ============================================================================
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hash a file in chunks, reporting (processed, total) bytes as it goes.
+/// The progress callback can stop the hash by returning an error.
+fn hash_path(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    mut progress: impl FnMut(u64, u64) -> Result<(), String>,
+) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut hasher = algorithm.hasher();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut processed = 0u64;
+    let mut reported = 0u64;
+
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        processed += read as u64;
+        if processed - reported >= PROGRESS_STEP {
+            progress(processed, total)?;
+            reported = processed;
+        }
+    }
+    progress(processed, total)?;
+    Ok(to_hex(&hasher.finalize()))
+}
+
+// ============================================================================
+// Manifests
+// ============================================================================
+
+/// Parse `<hex>  <path>` lines as written by sha256sum/sha1sum/md5sum
+fn parse_sum_file(text: &str) -> Result<Vec<ManifestFile>, String> {
+    text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (hash, path) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("Malformed manifest line: {}", line))?;
+            // A leading '*' marks binary mode
+            let path = path.trim_start().trim_start_matches('*');
+            Ok(ManifestFile {
+                path: path.to_string(),
+                hash: hash.to_string(),
+                algorithm: None,
+                size: None,
+            })
+        })
+        .collect()
+}
+
+/// Resolve a manifest path relative to the manifest, refusing to escape it
+fn resolve(base: &Path, relative: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!("Manifest path outside bundle: {}", relative.display()));
+    }
+    Ok(base.join(relative))
+}
+
+fn verify(app: &AppHandle, job: &JobHandle, manifest_path: &Path) -> Result<ManifestReport, String> {
+    let text = std::fs::read_to_string(manifest_path).map_err(|e| e.to_string())?;
+    let files = if text.trim_start().starts_with('{') {
+        serde_json::from_str::<JsonManifest>(&text)
+            .map_err(|e| format!("Invalid manifest: {}", e))?
+            .files
+    } else {
+        parse_sum_file(&text)?
+    };
+    let base = manifest_path.parent().unwrap_or(Path::new("."));
+    let base_virtual = vfs::to_virtual(app, base);
+
+    let count = files.len();
+    let mut entries = Vec::with_capacity(count);
+    for (index, file) in files.into_iter().enumerate() {
+        job.check()?;
+        job.progress(index as f64 / count as f64, Some(file.path.clone()));
+        let expected = file.hash.trim().to_lowercase();
+        let algorithm = file
+            .algorithm
+            .or_else(|| HashAlgorithm::from_hex_len(expected.len()))
+            .ok_or_else(|| format!("Unknown hash type for {}", file.path))?;
+        let path = resolve(base, &file.path)?;
+        let display = match &base_virtual {
+            Some(base) => format!("{}/{}", base, file.path.trim_start_matches("./")),
+            None => file.path.clone(),
+        };
+
+        let size = std::fs::metadata(&path).ok().map(|m| m.len());
+        let (actual, status) = match size {
+            None => (None, EntryStatus::Missing),
+            Some(size) if file.size.is_some_and(|expected| expected != size) => (None, EntryStatus::SizeMismatch),
+            Some(_) => {
+                let actual = hash_path(&path, algorithm, |processed, total| {
+                    events::publish_progress(
+                        app,
+                        "hash-progress",
+                        HashProgress {
+                            path: display.clone(),
+                            processed,
+                            total,
+                        },
+                    );
+                    job.check()
+                })?;
+                let status = if actual == expected { EntryStatus::Ok } else { EntryStatus::Mismatch };
+                (Some(actual), status)
+            }
+        };
+
+        entries.push(ManifestEntry {
+            path: file.path,
+            algorithm,
+            expected,
+            actual,
+            status,
+        });
+    }
+
+    Ok(ManifestReport {
+        ok: entries.iter().all(|entry| entry.status == EntryStatus::Ok),
+        entries,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start hashing a file as a job; `hash-progress` reports large files.
+/// The hex digest is the job result.
+#[tauri::command]
+pub fn hash_file(app: AppHandle, window: Window, path: String, algorithm: HashAlgorithm) -> String {
+    let worker = app.clone();
+    jobs::spawn(&app, Some(window.label()), "hash_file", move |job| {
+        hash_path(&vfs::resolve(&worker, &path, Access::Read).map_err(|e| e.to_string())?, algorithm, |processed, total| {
+            events::publish_progress(
+                &worker,
+                "hash-progress",
+                HashProgress {
+                    path: path.clone(),
+                    processed,
+                    total,
+                },
+            );
+            job.check()
+        })
+    })
+}
+
+/// Start checking every file listed in a manifest; the job result is a `ManifestReport`
+#[tauri::command]
+pub fn verify_manifest(app: AppHandle, window: Window, path: String) -> String {
+    let worker = app.clone();
+    jobs::spawn(&app, Some(window.label()), "verify_manifest", move |job| {
+        verify(&worker, job, &vfs::resolve(&worker, &path, Access::Read).map_err(|e| e.to_string())?)
+    })
+}
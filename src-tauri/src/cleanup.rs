@@ -0,0 +1,353 @@
+//! Disk cleanup
+//!
+//! Finds reclaimable space in temp files, the thumbnail cache, the app cache,
+//! old logs and the trash, and deletes the selected categories as a
+//! cancellable job with `cleanup-progress` events. Cleanups can also run on a
+//! schedule.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, State, Window};
+
+use crate::error::KioskError;
+use crate::jobs::{self, JobHandle};
+use crate::{events, store};
+
+const CLEANUP_FILE: &str = "cleanup.json";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Temp files younger than this may still be in use
+const TEMP_MIN_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+const LOG_MIN_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Emit a progress event every this many files
+const PROGRESS_EVERY: usize = 50;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Reclaimable space in one category
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupCategory {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanupSchedule {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    /// Category ids to clean
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub last_run: Option<i64>,
+}
+
+impl Default for CleanupSchedule {
+    fn default() -> Self {
+        CleanupSchedule {
+            enabled: false,
+            interval_hours: 24,
+            categories: vec!["temp".to_string(), "thumbnails".to_string()],
+            last_run: None,
+        }
+    }
+}
+
+/// Payload of `cleanup-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupProgress {
+    pub category: String,
+    pub processed: usize,
+    pub total: usize,
+    pub freed: u64,
+}
+
+/// Payload of `cleanup-finished`
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupResult {
+    pub freed: u64,
+    pub deleted: usize,
+    pub failed: usize,
+    pub scheduled: bool,
+}
+
+pub struct CleanupState {
+    schedule: Mutex<CleanupSchedule>,
+    running: Mutex<bool>,
+}
+
+impl CleanupState {
+    pub fn load(app: &AppHandle) -> Self {
+        CleanupState {
+            schedule: Mutex::new(store::load(app, CLEANUP_FILE)),
+            running: Mutex::new(false),
+        }
+    }
+}
+
+// ============================================================================
+// Scanning
+// ============================================================================
+
+struct Target {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    root: Option<PathBuf>,
+    min_age: Option<Duration>,
+    /// Only files owned by the current user
+    own_only: bool,
+}
+
+fn targets(app: &AppHandle) -> Vec<Target> {
+    let path = app.path();
+    vec![
+        Target {
+            id: "temp",
+            name: "Temporary files",
+            description: "Files in the temp folder not touched for a day",
+            root: Some(std::env::temp_dir()),
+            min_age: Some(TEMP_MIN_AGE),
+            own_only: true,
+        },
+        Target {
+            id: "thumbnails",
+            name: "Thumbnails",
+            description: "Cached previews of pictures and videos, recreated when needed",
+            root: path.cache_dir().ok().map(|dir| dir.join("thumbnails")),
+            min_age: None,
+            own_only: false,
+        },
+        Target {
+            id: "app_cache",
+            name: "Application cache",
+            description: "Downloaded data the desktop can fetch again",
+            root: path.app_cache_dir().ok(),
+            min_age: None,
+            own_only: false,
+        },
+        Target {
+            id: "logs",
+            name: "Old log files",
+            description: "Logs older than a week",
+            root: path.app_log_dir().ok(),
+            min_age: Some(LOG_MIN_AGE),
+            own_only: false,
+        },
+        Target {
+            id: "trash",
+            name: "Recycle Bin",
+            description: "Files deleted to the Recycle Bin",
+            root: path.data_dir().ok().map(|dir| dir.join("Trash")),
+            min_age: None,
+            own_only: false,
+        },
+    ]
+}
+
+/// Collect deletable files under a directory without following symlinks
+fn collect(dir: &Path, target: &Target, uid: u32, now: SystemTime, files: &mut Vec<(PathBuf, u64)>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let Ok(metadata) = fs::symlink_metadata(&path) else { continue };
+        if metadata.is_dir() {
+            collect(&path, target, uid, now, files);
+            continue;
+        }
+        if target.own_only && metadata.uid() != uid {
+            continue;
+        }
+        if let Some(min_age) = target.min_age {
+            let age = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            if age < min_age {
+                continue;
+            }
+        }
+        files.push((path, metadata.len()));
+    }
+}
+
+fn scan(target: &Target) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    if let Some(root) = &target.root {
+        // SAFETY: getuid has no preconditions and cannot fail
+        let uid = unsafe { libc::getuid() };
+        collect(root, target, uid, SystemTime::now(), &mut files);
+    }
+    files
+}
+
+/// Remove directories left empty by a cleanup, keeping the root itself
+fn prune_empty(dir: &Path, root: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let is_dir = fs::symlink_metadata(&path).map(|m| m.is_dir()).unwrap_or(false);
+        if is_dir {
+            prune_empty(&path, root);
+        }
+    }
+    if dir != root {
+        // Fails harmlessly when the directory is not empty
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+// ============================================================================
+// Cleaning
+// ============================================================================
+
+fn clean(app: &AppHandle, job: &JobHandle, categories: &[String], scheduled: bool) -> CleanupResult {
+    let mut result = CleanupResult {
+        freed: 0,
+        deleted: 0,
+        failed: 0,
+        scheduled,
+    };
+
+    for target in targets(app).iter().filter(|target| categories.iter().any(|id| id == target.id)) {
+        let files = scan(target);
+        let total = files.len();
+        let mut freed = 0;
+
+        for (processed, (path, size)) in files.iter().enumerate() {
+            if job.is_cancelled() {
+                break;
+            }
+            if fs::remove_file(path).is_ok() {
+                freed += size;
+                result.deleted += 1;
+            } else {
+                result.failed += 1;
+            }
+            if (processed + 1) % PROGRESS_EVERY == 0 || processed + 1 == total {
+                events::publish_progress(
+                    app,
+                    "cleanup-progress",
+                    CleanupProgress {
+                        category: target.id.to_string(),
+                        processed: processed + 1,
+                        total,
+                        freed,
+                    },
+                );
+            }
+        }
+        if let Some(root) = &target.root {
+            prune_empty(root, root);
+        }
+        result.freed += freed;
+    }
+
+    events::publish(app, "cleanup-finished", &result);
+    result
+}
+
+/// Run a cleanup as a job unless one is already running
+fn spawn_clean(app: &AppHandle, window: Option<&str>, categories: Vec<String>, scheduled: bool) -> Result<String, String> {
+    let state = app.state::<CleanupState>();
+    let mut running = state.running.lock().expect("cleanup running lock");
+    if *running {
+        return Err("A cleanup is already running".to_string());
+    }
+    *running = true;
+
+    let worker = app.clone();
+    Ok(jobs::spawn(app, window, "cleanup", move |job| {
+        let result = clean(&worker, job, &categories, scheduled);
+        *worker.state::<CleanupState>().running.lock().expect("cleanup running lock") = false;
+        Ok(result)
+    }))
+}
+
+/// Spawn the scheduler that runs due cleanups
+pub fn start_cleanup_schedule(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<CleanupState>();
+        let due = {
+            let mut schedule = state.schedule.lock().expect("cleanup schedule lock");
+            let now = Local::now().timestamp();
+            let interval = schedule.interval_hours.max(1) as i64 * 3600;
+            let due = schedule.enabled && schedule.last_run.map_or(true, |last| now - last >= interval);
+            if due {
+                schedule.last_run = Some(now);
+                let _ = store::save(&app, CLEANUP_FILE, &*schedule);
+            }
+            due.then(|| schedule.categories.clone())
+        };
+
+        if let Some(categories) = due {
+            let _ = spawn_clean(&app, None, categories, true);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Report reclaimable space per category
+#[tauri::command]
+pub fn scan_cleanup(app: AppHandle) -> Vec<CleanupCategory> {
+    targets(&app)
+        .iter()
+        .map(|target| {
+            let files = scan(target);
+            CleanupCategory {
+                id: target.id.to_string(),
+                name: target.name.to_string(),
+                description: target.description.to_string(),
+                files: files.len(),
+                bytes: files.iter().map(|(_, size)| size).sum(),
+            }
+        })
+        .collect()
+}
+
+/// Start cleaning the selected categories as a job; progress arrives as events
+#[tauri::command]
+pub fn run_cleanup(app: AppHandle, window: Window, categories: Vec<String>) -> Result<String, KioskError> {
+    if categories.is_empty() {
+        return Err(KioskError::invalid("No categories selected"));
+    }
+    Ok(spawn_clean(&app, Some(window.label()), categories, false)?)
+}
+
+/// Get the cleanup schedule
+#[tauri::command]
+pub fn get_cleanup_schedule(state: State<'_, CleanupState>) -> CleanupSchedule {
+    state.schedule.lock().expect("cleanup schedule lock").clone()
+}
+
+/// Update the cleanup schedule
+#[tauri::command]
+pub fn set_cleanup_schedule(
+    app: AppHandle,
+    state: State<'_, CleanupState>,
+    schedule: CleanupSchedule,
+) -> Result<(), KioskError> {
+    let mut current = state.schedule.lock().expect("cleanup schedule lock");
+    let schedule = CleanupSchedule {
+        last_run: current.last_run,
+        ..schedule
+    };
+    store::save(&app, CLEANUP_FILE, &schedule)?;
+    *current = schedule;
+    Ok(())
+}
@@ -0,0 +1,184 @@
+//! Headless command line
+//!
+//! `kiosk-cli` lets fleet scripts and cron jobs drive a kiosk over SSH. It
+//! reuses the backend modules but never starts Tauri or a webview, so it
+//! works without a display session and beside a running kiosk. Results are
+//! printed as JSON on stdout; errors go to stderr with a nonzero exit code.
+//!
+//! ```text
+//! kiosk-cli stats
+//! kiosk-cli screenshot [FILE]
+//! kiosk-cli config list
+//! kiosk-cli config get NAME
+//! kiosk-cli config set NAME on|off
+//! kiosk-cli totp SECRET
+//! kiosk-cli setup-token
+//! ```
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+
+use crate::{auth, config, store, support, totp};
+
+/// Kept in step with `identifier` in tauri.conf.json
+const IDENTIFIER: &str = "com.kiosk.app";
+
+/// Display used for screenshots when the SSH session has none
+const DEFAULT_DISPLAY: &str = ":0";
+
+const USAGE: &str = "\
+Usage: kiosk-cli [--data-dir DIR] COMMAND
+
+Commands:
+  stats                    CPU and memory usage
+  screenshot [FILE]        PNG of the screen, to FILE or stdout
+  config list              Feature flags and their values
+  config get NAME          One feature flag
+  config set NAME on|off   Set a feature flag; the kiosk applies it on restart
+  totp SECRET              Current one-time unlock code for a base32 secret
+  setup-token              Token for creating the first operator account
+  version                  Version and enabled features";
+
+/// Exit code for bad usage, as distinct from a failed command
+const EXIT_USAGE: i32 = 2;
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The app data directory as Tauri resolves it on Linux
+fn default_data_dir() -> Result<PathBuf, String> {
+    let base = match std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME").ok_or("HOME is not set")?).join(".local/share"),
+    };
+    Ok(base.join(IDENTIFIER))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", text);
+    Ok(())
+}
+
+fn stats() -> Result<(), String> {
+    let refresh = RefreshKind::new()
+        .with_cpu(CpuRefreshKind::new().with_cpu_usage())
+        .with_memory(MemoryRefreshKind::new().with_ram());
+    let mut sys = System::new_with_specifics(refresh);
+    // CPU usage is a difference between two refreshes
+    std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+    print_json(&crate::system_stats(&mut sys))
+}
+
+fn screenshot(file: Option<&String>) -> Result<(), String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() && std::env::var_os("DISPLAY").is_none() {
+        std::env::set_var("DISPLAY", DEFAULT_DISPLAY);
+    }
+    let png = support::screenshot()?;
+    match file {
+        Some(file) => std::fs::write(file, &png).map_err(|e| format!("{}: {}", file, e)),
+        None => std::io::stdout().write_all(&png).map_err(|e| e.to_string()),
+    }
+}
+
+fn flag<'a>(flags: &'a [config::FeatureFlag], name: &str) -> Option<&'a config::FeatureFlag> {
+    flags.iter().find(|flag| flag.name == name)
+}
+
+fn config_command(data_dir: PathBuf, args: &[String]) -> Result<(), String> {
+    let path = data_dir.join(config::FLAGS_FILE);
+    let mut stored: BTreeMap<String, bool> = store::read_json(&path);
+    match args {
+        [action] if action == "list" => print_json(&config::flag_list(stored)),
+        [action, name] if action == "get" => {
+            let name = config::flag_name(name)?;
+            let flags = config::flag_list(stored);
+            print_json(flag(&flags, &name).ok_or_else(|| format!("Unknown feature flag: {}", name))?)
+        }
+        [action, name, value] if action == "set" => {
+            let name = config::flag_name(name)?;
+            let value = config::parse_flag(value).ok_or_else(|| format!("Not a flag value: {}", value))?;
+            std::fs::create_dir_all(&data_dir).map_err(|e| format!("{}: {}", data_dir.display(), e))?;
+            stored.insert(name.clone(), value);
+            store::write_json(&path, &stored)?;
+            let flags = config::flag_list(stored);
+            print_json(&flag(&flags, &name))
+        }
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// The code support reads out for a kiosk provisioned with `secret`
+fn totp_code(secret: &str) -> Result<(), String> {
+    let (code, expires_in_secs) = totp::current_code(secret, totp::DEFAULT_DIGITS, totp::DEFAULT_PERIOD_SECS)?;
+    print_json(&serde_json::json!({ "code": code, "expires_in_secs": expires_in_secs }))
+}
+
+/// The token the kiosk asks for before its first operator account is made
+fn setup_token(data_dir: PathBuf) -> Result<(), String> {
+    let path = data_dir.join(auth::SETUP_TOKEN_FILE);
+    let token = std::fs::read_to_string(&path)
+        .map_err(|_| "No setup token; an operator account already exists or the kiosk has not started yet")?;
+    print_json(&serde_json::json!({ "token": token.trim() }))
+}
+
+fn version() -> Result<(), String> {
+    print_json(&serde_json::json!({
+        "name": env!("CARGO_PKG_NAME"),
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": config::compiled_features(),
+    }))
+}
+
+// ============================================================================
+// Entry Point
+// ============================================================================
+
+/// Run the command line and return the process exit code
+pub fn main() -> i32 {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let mut data_dir = None;
+    if args.first().map(String::as_str) == Some("--data-dir") {
+        if args.len() < 2 {
+            eprintln!("{}", USAGE);
+            return EXIT_USAGE;
+        }
+        data_dir = Some(PathBuf::from(args.remove(1)));
+        args.remove(0);
+    }
+
+    let result = match args.split_first() {
+        Some((command, rest)) => match (command.as_str(), rest) {
+            ("stats", []) => stats(),
+            ("screenshot", [] | [_]) => screenshot(rest.first()),
+            ("config", _) => data_dir
+                .map_or_else(default_data_dir, Ok)
+                .and_then(|dir| config_command(dir, rest)),
+            ("totp", [secret]) => totp_code(secret),
+            ("setup-token", []) => data_dir.map_or_else(default_data_dir, Ok).and_then(setup_token),
+            ("version", []) => version(),
+            ("help" | "--help" | "-h", _) => {
+                println!("{}", USAGE);
+                return 0;
+            }
+            _ => Err(USAGE.to_string()),
+        },
+        None => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) if e == USAGE => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+        Err(e) => {
+            eprintln!("kiosk-cli: {}", e);
+            1
+        }
+    }
+}
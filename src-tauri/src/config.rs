@@ -0,0 +1,317 @@
+//! Runtime configuration
+//!
+//! `get_runtime_config` gathers what support needs to know about a running
+//! kiosk: resolved paths, feature flags, the process environment (sensitive
+//! values redacted), build info and registered plugins. Feature flags are
+//! stored in `feature-flags.json` for staged rollouts and can be forced per
+//! device with `KIOSK_FEATURE_<NAME>=1|0` in the environment.
+//!
+//! The `lite` flag (on by default in builds with the `lite` Cargo feature)
+//! trims memory and wakeups for 512 MB boards: no stats history, a title-only
+//! help index, slower background polling and throttled progress events. The
+//! history and index are set up at startup, so changing it needs a restart.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{events, store};
+
+pub(crate) const FLAGS_FILE: &str = "feature-flags.json";
+
+/// Prefix of environment variables that override a flag
+const FLAG_ENV_PREFIX: &str = "KIOSK_FEATURE_";
+
+/// Flags listed even when unset: the runtime switches for lazily started
+/// subsystems, and lite mode
+const DEFAULT_FLAGS: &[(&str, bool)] = &[
+    ("mail", true),
+    ("payments", true),
+    ("cash", true),
+    ("printing", true),
+    ("tickets", true),
+    ("speech", true),
+    ("streams", true),
+    ("recording", true),
+    ("lite", cfg!(feature = "lite")),
+];
+
+/// Background loops poll this many times less often in lite mode
+const LITE_POLL_FACTOR: u32 = 4;
+
+/// Kept in step with the plugins registered in `run`
+const PLUGINS: &[&str] = &["shell", "fs"];
+
+/// Environment variable names containing these are redacted
+const SENSITIVE_NAMES: &[&str] = &["KEY", "SECRET", "TOKEN", "PASSWORD", "PASSWD", "CREDENTIAL", "AUTH"];
+
+pub(crate) const REDACTED: &str = "[redacted]";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub enabled: bool,
+    /// Forced by a `KIOSK_FEATURE_*` environment variable
+    pub from_env: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub name: String,
+    pub version: String,
+    pub identifier: String,
+    pub tauri_version: String,
+    pub rust_version: String,
+    pub profile: String,
+    pub target_os: String,
+    pub target_arch: String,
+    /// Optional subsystems compiled in
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub executable: Option<String>,
+    pub working_dir: Option<String>,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuntimeConfig {
+    pub paths: BTreeMap<String, String>,
+    pub feature_flags: Vec<FeatureFlag>,
+    pub environment: BTreeMap<String, String>,
+    pub build: BuildInfo,
+    pub process: ProcessInfo,
+    pub plugins: Vec<String>,
+}
+
+pub struct FlagsState(Mutex<BTreeMap<String, bool>>);
+
+impl FlagsState {
+    pub fn load(app: &AppHandle) -> Self {
+        FlagsState(Mutex::new(store::load(app, FLAGS_FILE)))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Whether an environment variable or config key likely holds a secret
+pub(crate) fn is_sensitive(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    SENSITIVE_NAMES.iter().any(|word| upper.contains(word))
+}
+
+fn env_var(name: &str) -> String {
+    format!("{}{}", FLAG_ENV_PREFIX, name.to_uppercase().replace(['-', '.'], "_"))
+}
+
+pub(crate) fn parse_flag(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "on" | "yes" => Some(true),
+        "0" | "false" | "off" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Normalized flag name, or an error if it has characters a flag cannot
+pub(crate) fn flag_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) {
+        return Err(format!("Invalid feature flag name: {}", name));
+    }
+    Ok(name)
+}
+
+fn env_override(name: &str) -> Option<bool> {
+    parse_flag(&std::env::var(env_var(name)).ok()?)
+}
+
+fn default_flag(name: &str) -> bool {
+    DEFAULT_FLAGS.iter().any(|&(flag, enabled)| flag == name && enabled)
+}
+
+/// Effective value of a feature flag: environment, then stored, then default
+pub(crate) fn feature_enabled(app: &AppHandle, name: &str) -> bool {
+    env_override(name).unwrap_or_else(|| {
+        let state = app.state::<FlagsState>();
+        let flags = state.0.lock().expect("feature flags lock");
+        flags.get(name).copied().unwrap_or_else(|| default_flag(name))
+    })
+}
+
+/// Whether the kiosk runs in low-memory lite mode
+pub(crate) fn lite_mode(app: &AppHandle) -> bool {
+    feature_enabled(app, "lite")
+}
+
+/// How long a background loop sleeps between polls, stretched in lite mode
+pub(crate) fn poll_interval(app: &AppHandle, interval: Duration) -> Duration {
+    if lite_mode(app) {
+        interval * LITE_POLL_FACTOR
+    } else {
+        interval
+    }
+}
+
+fn feature_flags(state: &FlagsState) -> Vec<FeatureFlag> {
+    flag_list(state.0.lock().expect("feature flags lock").clone())
+}
+
+/// Every flag with its effective value, from the stored flags
+pub(crate) fn flag_list(stored: BTreeMap<String, bool>) -> Vec<FeatureFlag> {
+    let defaults = DEFAULT_FLAGS.iter().map(|&(name, enabled)| (name.to_string(), enabled));
+    let mut flags: BTreeMap<String, FeatureFlag> = defaults
+        .chain(stored)
+        .map(|(name, enabled)| {
+            let flag = FeatureFlag {
+                name: name.clone(),
+                enabled,
+                from_env: false,
+            };
+            (name.clone(), flag)
+        })
+        .collect();
+
+    // Flags set only in the environment are listed under their lowercased name
+    for (var, value) in std::env::vars() {
+        let Some(suffix) = var.strip_prefix(FLAG_ENV_PREFIX) else {
+            continue;
+        };
+        let Some(enabled) = parse_flag(&value) else {
+            continue;
+        };
+        let name = flags
+            .keys()
+            .find(|name| env_var(name) == var)
+            .cloned()
+            .unwrap_or_else(|| suffix.to_lowercase());
+        flags.insert(
+            name.clone(),
+            FeatureFlag {
+                name,
+                enabled,
+                from_env: true,
+            },
+        );
+    }
+    flags.into_values().collect()
+}
+
+fn paths(app: &AppHandle) -> BTreeMap<String, String> {
+    let resolver = app.path();
+    let entries = [
+        ("app_data", resolver.app_data_dir()),
+        ("app_config", resolver.app_config_dir()),
+        ("app_cache", resolver.app_cache_dir()),
+        ("app_log", resolver.app_log_dir()),
+        ("resources", resolver.resource_dir()),
+        ("home", resolver.home_dir()),
+    ];
+    let mut paths: BTreeMap<String, String> = entries
+        .into_iter()
+        .filter_map(|(name, path)| Some((name.to_string(), path.ok()?.display().to_string())))
+        .collect();
+    paths.insert("temp".to_string(), std::env::temp_dir().display().to_string());
+    paths
+}
+
+fn environment() -> BTreeMap<String, String> {
+    std::env::vars()
+        .map(|(name, value)| {
+            let value = if is_sensitive(&name) { REDACTED.to_string() } else { value };
+            (name, value)
+        })
+        .collect()
+}
+
+fn build_info(app: &AppHandle) -> BuildInfo {
+    BuildInfo {
+        name: env!("CARGO_PKG_NAME").to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        identifier: app.config().identifier.clone(),
+        tauri_version: tauri::VERSION.to_string(),
+        rust_version: env!("CARGO_PKG_RUST_VERSION").to_string(),
+        profile: if cfg!(debug_assertions) { "debug" } else { "release" }.to_string(),
+        target_os: std::env::consts::OS.to_string(),
+        target_arch: std::env::consts::ARCH.to_string(),
+        features: compiled_features(),
+    }
+}
+
+/// Optional subsystems compiled into this build
+pub(crate) fn compiled_features() -> Vec<String> {
+    [
+        ("mail", cfg!(feature = "mail")),
+        ("payments", cfg!(feature = "payments")),
+        ("printing", cfg!(feature = "printing")),
+        ("speech", cfg!(feature = "speech")),
+        ("lite", cfg!(feature = "lite")),
+    ]
+    .iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name.to_string())
+    .collect()
+}
+
+fn process_info() -> ProcessInfo {
+    ProcessInfo {
+        pid: std::process::id(),
+        executable: std::env::current_exe().ok().map(|path| path.display().to_string()),
+        working_dir: std::env::current_dir().ok().map(|path| path.display().to_string()),
+        args: std::env::args().collect(),
+    }
+}
+
+pub(crate) fn runtime_config(app: &AppHandle) -> RuntimeConfig {
+    RuntimeConfig {
+        paths: paths(app),
+        feature_flags: feature_flags(&app.state::<FlagsState>()),
+        environment: environment(),
+        build: build_info(app),
+        process: process_info(),
+        plugins: PLUGINS.iter().map(|plugin| plugin.to_string()).collect(),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Effective runtime configuration for diagnostics
+#[tauri::command]
+pub fn get_runtime_config(app: AppHandle) -> RuntimeConfig {
+    runtime_config(&app)
+}
+
+/// Set a feature flag and persist it (admin). An environment override still wins.
+#[tauri::command]
+pub fn set_feature_flag(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, FlagsState>,
+    name: String,
+    value: bool,
+) -> Result<Vec<FeatureFlag>, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let name = flag_name(&name).map_err(KioskError::invalid)?;
+
+    {
+        let mut flags = state.0.lock().expect("feature flags lock");
+        flags.insert(name.clone(), value);
+        store::save(&app, FLAGS_FILE, &*flags)?;
+    }
+    events::publish(&app, "feature-flag-changed", serde_json::json!({ "name": name, "enabled": value }));
+    Ok(feature_flags(&state))
+}
@@ -0,0 +1,365 @@
+//! Address book
+//!
+//! Contact storage for the Outlook Express style address book used by
+//! reception kiosks, with search and vCard 3.0 import/export.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+use crate::{recents, store};
+
+const CONTACTS_FILE: &str = "contacts.json";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// A phone number with its vCard type (work, home, cell, ...)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContactPhone {
+    pub kind: String,
+    pub number: String,
+}
+
+/// Editable contact fields
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContactInput {
+    pub display_name: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub company: String,
+    pub title: String,
+    pub emails: Vec<String>,
+    pub phones: Vec<ContactPhone>,
+    pub address: String,
+    pub notes: String,
+}
+
+/// A stored contact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    pub id: String,
+    #[serde(flatten)]
+    pub fields: ContactInput,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// All contacts, in insertion order
+pub struct ContactsState(Mutex<Vec<Contact>>);
+
+impl ContactsState {
+    pub fn load(app: &AppHandle) -> Self {
+        ContactsState(Mutex::new(store::load(app, CONTACTS_FILE)))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Fill in a display name from the other fields when none was given
+fn normalize(mut fields: ContactInput) -> Result<ContactInput, String> {
+    if fields.display_name.trim().is_empty() {
+        fields.display_name = [fields.first_name.trim(), fields.last_name.trim()]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+    if fields.display_name.is_empty() {
+        fields.display_name = fields.emails.first().cloned().unwrap_or_default();
+    }
+    if fields.display_name.is_empty() {
+        return Err("Contact needs a name or email address".to_string());
+    }
+    Ok(fields)
+}
+
+fn new_contact(fields: ContactInput) -> Result<Contact, String> {
+    let now = Local::now().timestamp();
+    Ok(Contact {
+        id: uuid::Uuid::new_v4().to_string(),
+        fields: normalize(fields)?,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+fn sorted(mut contacts: Vec<Contact>) -> Vec<Contact> {
+    contacts.sort_by_key(|contact| contact.fields.display_name.to_lowercase());
+    contacts
+}
+
+fn vcard_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+}
+
+fn vcard_unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Split a structured vCard value on unescaped `;`
+fn vcard_components(value: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            ';' if !escaped => parts.push(String::new()),
+            _ => {
+                if let Some(last) = parts.last_mut() {
+                    last.push(c);
+                }
+            }
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    parts.iter().map(|part| vcard_unescape(part)).collect()
+}
+
+fn to_vcard(contact: &Contact) -> String {
+    let fields = &contact.fields;
+    let mut lines = vec![
+        "BEGIN:VCARD".to_string(),
+        "VERSION:3.0".to_string(),
+        format!("FN:{}", vcard_escape(&fields.display_name)),
+        format!("N:{};{};;;", vcard_escape(&fields.last_name), vcard_escape(&fields.first_name)),
+    ];
+
+    if !fields.company.is_empty() {
+        lines.push(format!("ORG:{}", vcard_escape(&fields.company)));
+    }
+    if !fields.title.is_empty() {
+        lines.push(format!("TITLE:{}", vcard_escape(&fields.title)));
+    }
+    for email in &fields.emails {
+        lines.push(format!("EMAIL;TYPE=INTERNET:{}", vcard_escape(email)));
+    }
+    for phone in &fields.phones {
+        lines.push(format!("TEL;TYPE={}:{}", phone.kind.to_uppercase(), vcard_escape(&phone.number)));
+    }
+    if !fields.address.is_empty() {
+        lines.push(format!("ADR:;;{};;;;", vcard_escape(&fields.address)));
+    }
+    if !fields.notes.is_empty() {
+        lines.push(format!("NOTE:{}", vcard_escape(&fields.notes)));
+    }
+    lines.push(format!("UID:{}", contact.id));
+    lines.push("END:VCARD".to_string());
+
+    lines.join("\r\n") + "\r\n"
+}
+
+fn parse_vcards(text: &str) -> Vec<ContactInput> {
+    // Unfold continuation lines, which start with a space or tab
+    let mut unfolded: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), unfolded.last_mut()) {
+            (Some(rest), Some(previous)) => previous.push_str(rest),
+            _ => unfolded.push(line.to_string()),
+        }
+    }
+
+    let mut cards = Vec::new();
+    let mut current: Option<ContactInput> = None;
+
+    for line in unfolded {
+        let Some((name, value)) = line.split_once(':') else { continue };
+        let mut params = name.split(';');
+        let property = params.next().unwrap_or_default().to_uppercase();
+        // Strip vCard 2.1 / Apple item grouping prefixes such as "item1."
+        let property = property.rsplit('.').next().unwrap_or_default().to_string();
+
+        match (property.as_str(), current.as_mut()) {
+            ("BEGIN", _) if value.eq_ignore_ascii_case("VCARD") => current = Some(ContactInput::default()),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") => cards.extend(current.take()),
+            ("FN", Some(card)) => card.display_name = vcard_unescape(value),
+            ("N", Some(card)) => {
+                let parts = vcard_components(value);
+                card.last_name = parts.first().cloned().unwrap_or_default();
+                card.first_name = parts.get(1).cloned().unwrap_or_default();
+            }
+            ("ORG", Some(card)) => card.company = vcard_components(value).join(" ").trim().to_string(),
+            ("TITLE", Some(card)) => card.title = vcard_unescape(value),
+            ("EMAIL", Some(card)) => card.emails.push(vcard_unescape(value)),
+            ("TEL", Some(card)) => {
+                let kind = params
+                    .filter_map(|param| {
+                        let param = param.to_lowercase();
+                        let kind = param.strip_prefix("type=").unwrap_or(&param).to_string();
+                        (kind != "voice" && kind != "pref").then_some(kind)
+                    })
+                    .next()
+                    .unwrap_or_else(|| "work".to_string());
+                card.phones.push(ContactPhone {
+                    kind,
+                    number: vcard_unescape(value),
+                });
+            }
+            ("ADR", Some(card)) => {
+                card.address = vcard_components(value)
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+            }
+            ("NOTE", Some(card)) => card.notes = vcard_unescape(value),
+            _ => {}
+        }
+    }
+
+    cards
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List all contacts sorted by display name
+#[tauri::command]
+pub fn list_contacts(state: State<'_, ContactsState>) -> Vec<Contact> {
+    sorted(state.0.lock().expect("contacts lock").clone())
+}
+
+/// Get a single contact
+#[tauri::command]
+pub fn get_contact(state: State<'_, ContactsState>, id: String) -> Result<Contact, KioskError> {
+    let contacts = state.0.lock().expect("contacts lock");
+    contacts
+        .iter()
+        .find(|contact| contact.id == id)
+        .cloned()
+        .ok_or_else(|| KioskError::not_found(format!("Contact not found: {}", id)))
+}
+
+/// Create a contact
+#[tauri::command]
+pub fn create_contact(
+    app: AppHandle,
+    state: State<'_, ContactsState>,
+    contact: ContactInput,
+) -> Result<Contact, KioskError> {
+    let contact = new_contact(contact)?;
+    let mut contacts = state.0.lock().expect("contacts lock");
+    contacts.push(contact.clone());
+    store::save(&app, CONTACTS_FILE, &*contacts)?;
+    Ok(contact)
+}
+
+/// Replace a contact's fields
+#[tauri::command]
+pub fn update_contact(
+    app: AppHandle,
+    state: State<'_, ContactsState>,
+    id: String,
+    contact: ContactInput,
+) -> Result<Contact, KioskError> {
+    let fields = normalize(contact)?;
+    let mut contacts = state.0.lock().expect("contacts lock");
+    let existing = contacts
+        .iter_mut()
+        .find(|contact| contact.id == id)
+        .ok_or_else(|| format!("Contact not found: {}", id))?;
+
+    existing.fields = fields;
+    existing.updated_at = Local::now().timestamp();
+    let updated = existing.clone();
+
+    store::save(&app, CONTACTS_FILE, &*contacts)?;
+    Ok(updated)
+}
+
+/// Delete a contact
+#[tauri::command]
+pub fn delete_contact(app: AppHandle, state: State<'_, ContactsState>, id: String) -> Result<(), KioskError> {
+    let mut contacts = state.0.lock().expect("contacts lock");
+    let before = contacts.len();
+    contacts.retain(|contact| contact.id != id);
+    if contacts.len() == before {
+        return Err(KioskError::not_found(format!("Contact not found: {}", id)));
+    }
+    Ok(store::save(&app, CONTACTS_FILE, &*contacts)?)
+}
+
+/// Search names, company, emails and phone numbers (case-insensitive)
+#[tauri::command]
+pub fn search_contacts(state: State<'_, ContactsState>, query: String) -> Vec<Contact> {
+    let query = query.trim().to_lowercase();
+    let contacts = state.0.lock().expect("contacts lock");
+
+    let matches = contacts
+        .iter()
+        .filter(|contact| {
+            let fields = &contact.fields;
+            [&fields.display_name, &fields.first_name, &fields.last_name, &fields.company]
+                .into_iter()
+                .chain(fields.emails.iter())
+                .chain(fields.phones.iter().map(|phone| &phone.number))
+                .any(|value| value.to_lowercase().contains(&query))
+        })
+        .cloned()
+        .collect();
+
+    sorted(matches)
+}
+
+/// Import every card in a .vcf file, returning the number of contacts added
+#[tauri::command]
+pub fn import_vcard(app: AppHandle, state: State<'_, ContactsState>, path: String) -> Result<usize, KioskError> {
+    let text = fs::read_to_string(vfs::resolve(&app, &path, Access::Read)?).map_err(|e| e.to_string())?;
+    let imported: Vec<Contact> = parse_vcards(&text)
+        .into_iter()
+        .filter_map(|fields| new_contact(fields).ok())
+        .collect();
+
+    let count = imported.len();
+    let mut contacts = state.0.lock().expect("contacts lock");
+    contacts.extend(imported);
+    store::save(&app, CONTACTS_FILE, &*contacts)?;
+    let _ = recents::record(&app, "contacts", &path);
+    Ok(count)
+}
+
+/// Export contacts (all, or the given ids) to a .vcf file
+#[tauri::command]
+pub fn export_vcard(
+    app: AppHandle,
+    state: State<'_, ContactsState>,
+    path: String,
+    ids: Option<Vec<String>>,
+) -> Result<usize, KioskError> {
+    let contacts = state.0.lock().expect("contacts lock");
+    let selected: Vec<&Contact> = contacts
+        .iter()
+        .filter(|contact| ids.as_ref().map_or(true, |ids| ids.contains(&contact.id)))
+        .collect();
+
+    let text: String = selected.iter().map(|contact| to_vcard(contact)).collect();
+    fs::write(vfs::resolve_write(&app, &path, text.len() as u64)?, text).map_err(|e| e.to_string())?;
+    let _ = recents::record(&app, "contacts", &path);
+    Ok(selected.len())
+}
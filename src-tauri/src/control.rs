@@ -0,0 +1,245 @@
+//! D-Bus control interface
+//!
+//! Exports `org.kiosk.Control` at `/org/kiosk/Control` so other services on
+//! the device and distro tooling can drive the kiosk without going through
+//! the webview:
+//!
+//! - `ReloadContent()` reloads every window
+//! - `ShowMessage(s title, s body, u timeout_secs)` publishes `kiosk-message`
+//!   for the frontend to show; a timeout of 0 keeps it until dismissed
+//! - `GetStatus() -> a{sv}` returns version, host, uptime and usage figures,
+//!   and the position when the unit has a location fix
+//!
+//! The name is claimed on the system bus, which needs the policy in
+//! `dbus/org.kiosk.Control.conf` installed under /etc/dbus-1/system.d, and
+//! on the session bus when the system bus refuses it.
+
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::stdintf::org_freedesktop_dbus::RequestNameReply;
+use dbus::blocking::Connection;
+use dbus::channel::{MatchingReceiver, Sender};
+use dbus::message::MatchRule;
+use dbus::strings::ErrorName;
+use dbus::Message;
+use serde::Serialize;
+use std::ffi::CString;
+use std::sync::Mutex;
+use std::time::Duration;
+use sysinfo::System;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{config, events, location, mock, SharedSystem};
+
+const BUS_NAME: &str = "org.kiosk.Control";
+
+const OBJECT_PATH: &str = "/org/kiosk/Control";
+
+const INTERFACE: &str = "org.kiosk.Control";
+
+const INTROSPECTABLE: &str = "org.freedesktop.DBus.Introspectable";
+
+const INTROSPECTION: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node>
+  <interface name="org.kiosk.Control">
+    <method name="ReloadContent"/>
+    <method name="ShowMessage">
+      <arg name="title" type="s" direction="in"/>
+      <arg name="body" type="s" direction="in"/>
+      <arg name="timeout_secs" type="u" direction="in"/>
+    </method>
+    <method name="GetStatus">
+      <arg name="status" type="a{sv}" direction="out"/>
+    </method>
+  </interface>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect">
+      <arg name="xml" type="s" direction="out"/>
+    </method>
+  </interface>
+</node>
+"#;
+
+/// Longest a shown message may stay up before it is dismissed
+const MAX_MESSAGE_TIMEOUT: u32 = 3600;
+
+const PROCESS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Wait before trying the buses again
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Payload of `kiosk-message`
+#[derive(Debug, Clone, Serialize)]
+pub struct KioskMessage {
+    pub title: String,
+    pub body: String,
+    /// Seconds to show the message; 0 keeps it until dismissed
+    pub timeout_secs: u32,
+    /// Where the message came from, e.g. "dbus"
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ControlStatus {
+    /// "system" or "session" once the name is claimed
+    pub bus: Option<String>,
+    pub name: String,
+    /// Why the name could not be claimed, if it could not
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ControlState(Mutex<ControlStatus>);
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn bus_error(e: dbus::Error) -> String {
+    e.message().unwrap_or("D-Bus error").to_string()
+}
+
+fn error_reply(call: &Message, name: &'static str, message: &str) -> Message {
+    let text = CString::new(message.replace('\0', "")).unwrap_or_default();
+    call.error(&ErrorName::from(name), &text)
+}
+
+/// Claim the name on the system bus, or the session bus if that fails
+fn connect() -> Result<(Connection, &'static str), String> {
+    let claim = |connection: Connection| -> Result<Connection, String> {
+        match connection.request_name(BUS_NAME, false, true, true).map_err(bus_error)? {
+            RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => Ok(connection),
+            _ => Err(format!("{} is owned by another process", BUS_NAME)),
+        }
+    };
+    let system = Connection::new_system().map_err(bus_error).and_then(claim);
+    match system {
+        Ok(connection) => Ok((connection, "system")),
+        Err(system_error) => Connection::new_session()
+            .map_err(bus_error)
+            .and_then(claim)
+            .map(|connection| (connection, "session"))
+            .map_err(|session_error| format!("system bus: {}; session bus: {}", system_error, session_error)),
+    }
+}
+
+fn status(app: &AppHandle) -> PropMap {
+    let stats = {
+        let shared = app.state::<SharedSystem>();
+        let mut sys = shared.system.lock().expect("system state lock");
+        crate::system_stats(&mut sys)
+    };
+    let mut status = PropMap::new();
+    let mut put = |key: &str, value: Box<dyn RefArg>| {
+        status.insert(key.to_string(), Variant(value));
+    };
+    put("version", Box::new(env!("CARGO_PKG_VERSION").to_string()));
+    put("hostname", Box::new(System::host_name().unwrap_or_default()));
+    put("uptime_secs", Box::new(System::uptime()));
+    put("cpu_usage", Box::new(stats.cpu_usage as f64));
+    put("used_memory", Box::new(stats.used_memory));
+    put("total_memory", Box::new(stats.total_memory));
+    put("windows", Box::new(app.webview_windows().len() as u32));
+    put("lite", Box::new(config::lite_mode(app)));
+    put("mock", Box::new(mock::enabled()));
+    if let Some((latitude, longitude)) = location::last_position(app) {
+        put("latitude", Box::new(latitude));
+        put("longitude", Box::new(longitude));
+    }
+    status
+}
+
+fn handle_call(app: &AppHandle, call: &Message) -> Message {
+    let interface = call.interface().map(|name| name.to_string()).unwrap_or_default();
+    let member = call.member().map(|name| name.to_string()).unwrap_or_default();
+    match (interface.as_str(), member.as_str()) {
+        (INTROSPECTABLE, "Introspect") => call.method_return().append1(INTROSPECTION),
+        (INTERFACE, "ReloadContent") => {
+            let errors: Vec<String> = app
+                .webview_windows()
+                .values()
+                .filter_map(|window| window.reload().err().map(|e| e.to_string()))
+                .collect();
+            if errors.is_empty() {
+                call.method_return()
+            } else {
+                error_reply(call, "org.kiosk.Control.Error.Failed", &errors.join("; "))
+            }
+        }
+        (INTERFACE, "ShowMessage") => match call.read3::<String, String, u32>() {
+            Ok((title, body, timeout_secs)) => {
+                let message = KioskMessage {
+                    title,
+                    body,
+                    timeout_secs: timeout_secs.min(MAX_MESSAGE_TIMEOUT),
+                    source: "dbus".to_string(),
+                };
+                events::publish(app, "kiosk-message", &message);
+                call.method_return()
+            }
+            Err(e) => error_reply(call, "org.freedesktop.DBus.Error.InvalidArgs", &e.to_string()),
+        },
+        (INTERFACE, "GetStatus") => call.method_return().append1(status(app)),
+        _ => error_reply(
+            call,
+            "org.freedesktop.DBus.Error.UnknownMethod",
+            &format!("No method {}.{}", interface, member),
+        ),
+    }
+}
+
+fn set_status(app: &AppHandle, bus: Option<&str>, error: Option<String>) {
+    let state = app.state::<ControlState>();
+    let mut status = state.0.lock().expect("control status lock");
+    *status = ControlStatus {
+        bus: bus.map(str::to_string),
+        name: BUS_NAME.to_string(),
+        error,
+    };
+}
+
+/// Claim the bus name and serve calls on a background thread
+pub fn start_control(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let (connection, bus) = match connect() {
+            Ok(connected) => connected,
+            Err(e) => {
+                set_status(&app, None, Some(e));
+                std::thread::sleep(RETRY_DELAY);
+                continue;
+            }
+        };
+        set_status(&app, Some(bus), None);
+
+        let handler = app.clone();
+        let rule = MatchRule::new_method_call().with_path(OBJECT_PATH);
+        connection.start_receive(
+            rule,
+            Box::new(move |call, connection| {
+                let reply = handle_call(&handler, &call);
+                if !call.get_no_reply() {
+                    let _ = connection.send(reply);
+                }
+                true
+            }),
+        );
+        while connection.process(PROCESS_TIMEOUT).is_ok() {}
+
+        set_status(&app, None, Some("Lost the D-Bus connection".to_string()));
+        std::thread::sleep(RETRY_DELAY);
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Which bus the control interface is exported on, if any
+#[tauri::command]
+pub fn get_dbus_control_status(state: State<'_, ControlState>) -> ControlStatus {
+    state.0.lock().expect("control status lock").clone()
+}
@@ -0,0 +1,452 @@
+//! Demo server
+//!
+//! Serves a folder of JSON and HTML fixtures over HTTP on localhost, so
+//! sales demos of API-driven kiosk screens run with no network at all.
+//! Routes map a method and path pattern (`/api/items/:id`, or a trailing
+//! `*`) to a file, whose name may use the captured `{id}`, or to an inline
+//! JSON body, with a status, extra headers and a latency to simulate a
+//! slow backend. Requests no route matches are served from the folder as
+//! static files, trying `<path>.json` and `<path>/index.html` as well.
+//! Responses allow any origin, so screens served elsewhere can call it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{help, store};
+
+const CONFIG_FILE: &str = "demo-server.json";
+
+/// Fixture folder under the app data directory when none is set
+const DEFAULT_FOLDER: &str = "demo";
+
+const DEFAULT_PORT: u16 = 8787;
+
+/// How often the accept loop looks for a restart
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest request head and body read
+const MAX_HEAD: usize = 64 * 1024;
+const MAX_BODY: u64 = 1024 * 1024;
+
+/// Longest latency a route may ask for
+const MAX_LATENCY_MS: u64 = 30_000;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DemoRoute {
+    /// "GET", "POST", ... or "*" for any
+    pub method: String,
+    /// `/api/items/:id`; a trailing `*` matches the rest of the path
+    pub path: String,
+    /// Fixture under the folder; `{name}` is replaced by a captured segment
+    pub file: Option<String>,
+    /// Inline JSON answer, used when there is no file
+    pub body: Option<Value>,
+    pub status: u16,
+    pub headers: BTreeMap<String, String>,
+    /// Overrides the server-wide latency
+    pub latency_ms: Option<u64>,
+}
+
+impl Default for DemoRoute {
+    fn default() -> Self {
+        DemoRoute {
+            method: "GET".to_string(),
+            path: "/".to_string(),
+            file: None,
+            body: None,
+            status: 200,
+            headers: BTreeMap::new(),
+            latency_ms: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DemoServerConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Fixture folder; relative paths are under the app data directory
+    pub folder: String,
+    pub routes: Vec<DemoRoute>,
+    /// Delay before every answer
+    pub latency_ms: u64,
+}
+
+impl Default for DemoServerConfig {
+    fn default() -> Self {
+        DemoServerConfig {
+            enabled: false,
+            port: DEFAULT_PORT,
+            folder: DEFAULT_FOLDER.to_string(),
+            routes: Vec::new(),
+            latency_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DemoServerStatus {
+    pub running: bool,
+    /// Base URL while running, e.g. "http://127.0.0.1:8787"
+    pub url: Option<String>,
+    pub folder: String,
+    pub requests: u64,
+    /// Why the server could not start
+    pub error: Option<String>,
+}
+
+struct Answer {
+    status: u16,
+    content_type: String,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+    latency_ms: u64,
+}
+
+pub struct DemoState {
+    config: Mutex<DemoServerConfig>,
+    /// Bumped to stop the running server
+    generation: AtomicU64,
+    requests: AtomicU64,
+    error: Mutex<Option<String>>,
+    running: Mutex<bool>,
+}
+
+impl DemoState {
+    pub fn load(app: &AppHandle) -> Self {
+        DemoState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            generation: AtomicU64::new(0),
+            requests: AtomicU64::new(0),
+            error: Mutex::new(None),
+            running: Mutex::new(false),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn current(app: &AppHandle, generation: u64) -> bool {
+    app.state::<DemoState>().generation.load(Ordering::SeqCst) == generation
+}
+
+fn folder_path(app: &AppHandle, folder: &str) -> Result<PathBuf, String> {
+    let path = Path::new(folder);
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        store::data_path(app, folder)
+    }
+}
+
+fn mime_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "json" => "application/json",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "csv" => "text/csv",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Captured `:name` segments if a route pattern matches a path
+fn match_route(pattern: &str, path: &str) -> Option<HashMap<String, String>> {
+    let mut params = HashMap::new();
+    let mut parts = path.trim_matches('/').split('/');
+    for expected in pattern.trim_matches('/').split('/') {
+        if expected == "*" {
+            params.insert("*".to_string(), parts.collect::<Vec<_>>().join("/"));
+            return Some(params);
+        }
+        let part = parts.next()?;
+        match expected.strip_prefix(':') {
+            Some(name) => {
+                params.insert(name.to_string(), part.to_string());
+            }
+            None if expected == part => {}
+            None => return None,
+        }
+    }
+    parts.next().is_none().then_some(params)
+}
+
+/// A path under the folder, refusing anything that climbs out of it
+fn contained(folder: &Path, relative: &str) -> Option<PathBuf> {
+    let segments: Vec<&str> = relative.split('/').filter(|part| !part.is_empty()).collect();
+    if segments.iter().any(|part| *part == ".." || part.contains('\\')) {
+        return None;
+    }
+    Some(segments.iter().fold(folder.to_path_buf(), |path, part| path.join(part)))
+}
+
+fn file_answer(path: &Path, status: u16) -> Option<Answer> {
+    let body = fs::read(path).ok()?;
+    Some(Answer {
+        status,
+        content_type: mime_type(path).to_string(),
+        headers: BTreeMap::new(),
+        body,
+        latency_ms: 0,
+    })
+}
+
+fn not_found(path: &str) -> Answer {
+    Answer {
+        status: 404,
+        content_type: "application/json".to_string(),
+        headers: BTreeMap::new(),
+        body: serde_json::json!({ "error": format!("No fixture for {}", path) }).to_string().into_bytes(),
+        latency_ms: 0,
+    }
+}
+
+/// Answer a request from the routes, then the folder
+fn answer(config: &DemoServerConfig, folder: &Path, method: &str, path: &str) -> Answer {
+    let routed = config.routes.iter().find_map(|route| {
+        let method_matches = route.method == "*" || route.method.eq_ignore_ascii_case(method);
+        Some((route, match_route(&route.path, path).filter(|_| method_matches)?))
+    });
+    if let Some((route, params)) = routed {
+        let mut answer = match (&route.file, &route.body) {
+            (Some(file), _) => {
+                let file = params
+                    .iter()
+                    .fold(file.clone(), |file, (name, value)| file.replace(&format!("{{{}}}", name), value));
+                contained(folder, &file)
+                    .and_then(|path| file_answer(&path, route.status))
+                    .unwrap_or_else(|| not_found(path))
+            }
+            (None, body) => Answer {
+                status: route.status,
+                content_type: "application/json".to_string(),
+                headers: BTreeMap::new(),
+                body: body.as_ref().map(Value::to_string).unwrap_or_default().into_bytes(),
+                latency_ms: 0,
+            },
+        };
+        answer.headers = route.headers.clone();
+        answer.latency_ms = route.latency_ms.unwrap_or(config.latency_ms);
+        return answer;
+    }
+
+    let mut answer = contained(folder, path)
+        .and_then(|base| {
+            let json = base.with_extension("json");
+            let index = base.join("index.html");
+            [base, json, index]
+                .iter()
+                .filter(|candidate| candidate.is_file())
+                .find_map(|candidate| file_answer(candidate, 200))
+        })
+        .unwrap_or_else(|| not_found(path));
+    answer.latency_ms = config.latency_ms;
+    answer
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+fn write_answer(stream: &mut TcpStream, answer: &Answer, head_only: bool) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\
+         Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: *\r\nAccess-Control-Allow-Headers: *\r\n",
+        answer.status,
+        reason(answer.status),
+        answer.content_type,
+        answer.body.len()
+    );
+    for (name, value) in &answer.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value.replace(['\r', '\n'], " ")));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    if !head_only {
+        stream.write_all(&answer.body)?;
+    }
+    stream.flush()
+}
+
+fn handle(app: &AppHandle, stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.by_ref().take(MAX_HEAD as u64).read_line(&mut request_line)?;
+    let mut fields = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (fields.next(), fields.next()) else { return Ok(()) };
+
+    // Read the headers and any body, which fixtures ignore
+    let mut length = 0;
+    let mut head = request_line.len();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() || head > MAX_HEAD {
+            break;
+        }
+        head += line.len();
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    std::io::copy(&mut reader.take(length.min(MAX_BODY)), &mut std::io::sink())?;
+
+    let state = app.state::<DemoState>();
+    state.requests.fetch_add(1, Ordering::Relaxed);
+    let mut stream = stream;
+    if method == "OPTIONS" {
+        let preflight = Answer {
+            status: 204,
+            content_type: "text/plain".to_string(),
+            headers: BTreeMap::new(),
+            body: Vec::new(),
+            latency_ms: 0,
+        };
+        return write_answer(&mut stream, &preflight, false);
+    }
+
+    let config = state.config.lock().expect("demo config lock").clone();
+    let path = help::percent_decode(target.split(['?', '#']).next().unwrap_or("/"));
+    let answer = match folder_path(app, &config.folder) {
+        Ok(folder) => answer(&config, &folder, method, &path),
+        Err(_) => not_found(&path),
+    };
+    std::thread::sleep(Duration::from_millis(answer.latency_ms.min(MAX_LATENCY_MS)));
+    write_answer(&mut stream, &answer, method == "HEAD")
+}
+
+/// Start the server if enabled, stopping any running one
+pub fn start_demo(app: AppHandle) {
+    let state = app.state::<DemoState>();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let config = state.config.lock().expect("demo config lock").clone();
+    *state.error.lock().expect("demo error lock") = None;
+    *state.running.lock().expect("demo running lock") = false;
+    if !config.enabled {
+        return;
+    }
+
+    std::thread::spawn(move || {
+        let state = app.state::<DemoState>();
+        // The old server may still hold the port for one more poll
+        std::thread::sleep(ACCEPT_POLL * 2);
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, config.port)).and_then(|listener| {
+            listener.set_nonblocking(true)?;
+            Ok(listener)
+        });
+        let listener = match listener {
+            Ok(listener) => listener,
+            Err(e) => {
+                *state.error.lock().expect("demo error lock") = Some(format!("Port {}: {}", config.port, e));
+                return;
+            }
+        };
+        *state.running.lock().expect("demo running lock") = true;
+        while current(&app, generation) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let app = app.clone();
+                    std::thread::spawn(move || {
+                        let _ = stream.set_nonblocking(false);
+                        let _ = handle(&app, stream);
+                    });
+                }
+                Err(_) => std::thread::sleep(ACCEPT_POLL),
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_demo_server_config(state: State<'_, DemoState>) -> DemoServerConfig {
+    state.config.lock().expect("demo config lock").clone()
+}
+
+/// Update the demo server and restart it (admin)
+#[tauri::command]
+pub fn set_demo_server_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, DemoState>,
+    config: DemoServerConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if config.port < 1024 {
+        return Err(KioskError::invalid("Use a port from 1024 up"));
+    }
+    for route in &config.routes {
+        if !route.path.starts_with('/') {
+            return Err(KioskError::invalid(format!("A route path starts with /: {}", route.path)));
+        }
+        if !(100..=599).contains(&route.status) {
+            return Err(KioskError::invalid(format!("Not an HTTP status: {}", route.status)));
+        }
+    }
+    store::save(&app, CONFIG_FILE, &config)?;
+    *state.config.lock().expect("demo config lock") = config;
+    start_demo(app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_demo_server_status(app: AppHandle, state: State<'_, DemoState>) -> DemoServerStatus {
+    let config = state.config.lock().expect("demo config lock").clone();
+    let running = *state.running.lock().expect("demo running lock");
+    DemoServerStatus {
+        running,
+        url: running.then(|| format!("http://127.0.0.1:{}", config.port)),
+        folder: folder_path(&app, &config.folder)
+            .map(|path| path.display().to_string())
+            .unwrap_or(config.folder),
+        requests: state.requests.load(Ordering::Relaxed),
+        error: state.error.lock().expect("demo error lock").clone(),
+    }
+}
@@ -0,0 +1,425 @@
+//! Device tree
+//!
+//! Walks sysfs for USB, PCI, input, sound and video devices and arranges
+//! them the way Device Manager does: the computer at the root, a node per
+//! device category, and USB devices nested under the hubs they hang off.
+//! Vendor and product names missing from sysfs are looked up in the
+//! system's usb.ids / pci.ids databases when installed.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use sysinfo::System;
+
+const USB_DEVICES: &str = "/sys/bus/usb/devices";
+const PCI_DEVICES: &str = "/sys/bus/pci/devices";
+const INPUT_DEVICES: &str = "/sys/class/input";
+const SOUND_CARDS: &str = "/sys/class/sound";
+const DRM_DEVICES: &str = "/sys/class/drm";
+const VIDEO_DEVICES: &str = "/sys/class/video4linux";
+
+const USB_IDS: &[&str] = &["/usr/share/hwdata/usb.ids", "/usr/share/misc/usb.ids", "/var/lib/usbutils/usb.ids"];
+const PCI_IDS: &[&str] = &["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids", "/usr/share/pci.ids"];
+
+/// USB device class of hubs
+const USB_CLASS_HUB: &str = "09";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceStatus {
+    Ok,
+    /// No driver bound (Device Manager's yellow warning)
+    Warning,
+    Disabled,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceNode {
+    pub id: String,
+    pub name: String,
+    /// Icon category: computer, usb, display, sound, keyboard, mouse, hid,
+    /// camera, network, storage, system or other
+    pub class: String,
+    pub vendor: Option<String>,
+    /// `vvvv:pppp` for USB and PCI devices
+    pub hardware_id: Option<String>,
+    pub driver: Option<String>,
+    pub status: DeviceStatus,
+    pub children: Vec<DeviceNode>,
+}
+
+/// vendor id -> (vendor name, device id -> device name)
+type IdDatabase = HashMap<u16, (String, HashMap<u16, String>)>;
+
+static USB_DATABASE: OnceLock<IdDatabase> = OnceLock::new();
+static PCI_DATABASE: OnceLock<IdDatabase> = OnceLock::new();
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Parse the vendor and device sections of a usb.ids / pci.ids file
+fn parse_ids(text: &str) -> IdDatabase {
+    let mut database = IdDatabase::new();
+    let mut vendor: Option<u16> = None;
+    for line in text.lines() {
+        if line.starts_with('#') || line.trim().is_empty() {
+            continue;
+        }
+        // Subsystems (two tabs) are not needed
+        if line.starts_with("\t\t") {
+            continue;
+        }
+        let parse = |entry: &str| {
+            let (id, name) = entry.split_once("  ")?;
+            Some((u16::from_str_radix(id.trim(), 16).ok()?, name.trim().to_string()))
+        };
+        if let Some(device) = line.strip_prefix('\t') {
+            if let (Some(vendor), Some((id, name))) = (vendor, parse(device)) {
+                if let Some(entry) = database.get_mut(&vendor) {
+                    entry.1.insert(id, name);
+                }
+            }
+        } else if let Some((id, name)) = parse(line) {
+            vendor = Some(id);
+            database.insert(id, (name, HashMap::new()));
+        } else {
+            // Class and other trailing sections start with a letter
+            vendor = None;
+        }
+    }
+    database
+}
+
+fn load_ids(paths: &[&str]) -> IdDatabase {
+    paths
+        .iter()
+        .find_map(|path| fs::read(path).ok())
+        .map(|bytes| parse_ids(&String::from_utf8_lossy(&bytes)))
+        .unwrap_or_default()
+}
+
+fn lookup(database: &IdDatabase, vendor: u16, device: u16) -> (Option<String>, Option<String>) {
+    match database.get(&vendor) {
+        Some((vendor_name, devices)) => (Some(vendor_name.clone()), devices.get(&device).cloned()),
+        None => (None, None),
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+fn hex_id(path: &Path) -> Option<u16> {
+    let text = read_trimmed(path)?;
+    u16::from_str_radix(text.trim_start_matches("0x"), 16).ok()
+}
+
+fn driver(path: &Path) -> Option<String> {
+    fs::read_link(path.join("driver"))
+        .ok()
+        .and_then(|target| target.file_name().map(|name| name.to_string_lossy().to_string()))
+}
+
+fn sorted_entries(dir: &str) -> Vec<(String, PathBuf)> {
+    let mut entries: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| (entry.file_name().to_string_lossy().to_string(), entry.path()))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+fn node(id: String, name: String, class: &str) -> DeviceNode {
+    DeviceNode {
+        id,
+        name,
+        class: class.to_string(),
+        vendor: None,
+        hardware_id: None,
+        driver: None,
+        status: DeviceStatus::Ok,
+        children: Vec::new(),
+    }
+}
+
+// ============================================================================
+// Collectors
+// ============================================================================
+
+/// USB devices nested by topology: `1-1.2` hangs off `1-1`, which hangs off
+/// root hub `usb1`
+fn usb_tree() -> Vec<DeviceNode> {
+    let database = USB_DATABASE.get_or_init(|| load_ids(USB_IDS));
+    let entries = sorted_entries(USB_DEVICES);
+
+    let mut nodes: Vec<(String, DeviceNode)> = Vec::new();
+    for (name, path) in &entries {
+        // Interfaces (1-1:1.0) are folded into their device
+        if name.contains(':') {
+            continue;
+        }
+        let (Some(vendor_id), Some(product_id)) = (hex_id(&path.join("idVendor")), hex_id(&path.join("idProduct"))) else {
+            continue;
+        };
+        let (vendor_name, product_name) = lookup(database, vendor_id, product_id);
+        let product = read_trimmed(&path.join("product"))
+            .or(product_name)
+            .unwrap_or_else(|| format!("USB device {:04x}:{:04x}", vendor_id, product_id));
+
+        let is_hub = read_trimmed(&path.join("bDeviceClass")).as_deref() == Some(USB_CLASS_HUB);
+        let interfaces: Vec<Option<String>> = entries
+            .iter()
+            .filter(|(interface, _)| interface.starts_with(&format!("{}:", name)))
+            .map(|(_, interface)| driver(interface))
+            .collect();
+        let mut drivers: Vec<String> = interfaces.iter().flatten().cloned().collect();
+        drivers.dedup();
+
+        let status = if read_trimmed(&path.join("authorized")).as_deref() == Some("0") {
+            DeviceStatus::Disabled
+        } else if !is_hub && interfaces.iter().any(Option::is_none) {
+            DeviceStatus::Warning
+        } else {
+            DeviceStatus::Ok
+        };
+
+        let mut device = node(format!("usb:{}", name), product, "usb");
+        device.vendor = read_trimmed(&path.join("manufacturer")).or(vendor_name);
+        device.hardware_id = Some(format!("{:04x}:{:04x}", vendor_id, product_id));
+        device.driver = (!drivers.is_empty()).then(|| drivers.join(", "));
+        device.status = status;
+        nodes.push((name.clone(), device));
+    }
+
+    fn parent(name: &str) -> Option<String> {
+        if name.starts_with("usb") {
+            return None;
+        }
+        match name.rsplit_once('.') {
+            Some((parent, _)) => Some(parent.to_string()),
+            None => name.split_once('-').map(|(bus, _)| format!("usb{}", bus)),
+        }
+    }
+
+    fn attach(name: &str, nodes: &mut Vec<(String, DeviceNode)>) -> Option<DeviceNode> {
+        let index = nodes.iter().position(|(candidate, _)| candidate == name)?;
+        let (_, mut device) = nodes.remove(index);
+        let children: Vec<String> = nodes
+            .iter()
+            .filter(|(child, _)| parent(child).as_deref() == Some(name))
+            .map(|(child, _)| child.clone())
+            .collect();
+        for child in children {
+            device.children.extend(attach(&child, nodes));
+        }
+        Some(device)
+    }
+
+    let roots: Vec<String> = nodes
+        .iter()
+        .filter(|(name, _)| parent(name).map_or(true, |parent| !nodes.iter().any(|(other, _)| *other == parent)))
+        .map(|(name, _)| name.clone())
+        .collect();
+    roots.iter().filter_map(|name| attach(name, &mut nodes)).collect()
+}
+
+/// Category of a PCI class code (base class and subclass)
+fn pci_category(class: u32) -> &'static str {
+    match class >> 16 {
+        0x01 => "storage",
+        0x02 => "network",
+        0x03 => "display",
+        0x04 => "sound",
+        0x0c if (class >> 8) & 0xff == 0x03 => "usb",
+        0x06 | 0x08 | 0x0c => "system",
+        _ => "other",
+    }
+}
+
+fn pci_devices() -> Vec<(&'static str, DeviceNode)> {
+    let database = PCI_DATABASE.get_or_init(|| load_ids(PCI_IDS));
+    sorted_entries(PCI_DEVICES)
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let vendor_id = hex_id(&path.join("vendor"))?;
+            let device_id = hex_id(&path.join("device"))?;
+            let class = read_trimmed(&path.join("class"))
+                .and_then(|class| u32::from_str_radix(class.trim_start_matches("0x"), 16).ok())
+                .unwrap_or(0);
+            let (vendor_name, device_name) = lookup(database, vendor_id, device_id);
+            let driver = driver(&path);
+            let category = pci_category(class);
+
+            let mut device = node(
+                format!("pci:{}", name),
+                device_name.unwrap_or_else(|| format!("PCI device {:04x}:{:04x}", vendor_id, device_id)),
+                category,
+            );
+            device.vendor = vendor_name;
+            device.hardware_id = Some(format!("{:04x}:{:04x}", vendor_id, device_id));
+            device.status = if driver.is_some() { DeviceStatus::Ok } else { DeviceStatus::Warning };
+            device.driver = driver;
+            Some((category, device))
+        })
+        .collect()
+}
+
+/// Keyboards, pointing devices and other HID inputs, classified by the
+/// event handlers the kernel attached
+fn input_devices() -> Vec<(&'static str, DeviceNode)> {
+    let handlers: HashMap<String, String> = fs::read_to_string("/proc/bus/input/devices")
+        .unwrap_or_default()
+        .split("\n\n")
+        .filter_map(|block| {
+            let sysfs = block.lines().find_map(|line| line.strip_prefix("S: Sysfs="))?;
+            let handlers = block.lines().find_map(|line| line.strip_prefix("H: Handlers="))?;
+            let input = sysfs.rsplit('/').next()?.to_string();
+            Some((input, handlers.to_string()))
+        })
+        .collect();
+
+    sorted_entries(INPUT_DEVICES)
+        .into_iter()
+        .filter(|(name, _)| name.starts_with("input"))
+        .filter_map(|(name, path)| {
+            let device_name = read_trimmed(&path.join("name"))?;
+            let handlers = handlers.get(&name).map(String::as_str).unwrap_or("");
+            let class = if handlers.contains("kbd") && device_name.to_lowercase().contains("keyboard") {
+                "keyboard"
+            } else if handlers.contains("mouse") {
+                "mouse"
+            } else {
+                "hid"
+            };
+            let mut device = node(format!("input:{}", name), device_name, class);
+            device.driver = driver(&path.join("device"));
+            Some((class, device))
+        })
+        .collect()
+}
+
+fn sound_cards() -> Vec<DeviceNode> {
+    sorted_entries(SOUND_CARDS)
+        .into_iter()
+        .filter(|(name, _)| name.starts_with("card"))
+        .map(|(name, path)| {
+            let id = read_trimmed(&path.join("id")).unwrap_or_else(|| name.clone());
+            let mut device = node(format!("sound:{}", name), id, "sound");
+            device.driver = driver(&path.join("device"));
+            device
+        })
+        .collect()
+}
+
+/// GPUs with their display connectors as children
+fn display_adapters() -> Vec<DeviceNode> {
+    let entries = sorted_entries(DRM_DEVICES);
+    entries
+        .iter()
+        .filter(|(name, _)| name.starts_with("card") && !name.contains('-'))
+        .map(|(name, path)| {
+            let driver = driver(&path.join("device"));
+            let title = driver.clone().map_or_else(|| name.clone(), |driver| format!("{} ({})", driver, name));
+            let mut adapter = node(format!("drm:{}", name), title, "display");
+            adapter.driver = driver;
+            adapter.children = entries
+                .iter()
+                .filter_map(|(connector, path)| {
+                    let output = connector.strip_prefix(&format!("{}-", name))?;
+                    let status = read_trimmed(&path.join("status"))?;
+                    let enabled = read_trimmed(&path.join("enabled")).as_deref() != Some("disabled");
+                    let mut display = node(format!("drm:{}", connector), output.to_string(), "display");
+                    display.status = match (status.as_str(), enabled) {
+                        ("connected", true) => DeviceStatus::Ok,
+                        ("connected", false) => DeviceStatus::Disabled,
+                        _ => DeviceStatus::Disconnected,
+                    };
+                    Some(display)
+                })
+                .collect();
+            adapter
+        })
+        .collect()
+}
+
+fn cameras() -> Vec<DeviceNode> {
+    sorted_entries(VIDEO_DEVICES)
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let mut device = node(format!("video:{}", name), read_trimmed(&path.join("name"))?, "camera");
+            device.driver = driver(&path.join("device"));
+            Some(device)
+        })
+        .collect()
+}
+
+fn category(class: &str, name: &str, mut children: Vec<DeviceNode>) -> Option<DeviceNode> {
+    if children.is_empty() {
+        return None;
+    }
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut category = node(format!("category:{}", class), name.to_string(), class);
+    // A category shows a warning when any device below it does
+    if children.iter().any(|child| child.status == DeviceStatus::Warning) {
+        category.status = DeviceStatus::Warning;
+    }
+    category.children = children;
+    Some(category)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Devices grouped by category under the computer, Device Manager style
+#[tauri::command]
+pub fn get_device_tree() -> DeviceNode {
+    let mut by_class: HashMap<&'static str, Vec<DeviceNode>> = HashMap::new();
+    for (class, device) in pci_devices().into_iter().chain(input_devices()) {
+        by_class.entry(class).or_default().push(device);
+    }
+    // Root hubs sit next to the PCI host controllers that provide them
+    by_class.entry("usb").or_default().extend(usb_tree());
+    by_class.entry("display").or_default().extend(display_adapters());
+    by_class.entry("sound").or_default().extend(sound_cards());
+    by_class.entry("camera").or_default().extend(cameras());
+
+    let categories = [
+        ("display", "Display adapters"),
+        ("hid", "Human Interface Devices"),
+        ("camera", "Imaging devices"),
+        ("keyboard", "Keyboards"),
+        ("mouse", "Mice and other pointing devices"),
+        ("network", "Network adapters"),
+        ("other", "Other devices"),
+        ("sound", "Sound, video and game controllers"),
+        ("storage", "Storage controllers"),
+        ("system", "System devices"),
+        ("usb", "Universal Serial Bus controllers"),
+    ];
+    let mut computer = node(
+        "computer".to_string(),
+        System::host_name().unwrap_or_else(|| "Computer".to_string()),
+        "computer",
+    );
+    computer.children = categories
+        .iter()
+        .filter_map(|(class, name)| category(class, name, by_class.remove(class).unwrap_or_default()))
+        .collect();
+    computer
+}
@@ -0,0 +1,482 @@
+//! Download manager
+//!
+//! A persistent queue of HTTP downloads shared by the browser, content and
+//! update fetching. Files are written to `<name>.part` and resumed with a
+//! `Range` request after a pause, a failure or a restart, then checked
+//! against an optional SHA-256 before being moved into place. Downloads run
+//! one at a time within the shared bandwidth limit, each optionally capped
+//! further to `max_kbps`; background ones wait while the monthly data cap
+//! is used up. A file under a root with a quota is held to it as it
+//! streams in. Downloads from the browser may not reach local or private
+//! addresses, through redirects either. Progress is published as
+//! `download-progress` and the outcome as `download-finished`.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use url::Url;
+
+use crate::error::{ErrorKind, KioskError};
+use crate::http::ResponseExt;
+use crate::vfs::Root;
+use crate::{config, events, fetch, http, metering, quota, store, vfs};
+
+const DOWNLOADS_FILE: &str = "downloads.json";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Least time between `download-progress` events for one download
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Finished downloads kept in the list
+const FINISHED_KEPT: usize = 100;
+
+const MAX_REDIRECTS: usize = 5;
+
+/// Downloads asked for from the browser, which may not reach local or
+/// private addresses
+const BROWSER_SOURCE: &str = "browser";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadState {
+    Queued,
+    Active,
+    Paused,
+    Completed,
+    Failed,
+}
+
+/// Payload of `download-progress` and `download-finished`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Download {
+    pub id: String,
+    pub url: String,
+    /// Where the file ends up
+    pub path: PathBuf,
+    /// The same place as a virtual path, when it is under a root
+    pub virtual_path: Option<String>,
+    /// Who asked for it, e.g. "browser" or "content"
+    pub source: String,
+    /// Expected SHA-256, hex
+    pub sha256: Option<String>,
+    /// Non-essential: held back while the data cap is used up
+    pub background: bool,
+    pub max_kbps: Option<u32>,
+    pub state: DownloadState,
+    pub received_bytes: u64,
+    /// Unknown until the server says
+    pub total_bytes: Option<u64>,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+/// What another module asks to download
+pub(crate) struct DownloadRequest {
+    pub url: String,
+    pub path: PathBuf,
+    pub source: String,
+    pub sha256: Option<String>,
+    pub background: bool,
+    pub max_kbps: Option<u32>,
+}
+
+pub struct DownloadsState(Mutex<Vec<Download>>);
+
+impl DownloadsState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut downloads: Vec<Download> = store::load(app, DOWNLOADS_FILE);
+        // One that was running when the app stopped picks up where it left off
+        for download in downloads.iter_mut().filter(|download| download.state == DownloadState::Active) {
+            download.state = DownloadState::Queued;
+        }
+        DownloadsState(Mutex::new(downloads))
+    }
+}
+
+/// How a transfer ended without an error
+enum Outcome {
+    Finished,
+    /// Paused or cancelled while running
+    Stopped,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// Change one download and save the queue, returning the updated entry
+fn update(app: &AppHandle, id: &str, change: impl FnOnce(&mut Download)) -> Option<Download> {
+    let state = app.state::<DownloadsState>();
+    let mut downloads = state.0.lock().expect("downloads lock");
+    let download = downloads.iter_mut().find(|download| download.id == id)?;
+    change(download);
+    let updated = download.clone();
+    let _ = store::save(app, DOWNLOADS_FILE, &*downloads);
+    Some(updated)
+}
+
+/// Note bytes received, in memory only; the `.part` file is what resumes
+fn record_progress(app: &AppHandle, id: &str, received: u64) -> Option<Download> {
+    let state = app.state::<DownloadsState>();
+    let mut downloads = state.0.lock().expect("downloads lock");
+    let download = downloads.iter_mut().find(|download| download.id == id)?;
+    download.received_bytes = received;
+    Some(download.clone())
+}
+
+fn is_active(app: &AppHandle, id: &str) -> bool {
+    let state = app.state::<DownloadsState>();
+    let downloads = state.0.lock().expect("downloads lock");
+    downloads
+        .iter()
+        .any(|download| download.id == id && download.state == DownloadState::Active)
+}
+
+/// Total size from a `Content-Range: bytes 100-199/200` header
+fn range_total(header: &str) -> Option<u64> {
+    header.rsplit('/').next()?.trim().parse().ok()
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The quota error for `incoming` more bytes under `root`, publishing
+/// `quota-exceeded`
+fn over_quota(app: &AppHandle, root: &Root, incoming: u64) -> String {
+    match quota::check(app, root, incoming) {
+        Err(e) => e.message,
+        Ok(()) => format!("{} is full", root.info.name),
+    }
+}
+
+/// Request the file from `offset`, following redirects; browser downloads
+/// are checked at every hop
+fn request(download: &Download, offset: u64) -> Result<ureq::Response, String> {
+    let agent = http::transfer_agent();
+    let mut url = Url::parse(&download.url).map_err(|e| format!("{}: {}", download.url, e))?;
+    for _ in 0..=MAX_REDIRECTS {
+        if download.source == BROWSER_SOURCE {
+            fetch::check_public(&url)?;
+        }
+        let mut request = agent.get(url.as_str());
+        if offset > 0 {
+            request = request.set("Range", &format!("bytes={}-", offset));
+        }
+        let response = request.call().map_err(|e| format!("{}: {}", url, e))?;
+        let location = response.header("Location").map(str::to_string);
+        let (301..=303 | 307 | 308, Some(location)) = (response.status(), location) else {
+            return Ok(response);
+        };
+        url = url.join(&location).map_err(|e| format!("Bad redirect to {}: {}", location, e))?;
+    }
+    Err(format!("Too many redirects downloading {}", download.url))
+}
+
+fn transfer(app: &AppHandle, download: &Download) -> Result<Outcome, String> {
+    let part = part_path(&download.path);
+    if let Some(dir) = part.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let offset = fs::metadata(&part).map_or(0, |metadata| metadata.len());
+    let response = request(download, offset)?;
+
+    // A server that ignores the range sends the whole file again
+    let resumed = response.status() == 206;
+    let mut received = if resumed { offset } else { 0 };
+    let total = match response.header("Content-Range") {
+        Some(range) if resumed => range_total(range),
+        _ => response.header("Content-Length").and_then(|length| length.parse().ok()),
+    };
+    // What was measured before this run may already include the `.part`
+    let root = vfs::root_of(app, &download.path);
+    let allowance = root.as_ref().and_then(|root| quota::remaining(app, root));
+    if let (Some(root), Some(allowance), Some(total)) = (&root, allowance, total) {
+        if total.saturating_sub(received) > allowance {
+            return Err(over_quota(app, root, total.saturating_sub(received)));
+        }
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .map_err(|e| e.to_string())?;
+    update(app, &download.id, |download| {
+        download.received_bytes = received;
+        download.total_bytes = total;
+    });
+
+    let mut reader = response.into_throttled_reader();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let started = Instant::now();
+    let mut sent_since_start = 0u64;
+    let mut last_progress = Instant::now();
+    loop {
+        if !is_active(app, &download.id) {
+            return Ok(Outcome::Stopped);
+        }
+        let read = reader.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        // A missing or lying Content-Length is caught as the body arrives
+        if let (Some(root), Some(allowance)) = (&root, allowance) {
+            if sent_since_start + read as u64 > allowance {
+                drop(file);
+                let _ = fs::remove_file(&part);
+                return Err(over_quota(app, root, sent_since_start + read as u64));
+            }
+        }
+        file.write_all(&buffer[..read]).map_err(|e| e.to_string())?;
+        received += read as u64;
+        sent_since_start += read as u64;
+
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            last_progress = Instant::now();
+            if let Some(updated) = record_progress(app, &download.id, received) {
+                events::publish_progress(app, "download-progress", updated);
+            }
+        }
+        // Hold back until the average rate is under the cap
+        if let Some(kbps) = download.max_kbps.filter(|kbps| *kbps > 0) {
+            let due = Duration::from_secs_f64(sent_since_start as f64 * 8.0 / (kbps as f64 * 1000.0));
+            if let Some(wait) = due.checked_sub(started.elapsed()) {
+                std::thread::sleep(wait);
+            }
+        }
+    }
+    file.flush().map_err(|e| e.to_string())?;
+    drop(file);
+
+    if total.is_some_and(|total| received < total) {
+        return Err(format!("The download ended early, at {} of {:?} bytes", received, total));
+    }
+    if let Some(expected) = &download.sha256 {
+        let actual = sha256_file(&part)?;
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            let _ = fs::remove_file(&part);
+            return Err(format!("Checksum mismatch: expected {}, got {}", expected, actual));
+        }
+    }
+    fs::rename(&part, &download.path).map_err(|e| e.to_string())?;
+    update(app, &download.id, |download| download.received_bytes = received);
+    if let Some(path) = &download.virtual_path {
+        vfs::changed(app, path, "create");
+    }
+    Ok(Outcome::Finished)
+}
+
+/// Drop the oldest finished downloads beyond the retention limit
+fn prune(downloads: &mut Vec<Download>) {
+    let finished = |download: &Download| matches!(download.state, DownloadState::Completed | DownloadState::Failed);
+    let mut excess = downloads.iter().filter(|download| finished(download)).count().saturating_sub(FINISHED_KEPT);
+    downloads.retain(|download| {
+        let drop = excess > 0 && finished(download);
+        excess -= drop as usize;
+        !drop
+    });
+}
+
+/// Queue a download for the worker
+pub(crate) fn enqueue(app: &AppHandle, request: DownloadRequest) -> Result<Download, String> {
+    if !request.url.starts_with("http://") && !request.url.starts_with("https://") {
+        return Err("Download URL must start with http:// or https://".to_string());
+    }
+    if request.sha256.as_ref().is_some_and(|hash| {
+        let hash = hash.trim();
+        hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit())
+    }) {
+        return Err("A SHA-256 is 64 hex digits".to_string());
+    }
+    let download = Download {
+        id: uuid::Uuid::new_v4().to_string(),
+        virtual_path: vfs::to_virtual(app, &request.path),
+        url: request.url,
+        path: request.path,
+        source: request.source,
+        sha256: request.sha256,
+        background: request.background,
+        max_kbps: request.max_kbps,
+        state: DownloadState::Queued,
+        received_bytes: 0,
+        total_bytes: None,
+        error: None,
+        created_at: Local::now().timestamp(),
+        finished_at: None,
+    };
+    let state = app.state::<DownloadsState>();
+    let mut downloads = state.0.lock().expect("downloads lock");
+    let pending = |other: &Download| {
+        matches!(other.state, DownloadState::Queued | DownloadState::Active | DownloadState::Paused)
+    };
+    if downloads.iter().any(|other| other.path == download.path && pending(other)) {
+        return Err(format!("{} is already being downloaded", download.path.display()));
+    }
+    downloads.push(download.clone());
+    prune(&mut downloads);
+    store::save(app, DOWNLOADS_FILE, &*downloads)?;
+    Ok(download)
+}
+
+/// Spawn the worker that runs queued downloads in order
+pub fn start_downloads(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let background_allowed = metering::background_allowed(&app);
+        let next = {
+            let state = app.state::<DownloadsState>();
+            let mut downloads = state.0.lock().expect("downloads lock");
+            let next = downloads.iter_mut().find(|download| {
+                download.state == DownloadState::Queued && (background_allowed || !download.background)
+            });
+            next.map(|download| {
+                download.state = DownloadState::Active;
+                download.clone()
+            })
+        };
+        let Some(download) = next else {
+            std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+            continue;
+        };
+
+        let outcome = transfer(&app, &download);
+        if let Ok(Outcome::Stopped) = outcome {
+            continue;
+        }
+        let finished = update(&app, &download.id, |download| {
+            download.finished_at = Some(Local::now().timestamp());
+            match outcome {
+                Ok(_) => download.state = DownloadState::Completed,
+                Err(error) => {
+                    download.state = DownloadState::Failed;
+                    download.error = Some(error);
+                }
+            }
+        });
+        if let Some(finished) = finished {
+            events::publish(&app, "download-finished", finished);
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Queue a download to a virtual path, by default into Downloads under the
+/// URL's file name
+#[tauri::command]
+pub fn queue_download(
+    app: AppHandle,
+    url: String,
+    path: Option<String>,
+    sha256: Option<String>,
+    background: Option<bool>,
+    max_kbps: Option<u32>,
+) -> Result<Download, KioskError> {
+    let url = url.trim().to_string();
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let name = url
+                .split(['?', '#'])
+                .next()
+                .and_then(|url| url.rsplit('/').next())
+                .filter(|name| !name.is_empty())
+                .unwrap_or("download");
+            format!("downloads/{}", name)
+        }
+    };
+    let parsed = Url::parse(&url).map_err(|e| KioskError::invalid(format!("{}: {}", url, e)))?;
+    fetch::check_public(&parsed).map_err(|e| KioskError::new(ErrorKind::Denied, e))?;
+    // The size is only known once the server answers; the worker holds it to the quota then
+    let path = vfs::resolve_write(&app, &path, 0)?;
+    Ok(enqueue(
+        &app,
+        DownloadRequest {
+            url,
+            path,
+            source: BROWSER_SOURCE.to_string(),
+            sha256,
+            background: background.unwrap_or(false),
+            max_kbps,
+        },
+    )?)
+}
+
+/// Every download, oldest first
+#[tauri::command]
+pub fn list_downloads(state: State<'_, DownloadsState>) -> Vec<Download> {
+    state.0.lock().expect("downloads lock").clone()
+}
+
+/// Stop a queued or running download, keeping what has arrived
+#[tauri::command]
+pub fn pause_download(app: AppHandle, id: String) -> Result<Download, KioskError> {
+    update(&app, &id, |download| {
+        if matches!(download.state, DownloadState::Queued | DownloadState::Active) {
+            download.state = DownloadState::Paused;
+        }
+    })
+    .ok_or_else(|| KioskError::not_found(format!("No such download: {}", id)))
+}
+
+/// Queue a paused or failed download again, continuing from where it stopped
+#[tauri::command]
+pub fn resume_download(app: AppHandle, id: String) -> Result<Download, KioskError> {
+    update(&app, &id, |download| {
+        if matches!(download.state, DownloadState::Paused | DownloadState::Failed) {
+            download.state = DownloadState::Queued;
+            download.error = None;
+            download.finished_at = None;
+        }
+    })
+    .ok_or_else(|| KioskError::not_found(format!("No such download: {}", id)))
+}
+
+/// Remove a download from the list, deleting any partial file; a
+/// completed file is kept
+#[tauri::command]
+pub fn cancel_download(app: AppHandle, state: State<'_, DownloadsState>, id: String) -> Result<(), KioskError> {
+    let mut downloads = state.0.lock().expect("downloads lock");
+    let index = downloads
+        .iter()
+        .position(|download| download.id == id)
+        .ok_or_else(|| KioskError::not_found(format!("No such download: {}", id)))?;
+    let download = downloads.remove(index);
+    if download.state != DownloadState::Completed {
+        let _ = fs::remove_file(part_path(&download.path));
+    }
+    Ok(store::save(&app, DOWNLOADS_FILE, &*downloads)?)
+}
@@ -0,0 +1,103 @@
+//! Command errors
+//!
+//! `KioskError` is the error every Tauri command returns. It serializes to an
+//! envelope `{ kind, message }` so the frontend can branch on the kind
+//! instead of parsing message text. Internal helpers keep returning plain
+//! strings; `?` converts them at the command boundary. Messages are written
+//! in English and translated as they are sent (see `i18n`).
+
+use serde::Serialize;
+use std::fmt;
+use std::io;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Bad arguments or configuration
+    Invalid,
+    NotFound,
+    /// Not allowed for the current operator or by the OS
+    Denied,
+    /// Too many calls to the command; retry later
+    RateLimited,
+    Io,
+    /// Anything else a command reported
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KioskError {
+    pub kind: ErrorKind,
+    /// Sent in the kiosk's language when the catalog translates it
+    #[serde(serialize_with = "crate::i18n::serialize_error_message")]
+    pub message: String,
+}
+
+impl KioskError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        KioskError {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        KioskError::new(ErrorKind::Invalid, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        KioskError::new(ErrorKind::NotFound, message)
+    }
+}
+
+impl fmt::Display for KioskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for KioskError {}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+impl From<String> for KioskError {
+    fn from(message: String) -> Self {
+        KioskError::new(ErrorKind::Failed, message)
+    }
+}
+
+impl From<&str> for KioskError {
+    fn from(message: &str) -> Self {
+        KioskError::new(ErrorKind::Failed, message)
+    }
+}
+
+impl From<io::Error> for KioskError {
+    fn from(error: io::Error) -> Self {
+        let kind = match error.kind() {
+            io::ErrorKind::NotFound => ErrorKind::NotFound,
+            io::ErrorKind::PermissionDenied => ErrorKind::Denied,
+            io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData => ErrorKind::Invalid,
+            _ => ErrorKind::Io,
+        };
+        KioskError::new(kind, error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for KioskError {
+    fn from(error: serde_json::Error) -> Self {
+        KioskError::new(ErrorKind::Invalid, error.to_string())
+    }
+}
+
+impl From<tauri::Error> for KioskError {
+    fn from(error: tauri::Error) -> Self {
+        KioskError::new(ErrorKind::Failed, error.to_string())
+    }
+}
@@ -0,0 +1,191 @@
+//! Internal event bus
+//!
+//! Every backend event goes through `publish`: it is stamped with a sequence
+//! number, kept in a ring buffer of recent history, delivered to backend
+//! listeners (the rules engine) and emitted to the frontend under its topic.
+//! The frontend can also subscribe to a set of topics and receive them, with
+//! replay of recent history, on a per-subscription channel.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::config;
+use crate::error::KioskError;
+
+/// Number of events kept for replay and inspection
+const HISTORY_SIZE: usize = 500;
+
+/// Lite mode emits at most one progress event per topic this often
+const LITE_PROGRESS_SPACING: Duration = Duration::from_millis(500);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub seq: u64,
+    pub topic: String,
+    pub payload: Value,
+    pub time: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventSubscription {
+    pub id: String,
+    /// Frontend event name the subscription is delivered on
+    pub channel: String,
+    /// History matching the topics, oldest first
+    pub replay: Vec<EventRecord>,
+}
+
+type Listener = Arc<dyn Fn(&AppHandle, &EventRecord) + Send + Sync>;
+
+#[derive(Default)]
+struct Bus {
+    next_seq: u64,
+    history: VecDeque<EventRecord>,
+    /// Frontend subscriptions: id -> topic patterns
+    subscriptions: HashMap<String, Vec<String>>,
+    listeners: Vec<Listener>,
+    /// When each progress topic was last emitted, for lite mode throttling
+    progress_sent: HashMap<String, Instant>,
+}
+
+#[derive(Default)]
+pub struct EventBus(Mutex<Bus>);
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Topic patterns are exact names, a `prefix*`, or `*` for everything
+fn topic_matches(pattern: &str, topic: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => topic.starts_with(prefix),
+        None => pattern == topic,
+    }
+}
+
+fn channel(id: &str) -> String {
+    format!("event-bus:{}", id)
+}
+
+/// Register a backend listener called for every published event
+pub(crate) fn listen(app: &AppHandle, listener: impl Fn(&AppHandle, &EventRecord) + Send + Sync + 'static) {
+    let bus = app.state::<EventBus>();
+    bus.0.lock().expect("event bus lock").listeners.push(Arc::new(listener));
+}
+
+/// Publish an event to history, backend listeners and the frontend
+pub(crate) fn publish<T: Serialize>(app: &AppHandle, topic: &str, payload: T) {
+    let payload = serde_json::to_value(payload).unwrap_or(Value::Null);
+    let Some(bus) = app.try_state::<EventBus>() else {
+        let _ = app.emit(topic, payload);
+        return;
+    };
+
+    let (record, channels, listeners) = {
+        let mut bus = bus.0.lock().expect("event bus lock");
+        bus.next_seq += 1;
+        let record = EventRecord {
+            seq: bus.next_seq,
+            topic: topic.to_string(),
+            payload,
+            time: Local::now().timestamp_millis(),
+        };
+        if bus.history.len() == HISTORY_SIZE {
+            bus.history.pop_front();
+        }
+        bus.history.push_back(record.clone());
+        let channels: Vec<String> = bus
+            .subscriptions
+            .iter()
+            .filter(|(_, patterns)| patterns.iter().any(|pattern| topic_matches(pattern, topic)))
+            .map(|(id, _)| channel(id))
+            .collect();
+        (record, channels, bus.listeners.clone())
+    };
+
+    let _ = app.emit(topic, &record.payload);
+    for channel in channels {
+        let _ = app.emit(&channel, &record);
+    }
+    // Listeners run without the lock so they may publish in turn
+    for listener in listeners {
+        listener(app, &record);
+    }
+}
+
+/// Emit a high-frequency progress event without recording it in history
+pub(crate) fn publish_progress<T: Serialize>(app: &AppHandle, topic: &str, payload: T) {
+    if let Some(bus) = app.try_state::<EventBus>().filter(|_| config::lite_mode(app)) {
+        let mut bus = bus.0.lock().expect("event bus lock");
+        let now = Instant::now();
+        let recent = bus
+            .progress_sent
+            .get(topic)
+            .is_some_and(|sent| now.duration_since(*sent) < LITE_PROGRESS_SPACING);
+        if recent {
+            return;
+        }
+        bus.progress_sent.insert(topic.to_string(), now);
+    }
+    let _ = app.emit(topic, serde_json::to_value(payload).unwrap_or(Value::Null));
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Subscribe to topics (exact, `prefix*` or `*`), replaying matching history
+#[tauri::command]
+pub fn subscribe_events(state: State<'_, EventBus>, topics: Vec<String>, replay: Option<bool>) -> Result<EventSubscription, KioskError> {
+    if topics.is_empty() {
+        return Err(KioskError::invalid("At least one topic is required"));
+    }
+
+    let mut bus = state.0.lock().expect("event bus lock");
+    let replay = if replay.unwrap_or(true) {
+        bus.history
+            .iter()
+            .filter(|record| topics.iter().any(|pattern| topic_matches(pattern, &record.topic)))
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    bus.subscriptions.insert(id.clone(), topics);
+    Ok(EventSubscription {
+        channel: channel(&id),
+        id,
+        replay,
+    })
+}
+
+/// Stop delivering events to a subscription
+#[tauri::command]
+pub fn unsubscribe_events(state: State<'_, EventBus>, id: String) {
+    state.0.lock().expect("event bus lock").subscriptions.remove(&id);
+}
+
+/// Recent events, oldest first, optionally filtered by topic pattern
+#[tauri::command]
+pub fn dump_event_history(state: State<'_, EventBus>, topic: Option<String>, limit: Option<usize>) -> Vec<EventRecord> {
+    let bus = state.0.lock().expect("event bus lock");
+    let matching: Vec<EventRecord> = bus
+        .history
+        .iter()
+        .filter(|record| topic.as_ref().map_or(true, |pattern| topic_matches(pattern, &record.topic)))
+        .cloned()
+        .collect();
+    let skip = matching.len().saturating_sub(limit.unwrap_or(HISTORY_SIZE));
+    matching.into_iter().skip(skip).collect()
+}
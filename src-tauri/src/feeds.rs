@@ -0,0 +1,403 @@
+//! News feeds
+//!
+//! RSS 2.0 and Atom fetching for the Active Desktop style news ticker. Items
+//! are cached on disk so the ticker keeps scrolling while offline, and item
+//! summaries are reduced to a small whitelist of inline HTML before they
+//! reach the webview.
+
+use chrono::{DateTime, Local};
+use quick_xml::events::{BytesStart, Event};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::error::KioskError;
+use crate::http::ResponseExt;
+use crate::{http, store};
+
+const FEEDS_FILE: &str = "feeds.json";
+
+/// Items kept per feed, newest first
+const MAX_ITEMS_PER_FEED: usize = 50;
+
+/// Tags allowed through the summary sanitizer (attributes are dropped except `a href`)
+const ALLOWED_TAGS: &[&str] = &[
+    "a", "b", "strong", "i", "em", "u", "p", "br", "ul", "ol", "li", "blockquote",
+];
+
+/// Tags whose content is dropped along with the tag
+const DROPPED_TAGS: &[&str] = &["script", "style", "iframe", "object", "noscript"];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// A subscribed feed and the outcome of its last refresh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feed {
+    pub url: String,
+    pub title: String,
+    pub last_fetched: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// A cached feed item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedItem {
+    pub id: String,
+    pub feed_url: String,
+    pub feed_title: String,
+    pub title: String,
+    pub link: String,
+    /// Sanitized HTML summary
+    pub summary: String,
+    pub published: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FeedCache {
+    feeds: Vec<Feed>,
+    items: Vec<FeedItem>,
+}
+
+pub struct FeedsState(Mutex<FeedCache>);
+
+impl FeedsState {
+    pub fn load(app: &AppHandle) -> Self {
+        FeedsState(Mutex::new(store::load(app, FEEDS_FILE)))
+    }
+}
+
+/// Fields collected while walking an <item> or <entry>
+#[derive(Default)]
+struct ParsedItem {
+    id: String,
+    title: String,
+    link: String,
+    summary: String,
+    published: Option<i64>,
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+fn parse_date(value: &str) -> Option<i64> {
+    let value = value.trim();
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .map(|dt| dt.timestamp())
+        .ok()
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .try_get_attribute(name)
+        .ok()
+        .flatten()
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+/// Route element text into the current item, or the feed title outside items
+fn collect_text(path: &[String], item: Option<&mut ParsedItem>, title: &mut String, text: &str) {
+    let Some(element) = path.last().map(String::as_str) else { return };
+
+    match item {
+        Some(item) => match element {
+            "title" => item.title.push_str(text),
+            "link" => item.link.push_str(text.trim()),
+            "guid" | "id" => item.id.push_str(text.trim()),
+            // Prefer the short form when both are present
+            "description" | "summary" => item.summary = text.to_string(),
+            "encoded" | "content" if item.summary.is_empty() => item.summary = text.to_string(),
+            "pubdate" | "published" | "updated" | "date" if item.published.is_none() => {
+                item.published = parse_date(text);
+            }
+            _ => {}
+        },
+        None if element == "title" && title.is_empty() => *title = text.trim().to_string(),
+        None => {}
+    }
+}
+
+/// Parse an RSS or Atom document into its title and items
+fn parse_feed(xml: &str) -> Result<(String, Vec<ParsedItem>), String> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut path: Vec<String> = Vec::new();
+    let mut title = String::new();
+    let mut items = Vec::new();
+    let mut current: Option<ParsedItem> = None;
+    let mut recognized = false;
+
+    loop {
+        let event = reader.read_event().map_err(|e| format!("Invalid feed: {}", e))?;
+        match event {
+            Event::Start(element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).to_lowercase();
+                match name.as_str() {
+                    "rss" | "feed" | "rdf" => recognized = true,
+                    "item" | "entry" => current = Some(ParsedItem::default()),
+                    "link" => {
+                        if let (Some(item), Some(href)) = (current.as_mut(), attribute(&element, "href")) {
+                            item.link = href;
+                        }
+                    }
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::Empty(element) => {
+                // Atom links are empty elements; prefer rel="alternate" (the default)
+                let name = element.local_name();
+                if name.as_ref().eq_ignore_ascii_case(b"link") {
+                    let rel = attribute(&element, "rel").unwrap_or_else(|| "alternate".to_string());
+                    if let (Some(item), Some(href), true) = (current.as_mut(), attribute(&element, "href"), rel == "alternate") {
+                        item.link = href;
+                    }
+                }
+            }
+            Event::End(element) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).to_lowercase();
+                if name == "item" || name == "entry" {
+                    items.extend(current.take());
+                }
+                path.pop();
+            }
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|e| e.to_string())?;
+                collect_text(&path, current.as_mut(), &mut title, &text);
+            }
+            Event::CData(data) => {
+                let text = String::from_utf8_lossy(&data.into_inner()).to_string();
+                collect_text(&path, current.as_mut(), &mut title, &text);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if !recognized {
+        return Err("Not an RSS or Atom feed".to_string());
+    }
+    Ok((title, items))
+}
+
+// ============================================================================
+// Sanitizing
+// ============================================================================
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
+
+/// Rebuild a tag from the whitelist, or None to drop it
+fn sanitize_tag(tag: &str) -> Option<String> {
+    let closing = tag.starts_with('/');
+    let body = tag.trim_start_matches('/').trim_end_matches('/');
+    let name = body
+        .split(|c: char| c.is_whitespace())
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    if !ALLOWED_TAGS.contains(&name.as_str()) {
+        return None;
+    }
+    if closing {
+        return Some(format!("</{}>", name));
+    }
+    if name == "br" {
+        return Some("<br>".to_string());
+    }
+    if name == "a" {
+        let href = body
+            .split_once("href=")
+            .map(|(_, rest)| {
+                let rest = rest.trim_start();
+                match rest.chars().next() {
+                    Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next().unwrap_or_default(),
+                    _ => rest.split(|c: char| c.is_whitespace()).next().unwrap_or_default(),
+                }
+            })
+            .filter(|href| href.starts_with("http://") || href.starts_with("https://"));
+        return Some(match href {
+            Some(href) => format!("<a href=\"{}\">", escape_attribute(href)),
+            None => "<a>".to_string(),
+        });
+    }
+    Some(format!("<{}>", name))
+}
+
+/// Reduce untrusted feed HTML to whitelisted tags without scripts or event handlers
+fn sanitize_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        out.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.split_once("-->").map_or("", |(_, after)| after);
+            continue;
+        }
+        let Some(close) = rest.find('>') else {
+            out.push_str("&lt;");
+            rest = &rest[1..];
+            continue;
+        };
+
+        let tag = &rest[1..close];
+        rest = &rest[close + 1..];
+
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or_default().to_lowercase();
+        if DROPPED_TAGS.contains(&name.as_str()) && !tag.starts_with('/') {
+            let end = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&end) {
+                Some(index) => rest[index..].split_once('>').map_or("", |(_, after)| after),
+                None => "",
+            };
+            continue;
+        }
+        if let Some(tag) = sanitize_tag(tag) {
+            out.push_str(&tag);
+        }
+    }
+
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+// ============================================================================
+// Fetching
+// ============================================================================
+
+fn fetch(url: &str) -> Result<(String, Vec<FeedItem>), String> {
+    let xml = http::agent()
+        .get(url)
+        .call()
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?
+        .into_throttled_string()
+        .map_err(|e| e.to_string())?;
+    let (title, parsed) = parse_feed(&xml)?;
+    let title = if title.is_empty() { url.to_string() } else { title };
+
+    let mut items: Vec<FeedItem> = parsed
+        .into_iter()
+        .map(|item| {
+            let id = [&item.id, &item.link, &item.title]
+                .into_iter()
+                .find(|value| !value.is_empty())
+                .cloned()
+                .unwrap_or_default();
+            FeedItem {
+                id,
+                feed_url: url.to_string(),
+                feed_title: title.clone(),
+                title: item.title.trim().to_string(),
+                link: item.link,
+                summary: sanitize_html(&item.summary),
+                published: item.published,
+            }
+        })
+        .filter(|item| !item.title.is_empty())
+        .collect();
+
+    items.sort_by_key(|item| std::cmp::Reverse(item.published));
+    items.truncate(MAX_ITEMS_PER_FEED);
+    Ok((title, items))
+}
+
+/// Store a fetch result, keeping the feed's old items on failure
+fn apply(cache: &mut FeedCache, url: &str, result: Result<(String, Vec<FeedItem>), String>) -> Result<(), String> {
+    let Some(feed) = cache.feeds.iter_mut().find(|feed| feed.url == url) else {
+        return Ok(());
+    };
+
+    match result {
+        Ok((title, items)) => {
+            feed.title = title;
+            feed.last_fetched = Some(Local::now().timestamp());
+            feed.last_error = None;
+            cache.items.retain(|item| item.feed_url != url);
+            cache.items.extend(items);
+            Ok(())
+        }
+        Err(error) => {
+            feed.last_error = Some(error.clone());
+            Err(error)
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List subscribed feeds
+#[tauri::command]
+pub fn list_feeds(state: State<'_, FeedsState>) -> Vec<Feed> {
+    state.0.lock().expect("feeds lock").feeds.clone()
+}
+
+/// Subscribe to an RSS or Atom feed and fetch it once
+#[tauri::command(async)]
+pub fn add_feed(app: AppHandle, state: State<'_, FeedsState>, url: String) -> Result<Feed, KioskError> {
+    let url = url.trim().to_string();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(KioskError::invalid("Feed URL must start with http:// or https://"));
+    }
+
+    if state.0.lock().expect("feeds lock").feeds.iter().any(|feed| feed.url == url) {
+        return Err(format!("Already subscribed to {}", url).into());
+    }
+    let result = fetch(&url)?;
+
+    let mut cache = state.0.lock().expect("feeds lock");
+    cache.feeds.push(Feed {
+        url: url.clone(),
+        title: url.clone(),
+        last_fetched: None,
+        last_error: None,
+    });
+    apply(&mut cache, &url, Ok(result))?;
+
+    store::save(&app, FEEDS_FILE, &*cache)?;
+    Ok(cache.feeds.iter().find(|feed| feed.url == url).cloned().expect("feed just added"))
+}
+
+/// Unsubscribe from a feed and drop its cached items
+#[tauri::command]
+pub fn remove_feed(app: AppHandle, state: State<'_, FeedsState>, url: String) -> Result<(), KioskError> {
+    let mut cache = state.0.lock().expect("feeds lock");
+    cache.feeds.retain(|feed| feed.url != url);
+    cache.items.retain(|item| item.feed_url != url);
+    Ok(store::save(&app, FEEDS_FILE, &*cache)?)
+}
+
+/// Refresh every feed. Feeds that fail keep their cached items and report the error.
+#[tauri::command(async)]
+pub fn refresh_feeds(app: AppHandle, state: State<'_, FeedsState>) -> Result<Vec<Feed>, KioskError> {
+    let urls: Vec<String> = state.0.lock().expect("feeds lock").feeds.iter().map(|feed| feed.url.clone()).collect();
+    // Fetch without holding the lock so the ticker can keep reading the cache
+    let results: Vec<_> = urls.iter().map(|url| fetch(url)).collect();
+
+    let mut cache = state.0.lock().expect("feeds lock");
+    for (url, result) in urls.iter().zip(results) {
+        let _ = apply(&mut cache, url, result);
+    }
+
+    store::save(&app, FEEDS_FILE, &*cache)?;
+    Ok(cache.feeds.clone())
+}
+
+/// Get the newest cached items across all feeds
+#[tauri::command]
+pub fn get_feed_items(state: State<'_, FeedsState>, limit: Option<usize>) -> Vec<FeedItem> {
+    let cache = state.0.lock().expect("feeds lock");
+    let mut items = cache.items.clone();
+    items.sort_by_key(|item| std::cmp::Reverse(item.published));
+    items.truncate(limit.unwrap_or(50));
+    items
+}
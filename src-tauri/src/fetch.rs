@@ -0,0 +1,560 @@
+//! HTTP fetch for applets
+//!
+//! `fetch_url` makes HTTP requests from the backend for frontend applets,
+//! so they are not bound by CORS and go out with the kiosk's TLS, proxy and
+//! bandwidth settings. A request can name a cookie jar, kept in memory and
+//! emptied with the rest of the browsing data on a session reset. GET
+//! responses are cached in memory as `Cache-Control` allows and revalidated
+//! with `ETag`/`Last-Modified`; `cache` picks the mode as in the browser's
+//! `fetch`. Network errors and 429/502/503/504 answers can be retried with
+//! backoff. Hosts can be limited to an allow-list in `fetch.json`, which
+//! also holds the proxy for all backend requests. Loopback, private and
+//! link-local addresses are refused, on every redirect hop, unless listed.
+
+use base64::Engine;
+use chrono::Local;
+use cookie::Cookie;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use url::{Host, Url};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::http::ResponseExt;
+use crate::{http, store};
+
+const CONFIG_FILE: &str = "fetch.json";
+
+/// Largest response body returned
+const MAX_BODY: u64 = 10 * 1024 * 1024;
+
+/// Largest response body cached
+const MAX_CACHED_BODY: usize = 1024 * 1024;
+
+const CACHE_ENTRIES: usize = 200;
+
+const MAX_RETRIES: u32 = 5;
+
+const MAX_REDIRECTS: usize = 5;
+
+/// First retry delay, doubled for each further attempt
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest `Retry-After` honoured
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+const RETRY_STATUSES: &[u16] = &[429, 502, 503, 504];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// How the cache is used, as in the browser's `fetch`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheMode {
+    /// Serve fresh entries, revalidate stale ones
+    #[default]
+    Default,
+    /// Neither read nor write the cache
+    NoStore,
+    /// Skip the cache but store the answer
+    Reload,
+    /// Always revalidate a cached entry
+    NoCache,
+    /// Serve any cached entry, however old
+    ForceCache,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FetchRequest {
+    pub url: String,
+    /// GET when empty
+    pub method: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+    /// Cookie jar to send and store cookies with; none when absent
+    pub jar: Option<String>,
+    pub cache: CacheMode,
+    /// Extra attempts after a network error or a 429/502/503/504
+    pub retries: u32,
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchResponse {
+    pub status: u16,
+    pub status_text: String,
+    /// Final URL after redirects
+    pub url: String,
+    /// Lower-case names; repeated headers are joined with ", "
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+    /// The body is base64 because it is not text
+    pub base64: bool,
+    pub from_cache: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchConfig {
+    /// Proxy for every backend request, e.g. "http://proxy:3128"
+    pub proxy: Option<String>,
+    /// Hosts `fetch_url` may reach; "*.example.com" covers subdomains.
+    /// Any public host when empty; local and private addresses only when
+    /// listed here.
+    pub allowed_hosts: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    domain: String,
+    /// Sent to `domain` only, not its subdomains
+    host_only: bool,
+    path: String,
+    secure: bool,
+    expires: Option<i64>,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    response: FetchResponse,
+    stored: Instant,
+    fresh_for: Duration,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+pub struct FetchState {
+    config: Mutex<FetchConfig>,
+    jars: Mutex<HashMap<String, Vec<StoredCookie>>>,
+    cache: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl FetchState {
+    pub fn load(app: &AppHandle) -> Self {
+        let config: FetchConfig = store::load(app, CONFIG_FILE);
+        let _ = http::set_proxy(config.proxy.clone());
+        FetchState {
+            config: Mutex::new(config),
+            jars: Mutex::new(HashMap::new()),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Whether `host` is on the allow-list itself, rather than let in by an
+/// empty one
+fn host_listed(config: &FetchConfig, host: &str) -> bool {
+    config.allowed_hosts.iter().any(|pattern| {
+        let pattern = pattern.trim().to_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+            None => host == pattern,
+        }
+    })
+}
+
+fn host_allowed(config: &FetchConfig, host: &str) -> bool {
+    config.allowed_hosts.is_empty() || host_listed(config, host)
+}
+
+/// Loopback, private, link-local (including the 169.254.169.254 metadata
+/// service) and other non-public addresses
+fn internal_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            match ip.to_ipv4_mapped() {
+                Some(mapped) => internal_address(IpAddr::V4(mapped)),
+                None => {
+                    ip.is_loopback() || ip.is_unspecified() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+                }
+            }
+        }
+    }
+}
+
+fn check_url(config: &FetchConfig, url: &Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Only http and https URLs can be fetched: {}", url));
+    }
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    if !host_allowed(config, &host) {
+        return Err(format!("{} is not an allowed host", host));
+    }
+    if !host_listed(config, &host) {
+        check_public(url).map_err(|e| format!("{}; add it to the allowed hosts to fetch it", e))?;
+    }
+    Ok(())
+}
+
+/// Refuse a URL whose host is, or resolves to, a local or private address
+pub(crate) fn check_public(url: &Url) -> Result<(), String> {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addresses = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![IpAddr::V4(ip)],
+        Some(Host::Ipv6(ip)) => vec![IpAddr::V6(ip)],
+        _ => (host.as_str(), port)
+            .to_socket_addrs()
+            .map_err(|e| format!("Cannot resolve {}: {}", host, e))?
+            .map(|address| address.ip())
+            .collect(),
+    };
+    if addresses.into_iter().any(internal_address) {
+        return Err(format!("{} is a local or private address", host));
+    }
+    Ok(())
+}
+
+fn domain_matches(cookie: &StoredCookie, host: &str) -> bool {
+    host == cookie.domain || (!cookie.host_only && host.ends_with(&format!(".{}", cookie.domain)))
+}
+
+fn path_matches(cookie_path: &str, path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path) && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
+
+/// `Cookie` header for a request from a jar
+fn cookie_header(cookies: &[StoredCookie], url: &Url) -> Option<String> {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let now = Local::now().timestamp();
+    let pairs: Vec<String> = cookies
+        .iter()
+        .filter(|cookie| cookie.expires.map_or(true, |expires| expires > now))
+        .filter(|cookie| domain_matches(cookie, &host) && path_matches(&cookie.path, url.path()))
+        .filter(|cookie| !cookie.secure || url.scheme() == "https")
+        .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+        .collect();
+    (!pairs.is_empty()).then(|| pairs.join("; "))
+}
+
+/// Add or replace cookies from `Set-Cookie` headers
+fn store_cookies(cookies: &mut Vec<StoredCookie>, url: &Url, headers: &[&str]) {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let now = Local::now().timestamp();
+    for header in headers {
+        let Ok(parsed) = Cookie::parse(header.to_string()) else { continue };
+        let domain = parsed.domain().map(|domain| domain.trim_start_matches('.').to_lowercase());
+        // A server may only set cookies for itself or a parent domain
+        if domain
+            .as_ref()
+            .is_some_and(|domain| host != *domain && !host.ends_with(&format!(".{}", domain)))
+        {
+            continue;
+        }
+        let default_path = match url.path().rfind('/') {
+            Some(0) | None => "/".to_string(),
+            Some(end) => url.path()[..end].to_string(),
+        };
+        let expires = match parsed.max_age() {
+            Some(max_age) => Some(now + max_age.whole_seconds()),
+            None => parsed.expires_datetime().map(|at| at.unix_timestamp()),
+        };
+        let cookie = StoredCookie {
+            name: parsed.name().to_string(),
+            value: parsed.value().to_string(),
+            host_only: domain.is_none(),
+            domain: domain.unwrap_or(host.clone()),
+            path: parsed.path().filter(|path| path.starts_with('/')).map_or(default_path, str::to_string),
+            secure: parsed.secure().unwrap_or(false),
+            expires,
+        };
+        cookies.retain(|other| {
+            !(other.name == cookie.name && other.domain == cookie.domain && other.path == cookie.path)
+        });
+        // An expiry in the past is how a server deletes a cookie
+        if cookie.expires.map_or(true, |expires| expires > now) {
+            cookies.push(cookie);
+        }
+    }
+}
+
+/// Whether a content type can be returned as text
+fn is_text(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+    mime.starts_with("text/")
+        || mime.ends_with("/json")
+        || mime.ends_with("+json")
+        || mime.ends_with("/xml")
+        || mime.ends_with("+xml")
+        || mime.ends_with("/javascript")
+        || mime == "application/x-www-form-urlencoded"
+}
+
+/// `no-store`, `no-cache` and `max-age` from `Cache-Control`
+fn cache_directives(header: &str) -> (bool, bool, Option<u64>) {
+    let (mut no_store, mut no_cache, mut max_age) = (false, false, None);
+    for directive in header.split(',').map(|directive| directive.trim().to_lowercase()) {
+        match directive.split_once('=') {
+            Some(("max-age", value)) => max_age = value.trim_matches('"').parse().ok(),
+            _ if directive == "no-store" || directive == "private" => no_store = true,
+            _ if directive == "no-cache" => no_cache = true,
+            _ => {}
+        }
+    }
+    (no_store, no_cache, max_age)
+}
+
+/// One request and its redirects, keeping the cookie jar up to date
+fn exchange(
+    app: &AppHandle,
+    request: &FetchRequest,
+    method: &str,
+    url: Url,
+    headers: &BTreeMap<String, String>,
+) -> Result<(Url, ureq::Response), String> {
+    let state = app.state::<FetchState>();
+    let config = state.config.lock().expect("fetch config lock").clone();
+    let agent = http::redirectless_agent();
+    let (mut method, mut url, mut body) = (method.to_string(), url, request.body.clone());
+    for _ in 0..=MAX_REDIRECTS {
+        check_url(&config, &url)?;
+        let mut call = agent.request(&method, url.as_str());
+        for (name, value) in headers {
+            call = call.set(name, value);
+        }
+        if let Some(jar) = &request.jar {
+            let jars = state.jars.lock().expect("fetch cookies lock");
+            if let Some(cookies) = jars.get(jar).and_then(|cookies| cookie_header(cookies, &url)) {
+                call = call.set("Cookie", &cookies);
+            }
+        }
+        if let Some(timeout) = request.timeout_ms {
+            call = call.timeout(Duration::from_millis(timeout));
+        }
+        let result = match &body {
+            Some(body) => call.send_string(body),
+            None => call.call(),
+        };
+        let response = match result {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(format!("{}: {}", url, e)),
+        };
+        if let Some(jar) = &request.jar {
+            let mut jars = state.jars.lock().expect("fetch cookies lock");
+            store_cookies(jars.entry(jar.clone()).or_default(), &url, &response.all("set-cookie"));
+        }
+
+        let location = response.header("Location").map(str::to_string);
+        let (301..=303 | 307 | 308, Some(location)) = (response.status(), location) else {
+            return Ok((url, response));
+        };
+        // 307 and 308 repeat the request as it was; the others become a GET
+        if !matches!(response.status(), 307 | 308) {
+            method = "GET".to_string();
+            body = None;
+        }
+        url = url.join(&location).map_err(|e| format!("Bad redirect to {}: {}", location, e))?;
+    }
+    Err(format!("Too many redirects fetching {}", request.url))
+}
+
+/// How long to wait before another attempt
+fn retry_wait(response: Option<&ureq::Response>, attempt: u32) -> Duration {
+    let retry_after = response
+        .and_then(|response| response.header("Retry-After"))
+        .and_then(|secs| secs.trim().parse().ok())
+        .map(Duration::from_secs);
+    retry_after.unwrap_or(RETRY_DELAY * 2u32.pow(attempt.min(6))).min(MAX_RETRY_WAIT)
+}
+
+fn read_response(url: &Url, response: ureq::Response) -> Result<FetchResponse, String> {
+    let mut headers = BTreeMap::new();
+    for name in response.headers_names() {
+        headers.insert(name.to_lowercase(), response.all(&name).join(", "));
+    }
+    let status = response.status();
+    let status_text = response.status_text().to_string();
+    let text = is_text(response.header("Content-Type").unwrap_or("text/plain"));
+
+    let mut data = Vec::new();
+    response
+        .into_throttled_reader()
+        .take(MAX_BODY + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| e.to_string())?;
+    if data.len() as u64 > MAX_BODY {
+        return Err(format!("The response is over {} MB", MAX_BODY / 1024 / 1024));
+    }
+    let (body, base64) = match String::from_utf8(data) {
+        Ok(body) if text => (body, false),
+        Ok(body) => (base64::engine::general_purpose::STANDARD.encode(body), true),
+        Err(e) => (base64::engine::general_purpose::STANDARD.encode(e.into_bytes()), true),
+    };
+    Ok(FetchResponse {
+        status,
+        status_text,
+        url: url.to_string(),
+        headers,
+        body,
+        base64,
+        from_cache: false,
+    })
+}
+
+/// Keep a GET answer when its headers allow
+fn cache_response(state: &FetchState, key: String, response: &FetchResponse) {
+    let header = |name: &str| response.headers.get(name).cloned();
+    let (no_store, no_cache, max_age) = cache_directives(&header("cache-control").unwrap_or_default());
+    let (etag, last_modified) = (header("etag"), header("last-modified"));
+    let validators = etag.is_some() || last_modified.is_some();
+    if response.status != 200 || no_store || response.body.len() > MAX_CACHED_BODY || (max_age.is_none() && !validators)
+    {
+        return;
+    }
+    let fresh_for = if no_cache { 0 } else { max_age.unwrap_or(0) };
+    let mut cache = state.cache.lock().expect("fetch cache lock");
+    if cache.len() >= CACHE_ENTRIES && !cache.contains_key(&key) {
+        let oldest = cache.iter().min_by_key(|(_, entry)| entry.stored).map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(
+        key,
+        CacheEntry {
+            response: response.clone(),
+            stored: Instant::now(),
+            fresh_for: Duration::from_secs(fresh_for),
+            etag,
+            last_modified,
+        },
+    );
+}
+
+/// Forget every cookie and cached response
+pub(crate) fn clear_browsing_data(app: &AppHandle) {
+    if let Some(state) = app.try_state::<FetchState>() {
+        state.jars.lock().expect("fetch cookies lock").clear();
+        state.cache.lock().expect("fetch cache lock").clear();
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Make an HTTP request from the backend, off the main thread
+#[tauri::command(async)]
+pub fn fetch_url(app: AppHandle, state: State<'_, FetchState>, request: FetchRequest) -> Result<FetchResponse, KioskError> {
+    let url = Url::parse(request.url.trim()).map_err(|e| KioskError::invalid(format!("Invalid URL: {}", e)))?;
+    let config = state.config.lock().expect("fetch config lock").clone();
+    check_url(&config, &url).map_err(KioskError::invalid)?;
+    if request.retries > MAX_RETRIES {
+        return Err(KioskError::invalid(format!("At most {} retries", MAX_RETRIES)));
+    }
+    let method = match request.method.trim().to_uppercase() {
+        method if method.is_empty() => "GET".to_string(),
+        method => method,
+    };
+
+    let cacheable = method == "GET" && request.cache != CacheMode::NoStore;
+    let key = format!("{} {}", request.jar.as_deref().unwrap_or_default(), url);
+    let cached = cacheable
+        .then(|| state.cache.lock().expect("fetch cache lock").get(&key).cloned())
+        .flatten();
+    let mut headers = request.headers.clone();
+    if let Some(entry) = &cached {
+        let fresh = entry.stored.elapsed() < entry.fresh_for;
+        if request.cache == CacheMode::ForceCache || (request.cache == CacheMode::Default && fresh) {
+            return Ok(FetchResponse {
+                from_cache: true,
+                ..entry.response.clone()
+            });
+        }
+        if request.cache != CacheMode::Reload {
+            if let Some(etag) = &entry.etag {
+                headers.entry("If-None-Match".to_string()).or_insert(etag.clone());
+            }
+            if let Some(modified) = &entry.last_modified {
+                headers.entry("If-Modified-Since".to_string()).or_insert(modified.clone());
+            }
+        }
+    }
+
+    let mut attempt = 0;
+    let (final_url, response) = loop {
+        let result = exchange(&app, &request, &method, url.clone(), &headers);
+        let retry = match &result {
+            Ok((_, response)) => RETRY_STATUSES.contains(&response.status()),
+            Err(_) => true,
+        };
+        if !retry || attempt >= request.retries {
+            break result.map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+        }
+        std::thread::sleep(retry_wait(result.as_ref().ok().map(|(_, response)| response), attempt));
+        attempt += 1;
+    };
+
+    if let (304, Some(mut entry)) = (response.status(), cached) {
+        entry.stored = Instant::now();
+        let response = FetchResponse {
+            from_cache: true,
+            ..entry.response.clone()
+        };
+        state.cache.lock().expect("fetch cache lock").insert(key, entry);
+        return Ok(response);
+    }
+    let response = read_response(&final_url, response)?;
+    if cacheable {
+        cache_response(&state, key, &response);
+    }
+    Ok(response)
+}
+
+/// Empty one cookie jar, or all of them
+#[tauri::command]
+pub fn clear_fetch_cookies(state: State<'_, FetchState>, jar: Option<String>) {
+    let mut jars = state.jars.lock().expect("fetch cookies lock");
+    match jar {
+        Some(jar) => {
+            jars.remove(&jar);
+        }
+        None => jars.clear(),
+    }
+}
+
+#[tauri::command]
+pub fn get_fetch_config(state: State<'_, FetchState>) -> FetchConfig {
+    state.config.lock().expect("fetch config lock").clone()
+}
+
+/// Update the proxy and allowed hosts (admin)
+#[tauri::command]
+pub fn set_fetch_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, FetchState>,
+    mut config: FetchConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    config.proxy = config.proxy.map(|proxy| proxy.trim().to_string()).filter(|proxy| !proxy.is_empty());
+    http::set_proxy(config.proxy.clone()).map_err(KioskError::invalid)?;
+    store::save(&app, CONFIG_FILE, &config)?;
+    *state.config.lock().expect("fetch config lock") = config;
+    Ok(())
+}
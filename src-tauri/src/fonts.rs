@@ -0,0 +1,160 @@
+//! Font enumeration and installation
+//!
+//! Lists system fonts through fontconfig and manages user-installed fonts in
+//! `~/.local/share/fonts`, backing the Fonts control-panel folder and the
+//! font pickers in the text editors.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+
+/// Font file extensions fontconfig can load
+const FONT_EXTENSIONS: &[&str] = &["ttf", "otf", "ttc", "pfb", "woff", "woff2"];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// An installed font face
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FontInfo {
+    pub family: String,
+    pub style: String,
+    pub file: String,
+    pub user_installed: bool,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Directory holding fonts installed from the control panel
+fn user_font_dir() -> Result<PathBuf, String> {
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join(".local/share/fonts"))
+        .ok_or_else(|| "HOME is not set".to_string())
+}
+
+/// Rebuild the fontconfig cache so new fonts show up immediately
+fn refresh_font_cache() -> Result<(), String> {
+    let status = Command::new("fc-cache")
+        .arg("-f")
+        .status()
+        .map_err(|e| format!("Failed to run fc-cache: {}", e))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err("fc-cache failed".to_string())
+    }
+}
+
+/// Query fontconfig, returning one line per matching face
+pub(crate) fn fc_list(pattern: &str, format: &str) -> Result<Vec<String>, String> {
+    let output = Command::new("fc-list")
+        .args(["--format", format, pattern])
+        .output()
+        .map_err(|e| format!("Failed to run fc-list: {}", e))?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Resolve a fontconfig pattern (e.g. "sans-serif:bold") to the best matching font file
+pub(crate) fn fc_match(pattern: &str) -> Result<PathBuf, String> {
+    let output = Command::new("fc-match")
+        .args(["--format", "%{file}", pattern])
+        .output()
+        .map_err(|e| format!("Failed to run fc-match: {}", e))?;
+
+    let file = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if file.is_empty() {
+        return Err(format!("No font matches {}", pattern));
+    }
+    Ok(PathBuf::from(file))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List installed font faces, sorted by family and style
+#[tauri::command]
+pub fn list_fonts() -> Result<Vec<FontInfo>, KioskError> {
+    let user_dir = user_font_dir().ok();
+    let mut fonts: Vec<FontInfo> = fc_list(":", "%{family[0]}\t%{style[0]}\t%{file}\n")?
+        .into_iter()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let family = parts.next()?.to_string();
+            let style = parts.next()?.to_string();
+            let file = parts.next()?.to_string();
+            let user_installed = user_dir
+                .as_ref()
+                .map(|dir| Path::new(&file).starts_with(dir))
+                .unwrap_or(false);
+
+            Some(FontInfo { family, style, file, user_installed })
+        })
+        .collect();
+
+    fonts.sort_by(|a, b| (&a.family, &a.style).cmp(&(&b.family, &b.style)));
+    Ok(fonts)
+}
+
+/// Install a font file for the kiosk user
+#[tauri::command]
+pub fn install_font(app: AppHandle, path: String) -> Result<FontInfo, KioskError> {
+    let source = vfs::resolve(&app, &path, Access::Read)?;
+    let extension = source
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if !FONT_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(KioskError::invalid(format!("Not a font file: {}", path)));
+    }
+
+    let file_name = source.file_name().ok_or("Invalid font path")?;
+    let dir = user_font_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let target = dir.join(file_name);
+    fs::copy(&source, &target).map_err(|e| e.to_string())?;
+    refresh_font_cache()?;
+
+    let target = target.to_string_lossy().to_string();
+    list_fonts()?
+        .into_iter()
+        .find(|font| font.file == target)
+        .ok_or_else(|| format!("fontconfig did not recognise {}", path).into())
+}
+
+/// Remove a user-installed font family. System fonts cannot be removed.
+#[tauri::command]
+pub fn remove_font(name: String) -> Result<usize, KioskError> {
+    let files: Vec<String> = list_fonts()?
+        .into_iter()
+        .filter(|font| font.user_installed && font.family == name)
+        .map(|font| font.file)
+        .collect();
+
+    if files.is_empty() {
+        return Err(KioskError::not_found(format!("No user-installed font named {}", name)));
+    }
+
+    for file in &files {
+        fs::remove_file(file).map_err(|e| e.to_string())?;
+    }
+
+    refresh_font_cache()?;
+    Ok(files.len())
+}
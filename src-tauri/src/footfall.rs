@@ -0,0 +1,227 @@
+//! Footfall statistics
+//!
+//! Counts visitors per hour from the proximity sensor's `person-approached`
+//! and `person-left` events, and from any other counter (a camera, a door
+//! beam) that reports through `record_footfall`, so retail operators get
+//! basic analytics from the kiosk itself. Counts are kept per day in hourly
+//! buckets in `footfall.json` for a bit over a year, and can be exported as
+//! CSV for a spreadsheet.
+
+use chrono::{Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::{events, store, vfs};
+
+const FOOTFALL_FILE: &str = "footfall.json";
+
+const DAYS_KEPT: usize = 400;
+
+const HOURS: usize = 24;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DayCounts {
+    visitors: Vec<u32>,
+    /// Total seconds visitors stayed, by the hour they arrived
+    dwell_secs: Vec<f64>,
+    /// Visitors whose departure was seen, for the dwell average
+    departures: Vec<u32>,
+}
+
+impl DayCounts {
+    fn new() -> Self {
+        DayCounts {
+            visitors: vec![0; HOURS],
+            dwell_secs: vec![0.0; HOURS],
+            departures: vec![0; HOURS],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FootfallDay {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    pub total: u32,
+    /// Visitors per hour, midnight first
+    pub hours: Vec<u32>,
+    pub average_dwell_secs: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FootfallStats {
+    pub from: String,
+    pub to: String,
+    pub total: u32,
+    pub days: Vec<FootfallDay>,
+    /// Visitors per hour of day over the whole range
+    pub by_hour: Vec<u32>,
+    /// Busiest hour of day, if anyone came
+    pub peak_hour: Option<u32>,
+    pub average_dwell_secs: Option<f64>,
+}
+
+pub struct FootfallState {
+    /// Keyed by "YYYY-MM-DD", which sorts by date
+    days: Mutex<BTreeMap<String, DayCounts>>,
+}
+
+impl FootfallState {
+    pub fn load(app: &AppHandle) -> Self {
+        FootfallState {
+            days: Mutex::new(store::load(app, FOOTFALL_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Not a date (YYYY-MM-DD): {}", date))
+}
+
+/// Apply a change to the current hour's bucket and save
+fn record(app: &AppHandle, change: impl FnOnce(&mut DayCounts, usize)) {
+    let now = Local::now();
+    let state = app.state::<FootfallState>();
+    let mut days = state.days.lock().expect("footfall lock");
+    let day = days.entry(now.format("%Y-%m-%d").to_string()).or_insert_with(DayCounts::new);
+    change(day, now.hour() as usize);
+    while days.len() > DAYS_KEPT {
+        days.pop_first();
+    }
+    let _ = store::save(app, FOOTFALL_FILE, &*days);
+}
+
+fn sum(values: &[u32]) -> u32 {
+    values.iter().sum()
+}
+
+fn average_dwell(dwell_secs: f64, departures: u32) -> Option<f64> {
+    (departures > 0).then(|| dwell_secs / departures as f64)
+}
+
+fn stats(days: &BTreeMap<String, DayCounts>, from: String, to: String) -> FootfallStats {
+    let mut by_hour = vec![0u32; HOURS];
+    let (mut dwell_secs, mut departures) = (0.0, 0);
+    let days: Vec<FootfallDay> = days
+        .range(from.clone()..=to.clone())
+        .map(|(date, counts)| {
+            for (total, count) in by_hour.iter_mut().zip(&counts.visitors) {
+                *total += count;
+            }
+            let day_dwell: f64 = counts.dwell_secs.iter().sum();
+            let day_departures = sum(&counts.departures);
+            dwell_secs += day_dwell;
+            departures += day_departures;
+            FootfallDay {
+                date: date.clone(),
+                total: sum(&counts.visitors),
+                hours: counts.visitors.clone(),
+                average_dwell_secs: average_dwell(day_dwell, day_departures),
+            }
+        })
+        .collect();
+    let peak_hour = (0..HOURS)
+        .filter(|&hour| by_hour[hour] > 0)
+        .max_by_key(|&hour| by_hour[hour])
+        .map(|hour| hour as u32);
+    FootfallStats {
+        from,
+        to,
+        total: sum(&by_hour),
+        days,
+        by_hour,
+        peak_hour,
+        average_dwell_secs: average_dwell(dwell_secs, departures),
+    }
+}
+
+fn range(from: Option<String>, to: Option<String>) -> Result<(String, String), KioskError> {
+    let today = Local::now().date_naive();
+    let to = to.as_deref().map(parse_date).transpose()?.unwrap_or(today);
+    let from = from.as_deref().map(parse_date).transpose()?.unwrap_or(to);
+    if from > to {
+        return Err(KioskError::invalid("The range starts after it ends"));
+    }
+    Ok((from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string()))
+}
+
+/// Count visitors from the proximity sensor
+pub fn start_footfall(app: AppHandle) {
+    events::listen(&app, |app, event| match event.topic.as_str() {
+        "person-approached" => record(app, |day, hour| day.visitors[hour] += 1),
+        "person-left" => {
+            let stayed = event.payload.get("present_secs").and_then(|secs| secs.as_f64()).unwrap_or(0.0);
+            record(app, |day, hour| {
+                day.dwell_secs[hour] += stayed;
+                day.departures[hour] += 1;
+            });
+        }
+        _ => {}
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Add visitors counted by another source, such as a camera
+#[tauri::command]
+pub fn record_footfall(app: AppHandle, count: u32) {
+    if count > 0 {
+        record(&app, |day, hour| day.visitors[hour] += count);
+    }
+}
+
+/// Visitor counts from `from` to `to` (inclusive, "YYYY-MM-DD"); today when
+/// neither is given
+#[tauri::command]
+pub fn get_footfall_stats(
+    state: State<'_, FootfallState>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<FootfallStats, KioskError> {
+    let (from, to) = range(from, to)?;
+    let days = state.days.lock().expect("footfall lock");
+    Ok(stats(&days, from, to))
+}
+
+/// Write hourly counts for a range as CSV to a virtual path, returning the
+/// number of rows
+#[tauri::command]
+pub fn export_footfall_csv(
+    app: AppHandle,
+    state: State<'_, FootfallState>,
+    path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<usize, KioskError> {
+    let (from, to) = range(from, to)?;
+    let days = state.days.lock().expect("footfall lock").clone();
+
+    let mut csv = String::from("date,hour,visitors,average_dwell_secs\r\n");
+    let mut rows = 0;
+    for (date, counts) in days.range(from..=to) {
+        for hour in 0..HOURS {
+            let dwell = average_dwell(counts.dwell_secs[hour], counts.departures[hour])
+                .map(|secs| format!("{:.1}", secs))
+                .unwrap_or_default();
+            let _ = write!(csv, "{},{:02}:00,{},{}\r\n", date, hour, counts.visitors[hour], dwell);
+            rows += 1;
+        }
+    }
+    fs::write(vfs::resolve_write(&app, &path, csv.len() as u64)?, csv).map_err(|e| e.to_string())?;
+    Ok(rows)
+}
@@ -0,0 +1,317 @@
+//! Number, currency and measurement formatting
+//!
+//! Formats prices and measurements for a locale with the rules in its
+//! catalog (see `i18n`): decimal and group separators, where the currency
+//! symbol goes, and whether the region uses metric, US or UK units, so the
+//! frontend does not need its own copy of the rules. Prices are rounded to
+//! the currency's minor unit as decimals, not floats, so 1.005 EUR shows as
+//! 1,01 €. Measurements are converted to the region's usual unit unless the
+//! caller asks for them as given.
+
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::i18n::{self, MeasurementSystem, NumberFormat};
+
+/// Fraction digits shown for measurements unless the caller picks
+const DEFAULT_MEASUREMENT_DIGITS: u32 = 1;
+
+const MAX_DIGITS: u32 = 10;
+
+/// ISO 4217 code, symbol and minor unit digits; other codes show the code
+/// with two digits
+const CURRENCIES: &[(&str, &str, u32)] = &[
+    ("AUD", "A$", 2),
+    ("BGN", "лв", 2),
+    ("BRL", "R$", 2),
+    ("CAD", "CA$", 2),
+    ("CHF", "CHF", 2),
+    ("CNY", "CN¥", 2),
+    ("CZK", "Kč", 2),
+    ("DKK", "kr.", 2),
+    ("EUR", "€", 2),
+    ("GBP", "£", 2),
+    ("HUF", "Ft", 2),
+    ("INR", "₹", 2),
+    ("ISK", "kr", 0),
+    ("JPY", "¥", 0),
+    ("KRW", "₩", 0),
+    ("MXN", "MX$", 2),
+    ("NOK", "kr", 2),
+    ("NZD", "NZ$", 2),
+    ("PLN", "zł", 2),
+    ("RON", "lei", 2),
+    ("SEK", "kr", 2),
+    ("TRY", "₺", 2),
+    ("UAH", "₴", 2),
+    ("USD", "$", 2),
+    ("ZAR", "R", 2),
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Temperature,
+    Speed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum System {
+    Metric,
+    Us,
+}
+
+struct Unit {
+    /// Names accepted for the unit; the first is the one reported back
+    names: &'static [&'static str],
+    symbol: &'static str,
+    dimension: Dimension,
+    system: System,
+    /// To the dimension's base unit: (value + offset) * factor
+    factor: f64,
+    offset: f64,
+}
+
+/// Base units: metre, kilogram, litre, degree Celsius, kilometre per hour
+const UNITS: &[Unit] = &[
+    unit(&["mm", "millimeter", "millimetre"], "mm", Dimension::Length, System::Metric, 0.001),
+    unit(&["cm", "centimeter", "centimetre"], "cm", Dimension::Length, System::Metric, 0.01),
+    unit(&["m", "meter", "metre"], "m", Dimension::Length, System::Metric, 1.0),
+    unit(&["km", "kilometer", "kilometre"], "km", Dimension::Length, System::Metric, 1000.0),
+    unit(&["in", "inch"], "in", Dimension::Length, System::Us, 0.0254),
+    unit(&["ft", "foot", "feet"], "ft", Dimension::Length, System::Us, 0.3048),
+    unit(&["mi", "mile"], "mi", Dimension::Length, System::Us, 1609.344),
+    unit(&["g", "gram"], "g", Dimension::Mass, System::Metric, 0.001),
+    unit(&["kg", "kilogram"], "kg", Dimension::Mass, System::Metric, 1.0),
+    unit(&["oz", "ounce"], "oz", Dimension::Mass, System::Us, 0.028349523125),
+    unit(&["lb", "pound"], "lb", Dimension::Mass, System::Us, 0.45359237),
+    unit(&["ml", "milliliter", "millilitre"], "ml", Dimension::Volume, System::Metric, 0.001),
+    unit(&["l", "liter", "litre"], "l", Dimension::Volume, System::Metric, 1.0),
+    unit(&["fl-oz", "fluid-ounce"], "fl oz", Dimension::Volume, System::Us, 0.0295735295625),
+    unit(&["gal", "gallon"], "gal", Dimension::Volume, System::Us, 3.785411784),
+    unit(&["km/h", "kilometer-per-hour"], "km/h", Dimension::Speed, System::Metric, 1.0),
+    unit(&["mph", "mile-per-hour"], "mph", Dimension::Speed, System::Us, 1.609344),
+    Unit {
+        names: &["celsius", "c", "°c"],
+        symbol: "°C",
+        dimension: Dimension::Temperature,
+        system: System::Metric,
+        factor: 1.0,
+        offset: 0.0,
+    },
+    Unit {
+        names: &["fahrenheit", "f", "°f"],
+        symbol: "°F",
+        dimension: Dimension::Temperature,
+        system: System::Us,
+        factor: 5.0 / 9.0,
+        offset: -32.0,
+    },
+];
+
+/// Units swapped when converting to the other system
+const COUNTERPARTS: &[(&str, &str)] = &[
+    ("mm", "in"),
+    ("cm", "in"),
+    ("m", "ft"),
+    ("km", "mi"),
+    ("g", "oz"),
+    ("kg", "lb"),
+    ("ml", "fl-oz"),
+    ("l", "gal"),
+    ("km/h", "mph"),
+    ("celsius", "fahrenheit"),
+];
+
+/// A measurement in the region's unit
+#[derive(Debug, Clone, Serialize)]
+pub struct Measurement {
+    /// Ready to display, such as `5,2 km`
+    pub text: String,
+    pub value: f64,
+    /// Unit the value ended up in
+    pub unit: String,
+    pub symbol: String,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+const fn unit(
+    names: &'static [&'static str],
+    symbol: &'static str,
+    dimension: Dimension,
+    system: System,
+    factor: f64,
+) -> Unit {
+    Unit {
+        names,
+        symbol,
+        dimension,
+        system,
+        factor,
+        offset: 0.0,
+    }
+}
+
+fn find_unit(name: &str) -> Option<&'static Unit> {
+    let name = name.trim().to_lowercase();
+    UNITS.iter().find(|unit| unit.names.contains(&name.as_str()))
+}
+
+/// The unit `unit` is usually shown in under `system`
+fn regional_unit(unit: &'static Unit, system: MeasurementSystem) -> &'static Unit {
+    let wanted = match system {
+        MeasurementSystem::Metric => System::Metric,
+        MeasurementSystem::Us => System::Us,
+        // Road distances and speeds are in miles; everything else is metric
+        MeasurementSystem::Uk if ["km", "mi", "km/h", "mph"].contains(&unit.names[0]) => System::Us,
+        MeasurementSystem::Uk => System::Metric,
+    };
+    if unit.system == wanted {
+        return unit;
+    }
+    COUNTERPARTS
+        .iter()
+        .find_map(|&(metric, us)| match unit.system {
+            System::Metric if unit.names[0] == metric => find_unit(us),
+            System::Us if unit.names[0] == us => find_unit(metric),
+            _ => None,
+        })
+        .unwrap_or(unit)
+}
+
+fn convert(value: f64, from: &Unit, to: &Unit) -> f64 {
+    let base = (value + from.offset) * from.factor;
+    base / to.factor - to.offset
+}
+
+/// `value` rounded to `digits` places, with the locale's separators
+fn format_decimal(value: Decimal, digits: u32, format: &NumberFormat) -> String {
+    let rounded = value.round_dp_with_strategy(digits, RoundingStrategy::MidpointAwayFromZero);
+    let text = format!("{:.*}", digits as usize, rounded.abs());
+    let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+
+    let mut grouped = String::with_capacity(text.len() + integer.len() / 3);
+    if integer.len() > 3 + format.min_grouping.saturating_sub(1) {
+        for (index, digit) in integer.chars().enumerate() {
+            if index > 0 && (integer.len() - index) % 3 == 0 {
+                grouped.push_str(&format.group);
+            }
+            grouped.push(digit);
+        }
+    } else {
+        grouped.push_str(integer);
+    }
+    if !fraction.is_empty() {
+        grouped.push_str(&format.decimal);
+        grouped.push_str(fraction);
+    }
+    if rounded.is_sign_negative() && !rounded.is_zero() {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
+fn to_decimal(value: f64) -> Result<Decimal, KioskError> {
+    Decimal::from_f64(value).ok_or_else(|| KioskError::invalid(format!("Cannot format {}", value)))
+}
+
+fn check_digits(digits: Option<u32>, default: u32) -> Result<u32, KioskError> {
+    let digits = digits.unwrap_or(default);
+    if digits > MAX_DIGITS {
+        return Err(KioskError::invalid(format!("At most {} fraction digits", MAX_DIGITS)));
+    }
+    Ok(digits)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Format a number with the separators of `locale`, or the kiosk's locale
+#[tauri::command]
+pub fn format_number(
+    app: AppHandle,
+    value: f64,
+    digits: Option<u32>,
+    locale: Option<String>,
+) -> Result<String, KioskError> {
+    let format = i18n::number_format(&app, locale.as_deref()).map_err(KioskError::not_found)?;
+    Ok(format_decimal(to_decimal(value)?, check_digits(digits, 0)?, &format))
+}
+
+/// Format a price in `currency` (ISO 4217, such as `EUR`) for `locale`, or
+/// the kiosk's locale
+#[tauri::command]
+pub fn format_currency(
+    app: AppHandle,
+    amount: f64,
+    currency: String,
+    locale: Option<String>,
+) -> Result<String, KioskError> {
+    let code = currency.trim().to_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(KioskError::invalid(format!("Not a currency code: {}", currency)));
+    }
+    let format = i18n::number_format(&app, locale.as_deref()).map_err(KioskError::not_found)?;
+    let (symbol, digits) = CURRENCIES
+        .iter()
+        .find(|(known, _, _)| *known == code)
+        .map_or((code.as_str(), 2), |&(_, symbol, digits)| (symbol, digits));
+
+    let amount = to_decimal(amount)?;
+    let number = format_decimal(amount.abs(), digits, &format);
+    let price = format.currency.replace('#', &number).replace('¤', symbol);
+    let negative = amount.round_dp_with_strategy(digits, RoundingStrategy::MidpointAwayFromZero);
+    Ok(if negative.is_sign_negative() && !negative.is_zero() {
+        format!("-{}", price)
+    } else {
+        price
+    })
+}
+
+/// Format a measurement for `locale`, or the kiosk's locale, converted to
+/// the region's usual unit unless `convert` is false
+#[tauri::command]
+pub fn format_measurement(
+    app: AppHandle,
+    value: f64,
+    unit: String,
+    locale: Option<String>,
+    digits: Option<u32>,
+    convert: Option<bool>,
+) -> Result<Measurement, KioskError> {
+    let from = find_unit(&unit).ok_or_else(|| KioskError::invalid(format!("Unknown unit: {}", unit)))?;
+    let format = i18n::number_format(&app, locale.as_deref()).map_err(KioskError::not_found)?;
+    let digits = check_digits(digits, DEFAULT_MEASUREMENT_DIGITS)?;
+    let to = if convert.unwrap_or(true) {
+        regional_unit(from, format.measurement_system)
+    } else {
+        from
+    };
+
+    let value = self::convert(value, from, to);
+    let number = format_decimal(to_decimal(value)?, digits, &format);
+    let text = if to.dimension == Dimension::Temperature {
+        format!("{}{}", number, to.symbol)
+    } else {
+        format!("{} {}", number, to.symbol)
+    };
+    Ok(Measurement {
+        text,
+        value,
+        unit: to.names[0].to_string(),
+        symbol: to.symbol.to_string(),
+    })
+}
@@ -0,0 +1,453 @@
+//! Games
+//!
+//! High scores and save states for the bundled games (Minesweeper,
+//! Solitaire), kept in `games.json` so they survive reboots. Each game has
+//! a leaderboard per variant (such as a Minesweeper difficulty), ordered
+//! with the highest or the lowest score first as the game asks; a new best
+//! is published as `game-high-score`. Save states are kept per player, so
+//! several people on one kiosk each resume their own game. The player is
+//! the name given, the logged-in operator, or "Guest".
+//!
+//! Minesweeper and Solitaire run in the backend (see `minesweeper` and
+//! `solitaire`), which scores their games itself; scores for them cannot be
+//! submitted from the frontend.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{events, store};
+
+const GAMES_FILE: &str = "games.json";
+
+/// Entries kept per leaderboard
+const BOARD_SIZE: usize = 50;
+
+const DEFAULT_LEADERBOARD_LIMIT: usize = 10;
+
+const MAX_NAME_CHARS: usize = 32;
+
+/// Save states larger than this, as JSON, are refused
+const MAX_SAVE_BYTES: usize = 256 * 1024;
+
+const GUEST: &str = "Guest";
+
+/// Games only the backend may score, from games it ran itself
+const BACKEND_SCORED: &[&str] = &["minesweeper", "solitaire"];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Which end of the leaderboard is best
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreOrder {
+    /// Points: the highest score wins
+    #[default]
+    Higher,
+    /// Times or moves: the lowest score wins
+    Lower,
+}
+
+/// A finished game's score
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScoreSubmission {
+    pub game: String,
+    pub score: f64,
+    /// The logged-in operator, or "Guest", when not given
+    #[serde(default)]
+    pub player: Option<String>,
+    /// Leaderboard within the game, such as a difficulty; the default one
+    /// when not given
+    #[serde(default)]
+    pub variant: Option<String>,
+    /// Sets the leaderboard's order; kept from earlier scores when not given
+    #[serde(default)]
+    pub order: Option<ScoreOrder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub player: String,
+    pub score: f64,
+    pub achieved_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Leaderboard {
+    pub game: String,
+    pub variant: String,
+    pub order: ScoreOrder,
+    pub entries: Vec<HighScore>,
+}
+
+/// Where a submitted score landed
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreResult {
+    /// 1-based place on the leaderboard; `None` when it did not make it
+    pub rank: Option<usize>,
+    /// Whether it is the new best
+    pub best: bool,
+    /// The player's own best on this leaderboard
+    pub personal_best: Option<f64>,
+}
+
+/// Payload of `game-high-score`
+#[derive(Debug, Clone, Serialize)]
+pub struct GameHighScore {
+    pub game: String,
+    pub variant: String,
+    pub player: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub game: String,
+    pub player: String,
+    /// Whatever the game needs to resume
+    pub state: Value,
+    pub saved_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Board {
+    order: ScoreOrder,
+    entries: Vec<HighScore>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct GamesData {
+    /// Keyed by game, then variant
+    boards: HashMap<String, HashMap<String, Board>>,
+    /// Keyed by game, then player
+    saves: HashMap<String, HashMap<String, SavedGame>>,
+}
+
+/// Seeded random numbers (SplitMix64) for boards and deals, so a seed
+/// replays the same game on any machine
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u32) -> Self {
+        Rng(seed as u64)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound.max(1) as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle
+    pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// A fresh seed for a game the player did not ask to replay
+pub(crate) fn random_seed() -> u32 {
+    uuid::Uuid::new_v4().as_u128() as u32
+}
+
+pub struct GamesState(Mutex<GamesData>);
+
+impl GamesState {
+    pub fn load(app: &AppHandle) -> Self {
+        GamesState(Mutex::new(store::load(app, GAMES_FILE)))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn check_game(game: &str) -> Result<(), KioskError> {
+    let valid = !game.is_empty()
+        && game.len() <= 64
+        && game.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(KioskError::invalid(format!("Not a game id: {}", game)))
+    }
+}
+
+/// The given name, or the logged-in operator, or the guest player
+pub(crate) fn player_name(auth: &AuthState, player: Option<String>) -> String {
+    let name = player
+        .map(|name| name.trim().chars().take(MAX_NAME_CHARS).collect::<String>())
+        .filter(|name| !name.is_empty());
+    name.or_else(|| auth::require_role(auth, Role::Operator).ok().map(|session| session.username))
+        .unwrap_or_else(|| GUEST.to_string())
+}
+
+/// Whether `a` beats `b`
+fn better(order: ScoreOrder, a: f64, b: f64) -> bool {
+    match order {
+        ScoreOrder::Higher => a > b,
+        ScoreOrder::Lower => a < b,
+    }
+}
+
+fn personal_best(board: &Board, player: &str) -> Option<f64> {
+    board
+        .entries
+        .iter()
+        .find(|entry| entry.player.eq_ignore_ascii_case(player))
+        .map(|entry| entry.score)
+}
+
+/// Put a score on a leaderboard, publishing a new best
+fn record(
+    app: &AppHandle,
+    state: &GamesState,
+    game: String,
+    variant: String,
+    player: String,
+    score: f64,
+    order: Option<ScoreOrder>,
+) -> Result<ScoreResult, KioskError> {
+    let (result, data) = {
+        let mut data = state.0.lock().expect("games lock");
+        let board = data
+            .boards
+            .entry(game.clone())
+            .or_default()
+            .entry(variant.clone())
+            .or_default();
+        if let Some(order) = order {
+            board.order = order;
+        }
+        let order = board.order;
+        // Entries stay sorted best first; ties keep the earlier score ahead
+        let rank = board
+            .entries
+            .iter()
+            .position(|entry| better(order, score, entry.score))
+            .unwrap_or(board.entries.len());
+        let made_board = rank < BOARD_SIZE;
+        if made_board {
+            board.entries.insert(
+                rank,
+                HighScore {
+                    player: player.clone(),
+                    score,
+                    achieved_at: Utc::now().timestamp(),
+                },
+            );
+            board.entries.truncate(BOARD_SIZE);
+        }
+        let result = ScoreResult {
+            rank: made_board.then_some(rank + 1),
+            best: rank == 0,
+            personal_best: personal_best(board, &player),
+        };
+        (result, data.clone())
+    };
+    store::save(app, GAMES_FILE, &data)?;
+
+    if result.best {
+        events::publish(
+            app,
+            "game-high-score",
+            GameHighScore {
+                game,
+                variant,
+                player,
+                score,
+            },
+        );
+    }
+    Ok(result)
+}
+
+/// Score a game the backend ran from start to finish
+pub(crate) fn record_score(
+    app: &AppHandle,
+    game: &str,
+    variant: &str,
+    player: &str,
+    score: f64,
+    order: ScoreOrder,
+) -> Result<ScoreResult, KioskError> {
+    let state = app.state::<GamesState>();
+    record(app, &state, game.to_string(), variant.to_string(), player.to_string(), score, Some(order))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Record a finished game's score on its leaderboard
+#[tauri::command]
+pub fn submit_score(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, GamesState>,
+    submission: ScoreSubmission,
+) -> Result<ScoreResult, KioskError> {
+    let ScoreSubmission {
+        game,
+        score,
+        player,
+        variant,
+        order,
+    } = submission;
+    check_game(&game)?;
+    if BACKEND_SCORED.contains(&game.as_str()) {
+        return Err(KioskError::new(
+            ErrorKind::Denied,
+            format!("Scores for {} are recorded when the game is won", game),
+        ));
+    }
+    if !score.is_finite() {
+        return Err(KioskError::invalid("The score is not a number"));
+    }
+    let player = player_name(&auth, player);
+    record(&app, &state, game, variant.unwrap_or_default(), player, score, order)
+}
+
+/// The best scores for a game's `variant` leaderboard, best first
+#[tauri::command]
+pub fn get_leaderboard(
+    state: State<'_, GamesState>,
+    game: String,
+    variant: Option<String>,
+    limit: Option<usize>,
+) -> Result<Leaderboard, KioskError> {
+    check_game(&game)?;
+    let variant = variant.unwrap_or_default();
+    let data = state.0.lock().expect("games lock");
+    let board = data
+        .boards
+        .get(&game)
+        .and_then(|boards| boards.get(&variant))
+        .cloned()
+        .unwrap_or_default();
+    Ok(Leaderboard {
+        game,
+        variant,
+        order: board.order,
+        entries: board
+            .entries
+            .into_iter()
+            .take(limit.unwrap_or(DEFAULT_LEADERBOARD_LIMIT))
+            .collect(),
+    })
+}
+
+/// Clear a game's leaderboards, or just one variant's (supervisor)
+#[tauri::command]
+pub fn clear_leaderboard(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, GamesState>,
+    game: String,
+    variant: Option<String>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    check_game(&game)?;
+    let data = {
+        let mut data = state.0.lock().expect("games lock");
+        match variant {
+            Some(variant) => {
+                if let Some(boards) = data.boards.get_mut(&game) {
+                    boards.remove(&variant);
+                }
+            }
+            None => {
+                data.boards.remove(&game);
+            }
+        }
+        data.clone()
+    };
+    Ok(store::save(&app, GAMES_FILE, &data)?)
+}
+
+/// Keep a game in progress so the player can resume it later
+#[tauri::command]
+pub fn save_game_state(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, GamesState>,
+    game: String,
+    game_state: Value,
+    player: Option<String>,
+) -> Result<SavedGame, KioskError> {
+    check_game(&game)?;
+    if serde_json::to_vec(&game_state)?.len() > MAX_SAVE_BYTES {
+        return Err(KioskError::invalid(format!(
+            "Save states are limited to {} KB",
+            MAX_SAVE_BYTES / 1024
+        )));
+    }
+    let saved = SavedGame {
+        player: player_name(&auth, player),
+        game: game.clone(),
+        state: game_state,
+        saved_at: Utc::now().timestamp(),
+    };
+    let data = {
+        let mut data = state.0.lock().expect("games lock");
+        data.saves
+            .entry(game)
+            .or_default()
+            .insert(saved.player.clone(), saved.clone());
+        data.clone()
+    };
+    store::save(&app, GAMES_FILE, &data)?;
+    Ok(saved)
+}
+
+/// The player's saved game, if any
+#[tauri::command]
+pub fn load_game_state(
+    auth: State<'_, AuthState>,
+    state: State<'_, GamesState>,
+    game: String,
+    player: Option<String>,
+) -> Result<Option<SavedGame>, KioskError> {
+    check_game(&game)?;
+    let player = player_name(&auth, player);
+    let data = state.0.lock().expect("games lock");
+    Ok(data.saves.get(&game).and_then(|saves| saves.get(&player)).cloned())
+}
+
+/// Drop the player's saved game, e.g. once it is finished
+#[tauri::command]
+pub fn delete_game_state(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, GamesState>,
+    game: String,
+    player: Option<String>,
+) -> Result<(), KioskError> {
+    check_game(&game)?;
+    let player = player_name(&auth, player);
+    let data = {
+        let mut data = state.0.lock().expect("games lock");
+        let Some(saves) = data.saves.get_mut(&game) else { return Ok(()) };
+        if saves.remove(&player).is_none() {
+            return Ok(());
+        }
+        data.clone()
+    };
+    Ok(store::save(&app, GAMES_FILE, &data)?)
+}
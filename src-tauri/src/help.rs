@@ -0,0 +1,381 @@
+//! Help and Support
+//!
+//! Indexes the HTML and Markdown help pages bundled under `help/`, serves
+//! them to the F1 window over the `help://` protocol (Markdown is rendered
+//! on the fly) and answers ranked full-text searches, all without network
+//! access. In lite mode only page titles are indexed.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri::http::{Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, Runtime, State, UriSchemeContext};
+
+use crate::config;
+
+/// Score multiplier for query terms found in a page title
+const TITLE_WEIGHT: f64 = 5.0;
+
+/// Characters of context shown around the first match
+const SNIPPET_CHARS: usize = 160;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// A help page listed in the contents pane
+#[derive(Debug, Clone, Serialize)]
+pub struct HelpTopic {
+    /// Path relative to the help root, e.g. "desktop.md"
+    pub path: String,
+    pub title: String,
+}
+
+/// A ranked search hit
+#[derive(Debug, Clone, Serialize)]
+pub struct HelpResult {
+    pub path: String,
+    pub title: String,
+    pub snippet: String,
+    pub score: f64,
+}
+
+struct HelpPage {
+    topic: HelpTopic,
+    text: String,
+    title_terms: Vec<String>,
+    terms: HashMap<String, usize>,
+    length: usize,
+}
+
+/// Search index over the bundled help pages, built once at startup
+pub struct HelpState(Vec<HelpPage>);
+
+impl HelpState {
+    pub fn load(app: &AppHandle) -> Self {
+        let full_text = !config::lite_mode(app);
+        let pages = help_root(app)
+            .map(|root| {
+                let mut files = Vec::new();
+                collect_files(&root, &mut files);
+                files.iter().filter_map(|file| index_page(&root, file, full_text)).collect()
+            })
+            .unwrap_or_default();
+        HelpState(pages)
+    }
+}
+
+// ============================================================================
+// Indexing
+// ============================================================================
+
+fn help_root<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path().resource_dir().ok().map(|dir| dir.join("help"))
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if is_page(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_page(path: &Path) -> bool {
+    matches!(extension(path).as_str(), "md" | "html" | "htm")
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+}
+
+/// Plain text of a Markdown page and its first heading
+fn markdown_text(source: &str) -> (String, Option<String>) {
+    use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+
+    let mut text = String::new();
+    let mut title = None;
+    let mut heading: Option<String> = None;
+
+    for event in Parser::new(source) {
+        match event {
+            Event::Start(Tag::Heading { .. }) if title.is_none() => heading = Some(String::new()),
+            Event::End(TagEnd::Heading(_)) => title = title.or(heading.take()),
+            Event::Text(value) | Event::Code(value) => {
+                if let Some(heading) = heading.as_mut() {
+                    heading.push_str(&value);
+                }
+                text.push_str(&value);
+            }
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+
+    (text, title)
+}
+
+/// Plain text of an HTML page and its <title> (or first <h1>)
+fn html_text(source: &str) -> (String, Option<String>) {
+    let lower = source.to_ascii_lowercase();
+    let between = |open: &str, close: &str| {
+        let start = lower.find(open)?;
+        let start = start + lower[start..].find('>')? + 1;
+        let end = start + lower[start..].find(close)?;
+        Some(strip_tags(&source[start..end]).trim().to_string())
+    };
+    let title = between("<title", "</title>").or_else(|| between("<h1", "</h1>"));
+
+    let body = lower
+        .find("<body")
+        .map_or(source, |start| &source[start..]);
+    (strip_tags(body), title)
+}
+
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// Index a page; without `full_text` only its title is kept
+fn index_page(root: &Path, file: &Path, full_text: bool) -> Option<HelpPage> {
+    let source = fs::read_to_string(file).ok()?;
+    let (text, title) = match extension(file).as_str() {
+        "md" => markdown_text(&source),
+        _ => html_text(&source),
+    };
+    let path = file
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    let title = title.unwrap_or_else(|| {
+        file.file_stem()
+            .map(|stem| stem.to_string_lossy().replace(['-', '_'], " "))
+            .unwrap_or_default()
+    });
+    let text = if full_text {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        String::new()
+    };
+
+    let mut terms = HashMap::new();
+    let mut length = 0;
+    for term in tokenize(&text) {
+        *terms.entry(term).or_insert(0) += 1;
+        length += 1;
+    }
+
+    Some(HelpPage {
+        title_terms: tokenize(&title).collect(),
+        topic: HelpTopic { path, title },
+        text,
+        terms,
+        length,
+    })
+}
+
+// ============================================================================
+// Searching
+// ============================================================================
+
+/// Text around the first occurrence of any query term
+fn snippet(text: &str, query: &[String]) -> String {
+    let lower = text.to_lowercase();
+    let position = query
+        .iter()
+        .filter_map(|term| lower.find(term.as_str()))
+        .min()
+        .unwrap_or(0);
+
+    // Lowercasing can shift byte offsets for non-ASCII text, so work in chars
+    let start_char = lower[..position.min(lower.len())].chars().count().saturating_sub(SNIPPET_CHARS / 4);
+    let snippet: String = text.chars().skip(start_char).take(SNIPPET_CHARS).collect();
+
+    let prefix = if start_char > 0 { "..." } else { "" };
+    let suffix = if text.chars().count() > start_char + SNIPPET_CHARS { "..." } else { "" };
+    format!("{}{}{}", prefix, snippet.trim(), suffix)
+}
+
+/// Rank pages by term frequency weighted by rarity, boosting title matches.
+/// The last query term also matches as a prefix so results update while typing.
+fn rank(pages: &[HelpPage], query: &str) -> Vec<HelpResult> {
+    let terms: Vec<String> = tokenize(query).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let matches = |page: &HelpPage, index: usize| -> (usize, usize) {
+        let term = &terms[index];
+        let prefix = index == terms.len() - 1;
+        let hit = |word: &String| if prefix { word.starts_with(term.as_str()) } else { word == term };
+        let body = page.terms.iter().filter(|(word, _)| hit(word)).map(|(_, count)| count).sum();
+        let title = page.title_terms.iter().filter(|word| hit(word)).count();
+        (body, title)
+    };
+
+    let total = pages.len() as f64;
+    let rarity: Vec<f64> = (0..terms.len())
+        .map(|index| {
+            let containing = pages
+                .iter()
+                .filter(|page| matches(page, index) != (0, 0))
+                .count() as f64;
+            (total / (1.0 + containing)).ln() + 1.0
+        })
+        .collect();
+
+    let mut results: Vec<HelpResult> = pages
+        .iter()
+        .filter_map(|page| {
+            let score: f64 = (0..terms.len())
+                .map(|index| {
+                    let (body, title) = matches(page, index);
+                    let frequency = body as f64 / (page.length.max(1) as f64).sqrt();
+                    (frequency + title as f64 * TITLE_WEIGHT) * rarity[index]
+                })
+                .sum();
+            (score > 0.0).then(|| HelpResult {
+                path: page.topic.path.clone(),
+                title: page.topic.title.clone(),
+                snippet: snippet(&page.text, &terms),
+                score,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    results
+}
+
+// ============================================================================
+// help:// Protocol
+// ============================================================================
+
+fn mime_type(path: &Path) -> &'static str {
+    match extension(path).as_str() {
+        "md" | "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+fn render_markdown(source: &str) -> String {
+    let parser = pulldown_cmark::Parser::new_ext(source, pulldown_cmark::Options::ENABLE_TABLES);
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, parser);
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><link rel=\"stylesheet\" href=\"help.css\"></head>\n<body>\n{}</body></html>\n",
+        body
+    )
+}
+
+/// Decode %XX escapes (the frontend encodes the whole path with convertFileSrc)
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn respond(status: StatusCode, mime: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", mime)
+        .body(body)
+        .expect("valid help response")
+}
+
+/// Serve a file from the help root; Markdown pages are rendered to HTML
+pub fn protocol<R: Runtime>(ctx: UriSchemeContext<'_, R>, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let not_found = || respond(StatusCode::NOT_FOUND, "text/plain", b"Help page not found".to_vec());
+    let Some(root) = help_root(ctx.app_handle()) else { return not_found() };
+
+    let requested = percent_decode(request.uri().path());
+    let requested = requested.trim_start_matches('/');
+    let relative = Path::new(if requested.is_empty() { "index.md" } else { requested });
+    // Only plain path segments; no "..", roots or drive prefixes
+    if !relative.components().all(|part| matches!(part, Component::Normal(_))) {
+        return respond(StatusCode::FORBIDDEN, "text/plain", b"Forbidden".to_vec());
+    }
+
+    let path = root.join(relative);
+    let Ok(data) = fs::read(&path) else { return not_found() };
+    let body = match extension(&path).as_str() {
+        "md" => render_markdown(&String::from_utf8_lossy(&data)).into_bytes(),
+        _ => data,
+    };
+    respond(StatusCode::OK, mime_type(&path), body)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List every help page for the contents pane
+#[tauri::command]
+pub fn list_help_topics(state: State<'_, HelpState>) -> Vec<HelpTopic> {
+    let mut topics: Vec<HelpTopic> = state.0.iter().map(|page| page.topic.clone()).collect();
+    topics.sort_by_key(|topic| (topic.path != "index.md", topic.title.to_lowercase()));
+    topics
+}
+
+/// Search the help pages, best matches first
+#[tauri::command]
+pub fn search_help(state: State<'_, HelpState>, query: String, limit: Option<usize>) -> Vec<HelpResult> {
+    let mut results = rank(&state.0, &query);
+    results.truncate(limit.unwrap_or(20));
+    results
+}
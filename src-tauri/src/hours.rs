@@ -0,0 +1,393 @@
+//! Operating hours
+//!
+//! Turns the display on and off on a weekly schedule with holiday
+//! exceptions, replacing cron scripts around xset and cec-client. Each day
+//! has an on and an off time; an off time at or before the on time runs past
+//! midnight. The display is switched through whichever outputs are enabled:
+//! DPMS (xset or wlopm), the TV over HDMI-CEC, and the panel backlight.
+//!
+//! An operator can override the schedule, e.g. to light the screen for an
+//! evening event; the override lasts until the given time or, by default,
+//! until the next scheduled change. Changes are published as
+//! `display-power` and overrides as `display-override`.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{cec, events, store};
+
+const HOURS_FILE: &str = "operating-hours.json";
+
+const BACKLIGHT_DIR: &str = "/sys/class/backlight";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+/// How far ahead to look for the next scheduled change
+const LOOKAHEAD_DAYS: i64 = 8;
+
+const DAYS: usize = 7;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// On and off times as "HH:MM"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayHours {
+    pub on: String,
+    pub off: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holiday {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    #[serde(default)]
+    pub name: String,
+    /// Special hours for the day; closed all day when absent
+    #[serde(default)]
+    pub hours: Option<DayHours>,
+}
+
+/// Which ways of turning the display off are used
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DisplayOutputs {
+    pub dpms: bool,
+    pub cec: bool,
+    pub backlight: bool,
+}
+
+impl Default for DisplayOutputs {
+    fn default() -> Self {
+        DisplayOutputs {
+            dpms: true,
+            cec: false,
+            backlight: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Schedule {
+    pub enabled: bool,
+    /// Monday first; a day without hours stays off
+    pub days: Vec<Option<DayHours>>,
+    pub holidays: Vec<Holiday>,
+    pub outputs: DisplayOutputs,
+}
+
+/// Payload of `display-override`
+#[derive(Debug, Clone, Serialize)]
+pub struct DisplayOverride {
+    /// Forced state, or `None` once the schedule is back in charge
+    pub on: Option<bool>,
+    /// Unix time the override ends
+    pub until: Option<i64>,
+}
+
+/// Payload of `display-power`
+#[derive(Debug, Clone, Serialize)]
+pub struct DisplayPower {
+    pub on: bool,
+    /// "schedule" or "override"
+    pub reason: String,
+    /// Outputs that could not be switched
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DisplayState {
+    /// Last state applied to the display, if the schedule has acted yet
+    pub on: Option<bool>,
+    pub scheduled_on: bool,
+    pub override_on: Option<bool>,
+    pub override_until: Option<i64>,
+    /// Unix time of the next scheduled change
+    pub next_change: Option<i64>,
+}
+
+struct Override {
+    on: bool,
+    until: i64,
+}
+
+pub struct HoursState {
+    schedule: Mutex<Schedule>,
+    manual: Mutex<Option<Override>>,
+    applied: Mutex<Option<bool>>,
+}
+
+impl HoursState {
+    pub fn load(app: &AppHandle) -> Self {
+        HoursState {
+            schedule: Mutex::new(store::load(app, HOURS_FILE)),
+            manual: Mutex::new(None),
+            applied: Mutex::new(None),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Not a time (HH:MM): {}", time))
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Not a date (YYYY-MM-DD): {}", date))
+}
+
+fn validate(schedule: &Schedule) -> Result<(), String> {
+    if schedule.days.len() != DAYS {
+        return Err(format!("Expected hours for {} days, got {}", DAYS, schedule.days.len()));
+    }
+    let hours = schedule.days.iter().flatten().chain(schedule.holidays.iter().filter_map(|day| day.hours.as_ref()));
+    for day in hours {
+        parse_time(&day.on)?;
+        parse_time(&day.off)?;
+    }
+    for holiday in &schedule.holidays {
+        parse_date(&holiday.date)?;
+    }
+    Ok(())
+}
+
+/// Hours in effect on a date: the holiday's if it is one, else the weekday's
+fn hours_on(schedule: &Schedule, date: NaiveDate) -> Option<(NaiveTime, NaiveTime)> {
+    let holiday = schedule.holidays.iter().find(|holiday| parse_date(&holiday.date).ok() == Some(date));
+    let hours = match holiday {
+        Some(holiday) => holiday.hours.as_ref(),
+        None => schedule.days.get(date.weekday().num_days_from_monday() as usize)?.as_ref(),
+    }?;
+    Some((parse_time(&hours.on).ok()?, parse_time(&hours.off).ok()?))
+}
+
+/// Whether the schedule wants the display on at a given minute
+fn scheduled_on(schedule: &Schedule, at: DateTime<Local>) -> bool {
+    if !schedule.enabled {
+        return true;
+    }
+    let (date, time) = (at.date_naive(), at.time());
+    let today = hours_on(schedule, date).is_some_and(|(on, off)| {
+        if off > on {
+            time >= on && time < off
+        } else {
+            time >= on
+        }
+    });
+    // Yesterday's hours may run past midnight
+    let overnight = date
+        .pred_opt()
+        .and_then(|yesterday| hours_on(schedule, yesterday))
+        .is_some_and(|(on, off)| off <= on && time < off);
+    today || overnight
+}
+
+/// The next minute the schedule changes state
+fn next_change(schedule: &Schedule, from: DateTime<Local>) -> Option<DateTime<Local>> {
+    if !schedule.enabled {
+        return None;
+    }
+    let start = from.with_second(0)?.with_nanosecond(0)?;
+    let current = scheduled_on(schedule, start);
+    (1..=LOOKAHEAD_DAYS * 24 * 60)
+        .map(|minutes| start + ChronoDuration::minutes(minutes))
+        .find(|at| scheduled_on(schedule, *at) != current)
+}
+
+fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", program, status))
+    }
+}
+
+fn set_dpms(on: bool) -> Result<(), String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        run("wlopm", &[if on { "--on" } else { "--off" }, "*"])
+    } else {
+        run("xset", &["dpms", "force", if on { "on" } else { "off" }])?;
+        if on {
+            // Also wake from the X screensaver
+            run("xset", &["s", "reset"])?;
+        }
+        Ok(())
+    }
+}
+
+fn set_backlight(on: bool) -> Result<(), String> {
+    let entries = fs::read_dir(BACKLIGHT_DIR).map_err(|e| format!("{}: {}", BACKLIGHT_DIR, e))?;
+    // bl_power is 0 for on and 4 (FB_BLANK_POWERDOWN) for off
+    let value = if on { "0" } else { "4" };
+    for entry in entries.flatten() {
+        let path = entry.path().join("bl_power");
+        fs::write(&path, value).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Switch every enabled output and publish `display-power`
+fn apply(app: &AppHandle, on: bool, reason: &str) {
+    let outputs = app.state::<HoursState>().schedule.lock().expect("hours lock").outputs.clone();
+    let mut errors = Vec::new();
+    if outputs.dpms {
+        errors.extend(set_dpms(on).err().map(|e| format!("DPMS: {}", e)));
+    }
+    if outputs.cec {
+        errors.extend(cec::set_power(app, on).err().map(|e| format!("CEC: {}", e.message)));
+    }
+    if outputs.backlight {
+        errors.extend(set_backlight(on).err().map(|e| format!("Backlight: {}", e)));
+    }
+    *app.state::<HoursState>().applied.lock().expect("hours lock") = Some(on);
+    events::publish(
+        app,
+        "display-power",
+        &DisplayPower {
+            on,
+            reason: reason.to_string(),
+            errors,
+        },
+    );
+}
+
+/// Apply the wanted state if it differs from the last one applied
+fn tick(app: &AppHandle) {
+    let state = app.state::<HoursState>();
+    let now = Local::now();
+    let schedule = state.schedule.lock().expect("hours lock").clone();
+
+    let mut manual = state.manual.lock().expect("hours lock");
+    if manual.as_ref().is_some_and(|manual| manual.until <= now.timestamp()) {
+        *manual = None;
+        events::publish(app, "display-override", &DisplayOverride { on: None, until: None });
+    }
+    let (wanted, reason) = match manual.as_ref() {
+        Some(manual) => (manual.on, "override"),
+        None => (scheduled_on(&schedule, now), "schedule"),
+    };
+    drop(manual);
+
+    let applied = *state.applied.lock().expect("hours lock");
+    // Nothing is switched until the schedule is first enabled or overridden
+    let in_charge = schedule.enabled || reason == "override" || applied.is_some();
+    if in_charge && applied != Some(wanted) {
+        apply(app, wanted, reason);
+    }
+}
+
+/// Wake a display blanked by the screensaver, unless the schedule has it off
+pub(crate) fn wake_display(app: &AppHandle) -> Result<(), String> {
+    if *app.state::<HoursState>().applied.lock().expect("hours lock") == Some(false) {
+        return Ok(());
+    }
+    set_dpms(true)
+}
+
+/// Whether the operating hours have the kiosk open now; always when no
+/// schedule is enabled
+pub(crate) fn is_open(app: &AppHandle) -> bool {
+    let schedule = app.state::<HoursState>().schedule.lock().expect("hours lock").clone();
+    scheduled_on(&schedule, Local::now())
+}
+
+/// Follow the schedule in the background
+pub fn start_hours(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        tick(&app);
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// The operating hours
+#[tauri::command]
+pub fn get_schedule(state: State<'_, HoursState>) -> Schedule {
+    state.schedule.lock().expect("hours lock").clone()
+}
+
+/// Replace the operating hours and apply them right away (admin)
+#[tauri::command]
+pub fn set_schedule(
+    app: AppHandle,
+    state: State<'_, HoursState>,
+    auth: State<'_, AuthState>,
+    schedule: Schedule,
+) -> Result<DisplayState, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    validate(&schedule).map_err(KioskError::invalid)?;
+    {
+        let mut current = state.schedule.lock().expect("hours lock");
+        store::save(&app, HOURS_FILE, &schedule)?;
+        *current = schedule;
+    }
+    tick(&app);
+    Ok(get_display_state(state))
+}
+
+/// Force the display on or off until `until` (unix time), by default the
+/// next scheduled change; `on: null` hands control back to the schedule
+#[tauri::command]
+pub fn set_display_override(
+    app: AppHandle,
+    state: State<'_, HoursState>,
+    auth: State<'_, AuthState>,
+    on: Option<bool>,
+    until: Option<i64>,
+) -> Result<DisplayState, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    let now = Local::now();
+    let event = match on {
+        Some(on) => {
+            let schedule = state.schedule.lock().expect("hours lock").clone();
+            let until = until
+                .or_else(|| next_change(&schedule, now).map(|at| at.timestamp()))
+                .unwrap_or_else(|| (now + ChronoDuration::days(1)).timestamp());
+            if until <= now.timestamp() {
+                return Err(KioskError::invalid("The override must end in the future"));
+            }
+            *state.manual.lock().expect("hours lock") = Some(Override { on, until });
+            DisplayOverride { on: Some(on), until: Some(until) }
+        }
+        None => {
+            *state.manual.lock().expect("hours lock") = None;
+            DisplayOverride { on: None, until: None }
+        }
+    };
+    events::publish(&app, "display-override", &event);
+    tick(&app);
+    Ok(get_display_state(state))
+}
+
+/// Current, scheduled and overridden display state
+#[tauri::command]
+pub fn get_display_state(state: State<'_, HoursState>) -> DisplayState {
+    let now = Local::now();
+    let schedule = state.schedule.lock().expect("hours lock").clone();
+    let manual = state.manual.lock().expect("hours lock");
+    DisplayState {
+        on: *state.applied.lock().expect("hours lock"),
+        scheduled_on: scheduled_on(&schedule, now),
+        override_on: manual.as_ref().map(|manual| manual.on),
+        override_until: manual.as_ref().map(|manual| manual.until),
+        next_change: next_change(&schedule, now).map(|at| at.timestamp()),
+    }
+}
@@ -0,0 +1,598 @@
+//! Localization
+//!
+//! String catalogs for the frontend and for text the backend generates:
+//! dates, numbers and prices, and error messages sent to the frontend.
+//! English (US and UK), German, French and Spanish catalogs are built in
+//! (`locales/*.json`); a deployment can add languages or override entries
+//! with `locales/<code>.json` in the data directory. A catalog falls back to
+//! its base language and then English for anything it leaves out, so `de-AT`
+//! only needs what differs from `de`.
+//!
+//! The active locale's number, currency and date formats can be customized
+//! (see `regional`); the customizations are saved with the locale and
+//! dropped when the locale is switched.
+//!
+//! Error translations are keyed by the English message with `{}` where the
+//! details go; a translation can reorder them with `{0}`, `{1}`. Messages
+//! without a translation are sent as they are.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Local};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, RwLock};
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::{events, store};
+
+const LOCALE_FILE: &str = "locale.json";
+
+/// Data directory subdirectory for added or overriding catalogs
+const LOCALE_DIR: &str = "locales";
+
+const DEFAULT_LOCALE: &str = "en";
+
+const BUNDLED: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.json")),
+    ("de", include_str!("../locales/de.json")),
+    ("fr", include_str!("../locales/fr.json")),
+    ("es", include_str!("../locales/es.json")),
+    ("en-GB", include_str!("../locales/en-GB.json")),
+];
+
+/// The active catalog, read when errors are serialized
+static ACTIVE: RwLock<Option<Arc<Catalog>>> = RwLock::new(None);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct DateNames {
+    date_short: String,
+    date_medium: String,
+    date_long: String,
+    time: String,
+    months: Vec<String>,
+    months_short: Vec<String>,
+    /// Monday first
+    weekdays: Vec<String>,
+    weekdays_short: Vec<String>,
+}
+
+/// How numbers, prices and measurements are written
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct NumberFormat {
+    pub decimal: String,
+    pub group: String,
+    /// Integer digits needed before grouping starts beyond the first group;
+    /// 2 leaves 4-digit numbers ungrouped as in Spanish
+    pub min_grouping: usize,
+    /// Where the price goes (`#`) relative to the currency symbol (`¤`)
+    pub currency: String,
+    pub measurement_system: MeasurementSystem,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            decimal: ".".to_string(),
+            group: ",".to_string(),
+            min_grouping: 1,
+            currency: "¤#".to_string(),
+            measurement_system: MeasurementSystem::Metric,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeasurementSystem {
+    Metric,
+    /// US customary units
+    Us,
+    /// Metric, but miles for distances and speeds
+    Uk,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Catalog {
+    #[serde(skip)]
+    code: String,
+    name: String,
+    dates: DateNames,
+    numbers: NumberFormat,
+    strings: BTreeMap<String, String>,
+    errors: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LocaleSetting {
+    locale: Option<String>,
+    /// Customized formats, over the locale's own
+    formats: FormatOverrides,
+    /// ISO 4217 code of the kiosk's currency
+    currency: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct FormatOverrides {
+    decimal: Option<String>,
+    group: Option<String>,
+    currency: Option<String>,
+    measurement_system: Option<MeasurementSystem>,
+    date_short: Option<String>,
+    date_long: Option<String>,
+    time: Option<String>,
+}
+
+/// The formats a locale writes numbers, prices and dates in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegionalFormats {
+    pub decimal_separator: String,
+    pub group_separator: String,
+    /// Where the price goes (`#`) relative to the currency symbol (`¤`)
+    pub currency_format: String,
+    pub measurement_system: MeasurementSystem,
+    /// strftime patterns
+    pub date_short: String,
+    pub date_long: String,
+    pub time: String,
+}
+
+/// A locale and its customized formats, checked and ready to save
+pub(crate) struct PreparedLocale {
+    catalog: Catalog,
+    setting: LocaleSetting,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleInfo {
+    /// BCP 47 style code, such as `de` or `de-AT`
+    pub code: String,
+    pub name: String,
+    /// Shipped with the kiosk rather than added in the data directory
+    pub bundled: bool,
+}
+
+/// Payload of `locale-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleChanged {
+    pub locale: String,
+    pub name: String,
+}
+
+/// Which of the catalog's date formats to use
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum DateStyle {
+    Short,
+    Medium,
+    Long,
+    Time,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// `de_DE.UTF-8` or `DE-de` to `de-DE`, rejecting anything that is not a
+/// language tag
+fn normalize(code: &str) -> Result<String, String> {
+    let code = code.split('.').next().unwrap_or_default().replace('_', "-");
+    let mut parts = code.split('-');
+    let language = parts.next().unwrap_or_default();
+    let valid = (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && code.split('-').skip(1).all(|part| {
+            (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+    if !valid {
+        return Err(format!("Invalid locale: {}", code));
+    }
+    let region = parts.map(|part| if part.len() == 2 { part.to_uppercase() } else { part.to_string() });
+    Ok(std::iter::once(language.to_lowercase()).chain(region).collect::<Vec<_>>().join("-"))
+}
+
+fn bundled(code: &str) -> Option<Value> {
+    let (_, text) = BUNDLED.iter().find(|(bundled, _)| *bundled == code)?;
+    serde_json::from_str(text).ok()
+}
+
+fn user_catalog(app: &AppHandle, code: &str) -> Option<Value> {
+    let path = store::data_path(app, &format!("{}/{}.json", LOCALE_DIR, code)).ok()?;
+    let value: Value = store::read_json(&path);
+    value.is_object().then_some(value)
+}
+
+/// Merge `overlay` into `base`, object by object
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge(base.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// The catalog for `code` with its base language and English beneath it
+fn catalog(app: &AppHandle, code: &str) -> Result<Catalog, String> {
+    let code = normalize(code)?;
+    let language = code.split('-').next().unwrap_or_default().to_string();
+    let mut chain = vec![DEFAULT_LOCALE.to_string(), language.clone(), code.clone()];
+    chain.dedup();
+
+    let mut merged = Value::Object(Default::default());
+    let mut found = false;
+    for layer in &chain {
+        for value in [bundled(layer), user_catalog(app, layer)].into_iter().flatten() {
+            // The English fallback alone does not make an unknown locale exist
+            found |= layer == &language || layer == &code;
+            merge(&mut merged, value);
+        }
+    }
+    if !found {
+        return Err(format!("No catalog for {}", code));
+    }
+    let mut catalog: Catalog = serde_json::from_value(merged).map_err(|e| format!("Catalog for {}: {}", code, e))?;
+    catalog.code = code;
+    Ok(catalog)
+}
+
+fn active() -> Option<Arc<Catalog>> {
+    ACTIVE.read().expect("locale lock").clone()
+}
+
+/// The locale to start in: the saved choice, then the system's `LANG`
+fn initial_locale(setting: &LocaleSetting) -> String {
+    setting
+        .locale
+        .clone()
+        .or_else(|| std::env::var("LANG").ok())
+        .and_then(|code| normalize(&code).ok())
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+fn customize(catalog: &mut Catalog, formats: &FormatOverrides) {
+    let numbers = &mut catalog.numbers;
+    let dates = &mut catalog.dates;
+    let fields = [
+        (&mut numbers.decimal, &formats.decimal),
+        (&mut numbers.group, &formats.group),
+        (&mut numbers.currency, &formats.currency),
+        (&mut dates.date_short, &formats.date_short),
+        (&mut dates.date_long, &formats.date_long),
+        (&mut dates.time, &formats.time),
+    ];
+    for (field, custom) in fields {
+        if let Some(custom) = custom {
+            field.clone_from(custom);
+        }
+    }
+    if let Some(system) = formats.measurement_system {
+        numbers.measurement_system = system;
+    }
+}
+
+/// Load the saved locale's catalog
+pub fn init(app: &AppHandle) {
+    let setting: LocaleSetting = store::load(app, LOCALE_FILE);
+    let catalog = match catalog(app, &initial_locale(&setting)) {
+        Ok(mut catalog) => {
+            customize(&mut catalog, &setting.formats);
+            Ok(catalog)
+        }
+        Err(_) => catalog(app, DEFAULT_LOCALE),
+    };
+    if let Ok(catalog) = catalog {
+        *ACTIVE.write().expect("locale lock") = Some(Arc::new(catalog));
+    }
+}
+
+fn regional_formats(catalog: &Catalog) -> RegionalFormats {
+    let pattern = |pattern: &str, fallback: &str| if pattern.is_empty() { fallback } else { pattern }.to_string();
+    RegionalFormats {
+        decimal_separator: catalog.numbers.decimal.clone(),
+        group_separator: catalog.numbers.group.clone(),
+        currency_format: catalog.numbers.currency.clone(),
+        measurement_system: catalog.numbers.measurement_system,
+        date_short: pattern(&catalog.dates.date_short, "%x"),
+        date_long: pattern(&catalog.dates.date_long, "%B %d, %Y"),
+        time: pattern(&catalog.dates.time, "%X"),
+    }
+}
+
+/// Formats of `locale` as its catalog has them, or the active locale's
+/// as customized
+pub(crate) fn formats(app: &AppHandle, locale: Option<&str>) -> Result<RegionalFormats, String> {
+    match locale {
+        Some(locale) => Ok(regional_formats(&catalog(app, locale)?)),
+        None => Ok(regional_formats(&active().unwrap_or_default())),
+    }
+}
+
+/// The kiosk's currency, when one has been picked
+pub(crate) fn currency(app: &AppHandle) -> Option<String> {
+    store::load::<LocaleSetting>(app, LOCALE_FILE).currency
+}
+
+fn check_separator(name: &str, separator: &str) -> Result<(), String> {
+    if !(1..=3).contains(&separator.chars().count()) || separator.chars().any(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid {} separator: {}", name, separator));
+    }
+    Ok(())
+}
+
+fn check_pattern(name: &str, pattern: &str) -> Result<(), String> {
+    if pattern.trim().is_empty() || StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("Invalid {} format: {}", name, pattern));
+    }
+    Ok(())
+}
+
+/// Check a locale and formats for it, keeping only what differs from the
+/// locale's own
+pub(crate) fn prepare_locale(
+    app: &AppHandle,
+    locale: &str,
+    formats: &RegionalFormats,
+    currency: Option<String>,
+) -> Result<PreparedLocale, String> {
+    let mut catalog = catalog(app, locale)?;
+    check_separator("decimal", &formats.decimal_separator)?;
+    check_separator("group", &formats.group_separator)?;
+    if formats.decimal_separator == formats.group_separator {
+        return Err("The decimal and group separators must differ".into());
+    }
+    if formats.currency_format.matches('#').count() != 1 || formats.currency_format.matches('¤').count() != 1 {
+        return Err("The currency format needs one # for the price and one ¤ for the symbol".into());
+    }
+    check_pattern("short date", &formats.date_short)?;
+    check_pattern("long date", &formats.date_long)?;
+    check_pattern("time", &formats.time)?;
+    let currency = currency.map(|code| code.trim().to_uppercase());
+    let invalid = |code: &&String| code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic());
+    if let Some(code) = currency.as_ref().filter(invalid) {
+        return Err(format!("Not a currency code: {}", code));
+    }
+
+    let own = regional_formats(&catalog);
+    let differs = |custom: &String, own: &String| (custom != own).then(|| custom.clone());
+    let overrides = FormatOverrides {
+        decimal: differs(&formats.decimal_separator, &own.decimal_separator),
+        group: differs(&formats.group_separator, &own.group_separator),
+        currency: differs(&formats.currency_format, &own.currency_format),
+        measurement_system: Some(formats.measurement_system).filter(|system| *system != own.measurement_system),
+        date_short: differs(&formats.date_short, &own.date_short),
+        date_long: differs(&formats.date_long, &own.date_long),
+        time: differs(&formats.time, &own.time),
+    };
+    customize(&mut catalog, &overrides);
+    let setting = LocaleSetting {
+        locale: Some(catalog.code.clone()),
+        formats: overrides,
+        currency,
+    };
+    Ok(PreparedLocale { catalog, setting })
+}
+
+/// Save a prepared locale and switch to it
+pub(crate) fn commit_locale(app: &AppHandle, prepared: PreparedLocale) -> Result<LocaleInfo, String> {
+    store::save(app, LOCALE_FILE, &prepared.setting)?;
+    Ok(activate(app, prepared.catalog))
+}
+
+/// Make a catalog the active one and announce it
+fn activate(app: &AppHandle, catalog: Catalog) -> LocaleInfo {
+    let info = LocaleInfo {
+        code: catalog.code.clone(),
+        name: catalog.name.clone(),
+        bundled: BUNDLED.iter().any(|(code, _)| *code == catalog.code),
+    };
+    *ACTIVE.write().expect("locale lock") = Some(Arc::new(catalog));
+    events::publish(
+        app,
+        "locale-changed",
+        &LocaleChanged {
+            locale: info.code.clone(),
+            name: info.name.clone(),
+        },
+    );
+    info
+}
+
+/// Match `message` against an English template, returning the details in
+/// place of its `{}`s
+fn match_template<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut pieces = template.split("{}");
+    let mut rest = message.strip_prefix(pieces.next()?)?;
+    let pieces: Vec<&str> = pieces.collect();
+    let mut args = Vec::with_capacity(pieces.len());
+    for (index, piece) in pieces.iter().enumerate() {
+        if index + 1 == pieces.len() {
+            args.push(rest.strip_suffix(piece)?);
+            rest = "";
+        } else {
+            if piece.is_empty() {
+                return None;
+            }
+            let at = rest.find(piece)?;
+            args.push(&rest[..at]);
+            rest = &rest[at + piece.len()..];
+        }
+    }
+    rest.is_empty().then_some(args)
+}
+
+/// Fill a translation's `{}` (in order) and `{0}`, `{1}` placeholders
+fn fill(translation: &str, args: &[&str]) -> String {
+    let mut out = String::with_capacity(translation.len());
+    let mut next = 0;
+    let mut rest = translation;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            rest = &rest[start..];
+            break;
+        };
+        let inside = &rest[start + 1..end];
+        let index = if inside.is_empty() {
+            next += 1;
+            Some(next - 1)
+        } else {
+            inside.parse::<usize>().ok()
+        };
+        match index.and_then(|index| args.get(index)) {
+            Some(arg) => out.push_str(arg),
+            None => out.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// An English error message in the active language, if it has a translation
+pub(crate) fn translate_error(message: &str) -> String {
+    let Some(catalog) = active() else {
+        return message.to_string();
+    };
+    catalog
+        .errors
+        .iter()
+        .find_map(|(template, translation)| Some(fill(translation, &match_template(template, message)?)))
+        .unwrap_or_else(|| message.to_string())
+}
+
+/// Serialize an error message translated, for `KioskError`
+pub(crate) fn serialize_error_message<S: Serializer>(message: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&translate_error(message))
+}
+
+/// Number rules for `locale`, or the active locale
+pub(crate) fn number_format(app: &AppHandle, locale: Option<&str>) -> Result<NumberFormat, String> {
+    match locale {
+        Some(locale) => Ok(catalog(app, locale)?.numbers),
+        None => Ok(active().map(|catalog| catalog.numbers.clone()).unwrap_or_default()),
+    }
+}
+
+/// Format a time with strftime specifiers, with month and weekday names in
+/// the active language
+pub(crate) fn format_time(time: &DateTime<Local>, pattern: &str) -> String {
+    let Some(catalog) = active() else {
+        return time.format(pattern).to_string();
+    };
+    let names = &catalog.dates;
+    let month = time.month0() as usize;
+    let weekday = time.weekday().num_days_from_monday() as usize;
+    let mut localized = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            localized.push(c);
+            continue;
+        }
+        let Some(spec) = chars.next() else {
+            localized.push('%');
+            break;
+        };
+        let name = match spec {
+            'B' => names.months.get(month),
+            'b' | 'h' => names.months_short.get(month),
+            'A' => names.weekdays.get(weekday),
+            'a' => names.weekdays_short.get(weekday),
+            _ => None,
+        };
+        match name {
+            // Escaped so chrono leaves the name alone
+            Some(name) => localized.push_str(&name.replace('%', "%%")),
+            None => {
+                localized.push('%');
+                localized.push(spec);
+            }
+        }
+    }
+    // A bad specifier in a catalog makes chrono fail rather than print
+    let mut formatted = String::new();
+    if write!(formatted, "{}", time.format(&localized)).is_err() {
+        return time.format("%c").to_string();
+    }
+    formatted
+}
+
+/// Format a time in one of the active language's date styles
+pub(crate) fn format_date(time: &DateTime<Local>, style: DateStyle) -> String {
+    let catalog = active().unwrap_or_default();
+    let dates = &catalog.dates;
+    let (pattern, fallback) = match style {
+        DateStyle::Short => (&dates.date_short, "%x"),
+        DateStyle::Medium => (&dates.date_medium, "%d %b %Y"),
+        DateStyle::Long => (&dates.date_long, "%B %d, %Y"),
+        DateStyle::Time => (&dates.time, "%X"),
+    };
+    format_time(time, if pattern.is_empty() { fallback } else { pattern })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Locales with a catalog, bundled or added in the data directory
+#[tauri::command]
+pub fn list_locales(app: AppHandle) -> Vec<LocaleInfo> {
+    let mut locales: BTreeMap<String, bool> = BUNDLED.iter().map(|(code, _)| (code.to_string(), true)).collect();
+    let added = store::data_path(&app, LOCALE_DIR).ok().and_then(|dir| std::fs::read_dir(dir).ok());
+    for path in added.into_iter().flatten().flatten().map(|entry| entry.path()) {
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
+        if let Some(code) = path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| normalize(stem).ok()) {
+            locales.entry(code).or_insert(false);
+        }
+    }
+    locales
+        .into_iter()
+        .filter_map(|(code, bundled)| {
+            let name = catalog(&app, &code).ok()?.name;
+            Some(LocaleInfo { code, name, bundled })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_locale() -> String {
+    active().map_or_else(|| DEFAULT_LOCALE.to_string(), |catalog| catalog.code.clone())
+}
+
+/// The UI strings for `locale`, or the active locale, with fallbacks filled in
+#[tauri::command]
+pub fn get_strings(app: AppHandle, locale: Option<String>) -> Result<BTreeMap<String, String>, KioskError> {
+    match locale {
+        Some(locale) => Ok(catalog(&app, &locale).map_err(KioskError::not_found)?.strings),
+        None => Ok(active().map(|catalog| catalog.strings.clone()).unwrap_or_default()),
+    }
+}
+
+/// Switch the kiosk's language and remember it; customized formats are
+/// dropped for the new locale's own
+#[tauri::command]
+pub fn set_locale(app: AppHandle, locale: String) -> Result<LocaleInfo, KioskError> {
+    let catalog = catalog(&app, &locale).map_err(KioskError::not_found)?;
+    let setting = LocaleSetting {
+        locale: Some(catalog.code.clone()),
+        formats: FormatOverrides::default(),
+        currency: currency(&app),
+    };
+    store::save(&app, LOCALE_FILE, &setting)?;
+    Ok(activate(&app, catalog))
+}
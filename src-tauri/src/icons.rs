@@ -0,0 +1,509 @@
+//! Icon extraction
+//!
+//! Gives the file manager and Start menu a proper icon for a file: the
+//! best-sized image of an .ico or .cur, the main icon in the resources of
+//! a Windows .exe or .dll, or the icon a .desktop entry names, found in the
+//! freedesktop icon themes and pixmaps when it is not a path. Raster icons
+//! are scaled to the size asked for and come back as PNG; SVG icons are
+//! passed through as they are, since they scale on their own.
+//!
+//! Results are cached in memory by file and size, and dropped when the
+//! file changes. Lite mode keeps no cache.
+
+use base64::Engine;
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime};
+use tauri::{AppHandle, State};
+
+use crate::{bitmap, config};
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+
+/// Largest file read for its icon
+const MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+const CACHE_ENTRIES: usize = 256;
+
+const MIN_SIZE: u32 = 8;
+const MAX_SIZE: u32 = 256;
+
+/// Resource types in a Windows executable
+const RT_ICON: u32 = 3;
+const RT_GROUP_ICON: u32 = 14;
+
+/// Files a .desktop entry may name as its icon by path
+const ICON_EXTENSIONS: &[&str] = &["png", "svg", "svgz", "ico", "bmp", "jpg", "jpeg"];
+
+/// Where .desktop entries may be read from outside the virtual roots
+const APPLICATION_DIRS: &[&str] = &[
+    "/usr/share/applications",
+    "/usr/local/share/applications",
+    "/var/lib/flatpak/exports/share/applications",
+    "/var/lib/snapd/desktop/applications",
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconSource {
+    /// An .ico or .cur file
+    Ico,
+    /// The resources of an .exe or .dll
+    Executable,
+    /// A PNG or other picture
+    Image,
+    /// An SVG, passed through unscaled
+    Svg,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractedIcon {
+    pub source: IconSource,
+    pub width: u32,
+    pub height: u32,
+    /// PNG, or the SVG, as a `data:` URL
+    pub data_url: String,
+    /// The file the icon came from, when a .desktop entry pointed elsewhere
+    pub file: Option<String>,
+}
+
+struct CachedIcon {
+    modified: Option<SystemTime>,
+    icon: ExtractedIcon,
+    used: Instant,
+}
+
+#[derive(Default)]
+pub struct IconState(Mutex<HashMap<String, CachedIcon>>);
+
+// ============================================================================
+// Executables
+// ============================================================================
+
+fn u16_at(data: &[u8], at: usize) -> Result<u16, String> {
+    data.get(at..at + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| "The executable is cut short".to_string())
+}
+
+fn u32_at(data: &[u8], at: usize) -> Result<u32, String> {
+    data.get(at..at + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| "The executable is cut short".to_string())
+}
+
+/// A PE section as (virtual address, virtual size, file offset, file size)
+type Section = (u32, u32, u32, u32);
+
+/// The sections of a PE file and the address of its resources
+fn pe_layout(data: &[u8]) -> Result<(Vec<Section>, u32), String> {
+    if !data.starts_with(b"MZ") {
+        return Err("Not a Windows executable".into());
+    }
+    let pe = u32_at(data, 0x3c)? as usize;
+    if data.get(pe..pe + 4) != Some(b"PE\0\0".as_slice()) {
+        return Err("Not a Windows executable".into());
+    }
+    let sections = u16_at(data, pe + 6)? as usize;
+    let optional = pe + 24;
+    let optional_size = u16_at(data, pe + 20)? as usize;
+    // Data directories sit further in for 64-bit images
+    let (count_at, directories) = match u16_at(data, optional)? {
+        0x10b => (optional + 92, optional + 96),
+        0x20b => (optional + 108, optional + 112),
+        magic => return Err(format!("Unknown executable type {:#x}", magic)),
+    };
+    if u32_at(data, count_at)? < 3 {
+        return Err("The executable has no resources".into());
+    }
+    let resources = u32_at(data, directories + 16)?;
+    if resources == 0 {
+        return Err("The executable has no resources".into());
+    }
+    let table = optional + optional_size;
+    let sections = (0..sections)
+        .map(|index| {
+            let at = table + index * 40;
+            Ok((u32_at(data, at + 12)?, u32_at(data, at + 8)?, u32_at(data, at + 20)?, u32_at(data, at + 16)?))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok((sections, resources))
+}
+
+fn rva_to_offset(sections: &[Section], rva: u32) -> Result<usize, String> {
+    sections
+        .iter()
+        .find(|&&(address, size, _, raw_size)| rva >= address && rva - address < size.max(raw_size))
+        .map(|&(address, _, offset, _)| (offset + (rva - address)) as usize)
+        .ok_or_else(|| format!("Address {:#x} is outside the executable", rva))
+}
+
+/// Entries of the resource directory at `at` as (id, offset); named
+/// entries have the top bit of their id set, subdirectories of their offset
+fn resource_entries(data: &[u8], base: usize, at: u32) -> Result<Vec<(u32, u32)>, String> {
+    let at = base + at as usize;
+    let count = u16_at(data, at + 12)? as usize + u16_at(data, at + 14)? as usize;
+    (0..count)
+        .map(|index| Ok((u32_at(data, at + 16 + index * 8)?, u32_at(data, at + 20 + index * 8)?)))
+        .collect()
+}
+
+/// The data of the first language of each resource of one type, by id
+fn resources_of(
+    data: &[u8],
+    sections: &[Section],
+    base: usize,
+    kind: u32,
+) -> Result<Vec<(u32, Vec<u8>)>, String> {
+    let Some(&(_, types)) = resource_entries(data, base, 0)?.iter().find(|&&(id, _)| id == kind) else {
+        return Ok(Vec::new());
+    };
+    let mut found = Vec::new();
+    for (id, names) in resource_entries(data, base, types & 0x7fff_ffff)? {
+        let Some(&(_, entry)) = resource_entries(data, base, names & 0x7fff_ffff)?.first() else {
+            continue;
+        };
+        let entry = base + (entry & 0x7fff_ffff) as usize;
+        let offset = rva_to_offset(sections, u32_at(data, entry)?)?;
+        let size = u32_at(data, entry + 4)? as usize;
+        let bytes = data
+            .get(offset..offset.saturating_add(size))
+            .ok_or("A resource is cut short")?;
+        found.push((id, bytes.to_vec()));
+    }
+    Ok(found)
+}
+
+/// The executable's main icon, rebuilt as an .ico file: the first icon
+/// group, whose entries name the icon images by id
+fn executable_icon(data: &[u8]) -> Result<Vec<u8>, String> {
+    let (sections, resources) = pe_layout(data)?;
+    let base = rva_to_offset(&sections, resources)?;
+    let groups = resources_of(data, &sections, base, RT_GROUP_ICON)?;
+    let (_, group) = groups.first().ok_or("The executable has no icon")?;
+    let images: HashMap<u32, Vec<u8>> = resources_of(data, &sections, base, RT_ICON)?.into_iter().collect();
+
+    let count = u16_at(group, 4)? as usize;
+    let mut found = Vec::new();
+    for index in 0..count {
+        let at = 6 + index * 14;
+        let entry = group.get(at..at + 14).ok_or("The icon group is cut short")?;
+        if let Some(body) = images.get(&(u16_at(entry, 12)? as u32)) {
+            found.push((&entry[..8], body));
+        }
+    }
+    if found.is_empty() {
+        return Err("The executable has no icon".into());
+    }
+    // The same fields as an .ico entry, with the image's offset in place of its id
+    let mut ico = vec![0, 0, 1, 0];
+    ico.extend((found.len() as u16).to_le_bytes());
+    let mut offset = 6 + 16 * found.len();
+    for (fields, body) in &found {
+        ico.extend(*fields);
+        ico.extend((body.len() as u32).to_le_bytes());
+        ico.extend((offset as u32).to_le_bytes());
+        offset += body.len();
+    }
+    found.into_iter().for_each(|(_, body)| ico.extend(body));
+    Ok(ico)
+}
+
+// ============================================================================
+// Desktop Entries
+// ============================================================================
+
+/// `Icon=` of a .desktop entry's main section
+fn desktop_icon_name(text: &str) -> Option<String> {
+    let mut in_entry = false;
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+        } else if in_entry {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim() == "Icon" && !value.trim().is_empty() {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// XDG data directories, the user's first
+pub(crate) fn data_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    match std::env::var_os("XDG_DATA_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => dirs.push(PathBuf::from(dir)),
+        None => dirs.extend(std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))),
+    }
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(system.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+    dirs
+}
+
+/// Whether a .desktop entry sits in a standard applications folder
+fn in_application_dir(path: &Path) -> bool {
+    let user_dirs = data_dirs().into_iter().map(|dir| dir.join("applications"));
+    let mut dirs = APPLICATION_DIRS.iter().map(PathBuf::from).chain(user_dirs);
+    path.extension().is_some_and(|extension| extension == "desktop")
+        && !path.components().any(|part| part == std::path::Component::ParentDir)
+        && dirs.any(|dir| path.starts_with(dir))
+}
+
+/// Pixel size of a theme folder such as `48x48` or `48x48@2`
+fn folder_size(name: &str) -> Option<u32> {
+    let (width, rest) = name.split_once('x')?;
+    let height = rest.split('@').next()?;
+    if width == height {
+        width.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Look an icon name up in the icon themes, hicolor first, then the
+/// pixmaps folders: the smallest PNG at least `size`, else an SVG, else
+/// the largest PNG
+fn find_themed_icon(name: &str, size: u32) -> Option<PathBuf> {
+    let bases: Vec<PathBuf> = data_dirs().into_iter().map(|dir| dir.join("icons")).collect();
+    let mut themes: Vec<String> = vec!["hicolor".to_string()];
+    for base in &bases {
+        let Ok(entries) = fs::read_dir(base) else {
+            continue;
+        };
+        let mut found: Vec<String> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|theme| !themes.contains(theme))
+            .collect();
+        found.sort();
+        themes.extend(found);
+    }
+
+    for theme in &themes {
+        let mut sized: Vec<(u32, PathBuf)> = Vec::new();
+        let mut scalable = None;
+        for folder in bases.iter().map(|base| base.join(theme)).filter(|folder| folder.is_dir()) {
+            let Ok(entries) = fs::read_dir(&folder) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let dir_name = entry.file_name().to_string_lossy().to_string();
+                let size_of = folder_size(&dir_name);
+                let Ok(contexts) = fs::read_dir(entry.path()) else {
+                    continue;
+                };
+                for context in contexts.flatten() {
+                    let png = context.path().join(format!("{}.png", name));
+                    let svg = context.path().join(format!("{}.svg", name));
+                    match size_of {
+                        Some(pixels) if png.is_file() => sized.push((pixels, png)),
+                        _ if scalable.is_none() && svg.is_file() => scalable = Some(svg),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        sized.sort_by_key(|(pixels, _)| *pixels);
+        if let Some((_, path)) = sized.iter().find(|(pixels, _)| *pixels >= size) {
+            return Some(path.clone());
+        }
+        if let Some(svg) = scalable {
+            return Some(svg);
+        }
+        if let Some((_, path)) = sized.pop() {
+            return Some(path);
+        }
+    }
+
+    let pixmaps = data_dirs().into_iter().map(|dir| dir.join("pixmaps"));
+    pixmaps
+        .flat_map(|dir| ["png", "svg"].map(|extension| dir.join(format!("{}.{}", name, extension))))
+        .find(|path| path.is_file())
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The image closest to `size`: the smallest at least that big, or the
+/// largest, preferring more colours at the same size
+fn best_image(images: Vec<bitmap::Bitmap>, size: u32) -> Option<RgbaImage> {
+    let mut images: Vec<_> = images.into_iter().map(|bitmap| (bitmap.image, bitmap.bits)).collect();
+    let side = |image: &RgbaImage| image.width().max(image.height());
+    images.sort_by_key(|(image, bits)| (side(image), *bits));
+    let largest = side(&images.last()?.0);
+    let target = images.iter().map(|(image, _)| side(image)).find(|&side| side >= size).unwrap_or(largest);
+    let index = images.iter().rposition(|(image, _)| side(image) == target)?;
+    Some(images.swap_remove(index).0)
+}
+
+fn scaled_icon(source: IconSource, image: RgbaImage, size: u32) -> Result<ExtractedIcon, String> {
+    let image = if image.width() == size && image.height() == size {
+        image
+    } else {
+        // Keep the aspect ratio, centred on a transparent square
+        let scale = size as f32 / image.width().max(image.height()) as f32;
+        let width = ((image.width() as f32 * scale).round() as u32).max(1);
+        let height = ((image.height() as f32 * scale).round() as u32).max(1);
+        let resized = imageops::resize(&image, width, height, FilterType::Lanczos3);
+        let mut square = RgbaImage::new(size, size);
+        imageops::overlay(&mut square, &resized, ((size - width) / 2) as i64, ((size - height) / 2) as i64);
+        square
+    };
+    let mut png = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(ExtractedIcon {
+        source,
+        width: size,
+        height: size,
+        data_url: format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png)),
+        file: None,
+    })
+}
+
+fn read_limited(path: &Path) -> Result<Vec<u8>, String> {
+    let size = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?.len();
+    if size > MAX_FILE_BYTES {
+        return Err(format!("{} is too large to read for its icon", path.display()));
+    }
+    fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// The icon of a file, by its extension
+fn icon_of(path: &Path, size: u32, follow_desktop: bool) -> Result<ExtractedIcon, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "desktop" if follow_desktop => {
+            let text = String::from_utf8_lossy(&read_limited(path)?).to_string();
+            let name = desktop_icon_name(&text).ok_or("The entry names no icon")?;
+            let file = if Path::new(&name).is_absolute() {
+                let extension = Path::new(&name)
+                    .extension()
+                    .and_then(|extension| extension.to_str())
+                    .map(|extension| extension.to_ascii_lowercase());
+                if !extension.is_some_and(|extension| ICON_EXTENSIONS.contains(&extension.as_str())) {
+                    return Err(format!("{} is not an icon file", name));
+                }
+                PathBuf::from(&name)
+            } else {
+                find_themed_icon(&name, size).ok_or_else(|| format!("No icon named {} is installed", name))?
+            };
+            let mut icon = icon_of(&file, size, false)?;
+            icon.file = Some(file.to_string_lossy().to_string());
+            Ok(icon)
+        }
+        "ico" | "cur" => {
+            let images = bitmap::read_icon(&read_limited(path)?)?;
+            let image = best_image(images, size).ok_or("The icon has no images")?;
+            scaled_icon(IconSource::Ico, image, size)
+        }
+        "exe" | "dll" | "cpl" | "scr" | "ocx" => {
+            let images = bitmap::read_icon(&executable_icon(&read_limited(path)?)?)?;
+            let image = best_image(images, size).ok_or("The executable has no icon")?;
+            scaled_icon(IconSource::Executable, image, size)
+        }
+        "svg" | "svgz" => {
+            let data = read_limited(path)?;
+            let text = if extension == "svgz" {
+                let mut text = Vec::new();
+                std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(data.as_slice()), &mut text)
+                    .map_err(|e| format!("Invalid compressed SVG: {}", e))?;
+                text
+            } else {
+                data
+            };
+            Ok(ExtractedIcon {
+                source: IconSource::Svg,
+                width: size,
+                height: size,
+                data_url: format!(
+                    "data:image/svg+xml;base64,{}",
+                    base64::engine::general_purpose::STANDARD.encode(text)
+                ),
+                file: None,
+            })
+        }
+        _ => {
+            let data = read_limited(path)?;
+            let image = match bitmap::decode(&data) {
+                Ok((_, images, _)) => best_image(images, size).ok_or("The file has no images")?,
+                Err(_) => image::load_from_memory(&data)
+                    .map_err(|_| format!("No icon can be taken from {}", path.display()))?
+                    .to_rgba8(),
+            };
+            scaled_icon(IconSource::Image, image, size)
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// An icon for a file at a virtual path, or for a .desktop entry in the
+/// system's applications folders, `size` pixels square
+#[tauri::command]
+pub fn extract_icon(
+    app: AppHandle,
+    state: State<'_, IconState>,
+    path: String,
+    size: Option<u32>,
+) -> Result<ExtractedIcon, KioskError> {
+    let size = size.unwrap_or(32).clamp(MIN_SIZE, MAX_SIZE);
+    let real = if in_application_dir(Path::new(&path)) {
+        PathBuf::from(&path)
+    } else {
+        vfs::resolve(&app, &path, Access::Read)?
+    };
+    let modified = fs::metadata(&real).and_then(|metadata| metadata.modified()).ok();
+    let key = format!("{}:{}", real.display(), size);
+
+    if let Some(cached) = state.0.lock().expect("icon cache lock").get_mut(&key) {
+        if cached.modified == modified {
+            cached.used = Instant::now();
+            return Ok(cached.icon.clone());
+        }
+    }
+    let icon = icon_of(&real, size, true).map_err(KioskError::invalid)?;
+    if config::lite_mode(&app) {
+        return Ok(icon);
+    }
+
+    let mut cache = state.0.lock().expect("icon cache lock");
+    if cache.len() >= CACHE_ENTRIES && !cache.contains_key(&key) {
+        let oldest = cache.iter().min_by_key(|(_, entry)| entry.used).map(|(key, _)| key.clone());
+        if let Some(oldest) = oldest {
+            cache.remove(&oldest);
+        }
+    }
+    cache.insert(
+        key,
+        CachedIcon {
+            modified,
+            icon: icon.clone(),
+            used: Instant::now(),
+        },
+    );
+    Ok(icon)
+}
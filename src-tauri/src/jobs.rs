@@ -0,0 +1,224 @@
+//! Background jobs
+//!
+//! Long-running commands start a job and return its id straight away. The
+//! work runs on its own thread with a `JobHandle` for progress reports and
+//! cancellation checks; the outcome is kept in the registry for
+//! `get_job_status` and published as `job-finished`. Jobs started from a
+//! window are cancelled when that window closes.
+
+use chrono::Local;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::events;
+
+/// Finished jobs kept for status queries
+const FINISHED_KEPT: usize = 100;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Payload of `job-progress` and `job-finished`
+#[derive(Debug, Clone, Serialize)]
+pub struct JobStatus {
+    pub id: String,
+    pub kind: String,
+    pub state: JobState,
+    /// 0.0 to 1.0 when the job can tell
+    pub progress: Option<f64>,
+    pub message: Option<String>,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+    /// Label of the window that started the job
+    pub window: Option<String>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+struct Job {
+    status: Mutex<JobStatus>,
+    cancelled: AtomicBool,
+}
+
+#[derive(Default)]
+pub struct JobRegistry(Mutex<HashMap<String, Arc<Job>>>);
+
+/// Passed to the job's work for progress and cancellation
+pub struct JobHandle {
+    app: AppHandle,
+    job: Arc<Job>,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> String {
+        self.job.status.lock().expect("job status lock").id.clone()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.job.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Err once the job has been cancelled, for use with `?`
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        Ok(())
+    }
+
+    /// Record progress and emit `job-progress`
+    pub fn progress(&self, fraction: f64, message: Option<String>) {
+        let status = {
+            let mut status = self.job.status.lock().expect("job status lock");
+            status.progress = Some(fraction.clamp(0.0, 1.0));
+            status.message = message;
+            status.clone()
+        };
+        events::publish_progress(&self.app, "job-progress", status);
+    }
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+fn finish(app: &AppHandle, job: &Job, outcome: Result<Value, String>) {
+    let status = {
+        let mut status = job.status.lock().expect("job status lock");
+        status.finished_at = Some(Local::now().timestamp());
+        match outcome {
+            _ if job.cancelled.load(Ordering::Relaxed) => status.state = JobState::Cancelled,
+            Ok(result) => {
+                status.state = JobState::Completed;
+                status.progress = Some(1.0);
+                status.result = Some(result);
+            }
+            Err(error) => {
+                status.state = JobState::Failed;
+                status.error = Some(error);
+            }
+        }
+        status.clone()
+    };
+    events::publish(app, "job-finished", status);
+}
+
+/// Drop the oldest finished jobs beyond the retention limit
+fn prune(jobs: &mut HashMap<String, Arc<Job>>) {
+    let mut finished: Vec<(i64, String)> = jobs
+        .iter()
+        .filter_map(|(id, job)| {
+            let status = job.status.lock().expect("job status lock");
+            status.finished_at.map(|at| (at, id.clone()))
+        })
+        .collect();
+    if finished.len() <= FINISHED_KEPT {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() - FINISHED_KEPT) {
+        jobs.remove(id);
+    }
+}
+
+/// Start a job on its own thread and return its id
+pub(crate) fn spawn<T, F>(app: &AppHandle, window: Option<&str>, kind: &str, work: F) -> String
+where
+    T: Serialize,
+    F: FnOnce(&JobHandle) -> Result<T, String> + Send + 'static,
+{
+    let id = uuid::Uuid::new_v4().to_string();
+    let job = Arc::new(Job {
+        status: Mutex::new(JobStatus {
+            id: id.clone(),
+            kind: kind.to_string(),
+            state: JobState::Running,
+            progress: None,
+            message: None,
+            result: None,
+            error: None,
+            window: window.map(str::to_string),
+            started_at: Local::now().timestamp(),
+            finished_at: None,
+        }),
+        cancelled: AtomicBool::new(false),
+    });
+
+    {
+        let registry = app.state::<JobRegistry>();
+        let mut jobs = registry.0.lock().expect("job registry lock");
+        prune(&mut jobs);
+        jobs.insert(id.clone(), job.clone());
+    }
+
+    let handle = JobHandle { app: app.clone(), job };
+    std::thread::spawn(move || {
+        let outcome = work(&handle).and_then(|result| serde_json::to_value(result).map_err(|e| e.to_string()));
+        finish(&handle.app, &handle.job, outcome);
+    });
+    id
+}
+
+/// Cancel running jobs started from a closed window and forget its finished ones
+pub fn cancel_window_jobs(app: &AppHandle, window: &str) {
+    let registry = app.state::<JobRegistry>();
+    let mut jobs = registry.0.lock().expect("job registry lock");
+    jobs.retain(|_, job| {
+        let status = job.status.lock().expect("job status lock");
+        if status.window.as_deref() != Some(window) {
+            return true;
+        }
+        job.cancelled.store(true, Ordering::Relaxed);
+        // Running jobs stay until their thread notices and finishes
+        status.state == JobState::Running
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get a job's state, progress and result
+#[tauri::command]
+pub fn get_job_status(state: State<'_, JobRegistry>, id: String) -> Result<JobStatus, KioskError> {
+    let jobs = state.0.lock().expect("job registry lock");
+    let job = jobs.get(&id).ok_or_else(|| KioskError::not_found(format!("Job not found: {}", id)))?;
+    let status = job.status.lock().expect("job status lock").clone();
+    Ok(status)
+}
+
+/// Ask a running job to stop; it finishes as cancelled
+#[tauri::command]
+pub fn cancel_job(state: State<'_, JobRegistry>, id: String) -> Result<(), KioskError> {
+    let jobs = state.0.lock().expect("job registry lock");
+    let job = jobs.get(&id).ok_or_else(|| KioskError::not_found(format!("Job not found: {}", id)))?;
+    job.cancelled.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// List known jobs, newest first
+#[tauri::command]
+pub fn list_jobs(state: State<'_, JobRegistry>) -> Vec<JobStatus> {
+    let jobs = state.0.lock().expect("job registry lock");
+    let mut statuses: Vec<JobStatus> = jobs
+        .values()
+        .map(|job| job.status.lock().expect("job status lock").clone())
+        .collect();
+    statuses.sort_by_key(|status| std::cmp::Reverse(status.started_at));
+    statuses
+}
@@ -0,0 +1,438 @@
+//! Keyboard layouts
+//!
+//! Lists the XKB layouts and variants the system knows (from the rules'
+//! `evdev.lst`, or `localectl` without one) and switches the session's
+//! layout. Each operator keeps their own layouts and the one in use; the
+//! kiosk's own (when nobody is signed in) is also made the system default
+//! with `localectl`. Saved in `keyboard.json`, applied again at startup and
+//! whenever the operator changes, and announced as
+//! `keyboard-layout-changed` so the taskbar indicator can follow.
+//!
+//! Layouts are named the XKB way: `de`, or `de(nodeadkeys)` for a variant.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::events;
+use crate::pointer::run;
+use crate::store;
+
+const KEYBOARD_FILE: &str = "keyboard.json";
+
+const DEFAULT_LAYOUT: &str = "us";
+
+/// Layouts a user can switch between
+const MAX_LAYOUTS: usize = 8;
+
+/// Layout lists of the XKB rules, most complete first
+const RULES_LISTS: &[&str] = &["/usr/share/X11/xkb/rules/evdev.lst", "/usr/share/X11/xkb/rules/base.lst"];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyboardLayout {
+    /// `layout` or `layout(variant)`
+    pub id: String,
+    pub layout: String,
+    pub variant: Option<String>,
+    pub name: String,
+}
+
+/// One user's layouts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyboardSettings {
+    /// Layouts offered by the indicator, in the user's order
+    pub layouts: Vec<String>,
+    /// The layout in use
+    pub active: String,
+}
+
+impl Default for KeyboardSettings {
+    fn default() -> Self {
+        KeyboardSettings {
+            layouts: vec![DEFAULT_LAYOUT.to_string()],
+            active: DEFAULT_LAYOUT.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct KeyboardData {
+    /// The kiosk's own, used when nobody is signed in
+    kiosk: KeyboardSettings,
+    /// Operators' own, by username
+    operators: HashMap<String, KeyboardSettings>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LayoutChanged<'a> {
+    operator: Option<&'a str>,
+    #[serde(flatten)]
+    settings: &'a KeyboardSettings,
+}
+
+pub struct KeyboardState(Mutex<KeyboardData>);
+
+impl KeyboardState {
+    pub fn load(app: &AppHandle) -> Self {
+        KeyboardState(Mutex::new(store::load(app, KEYBOARD_FILE)))
+    }
+}
+
+/// A way of switching the session's layout
+type Backend = fn(&str, Option<&str>) -> Result<(), String>;
+
+// ============================================================================
+// Layouts
+// ============================================================================
+
+/// `de(nodeadkeys)` as `("de", Some("nodeadkeys"))`
+fn split_id(id: &str) -> (&str, Option<&str>) {
+    match id.strip_suffix(')').and_then(|id| id.split_once('(')) {
+        Some((layout, variant)) => (layout, Some(variant)),
+        None => (id, None),
+    }
+}
+
+/// The `! layout` and `! variant` sections of a rules list
+fn parse_rules_list(text: &str) -> Vec<KeyboardLayout> {
+    let mut layouts = Vec::new();
+    let mut section = "";
+    for line in text.lines() {
+        if let Some(name) = line.strip_prefix('!') {
+            section = name.trim();
+            continue;
+        }
+        let Some((key, description)) = line.trim().split_once(char::is_whitespace) else {
+            continue;
+        };
+        let description = description.trim();
+        match section {
+            "layout" => {
+                layouts.push(KeyboardLayout {
+                    id: key.to_string(),
+                    layout: key.to_string(),
+                    variant: None,
+                    name: description.to_string(),
+                });
+            }
+            "variant" => {
+                // `nodeadkeys      de: German (no dead keys)`
+                let Some((layout, name)) = description.split_once(':') else {
+                    continue;
+                };
+                layouts.push(KeyboardLayout {
+                    id: format!("{}({})", layout, key),
+                    layout: layout.to_string(),
+                    variant: Some(key.to_string()),
+                    name: name.trim().to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+    // Variants follow their layout
+    let order: HashMap<String, usize> = layouts
+        .iter()
+        .filter(|layout| layout.variant.is_none())
+        .enumerate()
+        .map(|(index, layout)| (layout.layout.clone(), index))
+        .collect();
+    layouts.retain(|layout| order.contains_key(&layout.layout));
+    layouts.sort_by_key(|layout| (order[&layout.layout], layout.variant.is_some()));
+    layouts
+}
+
+fn localectl_layouts() -> Vec<KeyboardLayout> {
+    let Ok(output) = Command::new("localectl").arg("list-x11-keymap-layouts").output() else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|layout| KeyboardLayout {
+            id: layout.to_string(),
+            layout: layout.to_string(),
+            variant: None,
+            name: layout.to_string(),
+        })
+        .collect()
+}
+
+fn keyboard_layouts() -> Vec<KeyboardLayout> {
+    RULES_LISTS
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|text| parse_rules_list(&text))
+        .find(|layouts| !layouts.is_empty())
+        .unwrap_or_else(localectl_layouts)
+}
+
+fn validate(id: &str, known: &[KeyboardLayout]) -> Result<(), String> {
+    let (layout, variant) = split_id(id);
+    let valid = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || "_-".contains(c));
+    if !valid(layout) || !variant.map_or(true, valid) {
+        return Err(format!("{} is not a keyboard layout", id));
+    }
+    // Without a list to go by, the backends have the last word
+    if !known.is_empty() && !known.iter().any(|known| known.id == id) {
+        return Err(format!("No keyboard layout {} is installed", id));
+    }
+    Ok(())
+}
+
+/// Check a whole set of layouts, as the regional settings give them
+pub(crate) fn check_settings(settings: &KeyboardSettings) -> Result<(), String> {
+    if settings.layouts.is_empty() || settings.layouts.len() > MAX_LAYOUTS {
+        return Err(format!("Keep 1-{} keyboard layouts", MAX_LAYOUTS));
+    }
+    if !settings.layouts.contains(&settings.active) {
+        return Err(format!("{} is not one of the keyboard layouts", settings.active));
+    }
+    let known = keyboard_layouts();
+    settings.layouts.iter().try_for_each(|layout| validate(layout, &known))
+}
+
+// ============================================================================
+// Session
+// ============================================================================
+
+fn apply_sway(layout: &str, variant: Option<&str>) -> Result<(), String> {
+    run("swaymsg", &["input", "type:keyboard", "xkb_layout", layout])?;
+    run("swaymsg", &["input", "type:keyboard", "xkb_variant", variant.unwrap_or("\"\"")])
+}
+
+fn apply_hyprland(layout: &str, variant: Option<&str>) -> Result<(), String> {
+    run("hyprctl", &["keyword", "input:kb_variant", variant.unwrap_or("")])?;
+    run("hyprctl", &["keyword", "input:kb_layout", layout])
+}
+
+fn apply_x11(layout: &str, variant: Option<&str>) -> Result<(), String> {
+    run("setxkbmap", &["-layout", layout, "-variant", variant.unwrap_or("")])
+}
+
+fn apply_gsettings(layout: &str, variant: Option<&str>) -> Result<(), String> {
+    let source = match variant {
+        Some(variant) => format!("{}+{}", layout, variant),
+        None => layout.to_string(),
+    };
+    let sources = format!("[('xkb', '{}')]", source);
+    run("gsettings", &["set", "org.gnome.desktop.input-sources", "sources", &sources])
+}
+
+/// The system default, for the console, the login screen and new sessions
+fn apply_localectl(layout: &str, variant: Option<&str>) -> Result<(), String> {
+    run("localectl", &["set-x11-keymap", layout, "", variant.unwrap_or("")])
+}
+
+/// Switch every part of the session that is running to the layout;
+/// returns the ones that took it
+fn apply(id: &str, system: bool) -> Result<Vec<String>, String> {
+    let (layout, variant) = split_id(id);
+    let mut backends: Vec<(&str, Backend)> = Vec::new();
+    if std::env::var_os("SWAYSOCK").is_some() {
+        backends.push(("sway", apply_sway));
+    } else if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        backends.push(("hyprland", apply_hyprland));
+    } else if std::env::var_os("DISPLAY").is_some() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        backends.push(("x11", apply_x11));
+    }
+    backends.push(("gsettings", apply_gsettings));
+    if system {
+        backends.push(("localectl", apply_localectl));
+    }
+
+    let mut applied = Vec::new();
+    let mut errors = Vec::new();
+    for (name, apply) in backends {
+        match apply(layout, variant) {
+            Ok(()) => applied.push(name.to_string()),
+            Err(e) => errors.push(e),
+        }
+    }
+    if applied.is_empty() {
+        return Err(format!("The session did not take the keyboard layout: {}", errors.join("; ")));
+    }
+    Ok(applied)
+}
+
+/// The signed-in operator, whose layouts are in use
+fn operator(auth: &AuthState) -> Option<String> {
+    auth::require_role(auth, Role::Operator).ok().map(|session| session.username)
+}
+
+fn settings_for(data: &KeyboardData, operator: Option<&str>) -> KeyboardSettings {
+    operator
+        .and_then(|operator| data.operators.get(operator))
+        .unwrap_or(&data.kiosk)
+        .clone()
+}
+
+fn announce(app: &AppHandle, operator: Option<&str>, settings: &KeyboardSettings) {
+    events::publish(app, "keyboard-layout-changed", LayoutChanged { operator, settings });
+}
+
+/// Save the user's settings, switch to their layout and announce it
+fn update(
+    app: &AppHandle,
+    state: &KeyboardState,
+    operator: Option<String>,
+    change: impl FnOnce(&mut KeyboardSettings) -> Result<(), String>,
+) -> Result<KeyboardSettings, KioskError> {
+    let settings = {
+        let mut data = state.0.lock().expect("keyboard lock");
+        let mut settings = settings_for(&data, operator.as_deref());
+        change(&mut settings).map_err(KioskError::invalid)?;
+        let mut updated = data.clone();
+        match &operator {
+            Some(operator) => {
+                updated.operators.insert(operator.clone(), settings.clone());
+            }
+            None => updated.kiosk = settings.clone(),
+        }
+        store::save(app, KEYBOARD_FILE, &updated)?;
+        *data = updated;
+        settings
+    };
+    apply(&settings.active, operator.is_none())?;
+    announce(app, operator.as_deref(), &settings);
+    Ok(settings)
+}
+
+/// The kiosk's own layouts
+pub(crate) fn kiosk_settings(app: &AppHandle) -> KeyboardSettings {
+    app.state::<KeyboardState>().0.lock().expect("keyboard lock").kiosk.clone()
+}
+
+/// Save the kiosk's own layouts and make the active one the system
+/// default; the session switches to it when nobody is signed in
+pub(crate) fn save_kiosk_settings(app: &AppHandle, settings: KeyboardSettings) -> Result<(), String> {
+    {
+        let state = app.state::<KeyboardState>();
+        let mut data = state.0.lock().expect("keyboard lock");
+        let mut updated = data.clone();
+        updated.kiosk = settings.clone();
+        store::save(app, KEYBOARD_FILE, &updated)?;
+        *data = updated;
+    }
+    let (layout, variant) = split_id(&settings.active);
+    // Without the rights to change it, the system keeps its own
+    let _ = apply_localectl(layout, variant);
+    Ok(())
+}
+
+/// Switch to the saved layout at startup and to each operator's own as
+/// they sign in and out
+pub fn start_keyboard(app: AppHandle) {
+    let startup = app.clone();
+    std::thread::spawn(move || {
+        let settings = startup.state::<KeyboardState>().0.lock().expect("keyboard lock").kiosk.clone();
+        if settings != KeyboardSettings::default() {
+            let _ = apply(&settings.active, false);
+        }
+    });
+    events::listen(&app, |app, event| {
+        if event.topic != "operator-changed" {
+            return;
+        }
+        let operator = event.payload.get("username").and_then(|name| name.as_str()).map(str::to_string);
+        let settings = {
+            let data = app.state::<KeyboardState>().0.lock().expect("keyboard lock").clone();
+            settings_for(&data, operator.as_deref())
+        };
+        let app = app.clone();
+        // Switching shells out, so keep it off the publisher's thread
+        std::thread::spawn(move || {
+            if apply(&settings.active, false).is_ok() {
+                announce(&app, operator.as_deref(), &settings);
+            }
+        });
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Every layout and variant the system knows, variants after their layout
+#[tauri::command]
+pub fn list_keyboard_layouts() -> Vec<KeyboardLayout> {
+    keyboard_layouts()
+}
+
+/// The signed-in operator's layouts, or the kiosk's own
+#[tauri::command]
+pub fn get_keyboard_layout(state: State<'_, KeyboardState>, auth: State<'_, AuthState>) -> KeyboardSettings {
+    settings_for(&state.0.lock().expect("keyboard lock"), operator(&auth).as_deref())
+}
+
+/// Switch to a layout and add it to the user's layouts: the signed-in
+/// operator's, or the kiosk's own when nobody is signed in
+#[tauri::command]
+pub fn set_keyboard_layout(
+    app: AppHandle,
+    state: State<'_, KeyboardState>,
+    auth: State<'_, AuthState>,
+    layout: String,
+) -> Result<KeyboardSettings, KioskError> {
+    validate(&layout, &keyboard_layouts()).map_err(KioskError::invalid)?;
+    update(&app, &state, operator(&auth), |settings| {
+        if !settings.layouts.contains(&layout) {
+            if settings.layouts.len() >= MAX_LAYOUTS {
+                return Err(format!("Up to {} keyboard layouts can be kept", MAX_LAYOUTS));
+            }
+            settings.layouts.push(layout.clone());
+        }
+        settings.active = layout;
+        Ok(())
+    })
+}
+
+/// Switch to the user's next layout, as the indicator or Super+Space does
+#[tauri::command]
+pub fn next_keyboard_layout(
+    app: AppHandle,
+    state: State<'_, KeyboardState>,
+    auth: State<'_, AuthState>,
+) -> Result<KeyboardSettings, KioskError> {
+    update(&app, &state, operator(&auth), |settings| {
+        let index = settings.layouts.iter().position(|layout| layout == &settings.active);
+        let next = index.map_or(0, |index| (index + 1) % settings.layouts.len());
+        settings.active = settings.layouts.get(next).cloned().ok_or("No keyboard layouts are kept")?;
+        Ok(())
+    })
+}
+
+/// Drop a layout from the user's layouts; the last one stays
+#[tauri::command]
+pub fn remove_keyboard_layout(
+    app: AppHandle,
+    state: State<'_, KeyboardState>,
+    auth: State<'_, AuthState>,
+    layout: String,
+) -> Result<KeyboardSettings, KioskError> {
+    update(&app, &state, operator(&auth), |settings| {
+        if !settings.layouts.contains(&layout) {
+            return Err(format!("{} is not one of the keyboard layouts", layout));
+        }
+        if settings.layouts.len() == 1 {
+            return Err("The last keyboard layout can't be removed".into());
+        }
+        settings.layouts.retain(|kept| kept != &layout);
+        if settings.active == layout {
+            settings.active = settings.layouts[0].clone();
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,250 @@
+//! Encrypted secrets
+//!
+//! Passwords, PSKs and API tokens are kept in `secrets.json`, each value
+//! sealed with ChaCha20-Poly1305. The key is derived from the machine id and
+//! a random per-install secret, so a copy of the data directory is useless
+//! on another device. This protects settings at rest; it does not stand up
+//! to root on the running kiosk. To move secrets to new hardware, a backup
+//! exports them sealed under a passphrase instead, and a restore seals them
+//! again with the new device's key.
+//!
+//! The secret commands are for admins, and reach only their own `app/`
+//! namespace: the backend's secrets (operator TOTP, directory and mail
+//! passwords, OAuth tokens, certificate keys) cannot be read from a page.
+
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::store;
+
+const SECRETS_FILE: &str = "secrets.json";
+
+const INSTALL_KEY_FILE: &str = "keyring.key";
+
+const MACHINE_ID_FILES: &[&str] = &["/etc/machine-id", "/var/lib/dbus/machine-id"];
+
+const NONCE_SIZE: usize = 12;
+
+/// PBKDF2 rounds for export passphrases, which leave the device with the export
+const EXPORT_ROUNDS: u32 = 600_000;
+
+const EXPORT_SALT_SIZE: usize = 16;
+
+/// Prefix of the keys the secret commands work on
+const APP_PREFIX: &str = "app/";
+
+/// Serializes read-modify-write of the secrets file
+static SECRETS_LOCK: Mutex<()> = Mutex::new(());
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Every secret sealed under a passphrase rather than the device key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretsExport {
+    /// Base64 PBKDF2 salt
+    pub salt: String,
+    /// Sealed values by key name
+    pub secrets: BTreeMap<String, String>,
+}
+
+// ============================================================================
+// Device Key
+// ============================================================================
+
+/// Random secret created on first use, readable by the owner only
+fn install_secret(app: &AppHandle) -> Result<Vec<u8>, String> {
+    let path = store::data_path(app, INSTALL_KEY_FILE)?;
+    if let Ok(secret) = fs::read(&path) {
+        return Ok(secret);
+    }
+
+    let secret = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+    fs::write(&path, &secret).map_err(|e| e.to_string())?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+    Ok(secret)
+}
+
+fn machine_id() -> String {
+    MACHINE_ID_FILES
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn export_cipher(passphrase: &str, salt: &[u8]) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, EXPORT_ROUNDS, &mut key);
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+fn cipher(app: &AppHandle) -> Result<ChaCha20Poly1305, String> {
+    let mut hasher = Sha256::new();
+    hasher.update(b"kiosk-keyring-v1");
+    hasher.update(machine_id().as_bytes());
+    hasher.update(install_secret(app)?);
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize())))
+}
+
+// ============================================================================
+// Sealing
+// ============================================================================
+
+/// Encrypt a value as base64(nonce || ciphertext); the key name is bound in
+/// as associated data so sealed values cannot be swapped between entries
+fn seal(cipher: &ChaCha20Poly1305, name: &str, value: &str) -> Result<String, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let payload = chacha20poly1305::aead::Payload {
+        msg: value.as_bytes(),
+        aad: name.as_bytes(),
+    };
+    let ciphertext = cipher.encrypt(&nonce, payload).map_err(|_| "Encryption failed")?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    Ok(base64::engine::general_purpose::STANDARD.encode(sealed))
+}
+
+fn open(cipher: &ChaCha20Poly1305, name: &str, sealed: &str) -> Result<String, String> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(sealed)
+        .map_err(|e| e.to_string())?;
+    if data.len() < NONCE_SIZE {
+        return Err("Corrupt secret".to_string());
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_SIZE);
+    let payload = chacha20poly1305::aead::Payload {
+        msg: ciphertext,
+        aad: name.as_bytes(),
+    };
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| format!("Secret '{}' cannot be decrypted on this device", name))?;
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Crate API
+// ============================================================================
+
+/// Store a secret, replacing any previous value
+pub(crate) fn put(app: &AppHandle, name: &str, value: &str) -> Result<(), String> {
+    let _guard = SECRETS_LOCK.lock().expect("secrets lock");
+    let cipher = cipher(app)?;
+    let mut secrets: BTreeMap<String, String> = store::load(app, SECRETS_FILE);
+    secrets.insert(name.to_string(), seal(&cipher, name, value)?);
+    store::save(app, SECRETS_FILE, &secrets)
+}
+
+/// Read a secret; `None` when it was never stored
+pub(crate) fn get(app: &AppHandle, name: &str) -> Result<Option<String>, String> {
+    let _guard = SECRETS_LOCK.lock().expect("secrets lock");
+    let secrets: BTreeMap<String, String> = store::load(app, SECRETS_FILE);
+    match secrets.get(name) {
+        Some(sealed) => open(&cipher(app)?, name, sealed).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Remove a secret if present
+pub(crate) fn remove(app: &AppHandle, name: &str) -> Result<(), String> {
+    let _guard = SECRETS_LOCK.lock().expect("secrets lock");
+    let mut secrets: BTreeMap<String, String> = store::load(app, SECRETS_FILE);
+    if secrets.remove(name).is_some() {
+        store::save(app, SECRETS_FILE, &secrets)?;
+    }
+    Ok(())
+}
+
+/// Open every secret with the device key and seal it again under `passphrase`
+pub(crate) fn export(app: &AppHandle, passphrase: &str) -> Result<SecretsExport, String> {
+    let _guard = SECRETS_LOCK.lock().expect("secrets lock");
+    let device = cipher(app)?;
+    let mut salt = [0u8; EXPORT_SALT_SIZE];
+    OsRng.fill_bytes(&mut salt);
+    let export = export_cipher(passphrase, &salt);
+
+    let stored: BTreeMap<String, String> = store::load(app, SECRETS_FILE);
+    let mut secrets = BTreeMap::new();
+    for (name, sealed) in &stored {
+        secrets.insert(name.clone(), seal(&export, name, &open(&device, name, sealed)?)?);
+    }
+    Ok(SecretsExport {
+        salt: base64::engine::general_purpose::STANDARD.encode(salt),
+        secrets,
+    })
+}
+
+/// Open an export with its passphrase; nothing is stored yet, so a wrong
+/// passphrase can be reported before anything changes
+pub(crate) fn open_export(export: &SecretsExport, passphrase: &str) -> Result<BTreeMap<String, String>, String> {
+    let salt = base64::engine::general_purpose::STANDARD
+        .decode(&export.salt)
+        .map_err(|e| e.to_string())?;
+    let cipher = export_cipher(passphrase, &salt);
+    export
+        .secrets
+        .iter()
+        .map(|(name, sealed)| {
+            let value = open(&cipher, name, sealed).map_err(|_| "Wrong passphrase for the exported secrets")?;
+            Ok((name.clone(), value))
+        })
+        .collect()
+}
+
+/// Seal opened secrets with this device's key, replacing any with the same name
+pub(crate) fn import(app: &AppHandle, values: &BTreeMap<String, String>) -> Result<(), String> {
+    let _guard = SECRETS_LOCK.lock().expect("secrets lock");
+    let cipher = cipher(app)?;
+    let mut secrets: BTreeMap<String, String> = store::load(app, SECRETS_FILE);
+    for (name, value) in values {
+        secrets.insert(name.clone(), seal(&cipher, name, value)?);
+    }
+    store::save(app, SECRETS_FILE, &secrets)
+}
+
+/// The keyring entry behind a command's key, kept apart from the backend's own
+fn app_key(key: &str) -> Result<String, KioskError> {
+    if key.trim().is_empty() {
+        return Err(KioskError::invalid("Secret key is required"));
+    }
+    Ok(format!("{}{}", APP_PREFIX, key))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Encrypt and store a secret under `key` (admin)
+#[tauri::command]
+pub fn store_secret(app: AppHandle, auth: State<'_, AuthState>, key: String, value: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    Ok(put(&app, &app_key(&key)?, &value)?)
+}
+
+/// Decrypt the secret stored under `key` (admin)
+#[tauri::command]
+pub fn get_secret(app: AppHandle, auth: State<'_, AuthState>, key: String) -> Result<Option<String>, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    Ok(get(&app, &app_key(&key)?)?)
+}
+
+/// Delete the secret stored under `key` (admin)
+#[tauri::command]
+pub fn delete_secret(app: AppHandle, auth: State<'_, AuthState>, key: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    Ok(remove(&app, &app_key(&key)?)?)
+}
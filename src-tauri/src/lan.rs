@@ -0,0 +1,127 @@
+//! LAN bridge
+//!
+//! Lightweight kiosk-to-kiosk messaging over UDP broadcast on the local
+//! network. Each message carries a topic and a JSON payload; incoming
+//! messages from other kiosks are handed to the owning module and re-emitted
+//! to the frontend as `lan-message`.
+
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, UdpSocket};
+use tauri::{AppHandle, Manager};
+
+use crate::{events, queue, store};
+
+const LAN_FILE: &str = "lan.json";
+
+/// UDP port shared by every kiosk on the segment
+const LAN_PORT: u16 = 47810;
+
+const MAX_DATAGRAM: usize = 65_507;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LanIdentity {
+    kiosk_id: String,
+}
+
+impl Default for LanIdentity {
+    fn default() -> Self {
+        LanIdentity {
+            kiosk_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+}
+
+/// A message between kiosks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanMessage {
+    pub from: String,
+    pub topic: String,
+    pub payload: serde_json::Value,
+}
+
+pub struct LanState {
+    kiosk_id: String,
+    socket: Option<UdpSocket>,
+}
+
+impl LanState {
+    pub fn load(app: &AppHandle) -> Self {
+        let identity: LanIdentity = store::load(app, LAN_FILE);
+        // Persist the generated id so it stays stable across restarts
+        let _ = store::save(app, LAN_FILE, &identity);
+
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, LAN_PORT))
+            .and_then(|socket| socket.set_broadcast(true).map(|_| socket))
+            .ok();
+        LanState {
+            kiosk_id: identity.kiosk_id,
+            socket,
+        }
+    }
+}
+
+// ============================================================================
+// Messaging
+// ============================================================================
+
+/// Broadcast a message to every kiosk on the LAN
+pub fn broadcast<T: Serialize>(app: &AppHandle, topic: &str, payload: &T) -> Result<(), String> {
+    let state = app.state::<LanState>();
+    let socket = state.socket.as_ref().ok_or("LAN bridge is not available")?;
+    let message = LanMessage {
+        from: state.kiosk_id.clone(),
+        topic: topic.to_string(),
+        payload: serde_json::to_value(payload).map_err(|e| e.to_string())?,
+    };
+
+    let data = serde_json::to_vec(&message).map_err(|e| e.to_string())?;
+    if data.len() > MAX_DATAGRAM {
+        return Err("LAN message too large".to_string());
+    }
+    socket
+        .send_to(&data, (Ipv4Addr::BROADCAST, LAN_PORT))
+        .map_err(|e| format!("LAN broadcast failed: {}", e))?;
+    Ok(())
+}
+
+fn dispatch(app: &AppHandle, message: LanMessage) {
+    if message.topic == queue::LAN_TOPIC {
+        queue::apply_remote(app, &message.payload);
+    }
+    events::publish(app, "lan-message", message);
+}
+
+/// Spawn the listener for messages from other kiosks
+pub fn start_lan(app: AppHandle) {
+    let state = app.state::<LanState>();
+    let Some(socket) = state.socket.as_ref().and_then(|socket| socket.try_clone().ok()) else {
+        return;
+    };
+    let kiosk_id = state.kiosk_id.clone();
+
+    std::thread::spawn(move || {
+        let mut buffer = vec![0u8; MAX_DATAGRAM];
+        loop {
+            let Ok((length, _)) = socket.recv_from(&mut buffer) else { continue };
+            let Ok(message) = serde_json::from_slice::<LanMessage>(&buffer[..length]) else { continue };
+            // Broadcasts loop back to the sender
+            if message.from != kiosk_id {
+                dispatch(&app, message);
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// This kiosk's id on the LAN bridge
+#[tauri::command]
+pub fn get_kiosk_id(app: AppHandle) -> String {
+    app.state::<LanState>().kiosk_id.clone()
+}
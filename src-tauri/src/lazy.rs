@@ -0,0 +1,79 @@
+//! Lazily started subsystems
+//!
+//! Heavier subsystems (mail, payment and cash hardware, the ticket printer,
+//! speech recognition, camera streams, screen recording) are registered as
+//! an empty `Lazy` cell at startup and only load their state and start their
+//! background threads the first time a command needs them. Each can also be
+//! switched off at runtime with a feature flag of the same name (threads
+//! already started keep running until restart), and the optional ones
+//! compiled out entirely with the Cargo feature guarding their module.
+//!
+//! The AV self-test (`avcheck`) is not one of them: it keeps no state and
+//! starts nothing until `test_av_devices` runs it as a job.
+
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+use crate::error::{ErrorKind, KioskError};
+use crate::{config, events};
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+pub(crate) trait Subsystem: Send + Sync + Sized + 'static {
+    /// Feature flag that switches the subsystem off
+    const NAME: &'static str;
+
+    fn load(app: &AppHandle) -> Self;
+
+    /// Start background work once the state exists
+    fn start(_app: AppHandle) {}
+}
+
+/// Managed in place of a subsystem's state until first use
+pub struct Lazy<T>(OnceLock<T>);
+
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Lazy(OnceLock::new())
+    }
+}
+
+// ============================================================================
+// Access
+// ============================================================================
+
+/// Fail when a subsystem has been switched off at runtime
+pub(crate) fn require(app: &AppHandle, name: &str) -> Result<(), KioskError> {
+    if config::feature_enabled(app, name) {
+        return Ok(());
+    }
+    Err(KioskError::new(ErrorKind::Denied, format!("The {} feature is disabled on this kiosk", name)))
+}
+
+/// A subsystem's state if it has been loaded, whatever its flag says now;
+/// for the subsystem's own background threads
+pub(crate) fn loaded<T: Subsystem>(app: &AppHandle) -> Option<&T> {
+    app.state::<Lazy<T>>().inner().0.get()
+}
+
+/// A subsystem's state, loading and starting it on first use
+pub(crate) fn get<T: Subsystem>(app: &AppHandle) -> Result<&T, KioskError> {
+    require(app, T::NAME)?;
+    let cell = &app.state::<Lazy<T>>().inner().0;
+    if let Some(state) = cell.get() {
+        return Ok(state);
+    }
+
+    let mut loaded = false;
+    let state = cell.get_or_init(|| {
+        loaded = true;
+        T::load(app)
+    });
+    if loaded {
+        T::start(app.clone());
+        events::publish(app, "subsystem-started", T::NAME);
+    }
+    Ok(state)
+}
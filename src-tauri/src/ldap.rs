@@ -0,0 +1,372 @@
+//! Minimal LDAPv3 client
+//!
+//! Just enough of the protocol for operator logins: simple bind, StartTLS,
+//! and an equality search for the user's entry. Messages are BER-encoded by
+//! hand; TLS uses the shared configuration so custom CAs apply.
+
+use rustls::pki_types::ServerName;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use crate::http;
+
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(15);
+
+const STARTTLS_OID: &str = "1.3.6.1.4.1.1466.20037";
+
+// BER tags
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const ENUMERATED: u8 = 0x0A;
+const BOOLEAN: u8 = 0x01;
+const SEQUENCE: u8 = 0x30;
+
+// LDAP protocol operations
+const BIND_REQUEST: u8 = 0x60;
+const BIND_RESPONSE: u8 = 0x61;
+const UNBIND_REQUEST: u8 = 0x42;
+const SEARCH_REQUEST: u8 = 0x63;
+const SEARCH_ENTRY: u8 = 0x64;
+const SEARCH_DONE: u8 = 0x65;
+const EXTENDED_REQUEST: u8 = 0x77;
+const EXTENDED_RESPONSE: u8 = 0x78;
+
+const RESULT_SUCCESS: u32 = 0;
+const RESULT_INVALID_CREDENTIALS: u32 = 49;
+
+/// Transport encryption for the directory connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LdapSecurity {
+    /// LDAPS, usually port 636
+    Tls,
+    /// Plain connection upgraded with StartTLS, usually port 389
+    StartTls,
+    /// Unencrypted; test directories only
+    None,
+}
+
+/// Why a directory operation failed
+#[derive(Debug)]
+pub enum LdapError {
+    /// The server could not be reached or the connection broke
+    Unreachable(String),
+    /// The server answered and refused the request
+    Rejected(String),
+}
+
+impl std::fmt::Display for LdapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LdapError::Unreachable(message) => write!(f, "Directory unreachable: {}", message),
+            LdapError::Rejected(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+fn unreachable(e: impl std::fmt::Display) -> LdapError {
+    LdapError::Unreachable(e.to_string())
+}
+
+/// A directory entry with its requested attributes
+#[derive(Debug, Clone, Default)]
+pub struct LdapEntry {
+    pub dn: String,
+    pub attributes: Vec<(String, Vec<String>)>,
+}
+
+impl LdapEntry {
+    pub fn values(&self, name: &str) -> Vec<String> {
+        self.attributes
+            .iter()
+            .filter(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+            .flat_map(|(_, values)| values.iter().cloned())
+            .collect()
+    }
+
+    pub fn first(&self, name: &str) -> Option<String> {
+        self.values(name).into_iter().next()
+    }
+}
+
+trait ReadWrite: Read + Write + Send {}
+impl<T: Read + Write + Send> ReadWrite for T {}
+
+// ============================================================================
+// BER Encoding
+// ============================================================================
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend(bytes);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn integer(value: u32) -> Vec<u8> {
+    let mut bytes: Vec<u8> = value.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+    // Keep the value positive when the high bit is set
+    if bytes.first().map_or(true, |b| b & 0x80 != 0) {
+        bytes.insert(0, 0);
+    }
+    tlv(INTEGER, &bytes)
+}
+
+fn octets(value: &str) -> Vec<u8> {
+    tlv(OCTET_STRING, value.as_bytes())
+}
+
+fn message(id: u32, op: Vec<u8>) -> Vec<u8> {
+    tlv(SEQUENCE, &[integer(id), op].concat())
+}
+
+// ============================================================================
+// BER Decoding
+// ============================================================================
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn next(&mut self) -> Result<(u8, &'a [u8]), LdapError> {
+        let bad = || LdapError::Unreachable("Malformed LDAP response".to_string());
+        let (&tag, rest) = self.0.split_first().ok_or_else(bad)?;
+        let (&first, mut rest) = rest.split_first().ok_or_else(bad)?;
+        let len = if first & 0x80 == 0 {
+            first as usize
+        } else {
+            let count = (first & 0x7F) as usize;
+            if count > 4 || rest.len() < count {
+                return Err(bad());
+            }
+            let len = rest[..count].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            rest = &rest[count..];
+            len
+        };
+        if rest.len() < len {
+            return Err(bad());
+        }
+        self.0 = &rest[len..];
+        Ok((tag, &rest[..len]))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn decode_uint(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32)
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+/// Read one complete LDAPMessage from the stream
+fn read_message(stream: &mut dyn ReadWrite) -> Result<Vec<u8>, LdapError> {
+    let mut header = [0u8; 2];
+    stream.read_exact(&mut header).map_err(unreachable)?;
+    let mut raw = header.to_vec();
+    let len = if header[1] & 0x80 == 0 {
+        header[1] as usize
+    } else {
+        let count = (header[1] & 0x7F) as usize;
+        if count > 4 {
+            return Err(unreachable("Oversized LDAP message"));
+        }
+        let mut bytes = vec![0u8; count];
+        stream.read_exact(&mut bytes).map_err(unreachable)?;
+        raw.extend(&bytes);
+        bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize)
+    };
+    let mut content = vec![0u8; len];
+    stream.read_exact(&mut content).map_err(unreachable)?;
+    raw.extend(content);
+    Ok(raw)
+}
+
+/// Split an LDAPMessage into (message id, op tag, op content)
+fn parse_message(raw: &[u8]) -> Result<(u32, u8, Vec<u8>), LdapError> {
+    let (_, body) = Reader(raw).next()?;
+    let mut reader = Reader(body);
+    let (_, id) = reader.next()?;
+    let (tag, op) = reader.next()?;
+    Ok((decode_uint(id), tag, op.to_vec()))
+}
+
+/// Result code and diagnostic message of an LDAPResult
+fn parse_result(op: &[u8]) -> Result<(u32, String), LdapError> {
+    let mut reader = Reader(op);
+    let (_, code) = reader.next()?;
+    let _matched_dn = reader.next()?;
+    let (_, diagnostic) = reader.next()?;
+    Ok((decode_uint(code), text(diagnostic)))
+}
+
+fn parse_entry(op: &[u8]) -> Result<LdapEntry, LdapError> {
+    let mut reader = Reader(op);
+    let (_, dn) = reader.next()?;
+    let (_, attributes) = reader.next()?;
+
+    let mut entry = LdapEntry {
+        dn: text(dn),
+        attributes: Vec::new(),
+    };
+    let mut attributes = Reader(attributes);
+    while !attributes.is_empty() {
+        let (_, attribute) = attributes.next()?;
+        let mut attribute = Reader(attribute);
+        let (_, name) = attribute.next()?;
+        let (_, values) = attribute.next()?;
+        let mut values = Reader(values);
+        let mut list = Vec::new();
+        while !values.is_empty() {
+            list.push(text(values.next()?.1));
+        }
+        entry.attributes.push((text(name), list));
+    }
+    Ok(entry)
+}
+
+// ============================================================================
+// Connection
+// ============================================================================
+
+pub struct LdapConnection {
+    stream: Box<dyn ReadWrite>,
+    next_id: u32,
+}
+
+fn tls_wrap(host: &str, tcp: TcpStream) -> Result<Box<dyn ReadWrite>, LdapError> {
+    let server_name = ServerName::try_from(host.to_string()).map_err(unreachable)?;
+    let connection = rustls::ClientConnection::new(http::tls_config(), server_name).map_err(unreachable)?;
+    Ok(Box::new(rustls::StreamOwned::new(connection, tcp)))
+}
+
+impl LdapConnection {
+    pub fn connect(host: &str, port: u16, security: LdapSecurity) -> Result<Self, LdapError> {
+        let mut tcp = TcpStream::connect((host, port)).map_err(unreachable)?;
+        tcp.set_read_timeout(Some(SOCKET_TIMEOUT)).map_err(unreachable)?;
+        tcp.set_write_timeout(Some(SOCKET_TIMEOUT)).map_err(unreachable)?;
+
+        let mut next_id = 1;
+        let stream: Box<dyn ReadWrite> = match security {
+            LdapSecurity::Tls => tls_wrap(host, tcp)?,
+            LdapSecurity::None => Box::new(tcp),
+            LdapSecurity::StartTls => {
+                let op = tlv(EXTENDED_REQUEST, &tlv(0x80, STARTTLS_OID.as_bytes()));
+                tcp.write_all(&message(next_id, op)).map_err(unreachable)?;
+                next_id += 1;
+                let (_, tag, content) = parse_message(&read_message(&mut tcp)?)?;
+                if tag != EXTENDED_RESPONSE {
+                    return Err(unreachable("Unexpected StartTLS response"));
+                }
+                let (code, diagnostic) = parse_result(&content)?;
+                if code != RESULT_SUCCESS {
+                    return Err(LdapError::Rejected(format!("StartTLS refused: {}", diagnostic)));
+                }
+                tls_wrap(host, tcp)?
+            }
+        };
+        Ok(LdapConnection { stream, next_id })
+    }
+
+    fn send(&mut self, op: Vec<u8>) -> Result<u32, LdapError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.stream.write_all(&message(id, op)).map_err(unreachable)?;
+        self.stream.flush().map_err(unreachable)?;
+        Ok(id)
+    }
+
+    /// Send a request and return the (tag, content) of its single response
+    fn request(&mut self, op: Vec<u8>) -> Result<(u8, Vec<u8>), LdapError> {
+        let id = self.send(op)?;
+        loop {
+            let (response_id, tag, content) = parse_message(&read_message(self.stream.as_mut())?)?;
+            // Unsolicited notifications use message id 0
+            if response_id == id {
+                return Ok((tag, content));
+            }
+        }
+    }
+
+    /// Simple bind; an empty password is refused to avoid unauthenticated binds
+    pub fn bind(&mut self, dn: &str, password: &str) -> Result<(), LdapError> {
+        if password.is_empty() {
+            return Err(LdapError::Rejected("Password is required".to_string()));
+        }
+        let op = tlv(BIND_REQUEST, &[integer(3), octets(dn), tlv(0x80, password.as_bytes())].concat());
+        let (tag, content) = self.request(op)?;
+        if tag != BIND_RESPONSE {
+            return Err(unreachable("Unexpected bind response"));
+        }
+        match parse_result(&content)? {
+            (RESULT_SUCCESS, _) => Ok(()),
+            (RESULT_INVALID_CREDENTIALS, _) => Err(LdapError::Rejected("Invalid username or password".to_string())),
+            (code, diagnostic) => Err(LdapError::Rejected(format!("Bind failed ({}): {}", code, diagnostic))),
+        }
+    }
+
+    /// Subtree search for entries where `attribute` equals `value`
+    pub fn search_eq(
+        &mut self,
+        base: &str,
+        attribute: &str,
+        value: &str,
+        attributes: &[&str],
+    ) -> Result<Vec<LdapEntry>, LdapError> {
+        let filter = tlv(0xA3, &[octets(attribute), octets(value)].concat());
+        let requested: Vec<u8> = attributes.iter().flat_map(|name| octets(name)).collect();
+        let op = tlv(
+            SEARCH_REQUEST,
+            &[
+                octets(base),
+                tlv(ENUMERATED, &[2]),
+                tlv(ENUMERATED, &[0]),
+                integer(2),
+                integer(10),
+                tlv(BOOLEAN, &[0]),
+                filter,
+                tlv(SEQUENCE, &requested),
+            ]
+            .concat(),
+        );
+
+        let id = self.send(op)?;
+        let mut entries = Vec::new();
+        loop {
+            let (response_id, tag, content) = parse_message(&read_message(self.stream.as_mut())?)?;
+            if response_id != id {
+                continue;
+            }
+            match tag {
+                SEARCH_ENTRY => entries.push(parse_entry(&content)?),
+                SEARCH_DONE => {
+                    let (code, diagnostic) = parse_result(&content)?;
+                    // Size limit exceeded (4) still returns the entries found
+                    if code != RESULT_SUCCESS && code != 4 {
+                        return Err(LdapError::Rejected(format!("Search failed ({}): {}", code, diagnostic)));
+                    }
+                    return Ok(entries);
+                }
+                // Referrals are not followed
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Drop for LdapConnection {
+    fn drop(&mut self) {
+        let _ = self.send(tlv(UNBIND_REQUEST, &[]));
+    }
+}
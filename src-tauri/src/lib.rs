@@ -5,10 +5,120 @@
 
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use sysinfo::System;
-use tauri::State;
+use sysinfo::{CpuRefreshKind, Disks, MemoryRefreshKind, Networks, RefreshKind, System};
+use tauri::{Manager, State};
 use chrono::{Local, Datelike, Timelike};
 
+mod accessibility;
+mod approvals;
+mod attract;
+mod auth;
+mod avcheck;
+mod backup;
+mod badges;
+mod bandwidth;
+mod benchmark;
+mod bitmap;
+mod boot;
+mod brightness;
+mod bundles;
+mod business;
+mod calculator;
+mod calendar;
+mod cec;
+mod certs;
+mod charmap;
+mod checksum;
+mod cleanup;
+mod control;
+mod demo;
+mod downloads;
+mod fetch;
+mod footfall;
+mod formatting;
+mod games;
+mod hours;
+mod i18n;
+mod icons;
+mod keyboard;
+mod location;
+mod lock;
+mod magnifier;
+mod metering;
+mod microphone;
+mod minesweeper;
+mod modem;
+mod monotonic;
+mod nightlight;
+mod photoframe;
+mod pointer;
+mod proximity;
+mod regional;
+mod reports;
+mod sip;
+mod solitaire;
+mod streams;
+mod structured;
+mod tabular;
+mod tamper;
+mod templates;
+mod timers;
+mod totp;
+mod wol;
+pub mod cli;
+mod config;
+mod contacts;
+mod devices;
+mod error;
+mod events;
+mod feeds;
+mod fonts;
+mod help;
+mod http;
+mod jobs;
+mod keyring;
+mod lan;
+mod lazy;
+mod ldap;
+mod macros;
+mod middleware;
+mod mock;
+mod oauth;
+mod overlay;
+mod queue;
+mod quota;
+mod recents;
+mod recording;
+mod reset;
+mod rooms;
+mod rules;
+mod services;
+mod session;
+mod spellcheck;
+mod stats;
+mod store;
+mod supervisor;
+mod support;
+mod sysreport;
+mod tickers;
+mod vfs;
+mod wayfinding;
+mod weather;
+mod zip;
+
+#[cfg(feature = "mail")]
+mod mail;
+#[cfg(feature = "payments")]
+mod cash;
+#[cfg(feature = "payments")]
+mod payments;
+#[cfg(feature = "printing")]
+mod printing;
+#[cfg(feature = "speech")]
+mod speech;
+#[cfg(feature = "printing")]
+mod tickets;
+
 // ============================================================================
 // Data Structures
 // ============================================================================
@@ -40,6 +150,8 @@ pub struct HardwareProfile {
 pub struct DateTimeInfo {
     pub time_12h: String,
     pub time_24h: String,
+    /// Time in the kiosk language's usual format
+    pub time: String,
     pub date_short: String,
     pub date_long: String,
     pub day_of_week: String,
@@ -56,8 +168,39 @@ pub struct DriveInfo {
     pub is_removable: bool,
 }
 
-/// Shared system state to avoid repeated allocations.
-pub struct SharedSystem(Mutex<System>);
+/// Traffic counters for one network interface
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkStats {
+    pub name: String,
+    /// Bytes since the previous call
+    pub received: u64,
+    pub transmitted: u64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+}
+
+/// Shared sysinfo state, created once and refreshed incrementally.
+///
+/// Each command refreshes only what it reads; a full refresh walks every
+/// process and stalls for hundreds of milliseconds on a Pi Zero 2.
+pub struct SharedSystem {
+    pub(crate) system: Mutex<System>,
+    pub(crate) disks: Mutex<Disks>,
+    pub(crate) networks: Mutex<Networks>,
+}
+
+impl SharedSystem {
+    fn new() -> Self {
+        let refresh = RefreshKind::new()
+            .with_cpu(CpuRefreshKind::new().with_cpu_usage())
+            .with_memory(MemoryRefreshKind::new().with_ram());
+        SharedSystem {
+            system: Mutex::new(System::new_with_specifics(refresh)),
+            disks: Mutex::new(Disks::new_with_refreshed_list()),
+            networks: Mutex::new(Networks::new_with_refreshed_list()),
+        }
+    }
+}
 
 // ============================================================================
 // Tauri Commands
@@ -66,8 +209,14 @@ pub struct SharedSystem(Mutex<System>);
 /// Get current system statistics (CPU, memory usage)
 #[tauri::command]
 fn get_system_stats(state: State<'_, SharedSystem>) -> SystemStats {
-    let mut sys = state.0.lock().expect("system state lock");
-    sys.refresh_all();
+    let mut sys = state.system.lock().expect("system state lock");
+    system_stats(&mut sys)
+}
+
+/// Refresh and read CPU and memory usage
+pub(crate) fn system_stats(sys: &mut System) -> SystemStats {
+    sys.refresh_cpu_usage();
+    sys.refresh_memory();
 
     SystemStats {
         cpu_usage: sys.global_cpu_usage(),
@@ -87,7 +236,7 @@ fn get_hardware_profile(state: State<'_, SharedSystem>) -> HardwareProfile {
         .trim_matches('\0')
         .to_string();
 
-    let mut sys = state.0.lock().expect("system state lock");
+    let mut sys = state.system.lock().expect("system state lock");
     sys.refresh_memory();
 
     HardwareProfile {
@@ -107,9 +256,10 @@ fn get_datetime() -> DateTimeInfo {
     DateTimeInfo {
         time_12h: now.format("%I:%M %p").to_string(),
         time_24h: now.format("%H:%M").to_string(),
-        date_short: now.format("%m/%d/%Y").to_string(),
-        date_long: now.format("%B %d, %Y").to_string(),
-        day_of_week: now.format("%A").to_string(),
+        time: i18n::format_date(&now, i18n::DateStyle::Time),
+        date_short: i18n::format_date(&now, i18n::DateStyle::Short),
+        date_long: i18n::format_date(&now, i18n::DateStyle::Long),
+        day_of_week: i18n::format_time(&now, "%A"),
         timestamp: now.timestamp(),
     }
 }
@@ -117,11 +267,11 @@ fn get_datetime() -> DateTimeInfo {
 /// List available drives/disks
 #[tauri::command]
 fn list_drives(state: State<'_, SharedSystem>) -> Vec<DriveInfo> {
-    let mut sys = state.0.lock().expect("system state lock");
-    sys.refresh_disks_list();
-    sys.refresh_disks();
+    let mut disks = state.disks.lock().expect("disk state lock");
+    // Re-read the mount table so drives plugged in since the last call appear
+    disks.refresh_list();
 
-    sys.disks()
+    disks
         .iter()
         .map(|disk| DriveInfo {
             name: disk.name().to_string_lossy().to_string(),
@@ -133,6 +283,25 @@ fn list_drives(state: State<'_, SharedSystem>) -> Vec<DriveInfo> {
         .collect()
 }
 
+/// Get per-interface network traffic
+#[tauri::command]
+fn get_network_stats(state: State<'_, SharedSystem>) -> Vec<NetworkStats> {
+    let mut networks = state.networks.lock().expect("network state lock");
+    // Also picks up interfaces that came up since the last call
+    networks.refresh_list();
+
+    networks
+        .iter()
+        .map(|(name, data)| NetworkStats {
+            name: name.clone(),
+            received: data.received(),
+            transmitted: data.transmitted(),
+            total_received: data.total_received(),
+            total_transmitted: data.total_transmitted(),
+        })
+        .collect()
+}
+
 /// Greet command for testing connectivity
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -165,17 +334,592 @@ fn greet(name: &str) -> String {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    boot::mark("start");
     tauri::Builder::default()
-        .manage(SharedSystem(Mutex::new(System::new_all())))
+        .manage(SharedSystem::new())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![
+        .register_uri_scheme_protocol("help", help::protocol)
+        .register_uri_scheme_protocol("stream", streams::protocol)
+        .register_uri_scheme_protocol("photo", photoframe::protocol)
+        .on_page_load(|_, payload| {
+            if let tauri::webview::PageLoadEvent::Finished = payload.event() {
+                boot::mark_once("page-load");
+            }
+        })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                jobs::cancel_window_jobs(window.app_handle(), window.label());
+                middleware::forget_window(window.label());
+            }
+        })
+        .setup(|app| {
+            let handle = app.handle();
+            // Plugins have been initialized by the time setup runs
+            boot::mark("plugins");
+            // The event bus exists before anything can publish
+            app.manage(events::EventBus::default());
+            app.manage(jobs::JobRegistry::default());
+            // Flags decide how the modules below start
+            app.manage(config::FlagsState::load(handle));
+            boot::mark("core");
+            // Certificates and the proxy first so background network threads see them
+            app.manage(certs::CertState::load(handle));
+            app.manage(fetch::FetchState::load(handle));
+            app.manage(recents::RecentsState::load(handle));
+            app.manage(contacts::ContactsState::load(handle));
+            app.manage(calendar::CalendarState::load(handle));
+            calendar::start_reminders(handle.clone());
+            app.manage(rooms::RoomState::load(handle));
+            rooms::start_sync(handle.clone());
+            app.manage(feeds::FeedsState::load(handle));
+            app.manage(weather::WeatherState::load(handle));
+            weather::start_weather(handle.clone());
+            app.manage(tickers::TickersState::load(handle));
+            tickers::start_tickers(handle.clone());
+            app.manage(help::HelpState::load(handle));
+            app.manage(queue::QueueState::load(handle));
+            app.manage(lan::LanState::load(handle));
+            lan::start_lan(handle.clone());
+            app.manage(wayfinding::MapState::load(handle));
+            app.manage(attract::AttractState::load(handle));
+            attract::start_attract(handle.clone());
+            app.manage(session::SessionState::load(handle));
+            session::start_session_reset(handle.clone());
+            app.manage(cleanup::CleanupState::load(handle));
+            cleanup::start_cleanup_schedule(handle.clone());
+            app.manage(bundles::BundleState::load(handle));
+            app.manage(auth::AuthState::load(handle));
+            app.manage(oauth::OAuthState::load(handle));
+            app.manage(rules::RulesState::load(handle));
+            rules::start_rules(handle.clone());
+            app.manage(stats::StatsHistory::load(handle));
+            stats::start_stats(handle.clone());
+            app.manage(supervisor::Supervisor::load(handle));
+            app.manage(services::ServicesState::load(handle));
+            app.manage(vfs::ScopeState::load(handle));
+            app.manage(quota::QuotaState::load(handle));
+            quota::start_quota(handle.clone());
+            app.manage(lazy::Lazy::<recording::RecordingState>::default());
+            app.manage(macros::MacroState::default());
+            app.manage(control::ControlState::default());
+            control::start_control(handle.clone());
+            app.manage(wol::WolState::load(handle));
+            wol::start_wol(handle.clone());
+            app.manage(cec::CecState::default());
+            cec::start_cec(handle.clone());
+            app.manage(hours::HoursState::load(handle));
+            hours::start_hours(handle.clone());
+            app.manage(brightness::BrightnessState::load(handle));
+            brightness::start_brightness(handle.clone());
+            app.manage(proximity::ProximityState::load(handle));
+            proximity::start_proximity(handle.clone());
+            app.manage(footfall::FootfallState::load(handle));
+            footfall::start_footfall(handle.clone());
+            i18n::init(handle);
+            app.manage(business::BusinessState::load(handle));
+            business::start_business(handle.clone());
+            app.manage(timers::TimersState::load(handle));
+            timers::start_timers(handle.clone());
+            app.manage(monotonic::IntervalsState::default());
+            monotonic::start_clock_watch(handle.clone());
+            app.manage(lock::LockState::load(handle));
+            lock::start_auto_lock(handle.clone());
+            app.manage(approvals::ApprovalsState::load(handle));
+            app.manage(totp::TotpState::load(handle));
+            app.manage(tamper::TamperState::load(handle));
+            tamper::start_tamper(handle.clone());
+            app.manage(location::LocationState::load(handle));
+            location::start_location(handle.clone());
+            app.manage(modem::ModemState::load(handle));
+            modem::start_modem(handle.clone());
+            app.manage(metering::MeteringState::load(handle));
+            metering::start_metering(handle.clone());
+            app.manage(downloads::DownloadsState::load(handle));
+            downloads::start_downloads(handle.clone());
+            app.manage(bandwidth::BandwidthState::load(handle));
+            bandwidth::start_bandwidth(handle.clone());
+            app.manage(demo::DemoState::load(handle));
+            demo::start_demo(handle.clone());
+            app.manage(templates::TemplatesState::load(handle));
+            templates::start_templates(handle.clone());
+            app.manage(reports::ReportsState::load(handle));
+            reports::start_reports(handle.clone());
+            app.manage(structured::ConfigFilesState::load(handle));
+            app.manage(games::GamesState::load(handle));
+            app.manage(minesweeper::MinesweeperState::default());
+            app.manage(solitaire::SolitaireState::default());
+            app.manage(icons::IconState::default());
+            app.manage(pointer::PointerState::load(handle));
+            pointer::restore_pointer(handle.clone());
+            app.manage(keyboard::KeyboardState::load(handle));
+            keyboard::start_keyboard(handle.clone());
+            app.manage(accessibility::AccessibilityState::load(handle));
+            accessibility::start_accessibility(handle.clone());
+            app.manage(magnifier::MagnifierState::load(handle));
+            app.manage(nightlight::ColorState::load(handle));
+            nightlight::start_nightlight(handle.clone());
+            app.manage(microphone::MicrophoneState::default());
+            app.manage(sip::SipState::load(handle));
+            sip::start_sip(handle.clone());
+            app.manage(lazy::Lazy::<streams::StreamsState>::default());
+            streams::start_on_launch(handle);
+            app.manage(photoframe::PhotoFrameState::load(handle));
+            photoframe::start_on_launch(handle);
+            // Optional subsystems load on first use
+            #[cfg(feature = "mail")]
+            app.manage(lazy::Lazy::<mail::MailState>::default());
+            #[cfg(feature = "payments")]
+            app.manage(lazy::Lazy::<payments::PaymentState>::default());
+            #[cfg(feature = "payments")]
+            app.manage(lazy::Lazy::<cash::CashState>::default());
+            #[cfg(feature = "printing")]
+            app.manage(lazy::Lazy::<tickets::TicketState>::default());
+            #[cfg(feature = "speech")]
+            app.manage(lazy::Lazy::<speech::SpeechState>::default());
+            boot::mark("modules");
+            Ok(())
+        })
+        .invoke_handler(middleware::wrap(tauri::generate_handler![
             greet,
             get_system_stats,
             get_hardware_profile,
             get_datetime,
             list_drives,
-        ])
+            get_network_stats,
+            recents::add_recent,
+            recents::get_recents,
+            recents::clear_recents,
+            fonts::list_fonts,
+            fonts::install_font,
+            fonts::remove_font,
+            charmap::get_unicode_blocks,
+            charmap::get_glyphs,
+            spellcheck::spellcheck,
+            spellcheck::suggest,
+            spellcheck::list_dictionaries,
+            spellcheck::install_dictionary,
+            calculator::evaluate_expression,
+            contacts::list_contacts,
+            contacts::get_contact,
+            contacts::create_contact,
+            contacts::update_contact,
+            contacts::delete_contact,
+            contacts::search_contacts,
+            contacts::import_vcard,
+            contacts::export_vcard,
+            #[cfg(feature = "mail")]
+            mail::list_email_accounts,
+            #[cfg(feature = "mail")]
+            mail::save_email_account,
+            #[cfg(feature = "mail")]
+            mail::delete_email_account,
+            #[cfg(feature = "mail")]
+            mail::sync_email,
+            #[cfg(feature = "mail")]
+            mail::list_email_folders,
+            #[cfg(feature = "mail")]
+            mail::list_messages,
+            #[cfg(feature = "mail")]
+            mail::read_message,
+            #[cfg(feature = "mail")]
+            mail::send_email,
+            calendar::list_events,
+            calendar::get_events,
+            calendar::create_event,
+            calendar::update_event,
+            calendar::delete_event,
+            calendar::import_ics,
+            calendar::export_ics,
+            rooms::get_room_config,
+            rooms::set_room_config,
+            rooms::sync_room_calendar,
+            rooms::get_room_availability,
+            rooms::book_slot,
+            feeds::list_feeds,
+            feeds::add_feed,
+            feeds::remove_feed,
+            feeds::refresh_feeds,
+            feeds::get_feed_items,
+            weather::get_weather,
+            weather::refresh_weather,
+            weather::get_weather_config,
+            weather::set_weather_config,
+            tickers::get_quotes,
+            tickers::get_ticker_config,
+            tickers::set_ticker_config,
+            help::list_help_topics,
+            help::search_help,
+            #[cfg(feature = "printing")]
+            printing::list_printers,
+            #[cfg(feature = "printing")]
+            printing::print_text,
+            badges::preview_badge,
+            #[cfg(feature = "printing")]
+            badges::print_badge,
+            #[cfg(feature = "payments")]
+            payments::get_payment_config,
+            #[cfg(feature = "payments")]
+            payments::set_payment_config,
+            #[cfg(feature = "payments")]
+            payments::start_payment,
+            #[cfg(feature = "payments")]
+            payments::cancel_payment,
+            #[cfg(feature = "payments")]
+            payments::get_payment_status,
+            #[cfg(feature = "payments")]
+            cash::get_cash_config,
+            #[cfg(feature = "payments")]
+            cash::set_cash_config,
+            #[cfg(feature = "payments")]
+            cash::set_cash_accepting,
+            #[cfg(feature = "payments")]
+            cash::get_inserted_amount,
+            #[cfg(feature = "payments")]
+            cash::set_sale_total,
+            #[cfg(feature = "payments")]
+            cash::reset_inserted_amount,
+            #[cfg(feature = "payments")]
+            cash::accept_escrow,
+            #[cfg(feature = "payments")]
+            cash::return_escrow,
+            #[cfg(feature = "payments")]
+            cash::dispense_change,
+            #[cfg(feature = "printing")]
+            tickets::print_ticket,
+            #[cfg(feature = "printing")]
+            tickets::list_ticket_jobs,
+            #[cfg(feature = "printing")]
+            tickets::retry_ticket_job,
+            #[cfg(feature = "printing")]
+            tickets::cancel_ticket_job,
+            #[cfg(feature = "printing")]
+            tickets::get_ticket_printer_status,
+            #[cfg(feature = "printing")]
+            tickets::get_ticket_printer_config,
+            #[cfg(feature = "printing")]
+            tickets::set_ticket_printer_config,
+            queue::take_number,
+            queue::call_next,
+            queue::get_queue_state,
+            queue::reset_queue,
+            lan::get_kiosk_id,
+            wayfinding::load_map_bundle,
+            wayfinding::get_map_info,
+            wayfinding::find_route,
+            attract::get_attract_config,
+            attract::configure_attract_loop,
+            attract::report_activity,
+            attract::get_attract_state,
+            session::reset_session,
+            session::get_session_config,
+            session::set_session_config,
+            cleanup::scan_cleanup,
+            cleanup::run_cleanup,
+            cleanup::get_cleanup_schedule,
+            cleanup::set_cleanup_schedule,
+            checksum::hash_file,
+            checksum::verify_manifest,
+            bundles::verify_bundle,
+            bundles::get_bundle_config,
+            bundles::set_bundle_config,
+            keyring::store_secret,
+            keyring::get_secret,
+            keyring::delete_secret,
+            certs::import_certificate,
+            certs::list_certificates,
+            certs::remove_certificate,
+            certs::set_client_certificate,
+            auth::login,
+            auth::logout,
+            auth::get_current_operator,
+            auth::list_operator_accounts,
+            auth::save_operator_account,
+            auth::delete_operator_account,
+            auth::get_ldap_config,
+            auth::set_ldap_config,
+            oauth::list_oauth_providers,
+            oauth::save_oauth_provider,
+            oauth::delete_oauth_provider,
+            oauth::start_device_auth,
+            oauth::cancel_device_auth,
+            oauth::get_access_token,
+            oauth::sign_out_provider,
+            rules::list_rules,
+            rules::save_rule,
+            rules::delete_rule,
+            rules::test_rule,
+            rules::get_rule_scripts,
+            rules::set_rule_scripts,
+            events::subscribe_events,
+            events::unsubscribe_events,
+            events::dump_event_history,
+            middleware::get_command_metrics,
+            middleware::reset_command_metrics,
+            jobs::get_job_status,
+            jobs::cancel_job,
+            jobs::list_jobs,
+            benchmark::run_benchmark,
+            benchmark::report_frame_rate,
+            benchmark::list_benchmark_results,
+            stats::get_stats_history,
+            supervisor::launch_app,
+            supervisor::list_apps,
+            supervisor::stop_app,
+            supervisor::restart_app,
+            supervisor::set_app_auto_restart,
+            supervisor::remove_app,
+            supervisor::get_app_log,
+            supervisor::get_app_programs,
+            supervisor::set_app_programs,
+            config::get_runtime_config,
+            config::set_feature_flag,
+            support::generate_support_bundle,
+            sysreport::get_system_report,
+            devices::get_device_tree,
+            services::list_services,
+            services::get_service_status,
+            services::start_service,
+            services::stop_service,
+            services::restart_service,
+            services::enable_service,
+            services::disable_service,
+            services::get_service_policy,
+            services::set_service_policy,
+            boot::report_first_paint,
+            boot::get_boot_timeline,
+            boot::set_boot_budget,
+            vfs::list_virtual_roots,
+            vfs::read_directory,
+            vfs::read_text_file,
+            vfs::write_text_file,
+            vfs::create_directory,
+            vfs::copy_path,
+            vfs::rename_path,
+            vfs::delete_path,
+            vfs::get_fs_scope,
+            vfs::set_fs_scope,
+            quota::get_quota_status,
+            quota::get_quota_config,
+            quota::set_quota,
+            overlay::get_overlay_status,
+            overlay::enable_overlay_fs,
+            overlay::disable_overlay_fs,
+            overlay::commit_changes,
+            reset::request_factory_reset,
+            reset::factory_reset,
+            backup::backup_to_drive,
+            backup::restore_from_drive,
+            recording::start_screen_recording,
+            recording::stop_screen_recording,
+            recording::get_screen_recording_status,
+            macros::record_macro,
+            macros::stop_macro_recording,
+            macros::play_macro,
+            macros::stop_macro,
+            macros::list_macros,
+            macros::delete_macro,
+            macros::get_macro_status,
+            mock::get_mock_status,
+            mock::reset_mock_sequences,
+            control::get_dbus_control_status,
+            wol::send_wol,
+            wol::get_wol_status,
+            wol::set_wol_enabled,
+            cec::tv_power_on,
+            cec::tv_power_off,
+            cec::set_tv_input,
+            cec::get_tv_state,
+            hours::get_schedule,
+            hours::set_schedule,
+            hours::set_display_override,
+            hours::get_display_state,
+            brightness::get_light_level,
+            brightness::get_brightness_config,
+            brightness::set_brightness_config,
+            brightness::set_auto_brightness,
+            brightness::set_brightness,
+            proximity::get_proximity_config,
+            proximity::set_proximity_config,
+            proximity::get_proximity_state,
+            footfall::get_footfall_stats,
+            footfall::export_footfall_csv,
+            footfall::record_footfall,
+            #[cfg(feature = "speech")]
+            speech::start_listening,
+            #[cfg(feature = "speech")]
+            speech::stop_listening,
+            #[cfg(feature = "speech")]
+            speech::get_speech_status,
+            #[cfg(feature = "speech")]
+            speech::get_speech_config,
+            #[cfg(feature = "speech")]
+            speech::set_speech_config,
+            i18n::list_locales,
+            i18n::get_locale,
+            i18n::get_strings,
+            i18n::set_locale,
+            formatting::format_number,
+            formatting::format_currency,
+            formatting::format_measurement,
+            business::get_business_hours,
+            business::set_business_hours,
+            business::is_open_now,
+            business::next_opening,
+            business::get_holidays,
+            business::list_holiday_regions,
+            timers::create_timer,
+            timers::list_timers,
+            timers::set_timer_paused,
+            timers::cancel_timer,
+            timers::create_alarm,
+            timers::list_alarms,
+            timers::set_alarm_enabled,
+            timers::snooze_alarm,
+            timers::delete_alarm,
+            monotonic::get_monotonic_time,
+            monotonic::start_interval,
+            monotonic::stop_interval,
+            lock::lock_screen,
+            lock::unlock,
+            lock::get_lock_status,
+            lock::get_lock_config,
+            lock::set_lock_config,
+            lock::set_lock_pin,
+            approvals::list_protected_actions,
+            approvals::get_dual_auth_config,
+            approvals::set_dual_auth_config,
+            approvals::request_approval,
+            approvals::approve_action,
+            approvals::cancel_approval,
+            approvals::list_pending_approvals,
+            approvals::get_dual_auth_audit,
+            totp::get_totp_config,
+            totp::set_totp_config,
+            totp::provision_totp,
+            totp::clear_totp,
+            tamper::get_tamper_config,
+            tamper::set_tamper_config,
+            tamper::list_tamper_events,
+            tamper::clear_tamper_events,
+            location::get_location,
+            location::get_geofence_status,
+            location::get_location_config,
+            location::set_location_config,
+            modem::get_modem_status,
+            modem::get_modem_config,
+            modem::set_modem_config,
+            modem::connect_modem,
+            modem::disconnect_modem,
+            modem::send_sms,
+            modem::list_sms,
+            modem::delete_sms,
+            metering::get_data_usage,
+            metering::get_data_cap_status,
+            metering::get_data_usage_config,
+            metering::set_data_usage_config,
+            downloads::queue_download,
+            downloads::list_downloads,
+            downloads::pause_download,
+            downloads::resume_download,
+            downloads::cancel_download,
+            bandwidth::get_bandwidth_limit,
+            bandwidth::set_bandwidth_limit,
+            fetch::fetch_url,
+            fetch::clear_fetch_cookies,
+            fetch::get_fetch_config,
+            fetch::set_fetch_config,
+            demo::get_demo_server_config,
+            demo::set_demo_server_config,
+            demo::get_demo_server_status,
+            templates::render_template,
+            templates::list_templates,
+            reports::generate_report,
+            reports::get_report_schedules,
+            reports::set_report_schedules,
+            tabular::parse_tabular_file,
+            tabular::write_tabular_file,
+            structured::read_structured_file,
+            structured::validate_structured_file,
+            structured::write_structured_file,
+            structured::get_config_files,
+            structured::set_config_files,
+            games::submit_score,
+            games::get_leaderboard,
+            games::clear_leaderboard,
+            games::save_game_state,
+            games::load_game_state,
+            games::delete_game_state,
+            minesweeper::new_minesweeper,
+            minesweeper::get_minesweeper,
+            minesweeper::minesweeper_reveal,
+            minesweeper::minesweeper_flag,
+            minesweeper::minesweeper_chord,
+            solitaire::new_solitaire,
+            solitaire::get_solitaire,
+            solitaire::solitaire_move,
+            solitaire::solitaire_undo,
+            bitmap::read_bitmap_file,
+            bitmap::write_bitmap_file,
+            icons::extract_icon,
+            pointer::list_cursor_themes,
+            pointer::get_pointer_settings,
+            pointer::set_pointer_settings,
+            pointer::install_cursor_theme,
+            pointer::remove_cursor_theme,
+            keyboard::list_keyboard_layouts,
+            keyboard::get_keyboard_layout,
+            keyboard::set_keyboard_layout,
+            keyboard::next_keyboard_layout,
+            keyboard::remove_keyboard_layout,
+            regional::get_regional_settings,
+            regional::set_regional_settings,
+            accessibility::get_accessibility_settings,
+            accessibility::set_accessibility_settings,
+            accessibility::get_accessibility_status,
+            magnifier::start_magnifier,
+            magnifier::stop_magnifier,
+            magnifier::get_magnifier_status,
+            magnifier::get_magnifier_settings,
+            magnifier::set_magnifier_settings,
+            magnifier::set_magnifier_zoom,
+            magnifier::magnifier_zoom_in,
+            magnifier::magnifier_zoom_out,
+            nightlight::get_color_settings,
+            nightlight::get_color_status,
+            nightlight::set_color_temperature,
+            nightlight::set_color_filter,
+            microphone::list_audio_inputs,
+            microphone::list_audio_outputs,
+            microphone::start_recording,
+            microphone::stop_recording,
+            microphone::get_audio_recording_status,
+            sip::get_sip_config,
+            sip::get_sip_status,
+            sip::sip_register,
+            sip::sip_unregister,
+            sip::set_help_desk,
+            sip::set_call_audio,
+            sip::sip_call,
+            sip::sip_answer,
+            sip::sip_hangup,
+            avcheck::test_av_devices,
+            streams::list_streams,
+            streams::save_stream,
+            streams::delete_stream,
+            streams::get_stream_status,
+            streams::probe_stream,
+            streams::stream_snapshot,
+            streams::start_stream_relay,
+            streams::stop_stream_relay,
+            photoframe::get_photo_frame_config,
+            photoframe::set_photo_frame_config,
+            photoframe::start_photo_frame,
+            photoframe::stop_photo_frame,
+            photoframe::next_photo,
+            photoframe::previous_photo,
+            photoframe::get_photo_frame_status,
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,453 @@
+//! Location and geofencing
+//!
+//! Where a portable unit is, from a GPS receiver (through gpsd, or NMEA
+//! sentences straight off a serial dongle), a Wi-Fi lookup against a
+//! geolocation service, or a fixed position entered at install. The latest
+//! fix is served by `get_location`, reported in the D-Bus status for fleet
+//! tooling and published as `location-changed` when the unit has moved or a
+//! while has passed. With a geofence set, leaving the site publishes
+//! `geofence-left`, and coming back `geofence-returned`.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::http::ResponseExt;
+use crate::{events, http, proximity, store};
+
+const LOCATION_FILE: &str = "location.json";
+
+/// Wait before reconnecting to a receiver that failed
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Movement that is published straight away
+const REPORT_DISTANCE_M: f64 = 25.0;
+
+/// A position is published at least this often while fixes keep coming
+const REPORT_INTERVAL: Duration = Duration::from_secs(300);
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LocationSource {
+    /// No location
+    None,
+    /// A GPS receiver shared through gpsd
+    Gpsd { host: String, port: u16 },
+    /// A GPS dongle sending NMEA sentences
+    Serial { port: String, baud: u32 },
+    /// Nearby access points looked up with a geolocation service speaking the
+    /// common `geolocate` JSON API
+    Wifi { url: String },
+    /// Entered at install, for units that do not move on their own
+    Fixed { latitude: f64, longitude: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geofence {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub radius_m: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocationConfig {
+    pub source: LocationSource,
+    /// Seconds between Wi-Fi lookups
+    pub wifi_interval_secs: u64,
+    pub geofence: Option<Geofence>,
+}
+
+impl Default for LocationConfig {
+    fn default() -> Self {
+        LocationConfig {
+            source: LocationSource::None,
+            wifi_interval_secs: 600,
+            geofence: None,
+        }
+    }
+}
+
+/// Payload of `location-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Estimated horizontal error
+    pub accuracy_m: Option<f64>,
+    pub altitude_m: Option<f64>,
+    /// gpsd, serial, wifi or fixed
+    pub source: String,
+    /// Unix time of the fix
+    pub time: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GeofenceStatus {
+    pub enabled: bool,
+    /// Unknown until there is a fix
+    pub inside: Option<bool>,
+    /// Distance from the geofence centre
+    pub distance_m: Option<f64>,
+}
+
+/// Payload of `geofence-left` and `geofence-returned`
+#[derive(Debug, Clone, Serialize)]
+pub struct GeofenceCrossed {
+    pub location: Location,
+    pub distance_m: f64,
+    pub radius_m: f64,
+}
+
+#[derive(Default)]
+struct Fix {
+    location: Option<Location>,
+    error: Option<String>,
+    inside: Option<bool>,
+    /// Last position published, and when
+    reported: Option<(Location, Instant)>,
+}
+
+pub struct LocationState {
+    config: Mutex<LocationConfig>,
+    fix: Mutex<Fix>,
+    /// Bumped on every config change so the old watcher stops
+    generation: AtomicU64,
+}
+
+impl LocationState {
+    pub fn load(app: &AppHandle) -> Self {
+        LocationState {
+            config: Mutex::new(store::load(app, LOCATION_FILE)),
+            fix: Mutex::new(Fix::default()),
+            generation: AtomicU64::new(0),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Great-circle distance in metres
+fn distance_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let dphi = (lat2 - lat1).to_radians();
+    let dlambda = (lon2 - lon1).to_radians();
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+fn location(latitude: f64, longitude: f64, source: &str) -> Location {
+    Location {
+        latitude,
+        longitude,
+        accuracy_m: None,
+        altitude_m: None,
+        source: source.to_string(),
+        time: Local::now().timestamp(),
+    }
+}
+
+fn current(app: &AppHandle, generation: u64) -> bool {
+    app.state::<LocationState>().generation.load(Ordering::SeqCst) == generation
+}
+
+fn set_error(app: &AppHandle, error: String) {
+    app.state::<LocationState>().fix.lock().expect("location lock").error = Some(error);
+}
+
+/// Take a new fix: check the geofence and publish what changed
+fn update(app: &AppHandle, location: Location) {
+    let state = app.state::<LocationState>();
+    let geofence = state.config.lock().expect("location config lock").geofence.clone();
+    let mut fix = state.fix.lock().expect("location lock");
+
+    let report = fix.reported.as_ref().map_or(true, |(last, at)| {
+        at.elapsed() >= REPORT_INTERVAL
+            || distance_m(last.latitude, last.longitude, location.latitude, location.longitude) >= REPORT_DISTANCE_M
+    });
+    let crossed = geofence.and_then(|fence| {
+        let distance = distance_m(fence.latitude, fence.longitude, location.latitude, location.longitude);
+        let inside = distance <= fence.radius_m;
+        let was_inside = fix.inside.replace(inside);
+        // The first fix sets the baseline, unless it is already outside
+        let changed = was_inside.map_or(!inside, |was| was != inside);
+        changed.then(|| {
+            let topic = if inside { "geofence-returned" } else { "geofence-left" };
+            let payload = GeofenceCrossed {
+                location: location.clone(),
+                distance_m: distance,
+                radius_m: fence.radius_m,
+            };
+            (topic, payload)
+        })
+    });
+    fix.location = Some(location.clone());
+    fix.error = None;
+    if report {
+        fix.reported = Some((location.clone(), Instant::now()));
+    }
+    drop(fix);
+
+    if report {
+        events::publish(app, "location-changed", &location);
+    }
+    if let Some((topic, payload)) = crossed {
+        events::publish(app, topic, payload);
+    }
+}
+
+/// NMEA `ddmm.mmmm` with its hemisphere, as signed degrees
+fn nmea_degrees(value: &str, hemisphere: &str) -> Option<f64> {
+    let dot = value.find('.')?;
+    let degrees: f64 = value.get(..dot.checked_sub(2)?)?.parse().ok()?;
+    let minutes: f64 = value.get(dot - 2..)?.parse().ok()?;
+    let degrees = degrees + minutes / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(degrees),
+        "S" | "W" => Some(-degrees),
+        _ => None,
+    }
+}
+
+/// A position from a GGA or RMC sentence with a valid checksum and a fix
+fn parse_nmea(line: &str) -> Option<Location> {
+    let (body, checksum) = line.trim().strip_prefix('$')?.split_once('*')?;
+    let expected = u8::from_str_radix(checksum.get(..2)?, 16).ok()?;
+    if body.bytes().fold(0u8, |acc, byte| acc ^ byte) != expected {
+        return None;
+    }
+    let fields: Vec<&str> = body.split(',').collect();
+    let kind = fields.first()?.get(2..)?;
+    match kind {
+        "GGA" if fields.get(6).is_some_and(|quality| *quality != "0") => {
+            let mut fix = location(
+                nmea_degrees(fields.get(2)?, fields.get(3)?)?,
+                nmea_degrees(fields.get(4)?, fields.get(5)?)?,
+                "serial",
+            );
+            // HDOP times the nominal 5 m error of a consumer receiver
+            fix.accuracy_m = fields.get(8).and_then(|hdop| hdop.parse::<f64>().ok()).map(|hdop| hdop * 5.0);
+            fix.altitude_m = fields.get(9).and_then(|altitude| altitude.parse().ok());
+            Some(fix)
+        }
+        "RMC" if fields.get(2) == Some(&"A") => Some(location(
+            nmea_degrees(fields.get(3)?, fields.get(4)?)?,
+            nmea_degrees(fields.get(5)?, fields.get(6)?)?,
+            "serial",
+        )),
+        _ => None,
+    }
+}
+
+/// A position from a gpsd TPV report with a 2D or 3D fix
+fn parse_tpv(report: &Value) -> Option<Location> {
+    if report.get("class")?.as_str()? != "TPV" || report.get("mode")?.as_u64()? < 2 {
+        return None;
+    }
+    let mut fix = location(report.get("lat")?.as_f64()?, report.get("lon")?.as_f64()?, "gpsd");
+    let epx = report.get("epx").and_then(Value::as_f64);
+    let epy = report.get("epy").and_then(Value::as_f64);
+    fix.accuracy_m = epx.zip(epy).map(|(x, y)| x.max(y)).or(epx);
+    fix.altitude_m = report.get("altMSL").or_else(|| report.get("alt")).and_then(Value::as_f64);
+    Some(fix)
+}
+
+fn watch_gpsd(app: &AppHandle, generation: u64, host: &str, port: u16) {
+    while current(app, generation) {
+        let result = (|| -> Result<(), String> {
+            let mut stream = TcpStream::connect((host, port)).map_err(|e| format!("gpsd {}:{}: {}", host, port, e))?;
+            stream
+                .write_all(b"?WATCH={\"enable\":true,\"json\":true}\n")
+                .map_err(|e| e.to_string())?;
+            for line in BufReader::new(stream).lines() {
+                if !current(app, generation) {
+                    return Ok(());
+                }
+                let line = line.map_err(|e| e.to_string())?;
+                if let Some(fix) = serde_json::from_str(&line).ok().as_ref().and_then(parse_tpv) {
+                    update(app, fix);
+                }
+            }
+            Err("gpsd closed the connection".to_string())
+        })();
+        if let Err(e) = result {
+            set_error(app, e);
+        }
+        std::thread::sleep(RETRY_DELAY);
+    }
+}
+
+fn watch_serial(app: &AppHandle, generation: u64, port: &str, baud: u32) {
+    while current(app, generation) {
+        let file = match proximity::open_serial(port, baud) {
+            Ok(file) => file,
+            Err(e) => {
+                set_error(app, e);
+                std::thread::sleep(RETRY_DELAY);
+                continue;
+            }
+        };
+        for line in BufReader::new(file).split(b'\n').flatten() {
+            if !current(app, generation) {
+                return;
+            }
+            if let Some(fix) = parse_nmea(&String::from_utf8_lossy(&line)) {
+                update(app, fix);
+            }
+        }
+        set_error(app, format!("{} closed", port));
+        std::thread::sleep(RETRY_DELAY);
+    }
+}
+
+/// Access points in range as (BSSID, signal in dBm)
+fn scan_wifi() -> Result<Vec<(String, i32)>, String> {
+    let output = Command::new("nmcli")
+        .args(["-t", "-f", "BSSID,SIGNAL", "device", "wifi", "list"])
+        .output()
+        .map_err(|e| format!("Could not run nmcli: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            // Colons inside the BSSID are escaped in terse output
+            let (bssid, signal) = line.rsplit_once(':')?;
+            let percent: i32 = signal.trim().parse().ok()?;
+            Some((bssid.replace("\\:", ":").to_lowercase(), percent / 2 - 100))
+        })
+        .collect())
+}
+
+fn wifi_lookup(url: &str) -> Result<Location, String> {
+    let access_points: Vec<Value> = scan_wifi()?
+        .into_iter()
+        .map(|(bssid, signal)| json!({ "macAddress": bssid, "signalStrength": signal }))
+        .collect();
+    if access_points.len() < 2 {
+        return Err("Too few Wi-Fi access points in range for a lookup".to_string());
+    }
+    let response: Value = http::agent()
+        .post(url)
+        .send_json(json!({ "considerIp": false, "wifiAccessPoints": access_points }))
+        .map_err(|e| format!("Location lookup failed: {}", e))?
+        .into_throttled_json()
+        .map_err(|e| e.to_string())?;
+    let position = response.get("location").ok_or("The lookup found no location")?;
+    let mut fix = location(
+        position.get("lat").and_then(Value::as_f64).ok_or("The lookup has no latitude")?,
+        position.get("lng").and_then(Value::as_f64).ok_or("The lookup has no longitude")?,
+        "wifi",
+    );
+    fix.accuracy_m = response.get("accuracy").and_then(Value::as_f64);
+    Ok(fix)
+}
+
+/// Start following the configured source, replacing any earlier watcher
+pub fn start_location(app: AppHandle) {
+    let state = app.state::<LocationState>();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let config = state.config.lock().expect("location config lock").clone();
+    *state.fix.lock().expect("location lock") = Fix::default();
+    std::thread::spawn(move || match config.source {
+        LocationSource::None => {}
+        LocationSource::Gpsd { host, port } => watch_gpsd(&app, generation, &host, port),
+        LocationSource::Serial { port, baud } => watch_serial(&app, generation, &port, baud),
+        LocationSource::Wifi { url } => {
+            while current(&app, generation) {
+                match wifi_lookup(&url) {
+                    Ok(fix) => update(&app, fix),
+                    Err(e) => set_error(&app, e),
+                }
+                std::thread::sleep(Duration::from_secs(config.wifi_interval_secs.max(60)));
+            }
+        }
+        LocationSource::Fixed { latitude, longitude } => update(&app, location(latitude, longitude, "fixed")),
+    });
+}
+
+/// Latest fix as (latitude, longitude), for status reports
+pub(crate) fn last_position(app: &AppHandle) -> Option<(f64, f64)> {
+    let state = app.try_state::<LocationState>()?;
+    let fix = state.fix.lock().expect("location lock");
+    fix.location.as_ref().map(|location| (location.latitude, location.longitude))
+}
+
+fn valid_coordinates(latitude: f64, longitude: f64) -> bool {
+    (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// The latest position
+#[tauri::command]
+pub fn get_location(state: State<'_, LocationState>) -> Result<Location, KioskError> {
+    let fix = state.fix.lock().expect("location lock");
+    fix.location.clone().ok_or_else(|| {
+        KioskError::not_found(fix.error.clone().unwrap_or_else(|| "No location fix yet".to_string()))
+    })
+}
+
+/// Whether the unit is inside its geofence
+#[tauri::command]
+pub fn get_geofence_status(state: State<'_, LocationState>) -> GeofenceStatus {
+    let geofence = state.config.lock().expect("location config lock").geofence.clone();
+    let fix = state.fix.lock().expect("location lock");
+    GeofenceStatus {
+        enabled: geofence.is_some(),
+        inside: fix.inside,
+        distance_m: geofence.zip(fix.location.as_ref()).map(|(fence, location)| {
+            distance_m(fence.latitude, fence.longitude, location.latitude, location.longitude)
+        }),
+    }
+}
+
+#[tauri::command]
+pub fn get_location_config(state: State<'_, LocationState>) -> LocationConfig {
+    state.config.lock().expect("location config lock").clone()
+}
+
+/// Update the location source and geofence, restarting the watcher (admin)
+#[tauri::command]
+pub fn set_location_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, LocationState>,
+    config: LocationConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if let LocationSource::Fixed { latitude, longitude } = config.source {
+        if !valid_coordinates(latitude, longitude) {
+            return Err(KioskError::invalid("Latitude or longitude out of range"));
+        }
+    }
+    if let Some(fence) = &config.geofence {
+        if !valid_coordinates(fence.latitude, fence.longitude) || fence.radius_m <= 0.0 {
+            return Err(KioskError::invalid("A geofence needs a valid centre and a positive radius"));
+        }
+    }
+    store::save(&app, LOCATION_FILE, &config)?;
+    *state.config.lock().expect("location config lock") = config;
+    start_location(app);
+    Ok(())
+}
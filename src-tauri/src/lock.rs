@@ -0,0 +1,288 @@
+//! Screen lock
+//!
+//! Locks the kiosk behind a PIN, on request or after it has been idle for
+//! `auto_lock_secs`. The frontend draws the lock dialog on `screen-locked`,
+//! but the backend enforces it: while locked, the command middleware rejects
+//! every command outside a short list the lock screen itself needs. Wrong
+//! PINs are allowed `max_attempts` times, then each further failure doubles
+//! the wait before the next try. The lock, the failure count and the
+//! lockout all survive a restart.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use chrono::Local;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{attract, config, events, store};
+
+const LOCK_FILE: &str = "lock.json";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const MIN_PIN_LEN: usize = 4;
+
+/// Commands the lock screen can call while locked
+const ALLOWED_WHILE_LOCKED: &[&str] = &[
+    "unlock",
+    "get_lock_status",
+    "report_activity",
+    "get_datetime",
+    "get_monotonic_time",
+    "get_strings",
+    "get_locale",
+    "subscribe_events",
+    "unsubscribe_events",
+    "report_first_paint",
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LockConfig {
+    /// Seconds idle before the screen locks itself; 0 disables it
+    pub auto_lock_secs: u64,
+    /// Wrong PINs allowed before unlocking is delayed
+    pub max_attempts: u32,
+    /// First delay after `max_attempts`; doubled with each further failure
+    pub lockout_secs: u64,
+    pub max_lockout_secs: u64,
+}
+
+impl Default for LockConfig {
+    fn default() -> Self {
+        LockConfig {
+            auto_lock_secs: 0,
+            max_attempts: 3,
+            lockout_secs: 30,
+            max_lockout_secs: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LockStatus {
+    pub locked: bool,
+    pub pin_set: bool,
+    /// Wrong PINs since the last unlock
+    pub failures: u32,
+    /// Milliseconds until the next unlock attempt is accepted
+    pub retry_in_ms: Option<u64>,
+}
+
+/// Payload of `screen-locked`
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenLocked {
+    /// Locked by the idle timer rather than on request
+    pub automatic: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct LockData {
+    config: LockConfig,
+    pin_hash: Option<String>,
+    pin_salt: String,
+    locked: bool,
+    /// Wrong PINs since the last unlock
+    failures: u32,
+    /// Wall-clock time (ms) before which unlocking is refused
+    retry_at: Option<i64>,
+}
+
+pub struct LockState {
+    stored: Mutex<LockData>,
+}
+
+impl LockState {
+    pub fn load(app: &AppHandle) -> Self {
+        LockState {
+            stored: Mutex::new(store::load(app, LOCK_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn status(stored: &LockData) -> LockStatus {
+    LockStatus {
+        locked: stored.locked,
+        pin_set: stored.pin_hash.is_some(),
+        failures: stored.failures,
+        retry_in_ms: retry_in(stored),
+    }
+}
+
+/// Milliseconds left on the lockout, if any
+fn retry_in(stored: &LockData) -> Option<u64> {
+    let left = stored.retry_at? - Local::now().timestamp_millis();
+    (left > 0).then_some(left as u64)
+}
+
+/// Forget wrong PINs and any lockout
+fn clear_attempts(stored: &mut LockData) {
+    stored.failures = 0;
+    stored.retry_at = None;
+}
+
+/// Whether the lock stops `command` from running now
+pub(crate) fn blocks(app: &AppHandle, command: &str) -> bool {
+    app.try_state::<LockState>()
+        .is_some_and(|state| state.stored.lock().expect("lock state lock").locked)
+        && !ALLOWED_WHILE_LOCKED.contains(&command)
+}
+
+/// Lock the screen if a PIN is set
+pub(crate) fn lock(app: &AppHandle, state: &LockState, automatic: bool) -> Result<LockStatus, String> {
+    let mut stored = state.stored.lock().expect("lock state lock");
+    if stored.pin_hash.is_none() {
+        return Err("Set a lock PIN first".to_string());
+    }
+    let newly_locked = !stored.locked;
+    if newly_locked {
+        stored.locked = true;
+        store::save(app, LOCK_FILE, &*stored)?;
+    }
+    let result = status(&stored);
+    drop(stored);
+    if newly_locked {
+        events::publish(app, "screen-locked", ScreenLocked { automatic });
+    }
+    Ok(result)
+}
+
+/// How long to wait after the `failures`th wrong PIN
+fn lockout(config: &LockConfig, failures: u32) -> Option<Duration> {
+    let over = failures.checked_sub(config.max_attempts)?;
+    let secs = config.lockout_secs.saturating_mul(1u64 << over.min(32)).min(config.max_lockout_secs);
+    Some(Duration::from_secs(secs))
+}
+
+/// Spawn the idle watcher that locks the screen
+pub fn start_auto_lock(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<LockState>();
+        let due = {
+            let stored = state.stored.lock().expect("lock state lock");
+            let secs = stored.config.auto_lock_secs;
+            !stored.locked
+                && stored.pin_hash.is_some()
+                && secs > 0
+                && attract::idle_time(&app) >= Duration::from_secs(secs)
+        };
+        if due {
+            let _ = lock(&app, &state, true);
+        }
+        std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Lock the screen now
+#[tauri::command]
+pub fn lock_screen(app: AppHandle, state: State<'_, LockState>) -> Result<LockStatus, KioskError> {
+    lock(&app, &state, false).map_err(KioskError::invalid)
+}
+
+/// Unlock the screen with the PIN
+#[tauri::command]
+pub fn unlock(app: AppHandle, state: State<'_, LockState>, pin: String) -> Result<LockStatus, KioskError> {
+    let mut stored = state.stored.lock().expect("lock state lock");
+    if !stored.locked {
+        return Ok(status(&stored));
+    }
+    if let Some(wait) = retry_in(&stored) {
+        return Err(KioskError::new(
+            ErrorKind::RateLimited,
+            format!("Too many wrong PINs; try again in {} s", wait / 1000 + 1),
+        ));
+    }
+
+    let correct = stored
+        .pin_hash
+        .as_deref()
+        .is_some_and(|hash| auth::verify_secret(pin.trim(), &stored.pin_salt, hash));
+    if !correct {
+        stored.failures += 1;
+        stored.retry_at = lockout(&stored.config, stored.failures)
+            .map(|wait| Local::now().timestamp_millis() + wait.as_millis() as i64);
+        // Saved at once, so restarting the kiosk does not reset the lockout
+        store::save(&app, LOCK_FILE, &*stored)?;
+        let failed = status(&stored);
+        drop(stored);
+        events::publish(&app, "unlock-failed", failed);
+        return Err(KioskError::new(ErrorKind::Denied, "Wrong PIN"));
+    }
+
+    stored.locked = false;
+    clear_attempts(&mut stored);
+    store::save(&app, LOCK_FILE, &*stored)?;
+    let result = status(&stored);
+    drop(stored);
+    // Unlocking counts as activity, or the idle timer would lock again at once
+    attract::record_activity(&app);
+    events::publish(&app, "screen-unlocked", &result);
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn get_lock_status(state: State<'_, LockState>) -> LockStatus {
+    status(&state.stored.lock().expect("lock state lock"))
+}
+
+#[tauri::command]
+pub fn get_lock_config(state: State<'_, LockState>) -> LockConfig {
+    state.stored.lock().expect("lock state lock").config.clone()
+}
+
+/// Update auto-lock and lockout settings (admin)
+#[tauri::command]
+pub fn set_lock_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, LockState>,
+    config: LockConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if config.max_attempts == 0 {
+        return Err(KioskError::invalid("Allow at least one PIN attempt"));
+    }
+    let mut stored = state.stored.lock().expect("lock state lock");
+    stored.config = config;
+    Ok(store::save(&app, LOCK_FILE, &*stored)?)
+}
+
+/// Set the lock PIN, or clear it to turn the lock off (admin)
+#[tauri::command]
+pub fn set_lock_pin(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, LockState>,
+    pin: Option<String>,
+) -> Result<LockStatus, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut stored = state.stored.lock().expect("lock state lock");
+    match pin.as_deref().map(str::trim) {
+        Some(pin) => {
+            if pin.len() < MIN_PIN_LEN || !pin.chars().all(|c| c.is_ascii_digit()) {
+                return Err(KioskError::invalid(format!("A PIN is at least {} digits", MIN_PIN_LEN)));
+            }
+            stored.pin_salt = auth::new_salt();
+            stored.pin_hash = Some(auth::hash_secret(pin, &stored.pin_salt));
+        }
+        None => stored.pin_hash = None,
+    }
+    clear_attempts(&mut stored);
+    store::save(&app, LOCK_FILE, &*stored)?;
+    Ok(status(&stored))
+}
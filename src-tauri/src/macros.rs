@@ -0,0 +1,385 @@
+//! Input macros
+//!
+//! Records pointer, touch and keyboard input straight from the evdev nodes
+//! in /dev/input, with the time of every event, and replays it by writing
+//! the same events back to the same nodes, which the kernel injects as if
+//! they came from the hardware. Used for showroom demo loops and repeatable
+//! UI test scripts. Needs read and write access to /dev/input (the `input`
+//! group). Because a recording captures every key typed, recording and
+//! playback are admin-only.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{events, store};
+
+const MACROS_FILE: &str = "macros.json";
+
+const INPUT_DEVICES: &str = "/sys/class/input";
+
+/// Event types worth recording: EV_KEY, EV_REL and EV_ABS
+const RECORDED_TYPES: u64 = (1 << 1) | (1 << 2) | (1 << 3);
+
+const EVENT_SIZE: usize = std::mem::size_of::<libc::input_event>();
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Recordings stop growing past this many events
+const MAX_EVENTS: usize = 200_000;
+
+/// Input this close to the stop is dropped, since it is the operator
+/// reaching for the stop button
+const TAIL_TRIM: Duration = Duration::from_millis(750);
+
+/// Pause between repetitions of a looping macro
+const LOOP_GAP: Duration = Duration::from_secs(1);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// One evdev event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroEvent {
+    /// Milliseconds since the recording started
+    pub at_ms: u64,
+    /// Device node, such as `/dev/input/event2`
+    pub device: String,
+    pub kind: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    pub created_at: i64,
+    pub duration_ms: u64,
+    pub events: Vec<MacroEvent>,
+}
+
+/// A macro without its events, for listing
+#[derive(Debug, Clone, Serialize)]
+pub struct MacroInfo {
+    pub name: String,
+    pub created_at: i64,
+    pub duration_ms: u64,
+    pub events: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MacroStatus {
+    /// Name of the macro being recorded
+    pub recording: Option<String>,
+    /// Name of the macro being played
+    pub playing: Option<String>,
+}
+
+/// Payload of `macro-finished`
+#[derive(Debug, Clone, Serialize)]
+pub struct MacroFinished {
+    pub name: String,
+    /// Why playback stopped early
+    pub error: Option<String>,
+}
+
+struct Recorder {
+    name: String,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<Vec<MacroEvent>>,
+}
+
+struct Player {
+    name: String,
+    stop: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+pub struct MacroState {
+    recorder: Mutex<Option<Recorder>>,
+    player: Mutex<Option<Player>>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Event nodes of devices that report keys, buttons or pointer motion
+fn input_nodes() -> Vec<String> {
+    let mut nodes: Vec<String> = fs::read_dir(INPUT_DEVICES)
+        .map(|entries| entries.flatten().map(|entry| entry.file_name().to_string_lossy().to_string()).collect())
+        .unwrap_or_default();
+    nodes.retain(|name| {
+        let capabilities = Path::new(INPUT_DEVICES).join(name).join("device/capabilities/ev");
+        name.starts_with("event")
+            && fs::read_to_string(capabilities)
+                .ok()
+                .and_then(|bits| u64::from_str_radix(bits.trim(), 16).ok())
+                .is_some_and(|bits| bits & RECORDED_TYPES != 0)
+    });
+    nodes.sort();
+    nodes.into_iter().map(|name| format!("/dev/input/{}", name)).collect()
+}
+
+fn decode(bytes: &[u8]) -> libc::input_event {
+    // SAFETY: `bytes` holds EVENT_SIZE bytes and input_event is plain data
+    unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const libc::input_event) }
+}
+
+fn encode(kind: u16, code: u16, value: i32) -> Vec<u8> {
+    // A zero timestamp is filled in by the kernel
+    // SAFETY: input_event is plain data, valid when zeroed
+    let mut event: libc::input_event = unsafe { std::mem::zeroed() };
+    event.type_ = kind;
+    event.code = code;
+    event.value = value;
+    // SAFETY: the pointer covers exactly one input_event
+    unsafe { std::slice::from_raw_parts(&event as *const libc::input_event as *const u8, EVENT_SIZE) }.to_vec()
+}
+
+/// Read every input node until `stop` is set
+fn record(mut devices: Vec<(String, File)>, stop: Arc<AtomicBool>) -> Vec<MacroEvent> {
+    let started = Instant::now();
+    let mut events = Vec::new();
+    let mut buffer = [0u8; EVENT_SIZE * 64];
+    while !stop.load(Ordering::Relaxed) && events.len() < MAX_EVENTS {
+        let mut idle = true;
+        for (device, file) in devices.iter_mut() {
+            // Nothing waiting (WouldBlock), or the device went away
+            let Ok(read) = file.read(&mut buffer) else { continue };
+            idle = false;
+            let at_ms = started.elapsed().as_millis() as u64;
+            for chunk in buffer[..read].chunks_exact(EVENT_SIZE) {
+                let event = decode(chunk);
+                events.push(MacroEvent {
+                    at_ms,
+                    device: device.clone(),
+                    kind: event.type_,
+                    code: event.code,
+                    value: event.value,
+                });
+            }
+        }
+        if idle {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    // Drop the tail, ending on a complete report (EV_SYN/SYN_REPORT)
+    let cutoff = (started.elapsed().saturating_sub(TAIL_TRIM)).as_millis() as u64;
+    let keep = events
+        .iter()
+        .rposition(|event| event.at_ms <= cutoff && event.kind == 0 && event.code == 0)
+        .map_or(0, |at| at + 1);
+    events.truncate(keep);
+    events
+}
+
+/// Replay a macro's events with their original timing, returning early if stopped
+fn play(events: &[MacroEvent], devices: &mut HashMap<String, File>, stop: &AtomicBool) -> Result<(), String> {
+    let started = Instant::now();
+    for event in events {
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            let due = Duration::from_millis(event.at_ms).saturating_sub(started.elapsed());
+            if due.is_zero() {
+                break;
+            }
+            std::thread::sleep(due.min(Duration::from_millis(50)));
+        }
+        let Some(file) = devices.get_mut(&event.device) else {
+            continue;
+        };
+        file.write_all(&encode(event.kind, event.code, event.value))
+            .map_err(|e| format!("{}: {}", event.device, e))?;
+    }
+    Ok(())
+}
+
+fn info(recorded: &Macro) -> MacroInfo {
+    MacroInfo {
+        name: recorded.name.clone(),
+        created_at: recorded.created_at,
+        duration_ms: recorded.duration_ms,
+        events: recorded.events.len(),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start recording input into a macro called `name` (admin)
+#[tauri::command]
+pub fn record_macro(
+    app: AppHandle,
+    state: State<'_, MacroState>,
+    auth: State<'_, AuthState>,
+    name: String,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(KioskError::invalid("Macro name is required"));
+    }
+    let mut recorder = state.recorder.lock().expect("macro recorder lock");
+    if recorder.is_some() || state.player.lock().expect("macro player lock").is_some() {
+        return Err(KioskError::invalid("A macro is already being recorded or played"));
+    }
+
+    let devices: Vec<(String, File)> = input_nodes()
+        .into_iter()
+        .filter_map(|node| {
+            let file = OpenOptions::new().read(true).custom_flags(libc::O_NONBLOCK).open(&node).ok()?;
+            Some((node, file))
+        })
+        .collect();
+    if devices.is_empty() {
+        return Err(KioskError::new(ErrorKind::Denied, "No readable input devices (is the kiosk user in the input group?)"));
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = std::thread::spawn({
+        let stop = stop.clone();
+        move || record(devices, stop)
+    });
+    *recorder = Some(Recorder {
+        name: name.clone(),
+        stop,
+        thread,
+    });
+    events::publish(&app, "macro-recording", &name);
+    Ok(())
+}
+
+/// Stop recording and save the macro (admin)
+#[tauri::command]
+pub fn stop_macro_recording(
+    app: AppHandle,
+    state: State<'_, MacroState>,
+    auth: State<'_, AuthState>,
+) -> Result<MacroInfo, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let recorder = state.recorder.lock().expect("macro recorder lock").take();
+    let recorder = recorder.ok_or_else(|| KioskError::invalid("No macro is being recorded"))?;
+    recorder.stop.store(true, Ordering::Relaxed);
+    let events = recorder.thread.join().map_err(|_| KioskError::invalid("The macro recorder failed"))?;
+
+    let recorded = Macro {
+        name: recorder.name,
+        created_at: chrono::Local::now().timestamp(),
+        duration_ms: events.last().map_or(0, |event| event.at_ms),
+        events,
+    };
+    let mut macros: BTreeMap<String, Macro> = store::load(&app, MACROS_FILE);
+    let info = info(&recorded);
+    macros.insert(recorded.name.clone(), recorded);
+    store::save(&app, MACROS_FILE, &macros)?;
+    events::publish(&app, "macro-saved", &info);
+    Ok(info)
+}
+
+/// Replay a macro, repeating it until stopped when `looped` (admin)
+#[tauri::command]
+pub fn play_macro(
+    app: AppHandle,
+    state: State<'_, MacroState>,
+    auth: State<'_, AuthState>,
+    name: String,
+    looped: Option<bool>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let macros: BTreeMap<String, Macro> = store::load(&app, MACROS_FILE);
+    let recorded = macros.get(&name).cloned().ok_or_else(|| KioskError::not_found(format!("Macro not found: {}", name)))?;
+    let mut player = state.player.lock().expect("macro player lock");
+    if player.is_some() || state.recorder.lock().expect("macro recorder lock").is_some() {
+        return Err(KioskError::invalid("A macro is already being recorded or played"));
+    }
+
+    let mut devices = HashMap::new();
+    for event in &recorded.events {
+        if !devices.contains_key(&event.device) {
+            let file = OpenOptions::new()
+                .write(true)
+                .open(&event.device)
+                .map_err(|e| KioskError::new(ErrorKind::Denied, format!("{}: {}", event.device, e)))?;
+            devices.insert(event.device.clone(), file);
+        }
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    *player = Some(Player {
+        name: name.clone(),
+        stop: stop.clone(),
+    });
+    let looped = looped.unwrap_or(false);
+    let worker = app.clone();
+    std::thread::spawn(move || {
+        let result = loop {
+            let result = play(&recorded.events, &mut devices, &stop);
+            if result.is_err() || !looped || stop.load(Ordering::Relaxed) {
+                break result;
+            }
+            std::thread::sleep(LOOP_GAP);
+        };
+        *worker.state::<MacroState>().player.lock().expect("macro player lock") = None;
+        events::publish(
+            &worker,
+            "macro-finished",
+            MacroFinished {
+                name: recorded.name,
+                error: result.err(),
+            },
+        );
+    });
+    events::publish(&app, "macro-playing", &name);
+    Ok(())
+}
+
+/// Stop a playing macro
+#[tauri::command]
+pub fn stop_macro(state: State<'_, MacroState>) {
+    if let Some(player) = state.player.lock().expect("macro player lock").as_ref() {
+        player.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Saved macros, without their events
+#[tauri::command]
+pub fn list_macros(app: AppHandle) -> Vec<MacroInfo> {
+    let macros: BTreeMap<String, Macro> = store::load(&app, MACROS_FILE);
+    macros.values().map(info).collect()
+}
+
+/// Delete a saved macro (admin)
+#[tauri::command]
+pub fn delete_macro(app: AppHandle, auth: State<'_, AuthState>, name: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut macros: BTreeMap<String, Macro> = store::load(&app, MACROS_FILE);
+    if macros.remove(&name).is_none() {
+        return Err(KioskError::not_found(format!("Macro not found: {}", name)));
+    }
+    Ok(store::save(&app, MACROS_FILE, &macros)?)
+}
+
+/// What is being recorded or played
+#[tauri::command]
+pub fn get_macro_status(state: State<'_, MacroState>) -> MacroStatus {
+    MacroStatus {
+        recording: state.recorder.lock().expect("macro recorder lock").as_ref().map(|r| r.name.clone()),
+        playing: state.player.lock().expect("macro player lock").as_ref().map(|p| p.name.clone()),
+    }
+}
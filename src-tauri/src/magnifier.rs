@@ -0,0 +1,452 @@
+//! Screen magnifier
+//!
+//! A lens that follows the pointer over everything on screen, the kiosk's
+//! own windows and launched apps alike. The area around the pointer is
+//! captured (grim on Wayland, ImageMagick on X11), scaled up and shown in a
+//! borderless always-on-top window that ignores the pointer. The window
+//! sits beside the captured area rather than over it, so it never magnifies
+//! itself, and flips to the other side near the screen's edges. The window
+//! loads a small page of its own and frames are handed to it directly, so
+//! it does not need the desktop frontend.
+//!
+//! Zoom, lens size, frame rate and smoothing are saved in `magnifier.json`.
+//! Compositors that place windows themselves (most of Wayland) may keep
+//! the lens in one place; it still shows the area around the pointer.
+
+use base64::Engine;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::{self, FilterType};
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, PhysicalPosition, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::error::KioskError;
+use crate::{events, store};
+
+const MAGNIFIER_FILE: &str = "magnifier.json";
+
+const WINDOW_LABEL: &str = "magnifier";
+
+/// Zoom levels the zoom in and out commands step through
+const ZOOM_STEPS: &[f64] = &[1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0];
+const MIN_ZOOM: f64 = 1.0;
+const MAX_ZOOM: f64 = 16.0;
+
+/// Gap between the pointer's area and the lens
+const LENS_GAP: i32 = 16;
+
+const JPEG_QUALITY: u8 = 80;
+
+/// Wait before trying again after a frame failed
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The lens page; `frame(url)` shows a frame
+const LENS_PAGE: &str = r#"<!doctype html>
+<html><body style="margin:0;overflow:hidden;background:#000">
+<img id="frame" alt="" style="display:block;width:100vw;height:100vh;box-sizing:border-box;border:2px solid #000080">
+<script>window.frame = function (url) { document.getElementById('frame').src = url; };</script>
+</body></html>"#;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MagnifierSettings {
+    pub zoom: f64,
+    /// Lens size in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Frames per second
+    pub fps: u32,
+    /// Smooth the scaled image rather than showing square pixels
+    pub smoothing: bool,
+    /// Keep the lens beside the pointer; otherwise it stays in the top
+    /// right corner
+    pub follow_pointer: bool,
+}
+
+impl Default for MagnifierSettings {
+    fn default() -> Self {
+        MagnifierSettings {
+            zoom: 2.0,
+            width: 480,
+            height: 320,
+            fps: 10,
+            smoothing: true,
+            follow_pointer: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MagnifierStatus {
+    pub running: bool,
+    pub zoom: f64,
+    /// Why the last frame could not be shown
+    pub error: Option<String>,
+}
+
+pub struct MagnifierState {
+    settings: Arc<Mutex<MagnifierSettings>>,
+    error: Arc<Mutex<Option<String>>>,
+    running: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+impl MagnifierState {
+    pub fn load(app: &AppHandle) -> Self {
+        MagnifierState {
+            settings: Arc::new(Mutex::new(store::load(app, MAGNIFIER_FILE))),
+            error: Arc::new(Mutex::new(None)),
+            running: Mutex::new(None),
+        }
+    }
+}
+
+/// A screen area, in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Area {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+// ============================================================================
+// Capture
+// ============================================================================
+
+fn command_output(program: &str, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// A binary PPM (P6) with 8-bit samples
+fn parse_ppm(data: &[u8]) -> Result<RgbImage, String> {
+    let mut fields = Vec::new();
+    let mut at = 0;
+    while fields.len() < 4 {
+        while at < data.len() && (data[at].is_ascii_whitespace() || data[at] == b'#') {
+            if data[at] == b'#' {
+                while at < data.len() && data[at] != b'\n' {
+                    at += 1;
+                }
+            }
+            at += 1;
+        }
+        let start = at;
+        while at < data.len() && !data[at].is_ascii_whitespace() {
+            at += 1;
+        }
+        if start == at {
+            return Err("Truncated PPM header".into());
+        }
+        fields.push(String::from_utf8_lossy(&data[start..at]).to_string());
+    }
+    // One whitespace byte separates the header from the samples
+    let pixels = data.get(at + 1..).unwrap_or_default();
+    let number = |field: &str| field.parse::<u32>().map_err(|_| format!("Invalid PPM header field: {}", field));
+    if fields[0] != "P6" || number(&fields[3])? != 255 {
+        return Err("Not an 8-bit binary PPM".into());
+    }
+    let (width, height) = (number(&fields[1])?, number(&fields[2])?);
+    let size = width as usize * height as usize * 3;
+    if pixels.len() < size {
+        return Err("Truncated PPM".into());
+    }
+    RgbImage::from_raw(width, height, pixels[..size].to_vec()).ok_or_else(|| "Invalid PPM".to_string())
+}
+
+fn capture(area: Area) -> Result<RgbImage, String> {
+    let ppm = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let geometry = format!("{},{} {}x{}", area.x, area.y, area.width, area.height);
+        command_output("grim", &["-g", &geometry, "-t", "ppm", "-"])?
+    } else {
+        let crop = format!("{}x{}+{}+{}", area.width, area.height, area.x, area.y);
+        command_output("import", &["-window", "root", "-crop", &crop, "+repage", "-depth", "8", "ppm:-"])?
+    };
+    parse_ppm(&ppm)
+}
+
+/// The pointer's position, from the compositor where the toolkit can't
+/// see it
+fn pointer(app: &AppHandle) -> Result<(i32, i32), String> {
+    let parse = |text: &str| -> Option<(i32, i32)> {
+        let numbers: Vec<i32> = text
+            .split(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.'))
+            .filter_map(|part| part.parse::<f64>().ok())
+            .map(|value| value as i32)
+            .collect();
+        Some((*numbers.first()?, *numbers.get(1)?))
+    };
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        let output = command_output("hyprctl", &["cursorpos"])?;
+        return parse(&String::from_utf8_lossy(&output)).ok_or_else(|| "hyprctl gave no position".to_string());
+    }
+    if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        if let Ok(output) = command_output("xdotool", &["getmouselocation"]) {
+            // `x:120 y:340 screen:0 window:...`
+            if let Some(position) = parse(&String::from_utf8_lossy(&output)) {
+                return Ok(position);
+            }
+        }
+    }
+    let position = app.cursor_position().map_err(|e| e.to_string())?;
+    Ok((position.x as i32, position.y as i32))
+}
+
+/// The area around the pointer a lens of this size shows at this zoom,
+/// kept on the screen
+fn source_area(pointer: (i32, i32), screen: (u32, u32), settings: &MagnifierSettings) -> Area {
+    let width = ((f64::from(settings.width) / settings.zoom).round() as u32).clamp(1, screen.0);
+    let height = ((f64::from(settings.height) / settings.zoom).round() as u32).clamp(1, screen.1);
+    let x = (pointer.0 - width as i32 / 2).clamp(0, (screen.0 - width) as i32);
+    let y = (pointer.1 - height as i32 / 2).clamp(0, (screen.1 - height) as i32);
+    Area { x, y, width, height }
+}
+
+/// Where the lens goes: below and right of the area, or on whichever side
+/// has room
+fn lens_position(area: Area, screen: (u32, u32), settings: &MagnifierSettings) -> (i32, i32) {
+    let (screen_width, screen_height) = (screen.0 as i32, screen.1 as i32);
+    let (width, height) = (settings.width as i32, settings.height as i32);
+    if !settings.follow_pointer {
+        return ((screen_width - width).max(0), 0);
+    }
+    let beside = |start: i32, extent: i32, size: i32, limit: i32| {
+        let after = start + extent + LENS_GAP;
+        let before = start - LENS_GAP - size;
+        if after + size <= limit {
+            after
+        } else if before >= 0 {
+            before
+        } else {
+            (limit - size).clamp(0, after.max(0))
+        }
+    };
+    (
+        beside(area.x, area.width as i32, width, screen_width),
+        beside(area.y, area.height as i32, height, screen_height),
+    )
+}
+
+fn frame_url(image: &RgbImage, settings: &MagnifierSettings) -> Result<String, String> {
+    let filter = if settings.smoothing { FilterType::Triangle } else { FilterType::Nearest };
+    let scaled = imageops::resize(image, settings.width, settings.height, filter);
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+        .encode_image(&scaled)
+        .map_err(|e| format!("Failed to encode frame: {}", e))?;
+    Ok(format!("data:image/jpeg;base64,{}", base64::engine::general_purpose::STANDARD.encode(jpeg)))
+}
+
+// ============================================================================
+// Lens
+// ============================================================================
+
+fn open_lens(app: &AppHandle, settings: &MagnifierSettings) -> Result<(), String> {
+    if app.get_webview_window(WINDOW_LABEL).is_some() {
+        return Ok(());
+    }
+    let page = format!("data:text/html;base64,{}", base64::engine::general_purpose::STANDARD.encode(LENS_PAGE));
+    let url = url::Url::parse(&page).map_err(|e| e.to_string())?;
+    let window = WebviewWindowBuilder::new(app, WINDOW_LABEL, WebviewUrl::External(url))
+        .title("Magnifier")
+        .inner_size(f64::from(settings.width), f64::from(settings.height))
+        .decorations(false)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .shadow(false)
+        .build()
+        .map_err(|e| format!("Failed to open the magnifier: {}", e))?;
+    let _ = window.set_ignore_cursor_events(true);
+    Ok(())
+}
+
+fn close_lens(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+        let _ = window.close();
+    }
+}
+
+fn screen_size(app: &AppHandle) -> Result<(u32, u32), String> {
+    let monitor = app.primary_monitor().map_err(|e| e.to_string())?.ok_or("No monitor found")?;
+    let size = monitor.size();
+    Ok((size.width, size.height))
+}
+
+/// Capture, scale and show one frame, moving the lens if the pointer moved
+fn show_frame(app: &AppHandle, settings: &MagnifierSettings, placed: &mut Option<(i32, i32)>) -> Result<(), String> {
+    let window = app.get_webview_window(WINDOW_LABEL).ok_or("The magnifier window was closed")?;
+    let screen = screen_size(app)?;
+    let area = source_area(pointer(app)?, screen, settings);
+    let position = lens_position(area, screen, settings);
+    if *placed != Some(position) {
+        window.set_position(PhysicalPosition::new(position.0, position.1)).map_err(|e| e.to_string())?;
+        *placed = Some(position);
+    }
+    let url = frame_url(&capture(area)?, settings)?;
+    window.eval(format!("window.frame && window.frame('{}')", url)).map_err(|e| e.to_string())
+}
+
+/// Show frames until stopped
+fn follow(
+    app: AppHandle,
+    settings: Arc<Mutex<MagnifierSettings>>,
+    error: Arc<Mutex<Option<String>>>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut placed = None;
+    let mut size = None;
+    while !stop.load(Ordering::Relaxed) {
+        let started = Instant::now();
+        let current = settings.lock().expect("magnifier lock").clone();
+        if size != Some((current.width, current.height)) {
+            if let Some(window) = app.get_webview_window(WINDOW_LABEL) {
+                let _ = window.set_size(tauri::PhysicalSize::new(current.width, current.height));
+            }
+            size = Some((current.width, current.height));
+            placed = None;
+        }
+        let result = show_frame(&app, &current, &mut placed);
+        let failed = result.is_err();
+        *error.lock().expect("magnifier error lock") = result.err();
+        let interval = if failed { RETRY_DELAY } else { Duration::from_secs(1) / current.fps.max(1) };
+        if let Some(rest) = interval.checked_sub(started.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+}
+
+fn status(state: &MagnifierState) -> MagnifierStatus {
+    MagnifierStatus {
+        running: state.running.lock().expect("magnifier running lock").is_some(),
+        zoom: state.settings.lock().expect("magnifier lock").zoom,
+        error: state.error.lock().expect("magnifier error lock").clone(),
+    }
+}
+
+fn validate(settings: &MagnifierSettings) -> Result<(), String> {
+    if !(MIN_ZOOM..=MAX_ZOOM).contains(&settings.zoom) {
+        return Err(format!("The zoom must be {}-{}", MIN_ZOOM, MAX_ZOOM));
+    }
+    if !(64..=4096).contains(&settings.width) || !(64..=4096).contains(&settings.height) {
+        return Err("The lens must be 64-4096 pixels each way".into());
+    }
+    if !(1..=30).contains(&settings.fps) {
+        return Err("The frame rate must be 1-30 per second".into());
+    }
+    Ok(())
+}
+
+/// Save a new zoom and announce it
+fn change_zoom(app: &AppHandle, state: &MagnifierState, zoom: impl FnOnce(f64) -> f64) -> Result<f64, KioskError> {
+    let zoom = {
+        let mut settings = state.settings.lock().expect("magnifier lock");
+        let mut updated = settings.clone();
+        updated.zoom = zoom(settings.zoom).clamp(MIN_ZOOM, MAX_ZOOM);
+        store::save(app, MAGNIFIER_FILE, &updated)?;
+        *settings = updated;
+        settings.zoom
+    };
+    events::publish(app, "magnifier-changed", status(state));
+    Ok(zoom)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Open the lens and start following the pointer
+#[tauri::command]
+pub fn start_magnifier(app: AppHandle, state: State<'_, MagnifierState>) -> Result<MagnifierStatus, KioskError> {
+    {
+        let mut running = state.running.lock().expect("magnifier running lock");
+        if running.is_none() {
+            let settings = state.settings.lock().expect("magnifier lock").clone();
+            open_lens(&app, &settings)?;
+            let stop = Arc::new(AtomicBool::new(false));
+            let (worker, shared, error) = (app.clone(), state.settings.clone(), state.error.clone());
+            let stopped = stop.clone();
+            std::thread::spawn(move || follow(worker, shared, error, stopped));
+            *running = Some(stop);
+        }
+    }
+    let status = status(&state);
+    events::publish(&app, "magnifier-changed", &status);
+    Ok(status)
+}
+
+/// Close the lens
+#[tauri::command]
+pub fn stop_magnifier(app: AppHandle, state: State<'_, MagnifierState>) -> MagnifierStatus {
+    if let Some(stop) = state.running.lock().expect("magnifier running lock").take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    close_lens(&app);
+    *state.error.lock().expect("magnifier error lock") = None;
+    let status = status(&state);
+    events::publish(&app, "magnifier-changed", &status);
+    status
+}
+
+#[tauri::command]
+pub fn get_magnifier_status(state: State<'_, MagnifierState>) -> MagnifierStatus {
+    status(&state)
+}
+
+#[tauri::command]
+pub fn get_magnifier_settings(state: State<'_, MagnifierState>) -> MagnifierSettings {
+    state.settings.lock().expect("magnifier lock").clone()
+}
+
+/// Save the lens settings; a running lens picks them up with its next frame
+#[tauri::command]
+pub fn set_magnifier_settings(
+    app: AppHandle,
+    state: State<'_, MagnifierState>,
+    settings: MagnifierSettings,
+) -> Result<(), KioskError> {
+    validate(&settings).map_err(KioskError::invalid)?;
+    {
+        let mut current = state.settings.lock().expect("magnifier lock");
+        store::save(&app, MAGNIFIER_FILE, &settings)?;
+        *current = settings;
+    }
+    events::publish(&app, "magnifier-changed", status(&state));
+    Ok(())
+}
+
+/// Set the zoom, from 1 to 16 times
+#[tauri::command]
+pub fn set_magnifier_zoom(app: AppHandle, state: State<'_, MagnifierState>, zoom: f64) -> Result<f64, KioskError> {
+    if !zoom.is_finite() {
+        return Err(KioskError::invalid("The zoom must be a number"));
+    }
+    change_zoom(&app, &state, |_| zoom)
+}
+
+/// Step up to the next zoom level
+#[tauri::command]
+pub fn magnifier_zoom_in(app: AppHandle, state: State<'_, MagnifierState>) -> Result<f64, KioskError> {
+    change_zoom(&app, &state, |zoom| ZOOM_STEPS.iter().copied().find(|step| *step > zoom).unwrap_or(MAX_ZOOM))
+}
+
+/// Step down to the previous zoom level
+#[tauri::command]
+pub fn magnifier_zoom_out(app: AppHandle, state: State<'_, MagnifierState>) -> Result<f64, KioskError> {
+    change_zoom(&app, &state, |zoom| ZOOM_STEPS.iter().rev().copied().find(|step| *step < zoom).unwrap_or(MIN_ZOOM))
+}
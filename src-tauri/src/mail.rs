@@ -0,0 +1,558 @@
+//! Local email client backend
+//!
+//! Account configuration, IMAP folder sync, message reading and SMTP sending
+//! for the Outlook Express clone. Folder listings and downloaded messages are
+//! cached under the app data directory so mail stays readable offline.
+//!
+//! Accounts are set up by admins. A stored password only stays with the
+//! servers and username it was entered for, and plaintext connections are
+//! only allowed to a relay on this machine.
+
+use chrono::Local;
+use mail_parser::MimeHeaders;
+use rustls::pki_types::ServerName;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::lazy::{self, Subsystem};
+use crate::{http, keyring, store};
+
+const ACCOUNTS_FILE: &str = "email_accounts.json";
+
+/// App data subdirectory holding per-account caches
+const MAIL_DIR: &str = "mail";
+
+/// Number of most recent messages synced per folder
+const SYNC_LIMIT: u32 = 200;
+
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Connection security for IMAP/SMTP
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MailSecurity {
+    /// Implicit TLS (IMAPS 993 / SMTPS 465)
+    #[default]
+    Tls,
+    /// STARTTLS upgrade, SMTP only (submission port 587)
+    StartTls,
+    /// Plaintext, for a relay on this machine only
+    None,
+}
+
+/// A configured mailbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailAccount {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub email: String,
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    pub imap_host: String,
+    pub imap_port: u16,
+    #[serde(default)]
+    pub imap_security: MailSecurity,
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub smtp_security: MailSecurity,
+}
+
+/// Message list entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageSummary {
+    pub uid: u32,
+    pub subject: String,
+    pub from: String,
+    pub date: i64,
+    pub size: u32,
+    pub seen: bool,
+}
+
+/// A full message ready for the preview pane
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EmailMessage {
+    pub uid: u32,
+    pub subject: String,
+    pub from: String,
+    pub to: Vec<String>,
+    pub date: i64,
+    pub text: String,
+    pub html: Option<String>,
+    pub attachments: Vec<String>,
+}
+
+/// An outgoing message
+#[derive(Debug, Deserialize)]
+pub struct EmailDraft {
+    pub account_id: String,
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+/// A file sent along with a message
+pub(crate) struct MailAttachment {
+    pub name: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Result of syncing one account
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncSummary {
+    pub folders: Vec<String>,
+    pub messages: usize,
+    pub unread: usize,
+    pub synced_at: i64,
+}
+
+/// Cached folder listing for one account
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MailCache {
+    folders: Vec<String>,
+    messages: HashMap<String, Vec<MessageSummary>>,
+}
+
+/// Configured email accounts
+pub struct MailState(Mutex<Vec<EmailAccount>>);
+
+impl Subsystem for MailState {
+    const NAME: &'static str = "mail";
+
+    fn load(app: &AppHandle) -> Self {
+        let mut accounts: Vec<EmailAccount> = store::load(app, ACCOUNTS_FILE);
+        // Older versions kept passwords in the accounts file; move them to the keyring
+        let legacy = accounts.iter().any(|account| !account.password.is_empty());
+        if legacy {
+            let _ = save_accounts(app, &accounts);
+        }
+        for account in accounts.iter_mut().filter(|account| account.password.is_empty()) {
+            account.password = keyring::get(app, &password_key(&account.id)).ok().flatten().unwrap_or_default();
+        }
+        MailState(Mutex::new(accounts))
+    }
+}
+
+type ImapSession = imap::Session<Box<dyn ReadWrite>>;
+
+trait ReadWrite: std::io::Read + std::io::Write + Send {}
+impl<T: std::io::Read + std::io::Write + Send> ReadWrite for T {}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn find_account(state: &MailState, id: &str) -> Result<EmailAccount, String> {
+    state
+        .0
+        .lock()
+        .expect("mail state lock")
+        .iter()
+        .find(|account| account.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Email account not found: {}", id))
+}
+
+fn password_key(account_id: &str) -> String {
+    format!("mail/{}", account_id)
+}
+
+/// Save accounts with passwords sealed in the keyring rather than the JSON file
+fn save_accounts(app: &AppHandle, accounts: &[EmailAccount]) -> Result<(), String> {
+    for account in accounts.iter().filter(|account| !account.password.is_empty()) {
+        keyring::put(app, &password_key(&account.id), &account.password)?;
+    }
+    let stripped: Vec<EmailAccount> = accounts
+        .iter()
+        .cloned()
+        .map(|account| EmailAccount {
+            password: String::new(),
+            ..account
+        })
+        .collect();
+    store::save(app, ACCOUNTS_FILE, &stripped)
+}
+
+/// Refuse plaintext to anything but a server on this machine
+fn check_security(host: &str, security: MailSecurity) -> Result<(), String> {
+    let loopback =
+        host.eq_ignore_ascii_case("localhost") || host.parse::<std::net::IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    if security == MailSecurity::None && !loopback {
+        return Err(format!("{} needs TLS; plaintext is only for a relay on this machine", host));
+    }
+    Ok(())
+}
+
+/// Whether the password of `old` would go somewhere else under `new`
+fn endpoints_changed(old: &EmailAccount, new: &EmailAccount) -> bool {
+    old.username != new.username
+        || !old.imap_host.eq_ignore_ascii_case(&new.imap_host)
+        || old.imap_port != new.imap_port
+        || !old.smtp_host.eq_ignore_ascii_case(&new.smtp_host)
+        || old.smtp_port != new.smtp_port
+}
+
+fn cache_file(account_id: &str) -> String {
+    format!("{}/{}.json", MAIL_DIR, account_id)
+}
+
+fn load_cache(app: &AppHandle, account_id: &str) -> MailCache {
+    store::load(app, &cache_file(account_id))
+}
+
+/// Path of a downloaded message, with the folder name made filesystem safe
+fn message_path(app: &AppHandle, account_id: &str, folder: &str, uid: u32) -> Result<PathBuf, String> {
+    let folder: String = folder
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    let dir = store::data_path(app, &format!("{}/{}/{}", MAIL_DIR, account_id, folder))?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(format!("{}.eml", uid)))
+}
+
+fn connect_imap(account: &EmailAccount) -> Result<ImapSession, String> {
+    check_security(&account.imap_host, account.imap_security)?;
+    let tcp = TcpStream::connect((account.imap_host.as_str(), account.imap_port))
+        .map_err(|e| format!("Failed to connect to {}: {}", account.imap_host, e))?;
+    tcp.set_read_timeout(Some(SOCKET_TIMEOUT)).map_err(|e| e.to_string())?;
+
+    let stream: Box<dyn ReadWrite> = match account.imap_security {
+        MailSecurity::Tls => {
+            let server_name = ServerName::try_from(account.imap_host.clone()).map_err(|e| e.to_string())?;
+            let connection =
+                rustls::ClientConnection::new(http::tls_config(), server_name).map_err(|e| e.to_string())?;
+            Box::new(rustls::StreamOwned::new(connection, tcp))
+        }
+        MailSecurity::StartTls => return Err("STARTTLS is not supported for IMAP; use port 993".to_string()),
+        MailSecurity::None => Box::new(tcp),
+    };
+
+    let mut client = imap::Client::new(stream);
+    client.read_greeting().map_err(|e| e.to_string())?;
+    client
+        .login(&account.username, &account.password)
+        .map_err(|(e, _)| format!("IMAP login failed: {}", e))
+}
+
+fn summarize(fetch: &imap::types::Fetch) -> Option<MessageSummary> {
+    let header = mail_parser::MessageParser::default().parse(fetch.header()?)?;
+    Some(MessageSummary {
+        uid: fetch.uid?,
+        subject: header.subject().unwrap_or_default().to_string(),
+        from: format_address(header.from()),
+        date: header.date().map(|date| date.to_timestamp()).unwrap_or_default(),
+        size: fetch.size.unwrap_or_default(),
+        seen: fetch.flags().contains(&imap::types::Flag::Seen),
+    })
+}
+
+fn format_address(address: Option<&mail_parser::Address>) -> String {
+    address
+        .and_then(|address| address.first())
+        .map(|addr| match (addr.name(), addr.address()) {
+            (Some(name), Some(email)) => format!("{} <{}>", name, email),
+            (name, email) => name.or(email).unwrap_or_default().to_string(),
+        })
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List configured accounts; passwords are never sent to the frontend
+#[tauri::command]
+pub fn list_email_accounts(app: AppHandle) -> Result<Vec<EmailAccount>, KioskError> {
+    let state = lazy::get::<MailState>(&app)?;
+    Ok(state
+        .0
+        .lock()
+        .expect("mail state lock")
+        .iter()
+        .cloned()
+        .map(|account| EmailAccount {
+            password: String::new(),
+            ..account
+        })
+        .collect())
+}
+
+/// Create or update an account (admin). An empty password keeps the stored
+/// one, unless a server, port or the username changed; then it is dropped
+/// and has to be entered again.
+#[tauri::command]
+pub fn save_email_account(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    mut account: EmailAccount,
+) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let state = lazy::get::<MailState>(&app)?;
+    check_security(&account.imap_host, account.imap_security).map_err(KioskError::invalid)?;
+    check_security(&account.smtp_host, account.smtp_security).map_err(KioskError::invalid)?;
+    let mut accounts = state.0.lock().expect("mail state lock");
+
+    match accounts.iter_mut().find(|existing| existing.id == account.id && !account.id.is_empty()) {
+        Some(existing) => {
+            if account.password.is_empty() {
+                if endpoints_changed(existing, &account) {
+                    keyring::remove(&app, &password_key(&existing.id))?;
+                } else {
+                    account.password = std::mem::take(&mut existing.password);
+                }
+            }
+            *existing = account.clone();
+        }
+        None => {
+            account.id = uuid::Uuid::new_v4().to_string();
+            accounts.push(account.clone());
+        }
+    }
+
+    save_accounts(&app, &accounts)?;
+    Ok(account.id)
+}
+
+/// Remove an account and its local cache (admin)
+#[tauri::command]
+pub fn delete_email_account(app: AppHandle, auth: State<'_, AuthState>, id: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let state = lazy::get::<MailState>(&app)?;
+    let mut accounts = state.0.lock().expect("mail state lock");
+    accounts.retain(|account| account.id != id);
+    save_accounts(&app, &accounts)?;
+    keyring::remove(&app, &password_key(&id))?;
+
+    let cache_dir = store::data_path(&app, &format!("{}/{}", MAIL_DIR, id))?;
+    let _ = fs::remove_dir_all(cache_dir);
+    let _ = fs::remove_file(store::data_path(&app, &cache_file(&id))?);
+    Ok(())
+}
+
+/// Sync the folder list and recent message headers from the IMAP server
+#[tauri::command(async)]
+pub fn sync_email(
+    app: AppHandle,
+    account_id: String,
+    folders: Option<Vec<String>>,
+) -> Result<SyncSummary, KioskError> {
+    let state = lazy::get::<MailState>(&app)?;
+    let account = find_account(state, &account_id)?;
+    let mut session = connect_imap(&account)?;
+    let mut cache = load_cache(&app, &account_id);
+
+    cache.folders = session
+        .list(Some(""), Some("*"))
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|name| name.name().to_string())
+        .collect();
+
+    let folders = folders.unwrap_or_else(|| vec!["INBOX".to_string()]);
+    for folder in &folders {
+        let mailbox = session.examine(folder).map_err(|e| e.to_string())?;
+        if mailbox.exists == 0 {
+            cache.messages.insert(folder.clone(), Vec::new());
+            continue;
+        }
+
+        let first = mailbox.exists.saturating_sub(SYNC_LIMIT - 1).max(1);
+        let fetches = session
+            .fetch(format!("{}:*", first), "(UID FLAGS RFC822.SIZE BODY.PEEK[HEADER])")
+            .map_err(|e| e.to_string())?;
+
+        let mut messages: Vec<MessageSummary> = fetches.iter().filter_map(summarize).collect();
+        messages.sort_by_key(|message| std::cmp::Reverse(message.date));
+        cache.messages.insert(folder.clone(), messages);
+    }
+
+    let _ = session.logout();
+    store::save(&app, &cache_file(&account_id), &cache)?;
+
+    let synced: Vec<&MessageSummary> = folders
+        .iter()
+        .filter_map(|folder| cache.messages.get(folder))
+        .flatten()
+        .collect();
+
+    Ok(SyncSummary {
+        folders: cache.folders.clone(),
+        messages: synced.len(),
+        unread: synced.iter().filter(|message| !message.seen).count(),
+        synced_at: Local::now().timestamp(),
+    })
+}
+
+/// Cached folder names for an account
+#[tauri::command]
+pub fn list_email_folders(app: AppHandle, account_id: String) -> Result<Vec<String>, KioskError> {
+    lazy::require(&app, MailState::NAME)?;
+    Ok(load_cache(&app, &account_id).folders)
+}
+
+/// Cached message list for a folder, newest first
+#[tauri::command]
+pub fn list_messages(
+    app: AppHandle,
+    account_id: String,
+    folder: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Vec<MessageSummary>, KioskError> {
+    lazy::require(&app, MailState::NAME)?;
+    Ok(load_cache(&app, &account_id)
+        .messages
+        .remove(&folder)
+        .unwrap_or_default()
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(50))
+        .collect())
+}
+
+/// Read a message, downloading it on first open and serving it from cache after
+#[tauri::command(async)]
+pub fn read_message(
+    app: AppHandle,
+    account_id: String,
+    folder: String,
+    uid: u32,
+) -> Result<EmailMessage, KioskError> {
+    let state = lazy::get::<MailState>(&app)?;
+    let path = message_path(&app, &account_id, &folder, uid)?;
+
+    let raw = match fs::read(&path) {
+        Ok(raw) => raw,
+        Err(_) => {
+            let account = find_account(state, &account_id)?;
+            let mut session = connect_imap(&account)?;
+            session.select(&folder).map_err(|e| e.to_string())?;
+
+            let fetches = session
+                .uid_fetch(uid.to_string(), "BODY[]")
+                .map_err(|e| e.to_string())?;
+            let raw = fetches
+                .iter()
+                .find_map(|fetch| fetch.body().map(<[u8]>::to_vec))
+                .ok_or_else(|| format!("Message {} not found", uid))?;
+            let _ = session.logout();
+
+            fs::write(&path, &raw).map_err(|e| e.to_string())?;
+
+            // Fetching BODY[] marks the message read on the server; mirror that locally
+            let mut cache = load_cache(&app, &account_id);
+            if let Some(message) = cache
+                .messages
+                .get_mut(&folder)
+                .and_then(|messages| messages.iter_mut().find(|message| message.uid == uid))
+            {
+                message.seen = true;
+                store::save(&app, &cache_file(&account_id), &cache)?;
+            }
+
+            raw
+        }
+    };
+
+    let message = mail_parser::MessageParser::default()
+        .parse(&raw)
+        .ok_or("Failed to parse message")?;
+
+    Ok(EmailMessage {
+        uid,
+        subject: message.subject().unwrap_or_default().to_string(),
+        from: format_address(message.from()),
+        to: message
+            .to()
+            .map(|to| to.iter().filter_map(|addr| addr.address().map(str::to_string)).collect())
+            .unwrap_or_default(),
+        date: message.date().map(|date| date.to_timestamp()).unwrap_or_default(),
+        text: message.body_text(0).unwrap_or_default().to_string(),
+        html: message.body_html(0).map(|html| html.to_string()),
+        attachments: message
+            .attachments()
+            .filter_map(|part| part.attachment_name().map(str::to_string))
+            .collect(),
+    })
+}
+
+/// Send a plain-text message with files attached through the account's
+/// SMTP server
+pub(crate) fn send(app: &AppHandle, draft: EmailDraft, attachments: Vec<MailAttachment>) -> Result<(), KioskError> {
+    use lettre::message::header::ContentType;
+    use lettre::message::{Attachment, MultiPart, SinglePart};
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{Message, SmtpTransport, Transport};
+
+    let state = lazy::get::<MailState>(app)?;
+    let account = find_account(state, &draft.account_id)?;
+    if draft.to.is_empty() {
+        return Err(KioskError::invalid("Message has no recipients"));
+    }
+
+    let from = format!("{} <{}>", account.name, account.email);
+    let mut builder = Message::builder()
+        .from(from.parse().map_err(|e| format!("Invalid sender: {}", e))?)
+        .subject(draft.subject);
+
+    for to in &draft.to {
+        builder = builder.to(to.parse().map_err(|e| format!("Invalid recipient {}: {}", to, e))?);
+    }
+    for cc in &draft.cc {
+        builder = builder.cc(cc.parse().map_err(|e| format!("Invalid recipient {}: {}", cc, e))?);
+    }
+
+    let message = if attachments.is_empty() {
+        builder.header(ContentType::TEXT_PLAIN).body(draft.body)
+    } else {
+        let parts = attachments.into_iter().fold(
+            MultiPart::mixed().singlepart(SinglePart::plain(draft.body)),
+            |parts, attachment| {
+                let content_type = ContentType::parse(&attachment.content_type)
+                    .unwrap_or_else(|_| ContentType::parse("application/octet-stream").expect("content type"));
+                parts.singlepart(Attachment::new(attachment.name).body(attachment.data, content_type))
+            },
+        );
+        builder.multipart(parts)
+    }
+    .map_err(|e| e.to_string())?;
+
+    check_security(&account.smtp_host, account.smtp_security)?;
+    let transport = match account.smtp_security {
+        MailSecurity::Tls => SmtpTransport::relay(&account.smtp_host),
+        MailSecurity::StartTls => SmtpTransport::starttls_relay(&account.smtp_host),
+        MailSecurity::None => Ok(SmtpTransport::builder_dangerous(&account.smtp_host)),
+    }
+    .map_err(|e| e.to_string())?
+    .port(account.smtp_port)
+    .credentials(Credentials::new(account.username, account.password))
+    .timeout(Some(SOCKET_TIMEOUT))
+    .build();
+
+    transport.send(&message).map_err(|e| format!("Failed to send: {}", e))?;
+    Ok(())
+}
+
+/// Send a plain-text message through the account's SMTP server
+#[tauri::command(async)]
+pub fn send_email(app: AppHandle, draft: EmailDraft) -> Result<(), KioskError> {
+    send(&app, draft, Vec::new())
+}
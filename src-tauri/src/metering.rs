@@ -0,0 +1,340 @@
+//! Data usage metering
+//!
+//! Adds up bytes sent and received per network interface from the kernel
+//! counters into daily totals in `data-usage.json`, kept for a bit over a
+//! year. Interfaces matching `metered` (the LTE modem by default) count
+//! toward a monthly cap; past `warn_percent` of it `data-cap-warning` is
+//! published, and once it is used up `data-cap-exceeded`, after which
+//! non-essential background transfers (ticker and weather polling,
+//! background downloads) hold off until the next billing period. Traffic
+//! while the app is not running is not counted.
+
+use chrono::{Datelike, Local, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{config, events, store};
+
+const USAGE_FILE: &str = "data-usage.json";
+
+const CONFIG_FILE: &str = "data-usage-config.json";
+
+const DAYS_KEPT: usize = 400;
+
+const NET_DIR: &str = "/sys/class/net";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Totals are written out this often, to spare the SD card
+const SAVE_INTERVAL: Duration = Duration::from_secs(600);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ByteCounts {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl ByteCounts {
+    fn add(&mut self, other: ByteCounts) {
+        self.rx_bytes += other.rx_bytes;
+        self.tx_bytes += other.tx_bytes;
+    }
+
+    fn total(&self) -> u64 {
+        self.rx_bytes + self.tx_bytes
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DataUsageConfig {
+    /// Interface name prefixes that count toward the cap
+    pub metered: Vec<String>,
+    /// Monthly allowance on metered interfaces; no cap when unset
+    pub monthly_cap_mb: Option<u64>,
+    /// Day of the month the allowance renews (1-28)
+    pub billing_day: u32,
+    /// Percentage of the cap that triggers `data-cap-warning`
+    pub warn_percent: u32,
+}
+
+impl Default for DataUsageConfig {
+    fn default() -> Self {
+        DataUsageConfig {
+            metered: vec!["wwan".to_string(), "ppp".to_string()],
+            monthly_cap_mb: None,
+            billing_day: 1,
+            warn_percent: 80,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageDay {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    pub interfaces: BTreeMap<String, ByteCounts>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataUsage {
+    pub from: String,
+    pub to: String,
+    pub days: Vec<UsageDay>,
+    /// Per interface over the whole range
+    pub totals: BTreeMap<String, ByteCounts>,
+}
+
+/// Payload of `data-cap-warning` and `data-cap-exceeded`
+#[derive(Debug, Clone, Serialize)]
+pub struct DataCapStatus {
+    pub cap_bytes: Option<u64>,
+    /// Metered bytes this billing period
+    pub used_bytes: u64,
+    /// First day of the billing period, "YYYY-MM-DD"
+    pub period_start: String,
+    pub exceeded: bool,
+}
+
+/// Warnings already published, so each fires once per billing period
+#[derive(Default)]
+struct Alerts {
+    period_start: String,
+    warned: bool,
+}
+
+pub struct MeteringState {
+    config: Mutex<DataUsageConfig>,
+    /// Keyed by "YYYY-MM-DD", then interface
+    days: Mutex<BTreeMap<String, BTreeMap<String, ByteCounts>>>,
+    exceeded: AtomicBool,
+    alerts: Mutex<Alerts>,
+}
+
+impl MeteringState {
+    pub fn load(app: &AppHandle) -> Self {
+        MeteringState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            days: Mutex::new(store::load(app, USAGE_FILE)),
+            exceeded: AtomicBool::new(false),
+            alerts: Mutex::new(Alerts::default()),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Not a date (YYYY-MM-DD): {}", date))
+}
+
+fn range(from: Option<String>, to: Option<String>) -> Result<(String, String), KioskError> {
+    let today = Local::now().date_naive();
+    let to = to.as_deref().map(parse_date).transpose()?.unwrap_or(today);
+    let from = from.as_deref().map(parse_date).transpose()?.unwrap_or(to);
+    if from > to {
+        return Err(KioskError::invalid("The range starts after it ends"));
+    }
+    Ok((from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string()))
+}
+
+/// Kernel byte counters of every interface but loopback
+fn read_counters() -> HashMap<String, ByteCounts> {
+    let Ok(entries) = fs::read_dir(NET_DIR) else { return HashMap::new() };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let read = |file: &str| {
+                fs::read_to_string(entry.path().join("statistics").join(file))
+                    .ok()
+                    .and_then(|text| text.trim().parse().ok())
+            };
+            let counts = ByteCounts {
+                rx_bytes: read("rx_bytes")?,
+                tx_bytes: read("tx_bytes")?,
+            };
+            (name != "lo").then_some((name, counts))
+        })
+        .collect()
+}
+
+/// First day of the billing period containing `today`
+fn period_start(today: NaiveDate, billing_day: u32) -> NaiveDate {
+    let this_month = today.with_day(billing_day).unwrap_or(today);
+    if today >= this_month {
+        this_month
+    } else {
+        this_month.checked_sub_months(Months::new(1)).unwrap_or(this_month)
+    }
+}
+
+fn cap_status(config: &DataUsageConfig, days: &BTreeMap<String, BTreeMap<String, ByteCounts>>) -> DataCapStatus {
+    let start = period_start(Local::now().date_naive(), config.billing_day)
+        .format("%Y-%m-%d")
+        .to_string();
+    let used_bytes = days
+        .range(start.clone()..)
+        .flat_map(|(_, interfaces)| interfaces.iter())
+        .filter(|(name, _)| config.metered.iter().any(|prefix| name.starts_with(prefix.as_str())))
+        .map(|(_, counts)| counts.total())
+        .sum();
+    let cap_bytes = config.monthly_cap_mb.map(|mb| mb * 1024 * 1024);
+    DataCapStatus {
+        cap_bytes,
+        used_bytes,
+        period_start: start,
+        exceeded: cap_bytes.is_some_and(|cap| used_bytes >= cap),
+    }
+}
+
+/// Re-check the cap, publishing a warning or the cap being hit once each
+fn check_cap(app: &AppHandle, state: &MeteringState) {
+    let config = state.config.lock().expect("data usage config lock").clone();
+    let status = cap_status(&config, &state.days.lock().expect("data usage lock"));
+    let was_exceeded = state.exceeded.swap(status.exceeded, Ordering::Relaxed);
+
+    let mut alerts = state.alerts.lock().expect("data usage alerts lock");
+    if alerts.period_start != status.period_start {
+        *alerts = Alerts {
+            period_start: status.period_start.clone(),
+            warned: false,
+        };
+    }
+    let warn = status
+        .cap_bytes
+        .is_some_and(|cap| status.used_bytes as f64 >= cap as f64 * config.warn_percent as f64 / 100.0);
+    let warning = warn && !alerts.warned && !status.exceeded;
+    alerts.warned |= warn;
+    drop(alerts);
+
+    if status.exceeded && !was_exceeded {
+        events::publish(app, "data-cap-exceeded", status);
+    } else if warning {
+        events::publish(app, "data-cap-warning", status);
+    }
+}
+
+/// Whether non-essential background transfers may run: false while the
+/// monthly data cap is used up
+pub(crate) fn background_allowed(app: &AppHandle) -> bool {
+    app.try_state::<MeteringState>()
+        .map_or(true, |state| !state.exceeded.load(Ordering::Relaxed))
+}
+
+/// Spawn the sampler that adds up interface counters
+pub fn start_metering(app: AppHandle) {
+    std::thread::spawn(move || {
+        let state = app.state::<MeteringState>();
+        check_cap(&app, &state);
+        let mut last = read_counters();
+        let mut last_save = Instant::now();
+        loop {
+            std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+            let counters = read_counters();
+            let date = Local::now().format("%Y-%m-%d").to_string();
+            {
+                let mut days = state.days.lock().expect("data usage lock");
+                let day = days.entry(date).or_default();
+                for (name, now) in &counters {
+                    let before = last.get(name).copied().unwrap_or_default();
+                    // Counters restart from zero when an interface comes back
+                    let delta = if now.rx_bytes >= before.rx_bytes && now.tx_bytes >= before.tx_bytes {
+                        ByteCounts {
+                            rx_bytes: now.rx_bytes - before.rx_bytes,
+                            tx_bytes: now.tx_bytes - before.tx_bytes,
+                        }
+                    } else {
+                        *now
+                    };
+                    day.entry(name.clone()).or_default().add(delta);
+                }
+                while days.len() > DAYS_KEPT {
+                    days.pop_first();
+                }
+                if last_save.elapsed() >= SAVE_INTERVAL {
+                    let _ = store::save(&app, USAGE_FILE, &*days);
+                    last_save = Instant::now();
+                }
+            }
+            last = counters;
+            check_cap(&app, &state);
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Bytes per interface per day from `from` to `to` (inclusive,
+/// "YYYY-MM-DD"); today when neither is given
+#[tauri::command]
+pub fn get_data_usage(
+    state: State<'_, MeteringState>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<DataUsage, KioskError> {
+    let (from, to) = range(from, to)?;
+    let days = state.days.lock().expect("data usage lock");
+    let mut totals: BTreeMap<String, ByteCounts> = BTreeMap::new();
+    let days = days
+        .range(from.clone()..=to.clone())
+        .map(|(date, interfaces)| {
+            for (name, counts) in interfaces {
+                totals.entry(name.clone()).or_default().add(*counts);
+            }
+            UsageDay {
+                date: date.clone(),
+                interfaces: interfaces.clone(),
+            }
+        })
+        .collect();
+    Ok(DataUsage { from, to, days, totals })
+}
+
+/// Metered usage against the cap this billing period
+#[tauri::command]
+pub fn get_data_cap_status(state: State<'_, MeteringState>) -> DataCapStatus {
+    let config = state.config.lock().expect("data usage config lock").clone();
+    cap_status(&config, &state.days.lock().expect("data usage lock"))
+}
+
+#[tauri::command]
+pub fn get_data_usage_config(state: State<'_, MeteringState>) -> DataUsageConfig {
+    state.config.lock().expect("data usage config lock").clone()
+}
+
+/// Update the metered interfaces and monthly cap (admin)
+#[tauri::command]
+pub fn set_data_usage_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, MeteringState>,
+    config: DataUsageConfig,
+) -> Result<DataCapStatus, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if !(1..=28).contains(&config.billing_day) {
+        return Err(KioskError::invalid("The billing day is 1 to 28"));
+    }
+    if !(1..=100).contains(&config.warn_percent) {
+        return Err(KioskError::invalid("Warn at 1 to 100 percent of the cap"));
+    }
+    store::save(&app, CONFIG_FILE, &config)?;
+    *state.config.lock().expect("data usage config lock") = config;
+    check_cap(&app, &state);
+    Ok(get_data_cap_status(state))
+}
@@ -0,0 +1,532 @@
+//! Microphone recording and level metering
+//!
+//! Records from a microphone into a file on one of the desktop's locations,
+//! for the Sound Recorder accessory and voice notes. Audio is captured as
+//! 48 kHz mono PCM through PulseAudio or PipeWire (`parec`) when a sound
+//! server is running and ALSA (`arecord`) otherwise. WAV files are written
+//! directly; FLAC, Ogg Vorbis, Opus and MP3 are encoded by ffmpeg. While
+//! recording, the peak and RMS level of every tenth of a second is
+//! published as `audio-level` for a meter. The recording stops when asked,
+//! after three hours, when the location runs out of quota or when the
+//! capture ends, and is published as `audio-recording-finished`. Playback
+//! devices are listed here too, for routing call audio.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+use crate::{events, quota};
+
+pub(crate) const SAMPLE_RATE: u32 = 48000;
+
+/// Audio handled at a time, and one level reading (100 ms of 16-bit samples)
+const CHUNK_BYTES: usize = SAMPLE_RATE as usize / 10 * 2;
+
+const MAX_DURATION: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// How often the file is announced as changed and the location's quota
+/// checked while recording
+const QUOTA_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How long the capture gets to notice a stop before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioDevice {
+    /// `pulse:<source or sink>` or `alsa:<device>`
+    pub id: String,
+    pub name: String,
+    pub default: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Flac,
+    Ogg,
+    Opus,
+    Mp3,
+}
+
+impl AudioFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => "wav",
+            AudioFormat::Flac => "flac",
+            AudioFormat::Ogg => "ogg",
+            AudioFormat::Opus => "opus",
+            AudioFormat::Mp3 => "mp3",
+        }
+    }
+
+    /// ffmpeg's encoder and its options
+    fn encoder(self) -> &'static [&'static str] {
+        match self {
+            AudioFormat::Wav => &["-c:a", "pcm_s16le"],
+            AudioFormat::Flac => &["-c:a", "flac"],
+            AudioFormat::Ogg => &["-c:a", "libvorbis", "-q:a", "4"],
+            AudioFormat::Opus => &["-c:a", "libopus", "-b:a", "64k"],
+            AudioFormat::Mp3 => &["-c:a", "libmp3lame", "-q:a", "4"],
+        }
+    }
+}
+
+/// Payload of `audio-level`, both 0 to 1 of full scale
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioLevel {
+    pub peak: f64,
+    pub rms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioRecording {
+    /// Virtual path of the file
+    pub path: String,
+    pub format: AudioFormat,
+    pub size: u64,
+    pub duration_secs: f64,
+    /// Why it stopped: "stopped", "time limit", "storage full" or "capture ended"
+    pub reason: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioRecordingStatus {
+    pub path: String,
+    pub format: AudioFormat,
+    pub device: Option<String>,
+    pub elapsed_secs: f64,
+}
+
+struct Active {
+    status: AudioRecordingStatus,
+    started: Instant,
+    stop: Arc<AtomicBool>,
+    /// The capture process, killed if it stops sending audio
+    capture: u32,
+    worker: JoinHandle<AudioRecording>,
+}
+
+#[derive(Default)]
+pub struct MicrophoneState(Mutex<Option<Active>>);
+
+/// Where the samples go
+enum Sink {
+    /// A WAV file whose sizes are filled in at the end
+    Wav(BufWriter<File>),
+    Encoder(Child, ChildStdin),
+}
+
+// ============================================================================
+// Devices
+// ============================================================================
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).stderr(Stdio::null()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Devices from `pactl list sources` or `pactl list sinks`, without the
+/// monitors of outputs
+fn parse_pulse_devices(text: &str, default: &str) -> Vec<AudioDevice> {
+    let mut inputs = Vec::new();
+    let mut name: Option<String> = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(value) = line.strip_prefix("Name: ") {
+            name = Some(value.to_string());
+        } else if let Some(description) = line.strip_prefix("Description: ") {
+            let Some(name) = name.take().filter(|name| !name.ends_with(".monitor")) else {
+                continue;
+            };
+            inputs.push(AudioDevice {
+                id: format!("pulse:{}", name),
+                name: description.to_string(),
+                default: name == default,
+            });
+        }
+    }
+    inputs
+}
+
+/// Devices from `arecord -l` or `aplay -l`, e.g.
+/// `card 1: Device [USB Audio Device], device 0: USB Audio [USB Audio]`
+fn parse_alsa_devices(text: &str) -> Vec<AudioDevice> {
+    let bracketed = |text: &str| Some(text.split_once('[')?.1.split_once(']')?.0.trim().to_string());
+    text.lines()
+        .filter_map(|line| {
+            let (card, device) = line.strip_prefix("card ")?.split_once(", device ")?;
+            let card_number = card.split(':').next()?.trim().parse::<u32>().ok()?;
+            let device_number = device.split(':').next()?.trim().parse::<u32>().ok()?;
+            Some(AudioDevice {
+                id: format!("alsa:plughw:{},{}", card_number, device_number),
+                name: format!("{} - {}", bracketed(card)?, bracketed(device)?),
+                default: false,
+            })
+        })
+        .collect()
+}
+
+fn pulse_running() -> bool {
+    output("pactl", &["info"]).is_some()
+}
+
+/// Capture devices, or playback devices when `playback` is set
+fn devices(playback: bool) -> Vec<AudioDevice> {
+    let (kind, alsa) = if playback { ("sink", "aplay") } else { ("source", "arecord") };
+    if let Some(list) = output("pactl", &["list", &format!("{}s", kind)]) {
+        let default = output("pactl", &[&format!("get-default-{}", kind)])
+            .map(|name| name.trim().to_string())
+            .unwrap_or_default();
+        return parse_pulse_devices(&list, &default);
+    }
+    let mut devices = parse_alsa_devices(&output(alsa, &["-l"]).unwrap_or_default());
+    if let Some(first) = devices.first_mut() {
+        first.default = true;
+    }
+    devices
+}
+
+/// Start capturing 16-bit mono PCM on stdout
+pub(crate) fn capture(device: Option<&str>) -> Result<Child, String> {
+    let rate = SAMPLE_RATE.to_string();
+    let pulse = match device {
+        Some(device) => device.starts_with("pulse:"),
+        None => pulse_running(),
+    };
+    let mut command = if pulse {
+        let mut command = Command::new("parec");
+        command.args(["--raw", "--format=s16le", "--channels=1", &format!("--rate={}", rate)]);
+        if let Some(source) = device.and_then(|device| device.strip_prefix("pulse:")) {
+            command.arg(format!("--device={}", source));
+        }
+        command
+    } else {
+        let mut command = Command::new("arecord");
+        command.args(["-q", "-t", "raw", "-f", "S16_LE", "-c", "1", "-r", &rate]);
+        if let Some(device) = device {
+            let device = device.strip_prefix("alsa:").ok_or_else(|| format!("Unknown audio input: {}", device))?;
+            command.args(["-D", device]);
+        }
+        command
+    };
+    let program = if pulse { "parec" } else { "arecord" };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+/// Start playing 16-bit mono PCM from stdin
+pub(crate) fn playback(device: Option<&str>) -> Result<Child, String> {
+    let rate = SAMPLE_RATE.to_string();
+    let pulse = match device {
+        Some(device) => device.starts_with("pulse:"),
+        None => pulse_running(),
+    };
+    let mut command = if pulse {
+        let mut command = Command::new("pacat");
+        command.args(["--playback", "--raw", "--format=s16le", "--channels=1", &format!("--rate={}", rate)]);
+        if let Some(sink) = device.and_then(|device| device.strip_prefix("pulse:")) {
+            command.arg(format!("--device={}", sink));
+        }
+        command
+    } else {
+        let mut command = Command::new("aplay");
+        command.args(["-q", "-t", "raw", "-f", "S16_LE", "-c", "1", "-r", &rate]);
+        if let Some(device) = device {
+            let device = device.strip_prefix("alsa:").ok_or_else(|| format!("Unknown audio output: {}", device))?;
+            command.args(["-D", device]);
+        }
+        command
+    };
+    let program = if pulse { "pacat" } else { "aplay" };
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+// ============================================================================
+// Files
+// ============================================================================
+
+/// A 16-bit mono WAV header for `data_bytes` of samples
+fn wav_header(data_bytes: u32) -> [u8; 44] {
+    let mut header = [0u8; 44];
+    let mut at = 0;
+    let mut put = |bytes: &[u8]| {
+        header[at..at + bytes.len()].copy_from_slice(bytes);
+        at += bytes.len();
+    };
+    put(b"RIFF");
+    put(&data_bytes.saturating_add(36).to_le_bytes());
+    put(b"WAVEfmt ");
+    put(&16u32.to_le_bytes());
+    // PCM, one channel
+    put(&1u16.to_le_bytes());
+    put(&1u16.to_le_bytes());
+    put(&SAMPLE_RATE.to_le_bytes());
+    put(&(SAMPLE_RATE * 2).to_le_bytes());
+    put(&2u16.to_le_bytes());
+    put(&16u16.to_le_bytes());
+    put(b"data");
+    put(&data_bytes.to_le_bytes());
+    header
+}
+
+fn open_sink(path: &Path, format: AudioFormat) -> Result<Sink, String> {
+    if format == AudioFormat::Wav {
+        let mut file = BufWriter::new(File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?);
+        file.write_all(&wav_header(0)).map_err(|e| e.to_string())?;
+        return Ok(Sink::Wav(file));
+    }
+    let rate = SAMPLE_RATE.to_string();
+    let mut child = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", "s16le", "-ar", &rate, "-ac", "1", "-i", "-"])
+        .args(format.encoder())
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+    let stdin = child.stdin.take().ok_or("ffmpeg has no input")?;
+    Ok(Sink::Encoder(child, stdin))
+}
+
+fn write_sink(sink: &mut Sink, samples: &[u8]) -> Result<(), String> {
+    match sink {
+        Sink::Wav(file) => file.write_all(samples),
+        Sink::Encoder(_, stdin) => stdin.write_all(samples),
+    }
+    .map_err(|e| format!("Failed to write the recording: {}", e))
+}
+
+/// Fill in the WAV sizes, or let ffmpeg finish the file
+fn close_sink(sink: Sink, data_bytes: u64) {
+    match sink {
+        Sink::Wav(file) => {
+            if let Ok(mut file) = file.into_inner() {
+                let header = wav_header(u32::try_from(data_bytes).unwrap_or(u32::MAX));
+                let _ = file.seek(SeekFrom::Start(0)).and_then(|_| file.write_all(&header));
+            }
+        }
+        Sink::Encoder(mut child, stdin) => {
+            drop(stdin);
+            let _ = child.wait();
+        }
+    }
+}
+
+pub(crate) fn level(samples: &[u8]) -> AudioLevel {
+    let values: Vec<f64> = samples
+        .chunks_exact(2)
+        .map(|pair| f64::from(i16::from_le_bytes([pair[0], pair[1]])) / 32768.0)
+        .collect();
+    if values.is_empty() {
+        return AudioLevel { peak: 0.0, rms: 0.0 };
+    }
+    let peak = values.iter().fold(0.0f64, |peak, value| peak.max(value.abs()));
+    let rms = (values.iter().map(|value| value * value).sum::<f64>() / values.len() as f64).sqrt();
+    AudioLevel { peak, rms }
+}
+
+/// The file so far, checked against the location's quota
+fn check_quota(app: &AppHandle, root: &vfs::Root, path: &str) -> bool {
+    // Announcing the change makes the quota measure the file afresh
+    vfs::changed(app, path, "write");
+    quota::check(app, root, 0).is_ok()
+}
+
+/// Move the capture into the file until stopped or a limit is reached
+fn record(
+    app: &AppHandle,
+    mut capture: Child,
+    mut sink: Sink,
+    root: &vfs::Root,
+    path: &str,
+    stop: &AtomicBool,
+) -> &'static str {
+    let Some(mut audio) = capture.stdout.take() else {
+        return "capture ended";
+    };
+    let started = Instant::now();
+    let mut quota_checked = Instant::now();
+    let mut buffer = vec![0u8; CHUNK_BYTES];
+    let mut written = 0u64;
+    let reason = loop {
+        if stop.load(Ordering::SeqCst) {
+            break "stopped";
+        }
+        if started.elapsed() >= MAX_DURATION {
+            break "time limit";
+        }
+        if quota_checked.elapsed() >= QUOTA_INTERVAL {
+            quota_checked = Instant::now();
+            if !check_quota(app, root, path) {
+                break "storage full";
+            }
+        }
+        if audio.read_exact(&mut buffer).is_err() || write_sink(&mut sink, &buffer).is_err() {
+            break "capture ended";
+        }
+        written += buffer.len() as u64;
+        events::publish_progress(app, "audio-level", level(&buffer));
+    };
+    let _ = capture.kill();
+    let _ = capture.wait();
+    close_sink(sink, written);
+    reason
+}
+
+/// Stop the worker and wait for the finished file
+fn finish(active: Active) -> AudioRecording {
+    active.stop.store(true, Ordering::SeqCst);
+    let deadline = Instant::now() + STOP_TIMEOUT;
+    while !active.worker.is_finished() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    if !active.worker.is_finished() {
+        // SAFETY: kill has no memory safety requirements; the capture is
+        // not reaped until the worker ends, so its pid is still its own
+        unsafe { libc::kill(active.capture as i32, libc::SIGKILL) };
+    }
+    let status = active.status;
+    active.worker.join().unwrap_or_else(|_| AudioRecording {
+        path: status.path,
+        format: status.format,
+        size: 0,
+        duration_secs: active.started.elapsed().as_secs_f64(),
+        reason: "capture ended".to_string(),
+        created_at: Local::now().timestamp(),
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Microphones and other capture devices
+#[tauri::command]
+pub fn list_audio_inputs() -> Vec<AudioDevice> {
+    devices(false)
+}
+
+/// Speakers and other playback devices
+#[tauri::command]
+pub fn list_audio_outputs() -> Vec<AudioDevice> {
+    devices(true)
+}
+
+/// Start recording to a virtual path such as `documents/Note.ogg`, whose
+/// extension must suit the format, from `device` (an id from
+/// `list_audio_inputs`) or the default input
+#[tauri::command]
+pub fn start_recording(
+    app: AppHandle,
+    state: State<'_, MicrophoneState>,
+    path: String,
+    format: AudioFormat,
+    device: Option<String>,
+) -> Result<AudioRecordingStatus, KioskError> {
+    let mut current = state.0.lock().expect("microphone lock");
+    if let Some(active) = current.take() {
+        if !active.worker.is_finished() {
+            *current = Some(active);
+            return Err(KioskError::invalid("A recording is already running"));
+        }
+        finish(active);
+    }
+    let extension = Path::new(&path).extension().and_then(|extension| extension.to_str()).unwrap_or("");
+    if !extension.eq_ignore_ascii_case(format.extension()) {
+        return Err(KioskError::invalid(format!("The file must end in .{}", format.extension())));
+    }
+    vfs::resolve_write(&app, &path, 0)?;
+    let (root, real) = vfs::resolve_in(&app, &path, Access::Write)?;
+
+    let mut capture = capture(device.as_deref())?;
+    let sink = match open_sink(&real, format) {
+        Ok(sink) => sink,
+        Err(e) => {
+            let _ = capture.kill();
+            let _ = capture.wait();
+            return Err(e.into());
+        }
+    };
+    let created_at = Local::now().timestamp();
+    let stop = Arc::new(AtomicBool::new(false));
+    let started = Instant::now();
+    let capture_pid = capture.id();
+    let worker = {
+        let (app, stop, path) = (app.clone(), stop.clone(), path.clone());
+        std::thread::spawn(move || {
+            let reason = record(&app, capture, sink, &root, &path, &stop);
+            let recording = AudioRecording {
+                path,
+                format,
+                size: quota::measure(&real),
+                duration_secs: started.elapsed().as_secs_f64(),
+                reason: reason.to_string(),
+                created_at,
+            };
+            vfs::changed(&app, &recording.path, "write");
+            events::publish(&app, "audio-recording-finished", &recording);
+            recording
+        })
+    };
+    let status = AudioRecordingStatus {
+        path,
+        format,
+        device,
+        elapsed_secs: 0.0,
+    };
+    *current = Some(Active {
+        status: status.clone(),
+        started,
+        stop,
+        capture: capture_pid,
+        worker,
+    });
+    events::publish(&app, "audio-recording-started", &status);
+    Ok(status)
+}
+
+/// Stop the recording and return the finished file
+#[tauri::command]
+pub fn stop_recording(state: State<'_, MicrophoneState>) -> Result<AudioRecording, KioskError> {
+    let active = state.0.lock().expect("microphone lock").take();
+    let active = active.ok_or_else(|| KioskError::invalid("No recording is running"))?;
+    Ok(finish(active))
+}
+
+/// The running recording, if any
+#[tauri::command]
+pub fn get_audio_recording_status(state: State<'_, MicrophoneState>) -> Option<AudioRecordingStatus> {
+    let current = state.0.lock().expect("microphone lock");
+    current
+        .as_ref()
+        .filter(|active| !active.worker.is_finished())
+        .map(|active| AudioRecordingStatus {
+            elapsed_secs: active.started.elapsed().as_secs_f64(),
+            ..active.status.clone()
+        })
+}
@@ -0,0 +1,189 @@
+//! Command middleware
+//!
+//! Wraps the generated invoke handler so every Tauri command passes through
+//! one place: calls are rate limited per window and command with a token
+//! bucket, so one page cannot use up another's logins, and timed into
+//! per-command metrics. Rejected calls get a `rate_limited`
+//! `KioskError`; slow calls are published as `command-slow` events. In mock
+//! mode, commands with a fixture are answered from it instead. While the
+//! screen is locked, commands the lock screen does not need are denied.
+//!
+//! Synchronous commands are timed end to end. Async commands return to the
+//! handler as soon as they are spawned, so only their dispatch is timed.
+
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::ipc::Invoke;
+use tauri::Manager;
+
+use crate::error::{ErrorKind, KioskError};
+use crate::{events, lock, mock};
+
+/// Default bucket: burst size and refill per second
+const DEFAULT_LIMIT: Limit = Limit { burst: 60.0, per_sec: 30.0 };
+
+/// Tighter limits for commands worth guessing at or expensive to run
+const LIMITS: &[(&str, Limit)] = &[
+    ("login", Limit { burst: 5.0, per_sec: 0.2 }),
+    ("get_secret", Limit { burst: 10.0, per_sec: 1.0 }),
+    ("get_access_token", Limit { burst: 10.0, per_sec: 1.0 }),
+    ("send_email", Limit { burst: 5.0, per_sec: 0.1 }),
+    ("start_device_auth", Limit { burst: 3.0, per_sec: 0.1 }),
+    ("run_cleanup", Limit { burst: 2.0, per_sec: 0.1 }),
+    ("reset_session", Limit { burst: 3.0, per_sec: 0.2 }),
+    ("unlock", Limit { burst: 5.0, per_sec: 0.5 }),
+];
+
+/// Calls slower than this are published on the event bus
+const SLOW_COMMAND: Duration = Duration::from_millis(250);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy)]
+struct Limit {
+    burst: f64,
+    per_sec: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommandMetrics {
+    pub command: String,
+    pub calls: u64,
+    pub rate_limited: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+    pub last_ms: f64,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+static COMMANDS: Mutex<BTreeMap<String, CommandMetrics>> = Mutex::new(BTreeMap::new());
+
+/// Keyed by window label, then command
+static BUCKETS: Mutex<BTreeMap<(String, String), Bucket>> = Mutex::new(BTreeMap::new());
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn limit_for(command: &str) -> Limit {
+    LIMITS
+        .iter()
+        .find(|(name, _)| *name == command)
+        .map_or(DEFAULT_LIMIT, |(_, limit)| *limit)
+}
+
+fn with_metrics(command: &str, update: impl FnOnce(&mut CommandMetrics)) {
+    let mut commands = COMMANDS.lock().expect("command metrics lock");
+    update(commands.entry(command.to_string()).or_insert_with(|| CommandMetrics {
+        command: command.to_string(),
+        ..Default::default()
+    }));
+}
+
+/// Take a token from the window's bucket for the command, or return how
+/// long until one is available
+fn take_token(window: &str, command: &str) -> Result<(), Duration> {
+    let limit = limit_for(command);
+    let mut buckets = BUCKETS.lock().expect("rate limit lock");
+    let bucket = buckets.entry((window.to_string(), command.to_string())).or_insert_with(|| Bucket {
+        tokens: limit.burst,
+        refilled: Instant::now(),
+    });
+
+    let now = Instant::now();
+    let refill = now.duration_since(bucket.refilled).as_secs_f64() * limit.per_sec;
+    bucket.tokens = (bucket.tokens + refill).min(limit.burst);
+    bucket.refilled = now;
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        Ok(())
+    } else {
+        let retry = Duration::from_secs_f64((1.0 - bucket.tokens) / limit.per_sec);
+        drop(buckets);
+        with_metrics(command, |metrics| metrics.rate_limited += 1);
+        Err(retry)
+    }
+}
+
+fn record(command: &str, elapsed: Duration) {
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    with_metrics(command, |metrics| {
+        metrics.calls += 1;
+        metrics.total_ms += ms;
+        metrics.max_ms = metrics.max_ms.max(ms);
+        metrics.last_ms = ms;
+    });
+}
+
+/// Drop a closed window's buckets
+pub(crate) fn forget_window(label: &str) {
+    BUCKETS.lock().expect("rate limit lock").retain(|(window, _), _| window != label);
+}
+
+/// Wrap a generated invoke handler with rate limiting and timing
+pub fn wrap(handler: impl Fn(Invoke) -> bool + Send + Sync + 'static) -> impl Fn(Invoke) -> bool + Send + Sync + 'static {
+    move |invoke: Invoke| {
+        let command = invoke.message.command().to_string();
+        let window = invoke.message.webview().label().to_string();
+        if let Err(retry) = take_token(&window, &command) {
+            invoke.resolver.reject(KioskError::new(
+                ErrorKind::RateLimited,
+                format!("Too many {} calls; retry in {} ms", command, retry.as_millis() + 1),
+            ));
+            return true;
+        }
+        let app = invoke.message.webview().app_handle().clone();
+        if lock::blocks(&app, &command) {
+            invoke.resolver.reject(KioskError::new(ErrorKind::Denied, "The screen is locked"));
+            return true;
+        }
+
+        let started = Instant::now();
+        let handled = match mock::respond(&command) {
+            Some(Ok(result)) => {
+                invoke.resolver.resolve(result);
+                true
+            }
+            Some(Err(e)) => {
+                invoke.resolver.reject(e);
+                true
+            }
+            None => handler(invoke),
+        };
+        let elapsed = started.elapsed();
+        record(&command, elapsed);
+
+        if elapsed >= SLOW_COMMAND {
+            events::publish(&app, "command-slow", json!({ "command": command, "ms": elapsed.as_millis() as u64 }));
+        }
+        handled
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Per-command call counts and timings, slowest total first
+#[tauri::command]
+pub fn get_command_metrics() -> Vec<CommandMetrics> {
+    let commands = COMMANDS.lock().expect("command metrics lock");
+    let mut metrics: Vec<CommandMetrics> = commands.values().cloned().collect();
+    metrics.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+    metrics
+}
+
+/// Clear collected metrics; rate limit buckets are kept
+#[tauri::command]
+pub fn reset_command_metrics() {
+    COMMANDS.lock().expect("command metrics lock").clear();
+}
@@ -0,0 +1,641 @@
+//! Minesweeper
+//!
+//! Boards live in the backend so the frontend only ever sees opened cells
+//! and a won game's time can go on the leaderboard. Mines are laid on the
+//! first reveal, away from the clicked cell, from a seed, so the same seed
+//! and first click give the same board. With `no_guess` boards are dealt
+//! until one can be cleared by deduction alone from the first click, trying
+//! a bounded number of layouts for a bounded time; `guaranteed` says
+//! whether one was found.
+//!
+//! Board rows are strings, one character per cell: `#` covered, `F`
+//! flagged, `0`-`8` open with that many neighbouring mines, and once the
+//! game is over `*` a mine, `X` the mine that went off and `x` a wrong flag.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::auth::AuthState;
+use crate::error::KioskError;
+use crate::games::{self, Rng, ScoreOrder, ScoreResult};
+
+const GAME: &str = "minesweeper";
+
+/// Games kept in memory; the least recently played goes first
+const MAX_GAMES: usize = 8;
+
+const MAX_SIDE: usize = 64;
+
+/// Layouts tried for a board that needs no guessing
+const NO_GUESS_ATTEMPTS: usize = 500;
+
+/// Time the first reveal may spend looking for such a layout, which on a
+/// large board is far less than `NO_GUESS_ATTEMPTS` would take
+const NO_GUESS_BUDGET: Duration = Duration::from_millis(750);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MinesweeperDifficulty {
+    /// 9 x 9, 10 mines
+    #[default]
+    Beginner,
+    /// 16 x 16, 40 mines
+    Intermediate,
+    /// 30 x 16, 99 mines
+    Expert,
+    /// Any size; not on the leaderboard
+    Custom,
+}
+
+impl MinesweeperDifficulty {
+    fn variant(self) -> Option<&'static str> {
+        match self {
+            MinesweeperDifficulty::Beginner => Some("beginner"),
+            MinesweeperDifficulty::Intermediate => Some("intermediate"),
+            MinesweeperDifficulty::Expert => Some("expert"),
+            MinesweeperDifficulty::Custom => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MinesweeperOptions {
+    pub difficulty: MinesweeperDifficulty,
+    /// Size and mines of a custom board
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub mines: Option<usize>,
+    /// Replays a board; random when unset
+    pub seed: Option<u32>,
+    /// Deal a board that never needs a guess
+    pub no_guess: bool,
+    pub player: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MinesweeperStatus {
+    Playing,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MinesweeperView {
+    pub id: String,
+    pub difficulty: MinesweeperDifficulty,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub seed: u32,
+    /// Mines less flags placed
+    pub mines_left: i64,
+    pub rows: Vec<String>,
+    pub status: MinesweeperStatus,
+    /// From the first reveal
+    pub elapsed_secs: f64,
+    /// Whether the board was checked to need no guessing
+    pub guaranteed: bool,
+    /// Where a won game landed on the leaderboard
+    pub score: Option<ScoreResult>,
+}
+
+struct Minefield {
+    difficulty: MinesweeperDifficulty,
+    width: usize,
+    height: usize,
+    mine_count: usize,
+    seed: u32,
+    no_guess: bool,
+    guaranteed: bool,
+    player: String,
+    /// Empty until the first reveal
+    mines: Vec<bool>,
+    open: Vec<bool>,
+    flags: Vec<bool>,
+    exploded: Option<usize>,
+    status: MinesweeperStatus,
+    started: Option<Instant>,
+    finished_secs: Option<f64>,
+    score: Option<ScoreResult>,
+    last_played: Instant,
+}
+
+#[derive(Default)]
+pub struct MinesweeperState(Mutex<HashMap<String, Minefield>>);
+
+// ============================================================================
+// Board
+// ============================================================================
+
+fn neighbours(width: usize, height: usize, cell: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = ((cell % width) as i64, (cell / width) as i64);
+    (-1..=1)
+        .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+        .filter(move |&(nx, ny)| {
+            (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && nx < width as i64 && ny < height as i64
+        })
+        .map(move |(nx, ny)| ny as usize * width + nx as usize)
+}
+
+fn count_mines(mines: &[bool], width: usize, height: usize, cell: usize) -> usize {
+    neighbours(width, height, cell).filter(|&n| mines[n]).count()
+}
+
+/// Open a cell, and everything around it while it has no neighbouring
+/// mines
+fn flood(mines: &[bool], open: &mut [bool], flags: &[bool], width: usize, height: usize, cell: usize) {
+    let mut pending = vec![cell];
+    while let Some(cell) = pending.pop() {
+        if open[cell] || flags[cell] {
+            continue;
+        }
+        open[cell] = true;
+        if !mines[cell] && count_mines(mines, width, height, cell) == 0 {
+            pending.extend(neighbours(width, height, cell).filter(|&n| !open[n]));
+        }
+    }
+}
+
+/// Whether the board can be cleared from `first` by deduction alone: the
+/// single-cell rules plus comparing overlapping neighbourhoods. Gives up,
+/// answering false, at `deadline`.
+fn solvable(mines: &[bool], width: usize, height: usize, first: usize, deadline: Instant) -> bool {
+    let cells = mines.len();
+    let mut open = vec![false; cells];
+    let mut known_mines = vec![false; cells];
+    flood(mines, &mut open, &known_mines, width, height, first);
+
+    loop {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        // For every open number: its unknown neighbours and the mines among them
+        let constraints: Vec<(Vec<usize>, usize)> = (0..cells)
+            .filter(|&cell| open[cell])
+            .filter_map(|cell| {
+                let unknown: Vec<usize> = neighbours(width, height, cell)
+                    .filter(|&n| !open[n] && !known_mines[n])
+                    .collect();
+                let flagged = neighbours(width, height, cell).filter(|&n| known_mines[n]).count();
+                let count = count_mines(mines, width, height, cell);
+                (!unknown.is_empty()).then(|| (unknown, count - flagged))
+            })
+            .collect();
+        if constraints.is_empty() {
+            break;
+        }
+
+        let mut safe = Vec::new();
+        let mut found = Vec::new();
+        for (unknown, count) in &constraints {
+            if *count == 0 {
+                safe.extend(unknown.iter().copied());
+            } else if *count == unknown.len() {
+                found.extend(unknown.iter().copied());
+            }
+        }
+        if safe.is_empty() && found.is_empty() {
+            for (small, small_count) in &constraints {
+                if Instant::now() >= deadline {
+                    return false;
+                }
+                for (large, large_count) in &constraints {
+                    if small.len() >= large.len() || !small.iter().all(|cell| large.contains(cell)) {
+                        continue;
+                    }
+                    let rest: Vec<usize> = large.iter().copied().filter(|cell| !small.contains(cell)).collect();
+                    let rest_count = large_count - small_count.min(large_count);
+                    if rest_count == 0 {
+                        safe.extend(rest);
+                    } else if rest_count == rest.len() {
+                        found.extend(rest);
+                    }
+                }
+            }
+        }
+        if safe.is_empty() && found.is_empty() {
+            break;
+        }
+        for cell in found {
+            known_mines[cell] = true;
+        }
+        for cell in safe {
+            flood(mines, &mut open, &known_mines, width, height, cell);
+        }
+    }
+    (0..cells).all(|cell| mines[cell] || open[cell])
+}
+
+impl Minefield {
+    fn cell(&self, x: usize, y: usize) -> Result<usize, KioskError> {
+        if x >= self.width || y >= self.height {
+            return Err(KioskError::invalid(format!("({}, {}) is off the board", x, y)));
+        }
+        Ok(y * self.width + x)
+    }
+
+    /// Lay the mines clear of the first cell (and its neighbours when there
+    /// is room)
+    fn lay_mines(&mut self, first: usize) {
+        let cells = self.width * self.height;
+        let mut keep_clear: Vec<usize> = vec![first];
+        if cells - self.mine_count >= 9 {
+            keep_clear.extend(neighbours(self.width, self.height, first));
+        }
+        let candidates: Vec<usize> = (0..cells).filter(|cell| !keep_clear.contains(cell)).collect();
+
+        let mut rng = Rng::new(self.seed);
+        let attempts = if self.no_guess { NO_GUESS_ATTEMPTS } else { 1 };
+        let deadline = Instant::now() + NO_GUESS_BUDGET;
+        for _ in 0..attempts {
+            let mut order = candidates.clone();
+            rng.shuffle(&mut order);
+            let mut mines = vec![false; cells];
+            for &cell in order.iter().take(self.mine_count) {
+                mines[cell] = true;
+            }
+            self.mines = mines;
+            if !self.no_guess || Instant::now() >= deadline {
+                return;
+            }
+            if solvable(&self.mines, self.width, self.height, first, deadline) {
+                self.guaranteed = true;
+                return;
+            }
+        }
+    }
+
+    fn elapsed(&self) -> f64 {
+        self.finished_secs
+            .or_else(|| self.started.map(|started| started.elapsed().as_secs_f64()))
+            .unwrap_or(0.0)
+    }
+
+    fn check_won(&mut self) {
+        let cleared = (0..self.mines.len()).all(|cell| self.mines[cell] || self.open[cell]);
+        if self.status != MinesweeperStatus::Playing || !cleared {
+            return;
+        }
+        self.status = MinesweeperStatus::Won;
+        self.flags = self.mines.clone();
+        self.finished_secs = Some(self.elapsed());
+    }
+
+    /// Put a game just won on the leaderboard
+    fn record_score(&mut self, app: &AppHandle) {
+        let (Some(variant), Some(secs)) = (self.difficulty.variant(), self.finished_secs) else {
+            return;
+        };
+        if self.status == MinesweeperStatus::Won && self.score.is_none() {
+            let seconds = (secs * 10.0).round() / 10.0;
+            self.score = games::record_score(app, GAME, variant, &self.player, seconds, ScoreOrder::Lower).ok();
+        }
+    }
+
+    fn lose(&mut self, cell: usize) {
+        self.status = MinesweeperStatus::Lost;
+        self.exploded = Some(cell);
+        self.finished_secs = Some(self.elapsed());
+    }
+
+    fn reveal(&mut self, cell: usize) {
+        if self.status != MinesweeperStatus::Playing || self.flags[cell] || self.open.get(cell) == Some(&true) {
+            return;
+        }
+        if self.mines.is_empty() {
+            self.lay_mines(cell);
+            self.started = Some(Instant::now());
+        }
+        if self.mines[cell] {
+            self.lose(cell);
+            return;
+        }
+        flood(&self.mines, &mut self.open, &self.flags, self.width, self.height, cell);
+        self.check_won();
+    }
+
+    /// Open the covered neighbours of a number whose mines are all flagged
+    fn chord(&mut self, cell: usize) {
+        if self.status != MinesweeperStatus::Playing || self.mines.is_empty() || !self.open[cell] {
+            return;
+        }
+        let flagged = neighbours(self.width, self.height, cell).filter(|&n| self.flags[n]).count();
+        if flagged != count_mines(&self.mines, self.width, self.height, cell) {
+            return;
+        }
+        let covered: Vec<usize> = neighbours(self.width, self.height, cell)
+            .filter(|&n| !self.open[n] && !self.flags[n])
+            .collect();
+        for n in covered {
+            if self.mines[n] {
+                self.lose(n);
+                return;
+            }
+            flood(&self.mines, &mut self.open, &self.flags, self.width, self.height, n);
+        }
+        self.check_won();
+    }
+
+    fn view(&self, id: &str) -> MinesweeperView {
+        let over = self.status != MinesweeperStatus::Playing;
+        let symbol = |cell: usize| {
+            let mine = self.mines.get(cell).copied().unwrap_or(false);
+            match (self.open.get(cell) == Some(&true), self.flags[cell]) {
+                _ if self.exploded == Some(cell) => 'X',
+                (true, _) => char::from_digit(count_mines(&self.mines, self.width, self.height, cell) as u32, 10)
+                    .unwrap_or('0'),
+                (false, true) if over && !mine => 'x',
+                (false, true) => 'F',
+                (false, false) if over && mine => '*',
+                (false, false) => '#',
+            }
+        };
+        let flags = self.flags.iter().filter(|&&flag| flag).count();
+        MinesweeperView {
+            id: id.to_string(),
+            difficulty: self.difficulty,
+            width: self.width,
+            height: self.height,
+            mines: self.mine_count,
+            seed: self.seed,
+            mines_left: self.mine_count as i64 - flags as i64,
+            rows: (0..self.height)
+                .map(|y| (0..self.width).map(|x| symbol(y * self.width + x)).collect())
+                .collect(),
+            status: self.status,
+            elapsed_secs: self.elapsed(),
+            guaranteed: self.guaranteed,
+            score: self.score.clone(),
+        }
+    }
+}
+
+/// Run `change` on a game and return its view
+fn with_game(
+    state: &MinesweeperState,
+    id: &str,
+    change: impl FnOnce(&mut Minefield) -> Result<(), KioskError>,
+) -> Result<MinesweeperView, KioskError> {
+    let mut games = state.0.lock().expect("minesweeper lock");
+    let game = games
+        .get_mut(id)
+        .ok_or_else(|| KioskError::not_found(format!("No game {}", id)))?;
+    change(game)?;
+    game.last_played = Instant::now();
+    Ok(game.view(id))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start a game; mines are laid on the first reveal
+#[tauri::command]
+pub fn new_minesweeper(
+    auth: State<'_, AuthState>,
+    state: State<'_, MinesweeperState>,
+    options: Option<MinesweeperOptions>,
+) -> Result<MinesweeperView, KioskError> {
+    let options = options.unwrap_or_default();
+    let (width, height, mines) = match options.difficulty {
+        MinesweeperDifficulty::Beginner => (9, 9, 10),
+        MinesweeperDifficulty::Intermediate => (16, 16, 40),
+        MinesweeperDifficulty::Expert => (30, 16, 99),
+        MinesweeperDifficulty::Custom => (
+            options.width.unwrap_or(9),
+            options.height.unwrap_or(9),
+            options.mines.unwrap_or(10),
+        ),
+    };
+    if !(2..=MAX_SIDE).contains(&width) || !(2..=MAX_SIDE).contains(&height) {
+        return Err(KioskError::invalid(format!("Boards are 2 to {} cells a side", MAX_SIDE)));
+    }
+    if mines == 0 || mines >= width * height {
+        return Err(KioskError::invalid("A board needs at least one mine and one free cell"));
+    }
+
+    let cells = width * height;
+    let game = Minefield {
+        difficulty: options.difficulty,
+        width,
+        height,
+        mine_count: mines,
+        seed: options.seed.unwrap_or_else(games::random_seed),
+        no_guess: options.no_guess,
+        guaranteed: false,
+        player: games::player_name(&auth, options.player),
+        mines: Vec::new(),
+        open: vec![false; cells],
+        flags: vec![false; cells],
+        exploded: None,
+        status: MinesweeperStatus::Playing,
+        started: None,
+        finished_secs: None,
+        score: None,
+        last_played: Instant::now(),
+    };
+    let id = uuid::Uuid::new_v4().to_string();
+    let view = game.view(&id);
+
+    let mut games = state.0.lock().expect("minesweeper lock");
+    while games.len() >= MAX_GAMES {
+        let Some(oldest) = games
+            .iter()
+            .min_by_key(|(_, game)| game.last_played)
+            .map(|(id, _)| id.clone())
+        else {
+            break;
+        };
+        games.remove(&oldest);
+    }
+    games.insert(id, game);
+    Ok(view)
+}
+
+#[tauri::command]
+pub fn get_minesweeper(state: State<'_, MinesweeperState>, id: String) -> Result<MinesweeperView, KioskError> {
+    with_game(&state, &id, |_| Ok(()))
+}
+
+/// Open a cell; a mine ends the game. The first reveal lays the mines,
+/// which for a board that needs no guessing takes a while, so this runs off
+/// the main thread.
+#[tauri::command(async)]
+pub fn minesweeper_reveal(
+    app: AppHandle,
+    state: State<'_, MinesweeperState>,
+    id: String,
+    x: usize,
+    y: usize,
+) -> Result<MinesweeperView, KioskError> {
+    with_game(&state, &id, |game| {
+        let cell = game.cell(x, y)?;
+        game.reveal(cell);
+        game.record_score(&app);
+        Ok(())
+    })
+}
+
+/// Put a flag on a covered cell, or take it off
+#[tauri::command]
+pub fn minesweeper_flag(
+    state: State<'_, MinesweeperState>,
+    id: String,
+    x: usize,
+    y: usize,
+) -> Result<MinesweeperView, KioskError> {
+    with_game(&state, &id, |game| {
+        let cell = game.cell(x, y)?;
+        if game.status == MinesweeperStatus::Playing && !game.open[cell] {
+            game.flags[cell] = !game.flags[cell];
+        }
+        Ok(())
+    })
+}
+
+/// Open the neighbours of a number whose mines are all flagged
+#[tauri::command]
+pub fn minesweeper_chord(
+    app: AppHandle,
+    state: State<'_, MinesweeperState>,
+    id: String,
+    x: usize,
+    y: usize,
+) -> Result<MinesweeperView, KioskError> {
+    with_game(&state, &id, |game| {
+        let cell = game.cell(x, y)?;
+        game.chord(cell);
+        game.record_score(&app);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(width: usize, height: usize, mine_count: usize, seed: u32, no_guess: bool) -> Minefield {
+        let cells = width * height;
+        Minefield {
+            difficulty: MinesweeperDifficulty::Custom,
+            width,
+            height,
+            mine_count,
+            seed,
+            no_guess,
+            guaranteed: false,
+            player: String::new(),
+            mines: Vec::new(),
+            open: vec![false; cells],
+            flags: vec![false; cells],
+            exploded: None,
+            status: MinesweeperStatus::Playing,
+            started: None,
+            finished_secs: None,
+            score: None,
+            last_played: Instant::now(),
+        }
+    }
+
+    /// A 3 x 3 board with its only mine in the top-left corner
+    fn corner_mine() -> Minefield {
+        let mut game = field(3, 3, 1, 0, false);
+        game.mines = vec![true, false, false, false, false, false, false, false, false];
+        game
+    }
+
+    fn later() -> Instant {
+        Instant::now() + Duration::from_secs(60)
+    }
+
+    #[test]
+    fn same_seed_and_first_click_give_the_same_board() {
+        let mut a = field(16, 16, 40, 1234, false);
+        let mut b = field(16, 16, 40, 1234, false);
+        let mut c = field(16, 16, 40, 4321, false);
+        for game in [&mut a, &mut b, &mut c] {
+            game.lay_mines(17);
+        }
+        assert_eq!(a.mines, b.mines);
+        assert_ne!(a.mines, c.mines);
+    }
+
+    #[test]
+    fn mines_are_laid_clear_of_the_first_click() {
+        for seed in 0..20 {
+            let mut game = field(9, 9, 10, seed, false);
+            game.lay_mines(40);
+            assert_eq!(game.mines.iter().filter(|&&mine| mine).count(), 10);
+            assert!(!game.mines[40]);
+            assert!(neighbours(9, 9, 40).all(|n| !game.mines[n]));
+        }
+
+        // Too crowded to keep the neighbours clear too
+        let mut game = field(3, 3, 8, 5, false);
+        game.lay_mines(4);
+        assert_eq!(game.mines.iter().filter(|&&mine| mine).count(), 8);
+        assert!(!game.mines[4]);
+    }
+
+    #[test]
+    fn solvable_needs_no_guess() {
+        assert!(solvable(&corner_mine().mines, 3, 3, 8, later()));
+
+        // 2 x 2 with one mine: the first click sees a 1 and three covered cells
+        assert!(!solvable(&[true, false, false, false], 2, 2, 3, later()));
+
+        // Gives up at the deadline
+        assert!(!solvable(&corner_mine().mines, 3, 3, 8, Instant::now()));
+    }
+
+    #[test]
+    fn no_guess_boards_are_solvable() {
+        let mut game = field(9, 9, 10, 99, true);
+        game.lay_mines(0);
+        assert!(game.guaranteed);
+        assert!(solvable(&game.mines, 9, 9, 0, later()));
+    }
+
+    #[test]
+    fn revealing_clears_the_board_or_explodes() {
+        let mut game = corner_mine();
+        game.reveal(8);
+        assert_eq!(game.status, MinesweeperStatus::Won);
+        assert_eq!(game.view("a").rows, vec!["F10", "110", "000"]);
+
+        let mut game = corner_mine();
+        game.reveal(0);
+        assert_eq!(game.status, MinesweeperStatus::Lost);
+        assert_eq!(game.view("a").rows[0], "X##");
+    }
+
+    #[test]
+    fn flags_and_chords() {
+        let mut game = corner_mine();
+        game.flags[0] = true;
+        game.reveal(0);
+        assert_eq!(game.status, MinesweeperStatus::Playing);
+
+        game.reveal(4);
+        assert_eq!(game.view("a").rows, vec!["F##", "#1#", "###"]);
+        game.chord(4);
+        assert_eq!(game.status, MinesweeperStatus::Won);
+
+        // A wrong flag sets the mine off
+        let mut game = corner_mine();
+        game.reveal(4);
+        game.flags[1] = true;
+        game.chord(4);
+        assert_eq!(game.status, MinesweeperStatus::Lost);
+        assert_eq!(game.view("a").rows[0], "Xx#");
+    }
+}
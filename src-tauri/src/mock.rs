@@ -0,0 +1,189 @@
+//! Mock mode
+//!
+//! Started with `--mock` (or `--mock=<file>`), the kiosk answers
+//! hardware-dependent commands from a JSON fixture file instead of touching
+//! GPIO, printers, sensors or vcgencmd, so the frontend can be built on a
+//! laptop against the full API. The middleware asks this module before it
+//! runs a command; commands without a fixture run as usual. The file is
+//! read again whenever it changes, so fixtures can be edited while the app
+//! is running.
+//!
+//! ```json
+//! {
+//!   "commands": {
+//!     "list_printers": { "result": [{ "name": "Mock", "is_default": true }] },
+//!     "get_inserted_amount": { "sequence": [{ "total": 0 }, { "total": 200 }] },
+//!     "print_text": { "error": "Printer offline", "delay_ms": 500 }
+//!   },
+//!   "gpio": { "17": 1 }
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+use crate::error::{ErrorKind, KioskError};
+
+const FLAG: &str = "--mock";
+
+/// Fixtures used by a bare `--mock`, from the source tree
+const DEFAULT_FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/mock/fixtures.json");
+
+/// Longest delay a fixture may ask for
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Scripted answer for one command. `sequence` is played one call at a time,
+/// repeating its last entry; otherwise `error` or `result` is returned.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Fixture {
+    result: Value,
+    sequence: Vec<Value>,
+    error: Option<String>,
+    delay_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct Fixtures {
+    commands: HashMap<String, Fixture>,
+    /// Input values by sysfs pin number
+    gpio: HashMap<u32, u8>,
+}
+
+#[derive(Default)]
+struct Loaded {
+    fixtures: Fixtures,
+    modified: Option<SystemTime>,
+    error: Option<String>,
+    /// Calls answered so far per command, for sequences
+    calls: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MockStatus {
+    pub enabled: bool,
+    pub fixtures: Option<String>,
+    /// Commands answered from fixtures
+    pub commands: Vec<String>,
+    /// Why the fixture file could not be read, if it could not
+    pub error: Option<String>,
+}
+
+static FIXTURE_PATH: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+static LOADED: Mutex<Option<Loaded>> = Mutex::new(None);
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Fixture file from the command line, or `None` outside mock mode
+fn fixture_path() -> Option<&'static PathBuf> {
+    FIXTURE_PATH
+        .get_or_init(|| {
+            let mut args = std::env::args().skip(1);
+            while let Some(arg) = args.next() {
+                if arg == FLAG {
+                    let path = args.next().filter(|next| !next.starts_with("--"));
+                    return Some(PathBuf::from(path.as_deref().unwrap_or(DEFAULT_FIXTURES)));
+                }
+                if let Some(path) = arg.strip_prefix("--mock=") {
+                    return Some(PathBuf::from(path));
+                }
+            }
+            None
+        })
+        .as_ref()
+}
+
+pub(crate) fn enabled() -> bool {
+    fixture_path().is_some()
+}
+
+/// Run `f` on the fixtures, reading the file again if it changed
+fn with_fixtures<T>(f: impl FnOnce(&mut Loaded) -> T) -> Option<T> {
+    let path = fixture_path()?;
+    let mut loaded = LOADED.lock().expect("mock fixtures lock");
+    let loaded = loaded.get_or_insert_with(Loaded::default);
+
+    let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    if modified.is_none() || modified != loaded.modified {
+        let read = fs::read_to_string(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+            .and_then(|text| serde_json::from_str::<Fixtures>(&text).map_err(|e| format!("{}: {}", path.display(), e)));
+        match read {
+            Ok(fixtures) => {
+                loaded.fixtures = fixtures;
+                loaded.error = None;
+                loaded.calls.clear();
+            }
+            Err(e) => loaded.error = Some(e),
+        }
+        loaded.modified = modified;
+    }
+    Some(f(loaded))
+}
+
+/// Scripted answer for a command, or `None` when it should run for real
+pub(crate) fn respond(command: &str) -> Option<Result<Value, KioskError>> {
+    let (fixture, call) = with_fixtures(|loaded| {
+        let fixture = loaded.fixtures.commands.get(command)?.clone();
+        let calls = loaded.calls.entry(command.to_string()).or_insert(0);
+        *calls += 1;
+        Some((fixture, *calls - 1))
+    })??;
+
+    if fixture.delay_ms > 0 {
+        std::thread::sleep(Duration::from_millis(fixture.delay_ms).min(MAX_DELAY));
+    }
+    if let Some(message) = fixture.error {
+        return Some(Err(KioskError::new(ErrorKind::Failed, message)));
+    }
+    let result = match fixture.sequence.len() {
+        0 => fixture.result,
+        len => fixture.sequence[call.min(len - 1)].clone(),
+    };
+    Some(Ok(result))
+}
+
+/// Mocked value of a GPIO input, when mock mode scripts one
+pub(crate) fn gpio(pin: u32) -> Option<u8> {
+    with_fixtures(|loaded| loaded.fixtures.gpio.get(&pin).copied()).flatten()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Whether the kiosk runs in mock mode, and which commands are scripted
+#[tauri::command]
+pub fn get_mock_status() -> MockStatus {
+    let (commands, error) = with_fixtures(|loaded| {
+        let mut commands: Vec<String> = loaded.fixtures.commands.keys().cloned().collect();
+        commands.sort();
+        (commands, loaded.error.clone())
+    })
+    .unwrap_or_default();
+    MockStatus {
+        enabled: enabled(),
+        fixtures: fixture_path().map(|path| path.display().to_string()),
+        commands,
+        error,
+    }
+}
+
+/// Start the scripted sequences over from their first entry
+#[tauri::command]
+pub fn reset_mock_sequences() -> Result<(), KioskError> {
+    with_fixtures(|loaded| loaded.calls.clear()).ok_or_else(|| KioskError::invalid("Mock mode is not enabled"))
+}
@@ -0,0 +1,508 @@
+//! Cellular modem
+//!
+//! Drives an LTE modem through ModemManager on the system D-Bus, for units
+//! deployed where there is no Ethernet or Wi-Fi: signal and registration,
+//! the APN to dial (`modem.json`, password in the keyring), connecting and
+//! disconnecting, the byte counters of the live connection, and SMS. A
+//! watcher publishes `modem-state-changed` when the modem changes state,
+//! `sms-received` for each new incoming message, and redials when
+//! `auto_connect` is set. The first modem ModemManager reports is used.
+
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::stdintf::org_freedesktop_dbus::{ObjectManager, Properties};
+use dbus::blocking::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{config, events, keyring, store};
+
+const CONFIG_FILE: &str = "modem.json";
+
+const PASSWORD_KEY: &str = "modem/apn";
+
+const MODEM_MANAGER: &str = "org.freedesktop.ModemManager1";
+const MODEM_MANAGER_PATH: &str = "/org/freedesktop/ModemManager1";
+const MODEM: &str = "org.freedesktop.ModemManager1.Modem";
+const MODEM_3GPP: &str = "org.freedesktop.ModemManager1.Modem.Modem3gpp";
+const SIMPLE: &str = "org.freedesktop.ModemManager1.Modem.Simple";
+const MESSAGING: &str = "org.freedesktop.ModemManager1.Modem.Messaging";
+const BEARER: &str = "org.freedesktop.ModemManager1.Bearer";
+const SMS: &str = "org.freedesktop.ModemManager1.Sms";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Registering and bringing up a bearer can take a while
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(90);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Wait between automatic redials
+const REDIAL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `MMModemState` of a registered, unconnected modem
+const STATE_REGISTERED: i32 = 8;
+
+/// `MMSmsState` of a fully received message
+const SMS_RECEIVED: u32 = 3;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModemConfig {
+    pub apn: String,
+    pub username: String,
+    /// Write-only: returned empty, and left unchanged when set empty
+    pub password: String,
+    pub allow_roaming: bool,
+    /// Dial whenever the modem is registered but not connected
+    pub auto_connect: bool,
+}
+
+/// Counters of the live data connection
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModemConnection {
+    /// Network interface the bearer brought up, e.g. `wwan0`
+    pub interface: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub duration_secs: u64,
+}
+
+/// Payload of `modem-state-changed`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModemStatus {
+    pub present: bool,
+    pub manufacturer: String,
+    pub model: String,
+    pub imei: String,
+    /// ModemManager state, e.g. "registered", "connected"
+    pub state: String,
+    pub signal_percent: u32,
+    /// Best access technology in use: "2G", "3G", "LTE" or "5G"
+    pub technology: String,
+    pub operator: String,
+    /// "home", "roaming", "searching", "denied", "idle" or "unknown"
+    pub registration: String,
+    pub own_number: Option<String>,
+    pub connection: Option<ModemConnection>,
+}
+
+/// Payload of `sms-received`
+#[derive(Debug, Clone, Serialize)]
+pub struct SmsMessage {
+    /// ModemManager object path, for `delete_sms`
+    pub id: String,
+    pub number: String,
+    pub text: String,
+    pub timestamp: String,
+    pub incoming: bool,
+}
+
+/// A modem's object path and the properties of each of its interfaces
+type ModemObject = (dbus::Path<'static>, HashMap<String, PropMap>);
+
+pub struct ModemState {
+    config: Mutex<ModemConfig>,
+}
+
+impl ModemState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut config: ModemConfig = store::load(app, CONFIG_FILE);
+        config.password = keyring::get(app, PASSWORD_KEY).ok().flatten().unwrap_or_default();
+        ModemState {
+            config: Mutex::new(config),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn bus_error(e: dbus::Error) -> String {
+    e.message().unwrap_or("D-Bus error").to_string()
+}
+
+fn connect() -> Result<Connection, String> {
+    Connection::new_system().map_err(bus_error)
+}
+
+/// Object path of the first modem, with its interfaces' properties
+fn find_modem(connection: &Connection) -> Result<Option<ModemObject>, String> {
+    let manager = connection.with_proxy(MODEM_MANAGER, MODEM_MANAGER_PATH, TIMEOUT);
+    let objects = manager.get_managed_objects().map_err(bus_error)?;
+    Ok(objects
+        .into_iter()
+        .filter(|(_, interfaces)| interfaces.contains_key(MODEM))
+        .min_by(|(a, _), (b, _)| a.cmp(b)))
+}
+
+fn require_modem(connection: &Connection) -> Result<dbus::Path<'static>, String> {
+    find_modem(connection)?
+        .map(|(path, _)| path)
+        .ok_or_else(|| "No modem found".to_string())
+}
+
+fn text(properties: &PropMap, key: &str) -> String {
+    dbus::arg::prop_cast::<String>(properties, key).cloned().unwrap_or_default()
+}
+
+fn state_name(state: i32) -> &'static str {
+    match state {
+        -1 => "failed",
+        1 => "initializing",
+        2 => "locked",
+        3 => "disabled",
+        4 => "disabling",
+        5 => "enabling",
+        6 => "enabled",
+        7 => "searching",
+        8 => "registered",
+        9 => "disconnecting",
+        10 => "connecting",
+        11 => "connected",
+        _ => "unknown",
+    }
+}
+
+fn registration_name(state: u32) -> &'static str {
+    match state {
+        0 => "idle",
+        1 => "home",
+        2 => "searching",
+        3 => "denied",
+        5 => "roaming",
+        _ => "unknown",
+    }
+}
+
+/// Generation of the best `MMModemAccessTechnology` bit set
+fn technology(bits: u32) -> &'static str {
+    if bits & 1 << 15 != 0 {
+        "5G"
+    } else if bits & 1 << 14 != 0 {
+        "LTE"
+    } else if bits & (0x1f << 5 | 0x7 << 11) != 0 {
+        "3G"
+    } else if bits & (0xf << 1 | 1 << 10) != 0 {
+        "2G"
+    } else {
+        ""
+    }
+}
+
+/// Counters of the first connected bearer
+fn bearer_connection(connection: &Connection, bearers: &[dbus::Path<'static>]) -> Option<ModemConnection> {
+    bearers.iter().find_map(|path| {
+        let bearer = connection.with_proxy(MODEM_MANAGER, path, TIMEOUT);
+        let properties: PropMap = bearer.get_all(BEARER).ok()?;
+        if !dbus::arg::prop_cast::<bool>(&properties, "Connected").copied().unwrap_or(false) {
+            return None;
+        }
+        let stats = properties.get("Stats").and_then(|stats| stats.0.as_iter()).map(|mut iter| {
+            let mut counters = (0, 0, 0);
+            while let (Some(key), Some(value)) = (iter.next(), iter.next()) {
+                let value = value.as_u64().unwrap_or(0);
+                match key.as_str() {
+                    Some("rx-bytes") => counters.0 = value,
+                    Some("tx-bytes") => counters.1 = value,
+                    Some("duration") => counters.2 = value,
+                    _ => {}
+                }
+            }
+            counters
+        });
+        let (rx_bytes, tx_bytes, duration_secs) = stats.unwrap_or_default();
+        Some(ModemConnection {
+            interface: text(&properties, "Interface"),
+            rx_bytes,
+            tx_bytes,
+            duration_secs,
+        })
+    })
+}
+
+fn read_status(connection: &Connection) -> Result<ModemStatus, String> {
+    let Some((_, interfaces)) = find_modem(connection)? else {
+        return Ok(ModemStatus {
+            present: false,
+            manufacturer: String::new(),
+            model: String::new(),
+            imei: String::new(),
+            state: state_name(0).to_string(),
+            signal_percent: 0,
+            technology: String::new(),
+            operator: String::new(),
+            registration: registration_name(4).to_string(),
+            own_number: None,
+            connection: None,
+        });
+    };
+    let empty = PropMap::new();
+    let interface = |name: &str| interfaces.get(name).unwrap_or(&empty);
+    let modem = interface(MODEM);
+    let gpp = interface(MODEM_3GPP);
+
+    // SignalQuality is a (percent, recent) struct
+    let signal_percent = modem
+        .get("SignalQuality")
+        .and_then(|quality| quality.0.as_iter())
+        .and_then(|mut fields| fields.next()?.as_u64())
+        .unwrap_or(0) as u32;
+    let own_number = modem
+        .get("OwnNumbers")
+        .and_then(|numbers| numbers.0.as_iter())
+        .and_then(|mut numbers| numbers.next()?.as_str().map(str::to_string));
+    let bearers = dbus::arg::prop_cast::<Vec<dbus::Path<'static>>>(modem, "Bearers")
+        .cloned()
+        .unwrap_or_default();
+    Ok(ModemStatus {
+        present: true,
+        manufacturer: text(modem, "Manufacturer"),
+        model: text(modem, "Model"),
+        imei: text(gpp, "Imei"),
+        state: state_name(dbus::arg::prop_cast::<i32>(modem, "State").copied().unwrap_or(0)).to_string(),
+        signal_percent,
+        technology: technology(dbus::arg::prop_cast::<u32>(modem, "AccessTechnologies").copied().unwrap_or(0))
+            .to_string(),
+        operator: text(gpp, "OperatorName"),
+        registration: registration_name(dbus::arg::prop_cast::<u32>(gpp, "RegistrationState").copied().unwrap_or(4))
+            .to_string(),
+        own_number,
+        connection: bearer_connection(connection, &bearers),
+    })
+}
+
+fn dial(connection: &Connection, config: &ModemConfig) -> Result<(), String> {
+    let path = require_modem(connection)?;
+    let mut properties = PropMap::new();
+    let mut put = |key: &str, value: Box<dyn RefArg>| {
+        properties.insert(key.to_string(), Variant(value));
+    };
+    put("apn", Box::new(config.apn.clone()));
+    if !config.username.is_empty() {
+        put("user", Box::new(config.username.clone()));
+        put("password", Box::new(config.password.clone()));
+    }
+    put("allow-roaming", Box::new(config.allow_roaming));
+
+    let modem = connection.with_proxy(MODEM_MANAGER, path, CONNECT_TIMEOUT);
+    let _: (dbus::Path<'static>,) = modem.method_call(SIMPLE, "Connect", (properties,)).map_err(bus_error)?;
+    Ok(())
+}
+
+fn read_sms(connection: &Connection, path: &dbus::Path<'static>) -> Result<(SmsMessage, u32), String> {
+    let sms = connection.with_proxy(MODEM_MANAGER, path, TIMEOUT);
+    let properties: PropMap = sms.get_all(SMS).map_err(bus_error)?;
+    let state = dbus::arg::prop_cast::<u32>(&properties, "State").copied().unwrap_or(0);
+    let message = SmsMessage {
+        id: path.to_string(),
+        number: text(&properties, "Number"),
+        text: text(&properties, "Text"),
+        timestamp: text(&properties, "Timestamp"),
+        // Received and receiving messages; the rest are ours
+        incoming: matches!(state, 2 | SMS_RECEIVED),
+    };
+    Ok((message, state))
+}
+
+fn sms_paths(connection: &Connection) -> Result<Vec<dbus::Path<'static>>, String> {
+    let modem = connection.with_proxy(MODEM_MANAGER, require_modem(connection)?, TIMEOUT);
+    let (paths,): (Vec<dbus::Path<'static>>,) = modem.method_call(MESSAGING, "List", ()).map_err(bus_error)?;
+    Ok(paths)
+}
+
+/// Accept `+15551234567`-style numbers only
+fn valid_number(number: &str) -> bool {
+    let digits = number.strip_prefix('+').unwrap_or(number);
+    (3..=20).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Spawn the watcher for state changes, incoming SMS and redialing
+pub fn start_modem(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_state = None;
+        // Messages already on the modem at startup are not announced
+        let mut seen: Option<HashSet<String>> = None;
+        let mut last_dial: Option<Instant> = None;
+        loop {
+            std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+            let Ok(connection) = connect() else { continue };
+            let Ok(status) = read_status(&connection) else { continue };
+            if last_state.as_ref() != Some(&status.state) {
+                last_state = Some(status.state.clone());
+                events::publish(&app, "modem-state-changed", status.clone());
+            }
+            if !status.present {
+                continue;
+            }
+
+            let config = app.state::<ModemState>().config.lock().expect("modem config lock").clone();
+            let registered = status.state == state_name(STATE_REGISTERED);
+            let due = last_dial.map_or(true, |at| at.elapsed() >= REDIAL_INTERVAL);
+            if config.auto_connect && !config.apn.is_empty() && registered && due {
+                last_dial = Some(Instant::now());
+                let _ = dial(&connection, &config);
+            }
+
+            let Ok(paths) = sms_paths(&connection) else { continue };
+            let first = seen.is_none();
+            let known = seen.get_or_insert_with(HashSet::new);
+            for path in paths {
+                if known.contains(&path.to_string()) {
+                    continue;
+                }
+                // Multipart messages stay "receiving" until the last part arrives
+                let Ok((message, state)) = read_sms(&connection, &path) else { continue };
+                if state == SMS_RECEIVED || !message.incoming {
+                    known.insert(message.id.clone());
+                    if !first && message.incoming {
+                        events::publish(&app, "sms-received", message);
+                    }
+                }
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Modem, signal and connection details
+#[tauri::command]
+pub fn get_modem_status() -> Result<ModemStatus, KioskError> {
+    let connection = connect().map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    read_status(&connection).map_err(|e| KioskError::new(ErrorKind::Failed, e))
+}
+
+#[tauri::command]
+pub fn get_modem_config(state: State<'_, ModemState>) -> ModemConfig {
+    ModemConfig {
+        password: String::new(),
+        ..state.config.lock().expect("modem config lock").clone()
+    }
+}
+
+/// Update the APN and dialing settings (admin)
+#[tauri::command]
+pub fn set_modem_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, ModemState>,
+    mut config: ModemConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    config.apn = config.apn.trim().to_string();
+    if config.auto_connect && config.apn.is_empty() {
+        return Err(KioskError::invalid("Set an APN to connect automatically"));
+    }
+    let mut current = state.config.lock().expect("modem config lock");
+    if config.password.is_empty() {
+        config.password = current.password.clone();
+    } else {
+        keyring::put(&app, PASSWORD_KEY, &config.password)?;
+    }
+
+    store::save(
+        &app,
+        CONFIG_FILE,
+        &ModemConfig {
+            password: String::new(),
+            ..config.clone()
+        },
+    )?;
+    *current = config;
+    Ok(())
+}
+
+/// Bring up mobile data with the configured APN (supervisor)
+#[tauri::command]
+pub fn connect_modem(auth: State<'_, AuthState>, state: State<'_, ModemState>) -> Result<ModemStatus, KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let config = state.config.lock().expect("modem config lock").clone();
+    if config.apn.is_empty() {
+        return Err(KioskError::invalid("Set an APN first"));
+    }
+    let connection = connect().map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    dial(&connection, &config).map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    read_status(&connection).map_err(|e| KioskError::new(ErrorKind::Failed, e))
+}
+
+/// Drop mobile data (supervisor). With `auto_connect` on, the watcher
+/// dials again, so turn that off first to stay offline.
+#[tauri::command]
+pub fn disconnect_modem(auth: State<'_, AuthState>) -> Result<ModemStatus, KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let connection = connect().map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    let path = require_modem(&connection).map_err(KioskError::not_found)?;
+    let modem = connection.with_proxy(MODEM_MANAGER, path, CONNECT_TIMEOUT);
+    // "/" disconnects every bearer
+    let _: () = modem
+        .method_call(SIMPLE, "Disconnect", (dbus::Path::from("/"),))
+        .map_err(|e| KioskError::new(ErrorKind::Failed, bus_error(e)))?;
+    read_status(&connection).map_err(|e| KioskError::new(ErrorKind::Failed, e))
+}
+
+/// Send a text message (supervisor)
+#[tauri::command]
+pub fn send_sms(auth: State<'_, AuthState>, number: String, text: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let number = number.trim().replace([' ', '-'], "");
+    if !valid_number(&number) {
+        return Err(KioskError::invalid(format!("Not a phone number: {}", number)));
+    }
+    if text.is_empty() {
+        return Err(KioskError::invalid("The message is empty"));
+    }
+
+    let connection = connect().map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    let path = require_modem(&connection).map_err(KioskError::not_found)?;
+    let mut properties = PropMap::new();
+    properties.insert("number".to_string(), Variant(Box::new(number) as Box<dyn RefArg>));
+    properties.insert("text".to_string(), Variant(Box::new(text) as Box<dyn RefArg>));
+    let modem = connection.with_proxy(MODEM_MANAGER, path, TIMEOUT);
+    let (sms,): (dbus::Path<'static>,) = modem
+        .method_call(MESSAGING, "Create", (properties,))
+        .map_err(|e| KioskError::new(ErrorKind::Failed, bus_error(e)))?;
+    let _: () = connection
+        .with_proxy(MODEM_MANAGER, sms, CONNECT_TIMEOUT)
+        .method_call(SMS, "Send", ())
+        .map_err(|e| KioskError::new(ErrorKind::Failed, bus_error(e)))?;
+    Ok(())
+}
+
+/// Messages stored on the modem (supervisor)
+#[tauri::command]
+pub fn list_sms(auth: State<'_, AuthState>) -> Result<Vec<SmsMessage>, KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let connection = connect().map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    let paths = sms_paths(&connection).map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    Ok(paths
+        .iter()
+        .filter_map(|path| read_sms(&connection, path).ok())
+        .map(|(message, _)| message)
+        .collect())
+}
+
+/// Delete a stored message by its id (supervisor)
+#[tauri::command]
+pub fn delete_sms(auth: State<'_, AuthState>, id: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let sms = dbus::Path::new(id).map_err(|_| KioskError::invalid("Not a message id"))?;
+    let connection = connect().map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    let path = require_modem(&connection).map_err(KioskError::not_found)?;
+    let modem = connection.with_proxy(MODEM_MANAGER, path, TIMEOUT);
+    let _: () = modem
+        .method_call(MESSAGING, "Delete", (sms,))
+        .map_err(|e| KioskError::new(ErrorKind::Failed, bus_error(e)))?;
+    Ok(())
+}
@@ -0,0 +1,192 @@
+//! Monotonic timing
+//!
+//! The wall clock jumps when NTP corrects it or an admin changes the time,
+//! which stalls or races anything that measures intervals with it.
+//! `get_monotonic_time` reads the system's monotonic clock, and
+//! `start_interval` emits `interval-tick:<id>` on a fixed schedule kept by
+//! that clock, so animations and stopwatches stay steady whatever the wall
+//! clock does. Ticks are not kept in event history and are throttled in lite
+//! mode. A background check publishes `clock-jumped` when the wall clock
+//! moves against the monotonic one, for anything showing wall times.
+
+use chrono::Local;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::error::KioskError;
+use crate::{config, events};
+
+const MIN_INTERVAL_MS: u64 = 10;
+
+const MAX_INTERVAL_MS: u64 = 24 * 60 * 60 * 1000;
+
+const MAX_INTERVALS: usize = 32;
+
+const JUMP_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Wall clock drift from the monotonic clock that counts as a jump
+const JUMP_THRESHOLD_MS: i64 = 2000;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonotonicTime {
+    /// Milliseconds on the monotonic clock; only differences are meaningful
+    pub monotonic_ms: f64,
+    /// Like `monotonic_ms`, but also counting time suspended
+    pub boottime_ms: f64,
+    /// Wall clock Unix time in milliseconds, read at the same moment
+    pub wall_ms: i64,
+}
+
+/// Payload of `interval-tick:<id>`
+#[derive(Debug, Clone, Serialize)]
+pub struct IntervalTick {
+    pub id: String,
+    /// Ticks since the interval started, from 1
+    pub seq: u64,
+    pub monotonic_ms: f64,
+    /// Milliseconds since the interval started
+    pub elapsed_ms: f64,
+    /// Ticks skipped because the previous one ran late
+    pub missed: u64,
+}
+
+/// Payload of `clock-jumped`
+#[derive(Debug, Clone, Serialize)]
+pub struct ClockJumped {
+    /// How far the wall clock moved, positive when forwards
+    pub offset_ms: i64,
+    pub wall_ms: i64,
+}
+
+#[derive(Default)]
+pub struct IntervalsState(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn clock_ms(clock: libc::clockid_t) -> f64 {
+    let mut spec = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    // SAFETY: clock_gettime only writes to the timespec it is given
+    if unsafe { libc::clock_gettime(clock, &mut spec) } != 0 {
+        return 0.0;
+    }
+    spec.tv_sec as f64 * 1000.0 + spec.tv_nsec as f64 / 1_000_000.0
+}
+
+pub(crate) fn monotonic_ms() -> f64 {
+    clock_ms(libc::CLOCK_MONOTONIC)
+}
+
+fn run_interval(app: AppHandle, id: String, interval: Duration, stop: Arc<AtomicBool>) {
+    let topic = format!("interval-tick:{}", id);
+    let started = Instant::now();
+    let mut seq = 0u64;
+    while !stop.load(Ordering::Relaxed) {
+        // Each tick is due a whole number of intervals after the start, so
+        // late wakeups do not push the rest of the schedule back
+        let due = started + Duration::from_nanos((interval.as_nanos() * (seq + 1) as u128) as u64);
+        let now = Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+        }
+        let elapsed = started.elapsed();
+        let reached = (elapsed.as_nanos() / interval.as_nanos()) as u64;
+        let missed = reached.saturating_sub(seq + 1);
+        seq = reached.max(seq + 1);
+        events::publish_progress(
+            &app,
+            &topic,
+            IntervalTick {
+                id: id.clone(),
+                seq,
+                monotonic_ms: monotonic_ms(),
+                elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+                missed,
+            },
+        );
+    }
+}
+
+/// Watch for the wall clock jumping against the monotonic clock
+pub fn start_clock_watch(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = (Instant::now(), Local::now().timestamp_millis());
+        loop {
+            std::thread::sleep(config::poll_interval(&app, JUMP_CHECK_INTERVAL));
+            let now = (Instant::now(), Local::now().timestamp_millis());
+            let expected = now.0.duration_since(last.0).as_millis() as i64;
+            let offset_ms = now.1 - last.1 - expected;
+            if offset_ms.abs() >= JUMP_THRESHOLD_MS {
+                events::publish(&app, "clock-jumped", ClockJumped { offset_ms, wall_ms: now.1 });
+            }
+            last = now;
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Read the monotonic clock, with the wall clock for reference
+#[tauri::command]
+pub fn get_monotonic_time() -> MonotonicTime {
+    MonotonicTime {
+        monotonic_ms: monotonic_ms(),
+        boottime_ms: clock_ms(libc::CLOCK_BOOTTIME),
+        wall_ms: Local::now().timestamp_millis(),
+    }
+}
+
+/// Emit `interval-tick:<id>` every `interval_ms` until stopped, returning
+/// the id
+#[tauri::command]
+pub fn start_interval(
+    app: AppHandle,
+    state: State<'_, IntervalsState>,
+    interval_ms: u64,
+) -> Result<String, KioskError> {
+    if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&interval_ms) {
+        return Err(KioskError::invalid(format!(
+            "An interval runs every {} to {} ms",
+            MIN_INTERVAL_MS, MAX_INTERVAL_MS
+        )));
+    }
+    let mut intervals = state.0.lock().expect("intervals lock");
+    if intervals.len() >= MAX_INTERVALS {
+        return Err(KioskError::invalid(format!("At most {} intervals can run at once", MAX_INTERVALS)));
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    let stop = Arc::new(AtomicBool::new(false));
+    intervals.insert(id.clone(), stop.clone());
+
+    let thread_id = id.clone();
+    let interval = Duration::from_millis(interval_ms);
+    std::thread::spawn(move || run_interval(app, thread_id, interval, stop));
+    Ok(id)
+}
+
+/// Stop an interval started with `start_interval`
+#[tauri::command]
+pub fn stop_interval(state: State<'_, IntervalsState>, id: String) -> Result<(), KioskError> {
+    let stop = state
+        .0
+        .lock()
+        .expect("intervals lock")
+        .remove(&id)
+        .ok_or_else(|| KioskError::not_found(format!("Interval not found: {}", id)))?;
+    stop.store(true, Ordering::Relaxed);
+    Ok(())
+}
@@ -0,0 +1,556 @@
+//! Night light and colour filters
+//!
+//! Warms the screen at night the way redshift does, and applies a colour
+//! filter for colour-blind or low-vision visitors, at the display level so
+//! launched apps are covered too. The night temperature follows a
+//! schedule: always on, between two times of day, or with the sun at the
+//! unit's location (the latest fix, or the weather location), fading as
+//! the sun goes from 3° above the horizon to 6° below it.
+//!
+//! Hyprland gets a screen shader. On X11 the colour transform matrix of
+//! each output is set through `xrandr`; drivers without one fall back to
+//! gamma ramps through gammastep or redshift. Other Wayland compositors
+//! get gamma ramps from a gammastep kept running, which cannot do filters.
+//! Changes are published as `color-changed`.
+
+use chrono::{Datelike, Local, NaiveTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fs;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::pointer::run;
+use crate::{events, location, store, weather};
+
+const COLOR_FILE: &str = "color.json";
+
+const SHADER_FILE: &str = "nightlight.frag";
+
+/// The screen's own white point
+const DAY_KELVIN: u32 = 6500;
+const MIN_KELVIN: u32 = 1000;
+
+/// Sun elevations, in degrees, where the fade to night starts and ends
+const DAY_ELEVATION: f64 = 3.0;
+const NIGHT_ELEVATION: f64 = -6.0;
+
+/// Smallest temperature change worth redrawing the screen for
+const KELVIN_STEP: u32 = 25;
+
+const TICK_INTERVAL: Duration = Duration::from_secs(60);
+
+type Matrix = [[f64; 3]; 3];
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ColorSchedule {
+    /// No night light
+    Off,
+    Always,
+    /// From sunset to sunrise at the unit's location
+    Sun,
+    /// From `start` to `end` ("HH:MM"), past midnight if `end` is earlier
+    Times { start: String, end: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorFilter {
+    None,
+    Grayscale,
+    /// Shifts reds for those without red cones
+    Protanopia,
+    /// Shifts greens for those without green cones
+    Deuteranopia,
+    /// Shifts blues for those without blue cones
+    Tritanopia,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorSettings {
+    /// Temperature at night
+    pub kelvin: u32,
+    pub schedule: ColorSchedule,
+    pub filter: ColorFilter,
+}
+
+impl Default for ColorSettings {
+    fn default() -> Self {
+        ColorSettings {
+            kelvin: 3400,
+            schedule: ColorSchedule::Off,
+            filter: ColorFilter::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColorStatus {
+    /// Temperature on screen now
+    pub kelvin: u32,
+    pub filter: ColorFilter,
+    /// How far into the night the schedule is, 0 (day) to 1
+    pub night: f64,
+    /// What applied it: "hyprland", "ctm", "gammastep", "redshift"
+    pub backend: Option<String>,
+    pub error: Option<String>,
+}
+
+impl Default for ColorStatus {
+    fn default() -> Self {
+        ColorStatus {
+            kelvin: DAY_KELVIN,
+            filter: ColorFilter::None,
+            night: 0.0,
+            backend: None,
+            error: None,
+        }
+    }
+}
+
+pub struct ColorState {
+    settings: Mutex<ColorSettings>,
+    status: Mutex<ColorStatus>,
+    /// gammastep holding the gamma ramps on Wayland
+    gammastep: Mutex<Option<Child>>,
+}
+
+impl ColorState {
+    pub fn load(app: &AppHandle) -> Self {
+        ColorState {
+            settings: Mutex::new(store::load(app, COLOR_FILE)),
+            status: Mutex::new(ColorStatus::default()),
+            gammastep: Mutex::new(None),
+        }
+    }
+}
+
+// ============================================================================
+// Colour
+// ============================================================================
+
+/// Red, green and blue gains for a white point, 1 at 6500 K (after Tanner
+/// Helland's fit to the blackbody colours)
+fn white_point(kelvin: u32) -> [f64; 3] {
+    let rgb = |kelvin: f64| {
+        let t = kelvin / 100.0;
+        let red = if t <= 66.0 { 255.0 } else { 329.698_727_446 * (t - 60.0).powf(-0.133_204_759_2) };
+        let green = if t <= 66.0 {
+            99.470_802_586_1 * t.ln() - 161.119_568_166_1
+        } else {
+            288.122_169_528_3 * (t - 60.0).powf(-0.075_514_849_2)
+        };
+        let blue = if t >= 66.0 {
+            255.0
+        } else if t <= 19.0 {
+            0.0
+        } else {
+            138.517_731_223_1 * (t - 10.0).ln() - 305.044_792_730_7
+        };
+        [red, green, blue].map(|value| value.clamp(0.0, 255.0))
+    };
+    let (target, reference) = (rgb(f64::from(kelvin)), rgb(f64::from(DAY_KELVIN)));
+    [0, 1, 2].map(|i| (target[i] / reference[i]).min(1.0))
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [[0.0; 3]; 3];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+/// Daltonization: the colours a viewer would lose are moved into the
+/// channels they can see
+fn daltonize(simulation: &Matrix, shift: &Matrix) -> Matrix {
+    let mut lost = IDENTITY;
+    for (i, row) in lost.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value -= simulation[i][j];
+        }
+    }
+    let mut matrix = multiply(shift, &lost);
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[i] += 1.0;
+    }
+    matrix
+}
+
+fn filter_matrix(filter: ColorFilter) -> Matrix {
+    // Error shifts after Fidaner, Lin and Ozguven; simulations from Machado,
+    // Oliveira and Fernandes (2009) at full severity
+    const RED_GREEN_SHIFT: Matrix = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+    const BLUE_SHIFT: Matrix = [[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]];
+    match filter {
+        ColorFilter::None => IDENTITY,
+        ColorFilter::Grayscale => [[0.2126, 0.7152, 0.0722]; 3],
+        ColorFilter::Protanopia => daltonize(
+            &[
+                [0.152_286, 1.052_583, -0.204_868],
+                [0.114_503, 0.786_281, 0.099_216],
+                [-0.003_882, -0.048_116, 1.051_998],
+            ],
+            &RED_GREEN_SHIFT,
+        ),
+        ColorFilter::Deuteranopia => daltonize(
+            &[
+                [0.367_322, 0.860_646, -0.227_968],
+                [0.280_085, 0.672_501, 0.047_413],
+                [-0.011_820, 0.042_940, 0.968_881],
+            ],
+            &RED_GREEN_SHIFT,
+        ),
+        ColorFilter::Tritanopia => daltonize(
+            &[
+                [1.255_528, -0.076_749, -0.178_779],
+                [-0.078_411, 0.930_809, 0.147_602],
+                [0.004_733, 0.691_367, 0.303_900],
+            ],
+            &BLUE_SHIFT,
+        ),
+    }
+}
+
+/// The filter, then the white point
+fn color_matrix(kelvin: u32, filter: ColorFilter) -> Matrix {
+    let [red, green, blue] = white_point(kelvin);
+    multiply(&[[red, 0.0, 0.0], [0.0, green, 0.0], [0.0, 0.0, blue]], &filter_matrix(filter))
+}
+
+// ============================================================================
+// Schedule
+// ============================================================================
+
+/// The sun's elevation in degrees (NOAA's approximation)
+fn sun_elevation(latitude: f64, longitude: f64, now: chrono::DateTime<Utc>) -> f64 {
+    let hours = f64::from(now.hour()) + f64::from(now.minute()) / 60.0 + f64::from(now.second()) / 3600.0;
+    let year = 2.0 * PI / 365.0 * (f64::from(now.ordinal0()) + (hours - 12.0) / 24.0);
+    let equation_of_time = 229.18
+        * (0.000_075 + 0.001_868 * year.cos()
+            - 0.032_077 * year.sin()
+            - 0.014_615 * (2.0 * year).cos()
+            - 0.040_849 * (2.0 * year).sin());
+    let declination = 0.006_918 - 0.399_912 * year.cos() + 0.070_257 * year.sin() - 0.006_758 * (2.0 * year).cos()
+        + 0.000_907 * (2.0 * year).sin()
+        - 0.002_697 * (3.0 * year).cos()
+        + 0.001_48 * (3.0 * year).sin();
+    let solar_minutes = hours * 60.0 + equation_of_time + 4.0 * longitude;
+    let hour_angle = (solar_minutes / 4.0 - 180.0).to_radians();
+    let latitude = latitude.to_radians();
+    let zenith_cos = latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    90.0 - zenith_cos.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Not a time (HH:MM): {}", time))
+}
+
+/// Where the sun is worked out for
+fn position(app: &AppHandle) -> Option<(f64, f64)> {
+    location::last_position(app).or_else(|| weather::configured_position(app))
+}
+
+/// How far into the night the schedule is, 0 to 1
+fn night(app: &AppHandle, schedule: &ColorSchedule) -> f64 {
+    match schedule {
+        ColorSchedule::Off => 0.0,
+        ColorSchedule::Always => 1.0,
+        ColorSchedule::Sun => match position(app) {
+            Some((latitude, longitude)) => {
+                let elevation = sun_elevation(latitude, longitude, Utc::now());
+                ((DAY_ELEVATION - elevation) / (DAY_ELEVATION - NIGHT_ELEVATION)).clamp(0.0, 1.0)
+            }
+            None => 0.0,
+        },
+        ColorSchedule::Times { start, end } => {
+            let (Ok(start), Ok(end)) = (parse_time(start), parse_time(end)) else {
+                return 0.0;
+            };
+            let now = Local::now().time();
+            let inside = if start <= end {
+                start <= now && now < end
+            } else {
+                now >= start || now < end
+            };
+            if inside {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Display
+// ============================================================================
+
+/// A fragment shader applying the matrix, for Hyprland's screen shader
+fn shader(matrix: &Matrix) -> String {
+    // GLSL matrices are built column by column
+    let columns: Vec<String> = (0..3)
+        .flat_map(|j| (0..3).map(move |i| (i, j)))
+        .map(|(i, j)| format!("{:.6}", matrix[i][j]))
+        .collect();
+    format!(
+        "precision highp float;\n\
+         varying vec2 v_texcoord;\n\
+         uniform sampler2D tex;\n\
+         const mat3 color = mat3({});\n\
+         void main() {{\n\
+         \x20   vec4 pixel = texture2D(tex, v_texcoord);\n\
+         \x20   gl_FragColor = vec4(clamp(color * pixel.rgb, 0.0, 1.0), pixel.a);\n\
+         }}\n",
+        columns.join(", ")
+    )
+}
+
+fn apply_hyprland(app: &AppHandle, matrix: &Matrix) -> Result<(), String> {
+    if *matrix == IDENTITY {
+        return run("hyprctl", &["keyword", "decoration:screen_shader", "[[EMPTY]]"]);
+    }
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let path = dir.join(SHADER_FILE);
+    fs::write(&path, shader(matrix)).map_err(|e| format!("{}: {}", path.display(), e))?;
+    run("hyprctl", &["keyword", "decoration:screen_shader", &path.to_string_lossy()])
+}
+
+/// The matrix as xrandr takes the CTM property: each entry as S31.32
+/// sign-magnitude fixed point, low word first
+fn ctm_value(matrix: &Matrix) -> String {
+    let words: Vec<String> = matrix
+        .iter()
+        .flatten()
+        .flat_map(|&value| {
+            let mut fixed = (value.abs() * 4_294_967_296.0).round() as u64 & !(1 << 63);
+            if value < 0.0 {
+                fixed |= 1 << 63;
+            }
+            [fixed & 0xffff_ffff, fixed >> 32]
+        })
+        .map(|word| word.to_string())
+        .collect();
+    words.join(",")
+}
+
+fn x11_outputs() -> Result<Vec<String>, String> {
+    let output = Command::new("xrandr")
+        .arg("--query")
+        .output()
+        .map_err(|e| format!("Failed to run xrandr: {}", e))?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.contains(" connected"))
+        .filter_map(|line| line.split_whitespace().next().map(str::to_string))
+        .collect())
+}
+
+fn apply_ctm(matrix: &Matrix) -> Result<(), String> {
+    let outputs = x11_outputs()?;
+    if outputs.is_empty() {
+        return Err("xrandr found no connected outputs".into());
+    }
+    let value = ctm_value(matrix);
+    outputs
+        .iter()
+        .try_for_each(|output| run("xrandr", &["--output", output, "--set", "CTM", &value]))
+}
+
+/// Gamma ramps through whichever of gammastep and redshift is installed;
+/// the ramps stay after it exits
+fn apply_x11_gamma(kelvin: u32) -> Result<&'static str, String> {
+    let temperature = kelvin.to_string();
+    let mut errors = Vec::new();
+    for program in ["gammastep", "redshift"] {
+        let result = if kelvin >= DAY_KELVIN {
+            run(program, &["-m", "randr", "-x"])
+        } else {
+            run(program, &["-m", "randr", "-P", "-O", &temperature])
+        };
+        match result {
+            Ok(()) => return Ok(program),
+            Err(e) => errors.push(e),
+        }
+    }
+    Err(errors.join("; "))
+}
+
+/// Wayland drops a client's gamma ramps when it exits, so gammastep is
+/// kept running while the screen is warmed
+fn apply_wayland_gamma(state: &ColorState, kelvin: u32) -> Result<(), String> {
+    let mut gammastep = state.gammastep.lock().expect("gammastep lock");
+    if let Some(mut child) = gammastep.take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    if kelvin >= DAY_KELVIN {
+        return Ok(());
+    }
+    let mut child = Command::new("gammastep")
+        .args(["-m", "wayland", "-O", &kelvin.to_string()])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run gammastep: {}", e))?;
+    // A compositor without gamma control makes it give up straight away
+    std::thread::sleep(Duration::from_millis(500));
+    if let Ok(Some(status)) = child.try_wait() {
+        return Err(format!("gammastep could not set the gamma ({})", status));
+    }
+    *gammastep = Some(child);
+    Ok(())
+}
+
+/// Put the temperature and filter on screen; returns what did it
+fn apply(app: &AppHandle, state: &ColorState, kelvin: u32, filter: ColorFilter) -> Result<String, String> {
+    let matrix = color_matrix(kelvin, filter);
+    if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        return apply_hyprland(app, &matrix).map(|()| "hyprland".to_string());
+    }
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        if filter != ColorFilter::None {
+            return Err("Colour filters need Hyprland or X11".into());
+        }
+        return apply_wayland_gamma(state, kelvin).map(|()| "gammastep".to_string());
+    }
+    if std::env::var_os("DISPLAY").is_none() {
+        return Err("No display to adjust".into());
+    }
+    match apply_ctm(&matrix) {
+        Ok(()) => Ok("ctm".to_string()),
+        Err(e) if filter != ColorFilter::None => Err(format!("The display cannot take a colour filter: {}", e)),
+        Err(_) => apply_x11_gamma(kelvin).map(str::to_string),
+    }
+}
+
+/// Bring the screen in line with the settings and the time of day,
+/// publishing any change
+fn update(app: &AppHandle) -> ColorStatus {
+    let state = app.state::<ColorState>();
+    let settings = state.settings.lock().expect("color lock").clone();
+    let night = night(app, &settings.schedule);
+    let target = DAY_KELVIN - (f64::from(DAY_KELVIN - settings.kelvin) * night).round() as u32;
+
+    let mut status = state.status.lock().expect("color status lock");
+    status.night = night;
+    let unchanged = status.filter == settings.filter && status.kelvin.abs_diff(target) < KELVIN_STEP;
+    if unchanged && status.error.is_none() {
+        return status.clone();
+    }
+    match apply(app, &state, target, settings.filter) {
+        Ok(backend) => {
+            status.kelvin = target;
+            status.filter = settings.filter;
+            status.backend = Some(backend);
+            status.error = None;
+        }
+        Err(e) => status.error = Some(e),
+    }
+    let status = status.clone();
+    events::publish(app, "color-changed", &status);
+    status
+}
+
+/// Follow the schedule in the background
+pub fn start_nightlight(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        update(&app);
+        std::thread::sleep(TICK_INTERVAL);
+    });
+}
+
+fn validate(settings: &ColorSettings) -> Result<(), String> {
+    if !(MIN_KELVIN..=DAY_KELVIN).contains(&settings.kelvin) {
+        return Err(format!("The temperature must be {}-{} K", MIN_KELVIN, DAY_KELVIN));
+    }
+    if let ColorSchedule::Times { start, end } = &settings.schedule {
+        if parse_time(start)? == parse_time(end)? {
+            return Err("The night must start and end at different times".into());
+        }
+    }
+    Ok(())
+}
+
+/// Save new settings and apply them straight away
+fn change(
+    app: &AppHandle,
+    state: &ColorState,
+    edit: impl FnOnce(&mut ColorSettings),
+) -> Result<ColorStatus, KioskError> {
+    {
+        let mut settings = state.settings.lock().expect("color lock");
+        let mut updated = settings.clone();
+        edit(&mut updated);
+        validate(&updated).map_err(KioskError::invalid)?;
+        store::save(app, COLOR_FILE, &updated)?;
+        *settings = updated;
+    }
+    let status = update(app);
+    match status.error {
+        Some(e) => Err(KioskError::new(ErrorKind::Failed, e)),
+        None => Ok(status),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_color_settings(state: State<'_, ColorState>) -> ColorSettings {
+    state.settings.lock().expect("color lock").clone()
+}
+
+/// The temperature and filter on screen now
+#[tauri::command]
+pub fn get_color_status(state: State<'_, ColorState>) -> ColorStatus {
+    state.status.lock().expect("color status lock").clone()
+}
+
+/// Set the night temperature and when it applies
+#[tauri::command]
+pub fn set_color_temperature(
+    app: AppHandle,
+    state: State<'_, ColorState>,
+    auth: State<'_, AuthState>,
+    kelvin: u32,
+    schedule: ColorSchedule,
+) -> Result<ColorStatus, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    if schedule == ColorSchedule::Sun && position(&app).is_none() {
+        return Err(KioskError::invalid("No location is configured to follow the sun at"));
+    }
+    change(&app, &state, |settings| {
+        settings.kelvin = kelvin;
+        settings.schedule = schedule;
+    })
+}
+
+/// Set the colour filter, which applies day and night
+#[tauri::command]
+pub fn set_color_filter(
+    app: AppHandle,
+    state: State<'_, ColorState>,
+    auth: State<'_, AuthState>,
+    filter: ColorFilter,
+) -> Result<ColorStatus, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    change(&app, &state, |settings| settings.filter = filter)
+}
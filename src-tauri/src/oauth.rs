@@ -0,0 +1,448 @@
+//! OAuth2 device authorization
+//!
+//! Connects the kiosk to cloud APIs with the device-code flow (RFC 8628): the
+//! kiosk shows a short code, an operator approves it on their own phone or
+//! PC, and no browser login is ever exposed to walk-up users. Refresh tokens
+//! are sealed in the keyring; access tokens live only in memory.
+//!
+//! Providers are set up by admins. Pointing a provider at different
+//! endpoints drops its stored client secret and tokens, so they are never
+//! sent to a server they were not issued for.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::http::ResponseExt;
+use crate::{events, http, keyring, store};
+
+const PROVIDERS_FILE: &str = "oauth_providers.json";
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Renew access tokens this long before they expire
+const EXPIRY_MARGIN: i64 = 60;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthProvider {
+    pub id: String,
+    pub name: String,
+    pub device_authorization_url: String,
+    pub token_url: String,
+    pub client_id: String,
+    /// Only some providers (e.g. Google) require a secret for device clients
+    #[serde(default)]
+    pub client_secret: String,
+    pub scope: String,
+}
+
+/// What the operator needs to approve the kiosk
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceAuthPrompt {
+    pub provider: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// URI with the code embedded, suitable for a QR code
+    pub verification_uri_complete: Option<String>,
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceAuthStatus {
+    Pending,
+    Authorized,
+    Denied,
+    Expired,
+    Cancelled,
+    Error,
+}
+
+/// Payload of `oauth-status`
+#[derive(Debug, Clone, Serialize)]
+pub struct OAuthStatusEvent {
+    pub provider: String,
+    pub status: DeviceAuthStatus,
+    pub error: Option<String>,
+}
+
+/// Provider as listed for the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct OAuthConnection {
+    pub provider: OAuthProvider,
+    /// A refresh token is stored
+    pub connected: bool,
+    pub pending: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    /// Google still uses the draft name `verification_url`
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    #[serde(default)]
+    verification_uri_complete: Option<String>,
+    expires_in: i64,
+    #[serde(default)]
+    interval: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<i64>,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+struct AccessToken {
+    token: String,
+    expires_at: i64,
+}
+
+pub struct OAuthState {
+    providers: Mutex<Vec<OAuthProvider>>,
+    tokens: Mutex<HashMap<String, AccessToken>>,
+    /// Cancel flags of device flows in progress
+    pending: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl OAuthState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut providers: Vec<OAuthProvider> = store::load(app, PROVIDERS_FILE);
+        for provider in &mut providers {
+            provider.client_secret = keyring::get(app, &secret_key(&provider.id)).ok().flatten().unwrap_or_default();
+        }
+        OAuthState {
+            providers: Mutex::new(providers),
+            tokens: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn refresh_key(provider: &str) -> String {
+    format!("oauth/{}/refresh", provider)
+}
+
+fn secret_key(provider: &str) -> String {
+    format!("oauth/{}/client_secret", provider)
+}
+
+fn find_provider(state: &OAuthState, id: &str) -> Result<OAuthProvider, String> {
+    state
+        .providers
+        .lock()
+        .expect("oauth providers lock")
+        .iter()
+        .find(|provider| provider.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown OAuth provider: {}", id))
+}
+
+/// Save providers with client secrets kept in the keyring only
+fn save_providers(app: &AppHandle, providers: &[OAuthProvider]) -> Result<(), String> {
+    let stripped: Vec<OAuthProvider> = providers
+        .iter()
+        .cloned()
+        .map(|provider| OAuthProvider {
+            client_secret: String::new(),
+            ..provider
+        })
+        .collect();
+    store::save(app, PROVIDERS_FILE, &stripped)
+}
+
+fn client_params(provider: &OAuthProvider) -> Vec<(&'static str, String)> {
+    let mut params = vec![("client_id", provider.client_id.clone())];
+    if !provider.client_secret.is_empty() {
+        params.push(("client_secret", provider.client_secret.clone()));
+    }
+    params
+}
+
+/// POST a form to the token endpoint; OAuth errors come back as `Err(TokenError)`
+fn token_request(url: &str, params: &[(&str, String)]) -> Result<Result<TokenResponse, TokenError>, String> {
+    let form: Vec<(&str, &str)> = params.iter().map(|(key, value)| (*key, value.as_str())).collect();
+    match http::agent().post(url).send_form(&form) {
+        Ok(response) => response.into_throttled_json().map(Ok).map_err(|e| e.to_string()),
+        Err(ureq::Error::Status(_, response)) => response
+            .into_throttled_json::<TokenError>()
+            .map(Err)
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Remember a fresh token response, sealing any new refresh token
+fn store_tokens(app: &AppHandle, provider: &str, response: TokenResponse) -> Result<String, String> {
+    if let Some(refresh) = &response.refresh_token {
+        keyring::put(app, &refresh_key(provider), refresh)?;
+    }
+    let expires_at = Local::now().timestamp() + response.expires_in.unwrap_or(3600);
+    app.state::<OAuthState>().tokens.lock().expect("oauth tokens lock").insert(
+        provider.to_string(),
+        AccessToken {
+            token: response.access_token.clone(),
+            expires_at,
+        },
+    );
+    Ok(response.access_token)
+}
+
+fn emit_status(app: &AppHandle, provider: &str, status: DeviceAuthStatus, error: Option<String>) {
+    events::publish(
+        app,
+        "oauth-status",
+        OAuthStatusEvent {
+            provider: provider.to_string(),
+            status,
+            error,
+        },
+    );
+}
+
+/// Poll the token endpoint until the operator approves, denies or the code expires
+fn poll_device_code(
+    app: AppHandle,
+    provider: OAuthProvider,
+    device: DeviceCodeResponse,
+    cancel: Arc<AtomicBool>,
+) {
+    let mut interval = Duration::from_secs(device.interval.unwrap_or(5).max(1));
+    let expires_at = Local::now().timestamp() + device.expires_in;
+    let mut params = client_params(&provider);
+    params.push(("grant_type", DEVICE_CODE_GRANT.to_string()));
+    params.push(("device_code", device.device_code));
+
+    let (status, error) = loop {
+        std::thread::sleep(interval);
+        if cancel.load(Ordering::SeqCst) {
+            break (DeviceAuthStatus::Cancelled, None);
+        }
+        if Local::now().timestamp() >= expires_at {
+            break (DeviceAuthStatus::Expired, None);
+        }
+
+        match token_request(&provider.token_url, &params) {
+            Ok(Ok(response)) => match store_tokens(&app, &provider.id, response) {
+                Ok(_) => break (DeviceAuthStatus::Authorized, None),
+                Err(e) => break (DeviceAuthStatus::Error, Some(e)),
+            },
+            Ok(Err(error)) => match error.error.as_str() {
+                "authorization_pending" => {}
+                "slow_down" => interval += Duration::from_secs(5),
+                "access_denied" => break (DeviceAuthStatus::Denied, None),
+                "expired_token" => break (DeviceAuthStatus::Expired, None),
+                _ => break (DeviceAuthStatus::Error, Some(error.error_description.unwrap_or(error.error))),
+            },
+            // Network hiccups: keep polling until the code expires
+            Err(_) => {}
+        }
+    };
+
+    let state = app.state::<OAuthState>();
+    let mut pending = state.pending.lock().expect("oauth pending lock");
+    // A restarted flow has replaced our entry; leave it alone
+    if pending.get(&provider.id).is_some_and(|flag| Arc::ptr_eq(flag, &cancel)) {
+        pending.remove(&provider.id);
+    }
+    drop(pending);
+    emit_status(&app, &provider.id, status, error);
+}
+
+/// A valid access token for the provider, refreshed when needed.
+///
+/// Other modules call this before talking to the provider's APIs.
+pub(crate) fn access_token(app: &AppHandle, provider_id: &str) -> Result<String, String> {
+    let state = app.state::<OAuthState>();
+    if let Some(token) = state.tokens.lock().expect("oauth tokens lock").get(provider_id) {
+        if token.expires_at - EXPIRY_MARGIN > Local::now().timestamp() {
+            return Ok(token.token.clone());
+        }
+    }
+
+    let provider = find_provider(&state, provider_id)?;
+    let refresh = keyring::get(app, &refresh_key(provider_id))?.ok_or("Not connected; run device sign-in first")?;
+    let mut params = client_params(&provider);
+    params.push(("grant_type", "refresh_token".to_string()));
+    params.push(("refresh_token", refresh));
+
+    match token_request(&provider.token_url, &params)? {
+        Ok(response) => store_tokens(app, provider_id, response),
+        Err(error) => {
+            // A revoked refresh token will never work again
+            if error.error == "invalid_grant" {
+                let _ = keyring::remove(app, &refresh_key(provider_id));
+            }
+            Err(error.error_description.unwrap_or(error.error))
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List OAuth providers and whether each is connected
+#[tauri::command]
+pub fn list_oauth_providers(app: AppHandle, state: State<'_, OAuthState>) -> Vec<OAuthConnection> {
+    let pending = state.pending.lock().expect("oauth pending lock");
+    state
+        .providers
+        .lock()
+        .expect("oauth providers lock")
+        .iter()
+        .map(|provider| OAuthConnection {
+            connected: keyring::get(&app, &refresh_key(&provider.id)).ok().flatten().is_some(),
+            pending: pending.contains_key(&provider.id),
+            provider: OAuthProvider {
+                client_secret: String::new(),
+                ..provider.clone()
+            },
+        })
+        .collect()
+}
+
+/// Create or update a provider (admin). An empty client secret keeps the
+/// stored one, unless an endpoint or the client id changed; then the secret
+/// and any tokens are dropped.
+#[tauri::command]
+pub fn save_oauth_provider(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, OAuthState>,
+    mut provider: OAuthProvider,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if provider.id.trim().is_empty() || provider.client_id.trim().is_empty() {
+        return Err(KioskError::invalid("Provider id and client id are required"));
+    }
+
+    let mut providers = state.providers.lock().expect("oauth providers lock");
+    let position = providers.iter().position(|existing| existing.id == provider.id);
+    let moved = position.is_some_and(|index| {
+        let existing = &providers[index];
+        existing.device_authorization_url != provider.device_authorization_url
+            || existing.token_url != provider.token_url
+            || existing.client_id != provider.client_id
+    });
+    if moved {
+        state.tokens.lock().expect("oauth tokens lock").remove(&provider.id);
+        keyring::remove(&app, &refresh_key(&provider.id))?;
+    }
+    if provider.client_secret.is_empty() {
+        match position {
+            Some(_) if moved => keyring::remove(&app, &secret_key(&provider.id))?,
+            Some(index) => provider.client_secret = providers[index].client_secret.clone(),
+            None => {}
+        }
+    } else {
+        keyring::put(&app, &secret_key(&provider.id), &provider.client_secret)?;
+    }
+    match position {
+        Some(index) => providers[index] = provider,
+        None => providers.push(provider),
+    }
+    Ok(save_providers(&app, &providers)?)
+}
+
+/// Remove a provider along with its stored tokens (admin)
+#[tauri::command]
+pub fn delete_oauth_provider(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, OAuthState>,
+    id: String,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut providers = state.providers.lock().expect("oauth providers lock");
+    providers.retain(|provider| provider.id != id);
+    state.tokens.lock().expect("oauth tokens lock").remove(&id);
+    keyring::remove(&app, &refresh_key(&id))?;
+    keyring::remove(&app, &secret_key(&id))?;
+    Ok(save_providers(&app, &providers)?)
+}
+
+/// Start device sign-in; the result arrives as an `oauth-status` event
+#[tauri::command(async)]
+pub fn start_device_auth(app: AppHandle, state: State<'_, OAuthState>, provider: String) -> Result<DeviceAuthPrompt, KioskError> {
+    let provider = find_provider(&state, &provider)?;
+    if let Some(cancel) = state.pending.lock().expect("oauth pending lock").remove(&provider.id) {
+        cancel.store(true, Ordering::SeqCst);
+    }
+
+    let mut params = client_params(&provider);
+    params.push(("scope", provider.scope.clone()));
+    let form: Vec<(&str, &str)> = params.iter().map(|(key, value)| (*key, value.as_str())).collect();
+    let device: DeviceCodeResponse = http::agent()
+        .post(&provider.device_authorization_url)
+        .send_form(&form)
+        .map_err(|e| format!("Device authorization failed: {}", e))?
+        .into_throttled_json()
+        .map_err(|e| e.to_string())?;
+
+    let prompt = DeviceAuthPrompt {
+        provider: provider.id.clone(),
+        user_code: device.user_code.clone(),
+        verification_uri: device.verification_uri.clone(),
+        verification_uri_complete: device.verification_uri_complete.clone(),
+        expires_at: Local::now().timestamp() + device.expires_in,
+    };
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let mut pending = state.pending.lock().expect("oauth pending lock");
+    if let Some(previous) = pending.insert(provider.id.clone(), cancel.clone()) {
+        // Another sign-in started while this one waited on the provider
+        previous.store(true, Ordering::SeqCst);
+    }
+    drop(pending);
+    emit_status(&app, &provider.id, DeviceAuthStatus::Pending, None);
+    std::thread::spawn(move || poll_device_code(app, provider, device, cancel));
+    Ok(prompt)
+}
+
+/// Abandon a device sign-in in progress
+#[tauri::command]
+pub fn cancel_device_auth(state: State<'_, OAuthState>, provider: String) {
+    if let Some(cancel) = state.pending.lock().expect("oauth pending lock").get(&provider) {
+        cancel.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Get a valid access token, refreshing it if needed
+#[tauri::command]
+pub fn get_access_token(app: AppHandle, provider: String) -> Result<String, KioskError> {
+    Ok(access_token(&app, &provider)?)
+}
+
+/// Forget the stored tokens for a provider
+#[tauri::command]
+pub fn sign_out_provider(app: AppHandle, state: State<'_, OAuthState>, provider: String) -> Result<(), KioskError> {
+    state.tokens.lock().expect("oauth tokens lock").remove(&provider);
+    Ok(keyring::remove(&app, &refresh_key(&provider))?)
+}
@@ -0,0 +1,212 @@
+//! Read-only root
+//!
+//! Turns the Raspberry Pi OS overlay filesystem on and off through
+//! `raspi-config`. With the overlay active the SD card is mounted read-only
+//! underneath a RAM-backed upper layer, so a power pull cannot corrupt it and
+//! every change is lost at reboot unless an admin commits it: the kiosk's own
+//! data folders (and any roots passed in) are copied down to the lower layer.
+//! Switching takes effect at the next boot. Needs root, or a sudoers rule
+//! letting the kiosk user run `raspi-config`, `mount`, `mkdir`, `cp`, `rsync`
+//! and `sync` without a password.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::events;
+use crate::vfs::{self, Access};
+
+const RASPI_CONFIG: &str = "/usr/bin/raspi-config";
+
+/// Where Raspberry Pi OS mounts the SD card under the overlay
+const DEFAULT_LOWER_DIR: &str = "/media/root-ro";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlayStatus {
+    /// raspi-config is installed, so the overlay can be switched
+    pub supported: bool,
+    /// The root filesystem is an overlay now
+    pub active: bool,
+    /// The overlay will be on after the next boot
+    pub enabled_next_boot: bool,
+    /// The boot partition is mounted read-only
+    pub boot_read_only: bool,
+    /// The change only happens at the next boot
+    pub reboot_pending: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitResult {
+    /// Folders copied to the SD card
+    pub committed: Vec<String>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Run a command as root, through `sudo -n` unless already root
+pub(crate) fn privileged(program: &str, args: &[&str]) -> Result<String, String> {
+    // SAFETY: geteuid has no preconditions
+    let is_root = unsafe { libc::geteuid() } == 0;
+    let mut command = if is_root {
+        Command::new(program)
+    } else {
+        let mut sudo = Command::new("sudo");
+        sudo.args(["-n", program]);
+        sudo
+    };
+    let output = command.args(args).output().map_err(|e| format!("{}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} failed: {}", program, stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// raspi-config's `get_*` queries print 0 for enabled
+fn raspi_query(query: &str) -> bool {
+    privileged(RASPI_CONFIG, &["nonint", query]).is_ok_and(|value| value == "0")
+}
+
+/// The lower directory of an overlay mounted on `/`, if there is one
+fn overlay_lower_dir() -> Option<PathBuf> {
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+    let options = mounts.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        (fields.get(1) == Some(&"/") && fields.get(2) == Some(&"overlay")).then(|| fields.get(3).copied())?
+    })?;
+    let lower = options
+        .split(',')
+        .find_map(|option| option.strip_prefix("lowerdir="))
+        .and_then(|dirs| dirs.split(':').next())
+        .unwrap_or(DEFAULT_LOWER_DIR);
+    Some(PathBuf::from(lower))
+}
+
+fn status() -> OverlayStatus {
+    let supported = Path::new(RASPI_CONFIG).exists();
+    let active = overlay_lower_dir().is_some();
+    let enabled_next_boot = supported && raspi_query("get_overlay_conf");
+    OverlayStatus {
+        supported,
+        active,
+        enabled_next_boot,
+        boot_read_only: supported && raspi_query("get_bootro_conf"),
+        reboot_pending: supported && active != enabled_next_boot,
+    }
+}
+
+fn switch(app: &AppHandle, enable: bool) -> Result<OverlayStatus, KioskError> {
+    auth::require(&app.state::<AuthState>(), Role::Admin)?;
+    if !Path::new(RASPI_CONFIG).exists() {
+        return Err(KioskError::invalid("raspi-config is not installed"));
+    }
+    let (overlay, boot) = if enable {
+        ("enable_overlayfs", "enable_bootro")
+    } else {
+        ("disable_overlayfs", "disable_bootro")
+    };
+    privileged(RASPI_CONFIG, &["nonint", overlay])?;
+    privileged(RASPI_CONFIG, &["nonint", boot])?;
+
+    let status = status();
+    events::publish(app, "overlay-changed", &status);
+    Ok(status)
+}
+
+/// Copy each folder down to the lower layer, with the lower layer writable
+/// only for the duration
+fn commit(lower: &Path, folders: &[PathBuf]) -> Result<(), String> {
+    let lower_dir = lower.to_string_lossy().to_string();
+    privileged("mount", &["-o", "remount,rw", &lower_dir])?;
+    let result = folders.iter().try_for_each(|folder| {
+        let relative = folder.strip_prefix("/").map_err(|_| format!("Not an absolute path: {}", folder.display()))?;
+        let target = lower.join(relative);
+        if !folder.is_dir() {
+            let parent = target.parent().unwrap_or(lower).to_string_lossy().to_string();
+            privileged("mkdir", &["-p", &parent])?;
+            return privileged("cp", &["-a", &folder.to_string_lossy(), &target.to_string_lossy()]).map(|_| ());
+        }
+        // Trailing slashes copy the folder's contents; --delete drops files removed since boot
+        let source = format!("{}/", folder.display());
+        let target = format!("{}/", target.display());
+        privileged("mkdir", &["-p", &target])?;
+        privileged("rsync", &["-a", "--delete", &source, &target]).map(|_| ())
+    });
+    let synced = privileged("sync", &[]);
+    let remounted = privileged("mount", &["-o", "remount,ro", &lower_dir]);
+    result.and(synced).and(remounted).map(|_| ())
+}
+
+/// With the overlay active, copy the kiosk's data folders and `extra` down
+/// to the SD card and publish `overlay-committed`; does nothing otherwise
+pub(crate) fn persist(app: &AppHandle, extra: Vec<PathBuf>) -> Result<Vec<PathBuf>, String> {
+    let Some(lower) = overlay_lower_dir() else {
+        return Ok(Vec::new());
+    };
+    let resolver = app.path();
+    let mut folders: Vec<PathBuf> = [resolver.app_data_dir(), resolver.app_config_dir(), resolver.app_local_data_dir()]
+        .into_iter()
+        .flatten()
+        .filter(|dir| dir.is_dir())
+        .chain(extra)
+        .collect();
+    folders.sort();
+    folders.dedup();
+
+    commit(&lower, &folders)?;
+    let committed: Vec<String> = folders.iter().map(|folder| folder.display().to_string()).collect();
+    events::publish(app, "overlay-committed", &committed);
+    Ok(folders)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Whether the read-only root is active now and after the next boot
+#[tauri::command]
+pub fn get_overlay_status() -> OverlayStatus {
+    status()
+}
+
+/// Make the root filesystem read-only from the next boot (admin)
+#[tauri::command]
+pub fn enable_overlay_fs(app: AppHandle) -> Result<OverlayStatus, KioskError> {
+    switch(&app, true)
+}
+
+/// Make the root filesystem writable again from the next boot (admin)
+#[tauri::command]
+pub fn disable_overlay_fs(app: AppHandle) -> Result<OverlayStatus, KioskError> {
+    switch(&app, false)
+}
+
+/// Persist the kiosk's data folders, and the given virtual folders, to the
+/// SD card underneath the overlay (admin)
+#[tauri::command]
+pub fn commit_changes(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    paths: Option<Vec<String>>,
+) -> Result<CommitResult, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if overlay_lower_dir().is_none() {
+        return Err(KioskError::invalid("The read-only root is not active"));
+    }
+    let mut folders = Vec::new();
+    for path in paths.unwrap_or_default() {
+        folders.push(vfs::resolve(&app, &path, Access::Write)?);
+    }
+    let committed = persist(&app, folders)?.iter().map(|folder| folder.display().to_string()).collect();
+    Ok(CommitResult { committed })
+}
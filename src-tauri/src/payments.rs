@@ -0,0 +1,521 @@
+//! Card payment terminals
+//!
+//! Drives unattended card terminals through the ZVT ECR protocol, over TCP
+//! (usually port 20007) or a serial line. One payment runs at a time on a
+//! background thread and reports progress through `payment-status` events.
+//!
+//! Payments fail until an admin configures a terminal. Builds with the
+//! `demo` feature add a simulator that approves every payment.
+
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::lazy::{self, Subsystem};
+use crate::{events, store};
+
+const CONFIG_FILE: &str = "payment_terminal.json";
+
+/// How often a waiting driver checks for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Longest a terminal may stay silent mid-transaction
+const TERMINAL_TIMEOUT: Duration = Duration::from_secs(180);
+
+// ZVT serial framing bytes
+const DLE: u8 = 0x10;
+const STX: u8 = 0x02;
+const ETX: u8 = 0x03;
+const ACK: u8 = 0x06;
+const NAK: u8 = 0x15;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TerminalConnection {
+    Tcp { host: String, port: u16 },
+    Serial { path: String, baud_rate: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TerminalDriver {
+    /// No terminal set up yet; every payment fails
+    Unconfigured,
+    Zvt,
+    #[cfg(feature = "demo")]
+    Simulator,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentConfig {
+    pub driver: TerminalDriver,
+    pub connection: TerminalConnection,
+    /// ISO 4217 numeric currency code, e.g. 978 for EUR
+    pub currency_code: u16,
+}
+
+impl Default for PaymentConfig {
+    fn default() -> Self {
+        PaymentConfig {
+            driver: TerminalDriver::Unconfigured,
+            connection: TerminalConnection::Tcp {
+                host: "192.168.1.50".to_string(),
+                port: 20007,
+            },
+            currency_code: 978,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentStatus {
+    Pending,
+    Processing,
+    Approved,
+    Declined,
+    Cancelled,
+    Error,
+}
+
+impl PaymentStatus {
+    fn is_final(self) -> bool {
+        !matches!(self, PaymentStatus::Pending | PaymentStatus::Processing)
+    }
+}
+
+/// Progress of a payment, emitted as `payment-status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentUpdate {
+    pub id: String,
+    /// Amount in minor units (cents)
+    pub amount: i64,
+    pub status: PaymentStatus,
+    pub message: String,
+}
+
+struct ActivePayment {
+    update: PaymentUpdate,
+    cancel: Arc<AtomicBool>,
+}
+
+pub struct PaymentState {
+    config: Mutex<PaymentConfig>,
+    current: Mutex<Option<ActivePayment>>,
+}
+
+impl Subsystem for PaymentState {
+    const NAME: &'static str = "payments";
+
+    fn load(app: &AppHandle) -> Self {
+        PaymentState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            current: Mutex::new(None),
+        }
+    }
+}
+
+// ============================================================================
+// ZVT Transport
+// ============================================================================
+
+/// Moves whole ZVT APDUs to and from the terminal
+trait Transport {
+    fn send(&mut self, apdu: &[u8]) -> Result<(), String>;
+    /// Wait up to `wait` for the next APDU
+    fn receive(&mut self, wait: Duration) -> Result<Option<Vec<u8>>, String>;
+}
+
+fn io_error(e: std::io::Error) -> String {
+    format!("Terminal connection error: {}", e)
+}
+
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+/// Read the rest of an APDU after its class byte
+fn read_apdu_body(stream: &mut impl Read, class: u8) -> Result<Vec<u8>, std::io::Error> {
+    let mut header = [0u8; 2];
+    stream.read_exact(&mut header)?;
+    let length = if header[1] == 0xFF {
+        let mut extended = [0u8; 2];
+        stream.read_exact(&mut extended)?;
+        u16::from_le_bytes(extended) as usize
+    } else {
+        header[1] as usize
+    };
+
+    let mut data = vec![0u8; length];
+    stream.read_exact(&mut data)?;
+    let mut apdu = vec![class, header[0]];
+    apdu.extend(data);
+    Ok(apdu)
+}
+
+/// Raw APDUs over TCP
+struct TcpTransport(TcpStream);
+
+impl Transport for TcpTransport {
+    fn send(&mut self, apdu: &[u8]) -> Result<(), String> {
+        self.0.write_all(apdu).map_err(io_error)
+    }
+
+    fn receive(&mut self, wait: Duration) -> Result<Option<Vec<u8>>, String> {
+        self.0.set_read_timeout(Some(wait)).map_err(io_error)?;
+        let mut class = [0u8; 1];
+        match self.0.read(&mut class) {
+            Ok(0) => return Err("Terminal closed the connection".to_string()),
+            Ok(_) => {}
+            Err(e) if is_timeout(&e) => return Ok(None),
+            Err(e) => return Err(io_error(e)),
+        }
+
+        // The rest of the APDU follows immediately
+        self.0.set_read_timeout(Some(Duration::from_secs(5))).map_err(io_error)?;
+        read_apdu_body(&mut self.0, class[0]).map(Some).map_err(io_error)
+    }
+}
+
+/// CRC-16/XMODEM over the unstuffed APDU and ETX
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+        }
+    }
+    crc
+}
+
+/// DLE/STX framed APDUs with CRC and ACK/NAK handshake over a serial line
+struct SerialTransport(Box<dyn serialport::SerialPort>);
+
+impl SerialTransport {
+    fn read_byte(&mut self) -> Result<Option<u8>, std::io::Error> {
+        let mut byte = [0u8; 1];
+        match self.0.read(&mut byte) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(byte[0])),
+            Err(e) if is_timeout(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_byte_required(&mut self) -> Result<u8, String> {
+        self.read_byte()
+            .map_err(io_error)?
+            .ok_or_else(|| "Terminal stopped mid-frame".to_string())
+    }
+}
+
+impl Transport for SerialTransport {
+    fn send(&mut self, apdu: &[u8]) -> Result<(), String> {
+        let mut frame = vec![DLE, STX];
+        for &byte in apdu {
+            frame.push(byte);
+            if byte == DLE {
+                frame.push(DLE);
+            }
+        }
+        let mut checked = apdu.to_vec();
+        checked.push(ETX);
+        let crc = crc16(&checked);
+        frame.extend([DLE, ETX, (crc & 0xFF) as u8, (crc >> 8) as u8]);
+
+        for _ in 0..3 {
+            self.0.write_all(&frame).map_err(io_error)?;
+            self.0.set_timeout(Duration::from_secs(5)).map_err(|e| e.to_string())?;
+            match self.read_byte().map_err(io_error)? {
+                Some(ACK) => return Ok(()),
+                Some(NAK) | None => continue,
+                Some(other) => return Err(format!("Unexpected reply 0x{:02X} from terminal", other)),
+            }
+        }
+        Err("Terminal did not acknowledge the command".to_string())
+    }
+
+    fn receive(&mut self, wait: Duration) -> Result<Option<Vec<u8>>, String> {
+        self.0.set_timeout(wait).map_err(|e| e.to_string())?;
+        match self.read_byte().map_err(io_error)? {
+            Some(DLE) => {}
+            Some(_) | None => return Ok(None),
+        }
+        self.0.set_timeout(Duration::from_secs(5)).map_err(|e| e.to_string())?;
+        if self.read_byte_required()? != STX {
+            return Ok(None);
+        }
+
+        let mut apdu = Vec::new();
+        loop {
+            match self.read_byte_required()? {
+                DLE => match self.read_byte_required()? {
+                    DLE => apdu.push(DLE),
+                    ETX => break,
+                    other => return Err(format!("Bad frame escape 0x{:02X}", other)),
+                },
+                byte => apdu.push(byte),
+            }
+        }
+        let low = self.read_byte_required()? as u16;
+        let high = self.read_byte_required()? as u16;
+
+        let mut checked = apdu.clone();
+        checked.push(ETX);
+        if crc16(&checked) != (high << 8 | low) {
+            self.0.write_all(&[NAK]).map_err(io_error)?;
+            return Ok(None);
+        }
+        self.0.write_all(&[ACK]).map_err(io_error)?;
+        Ok(Some(apdu))
+    }
+}
+
+fn connect(connection: &TerminalConnection) -> Result<Box<dyn Transport>, String> {
+    match connection {
+        TerminalConnection::Tcp { host, port } => {
+            let address = std::net::ToSocketAddrs::to_socket_addrs(&(host.as_str(), *port))
+                .map_err(io_error)?
+                .next()
+                .ok_or_else(|| format!("Cannot resolve {}", host))?;
+            let stream = TcpStream::connect_timeout(&address, Duration::from_secs(10)).map_err(io_error)?;
+            Ok(Box::new(TcpTransport(stream)))
+        }
+        TerminalConnection::Serial { path, baud_rate } => {
+            let port = serialport::new(path, *baud_rate)
+                .stop_bits(serialport::StopBits::Two)
+                .timeout(POLL_INTERVAL)
+                .open()
+                .map_err(|e| format!("Cannot open {}: {}", path, e))?;
+            Ok(Box::new(SerialTransport(port)))
+        }
+    }
+}
+
+// ============================================================================
+// ZVT Protocol
+// ============================================================================
+
+/// Encode a number as `digits` packed BCD digits
+fn bcd(value: u64, digits: usize) -> Vec<u8> {
+    let text = format!("{:0width$}", value, width = digits);
+    text.as_bytes()
+        .chunks(2)
+        .map(|pair| ((pair[0] - b'0') << 4) | (pair[1] - b'0'))
+        .collect()
+}
+
+fn apdu(class: u8, instruction: u8, data: &[u8]) -> Vec<u8> {
+    let mut apdu = vec![class, instruction];
+    if data.len() < 0xFF {
+        apdu.push(data.len() as u8);
+    } else {
+        apdu.push(0xFF);
+        apdu.extend((data.len() as u16).to_le_bytes());
+    }
+    apdu.extend(data);
+    apdu
+}
+
+/// Run an authorization (06 01) to completion
+fn zvt_payment(
+    transport: &mut dyn Transport,
+    amount: i64,
+    currency: u16,
+    cancel: &AtomicBool,
+    report: &dyn Fn(PaymentStatus, String),
+) -> Result<(PaymentStatus, String), String> {
+    let mut data = vec![0x04];
+    data.extend(bcd(amount as u64, 12));
+    data.push(0x49);
+    data.extend(bcd(currency as u64, 4));
+    transport.send(&apdu(0x06, 0x01, &data))?;
+
+    let mut abort_sent = false;
+    let mut silent = Duration::ZERO;
+
+    loop {
+        if cancel.load(Ordering::SeqCst) && !abort_sent {
+            transport.send(&apdu(0x06, 0xB0, &[]))?;
+            abort_sent = true;
+        }
+
+        let Some(message) = transport.receive(POLL_INTERVAL)? else {
+            silent += POLL_INTERVAL;
+            if silent >= TERMINAL_TIMEOUT {
+                return Err("Terminal stopped responding".to_string());
+            }
+            continue;
+        };
+        silent = Duration::ZERO;
+
+        let (class, instruction) = (message[0], message.get(1).copied().unwrap_or_default());
+        let body = message.get(3..).unwrap_or_default();
+        match (class, instruction) {
+            // Acknowledgement of our own command
+            (0x80, 0x00) => continue,
+            (0x84, code) => return Ok((PaymentStatus::Error, format!("Terminal rejected the command (0x{:02X})", code))),
+            _ => transport.send(&[0x80, 0x00, 0x00])?,
+        }
+
+        match (class, instruction) {
+            (0x04, 0xFF) => {
+                let code = body.first().copied().unwrap_or_default();
+                report(PaymentStatus::Processing, format!("Follow the instructions on the terminal (status 0x{:02X})", code));
+            }
+            (0x06, 0x0F) => return Ok((PaymentStatus::Approved, "Payment approved".to_string())),
+            (0x06, 0x1E) => {
+                let code = body.first().copied().unwrap_or_default();
+                return Ok(if abort_sent {
+                    (PaymentStatus::Cancelled, "Payment cancelled".to_string())
+                } else {
+                    (PaymentStatus::Declined, format!("Payment declined (result 0x{:02X})", code))
+                });
+            }
+            // Status information, receipt lines and anything else need only the ack
+            _ => {}
+        }
+    }
+}
+
+/// Pretend to be a terminal: ask for a card, then approve
+#[cfg(feature = "demo")]
+fn simulate_payment(cancel: &AtomicBool, report: &dyn Fn(PaymentStatus, String)) -> (PaymentStatus, String) {
+    report(PaymentStatus::Processing, "Present card".to_string());
+    for _ in 0..12 {
+        std::thread::sleep(POLL_INTERVAL);
+        if cancel.load(Ordering::SeqCst) {
+            return (PaymentStatus::Cancelled, "Payment cancelled".to_string());
+        }
+    }
+    (PaymentStatus::Approved, "Payment approved (simulated)".to_string())
+}
+
+fn publish(app: &AppHandle, update: PaymentUpdate) {
+    if let Ok(state) = lazy::get::<PaymentState>(app) {
+        let mut current = state.current.lock().expect("payment lock");
+        if let Some(active) = current.as_mut().filter(|active| active.update.id == update.id) {
+            active.update = update.clone();
+        }
+    }
+    events::publish(app, "payment-status", update);
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the payment terminal settings
+#[tauri::command]
+pub fn get_payment_config(app: AppHandle) -> Result<PaymentConfig, KioskError> {
+    let state = lazy::get::<PaymentState>(&app)?;
+    Ok(state.config.lock().expect("payment config lock").clone())
+}
+
+/// Update the payment terminal settings (admin)
+#[tauri::command]
+pub fn set_payment_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    config: PaymentConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let state = lazy::get::<PaymentState>(&app)?;
+    if config.currency_code > 999 {
+        return Err(KioskError::invalid("Currency code must be an ISO 4217 number"));
+    }
+    let mut current = state.config.lock().expect("payment config lock");
+    *current = config;
+    Ok(store::save(&app, CONFIG_FILE, &*current)?)
+}
+
+/// Start charging `amount` minor units (cents). Progress arrives as `payment-status` events.
+#[tauri::command]
+pub fn start_payment(app: AppHandle, amount: i64) -> Result<PaymentUpdate, KioskError> {
+    let state = lazy::get::<PaymentState>(&app)?;
+    if amount <= 0 || amount > 999_999_999_999 {
+        return Err(KioskError::invalid("Amount out of range"));
+    }
+    if state.config.lock().expect("payment config lock").driver == TerminalDriver::Unconfigured {
+        return Err("No payment terminal is configured".into());
+    }
+
+    let mut current = state.current.lock().expect("payment lock");
+    if current.as_ref().is_some_and(|active| !active.update.status.is_final()) {
+        return Err("A payment is already in progress".into());
+    }
+
+    let update = PaymentUpdate {
+        id: uuid::Uuid::new_v4().to_string(),
+        amount,
+        status: PaymentStatus::Pending,
+        message: "Connecting to terminal".to_string(),
+    };
+    let cancel = Arc::new(AtomicBool::new(false));
+    *current = Some(ActivePayment {
+        update: update.clone(),
+        cancel: cancel.clone(),
+    });
+    drop(current);
+    events::publish(&app, "payment-status", &update);
+
+    let config = state.config.lock().expect("payment config lock").clone();
+    let worker = app.clone();
+    let pending = update.clone();
+    std::thread::spawn(move || {
+        let report = |status: PaymentStatus, message: String| {
+            publish(&worker, PaymentUpdate { status, message, ..pending.clone() });
+        };
+
+        let (status, message) = match config.driver {
+            TerminalDriver::Unconfigured => (PaymentStatus::Error, "No payment terminal is configured".to_string()),
+            #[cfg(feature = "demo")]
+            TerminalDriver::Simulator => simulate_payment(&cancel, &report),
+            TerminalDriver::Zvt => connect(&config.connection)
+                .and_then(|mut transport| {
+                    zvt_payment(transport.as_mut(), amount, config.currency_code, &cancel, &report)
+                })
+                .unwrap_or_else(|error| (PaymentStatus::Error, error)),
+        };
+        report(status, message);
+    });
+
+    Ok(update)
+}
+
+/// Ask the terminal to abort the running payment
+#[tauri::command]
+pub fn cancel_payment(app: AppHandle) -> Result<(), KioskError> {
+    let state = lazy::get::<PaymentState>(&app)?;
+    let current = state.current.lock().expect("payment lock");
+    match current.as_ref() {
+        Some(active) if !active.update.status.is_final() => {
+            active.cancel.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        _ => Err("No payment in progress".into()),
+    }
+}
+
+/// Get the latest state of the current or most recent payment
+#[tauri::command]
+pub fn get_payment_status(app: AppHandle) -> Result<Option<PaymentUpdate>, KioskError> {
+    let state = lazy::get::<PaymentState>(&app)?;
+    Ok(state
+        .current
+        .lock()
+        .expect("payment lock")
+        .as_ref()
+        .map(|active| active.update.clone()))
+}
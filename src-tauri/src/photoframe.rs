@@ -0,0 +1,586 @@
+//! Photo frame
+//!
+//! Turns an idle kiosk into a digital photo frame. The configured folders,
+//! given as virtual paths, and optionally any USB drive that is plugged in,
+//! are scanned for JPEG and PNG photos. Each photo is decoded here, turned
+//! upright from its EXIF orientation and scaled to the display, the next
+//! one while the current is on screen (except in lite mode, which holds
+//! only the photo showing). `show-photo` tells the frontend
+//! what to show and how to transition to it; the scaled JPEG itself is
+//! served over the `photo://` protocol, keeping the event small.
+
+use chrono::Local;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tauri::http::{Request, Response, StatusCode};
+use tauri::{AppHandle, Manager, State, UriSchemeContext};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::vfs::{self, Access, RootKind};
+use crate::{config, events, games, store};
+
+const PHOTOFRAME_FILE: &str = "photoframe.json";
+
+const EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// Folders nested deeper than this are not scanned
+const MAX_DEPTH: usize = 8;
+
+const MAX_PHOTOS: usize = 10_000;
+
+/// Folders are scanned again this often, and whenever a drive comes or goes
+const RESCAN_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Wait before looking again when there are no photos
+const EMPTY_RETRY: Duration = Duration::from_secs(30);
+
+const MIN_INTERVAL_SECS: u64 = 3;
+
+/// Display size assumed when the monitor cannot be read
+const FALLBACK_SIZE: (u32, u32) = (1920, 1080);
+
+const JPEG_QUALITY: u8 = 85;
+
+/// Recent slides kept for the protocol, so the outgoing photo can still
+/// load while the transition runs
+const SLIDES_KEPT: usize = 3;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhotoTransition {
+    Cut,
+    Fade,
+    Slide,
+    /// A slow zoom and pan while the photo is shown
+    Zoom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhotoFit {
+    /// The whole photo, with bars where its shape differs from the screen's
+    Contain,
+    /// Fill the screen, cropping the edges
+    Cover,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhotoFrameConfig {
+    /// Virtual paths of the folders to show, scanned with their subfolders
+    pub folders: Vec<String>,
+    /// Also show photos on USB drives while they are plugged in
+    pub removable: bool,
+    /// Seconds each photo is shown
+    pub interval_secs: u64,
+    pub transition: PhotoTransition,
+    pub transition_ms: u64,
+    pub shuffle: bool,
+    pub fit: PhotoFit,
+    /// Start the photo frame when the kiosk starts
+    pub autostart: bool,
+}
+
+impl Default for PhotoFrameConfig {
+    fn default() -> Self {
+        PhotoFrameConfig {
+            folders: Vec::new(),
+            removable: true,
+            interval_secs: 10,
+            transition: PhotoTransition::Fade,
+            transition_ms: 1000,
+            shuffle: false,
+            fit: PhotoFit::Contain,
+            autostart: false,
+        }
+    }
+}
+
+/// Payload of the `show-photo` event
+#[derive(Debug, Clone, Serialize)]
+pub struct PhotoSlide {
+    pub seq: u64,
+    /// The scaled photo, e.g. `photo://localhost/12.jpg`
+    pub url: String,
+    /// Virtual path of the original
+    pub path: String,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub index: usize,
+    pub count: usize,
+    pub transition: PhotoTransition,
+    pub transition_ms: u64,
+    pub interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PhotoFrameStatus {
+    pub running: bool,
+    pub count: usize,
+    pub current: Option<PhotoSlide>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Photo {
+    /// Virtual path
+    path: String,
+    real: PathBuf,
+}
+
+struct Prepared {
+    photo: Photo,
+    jpeg: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+struct Playback {
+    /// Bumped on every start and stop, retiring the previous worker
+    generation: u64,
+    running: bool,
+    /// Move asked for by `next_photo` or `previous_photo`
+    step: Option<isize>,
+    photos: Vec<Photo>,
+    position: usize,
+    /// Removable roots present at the last scan
+    drives: Vec<String>,
+    scanned: Option<Instant>,
+    seq: u64,
+    slides: VecDeque<(u64, Vec<u8>)>,
+    current: Option<PhotoSlide>,
+    error: Option<String>,
+}
+
+pub struct PhotoFrameState {
+    config: Mutex<PhotoFrameConfig>,
+    playback: Mutex<Playback>,
+    wake: Condvar,
+}
+
+impl PhotoFrameState {
+    pub fn load(app: &AppHandle) -> Self {
+        PhotoFrameState {
+            config: Mutex::new(store::load(app, PHOTOFRAME_FILE)),
+            playback: Mutex::new(Playback {
+                generation: 0,
+                running: false,
+                step: None,
+                photos: Vec::new(),
+                position: 0,
+                drives: Vec::new(),
+                scanned: None,
+                seq: 0,
+                slides: VecDeque::new(),
+                current: None,
+                error: None,
+            }),
+            wake: Condvar::new(),
+        }
+    }
+}
+
+// ============================================================================
+// Scanning
+// ============================================================================
+
+fn is_photo(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// Collect photos under `dir`, skipping hidden entries and not following
+/// symlinked folders
+fn walk(dir: &Path, virtual_dir: &str, depth: usize, photos: &mut Vec<Photo>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        if photos.len() >= MAX_PHOTOS {
+            return;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Ok(kind) = entry.file_type() else { continue };
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{}/{}", virtual_dir, name);
+        if kind.is_dir() && depth < MAX_DEPTH {
+            walk(&entry.path(), &path, depth + 1, photos);
+        } else if !kind.is_dir() && is_photo(&entry.path()) {
+            photos.push(Photo { path, real: entry.path() });
+        }
+    }
+}
+
+fn removable_drives(app: &AppHandle) -> Vec<String> {
+    vfs::roots(app)
+        .into_iter()
+        .filter(|root| root.info.kind == RootKind::Removable)
+        .map(|root| root.info.id)
+        .collect()
+}
+
+fn scan(app: &AppHandle, config: &PhotoFrameConfig, drives: &[String]) -> Vec<Photo> {
+    let mut photos = Vec::new();
+    let folders = config.folders.iter().map(|folder| folder.trim_end_matches('/').to_string());
+    for folder in folders.chain(drives.iter().cloned()) {
+        // Folders that are gone, or out of reach of the current scope, are skipped
+        if let Ok(real) = vfs::resolve(app, &folder, Access::Read) {
+            walk(&real, &folder, 0, &mut photos);
+        }
+    }
+    // A folder can sit inside another one, or on a drive
+    let mut seen = std::collections::HashSet::new();
+    photos.retain(|photo| seen.insert(photo.real.clone()));
+    if config.shuffle {
+        games::Rng::new(games::random_seed()).shuffle(&mut photos);
+    }
+    photos
+}
+
+/// Scan again when it is due or the drives changed, staying on the photo
+/// that is showing
+fn refresh(app: &AppHandle, config: &PhotoFrameConfig) {
+    let state = app.state::<PhotoFrameState>();
+    let drives = if config.removable { removable_drives(app) } else { Vec::new() };
+    {
+        let playback = state.playback.lock().expect("photo frame lock");
+        let due = playback.scanned.map_or(true, |scanned| scanned.elapsed() >= RESCAN_INTERVAL);
+        if !due && playback.drives == drives {
+            return;
+        }
+    }
+    let photos = scan(app, config, &drives);
+    let mut playback = state.playback.lock().expect("photo frame lock");
+    let current = playback.photos.get(playback.position).map(|photo| photo.real.clone());
+    playback.position = current
+        .and_then(|current| photos.iter().position(|photo| photo.real == current))
+        .unwrap_or(0);
+    playback.photos = photos;
+    playback.drives = drives;
+    playback.scanned = Some(Instant::now());
+}
+
+// ============================================================================
+// Slides
+// ============================================================================
+
+fn display_size(app: &AppHandle) -> (u32, u32) {
+    app.primary_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| (monitor.size().width, monitor.size().height))
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .unwrap_or(FALLBACK_SIZE)
+}
+
+/// Decode, turn upright and scale a photo for the screen
+fn prepare(photo: &Photo, (width, height): (u32, u32), fit: PhotoFit) -> Result<Prepared, String> {
+    let failed = |e: &dyn std::fmt::Display| format!("Cannot show {}: {}", photo.path, e);
+    let reader = ImageReader::open(&photo.real).map_err(|e| failed(&e))?;
+    let mut decoder = reader.with_guessed_format().map_err(|e| failed(&e))?.into_decoder().map_err(|e| failed(&e))?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder).map_err(|e| failed(&e))?;
+    image.apply_orientation(orientation);
+
+    let image = match fit {
+        // Photos smaller than the screen are left for the webview to scale
+        PhotoFit::Contain if image.width() <= width && image.height() <= height => image,
+        PhotoFit::Contain => image.resize(width, height, FilterType::Triangle),
+        PhotoFit::Cover => image.resize_to_fill(width, height, FilterType::Triangle),
+    };
+    let rgb = image.to_rgb8();
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+        .encode_image(&rgb)
+        .map_err(|e| failed(&e))?;
+    Ok(Prepared {
+        photo: photo.clone(),
+        jpeg,
+        width: rgb.width(),
+        height: rgb.height(),
+    })
+}
+
+/// Put a prepared photo on screen; false once this run has been stopped
+fn show(app: &AppHandle, generation: u64, prepared: Prepared, config: &PhotoFrameConfig) -> bool {
+    let state = app.state::<PhotoFrameState>();
+    let slide = {
+        let mut playback = state.playback.lock().expect("photo frame lock");
+        if playback.generation != generation {
+            return false;
+        }
+        playback.seq += 1;
+        let seq = playback.seq;
+        playback.slides.push_back((seq, prepared.jpeg));
+        if playback.slides.len() > SLIDES_KEPT {
+            playback.slides.pop_front();
+        }
+        let name = prepared.photo.real.file_name().map(|name| name.to_string_lossy().to_string());
+        let slide = PhotoSlide {
+            seq,
+            url: format!("photo://localhost/{}.jpg", seq),
+            path: prepared.photo.path,
+            name: name.unwrap_or_default(),
+            width: prepared.width,
+            height: prepared.height,
+            index: playback.position,
+            count: playback.photos.len(),
+            transition: config.transition,
+            transition_ms: config.transition_ms,
+            interval_secs: config.interval_secs,
+        };
+        playback.current = Some(slide.clone());
+        playback.error = None;
+        slide
+    };
+    events::publish(app, "show-photo", &slide);
+    true
+}
+
+/// Wait out `interval` or until a move is asked for; the move to make, or
+/// None once this run has been stopped
+fn wait(app: &AppHandle, generation: u64, interval: Duration) -> Option<isize> {
+    let state = app.state::<PhotoFrameState>();
+    let deadline = Instant::now() + interval;
+    let mut playback = state.playback.lock().expect("photo frame lock");
+    loop {
+        if playback.generation != generation {
+            return None;
+        }
+        if let Some(step) = playback.step.take() {
+            return Some(step);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Some(1);
+        }
+        playback = state.wake.wait_timeout(playback, deadline - now).expect("photo frame lock").0;
+    }
+}
+
+fn advance(playback: &mut Playback, step: isize) {
+    let count = playback.photos.len() as isize;
+    if count > 0 {
+        playback.position = (playback.position as isize + step).rem_euclid(count) as usize;
+    }
+}
+
+fn run(app: AppHandle, generation: u64) {
+    let state = app.state::<PhotoFrameState>();
+    let mut preloaded: Option<Prepared> = None;
+    let mut failures = 0;
+    loop {
+        let config = state.config.lock().expect("photo frame config lock").clone();
+        refresh(&app, &config);
+        let (photo, count) = {
+            let playback = state.playback.lock().expect("photo frame lock");
+            if playback.generation != generation {
+                return;
+            }
+            (playback.photos.get(playback.position).cloned(), playback.photos.len())
+        };
+        let Some(photo) = photo else {
+            state.playback.lock().expect("photo frame lock").error = Some("No photos were found".to_string());
+            if wait(&app, generation, EMPTY_RETRY).is_none() {
+                return;
+            }
+            continue;
+        };
+
+        let size = display_size(&app);
+        let prepared = match preloaded.take() {
+            Some(prepared) if prepared.photo.real == photo.real => Ok(prepared),
+            _ => prepare(&photo, size, config.fit),
+        };
+        match prepared {
+            Ok(prepared) => {
+                failures = 0;
+                if !show(&app, generation, prepared, &config) {
+                    return;
+                }
+            }
+            Err(e) => {
+                failures += 1;
+                let mut playback = state.playback.lock().expect("photo frame lock");
+                playback.error = Some(e);
+                // Skip straight past unreadable photos, unless none can be read
+                if failures < count {
+                    advance(&mut playback, 1);
+                    continue;
+                }
+            }
+        }
+
+        // Get the next photo ready while this one is on screen
+        if !config::lite_mode(&app) {
+            let next = {
+                let playback = state.playback.lock().expect("photo frame lock");
+                playback.photos.get((playback.position + 1) % count.max(1)).cloned()
+            };
+            preloaded = next.and_then(|next| prepare(&next, size, config.fit).ok());
+        }
+
+        let Some(step) = wait(&app, generation, Duration::from_secs(config.interval_secs)) else { return };
+        advance(&mut state.playback.lock().expect("photo frame lock"), step);
+    }
+}
+
+fn start(app: &AppHandle) {
+    let state = app.state::<PhotoFrameState>();
+    let generation = {
+        let mut playback = state.playback.lock().expect("photo frame lock");
+        if playback.running {
+            return;
+        }
+        playback.generation += 1;
+        playback.running = true;
+        playback.step = None;
+        playback.scanned = None;
+        playback.generation
+    };
+    let worker = app.clone();
+    std::thread::spawn(move || run(worker, generation));
+}
+
+/// Start the photo frame at launch when it is set to
+pub fn start_on_launch(app: &AppHandle) {
+    if app.state::<PhotoFrameState>().config.lock().expect("photo frame config lock").autostart {
+        start(app);
+    }
+}
+
+fn status(playback: &Playback) -> PhotoFrameStatus {
+    PhotoFrameStatus {
+        running: playback.running,
+        count: playback.photos.len(),
+        current: playback.current.clone(),
+        error: playback.error.clone(),
+    }
+}
+
+// ============================================================================
+// Protocol
+// ============================================================================
+
+fn respond(status: StatusCode, mime: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", mime)
+        .body(body)
+        .expect("valid photo response")
+}
+
+/// Serve the scaled photos of recent slides: `photo://localhost/<seq>.jpg`
+pub fn protocol(ctx: UriSchemeContext<'_, tauri::Wry>, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let seq = request.uri().path().trim_start_matches('/').strip_suffix(".jpg").and_then(|seq| seq.parse().ok());
+    let state = ctx.app_handle().state::<PhotoFrameState>();
+    let playback = state.playback.lock().expect("photo frame lock");
+    match seq.and_then(|seq: u64| playback.slides.iter().find(|(slide, _)| *slide == seq)) {
+        Some((_, jpeg)) => respond(StatusCode::OK, "image/jpeg", jpeg.clone()),
+        None => respond(StatusCode::NOT_FOUND, "text/plain", b"Photo not found".to_vec()),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the photo frame settings
+#[tauri::command]
+pub fn get_photo_frame_config(state: State<'_, PhotoFrameState>) -> PhotoFrameConfig {
+    state.config.lock().expect("photo frame config lock").clone()
+}
+
+/// Change the photo frame settings; a running frame picks them up from the
+/// next photo
+#[tauri::command]
+pub fn set_photo_frame_config(
+    app: AppHandle,
+    state: State<'_, PhotoFrameState>,
+    auth: State<'_, AuthState>,
+    mut config: PhotoFrameConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    for folder in &config.folders {
+        if !vfs::resolve(&app, folder, Access::Read)?.is_dir() {
+            return Err(KioskError::invalid(format!("{} is not a folder", folder)));
+        }
+    }
+    config.interval_secs = config.interval_secs.max(MIN_INTERVAL_SECS);
+    // The transition has to finish well before the next photo
+    config.transition_ms = config.transition_ms.min(config.interval_secs * 1000 / 2);
+
+    store::save(&app, PHOTOFRAME_FILE, &config)?;
+    *state.config.lock().expect("photo frame config lock") = config;
+    state.playback.lock().expect("photo frame lock").scanned = None;
+    Ok(())
+}
+
+/// Start the slideshow; `show-photo` events follow
+#[tauri::command]
+pub fn start_photo_frame(app: AppHandle, auth: State<'_, AuthState>) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    start(&app);
+    Ok(())
+}
+
+/// Stop the slideshow and publish `photo-frame-stopped`
+#[tauri::command]
+pub fn stop_photo_frame(
+    app: AppHandle,
+    state: State<'_, PhotoFrameState>,
+    auth: State<'_, AuthState>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    {
+        let mut playback = state.playback.lock().expect("photo frame lock");
+        if !playback.running {
+            return Ok(());
+        }
+        playback.generation += 1;
+        playback.running = false;
+        playback.current = None;
+        playback.slides.clear();
+    }
+    state.wake.notify_all();
+    events::publish(&app, "photo-frame-stopped", Local::now().timestamp());
+    Ok(())
+}
+
+/// Show the next photo now
+#[tauri::command]
+pub fn next_photo(state: State<'_, PhotoFrameState>) {
+    state.playback.lock().expect("photo frame lock").step = Some(1);
+    state.wake.notify_all();
+}
+
+/// Go back to the previous photo
+#[tauri::command]
+pub fn previous_photo(state: State<'_, PhotoFrameState>) {
+    state.playback.lock().expect("photo frame lock").step = Some(-1);
+    state.wake.notify_all();
+}
+
+/// Whether the photo frame is running, how many photos it has and which
+/// is showing
+#[tauri::command]
+pub fn get_photo_frame_status(state: State<'_, PhotoFrameState>) -> PhotoFrameStatus {
+    status(&state.playback.lock().expect("photo frame lock"))
+}
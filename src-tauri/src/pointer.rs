@@ -0,0 +1,761 @@
+//! Cursor themes and pointer settings
+//!
+//! Backs the Mouse control-panel applet: lists the installed Xcursor
+//! themes, installs Windows cursor sets (.cur and .ani files, mapped to
+//! their roles by the set's .inf or by file name) as Xcursor themes, and
+//! applies the theme, cursor size and the libinput settings (speed,
+//! acceleration profile, left-handed buttons and scroll direction) to the
+//! running session, along with the double-click time toolkits read.
+//! Settings are saved in `pointer.json` and applied again at startup.
+//!
+//! The session is changed through whatever is running: sway (`swaymsg`),
+//! Hyprland (`hyprctl`), X11 (`xrdb`, `xsetroot` and `xinput`) and, where
+//! present, GNOME's `gsettings`. The theme is also made the user's default
+//! in `~/.icons/default` and exported as `XCURSOR_THEME` and `XCURSOR_SIZE`
+//! for apps the kiosk launches.
+
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::bitmap::{self, Hotspot};
+use crate::error::{ErrorKind, KioskError};
+use crate::vfs::{self, Access};
+use crate::{icons, store};
+
+const POINTER_FILE: &str = "pointer.json";
+
+const DEFAULT_CURSOR_SIZE: u32 = 24;
+
+const DEFAULT_DOUBLE_CLICK_MS: u32 = 400;
+
+/// Sizes made for converted cursors smaller than them, so large pointer
+/// sizes still find a cursor
+const SCALED_SIZES: &[u32] = &[48, 64];
+
+/// Xcursor image chunk type
+const XCURSOR_IMAGE: u32 = 0xfffd_0002;
+
+/// Windows cursor roles in the order of a scheme's registry entry, with
+/// the Xcursor names each is installed as (the first is the file, the
+/// rest link to it) and words that give the role away in a file name
+const ROLES: &[(&str, &[&str], &[&str])] = &[
+    ("Arrow", &["left_ptr", "default", "arrow", "top_left_arrow"], &["arrow", "normal", "pointer"]),
+    ("Help", &["help", "question_arrow", "whats_this", "left_ptr_help"], &["help"]),
+    ("AppStarting", &["progress", "left_ptr_watch", "half-busy"], &["appstart", "working", "background"]),
+    ("Wait", &["wait", "watch"], &["wait", "busy"]),
+    ("Crosshair", &["crosshair", "cross", "tcross"], &["cross", "precision"]),
+    ("IBeam", &["text", "xterm", "ibeam"], &["ibeam", "beam", "text"]),
+    ("NWPen", &["pencil"], &["pen", "handwriting"]),
+    ("No", &["not-allowed", "crossed_circle", "no-drop", "forbidden", "circle"], &["unavail", "no"]),
+    (
+        "SizeNS",
+        &["ns-resize", "size_ver", "sb_v_double_arrow", "v_double_arrow", "n-resize", "s-resize", "row-resize"],
+        &["vert", "ns"],
+    ),
+    (
+        "SizeWE",
+        &["ew-resize", "size_hor", "sb_h_double_arrow", "h_double_arrow", "e-resize", "w-resize", "col-resize"],
+        &["horz", "horiz", "we"],
+    ),
+    (
+        "SizeNWSE",
+        &["nwse-resize", "size_fdiag", "nw-resize", "se-resize", "top_left_corner", "bottom_right_corner"],
+        &["dgn1", "diag1", "diagonalresize1", "nwse"],
+    ),
+    (
+        "SizeNESW",
+        &["nesw-resize", "size_bdiag", "ne-resize", "sw-resize", "top_right_corner", "bottom_left_corner"],
+        &["dgn2", "diag2", "diagonalresize2", "nesw"],
+    ),
+    ("SizeAll", &["move", "fleur", "size_all", "all-scroll"], &["move", "sizeall"]),
+    ("UpArrow", &["up-arrow", "center_ptr"], &["alternate", "uparrow", "up"]),
+    ("Hand", &["pointer", "hand", "hand1", "hand2", "pointing_hand"], &["link", "hand"]),
+];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PointerSettings {
+    /// Xcursor theme id; the session's own when unset
+    pub cursor_theme: Option<String>,
+    /// Nominal cursor size in pixels
+    pub cursor_size: u32,
+    /// Pointer speed from -1 (slowest) to 1 (fastest)
+    pub speed: f64,
+    pub accel_profile: AccelProfile,
+    /// Longest gap between the clicks of a double-click
+    pub double_click_ms: u32,
+    /// Swap the primary and secondary buttons
+    pub left_handed: bool,
+    /// Content follows the fingers or wheel, as on a touchscreen
+    pub natural_scroll: bool,
+}
+
+/// How libinput turns device movement into pointer movement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccelProfile {
+    /// Faster movement goes further
+    #[default]
+    Adaptive,
+    /// Pointer movement is always in proportion to the device's
+    Flat,
+}
+
+impl AccelProfile {
+    fn as_str(self) -> &'static str {
+        match self {
+            AccelProfile::Adaptive => "adaptive",
+            AccelProfile::Flat => "flat",
+        }
+    }
+}
+
+impl Default for PointerSettings {
+    fn default() -> Self {
+        PointerSettings {
+            cursor_theme: None,
+            cursor_size: DEFAULT_CURSOR_SIZE,
+            speed: 0.0,
+            accel_profile: AccelProfile::Adaptive,
+            double_click_ms: DEFAULT_DOUBLE_CLICK_MS,
+            left_handed: false,
+            natural_scroll: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CursorTheme {
+    /// Folder name, used to select the theme
+    pub id: String,
+    pub name: String,
+    pub comment: Option<String>,
+    pub path: String,
+    /// Installed from the control panel, so it can be removed
+    pub user_installed: bool,
+}
+
+pub struct PointerState(Mutex<PointerSettings>);
+
+impl PointerState {
+    pub fn load(app: &AppHandle) -> Self {
+        PointerState(Mutex::new(store::load(app, POINTER_FILE)))
+    }
+}
+
+// ============================================================================
+// Themes
+// ============================================================================
+
+fn home() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+/// Where themes are installed from the control panel
+fn user_theme_dir() -> Result<PathBuf, String> {
+    icons::data_dirs()
+        .into_iter()
+        .next()
+        .map(|dir| dir.join("icons"))
+        .ok_or_else(|| "HOME is not set".to_string())
+}
+
+/// Folders searched for themes, in Xcursor's order
+fn theme_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = home().map(|home| home.join(".icons")).into_iter().collect();
+    dirs.extend(icons::data_dirs().into_iter().map(|dir| dir.join("icons")));
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs
+}
+
+/// `key=` of an index.theme's `[Icon Theme]` section
+fn theme_key(index: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in index.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line == "[Icon Theme]";
+        } else if in_section {
+            if let Some((name, value)) = line.split_once('=') {
+                if name.trim() == key && !value.trim().is_empty() {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Installed themes with a cursors folder, the first of each id
+fn cursor_themes() -> Vec<CursorTheme> {
+    let user_dir = user_theme_dir().ok();
+    let mut themes: Vec<CursorTheme> = Vec::new();
+    for dir in theme_dirs() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join("cursors").is_dir())
+            .collect();
+        found.sort();
+        for path in found {
+            let Some(id) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+                continue;
+            };
+            // ~/.icons/default only points at the chosen theme
+            if id == "default" || themes.iter().any(|theme| theme.id == id) {
+                continue;
+            }
+            let index = fs::read_to_string(path.join("index.theme")).unwrap_or_default();
+            themes.push(CursorTheme {
+                name: theme_key(&index, "Name").unwrap_or_else(|| id.clone()),
+                comment: theme_key(&index, "Comment"),
+                user_installed: user_dir.as_ref().is_some_and(|dir| path.starts_with(dir)),
+                path: path.to_string_lossy().to_string(),
+                id,
+            });
+        }
+    }
+    themes.sort_by_key(|theme| theme.name.to_lowercase());
+    themes
+}
+
+/// An Xcursor image as (image, hotspot, nominal size, delay in ms)
+type XcursorImage = (RgbaImage, Hotspot, u32, u32);
+
+/// A way of applying the settings to the session
+type Backend = fn(&PointerSettings) -> Result<(), String>;
+
+/// An Xcursor file holding each image; images of one size in a row make an
+/// animation
+fn xcursor(images: &[XcursorImage]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend(b"Xcur");
+    out.extend(16u32.to_le_bytes());
+    out.extend(0x1_0000u32.to_le_bytes());
+    out.extend((images.len() as u32).to_le_bytes());
+    let mut position = 16 + 12 * images.len();
+    for (image, _, nominal, _) in images {
+        out.extend(XCURSOR_IMAGE.to_le_bytes());
+        out.extend(nominal.to_le_bytes());
+        out.extend((position as u32).to_le_bytes());
+        position += 36 + 4 * (image.width() * image.height()) as usize;
+    }
+    for (image, hotspot, nominal, delay) in images {
+        for field in [36, XCURSOR_IMAGE, *nominal, 1, image.width(), image.height()] {
+            out.extend(field.to_le_bytes());
+        }
+        out.extend((hotspot.x as u32).min(image.width() - 1).to_le_bytes());
+        out.extend((hotspot.y as u32).min(image.height() - 1).to_le_bytes());
+        out.extend(delay.to_le_bytes());
+        // Premultiplied ARGB
+        for pixel in image.pixels() {
+            let alpha = pixel[3] as u32;
+            let channel = |value: u8| (value as u32 * alpha + 127) / 255;
+            let argb = (alpha << 24) | (channel(pixel[0]) << 16) | (channel(pixel[1]) << 8) | channel(pixel[2]);
+            out.extend(argb.to_le_bytes());
+        }
+    }
+    out
+}
+
+/// Xcursor images for a .cur or .ani file, with enlarged copies for
+/// sizes above the largest image
+fn convert_cursor(data: &[u8]) -> Result<Vec<XcursorImage>, String> {
+    let (_, bitmaps, animation) = bitmap::decode(data)?;
+    let frames: Vec<(RgbaImage, Hotspot, u32)> = match animation {
+        Some(animation) => animation
+            .steps
+            .iter()
+            .filter_map(|step| bitmaps.get(step.frame).map(|frame| (frame, step.jiffies * 1000 / 60)))
+            .map(|(frame, delay)| (frame.image.clone(), frame.hotspot.unwrap_or_default(), delay))
+            .collect(),
+        None => bitmaps
+            .into_iter()
+            .map(|bitmap| (bitmap.image, bitmap.hotspot.unwrap_or_default(), 0))
+            .collect(),
+    };
+    let largest = frames.iter().map(|(image, _, _)| image.width().max(image.height())).max();
+    let Some(largest) = largest else {
+        return Err("The cursor has no images".into());
+    };
+
+    let mut images: Vec<_> = frames
+        .iter()
+        .map(|(image, hotspot, delay)| (image.clone(), *hotspot, image.width().max(image.height()), *delay))
+        .collect();
+    for &size in SCALED_SIZES.iter().filter(|&&size| size > largest) {
+        let biggest = frames.iter().filter(|(image, _, _)| image.width().max(image.height()) == largest);
+        for (image, hotspot, delay) in biggest {
+            let scale = |value: u32| value * size / largest;
+            // Nearest neighbour keeps the pixel-art look
+            let scaled = imageops::resize(image, scale(image.width()), scale(image.height()), FilterType::Nearest);
+            let hotspot = Hotspot {
+                x: scale(hotspot.x as u32) as u16,
+                y: scale(hotspot.y as u32) as u16,
+            };
+            images.push((scaled, hotspot, size, *delay));
+        }
+    }
+    Ok(images)
+}
+
+/// Values of an .inf's `[Strings]` section, keys lowercased
+fn inf_strings(inf: &str) -> HashMap<String, String> {
+    let mut strings = HashMap::new();
+    let mut in_strings = false;
+    for line in inf.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_strings = line.eq_ignore_ascii_case("[Strings]");
+        } else if in_strings {
+            if let Some((key, value)) = line.split_once('=') {
+                strings.insert(key.trim().to_lowercase(), value.trim().trim_matches('"').to_string());
+            }
+        }
+    }
+    strings
+}
+
+/// The scheme name and a file name for each role, from the registry line
+/// of an .inf's `[Scheme.Reg]` section
+fn inf_scheme(inf: &str) -> Option<(String, Vec<String>)> {
+    let strings = inf_strings(inf);
+    let expand = |text: &str| {
+        let mut out = String::new();
+        for (index, part) in text.split('%').enumerate() {
+            if index % 2 == 0 {
+                out.push_str(part);
+            } else {
+                out.push_str(strings.get(&part.to_lowercase()).map_or("", String::as_str));
+            }
+        }
+        out
+    };
+    let mut in_scheme = false;
+    for line in inf.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_scheme = line.eq_ignore_ascii_case("[Scheme.Reg]");
+            continue;
+        }
+        if !in_scheme || !line.contains("Schemes") {
+            continue;
+        }
+        // HKCU,"Control Panel\Cursors\Schemes","Name",,"file,file,..."
+        let quoted: Vec<&str> = line.split('"').skip(1).step_by(2).collect();
+        let (Some(name), Some(files)) = (quoted.get(1), quoted.last()) else {
+            continue;
+        };
+        let files = files
+            .split(',')
+            .map(|file| {
+                let file = expand(file.trim());
+                file.rsplit(['\\', '/']).next().unwrap_or_default().to_string()
+            })
+            .collect();
+        return Some((expand(name), files));
+    }
+    None
+}
+
+/// The role a cursor's file name suggests, by the longest keyword in it
+fn role_from_name(stem: &str) -> Option<usize> {
+    let stem = stem.to_lowercase();
+    let words: Vec<&str> = stem.split(|c: char| !c.is_ascii_alphanumeric()).filter(|w| !w.is_empty()).collect();
+    let compact: String = words.concat();
+    // Short keywords must be a whole word, longer ones can be part of one
+    let matches = |keyword: &&&str| words.contains(*keyword) || (keyword.len() > 3 && compact.contains(**keyword));
+    ROLES
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, _, keywords))| Some((keywords.iter().filter(matches).map(|k| k.len()).max()?, index)))
+        .min_by_key(|&(length, index)| (std::cmp::Reverse(length), index))
+        .map(|(_, index)| index)
+}
+
+// ============================================================================
+// Session
+// ============================================================================
+
+pub(crate) fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    let status = Command::new(program)
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed", program))
+    }
+}
+
+fn apply_sway(settings: &PointerSettings) -> Result<(), String> {
+    if let Some(theme) = &settings.cursor_theme {
+        let size = settings.cursor_size.to_string();
+        run("swaymsg", &["seat", "*", "xcursor_theme", theme, &size])?;
+    }
+    let speed = format!("{:.2}", settings.speed);
+    let enabled = |on: bool| if on { "enabled" } else { "disabled" };
+    for device in ["type:pointer", "type:touchpad"] {
+        run("swaymsg", &["input", device, "pointer_accel", &speed])?;
+        run("swaymsg", &["input", device, "accel_profile", settings.accel_profile.as_str()])?;
+        run("swaymsg", &["input", device, "left_handed", enabled(settings.left_handed)])?;
+        run("swaymsg", &["input", device, "natural_scroll", enabled(settings.natural_scroll)])?;
+    }
+    Ok(())
+}
+
+fn apply_hyprland(settings: &PointerSettings) -> Result<(), String> {
+    if let Some(theme) = &settings.cursor_theme {
+        run("hyprctl", &["setcursor", theme, &settings.cursor_size.to_string()])?;
+    }
+    run("hyprctl", &["keyword", "input:sensitivity", &format!("{:.2}", settings.speed)])?;
+    run("hyprctl", &["keyword", "input:accel_profile", settings.accel_profile.as_str()])?;
+    run("hyprctl", &["keyword", "input:left_handed", &settings.left_handed.to_string()])?;
+    let natural = settings.natural_scroll.to_string();
+    run("hyprctl", &["keyword", "input:natural_scroll", &natural])?;
+    run("hyprctl", &["keyword", "input:touchpad:natural_scroll", &natural])
+}
+
+/// Pointer devices known to X, by id
+fn xinput_pointers() -> Vec<String> {
+    let output = Command::new("xinput").args(["list", "--id-only"]).output();
+    output
+        .map(|output| String::from_utf8_lossy(&output.stdout).lines().map(|id| id.trim().to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn apply_x11(settings: &PointerSettings) -> Result<(), String> {
+    if let Some(theme) = &settings.cursor_theme {
+        let resources = format!("Xcursor.theme: {}\nXcursor.size: {}\n", theme, settings.cursor_size);
+        let mut xrdb = Command::new("xrdb")
+            .arg("-merge")
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run xrdb: {}", e))?;
+        if let Some(mut stdin) = xrdb.stdin.take() {
+            stdin.write_all(resources.as_bytes()).map_err(|e| e.to_string())?;
+        }
+        xrdb.wait().map_err(|e| e.to_string())?;
+        // The root window keeps its cursor until it is set again
+        run("xsetroot", &["-cursor_name", "left_ptr"])?;
+    }
+    let speed = format!("{:.2}", settings.speed);
+    let profile = match settings.accel_profile {
+        AccelProfile::Adaptive => ["1", "0"],
+        AccelProfile::Flat => ["0", "1"],
+    };
+    let flag = |on: bool| if on { "1" } else { "0" };
+    // Devices without the properties (keyboards) refuse them; the rest
+    // are set as far as their driver goes
+    let applied = xinput_pointers()
+        .iter()
+        .filter(|id| {
+            if run("xinput", &["set-prop", id, "libinput Accel Speed", &speed]).is_err() {
+                return false;
+            }
+            let _ = run("xinput", &["set-prop", id, "libinput Accel Profile Enabled", profile[0], profile[1]]);
+            let _ = run("xinput", &["set-prop", id, "libinput Left Handed Enabled", flag(settings.left_handed)]);
+            let natural = flag(settings.natural_scroll);
+            let _ = run("xinput", &["set-prop", id, "libinput Natural Scrolling Enabled", natural]);
+            true
+        })
+        .count();
+    if applied == 0 {
+        return Err("No pointer took the pointer settings".into());
+    }
+    Ok(())
+}
+
+fn apply_gsettings(settings: &PointerSettings) -> Result<(), String> {
+    if let Some(theme) = &settings.cursor_theme {
+        run("gsettings", &["set", "org.gnome.desktop.interface", "cursor-theme", theme])?;
+        let size = settings.cursor_size.to_string();
+        run("gsettings", &["set", "org.gnome.desktop.interface", "cursor-size", &size])?;
+    }
+    const MOUSE: &str = "org.gnome.desktop.peripherals.mouse";
+    const TOUCHPAD: &str = "org.gnome.desktop.peripherals.touchpad";
+    let speed = format!("{:.2}", settings.speed);
+    let left_handed = settings.left_handed.to_string();
+    let natural = settings.natural_scroll.to_string();
+    run("gsettings", &["set", MOUSE, "speed", &speed])?;
+    run("gsettings", &["set", MOUSE, "accel-profile", settings.accel_profile.as_str()])?;
+    run("gsettings", &["set", MOUSE, "left-handed", &left_handed])?;
+    run("gsettings", &["set", MOUSE, "natural-scroll", &natural])?;
+    run("gsettings", &["set", MOUSE, "double-click", &settings.double_click_ms.to_string()])?;
+    run("gsettings", &["set", TOUCHPAD, "speed", &speed])?;
+    run("gsettings", &["set", TOUCHPAD, "natural-scroll", &natural])
+}
+
+/// Set `key` in the `[Settings]` section of a GTK settings.ini
+fn set_gtk_setting(text: &str, key: &str, value: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut in_section = false;
+    let mut done = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with('[') {
+            if in_section && !done {
+                lines.push(format!("{}={}", key, value));
+                done = true;
+            }
+            in_section = line.trim() == "[Settings]";
+        } else if in_section && line.split_once('=').is_some_and(|(name, _)| name.trim() == key) {
+            if !done {
+                lines.push(format!("{}={}", key, value));
+                done = true;
+            }
+            continue;
+        }
+        lines.push(line.to_string());
+    }
+    if !done {
+        if !in_section {
+            lines.push("[Settings]".to_string());
+        }
+        lines.push(format!("{}={}", key, value));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Make the theme the user's default and the one launched apps get, and
+/// give GTK apps (the kiosk's own window among them) the double-click time
+fn apply_defaults(settings: &PointerSettings) -> Result<(), String> {
+    let home = home().ok_or("HOME is not set")?;
+    let gtk = home.join(".config/gtk-3.0");
+    fs::create_dir_all(&gtk).map_err(|e| format!("{}: {}", gtk.display(), e))?;
+    let ini = gtk.join("settings.ini");
+    let text = fs::read_to_string(&ini).unwrap_or_default();
+    let updated = set_gtk_setting(&text, "gtk-double-click-time", &settings.double_click_ms.to_string());
+    if updated != text {
+        fs::write(&ini, updated).map_err(|e| format!("{}: {}", ini.display(), e))?;
+    }
+
+    let Some(theme) = &settings.cursor_theme else {
+        return Ok(());
+    };
+    std::env::set_var("XCURSOR_THEME", theme);
+    std::env::set_var("XCURSOR_SIZE", settings.cursor_size.to_string());
+    let dir = home.join(".icons/default");
+    fs::create_dir_all(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    fs::write(dir.join("index.theme"), format!("[Icon Theme]\nInherits={}\n", theme)).map_err(|e| e.to_string())
+}
+
+/// Apply the settings to every part of the session that is running;
+/// returns the ones that took them
+fn apply(settings: &PointerSettings) -> Result<Vec<String>, String> {
+    let mut backends: Vec<(&str, Backend)> = vec![("defaults", apply_defaults)];
+    if std::env::var_os("SWAYSOCK").is_some() {
+        backends.push(("sway", apply_sway));
+    } else if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        backends.push(("hyprland", apply_hyprland));
+    } else if std::env::var_os("DISPLAY").is_some() && std::env::var_os("WAYLAND_DISPLAY").is_none() {
+        backends.push(("x11", apply_x11));
+    }
+    backends.push(("gsettings", apply_gsettings));
+
+    let mut applied = Vec::new();
+    let mut errors = Vec::new();
+    for (name, apply) in backends {
+        match apply(settings) {
+            Ok(()) => applied.push(name.to_string()),
+            Err(e) => errors.push(e),
+        }
+    }
+    if applied.iter().all(|name| name == "defaults") {
+        return Err(format!("The session did not take the pointer settings: {}", errors.join("; ")));
+    }
+    Ok(applied)
+}
+
+/// Apply the saved settings once the session has started
+pub fn restore_pointer(app: AppHandle) {
+    std::thread::spawn(move || {
+        let settings = app.state::<PointerState>().0.lock().expect("pointer lock").clone();
+        if settings != PointerSettings::default() {
+            let _ = apply(&settings);
+        }
+    });
+}
+
+fn validate(settings: &PointerSettings) -> Result<(), String> {
+    if !(8..=256).contains(&settings.cursor_size) {
+        return Err("The cursor size must be 8-256 pixels".into());
+    }
+    if !(-1.0..=1.0).contains(&settings.speed) {
+        return Err("The speed must be from -1 to 1".into());
+    }
+    if !(100..=2000).contains(&settings.double_click_ms) {
+        return Err("The double-click time must be 100-2000 ms".into());
+    }
+    if let Some(theme) = &settings.cursor_theme {
+        if !cursor_themes().iter().any(|installed| &installed.id == theme) {
+            return Err(format!("No cursor theme {} is installed", theme));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Installed cursor themes, by name
+#[tauri::command]
+pub fn list_cursor_themes() -> Vec<CursorTheme> {
+    cursor_themes()
+}
+
+#[tauri::command]
+pub fn get_pointer_settings(state: State<'_, PointerState>) -> PointerSettings {
+    state.0.lock().expect("pointer lock").clone()
+}
+
+/// Save and apply the cursor theme and size and the mouse and touchpad
+/// settings; returns the parts of the session that took them
+#[tauri::command]
+pub fn set_pointer_settings(
+    app: AppHandle,
+    state: State<'_, PointerState>,
+    auth: State<'_, AuthState>,
+    settings: PointerSettings,
+) -> Result<Vec<String>, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    validate(&settings).map_err(KioskError::invalid)?;
+    {
+        let mut current = state.0.lock().expect("pointer lock");
+        store::save(&app, POINTER_FILE, &settings)?;
+        *current = settings.clone();
+    }
+    Ok(apply(&settings)?)
+}
+
+/// Convert a folder of Windows .cur and .ani files into a cursor theme
+#[tauri::command]
+pub fn install_cursor_theme(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    folder: String,
+    name: Option<String>,
+) -> Result<CursorTheme, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    let source = vfs::resolve(&app, &folder, Access::Read)?;
+    let mut files: HashMap<String, PathBuf> = HashMap::new();
+    let mut scheme = None;
+    for entry in fs::read_dir(&source)?.flatten() {
+        let path = entry.path();
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "cur" | "ani" => {
+                let file_name = entry.file_name().to_string_lossy().to_lowercase();
+                files.insert(file_name, path);
+            }
+            "inf" if scheme.is_none() => {
+                scheme = inf_scheme(&String::from_utf8_lossy(&fs::read(&path)?));
+            }
+            _ => {}
+        }
+    }
+    if files.is_empty() {
+        return Err(KioskError::invalid(format!("{} has no .cur or .ani files", folder)));
+    }
+
+    // The .inf decides where it names a file; file names fill the gaps
+    let mut roles: Vec<Option<PathBuf>> = vec![None; ROLES.len()];
+    if let Some((_, scheme_files)) = &scheme {
+        for (role, file) in roles.iter_mut().zip(scheme_files) {
+            *role = files.get(&file.to_lowercase()).cloned();
+        }
+    }
+    let mut names: Vec<&String> = files.keys().collect();
+    names.sort();
+    for file_name in names {
+        let stem = file_name.rsplit_once('.').map_or(file_name.as_str(), |(stem, _)| stem);
+        if let Some(role) = role_from_name(stem).filter(|&role| roles[role].is_none()) {
+            roles[role] = files.get(file_name).cloned();
+        }
+    }
+    if roles[0].is_none() {
+        return Err(KioskError::invalid("The set has no normal-select (arrow) cursor"));
+    }
+
+    let name = name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| scheme.map(|(name, _)| name).filter(|name| !name.trim().is_empty()))
+        .unwrap_or_else(|| source.file_name().map_or("Cursors".into(), |name| name.to_string_lossy().to_string()));
+    let id: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let theme_dir = user_theme_dir()?.join(&id);
+    let cursors = theme_dir.join("cursors");
+    if theme_dir.exists() {
+        fs::remove_dir_all(&theme_dir)?;
+    }
+    fs::create_dir_all(&cursors)?;
+
+    for ((role, xnames, _), file) in ROLES.iter().zip(&roles) {
+        let Some(file) = file else { continue };
+        let images = convert_cursor(&fs::read(file)?)
+            .map_err(|e| KioskError::invalid(format!("{} cursor {}: {}", role, file.display(), e)))?;
+        fs::write(cursors.join(xnames[0]), xcursor(&images))?;
+        for alias in &xnames[1..] {
+            std::os::unix::fs::symlink(xnames[0], cursors.join(alias))?;
+        }
+    }
+    let comment = format!("Converted from Windows cursors in {}", folder);
+    fs::write(
+        theme_dir.join("index.theme"),
+        format!("[Icon Theme]\nName={}\nComment={}\n", name, comment),
+    )?;
+
+    cursor_themes()
+        .into_iter()
+        .find(|theme| theme.id == id)
+        .ok_or_else(|| KioskError::new(ErrorKind::Failed, format!("{} was not installed", name)))
+}
+
+/// Remove a theme installed from the control panel; a theme in use is
+/// replaced by the session's own
+#[tauri::command]
+pub fn remove_cursor_theme(
+    app: AppHandle,
+    state: State<'_, PointerState>,
+    auth: State<'_, AuthState>,
+    id: String,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    let theme = cursor_themes()
+        .into_iter()
+        .find(|theme| theme.id == id && theme.user_installed)
+        .ok_or_else(|| KioskError::not_found(format!("No installed cursor theme {}", id)))?;
+    fs::remove_dir_all(&theme.path)?;
+
+    let mut settings = state.0.lock().expect("pointer lock");
+    if settings.cursor_theme.as_deref() == Some(id.as_str()) {
+        settings.cursor_theme = None;
+        store::save(&app, POINTER_FILE, &*settings)?;
+        if let Some(dir) = home().map(|home| home.join(".icons/default")) {
+            let _ = fs::remove_file(dir.join("index.theme"));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,502 @@
+//! Printing
+//!
+//! Lays out plain text (or the text of an RTF document) as PostScript pages
+//! with Notepad-style headers, footers and margins, and submits jobs to CUPS
+//! through `lp`. Other modules hand their own documents to [`submit`].
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::i18n::{self, DateStyle};
+use crate::lazy;
+
+/// Points per inch
+const POINTS_PER_INCH: f64 = 72.0;
+
+/// Tab stops every this many characters
+const TAB_WIDTH: usize = 8;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// A CUPS print queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrinterInfo {
+    pub name: String,
+    pub is_default: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    Letter,
+    A4,
+}
+
+impl PaperSize {
+    /// Width and height in points
+    fn points(self) -> (f64, f64) {
+        match self {
+            PaperSize::Letter => (612.0, 792.0),
+            PaperSize::A4 => (595.0, 842.0),
+        }
+    }
+
+    fn media(self) -> &'static str {
+        match self {
+            PaperSize::Letter => "Letter",
+            PaperSize::A4 => "A4",
+        }
+    }
+}
+
+/// Page setup, defaulting to Notepad's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrintOptions {
+    /// CUPS queue; the system default when unset
+    pub printer: Option<String>,
+    /// Document name used for `&f` and the job title
+    pub title: String,
+    /// Header and footer templates: `&f` file, `&p` page, `&d` date, `&t` time,
+    /// `&l` `&c` `&r` align the following text, `&&` a literal ampersand
+    pub header: String,
+    pub footer: String,
+    /// Margins in inches
+    pub margin_left: f64,
+    pub margin_right: f64,
+    pub margin_top: f64,
+    pub margin_bottom: f64,
+    pub font_family: String,
+    pub font_size: f64,
+    pub paper: PaperSize,
+    pub word_wrap: bool,
+    pub copies: u32,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions {
+            printer: None,
+            title: "Untitled".to_string(),
+            header: "&f".to_string(),
+            footer: "Page &p".to_string(),
+            margin_left: 0.75,
+            margin_right: 0.75,
+            margin_top: 1.0,
+            margin_bottom: 1.0,
+            font_family: "Courier New".to_string(),
+            font_size: 10.0,
+            paper: PaperSize::Letter,
+            word_wrap: true,
+            copies: 1,
+        }
+    }
+}
+
+/// A submitted print job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrintJob {
+    /// CUPS request id, e.g. "Office-42"
+    pub job_id: Option<String>,
+    pub pages: usize,
+}
+
+// ============================================================================
+// RTF
+// ============================================================================
+
+/// Destinations whose content is metadata rather than document text
+const RTF_SKIPPED_GROUPS: &[&str] = &[
+    "fonttbl", "colortbl", "stylesheet", "info", "pict", "header", "footer", "generator", "listtable",
+    "listoverridetable", "themedata", "latentstyles", "datastore", "xmlnstbl", "rsidtbl",
+];
+
+/// Extract the plain text of an RTF document
+fn rtf_to_text(rtf: &str) -> String {
+    let chars: Vec<char> = rtf.chars().collect();
+    let mut text = String::new();
+    // Depth at which a skipped group started, if inside one
+    let mut skip_from: Option<usize> = None;
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                if skip_from == Some(depth) {
+                    skip_from = None;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            '\\' => {
+                let Some(&next) = chars.get(i) else { break };
+                if !next.is_ascii_alphabetic() {
+                    i += 1;
+                    match next {
+                        '*' if skip_from.is_none() => skip_from = Some(depth),
+                        '\'' => {
+                            let hex: String = chars.iter().skip(i).take(2).collect();
+                            i += 2;
+                            if let (None, Ok(byte)) = (skip_from, u8::from_str_radix(&hex, 16)) {
+                                // Windows-1252 and Latin-1 agree outside 0x80..0x9F
+                                text.push(byte as char);
+                            }
+                        }
+                        '~' if skip_from.is_none() => text.push('\u{a0}'),
+                        '\\' | '{' | '}' if skip_from.is_none() => text.push(next),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                let start = i;
+                while chars.get(i).is_some_and(|c| c.is_ascii_alphabetic()) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let num_start = i;
+                if chars.get(i) == Some(&'-') {
+                    i += 1;
+                }
+                while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+                    i += 1;
+                }
+                let param: Option<i32> = chars[num_start..i].iter().collect::<String>().parse().ok();
+                // A single space delimits the control word
+                if chars.get(i) == Some(&' ') {
+                    i += 1;
+                }
+
+                if skip_from.is_some() {
+                    continue;
+                }
+                match word.as_str() {
+                    "par" | "line" | "sect" | "page" => text.push('\n'),
+                    "tab" => text.push('\t'),
+                    "u" => {
+                        if let Some(ch) = param.and_then(|n| char::from_u32(n as u16 as u32)) {
+                            text.push(ch);
+                        }
+                        // Skip the ANSI fallback character
+                        if chars.get(i) == Some(&'\\') && chars.get(i + 1) == Some(&'\'') {
+                            i += 4;
+                        } else if chars.get(i).is_some_and(|c| *c != '\\' && *c != '{' && *c != '}') {
+                            i += 1;
+                        }
+                    }
+                    word if RTF_SKIPPED_GROUPS.contains(&word) => skip_from = Some(depth),
+                    _ => {}
+                }
+            }
+            '\r' | '\n' => {}
+            _ if skip_from.is_none() => text.push(c),
+            _ => {}
+        }
+    }
+
+    text
+}
+
+// ============================================================================
+// Layout
+// ============================================================================
+
+/// Standard PostScript font for a family name, with its average glyph width in ems
+fn postscript_font(family: &str) -> (&'static str, f64) {
+    let family = family.to_lowercase();
+    if family.contains("arial") || family.contains("helvetica") || family.contains("tahoma") || family.contains("sans") {
+        ("Helvetica", 0.55)
+    } else if family.contains("times") || family.contains("serif") {
+        ("Times-Roman", 0.5)
+    } else {
+        // Courier is monospaced at exactly 0.6 em
+        ("Courier", 0.6)
+    }
+}
+
+fn expand_tabs(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    for c in line.chars() {
+        if c == '\t' {
+            let spaces = TAB_WIDTH - out.chars().count() % TAB_WIDTH;
+            out.extend(std::iter::repeat(' ').take(spaces));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Break a line at word boundaries to fit `width` characters
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in line.split_inclusive(' ') {
+        if current.chars().count() + word.trim_end().chars().count() > width && !current.is_empty() {
+            lines.push(current.trim_end().to_string());
+            current.clear();
+        }
+        let mut word = word;
+        // Hard-break words longer than a whole line
+        while word.chars().count() > width {
+            let split = word.char_indices().nth(width).map_or(word.len(), |(index, _)| index);
+            lines.push(word[..split].to_string());
+            word = &word[split..];
+        }
+        current.push_str(word);
+    }
+
+    lines.push(current.trim_end().to_string());
+    lines
+}
+
+/// Expand a header/footer template into left, centre and right parts
+fn expand_template(template: &str, title: &str, page: usize) -> [String; 3] {
+    let mut parts: [String; 3] = Default::default();
+    // Notepad centres text that has no alignment code
+    let mut align = 1;
+    let now = Local::now();
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '&' {
+            parts[align].push(c);
+            continue;
+        }
+        match chars.next().map(|c| c.to_ascii_lowercase()) {
+            Some('f') => parts[align].push_str(title),
+            Some('p') => parts[align].push_str(&page.to_string()),
+            Some('d') => parts[align].push_str(&i18n::format_date(&now, DateStyle::Short)),
+            Some('t') => parts[align].push_str(&i18n::format_date(&now, DateStyle::Time)),
+            Some('l') => align = 0,
+            Some('c') => align = 1,
+            Some('r') => align = 2,
+            Some('&') => parts[align].push('&'),
+            Some(other) => {
+                parts[align].push('&');
+                parts[align].push(other);
+            }
+            None => parts[align].push('&'),
+        }
+    }
+    parts
+}
+
+/// Escape a string as a PostScript literal, mapping text to ISO Latin-1
+fn ps_string(text: &str) -> String {
+    let mut out = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => out.push_str(&format!("\\{:03o}", c as u32)),
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// Lay text out into pages of lines
+fn paginate(text: &str, options: &PrintOptions, columns: usize, rows: usize) -> Vec<Vec<String>> {
+    let mut lines = Vec::new();
+    for line in text.replace("\r\n", "\n").split(['\n', '\r']) {
+        let line = expand_tabs(line);
+        if options.word_wrap {
+            lines.extend(wrap(&line, columns));
+        } else {
+            lines.push(line);
+        }
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) && lines.len() > 1 {
+        lines.pop();
+    }
+
+    lines.chunks(rows.max(1)).map(|page| page.to_vec()).collect()
+}
+
+/// Render text as a PostScript document, returning it with its page count
+fn render(text: &str, options: &PrintOptions) -> Result<(String, usize), String> {
+    if options.font_size <= 0.0 {
+        return Err("Font size must be positive".to_string());
+    }
+    let (width, height) = options.paper.points();
+    let left = options.margin_left * POINTS_PER_INCH;
+    let right = width - options.margin_right * POINTS_PER_INCH;
+    let top = height - options.margin_top * POINTS_PER_INCH;
+    let bottom = options.margin_bottom * POINTS_PER_INCH;
+    if right - left < options.font_size * 4.0 || top - bottom < options.font_size * 2.0 {
+        return Err("Margins leave no room for text".to_string());
+    }
+
+    let (font, em_width) = postscript_font(&options.font_family);
+    let size = options.font_size;
+    let leading = size * 1.2;
+    let columns = ((right - left) / (size * em_width)).floor() as usize;
+    let rows = ((top - bottom) / leading).floor() as usize;
+    let pages = paginate(text, options, columns, rows);
+
+    let mut ps = format!(
+        "%!PS-Adobe-3.0\n%%Title: {}\n%%Creator: Kiosk\n%%Pages: {}\n%%BoundingBox: 0 0 {} {}\n%%EndComments\n\
+         /F {{ findfont dup length dict begin {{ 1 index /FID ne {{ def }} {{ pop pop }} ifelse }} forall \
+         /Encoding ISOLatin1Encoding def currentdict end /Latin1Font exch definefont pop /Latin1Font findfont exch scalefont setfont }} bind def\n\
+         /C {{ dup stringwidth pop 2 div neg 0 rmoveto show }} bind def\n\
+         /R {{ dup stringwidth pop neg 0 rmoveto show }} bind def\n",
+        options.title.replace(['\r', '\n'], " "),
+        pages.len(),
+        width as i64,
+        height as i64,
+    );
+
+    let header_y = height - options.margin_top * POINTS_PER_INCH / 2.0;
+    let footer_y = options.margin_bottom * POINTS_PER_INCH / 2.0;
+
+    for (index, page) in pages.iter().enumerate() {
+        let number = index + 1;
+        ps.push_str(&format!("%%Page: {} {}\n{} /{} F\n", number, number, size, font));
+
+        for (template, y) in [(&options.header, header_y), (&options.footer, footer_y)] {
+            let [l, c, r] = expand_template(template, &options.title, number);
+            if !l.is_empty() {
+                ps.push_str(&format!("{:.2} {:.2} moveto {} show\n", left, y, ps_string(&l)));
+            }
+            if !c.is_empty() {
+                ps.push_str(&format!("{:.2} {:.2} moveto {} C\n", (left + right) / 2.0, y, ps_string(&c)));
+            }
+            if !r.is_empty() {
+                ps.push_str(&format!("{:.2} {:.2} moveto {} R\n", right, y, ps_string(&r)));
+            }
+        }
+
+        for (row, line) in page.iter().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let y = top - size - row as f64 * leading;
+            ps.push_str(&format!("{:.2} {:.2} moveto {} show\n", left, y, ps_string(line)));
+        }
+        ps.push_str("showpage\n");
+    }
+
+    ps.push_str("%%EOF\n");
+    Ok((ps, pages.len()))
+}
+
+// ============================================================================
+// CUPS
+// ============================================================================
+
+/// Send a document to a CUPS queue with `lp`, returning the request id
+pub(crate) fn submit(
+    printer: Option<&str>,
+    title: &str,
+    data: &[u8],
+    extra_options: &[String],
+    copies: u32,
+) -> Result<Option<String>, String> {
+    let mut command = Command::new("lp");
+    if let Some(printer) = printer.filter(|printer| !printer.is_empty()) {
+        command.args(["-d", printer]);
+    }
+    command.args(["-t", title, "-n", &copies.max(1).to_string()]);
+    for option in extra_options {
+        command.args(["-o", option]);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run lp: {}", e))?;
+    child
+        .stdin
+        .take()
+        .expect("lp stdin")
+        .write_all(data)
+        .map_err(|e| e.to_string())?;
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(format!("Print failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    // "request id is Office-42 (1 file(s))"
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .skip_while(|word| *word != "is")
+        .nth(1)
+        .map(str::to_string))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List CUPS printers, marking the system default
+#[tauri::command]
+pub fn list_printers(app: AppHandle) -> Result<Vec<PrinterInfo>, KioskError> {
+    lazy::require(&app, "printing")?;
+    let output = Command::new("lpstat")
+        .args(["-e"])
+        .output()
+        .map_err(|e| format!("Failed to run lpstat: {}", e))?;
+    let default = Command::new("lpstat")
+        .arg("-d")
+        .output()
+        .ok()
+        .and_then(|output| {
+            // "system default destination: Office"
+            String::from_utf8_lossy(&output.stdout)
+                .split_once(": ")
+                .map(|(_, name)| name.trim().to_string())
+        });
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| PrinterInfo {
+            name: name.to_string(),
+            is_default: default.as_deref() == Some(name),
+        })
+        .collect())
+}
+
+/// Print plain text or RTF with page setup options
+#[tauri::command]
+pub fn print_text(
+    app: AppHandle,
+    content: String,
+    options: Option<PrintOptions>,
+) -> Result<PrintJob, KioskError> {
+    lazy::require(&app, "printing")?;
+    let options = options.unwrap_or_default();
+    let text = if content.trim_start().starts_with("{\\rtf") {
+        rtf_to_text(&content)
+    } else {
+        content
+    };
+
+    let (document, pages) = render(&text, &options)?;
+    let job_id = submit(
+        options.printer.as_deref(),
+        &options.title,
+        document.as_bytes(),
+        &[format!("media={}", options.paper.media())],
+        options.copies,
+    )?;
+    Ok(PrintJob { job_id, pages })
+}
@@ -0,0 +1,310 @@
+//! Proximity sensor
+//!
+//! Watches a PIR motion sensor on a GPIO input, or an ultrasonic range
+//! finder that prints distances on a serial port (MaxBotix style `R0123`
+//! lines), and publishes `person-approached` and `person-left`. A person has
+//! to be seen for the approach time before they count as arrived, and gone
+//! for the leave time before they count as left, so a PIR's retriggering
+//! or a range finder's odd echo does not flap. An approach can wake the
+//! screen and end the attract loop.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{attract, events, hours, rules, store};
+
+const PROXIMITY_FILE: &str = "proximity.json";
+
+const GPIO_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Wait before reopening a serial port that failed
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProximitySource {
+    /// A PIR or other digital sensor on a GPIO input (sysfs numbering)
+    Gpio { pin: u32, active_high: bool },
+    /// A range finder printing one distance per line
+    Serial {
+        port: String,
+        baud: u32,
+        /// Nearer than this (in the sensor's units) counts as present
+        threshold: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProximityConfig {
+    pub enabled: bool,
+    pub source: ProximitySource,
+    /// How long someone must be seen before they count as arrived
+    pub approach_ms: u64,
+    /// How long no one must be seen before they count as gone
+    pub leave_secs: u64,
+    pub wake_screen: bool,
+    pub exit_attract: bool,
+}
+
+impl Default for ProximityConfig {
+    fn default() -> Self {
+        ProximityConfig {
+            enabled: false,
+            source: ProximitySource::Gpio { pin: 4, active_high: true },
+            approach_ms: 300,
+            leave_secs: 20,
+            wake_screen: true,
+            exit_attract: true,
+        }
+    }
+}
+
+/// Payload of `person-approached`
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonApproached {
+    /// Last distance from a range finder, in the sensor's units
+    pub distance: Option<u32>,
+}
+
+/// Payload of `person-left`
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonLeft {
+    /// How long they were there
+    pub present_secs: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProximityStatus {
+    pub enabled: bool,
+    pub present: bool,
+    /// Raw sensor state before debouncing
+    pub detecting: bool,
+    pub distance: Option<u32>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct Presence {
+    present: bool,
+    detecting: bool,
+    /// When the raw state last changed
+    changed: Option<Instant>,
+    arrived: Option<Instant>,
+    distance: Option<u32>,
+    error: Option<String>,
+}
+
+pub struct ProximityState {
+    config: Mutex<ProximityConfig>,
+    presence: Mutex<Presence>,
+    /// Bumped on every config change so the old watcher stops
+    generation: AtomicU64,
+}
+
+impl ProximityState {
+    pub fn load(app: &AppHandle) -> Self {
+        ProximityState {
+            config: Mutex::new(store::load(app, PROXIMITY_FILE)),
+            presence: Mutex::new(Presence::default()),
+            generation: AtomicU64::new(0),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn termios_speed(baud: u32) -> Option<libc::speed_t> {
+    Some(match baud {
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        _ => return None,
+    })
+}
+
+/// Open a serial port raw at the given speed
+pub(crate) fn open_serial(port: &str, baud: u32) -> Result<File, String> {
+    if !port.starts_with("/dev/tty") {
+        return Err(format!("Not a serial port: {}", port));
+    }
+    let speed = termios_speed(baud).ok_or_else(|| format!("Unsupported baud rate: {}", baud))?;
+    let file = File::open(port).map_err(|e| format!("{}: {}", port, e))?;
+    let fd = file.as_raw_fd();
+    // SAFETY: termios is plain data and the fd stays open across the calls
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return Err(format!("{}: {}", port, std::io::Error::last_os_error()));
+        }
+        libc::cfmakeraw(&mut termios);
+        libc::cfsetspeed(&mut termios, speed);
+        termios.c_cflag |= libc::CLOCAL | libc::CREAD;
+        if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+            return Err(format!("{}: {}", port, std::io::Error::last_os_error()));
+        }
+    }
+    Ok(file)
+}
+
+/// The distance in a range finder line such as `R0123`
+fn parse_distance(line: &str) -> Option<u32> {
+    let digits: String = line.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Feed one raw reading through the debounce and publish arrivals and departures
+fn observe(app: &AppHandle, detecting: bool, distance: Option<u32>) {
+    let state = app.state::<ProximityState>();
+    let config = state.config.lock().expect("proximity lock").clone();
+    let mut presence = state.presence.lock().expect("proximity lock");
+    presence.error = None;
+    if distance.is_some() {
+        presence.distance = distance;
+    }
+    if detecting != presence.detecting || presence.changed.is_none() {
+        presence.detecting = detecting;
+        presence.changed = Some(Instant::now());
+    }
+    let held = presence.changed.map_or(Duration::ZERO, |changed| changed.elapsed());
+
+    if detecting && !presence.present && held >= Duration::from_millis(config.approach_ms) {
+        presence.present = true;
+        presence.arrived = Some(Instant::now());
+        let distance = presence.distance;
+        drop(presence);
+        if config.wake_screen {
+            let _ = hours::wake_display(app);
+        }
+        if config.exit_attract {
+            attract::record_activity(app);
+        }
+        events::publish(app, "person-approached", &PersonApproached { distance });
+    } else if !detecting && presence.present && held >= Duration::from_secs(config.leave_secs) {
+        presence.present = false;
+        let present_secs = presence.arrived.take().map_or(0.0, |arrived| arrived.elapsed().as_secs_f64());
+        drop(presence);
+        events::publish(app, "person-left", &PersonLeft { present_secs });
+    }
+}
+
+fn set_error(app: &AppHandle, error: String) {
+    app.state::<ProximityState>().presence.lock().expect("proximity lock").error = Some(error);
+}
+
+fn current(app: &AppHandle, generation: u64) -> bool {
+    app.state::<ProximityState>().generation.load(Ordering::SeqCst) == generation
+}
+
+fn watch_gpio(app: &AppHandle, generation: u64, pin: u32, active_high: bool) {
+    while current(app, generation) {
+        match rules::gpio_value(pin) {
+            Some(value) => observe(app, (value == 1) == active_high, None),
+            None => set_error(app, format!("Cannot read GPIO {}", pin)),
+        }
+        std::thread::sleep(GPIO_POLL_INTERVAL);
+    }
+}
+
+fn watch_serial(app: &AppHandle, generation: u64, port: &str, baud: u32, threshold: u32) {
+    while current(app, generation) {
+        let file = match open_serial(port, baud) {
+            Ok(file) => file,
+            Err(e) => {
+                set_error(app, e);
+                std::thread::sleep(RETRY_DELAY);
+                continue;
+            }
+        };
+        for line in BufReader::new(file).split(b'\r').flatten() {
+            if !current(app, generation) {
+                return;
+            }
+            if let Some(distance) = parse_distance(&String::from_utf8_lossy(&line)) {
+                observe(app, distance < threshold, Some(distance));
+            }
+        }
+        set_error(app, format!("{} closed", port));
+        std::thread::sleep(RETRY_DELAY);
+    }
+}
+
+/// Start watching the configured sensor, replacing any earlier watcher
+pub fn start_proximity(app: AppHandle) {
+    let state = app.state::<ProximityState>();
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let config = state.config.lock().expect("proximity lock").clone();
+    *state.presence.lock().expect("proximity lock") = Presence::default();
+    if !config.enabled {
+        return;
+    }
+    std::thread::spawn(move || match config.source {
+        ProximitySource::Gpio { pin, active_high } => watch_gpio(&app, generation, pin, active_high),
+        ProximitySource::Serial { port, baud, threshold } => watch_serial(&app, generation, &port, baud, threshold),
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_proximity_config(state: State<'_, ProximityState>) -> ProximityConfig {
+    state.config.lock().expect("proximity lock").clone()
+}
+
+/// Replace the sensor and debounce settings and restart the watcher (admin)
+#[tauri::command]
+pub fn set_proximity_config(
+    app: AppHandle,
+    state: State<'_, ProximityState>,
+    auth: State<'_, AuthState>,
+    config: ProximityConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if let ProximitySource::Serial { port, baud, .. } = &config.source {
+        if !port.starts_with("/dev/tty") {
+            return Err(KioskError::invalid(format!("Not a serial port: {}", port)));
+        }
+        if termios_speed(*baud).is_none() {
+            return Err(KioskError::invalid(format!("Unsupported baud rate: {}", baud)));
+        }
+    }
+    {
+        let mut current = state.config.lock().expect("proximity lock");
+        store::save(&app, PROXIMITY_FILE, &config)?;
+        *current = config;
+    }
+    start_proximity(app);
+    Ok(())
+}
+
+/// Whether someone is in front of the kiosk
+#[tauri::command]
+pub fn get_proximity_state(state: State<'_, ProximityState>) -> ProximityStatus {
+    let enabled = state.config.lock().expect("proximity lock").enabled;
+    let presence = state.presence.lock().expect("proximity lock");
+    ProximityStatus {
+        enabled,
+        present: presence.present,
+        detecting: presence.detecting,
+        distance: presence.distance,
+        error: presence.error.clone(),
+    }
+}
@@ -0,0 +1,183 @@
+//! Queue management
+//!
+//! Take-a-number queues for service desks. One kiosk issues numbers, counter
+//! staff call the next customer, and every change is persisted and broadcast
+//! over the LAN bridge so "Now Serving" displays on other kiosks stay in
+//! step. Numbering restarts each day.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::{events, lan, store};
+
+const QUEUE_FILE: &str = "queue.json";
+
+/// LAN bridge topic carrying queue snapshots
+pub const LAN_TOPIC: &str = "queue";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// An issued queue number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueTicket {
+    /// Display number, e.g. "A012"
+    pub number: String,
+    pub service: String,
+    pub issued_at: i64,
+}
+
+/// The customer currently called to a counter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Serving {
+    pub counter: String,
+    pub ticket: QueueTicket,
+    pub called_at: i64,
+}
+
+/// Full queue state, persisted and shared with peer kiosks
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    /// Day the counters belong to ("YYYY-MM-DD")
+    pub date: String,
+    pub waiting: Vec<QueueTicket>,
+    pub serving: Vec<Serving>,
+    /// Last number issued per service
+    pub counters: HashMap<String, u32>,
+    pub updated_at: i64,
+}
+
+pub struct QueueState(Mutex<QueueSnapshot>);
+
+impl QueueState {
+    pub fn load(app: &AppHandle) -> Self {
+        QueueState(Mutex::new(store::load(app, QUEUE_FILE)))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Start a fresh day's numbering when the date has changed
+fn roll_over(snapshot: &mut QueueSnapshot) {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    if snapshot.date != today {
+        *snapshot = QueueSnapshot {
+            date: today,
+            ..QueueSnapshot::default()
+        };
+    }
+}
+
+fn prefix(service: &str) -> char {
+    service
+        .chars()
+        .find(|c| c.is_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .unwrap_or('Q')
+}
+
+/// Persist, notify the frontend and broadcast to peers
+fn publish(app: &AppHandle, snapshot: &mut QueueSnapshot) -> Result<(), String> {
+    snapshot.updated_at = Local::now().timestamp_millis();
+    store::save(app, QUEUE_FILE, &*snapshot)?;
+    events::publish(app, "queue-updated", &*snapshot);
+    // Peers are optional; a standalone kiosk still works
+    let _ = lan::broadcast(app, LAN_TOPIC, &*snapshot);
+    Ok(())
+}
+
+/// Adopt a newer snapshot received from another kiosk
+pub fn apply_remote(app: &AppHandle, payload: &serde_json::Value) {
+    let Ok(remote) = serde_json::from_value::<QueueSnapshot>(payload.clone()) else { return };
+    let state = app.state::<QueueState>();
+    let mut snapshot = state.0.lock().expect("queue lock");
+    if remote.updated_at <= snapshot.updated_at {
+        return;
+    }
+
+    *snapshot = remote;
+    let _ = store::save(app, QUEUE_FILE, &*snapshot);
+    events::publish(app, "queue-updated", &*snapshot);
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Issue the next number for a service
+#[tauri::command]
+pub fn take_number(app: AppHandle, state: State<'_, QueueState>, service: String) -> Result<QueueTicket, KioskError> {
+    let service = service.trim().to_string();
+    if service.is_empty() {
+        return Err(KioskError::invalid("Service name is required"));
+    }
+
+    let mut snapshot = state.0.lock().expect("queue lock");
+    roll_over(&mut snapshot);
+    let counter = snapshot.counters.entry(service.clone()).or_insert(0);
+    *counter = counter.wrapping_add(1) % 1000;
+
+    let ticket = QueueTicket {
+        number: format!("{}{:03}", prefix(&service), counter),
+        service,
+        issued_at: Local::now().timestamp(),
+    };
+    snapshot.waiting.push(ticket.clone());
+    publish(&app, &mut snapshot)?;
+    Ok(ticket)
+}
+
+/// Call the longest-waiting customer (optionally for one service) to a counter
+#[tauri::command]
+pub fn call_next(
+    app: AppHandle,
+    state: State<'_, QueueState>,
+    counter: String,
+    service: Option<String>,
+) -> Result<Option<Serving>, KioskError> {
+    let mut snapshot = state.0.lock().expect("queue lock");
+    roll_over(&mut snapshot);
+
+    let position = snapshot
+        .waiting
+        .iter()
+        .position(|ticket| service.as_ref().map_or(true, |service| &ticket.service == service));
+    // Calling next always frees the counter, even when nobody is waiting
+    snapshot.serving.retain(|serving| serving.counter != counter);
+
+    let called = position.map(|index| Serving {
+        counter: counter.clone(),
+        ticket: snapshot.waiting.remove(index),
+        called_at: Local::now().timestamp(),
+    });
+    if let Some(serving) = &called {
+        snapshot.serving.push(serving.clone());
+    }
+
+    publish(&app, &mut snapshot)?;
+    Ok(called)
+}
+
+/// Get waiting customers and who is being served where
+#[tauri::command]
+pub fn get_queue_state(state: State<'_, QueueState>) -> QueueSnapshot {
+    let mut snapshot = state.0.lock().expect("queue lock");
+    roll_over(&mut snapshot);
+    snapshot.clone()
+}
+
+/// Clear the queue and restart numbering
+#[tauri::command]
+pub fn reset_queue(app: AppHandle, state: State<'_, QueueState>) -> Result<(), KioskError> {
+    let mut snapshot = state.0.lock().expect("queue lock");
+    *snapshot = QueueSnapshot::default();
+    roll_over(&mut snapshot);
+    Ok(publish(&app, &mut snapshot)?)
+}
@@ -0,0 +1,196 @@
+//! Storage quotas
+//!
+//! Caps how much walk-up users can store in the writable roots (My
+//! Documents and Downloads by default) so the SD card cannot be filled.
+//! Usage is measured by walking the root and cached until a file command
+//! changes it (`fs-changed`) or it goes stale. Writes that would go over are
+//! refused and published as `quota-exceeded`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::vfs::{self, Root};
+use crate::{events, store};
+
+const QUOTA_FILE: &str = "quotas.json";
+
+/// Usage older than this is measured again, to catch changes made outside the kiosk
+const USAGE_MAX_AGE: Duration = Duration::from_secs(60);
+
+const MIB: u64 = 1024 * 1024;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Limit in bytes per root id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaConfig {
+    pub limits: BTreeMap<String, u64>,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        QuotaConfig {
+            limits: BTreeMap::from([("documents".to_string(), 512 * MIB), ("downloads".to_string(), 1024 * MIB)]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaStatus {
+    pub root: String,
+    pub name: String,
+    pub used_bytes: u64,
+    pub limit_bytes: u64,
+    pub exceeded: bool,
+}
+
+pub struct QuotaState {
+    config: Mutex<QuotaConfig>,
+    /// Measured usage per root id
+    usage: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl QuotaState {
+    pub fn load(app: &AppHandle) -> Self {
+        QuotaState {
+            config: Mutex::new(store::load(app, QUOTA_FILE)),
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Bytes used under `path`, without following symlinks
+pub(crate) fn measure(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|items| items.flatten().map(|item| measure(&item.path())).sum())
+        .unwrap_or(0)
+}
+
+fn usage(state: &QuotaState, id: &str, path: &Path) -> u64 {
+    let cached = state.usage.lock().expect("quota usage lock").get(id).copied();
+    if let Some((used, measured_at)) = cached {
+        if measured_at.elapsed() < USAGE_MAX_AGE {
+            return used;
+        }
+    }
+    let used = measure(path);
+    state.usage.lock().expect("quota usage lock").insert(id.to_string(), (used, Instant::now()));
+    used
+}
+
+/// Bytes that still fit under `root`, or `None` when it has no quota
+pub(crate) fn remaining(app: &AppHandle, root: &Root) -> Option<u64> {
+    let state = app.state::<QuotaState>();
+    let limit = state.config.lock().expect("quota config lock").limits.get(&root.info.id).copied()?;
+    Some(limit.saturating_sub(usage(&state, &root.info.id, &root.path)))
+}
+
+/// Fail, and publish `quota-exceeded`, when writing `incoming` more bytes
+/// under `root` would take it over its quota
+pub(crate) fn check(app: &AppHandle, root: &Root, incoming: u64) -> Result<(), KioskError> {
+    let state = app.state::<QuotaState>();
+    let info = &root.info;
+    let Some(limit) = state.config.lock().expect("quota config lock").limits.get(&info.id).copied() else {
+        return Ok(());
+    };
+    let used = usage(&state, &info.id, &root.path);
+    if used.saturating_add(incoming) <= limit {
+        return Ok(());
+    }
+
+    events::publish(
+        app,
+        "quota-exceeded",
+        serde_json::json!({
+            "root": info.id,
+            "name": info.name,
+            "used_bytes": used,
+            "limit_bytes": limit,
+            "requested_bytes": incoming,
+        }),
+    );
+    Err(KioskError::new(
+        ErrorKind::Denied,
+        format!("{} is full ({} MB allowed)", info.name, limit / MIB),
+    ))
+}
+
+/// Forget measured usage when a file command changes a root
+pub fn start_quota(app: AppHandle) {
+    events::listen(&app, |app, event| {
+        if event.topic != "fs-changed" {
+            return;
+        }
+        let root = event.payload.get("path").and_then(|path| path.as_str()).and_then(|path| path.split('/').next());
+        if let Some(root) = root {
+            app.state::<QuotaState>().usage.lock().expect("quota usage lock").remove(root);
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Usage of every root with a quota
+#[tauri::command]
+pub fn get_quota_status(app: AppHandle, state: State<'_, QuotaState>) -> Vec<QuotaStatus> {
+    let limits = state.config.lock().expect("quota config lock").limits.clone();
+    vfs::roots(&app)
+        .into_iter()
+        .filter_map(|root| {
+            let limit = *limits.get(&root.info.id)?;
+            let used = usage(&state, &root.info.id, &root.path);
+            Some(QuotaStatus {
+                root: root.info.id,
+                name: root.info.name,
+                used_bytes: used,
+                limit_bytes: limit,
+                exceeded: used > limit,
+            })
+        })
+        .collect()
+}
+
+/// Get the configured limits
+#[tauri::command]
+pub fn get_quota_config(state: State<'_, QuotaState>) -> QuotaConfig {
+    state.config.lock().expect("quota config lock").clone()
+}
+
+/// Set or remove (`None`) the limit for a root (admin)
+#[tauri::command]
+pub fn set_quota(
+    app: AppHandle,
+    state: State<'_, QuotaState>,
+    auth: State<'_, AuthState>,
+    root: String,
+    limit_bytes: Option<u64>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut config = state.config.lock().expect("quota config lock");
+    match limit_bytes {
+        Some(limit) => config.limits.insert(root, limit),
+        None => config.limits.remove(&root),
+    };
+    Ok(store::save(&app, QUOTA_FILE, &*config)?)
+}
@@ -0,0 +1,124 @@
+//! Recent documents tracking
+//!
+//! Keeps a most-recently-used list of opened and saved documents, tagged with
+//! the app that touched them, so the Start menu Documents flyout and per-app
+//! jump lists reflect what the user actually worked on.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::store;
+
+const RECENTS_FILE: &str = "recents.json";
+
+/// Maximum number of entries kept across all apps
+const MAX_RECENTS: usize = 100;
+
+/// Default number of entries returned when no limit is given
+const DEFAULT_LIMIT: usize = 15;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// A single recently used document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentEntry {
+    pub path: String,
+    pub name: String,
+    pub app: String,
+    pub last_used: i64,
+}
+
+/// Recent documents, newest first
+pub struct RecentsState(Mutex<Vec<RecentEntry>>);
+
+impl RecentsState {
+    pub fn load(app: &AppHandle) -> Self {
+        RecentsState(Mutex::new(store::load(app, RECENTS_FILE)))
+    }
+}
+
+// ============================================================================
+// Backend API
+// ============================================================================
+
+/// Record that `app_id` opened or saved `path`.
+///
+/// Backend open/save operations call this directly; frontend-only file access
+/// goes through the `add_recent` command.
+pub fn record(app: &AppHandle, app_id: &str, path: &str) -> Result<(), String> {
+    let state = app.state::<RecentsState>();
+    let mut recents = state.0.lock().expect("recents lock");
+
+    recents.retain(|entry| !(entry.path == path && entry.app == app_id));
+    recents.insert(
+        0,
+        RecentEntry {
+            path: path.to_string(),
+            name: Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string()),
+            app: app_id.to_string(),
+            last_used: Local::now().timestamp(),
+        },
+    );
+    recents.truncate(MAX_RECENTS);
+
+    store::save(app, RECENTS_FILE, &*recents)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Add a document to the recents list
+#[tauri::command]
+pub fn add_recent(handle: AppHandle, app: String, path: String) -> Result<(), KioskError> {
+    Ok(record(&handle, &app, &path)?)
+}
+
+/// Get recent documents, optionally filtered to a single app's jump list.
+///
+/// Without an app filter each path appears once, at its most recent use.
+#[tauri::command]
+pub fn get_recents(
+    state: State<'_, RecentsState>,
+    app: Option<String>,
+    limit: Option<usize>,
+) -> Vec<RecentEntry> {
+    let recents = state.0.lock().expect("recents lock");
+    let mut seen = std::collections::HashSet::new();
+
+    recents
+        .iter()
+        .filter(|entry| match &app {
+            Some(app) => &entry.app == app,
+            None => seen.insert(entry.path.clone()),
+        })
+        .take(limit.unwrap_or(DEFAULT_LIMIT))
+        .cloned()
+        .collect()
+}
+
+/// Clear recent documents for one app, or all of them
+#[tauri::command]
+pub fn clear_recents(
+    handle: AppHandle,
+    state: State<'_, RecentsState>,
+    app: Option<String>,
+) -> Result<(), KioskError> {
+    let mut recents = state.0.lock().expect("recents lock");
+
+    match app {
+        Some(app) => recents.retain(|entry| entry.app != app),
+        None => recents.clear(),
+    }
+
+    Ok(store::save(&handle, RECENTS_FILE, &*recents)?)
+}
@@ -0,0 +1,251 @@
+//! Screen recording
+//!
+//! Records the screen to an MP4 in the data directory for bug reports:
+//! ffmpeg's x11grab under X11, wf-recorder under Wayland. A watcher stops
+//! the recording when it reaches its duration or the size cap, and the
+//! recorder is interrupted rather than killed so the file is finalized.
+//! Only the newest few recordings are kept. Recording is a lazy subsystem
+//! (`recording` flag).
+
+use chrono::Local;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::lazy::{self, Subsystem};
+use crate::{events, store};
+
+const RECORDING_DIR: &str = "recordings";
+
+const RECORDINGS_KEPT: usize = 5;
+
+const DEFAULT_DURATION: Duration = Duration::from_secs(60);
+
+const MAX_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// Recordings are stopped once the file reaches this size
+const MAX_BYTES: u64 = 200 * 1024 * 1024;
+
+const FRAMERATE: &str = "15";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long the recorder gets to finalize the file before it is killed
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Recording {
+    pub path: String,
+    pub size: u64,
+    pub duration_secs: f64,
+    /// Why it stopped: "stopped", "time limit", "size limit" or "recorder exited"
+    pub reason: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingStatus {
+    pub path: String,
+    pub elapsed_secs: f64,
+    pub max_duration_secs: u64,
+    pub size: u64,
+}
+
+struct Active {
+    child: Child,
+    path: PathBuf,
+    started: Instant,
+    created_at: i64,
+    max_duration: Duration,
+}
+
+pub struct RecordingState(Mutex<Option<Active>>);
+
+impl Subsystem for RecordingState {
+    const NAME: &'static str = "recording";
+
+    fn load(_app: &AppHandle) -> Self {
+        RecordingState(Mutex::new(None))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|meta| meta.len()).unwrap_or(0)
+}
+
+fn recorder(app: &AppHandle, path: &Path, max_duration: Duration) -> Result<Command, String> {
+    let output = path.to_string_lossy().to_string();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut command = Command::new("wf-recorder");
+        command.args(["-r", FRAMERATE, "-f", &output]);
+        return Ok(command);
+    }
+
+    let display = std::env::var("DISPLAY").unwrap_or_else(|_| ":0".to_string());
+    let size = app
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| *monitor.size())
+        .ok_or_else(|| "No display to record".to_string())?;
+    let mut command = Command::new("ffmpeg");
+    command.args(["-y", "-loglevel", "error", "-f", "x11grab", "-framerate", FRAMERATE]);
+    command.args(["-video_size", &format!("{}x{}", size.width, size.height), "-i", &display]);
+    command.args(["-t", &max_duration.as_secs().to_string(), "-fs", &MAX_BYTES.to_string()]);
+    // Fragmented MP4 stays playable even if the recorder dies mid-file
+    command.args(["-c:v", "libx264", "-preset", "ultrafast", "-pix_fmt", "yuv420p"]);
+    command.args(["-movflags", "+frag_keyframe+empty_moov", &output]);
+    Ok(command)
+}
+
+/// Interrupt the recorder so it finalizes the file, killing it if it hangs
+fn interrupt(child: &mut Child) {
+    if matches!(child.try_wait(), Ok(None)) {
+        // SAFETY: kill has no memory safety requirements
+        unsafe { libc::kill(child.id() as i32, libc::SIGINT) };
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while matches!(child.try_wait(), Ok(None)) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+    if matches!(child.try_wait(), Ok(None)) {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+/// Delete the oldest recordings beyond the limit
+fn prune(dir: &Path) {
+    let mut recordings: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    // Names embed the creation time, so sorted order is oldest first
+    recordings.sort();
+    for path in recordings.iter().take(recordings.len().saturating_sub(RECORDINGS_KEPT)) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn finish(app: &AppHandle, mut active: Active, reason: &str) -> Recording {
+    interrupt(&mut active.child);
+    let recording = Recording {
+        path: active.path.display().to_string(),
+        size: file_size(&active.path),
+        duration_secs: active.started.elapsed().as_secs_f64(),
+        reason: reason.to_string(),
+        created_at: active.created_at,
+    };
+    if let Some(dir) = active.path.parent() {
+        prune(dir);
+    }
+    events::publish(app, "screen-recording-finished", &recording);
+    recording
+}
+
+/// Stop the recording started at `created_at` once it exits or hits a limit
+fn watch(app: AppHandle, created_at: i64) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let Some(state) = lazy::loaded::<RecordingState>(&app) else { return };
+        let mut current = state.0.lock().expect("recording lock");
+        let Some(active) = current.as_mut().filter(|active| active.created_at == created_at) else {
+            return;
+        };
+        let reason = if !matches!(active.child.try_wait(), Ok(None)) {
+            "recorder exited"
+        } else if active.started.elapsed() >= active.max_duration {
+            "time limit"
+        } else if file_size(&active.path) >= MAX_BYTES {
+            "size limit"
+        } else {
+            continue;
+        };
+        let Some(active) = current.take() else { return };
+        drop(current);
+        finish(&app, active, reason);
+        return;
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start recording the screen for up to `max_duration` seconds (default 60,
+/// at most 10 minutes)
+#[tauri::command]
+pub fn start_screen_recording(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    max_duration: Option<u64>,
+) -> Result<RecordingStatus, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    let state = lazy::get::<RecordingState>(&app)?;
+    let mut current = state.0.lock().expect("recording lock");
+    if current.is_some() {
+        return Err(KioskError::invalid("A recording is already running"));
+    }
+
+    let max_duration = max_duration.map_or(DEFAULT_DURATION, Duration::from_secs).min(MAX_DURATION);
+    let dir = store::data_path(&app, RECORDING_DIR)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let now = Local::now();
+    let path = dir.join(format!("recording-{}.mp4", now.format("%Y%m%d-%H%M%S")));
+    let child = recorder(&app, &path, max_duration)?
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Cannot start the screen recorder: {}", e))?;
+
+    let status = RecordingStatus {
+        path: path.display().to_string(),
+        elapsed_secs: 0.0,
+        max_duration_secs: max_duration.as_secs(),
+        size: 0,
+    };
+    *current = Some(Active {
+        child,
+        path,
+        started: Instant::now(),
+        created_at: now.timestamp(),
+        max_duration,
+    });
+    watch(app.clone(), now.timestamp());
+    events::publish(&app, "screen-recording-started", &status);
+    Ok(status)
+}
+
+/// Stop the running recording and return the finished file
+#[tauri::command]
+pub fn stop_screen_recording(app: AppHandle) -> Result<Recording, KioskError> {
+    let active = lazy::get::<RecordingState>(&app)?.0.lock().expect("recording lock").take();
+    let active = active.ok_or_else(|| KioskError::invalid("No recording is running"))?;
+    Ok(finish(&app, active, "stopped"))
+}
+
+/// The running recording, if any
+#[tauri::command]
+pub fn get_screen_recording_status(app: AppHandle) -> Result<Option<RecordingStatus>, KioskError> {
+    let current = lazy::get::<RecordingState>(&app)?.0.lock().expect("recording lock");
+    Ok(current.as_ref().map(|active| RecordingStatus {
+        path: active.path.display().to_string(),
+        elapsed_secs: active.started.elapsed().as_secs_f64(),
+        max_duration_secs: active.max_duration.as_secs(),
+        size: file_size(&active.path),
+    }))
+}
@@ -0,0 +1,71 @@
+//! Regional settings
+//!
+//! One place for everything the classic Regional Options dialog covers:
+//! the kiosk's locale, its number, currency and date formats, its currency,
+//! the measurement system and the kiosk's own keyboard layouts. The whole
+//! set is checked before anything is saved, and a failure part way through
+//! puts back what was already changed, so the dialog's OK either applies
+//! everything or nothing.
+//!
+//! The formats are kept by `i18n` and the layouts by `keyboard`; this only
+//! reads and writes them together.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::i18n::{self, RegionalFormats};
+use crate::keyboard::{self, KeyboardSettings};
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionalSettings {
+    pub locale: String,
+    pub formats: RegionalFormats,
+    /// ISO 4217 code of the kiosk's currency, such as `EUR`
+    pub currency: Option<String>,
+    /// The layouts used when nobody is signed in
+    pub keyboard: KeyboardSettings,
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// The kiosk's regional settings, or with `locale`, that locale's own
+/// formats in place of the kiosk's, for when the dialog's locale changes
+#[tauri::command]
+pub fn get_regional_settings(app: AppHandle, locale: Option<String>) -> Result<RegionalSettings, KioskError> {
+    let formats = i18n::formats(&app, locale.as_deref()).map_err(KioskError::not_found)?;
+    Ok(RegionalSettings {
+        locale: locale.unwrap_or_else(i18n::get_locale),
+        formats,
+        currency: i18n::currency(&app),
+        keyboard: keyboard::kiosk_settings(&app),
+    })
+}
+
+/// Check and save all the regional settings at once
+#[tauri::command]
+pub fn set_regional_settings(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    settings: RegionalSettings,
+) -> Result<RegionalSettings, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    let prepared = i18n::prepare_locale(&app, &settings.locale, &settings.formats, settings.currency.clone())
+        .map_err(KioskError::invalid)?;
+    keyboard::check_settings(&settings.keyboard).map_err(KioskError::invalid)?;
+
+    let previous = keyboard::kiosk_settings(&app);
+    keyboard::save_kiosk_settings(&app, settings.keyboard.clone())?;
+    if let Err(e) = i18n::commit_locale(&app, prepared) {
+        let _ = keyboard::save_kiosk_settings(&app, previous);
+        return Err(e.into());
+    }
+    get_regional_settings(app, None)
+}
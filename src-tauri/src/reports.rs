@@ -0,0 +1,679 @@
+//! Reports
+//!
+//! Turns stored data into CSV or PDF reports: visitor counts from the
+//! footfall counter, kiosk uptime and network data usage. Reports are made
+//! on demand with `generate_report`, or on a daily, weekly or monthly
+//! schedule covering the previous day, week or month. Either way the file
+//! can be saved to a virtual folder, emailed through a mail account and
+//! uploaded with an HTTP PUT; scheduled runs publish `report-finished`.
+//!
+//! PDFs use `reports/<type>.html` or `reports/report.html` from the
+//! templates folder when there is one, and a plain table otherwise.
+//!
+//! Uptime is tallied here: seconds the app was running and the number of
+//! starts per day, in `uptime.json`. A crash loses at most the last few
+//! minutes.
+
+use base64::Engine;
+use chrono::{Datelike, Duration as DateDuration, Local, Months, NaiveDate, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{config, events, footfall, http, metering, store, templates, vfs};
+
+const REPORTS_FILE: &str = "reports.json";
+
+const UPTIME_FILE: &str = "uptime.json";
+
+const DAYS_KEPT: usize = 400;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Uptime is written out this often, to spare the SD card
+const SAVE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Folder reports are saved to when a delivery names none
+const DEFAULT_FOLDER: &str = "documents";
+
+const SECS_PER_DAY: f64 = 86_400.0;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportType {
+    Footfall,
+    Uptime,
+    DataUsage,
+}
+
+impl ReportType {
+    fn slug(self) -> &'static str {
+        match self {
+            ReportType::Footfall => "footfall",
+            ReportType::Uptime => "uptime",
+            ReportType::DataUsage => "data-usage",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            ReportType::Footfall => "Visitors",
+            ReportType::Uptime => "Uptime",
+            ReportType::DataUsage => "Data Usage",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFileFormat {
+    #[default]
+    Csv,
+    Pdf,
+}
+
+/// Days covered, inclusive ("YYYY-MM-DD"); today when neither is given
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportRange {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportEmail {
+    pub account_id: String,
+    pub to: Vec<String>,
+}
+
+/// Where a finished report goes; any combination
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportDelivery {
+    /// Virtual folder to save the file in
+    pub folder: Option<String>,
+    pub email: Option<ReportEmail>,
+    /// URL the file is PUT to; one ending in `/` gets the file name added
+    pub upload_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFrequency {
+    /// Every day, covering the day before
+    Daily,
+    /// Mondays, covering the week before
+    Weekly,
+    /// The 1st, covering the month before
+    Monthly,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSchedule {
+    pub id: String,
+    pub report: ReportType,
+    #[serde(default)]
+    pub format: ReportFileFormat,
+    pub frequency: ReportFrequency,
+    /// "HH:MM" the report is made at
+    pub time: String,
+    pub delivery: ReportDelivery,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ReportsConfig {
+    schedules: Vec<ReportSchedule>,
+    /// Date each schedule last ran, so a restart does not repeat it
+    last_runs: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GeneratedReport {
+    pub report: ReportType,
+    pub format: ReportFileFormat,
+    pub from: String,
+    pub to: String,
+    pub file_name: String,
+    pub rows: usize,
+    /// CSV text, or the PDF as base64
+    pub content: String,
+    /// Virtual path it was saved to
+    pub path: Option<String>,
+    pub emailed: bool,
+    pub uploaded: bool,
+}
+
+/// Payload of `report-finished`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportFinished {
+    pub schedule_id: String,
+    pub report: ReportType,
+    pub from: String,
+    pub to: String,
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct UptimeDay {
+    running_secs: u64,
+    starts: u32,
+}
+
+/// A report's contents before it is written out, also handed to templates
+#[derive(Debug, Clone, Serialize)]
+struct ReportTable {
+    title: String,
+    report: ReportType,
+    from: String,
+    to: String,
+    generated_at: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    /// Label and value pairs shown under the table
+    summary: Vec<(String, String)>,
+}
+
+pub struct ReportsState {
+    config: Mutex<ReportsConfig>,
+    /// Keyed by "YYYY-MM-DD"
+    uptime: Mutex<BTreeMap<String, UptimeDay>>,
+}
+
+impl ReportsState {
+    pub fn load(app: &AppHandle) -> Self {
+        ReportsState {
+            config: Mutex::new(store::load(app, REPORTS_FILE)),
+            uptime: Mutex::new(store::load(app, UPTIME_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Not a date (YYYY-MM-DD): {}", date))
+}
+
+fn date_string(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn range(range: &ReportRange) -> Result<(String, String), KioskError> {
+    let today = Local::now().date_naive();
+    let to = range.to.as_deref().map(parse_date).transpose()?.unwrap_or(today);
+    let from = range.from.as_deref().map(parse_date).transpose()?.unwrap_or(to);
+    if from > to {
+        return Err(KioskError::invalid("The range starts after it ends"));
+    }
+    Ok((date_string(from), date_string(to)))
+}
+
+/// The period a scheduled run on `today` covers
+fn scheduled_range(frequency: ReportFrequency, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let yesterday = today - DateDuration::days(1);
+    match frequency {
+        ReportFrequency::Daily => (yesterday, yesterday),
+        ReportFrequency::Weekly => {
+            let monday = today - DateDuration::days(today.weekday().num_days_from_monday() as i64);
+            (monday - DateDuration::days(7), monday - DateDuration::days(1))
+        }
+        ReportFrequency::Monthly => {
+            let first = today.with_day(1).unwrap_or(today);
+            (first.checked_sub_months(Months::new(1)).unwrap_or(first), first - DateDuration::days(1))
+        }
+    }
+}
+
+/// Whether a schedule is due at `now` on `today`
+fn due(schedule: &ReportSchedule, last_run: Option<&String>, today: NaiveDate, now: NaiveTime) -> bool {
+    let Ok(time) = NaiveTime::parse_from_str(&schedule.time, "%H:%M") else { return false };
+    let day = match schedule.frequency {
+        ReportFrequency::Daily => true,
+        ReportFrequency::Weekly => today.weekday() == Weekday::Mon,
+        ReportFrequency::Monthly => today.day() == 1,
+    };
+    schedule.enabled && day && now >= time && last_run.map_or(true, |last| *last != date_string(today))
+}
+
+fn footfall_table(app: &AppHandle, from: &str, to: &str) -> Result<ReportTable, KioskError> {
+    let stats = footfall::get_footfall_stats(app.state(), Some(from.to_string()), Some(to.to_string()))?;
+    let dwell = |secs: Option<f64>| secs.map(|secs| format!("{:.0}", secs)).unwrap_or_default();
+    let rows = stats
+        .days
+        .iter()
+        .map(|day| {
+            let peak = (0..day.hours.len()).max_by_key(|&hour| day.hours[hour]);
+            vec![
+                day.date.clone(),
+                day.total.to_string(),
+                peak.filter(|_| day.total > 0).map(|hour| format!("{:02}:00", hour)).unwrap_or_default(),
+                dwell(day.average_dwell_secs),
+            ]
+        })
+        .collect();
+    Ok(ReportTable {
+        columns: vec!["date", "visitors", "peak_hour", "average_dwell_secs"]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        rows,
+        summary: vec![
+            ("Visitors".to_string(), stats.total.to_string()),
+            (
+                "Busiest hour".to_string(),
+                stats.peak_hour.map(|hour| format!("{:02}:00", hour)).unwrap_or_default(),
+            ),
+            ("Average dwell (s)".to_string(), dwell(stats.average_dwell_secs)),
+        ],
+        ..table(ReportType::Footfall, from, to)
+    })
+}
+
+fn uptime_table(app: &AppHandle, from: &str, to: &str) -> ReportTable {
+    let uptime = app.state::<ReportsState>().uptime.lock().expect("uptime lock").clone();
+    let mut running = 0;
+    let mut starts = 0;
+    let rows = uptime
+        .range(from.to_string()..=to.to_string())
+        .map(|(date, day)| {
+            running += day.running_secs;
+            starts += day.starts;
+            vec![
+                date.clone(),
+                day.running_secs.to_string(),
+                format!("{:.1}", day.running_secs as f64 / SECS_PER_DAY * 100.0),
+                day.starts.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let days = parse_date(to)
+        .ok()
+        .zip(parse_date(from).ok())
+        .map_or(1, |(to, from)| (to - from).num_days() + 1);
+    ReportTable {
+        columns: vec!["date", "running_secs", "uptime_percent", "starts"]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        rows,
+        summary: vec![
+            ("Running (h)".to_string(), format!("{:.1}", running as f64 / 3600.0)),
+            (
+                "Uptime (%)".to_string(),
+                format!("{:.1}", running as f64 / (days as f64 * SECS_PER_DAY) * 100.0),
+            ),
+            ("Starts".to_string(), starts.to_string()),
+        ],
+        ..table(ReportType::Uptime, from, to)
+    }
+}
+
+fn data_usage_table(app: &AppHandle, from: &str, to: &str) -> Result<ReportTable, KioskError> {
+    let usage = metering::get_data_usage(app.state(), Some(from.to_string()), Some(to.to_string()))?;
+    let rows = usage
+        .days
+        .iter()
+        .flat_map(|day| {
+            day.interfaces.iter().map(|(name, counts)| {
+                vec![
+                    day.date.clone(),
+                    name.clone(),
+                    counts.rx_bytes.to_string(),
+                    counts.tx_bytes.to_string(),
+                ]
+            })
+        })
+        .collect();
+    Ok(ReportTable {
+        columns: vec!["date", "interface", "rx_bytes", "tx_bytes"]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        rows,
+        summary: usage
+            .totals
+            .iter()
+            .map(|(name, counts)| {
+                let mb = (counts.rx_bytes + counts.tx_bytes) as f64 / (1024.0 * 1024.0);
+                (format!("{} (MB)", name), format!("{:.1}", mb))
+            })
+            .collect(),
+        ..table(ReportType::DataUsage, from, to)
+    })
+}
+
+fn table(report: ReportType, from: &str, to: &str) -> ReportTable {
+    ReportTable {
+        title: report.title().to_string(),
+        report,
+        from: from.to_string(),
+        to: to.to_string(),
+        generated_at: Local::now().to_rfc3339(),
+        columns: Vec::new(),
+        rows: Vec::new(),
+        summary: Vec::new(),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(table: &ReportTable) -> String {
+    std::iter::once(&table.columns)
+        .chain(&table.rows)
+        .map(|row| row.iter().map(|value| csv_field(value)).collect::<Vec<_>>().join(",") + "\r\n")
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// A plain table for when the templates folder has no report template
+fn default_html(table: &ReportTable) -> String {
+    let cells = |row: &[String], tag: &str| {
+        row.iter()
+            .map(|value| format!("<{0}>{1}</{0}>", tag, escape_html(value)))
+            .collect::<String>()
+    };
+    let rows: String = table
+        .rows
+        .iter()
+        .map(|row| format!("<tr>{}</tr>", cells(row, "td")))
+        .collect();
+    let summary: String = table
+        .summary
+        .iter()
+        .map(|(label, value)| format!("<tr><th>{}</th><td>{}</td></tr>", escape_html(label), escape_html(value)))
+        .collect();
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title><style>\
+         body{{font-family:sans-serif;font-size:10pt}}table{{border-collapse:collapse;margin-bottom:1em}}\
+         th,td{{border:1px solid #999;padding:2px 6px;text-align:left}}</style></head><body>\
+         <h1>{title}</h1><p>{from} to {to}</p><table><tr>{columns}</tr>{rows}</table>\
+         <table>{summary}</table></body></html>",
+        title = escape_html(&table.title),
+        from = table.from,
+        to = table.to,
+        columns = cells(&table.columns, "th"),
+        rows = rows,
+        summary = summary,
+    )
+}
+
+#[cfg(feature = "mail")]
+fn email(
+    app: &AppHandle,
+    email: &ReportEmail,
+    table: &ReportTable,
+    file_name: &str,
+    data: &[u8],
+) -> Result<(), String> {
+    use crate::mail::{self, EmailDraft, MailAttachment};
+
+    let draft = EmailDraft {
+        account_id: email.account_id.clone(),
+        to: email.to.clone(),
+        cc: Vec::new(),
+        subject: format!("{} report {} to {}", table.title, table.from, table.to),
+        body: table
+            .summary
+            .iter()
+            .map(|(label, value)| format!("{}: {}\n", label, value))
+            .collect(),
+    };
+    let content_type = if file_name.ends_with(".pdf") { "application/pdf" } else { "text/csv" };
+    let attachment = MailAttachment {
+        name: file_name.to_string(),
+        content_type: content_type.to_string(),
+        data: data.to_vec(),
+    };
+    mail::send(app, draft, vec![attachment]).map_err(|e| e.message)
+}
+
+#[cfg(not(feature = "mail"))]
+fn email(_: &AppHandle, _: &ReportEmail, _: &ReportTable, _: &str, _: &[u8]) -> Result<(), String> {
+    Err("This build has no mail support".to_string())
+}
+
+fn upload(url: &str, file_name: &str, format: ReportFileFormat, data: &[u8]) -> Result<(), String> {
+    let url = if url.ends_with('/') {
+        format!("{}{}", url, file_name)
+    } else {
+        url.to_string()
+    };
+    let content_type = match format {
+        ReportFileFormat::Csv => "text/csv",
+        ReportFileFormat::Pdf => "application/pdf",
+    };
+    http::agent()
+        .put(&url)
+        .set("Content-Type", content_type)
+        .send_bytes(data)
+        .map_err(|e| format!("Upload failed: {}", e))?;
+    Ok(())
+}
+
+/// Build a report and deliver it
+fn generate(
+    app: &AppHandle,
+    report: ReportType,
+    from: String,
+    to: String,
+    format: ReportFileFormat,
+    delivery: &ReportDelivery,
+) -> Result<GeneratedReport, KioskError> {
+    let table = match report {
+        ReportType::Footfall => footfall_table(app, &from, &to)?,
+        ReportType::Uptime => uptime_table(app, &from, &to),
+        ReportType::DataUsage => data_usage_table(app, &from, &to)?,
+    };
+    let extension = match format {
+        ReportFileFormat::Csv => "csv",
+        ReportFileFormat::Pdf => "pdf",
+    };
+    let file_name = if from == to {
+        format!("{}-{}.{}", report.slug(), from, extension)
+    } else {
+        format!("{}-{}-to-{}.{}", report.slug(), from, to, extension)
+    };
+    let data = match format {
+        ReportFileFormat::Csv => to_csv(&table).into_bytes(),
+        ReportFileFormat::Pdf => {
+            let specific = format!("reports/{}.html", report.slug());
+            let html = templates::render_first(app, &[&specific, "reports/report.html"], &table)?
+                .unwrap_or_else(|| default_html(&table));
+            templates::html_to_pdf(&html)?
+        }
+    };
+
+    let path = match &delivery.folder {
+        Some(folder) => {
+            let path = format!("{}/{}", folder.trim_end_matches('/'), file_name);
+            let real = vfs::resolve_write(app, &path, data.len() as u64)?;
+            if let Some(parent) = real.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&real, &data)?;
+            Some(path)
+        }
+        None => None,
+    };
+    if let Some(email_to) = &delivery.email {
+        email(app, email_to, &table, &file_name, &data).map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    }
+    if let Some(url) = &delivery.upload_url {
+        upload(url, &file_name, format, &data)?;
+    }
+
+    Ok(GeneratedReport {
+        report,
+        format,
+        from,
+        to,
+        rows: table.rows.len(),
+        content: match format {
+            ReportFileFormat::Csv => String::from_utf8(data).unwrap_or_default(),
+            ReportFileFormat::Pdf => base64::engine::general_purpose::STANDARD.encode(&data),
+        },
+        file_name,
+        path,
+        emailed: delivery.email.is_some(),
+        uploaded: delivery.upload_url.is_some(),
+    })
+}
+
+/// Run the schedules that are due
+fn run_due(app: &AppHandle, state: &ReportsState) {
+    let now = Local::now();
+    let today = now.date_naive();
+    let due_now: Vec<ReportSchedule> = {
+        let config = state.config.lock().expect("reports lock");
+        config
+            .schedules
+            .iter()
+            .filter(|schedule| due(schedule, config.last_runs.get(&schedule.id), today, now.time()))
+            .cloned()
+            .collect()
+    };
+    for schedule in due_now {
+        let (from, to) = scheduled_range(schedule.frequency, today);
+        let (from, to) = (date_string(from), date_string(to));
+        let mut delivery = schedule.delivery.clone();
+        if delivery.email.is_none() && delivery.upload_url.is_none() && delivery.folder.is_none() {
+            delivery.folder = Some(DEFAULT_FOLDER.to_string());
+        }
+        let result = generate(app, schedule.report, from.clone(), to.clone(), schedule.format, &delivery);
+        {
+            let mut config = state.config.lock().expect("reports lock");
+            config.last_runs.insert(schedule.id.clone(), date_string(today));
+            let _ = store::save(app, REPORTS_FILE, &*config);
+        }
+        let (path, error) = match result {
+            Ok(report) => (report.path, None),
+            Err(e) => (None, Some(e.message)),
+        };
+        events::publish(
+            app,
+            "report-finished",
+            ReportFinished {
+                schedule_id: schedule.id,
+                report: schedule.report,
+                from,
+                to,
+                path,
+                error,
+            },
+        );
+    }
+}
+
+/// Tally uptime and run scheduled reports in the background
+pub fn start_reports(app: AppHandle) {
+    std::thread::spawn(move || {
+        let state = app.state::<ReportsState>();
+        let date = Local::now().format("%Y-%m-%d").to_string();
+        state.uptime.lock().expect("uptime lock").entry(date).or_default().starts += 1;
+        let mut last_tick = Instant::now();
+        let mut last_save = None::<Instant>;
+        loop {
+            let elapsed = last_tick.elapsed().as_secs();
+            last_tick = Instant::now();
+            {
+                let mut uptime = state.uptime.lock().expect("uptime lock");
+                let date = Local::now().format("%Y-%m-%d").to_string();
+                uptime.entry(date).or_default().running_secs += elapsed;
+                while uptime.len() > DAYS_KEPT {
+                    uptime.pop_first();
+                }
+                if last_save.map_or(true, |saved: Instant| saved.elapsed() >= SAVE_INTERVAL) {
+                    let _ = store::save(&app, UPTIME_FILE, &*uptime);
+                    last_save = Some(Instant::now());
+                }
+            }
+            run_due(&app, &state);
+            std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Make a report over `range` (today when not given) and deliver it;
+/// without a delivery it is only returned (supervisor)
+#[tauri::command]
+pub fn generate_report(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    report: ReportType,
+    range: Option<ReportRange>,
+    format: Option<ReportFileFormat>,
+    delivery: Option<ReportDelivery>,
+) -> Result<GeneratedReport, KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    let (from, to) = self::range(&range.unwrap_or_default())?;
+    generate(&app, report, from, to, format.unwrap_or_default(), &delivery.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn get_report_schedules(state: State<'_, ReportsState>) -> Vec<ReportSchedule> {
+    state.config.lock().expect("reports lock").schedules.clone()
+}
+
+/// Replace the report schedules (admin)
+#[tauri::command]
+pub fn set_report_schedules(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, ReportsState>,
+    schedules: Vec<ReportSchedule>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    for schedule in &schedules {
+        if schedule.id.trim().is_empty() {
+            return Err(KioskError::invalid("Every schedule needs an id"));
+        }
+        if schedules.iter().filter(|other| other.id == schedule.id).count() > 1 {
+            return Err(KioskError::invalid(format!("Duplicate schedule id: {}", schedule.id)));
+        }
+        NaiveTime::parse_from_str(&schedule.time, "%H:%M")
+            .map_err(|_| KioskError::invalid(format!("Not a time (HH:MM): {}", schedule.time)))?;
+    }
+    let mut config = state.config.lock().expect("reports lock");
+    let mut updated = ReportsConfig {
+        schedules,
+        last_runs: config.last_runs.clone(),
+    };
+    updated
+        .last_runs
+        .retain(|id, _| updated.schedules.iter().any(|schedule| schedule.id == *id));
+    store::save(&app, REPORTS_FILE, &updated)?;
+    *config = updated;
+    Ok(())
+}
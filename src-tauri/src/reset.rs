@@ -0,0 +1,193 @@
+//! Factory reset
+//!
+//! Returns a unit to its out-of-the-box state without re-imaging the SD
+//! card: settings, databases, secrets, operator accounts, caches and the
+//! walk-up users' files are wiped, the JSON documents bundled under
+//! `defaults/` are copied back in, and the kiosk reboots. An admin first asks
+//! for a confirmation token, which `factory_reset` must echo back within a
+//! couple of minutes, along with a second admin's approval when dual
+//! authorization covers it. Runs as a job with `factory-reset-progress` events.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State, Window};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::jobs::{self, JobHandle};
+use crate::vfs::{self, RootKind};
+use crate::{approvals, events, overlay};
+
+/// How long a confirmation token stays valid
+const TOKEN_LIFETIME: Duration = Duration::from_secs(120);
+
+/// Time for the frontend to show the final progress before the reboot
+const REBOOT_DELAY: Duration = Duration::from_secs(3);
+
+/// Bundled resource folder holding the default JSON documents
+const DEFAULTS_DIR: &str = "defaults";
+
+/// The outstanding confirmation token and when it was issued
+static PENDING: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResetToken {
+    pub token: String,
+    pub expires_in_secs: u64,
+}
+
+/// Payload of `factory-reset-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct ResetProgress {
+    pub step: String,
+    pub index: usize,
+    pub total: usize,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Delete everything inside `dir`, keeping the folder itself
+fn clear(dir: &Path) -> Result<(), String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let is_dir = fs::symlink_metadata(&path).map(|m| m.is_dir()).unwrap_or(false);
+        let removed = if is_dir { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        removed.map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Copy the bundled default documents into the app data directory
+fn restore_defaults(app: &AppHandle) -> Result<(), String> {
+    let Ok(dir) = app.path().resource_dir().map(|dir| dir.join(DEFAULTS_DIR)) else {
+        return Ok(());
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return Ok(());
+    };
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&data_dir).map_err(|e| e.to_string())?;
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(name) = path.file_name() {
+            fs::copy(&path, data_dir.join(name)).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+fn report(app: &AppHandle, job: &JobHandle, step: &str, index: usize, total: usize) -> Result<(), String> {
+    job.check()?;
+    job.progress(index as f64 / total as f64, Some(step.to_string()));
+    events::publish(
+        app,
+        "factory-reset-progress",
+        ResetProgress {
+            step: step.to_string(),
+            index,
+            total,
+        },
+    );
+    Ok(())
+}
+
+fn reset(app: &AppHandle, job: &JobHandle) -> Result<(), String> {
+    let resolver = app.path();
+    let user_files = vfs::roots(app)
+        .into_iter()
+        .filter(|root| matches!(root.info.kind, RootKind::Documents | RootKind::Downloads | RootKind::Trash))
+        .map(|root| root.path)
+        .collect();
+    let wipes: [(&str, Vec<PathBuf>); 4] = [
+        ("Settings, accounts and secrets", [resolver.app_data_dir(), resolver.app_config_dir()].into_iter().flatten().collect()),
+        ("Databases", resolver.app_local_data_dir().into_iter().collect()),
+        (
+            "Caches",
+            [resolver.app_cache_dir(), resolver.cache_dir().map(|dir| dir.join("thumbnails"))].into_iter().flatten().collect(),
+        ),
+        ("User files", user_files),
+    ];
+    // Wiping, then defaults, saving and restarting
+    let total = wipes.len() + 3;
+
+    for (index, (step, dirs)) in wipes.iter().enumerate() {
+        report(app, job, step, index, total)?;
+        dirs.iter().try_for_each(|dir| clear(dir))?;
+    }
+
+    report(app, job, "Defaults", wipes.len(), total)?;
+    restore_defaults(app)?;
+
+    // Under a read-only root the wipe would otherwise be undone by the reboot
+    report(app, job, "Saving to the SD card", wipes.len() + 1, total)?;
+    // persist always includes the settings folders
+    let wiped = wipes.into_iter().skip(1).flat_map(|(_, dirs)| dirs).collect();
+    overlay::persist(app, wiped)?;
+
+    report(app, job, "Restarting", total - 1, total)?;
+    std::thread::sleep(REBOOT_DELAY);
+    Command::new("systemctl")
+        .arg("reboot")
+        .status()
+        .map_err(|e| format!("Reboot failed: {}", e))?;
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Issue the token `factory_reset` must be called with (admin)
+#[tauri::command]
+pub fn request_factory_reset(auth: State<'_, AuthState>) -> Result<ResetToken, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let token = uuid::Uuid::new_v4().to_string();
+    *PENDING.lock().expect("reset token lock") = Some((token.clone(), Instant::now()));
+    Ok(ResetToken {
+        token,
+        expires_in_secs: TOKEN_LIFETIME.as_secs(),
+    })
+}
+
+/// Wipe the kiosk back to its defaults and reboot, as a job (admin; with
+/// dual authorization, a second admin's approval)
+#[tauri::command]
+pub fn factory_reset(
+    app: AppHandle,
+    window: Window,
+    auth: State<'_, AuthState>,
+    confirm_token: String,
+    approval_id: Option<String>,
+) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    // The token is checked first so a wrong one does not use up an approval;
+    // it is kept through a refused approval so the reset can be retried
+    let mut pending = PENDING.lock().expect("reset token lock");
+    let confirmed = pending
+        .as_ref()
+        .is_some_and(|(token, issued)| *token == confirm_token && issued.elapsed() < TOKEN_LIFETIME);
+    if !confirmed {
+        *pending = None;
+        return Err(KioskError::new(ErrorKind::Denied, "The confirmation token is wrong or has expired"));
+    }
+    approvals::authorize(&app, "factory_reset", approval_id.as_deref())?;
+    *pending = None;
+    drop(pending);
+
+    let worker = app.clone();
+    Ok(jobs::spawn(&app, Some(window.label()), "factory-reset", move |job| reset(&worker, job)))
+}
@@ -0,0 +1,428 @@
+//! Meeting room booking over CalDAV
+//!
+//! Syncs a room's CalDAV calendar into a local cache, reports free/busy slots
+//! within opening hours, and books slots by writing new events back to the
+//! server. Availability is served from the cache when the server is
+//! unreachable; bookings always require a live connection so two panels can't
+//! double-book the same slot.
+//!
+//! The room is set up by admins, and the stored password is dropped when
+//! the calendar URL or username changes.
+
+use base64::Engine;
+use chrono::{Duration, Local, NaiveTime, TimeZone};
+use quick_xml::events::Event;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::calendar::{self, CalendarEvent, EventInput};
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::http::ResponseExt;
+use crate::{events, http, keyring, store};
+
+const CONFIG_FILE: &str = "room_booking.json";
+
+/// Keyring entry holding the CalDAV password
+const PASSWORD_KEY: &str = "rooms/caldav";
+const CACHE_FILE: &str = "room_booking_cache.json";
+
+/// How often the calendar is re-synced in the background
+const SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Days of bookings fetched ahead of now
+const SYNC_DAYS_AHEAD: i64 = 30;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// CalDAV connection and opening hours for the room
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomConfig {
+    pub room_name: String,
+    /// Calendar collection URL, e.g. https://dav.example.com/calendars/room-1/
+    pub calendar_url: String,
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// Opening hours as "HH:MM"
+    pub open_time: String,
+    pub close_time: String,
+    pub slot_minutes: i64,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        RoomConfig {
+            room_name: "Meeting Room".to_string(),
+            calendar_url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            open_time: "08:00".to_string(),
+            close_time: "18:00".to_string(),
+            slot_minutes: 30,
+        }
+    }
+}
+
+/// A bookable time slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomSlot {
+    pub start: i64,
+    pub end: i64,
+    pub busy: bool,
+    /// Title of the booking occupying the slot
+    pub title: Option<String>,
+}
+
+/// Availability answer, flagged when served from a stale cache
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoomAvailability {
+    pub room_name: String,
+    pub slots: Vec<RoomSlot>,
+    pub last_synced: Option<i64>,
+    pub offline: bool,
+}
+
+/// Requested booking window
+#[derive(Debug, Deserialize)]
+pub struct SlotRequest {
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Booking details entered on the panel
+#[derive(Debug, Deserialize)]
+pub struct BookingDetails {
+    pub title: String,
+    #[serde(default)]
+    pub organizer: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RoomCache {
+    events: Vec<CalendarEvent>,
+    last_synced: Option<i64>,
+    last_error: Option<String>,
+}
+
+pub struct RoomState {
+    config: Mutex<RoomConfig>,
+    cache: Mutex<RoomCache>,
+}
+
+impl RoomState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut config: RoomConfig = store::load(app, CONFIG_FILE);
+        if config.password.is_empty() {
+            config.password = keyring::get(app, PASSWORD_KEY).ok().flatten().unwrap_or_default();
+        } else {
+            // Older versions kept the password in the config file
+            let _ = save_config(app, &config);
+        }
+        RoomState {
+            config: Mutex::new(config),
+            cache: Mutex::new(store::load(app, CACHE_FILE)),
+        }
+    }
+}
+
+/// Save the config with the password sealed in the keyring
+fn save_config(app: &AppHandle, config: &RoomConfig) -> Result<(), String> {
+    if !config.password.is_empty() {
+        keyring::put(app, PASSWORD_KEY, &config.password)?;
+    }
+    let stripped = RoomConfig {
+        password: String::new(),
+        ..config.clone()
+    };
+    store::save(app, CONFIG_FILE, &stripped)
+}
+
+// ============================================================================
+// CalDAV
+// ============================================================================
+
+fn authorization(config: &RoomConfig) -> String {
+    let credentials = format!("{}:{}", config.username, config.password);
+    format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials))
+}
+
+/// Extract the iCalendar payloads from a multistatus REPORT response
+fn calendar_data(xml: &str) -> Result<Vec<String>, String> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut inside = false;
+    let mut payloads = Vec::new();
+
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(start) if start.local_name().as_ref() == b"calendar-data" => {
+                inside = true;
+                payloads.push(String::new());
+            }
+            Event::End(end) if end.local_name().as_ref() == b"calendar-data" => inside = false,
+            Event::Text(text) if inside => {
+                if let Some(payload) = payloads.last_mut() {
+                    payload.push_str(&text.unescape().map_err(|e| e.to_string())?);
+                }
+            }
+            Event::CData(data) if inside => {
+                if let Some(payload) = payloads.last_mut() {
+                    payload.push_str(&String::from_utf8_lossy(&data.into_inner()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(payloads)
+}
+
+fn fetch_events(config: &RoomConfig) -> Result<Vec<CalendarEvent>, String> {
+    if config.calendar_url.is_empty() {
+        return Err("No room calendar configured".to_string());
+    }
+
+    let now = Local::now();
+    let format = |dt: chrono::DateTime<Local>| dt.with_timezone(&chrono::Utc).format("%Y%m%dT%H%M%SZ").to_string();
+    let body = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop><C:calendar-data/></D:prop>
+  <C:filter>
+    <C:comp-filter name="VCALENDAR">
+      <C:comp-filter name="VEVENT">
+        <C:time-range start="{}" end="{}"/>
+      </C:comp-filter>
+    </C:comp-filter>
+  </C:filter>
+</C:calendar-query>"#,
+        format(now - Duration::days(1)),
+        format(now + Duration::days(SYNC_DAYS_AHEAD)),
+    );
+
+    let response = http::agent()
+        .request("REPORT", &config.calendar_url)
+        .set("Authorization", &authorization(config))
+        .set("Depth", "1")
+        .set("Content-Type", "application/xml; charset=utf-8")
+        .send_string(&body)
+        .map_err(|e| format!("CalDAV sync failed: {}", e))?;
+    let xml = response.into_throttled_string().map_err(|e| e.to_string())?;
+
+    Ok(calendar_data(&xml)?
+        .iter()
+        .flat_map(|ics| calendar::parse_ics(ics))
+        .map(|(uid, fields)| CalendarEvent {
+            id: uid.clone(),
+            uid,
+            fields,
+        })
+        .collect())
+}
+
+fn sync(app: &AppHandle, state: &RoomState) -> Result<usize, String> {
+    let config = state.config.lock().expect("room config lock").clone();
+    let result = fetch_events(&config);
+
+    let mut cache = state.cache.lock().expect("room cache lock");
+    match &result {
+        Ok(events) => {
+            cache.events = events.clone();
+            cache.last_synced = Some(Local::now().timestamp());
+            cache.last_error = None;
+        }
+        Err(error) => cache.last_error = Some(error.clone()),
+    }
+    store::save(app, CACHE_FILE, &*cache)?;
+
+    let events = result?;
+    events::publish(app, "room-calendar-synced", cache.last_synced);
+    Ok(events.len())
+}
+
+/// Spawn the background loop that keeps the room cache fresh
+pub fn start_sync(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<RoomState>();
+        let configured = !state.config.lock().expect("room config lock").calendar_url.is_empty();
+        if configured {
+            let _ = sync(&app, &state);
+        }
+        std::thread::sleep(SYNC_INTERVAL);
+    });
+}
+
+fn parse_hour(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("Invalid time: {}", time))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the room configuration (password blanked)
+#[tauri::command]
+pub fn get_room_config(state: State<'_, RoomState>) -> RoomConfig {
+    RoomConfig {
+        password: String::new(),
+        ..state.config.lock().expect("room config lock").clone()
+    }
+}
+
+/// Update the room configuration (admin). An empty password keeps the
+/// stored one, unless the calendar URL or username changed.
+#[tauri::command]
+pub fn set_room_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, RoomState>,
+    mut config: RoomConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    parse_hour(&config.open_time)?;
+    parse_hour(&config.close_time)?;
+    if config.slot_minutes <= 0 {
+        return Err(KioskError::invalid("Slot length must be positive"));
+    }
+
+    let mut current = state.config.lock().expect("room config lock");
+    if config.password.is_empty() {
+        if config.calendar_url != current.calendar_url || config.username != current.username {
+            keyring::remove(&app, PASSWORD_KEY)?;
+        } else {
+            config.password = current.password.clone();
+        }
+    }
+    *current = config;
+    Ok(save_config(&app, &current)?)
+}
+
+/// Sync the room calendar now, returning the number of cached bookings
+#[tauri::command(async)]
+pub fn sync_room_calendar(app: AppHandle, state: State<'_, RoomState>) -> Result<usize, KioskError> {
+    Ok(sync(&app, &state)?)
+}
+
+/// Get slots within opening hours between `from` and `to`
+#[tauri::command]
+pub fn get_room_availability(state: State<'_, RoomState>, from: i64, to: i64) -> Result<RoomAvailability, KioskError> {
+    let config = state.config.lock().expect("room config lock").clone();
+    let cache = state.cache.lock().expect("room cache lock");
+    let open = parse_hour(&config.open_time)?;
+    let close = parse_hour(&config.close_time)?;
+    let slot = Duration::minutes(config.slot_minutes.max(1));
+
+    let bookings: Vec<calendar::EventOccurrence> = cache
+        .events
+        .iter()
+        .flat_map(|event| calendar::occurrences_in(event, from, to))
+        .filter(|occurrence| !occurrence.all_day)
+        .collect();
+
+    let mut slots = Vec::new();
+    let first_day = Local.timestamp_opt(from, 0).single().ok_or("Invalid range")?.date_naive();
+    let last_day = Local.timestamp_opt(to, 0).single().ok_or("Invalid range")?.date_naive();
+
+    for day in first_day.iter_days().take_while(|day| *day <= last_day) {
+        let (Some(day_open), Some(day_close)) = (
+            Local.from_local_datetime(&day.and_time(open)).earliest(),
+            Local.from_local_datetime(&day.and_time(close)).earliest(),
+        ) else {
+            continue;
+        };
+
+        let mut start = day_open;
+        while start + slot <= day_close {
+            let (slot_start, slot_end) = (start.timestamp(), (start + slot).timestamp());
+            if slot_start >= from && slot_end <= to {
+                let booking = bookings
+                    .iter()
+                    .find(|booking| booking.start < slot_end && booking.end > slot_start);
+                slots.push(RoomSlot {
+                    start: slot_start,
+                    end: slot_end,
+                    busy: booking.is_some(),
+                    title: booking.map(|booking| booking.title.clone()),
+                });
+            }
+            start += slot;
+        }
+    }
+
+    Ok(RoomAvailability {
+        room_name: config.room_name,
+        slots,
+        last_synced: cache.last_synced,
+        offline: cache.last_error.is_some(),
+    })
+}
+
+/// Book a slot by creating an event on the CalDAV server
+#[tauri::command(async)]
+pub fn book_slot(
+    app: AppHandle,
+    state: State<'_, RoomState>,
+    slot: SlotRequest,
+    details: BookingDetails,
+) -> Result<CalendarEvent, KioskError> {
+    if slot.end <= slot.start {
+        return Err(KioskError::invalid("Booking ends before it starts"));
+    }
+    if details.title.trim().is_empty() {
+        return Err(KioskError::invalid("Booking needs a title"));
+    }
+
+    // Refresh first so the conflict check sees other panels' bookings
+    sync(&app, &state)?;
+
+    let conflict = state
+        .cache
+        .lock()
+        .expect("room cache lock")
+        .events
+        .iter()
+        .flat_map(|event| calendar::occurrences_in(event, slot.start, slot.end))
+        .next();
+    if let Some(conflict) = conflict {
+        return Err(format!("Slot is already booked: {}", conflict.title).into());
+    }
+
+    let config = state.config.lock().expect("room config lock").clone();
+    let uid = format!("{}@kiosk", uuid::Uuid::new_v4());
+    let event = CalendarEvent {
+        id: uid.clone(),
+        uid: uid.clone(),
+        fields: EventInput {
+            title: details.title,
+            location: config.room_name.clone(),
+            description: if details.organizer.is_empty() {
+                String::new()
+            } else {
+                format!("Booked by {} at the room panel", details.organizer)
+            },
+            start: slot.start,
+            end: slot.end,
+            all_day: false,
+            recurrence: None,
+            reminder_minutes: None,
+        },
+    };
+
+    let url = format!("{}/{}.ics", config.calendar_url.trim_end_matches('/'), uid);
+    http::agent()
+        .put(&url)
+        .set("Authorization", &authorization(&config))
+        .set("Content-Type", "text/calendar; charset=utf-8")
+        .set("If-None-Match", "*")
+        .send_string(&calendar::to_ics(std::slice::from_ref(&event)))
+        .map_err(|e| format!("Booking failed: {}", e))?;
+
+    let mut cache = state.cache.lock().expect("room cache lock");
+    cache.events.push(event.clone());
+    store::save(&app, CACHE_FILE, &*cache)?;
+    Ok(event)
+}
@@ -0,0 +1,497 @@
+//! Event rules
+//!
+//! Operator-defined "when this happens, do that" rules kept in `rules.json`.
+//! Triggers match backend events (USB device inserted, temperature crossing a
+//! threshold, a GPIO input such as a door contact changing, an app crashing,
+//! or any event on the bus); actions call a webhook, show a notification, run
+//! a program or reboot the kiosk. A watcher thread samples USB, temperature
+//! and GPIO state and publishes the changes, including `usb-removed`.
+//!
+//! Rules are edited and tested by admins. Programs a rule may run are limited
+//! to an allow-list of absolute paths (`rule-scripts.json`), kept by admins
+//! and checked again each time a rule fires.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{config, events, http, mock, store};
+
+const RULES_FILE: &str = "rules.json";
+
+/// Programs `Script` actions may run
+const SCRIPTS_FILE: &str = "rule-scripts.json";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const USB_DEVICES_DIR: &str = "/sys/bus/usb/devices";
+const GPIO_DIR: &str = "/sys/class/gpio";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Trigger {
+    /// A USB device appeared, optionally matching vendor/product ids (hex)
+    UsbInserted {
+        #[serde(default)]
+        vendor_id: Option<String>,
+        #[serde(default)]
+        product_id: Option<String>,
+    },
+    /// A temperature sensor rose above the threshold
+    TemperatureAbove {
+        celsius: f32,
+        /// Substring of the sensor label; any sensor when absent
+        #[serde(default)]
+        sensor: Option<String>,
+    },
+    /// A GPIO input changed to the given value (sysfs numbering)
+    GpioChanged { pin: u32, value: u8 },
+    /// A supervised app exited abnormally
+    AppCrashed {
+        #[serde(default)]
+        name: Option<String>,
+    },
+    /// Any backend event by topic, optionally with a payload field equal to a value
+    Event {
+        topic: String,
+        /// Dot-separated path into the payload, e.g. "status.paper"
+        #[serde(default)]
+        field: Option<String>,
+        #[serde(default)]
+        equals: Option<Value>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Action {
+    /// POST the event as JSON
+    Webhook { url: String },
+    /// Show a notification on the kiosk (`rule-notification` event)
+    Notification { title: String, message: String },
+    /// Run an allow-listed program; the event is passed in RULE_EVENT as JSON
+    Script {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    Reboot,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: Trigger,
+    pub actions: Vec<Action>,
+    /// Minimum seconds between firings
+    #[serde(default)]
+    pub cooldown_secs: u64,
+}
+
+/// Payload of `rule-fired`
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleFired {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub topic: String,
+    pub payload: Value,
+    pub errors: Vec<String>,
+    pub fired_at: i64,
+}
+
+pub struct RulesState {
+    rules: Mutex<Vec<Rule>>,
+    scripts: Mutex<Vec<String>>,
+    last_fired: Mutex<HashMap<String, Instant>>,
+    /// (rule id, sensor) pairs currently above their threshold
+    above: Mutex<HashSet<(String, String)>>,
+}
+
+impl RulesState {
+    pub fn load(app: &AppHandle) -> Self {
+        RulesState {
+            rules: Mutex::new(store::load(app, RULES_FILE)),
+            scripts: Mutex::new(store::load(app, SCRIPTS_FILE)),
+            last_fired: Mutex::new(HashMap::new()),
+            above: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+fn lookup<'a>(payload: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(payload, |value, key| value.get(key))
+}
+
+fn same_id(expected: &Option<String>, actual: Option<&Value>) -> bool {
+    expected.as_ref().map_or(true, |expected| {
+        actual
+            .and_then(Value::as_str)
+            .is_some_and(|actual| actual.eq_ignore_ascii_case(expected))
+    })
+}
+
+fn matches(state: &RulesState, rule: &Rule, topic: &str, payload: &Value) -> bool {
+    match &rule.trigger {
+        Trigger::UsbInserted { vendor_id, product_id } => {
+            topic == "usb-inserted"
+                && same_id(vendor_id, payload.get("vendor_id"))
+                && same_id(product_id, payload.get("product_id"))
+        }
+        Trigger::TemperatureAbove { celsius, sensor } => {
+            if topic != "temperature" {
+                return false;
+            }
+            let label = payload.get("sensor").and_then(Value::as_str).unwrap_or_default();
+            if sensor.as_ref().is_some_and(|sensor| !label.contains(sensor.as_str())) {
+                return false;
+            }
+            let reading = payload.get("celsius").and_then(Value::as_f64).unwrap_or(f64::MIN);
+            // Fire on the upward crossing only
+            let key = (rule.id.clone(), label.to_string());
+            let mut above = state.above.lock().expect("rules threshold lock");
+            if reading > *celsius as f64 {
+                above.insert(key)
+            } else {
+                above.remove(&key);
+                false
+            }
+        }
+        Trigger::GpioChanged { pin, value } => {
+            topic == "gpio-changed"
+                && payload.get("pin").and_then(Value::as_u64) == Some(*pin as u64)
+                && payload.get("value").and_then(Value::as_u64) == Some(*value as u64)
+        }
+        Trigger::AppCrashed { name } => {
+            topic == "app-crashed"
+                && name.as_ref().map_or(true, |name| {
+                    payload.get("name").and_then(Value::as_str) == Some(name.as_str())
+                })
+        }
+        Trigger::Event {
+            topic: wanted,
+            field,
+            equals,
+        } => {
+            topic == wanted
+                && match (field, equals) {
+                    (Some(field), Some(equals)) => lookup(payload, field) == Some(equals),
+                    (Some(field), None) => lookup(payload, field).is_some(),
+                    _ => true,
+                }
+        }
+    }
+}
+
+// ============================================================================
+// Actions
+// ============================================================================
+
+fn script_allowed(app: &AppHandle, program: &str) -> bool {
+    let state = app.state::<RulesState>();
+    let scripts = state.scripts.lock().expect("rule scripts lock");
+    scripts.iter().any(|allowed| allowed == program)
+}
+
+fn run_action(app: &AppHandle, rule: &Rule, action: &Action, event: &Value) -> Result<(), String> {
+    match action {
+        Action::Webhook { url } => {
+            http::agent().post(url).send_json(event).map_err(|e| format!("Webhook {}: {}", url, e))?;
+        }
+        Action::Notification { title, message } => {
+            events::publish(app, "rule-notification", json!({ "rule": rule.name, "title": title, "message": message }));
+        }
+        Action::Script { program, args } => {
+            if !script_allowed(app, program) {
+                return Err(format!("{} is not an allowed rule program", program));
+            }
+            let status = Command::new(program)
+                .args(args)
+                .env("RULE_NAME", &rule.name)
+                .env("RULE_EVENT", event.to_string())
+                .status()
+                .map_err(|e| format!("{}: {}", program, e))?;
+            if !status.success() {
+                return Err(format!("{} exited with {}", program, status));
+            }
+        }
+        Action::Reboot => {
+            Command::new("systemctl")
+                .arg("reboot")
+                .status()
+                .map_err(|e| format!("Reboot failed: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+fn fire(app: &AppHandle, rule: Rule, topic: String, payload: Value) {
+    std::thread::spawn({
+        let app = app.clone();
+        move || {
+            let fired_at = Local::now().timestamp();
+            let event = json!({ "rule": rule.name, "topic": topic, "payload": payload, "time": fired_at });
+            let errors: Vec<String> = rule
+                .actions
+                .iter()
+                .filter_map(|action| run_action(&app, &rule, action, &event).err())
+                .collect();
+            events::publish(
+                &app,
+                "rule-fired",
+                RuleFired {
+                    rule_id: rule.id,
+                    rule_name: rule.name,
+                    topic,
+                    payload,
+                    errors,
+                    fired_at,
+                },
+            );
+        }
+    });
+}
+
+/// Evaluate all rules against an event. Actions run on their own thread.
+pub(crate) fn handle_event(app: &AppHandle, topic: &str, payload: &Value) {
+    let state = app.state::<RulesState>();
+    let rules = state.rules.lock().expect("rules lock").clone();
+    for rule in rules.into_iter().filter(|rule| rule.enabled) {
+        if !matches(&state, &rule, topic, payload) {
+            continue;
+        }
+        {
+            let mut last_fired = state.last_fired.lock().expect("rules fired lock");
+            let cooling = last_fired
+                .get(&rule.id)
+                .is_some_and(|at| at.elapsed() < Duration::from_secs(rule.cooldown_secs));
+            if cooling {
+                continue;
+            }
+            last_fired.insert(rule.id.clone(), Instant::now());
+        }
+        fire(app, rule, topic.to_string(), payload.clone());
+    }
+}
+
+// ============================================================================
+// Watcher
+// ============================================================================
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok().map(|text| text.trim().to_string())
+}
+
+/// USB devices by sysfs name, with their descriptor fields
+fn usb_devices() -> HashMap<String, Value> {
+    let Ok(entries) = fs::read_dir(USB_DEVICES_DIR) else { return HashMap::new() };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let dir = format!("{}/{}", USB_DEVICES_DIR, name);
+            let vendor_id = read_trimmed(&format!("{}/idVendor", dir))?;
+            let device = json!({
+                "path": name,
+                "vendor_id": vendor_id,
+                "product_id": read_trimmed(&format!("{}/idProduct", dir)),
+                "product": read_trimmed(&format!("{}/product", dir)),
+                "manufacturer": read_trimmed(&format!("{}/manufacturer", dir)),
+            });
+            Some((name, device))
+        })
+        .collect()
+}
+
+pub(crate) fn gpio_value(pin: u32) -> Option<u8> {
+    if mock::enabled() {
+        return mock::gpio(pin);
+    }
+    let path = format!("{}/gpio{}/value", GPIO_DIR, pin);
+    if fs::metadata(&path).is_err() {
+        // Export the pin on first use; needs gpio group membership
+        let _ = fs::write(format!("{}/export", GPIO_DIR), pin.to_string());
+    }
+    read_trimmed(&path)?.parse().ok()
+}
+
+/// Subscribe the rules to the event bus and spawn the hardware watcher
+pub fn start_rules(app: AppHandle) {
+    events::listen(&app, |app, event| handle_event(app, &event.topic, &event.payload));
+
+    std::thread::spawn(move || {
+        let mut known_usb = usb_devices();
+        let mut gpio: HashMap<u32, u8> = HashMap::new();
+        let mut components = sysinfo::Components::new();
+
+        loop {
+            std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+            let rules = app.state::<RulesState>().rules.lock().expect("rules lock").clone();
+            let enabled = || rules.iter().filter(|rule| rule.enabled);
+
+            let current_usb = usb_devices();
+            for (name, device) in &current_usb {
+                if !known_usb.contains_key(name) {
+                    events::publish(&app, "usb-inserted", device);
+                }
+            }
+            for (name, device) in &known_usb {
+                if !current_usb.contains_key(name) {
+                    events::publish(&app, "usb-removed", device);
+                }
+            }
+            known_usb = current_usb;
+
+            if enabled().any(|rule| matches!(rule.trigger, Trigger::TemperatureAbove { .. })) {
+                components.refresh_list();
+                // Readings go straight to the rules rather than flooding the bus history
+                for component in &components {
+                    let reading = json!({ "sensor": component.label(), "celsius": component.temperature() });
+                    handle_event(&app, "temperature", &reading);
+                }
+            }
+
+            let pins: HashSet<u32> = enabled()
+                .filter_map(|rule| match rule.trigger {
+                    Trigger::GpioChanged { pin, .. } => Some(pin),
+                    _ => None,
+                })
+                .collect();
+            gpio.retain(|pin, _| pins.contains(pin));
+            for pin in pins {
+                let Some(value) = gpio_value(pin) else { continue };
+                // The first reading is the baseline, not a change
+                if gpio.insert(pin, value).is_some_and(|previous| previous != value) {
+                    events::publish(&app, "gpio-changed", json!({ "pin": pin, "value": value }));
+                }
+            }
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List configured rules
+#[tauri::command]
+pub fn list_rules(state: State<'_, RulesState>) -> Vec<Rule> {
+    state.rules.lock().expect("rules lock").clone()
+}
+
+/// Create or update a rule, returning its id (admin)
+#[tauri::command]
+pub fn save_rule(
+    app: AppHandle,
+    state: State<'_, RulesState>,
+    auth: State<'_, AuthState>,
+    mut rule: Rule,
+) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if rule.name.trim().is_empty() {
+        return Err(KioskError::invalid("Rule name is required"));
+    }
+    if rule.actions.is_empty() {
+        return Err(KioskError::invalid("A rule needs at least one action"));
+    }
+    for action in &rule.actions {
+        if let Action::Script { program, .. } = action {
+            if !script_allowed(&app, program) {
+                let message = format!("{} is not on the list of programs rules may run", program);
+                return Err(KioskError::new(ErrorKind::Denied, message));
+            }
+        }
+    }
+
+    let mut rules = state.rules.lock().expect("rules lock");
+    match rules.iter_mut().find(|existing| existing.id == rule.id && !rule.id.is_empty()) {
+        Some(existing) => *existing = rule.clone(),
+        None => {
+            rule.id = uuid::Uuid::new_v4().to_string();
+            rules.push(rule.clone());
+        }
+    }
+    store::save(&app, RULES_FILE, &*rules)?;
+    Ok(rule.id)
+}
+
+/// Delete a rule (admin)
+#[tauri::command]
+pub fn delete_rule(
+    app: AppHandle,
+    state: State<'_, RulesState>,
+    auth: State<'_, AuthState>,
+    id: String,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut rules = state.rules.lock().expect("rules lock");
+    rules.retain(|rule| rule.id != id);
+    Ok(store::save(&app, RULES_FILE, &*rules)?)
+}
+
+/// Run a rule's actions now with a test payload, ignoring its trigger (admin)
+#[tauri::command]
+pub fn test_rule(
+    app: AppHandle,
+    state: State<'_, RulesState>,
+    auth: State<'_, AuthState>,
+    id: String,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let rule = state
+        .rules
+        .lock()
+        .expect("rules lock")
+        .iter()
+        .find(|rule| rule.id == id)
+        .cloned()
+        .ok_or("Rule not found")?;
+    fire(&app, rule, "test".to_string(), json!({ "test": true }));
+    Ok(())
+}
+
+/// Programs `Script` actions may run
+#[tauri::command]
+pub fn get_rule_scripts(state: State<'_, RulesState>) -> Vec<String> {
+    state.scripts.lock().expect("rule scripts lock").clone()
+}
+
+/// Replace the programs `Script` actions may run; each must be an absolute
+/// path to an existing file (admin)
+#[tauri::command]
+pub fn set_rule_scripts(
+    app: AppHandle,
+    state: State<'_, RulesState>,
+    auth: State<'_, AuthState>,
+    programs: Vec<String>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    for program in &programs {
+        let path = std::path::Path::new(program);
+        if !path.is_absolute() || !path.is_file() {
+            return Err(KioskError::invalid(format!("{} is not the absolute path of a program", program)));
+        }
+    }
+    let mut scripts = state.scripts.lock().expect("rule scripts lock");
+    store::save(&app, SCRIPTS_FILE, &programs)?;
+    *scripts = programs;
+    Ok(())
+}
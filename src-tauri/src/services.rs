@@ -0,0 +1,311 @@
+//! System services
+//!
+//! Lists and controls systemd service units over the system D-Bus for the
+//! "Services" admin applet. Anyone can look; starting and stopping needs a
+//! supervisor login and enabling or disabling an admin, and only units on
+//! the policy's allow-list (`services.json`) can be touched at all. The
+//! kiosk user also needs a polkit rule granting `org.freedesktop.systemd1.manage-units`.
+
+use dbus::arg::PropMap;
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::{Connection, Proxy};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{events, store};
+
+const POLICY_FILE: &str = "services.json";
+
+const SYSTEMD: &str = "org.freedesktop.systemd1";
+const SYSTEMD_PATH: &str = "/org/freedesktop/systemd1";
+const MANAGER: &str = "org.freedesktop.systemd1.Manager";
+const UNIT: &str = "org.freedesktop.systemd1.Unit";
+const SERVICE: &str = "org.freedesktop.systemd1.Service";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// Units the applet may control; a trailing `*` matches a prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServicePolicy {
+    pub manageable: Vec<String>,
+}
+
+impl Default for ServicePolicy {
+    fn default() -> Self {
+        ServicePolicy {
+            manageable: vec![
+                "ssh.service".to_string(),
+                "vncserver-x11-serviced.service".to_string(),
+                "wayvnc.service".to_string(),
+                "cups.service".to_string(),
+                "bluetooth.service".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub description: String,
+    /// loaded, not-found, masked...
+    pub load_state: String,
+    /// active, inactive, failed, activating...
+    pub active_state: String,
+    /// running, dead, exited...
+    pub sub_state: String,
+    pub manageable: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceStatus {
+    #[serde(flatten)]
+    pub info: ServiceInfo,
+    /// enabled, disabled, static, masked...
+    pub unit_file_state: String,
+    pub main_pid: Option<u32>,
+    /// Unix time the unit last became active
+    pub active_since: Option<i64>,
+}
+
+pub struct ServicesState(Mutex<ServicePolicy>);
+
+impl ServicesState {
+    pub fn load(app: &AppHandle) -> Self {
+        ServicesState(Mutex::new(store::load(app, POLICY_FILE)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+    Enable,
+    Disable,
+}
+
+/// Row of `ListUnits`: name, description, load, active, sub, following,
+/// unit path, job id, job type, job path
+type UnitRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    dbus::Path<'static>,
+    u32,
+    String,
+    dbus::Path<'static>,
+);
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn bus_error(e: dbus::Error) -> String {
+    e.message().unwrap_or("D-Bus error").to_string()
+}
+
+fn connect() -> Result<Connection, String> {
+    Connection::new_system().map_err(bus_error)
+}
+
+fn manager(connection: &Connection) -> Proxy<'_, &Connection> {
+    connection.with_proxy(SYSTEMD, SYSTEMD_PATH, TIMEOUT)
+}
+
+/// Accept `ssh` or `ssh.service`; reject anything that is not a unit name
+fn unit_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '_' | '.' | '@' | '-' | '\\'));
+    if !valid {
+        return Err(format!("Invalid service name: {}", name));
+    }
+    Ok(if name.ends_with(".service") {
+        name.to_string()
+    } else {
+        format!("{}.service", name)
+    })
+}
+
+fn is_manageable(policy: &ServicePolicy, name: &str) -> bool {
+    policy.manageable.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    })
+}
+
+/// Check the policy and the operator's role before changing a unit
+fn authorize(app: &AppHandle, name: &str, role: Role) -> Result<String, KioskError> {
+    let name = unit_name(name)?;
+    let state = app.state::<ServicesState>();
+    if !is_manageable(&state.0.lock().expect("service policy lock"), &name) {
+        return Err(KioskError::new(ErrorKind::Denied, format!("{} is not manageable", name)));
+    }
+    auth::require(&app.state::<AuthState>(), role)?;
+    Ok(name)
+}
+
+fn status(connection: &Connection, policy: &ServicePolicy, name: &str) -> Result<ServiceStatus, String> {
+    let manager = manager(connection);
+    let (path,): (dbus::Path<'static>,) = manager.method_call(MANAGER, "LoadUnit", (name,)).map_err(bus_error)?;
+    let unit = connection.with_proxy(SYSTEMD, path, TIMEOUT);
+    let properties: PropMap = unit.get_all(UNIT).map_err(bus_error)?;
+    let text = |key: &str| dbus::arg::prop_cast::<String>(&properties, key).cloned().unwrap_or_default();
+
+    let (unit_file_state,): (String,) = manager
+        .method_call(MANAGER, "GetUnitFileState", (name,))
+        .unwrap_or_else(|_| (String::new(),));
+    let main_pid = unit.get::<u32>(SERVICE, "MainPID").ok().filter(|pid| *pid != 0);
+    // Microseconds since the epoch, zero when never active
+    let active_since = dbus::arg::prop_cast::<u64>(&properties, "ActiveEnterTimestamp")
+        .filter(|usec| **usec != 0)
+        .map(|usec| (*usec / 1_000_000) as i64);
+
+    Ok(ServiceStatus {
+        info: ServiceInfo {
+            name: name.to_string(),
+            description: text("Description"),
+            load_state: text("LoadState"),
+            active_state: text("ActiveState"),
+            sub_state: text("SubState"),
+            manageable: is_manageable(policy, name),
+        },
+        unit_file_state,
+        main_pid,
+        active_since,
+    })
+}
+
+fn change(app: &AppHandle, name: &str, action: ServiceAction, role: Role) -> Result<(), KioskError> {
+    let name = authorize(app, name, role)?;
+    let connection = connect()?;
+    let manager = manager(&connection);
+    let method = match action {
+        ServiceAction::Start => "StartUnit",
+        ServiceAction::Stop => "StopUnit",
+        ServiceAction::Restart => "RestartUnit",
+        ServiceAction::Enable => "EnableUnitFiles",
+        ServiceAction::Disable => "DisableUnitFiles",
+    };
+    match action {
+        ServiceAction::Start | ServiceAction::Stop | ServiceAction::Restart => {
+            let _: (dbus::Path<'static>,) = manager.method_call(MANAGER, method, (&name, "replace")).map_err(bus_error)?;
+        }
+        ServiceAction::Enable => {
+            // Not runtime-only, no force
+            let _: (bool, Vec<(String, String, String)>) = manager
+                .method_call(MANAGER, method, (vec![name.as_str()], false, false))
+                .map_err(bus_error)?;
+        }
+        ServiceAction::Disable => {
+            let _: (Vec<(String, String, String)>,) = manager
+                .method_call(MANAGER, method, (vec![name.as_str()], false))
+                .map_err(bus_error)?;
+        }
+    }
+    if matches!(action, ServiceAction::Enable | ServiceAction::Disable) {
+        manager.method_call::<(), _, _, _>(MANAGER, "Reload", ()).map_err(bus_error)?;
+    }
+    events::publish(app, "service-changed", serde_json::json!({ "name": name, "action": action }));
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// All loaded service units, sorted by name
+#[tauri::command]
+pub fn list_services(state: State<'_, ServicesState>) -> Result<Vec<ServiceInfo>, KioskError> {
+    let connection = connect()?;
+    let (units,): (Vec<UnitRow>,) = manager(&connection).method_call(MANAGER, "ListUnits", ()).map_err(bus_error)?;
+    let policy = state.0.lock().expect("service policy lock");
+    let mut services: Vec<ServiceInfo> = units
+        .into_iter()
+        .filter(|unit| unit.0.ends_with(".service"))
+        .map(|(name, description, load_state, active_state, sub_state, ..)| ServiceInfo {
+            manageable: is_manageable(&policy, &name),
+            name,
+            description,
+            load_state,
+            active_state,
+            sub_state,
+        })
+        .collect();
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(services)
+}
+
+/// Detailed state of one service
+#[tauri::command]
+pub fn get_service_status(state: State<'_, ServicesState>, name: String) -> Result<ServiceStatus, KioskError> {
+    let name = unit_name(&name)?;
+    let connection = connect()?;
+    let policy = state.0.lock().expect("service policy lock").clone();
+    Ok(status(&connection, &policy, &name)?)
+}
+
+/// Start a service (supervisor)
+#[tauri::command]
+pub fn start_service(app: AppHandle, name: String) -> Result<(), KioskError> {
+    change(&app, &name, ServiceAction::Start, Role::Supervisor)
+}
+
+/// Stop a service (supervisor)
+#[tauri::command]
+pub fn stop_service(app: AppHandle, name: String) -> Result<(), KioskError> {
+    change(&app, &name, ServiceAction::Stop, Role::Supervisor)
+}
+
+/// Restart a service (supervisor)
+#[tauri::command]
+pub fn restart_service(app: AppHandle, name: String) -> Result<(), KioskError> {
+    change(&app, &name, ServiceAction::Restart, Role::Supervisor)
+}
+
+/// Enable a service at boot (admin)
+#[tauri::command]
+pub fn enable_service(app: AppHandle, name: String) -> Result<(), KioskError> {
+    change(&app, &name, ServiceAction::Enable, Role::Admin)
+}
+
+/// Stop a service starting at boot (admin)
+#[tauri::command]
+pub fn disable_service(app: AppHandle, name: String) -> Result<(), KioskError> {
+    change(&app, &name, ServiceAction::Disable, Role::Admin)
+}
+
+/// Get the list of manageable services
+#[tauri::command]
+pub fn get_service_policy(state: State<'_, ServicesState>) -> ServicePolicy {
+    state.0.lock().expect("service policy lock").clone()
+}
+
+/// Update the list of manageable services (admin)
+#[tauri::command]
+pub fn set_service_policy(
+    app: AppHandle,
+    state: State<'_, ServicesState>,
+    auth: State<'_, AuthState>,
+    policy: ServicePolicy,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    store::save(&app, POLICY_FILE, &policy)?;
+    *state.0.lock().expect("service policy lock") = policy;
+    Ok(())
+}
@@ -0,0 +1,246 @@
+//! Session reset
+//!
+//! Wipes everything a walk-up user may have left behind: the clipboard,
+//! webview browsing data, session temp files, recent documents, files in the
+//! folders walk-up users may write to, the signed-in operator, and network
+//! shares mounted since the kiosk started (shares already mounted at start
+//! belong to the system and stay). The frontend receives `session-reset` and
+//! drops its own form drafts. Runs on demand or automatically after the kiosk
+//! has been idle; the settings are for supervisors.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{attract, config, events, fetch, recents, store, vfs};
+
+const SESSION_FILE: &str = "session.json";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Filesystems treated as network shares
+const SHARE_FILESYSTEMS: &[&str] = &["cifs", "smb3", "nfs", "nfs4", "davfs", "fuse.sshfs", "fuse.rclone"];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Seconds of inactivity before an automatic reset; 0 disables it
+    pub idle_reset_secs: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig { idle_reset_secs: 300 }
+    }
+}
+
+/// Outcome of one reset step
+#[derive(Debug, Clone, Serialize)]
+pub struct ResetStep {
+    pub name: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// Result of a session reset, also the payload of `session-reset`
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionReset {
+    pub steps: Vec<ResetStep>,
+    /// True when triggered by the idle timer
+    pub automatic: bool,
+}
+
+pub struct SessionState {
+    config: Mutex<SessionConfig>,
+    /// Held for the duration of a reset; also records whether the current
+    /// idle period has already been wiped
+    reset_done: Mutex<bool>,
+    /// Mount points of shares that were already mounted at startup
+    system_shares: Vec<String>,
+}
+
+impl SessionState {
+    pub fn load(app: &AppHandle) -> Self {
+        SessionState {
+            config: Mutex::new(store::load(app, SESSION_FILE)),
+            reset_done: Mutex::new(false),
+            system_shares: mounted_shares().into_iter().map(|(mount_point, _)| mount_point).collect(),
+        }
+    }
+}
+
+// ============================================================================
+// Reset Steps
+// ============================================================================
+
+/// Scratch directory for per-session files; emptied on every reset
+pub fn session_temp_dir() -> PathBuf {
+    std::env::temp_dir().join("kiosk-session")
+}
+
+fn run_quiet(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+fn clear_clipboard() -> Result<(), String> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let cleared = if wayland {
+        run_quiet("wl-copy", &["--clear"]) && run_quiet("wl-copy", &["--primary", "--clear"])
+    } else {
+        run_quiet("xsel", &["--clipboard", "--clear"]) && run_quiet("xsel", &["--primary", "--clear"])
+    };
+    if cleared {
+        Ok(())
+    } else {
+        Err("No clipboard tool available (install wl-clipboard or xsel)".to_string())
+    }
+}
+
+fn clear_browsing_data(app: &AppHandle) -> Result<(), String> {
+    fetch::clear_browsing_data(app);
+    for webview in app.webview_windows().values() {
+        webview.clear_all_browsing_data().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn clear_temp_files() -> Result<(), String> {
+    let dir = session_temp_dir();
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    }
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())
+}
+
+/// Network shares mounted by this user, from /proc/mounts
+fn mounted_shares() -> Vec<(String, String)> {
+    let Ok(mounts) = fs::read_to_string("/proc/mounts") else { return Vec::new() };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (mount_point, fs_type) = (*fields.get(1)?, *fields.get(2)?);
+            SHARE_FILESYSTEMS
+                .contains(&fs_type)
+                // Octal escapes for spaces etc. in mount points
+                .then(|| (mount_point.replace("\\040", " ").replace("\\011", "\t"), fs_type.to_string()))
+        })
+        .collect()
+}
+
+fn unmount_shares(state: &SessionState) -> Result<(), String> {
+    let failed: Vec<String> = mounted_shares()
+        .into_iter()
+        .filter(|(mount_point, _)| !state.system_shares.contains(mount_point))
+        .filter(|(mount_point, fs_type)| {
+            let unmounted = if fs_type.starts_with("fuse.") {
+                run_quiet("fusermount", &["-u", mount_point])
+            } else {
+                run_quiet("umount", &[mount_point])
+            };
+            !unmounted
+        })
+        .map(|(mount_point, _)| mount_point)
+        .collect();
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Could not unmount {}", failed.join(", ")))
+    }
+}
+
+fn reset(app: &AppHandle, automatic: bool) -> SessionReset {
+    let steps = [
+        ("clipboard", clear_clipboard()),
+        ("browsing_data", clear_browsing_data(app)),
+        ("temp_files", clear_temp_files()),
+        ("recents", recents::clear_recents(app.clone(), app.state(), None).map_err(|e| e.to_string())),
+        ("public_files", vfs::clear_public_folders(app)),
+        ("shares", unmount_shares(&app.state::<SessionState>())),
+        ("operator", {
+            auth::sign_out(app);
+            Ok(())
+        }),
+    ]
+    .into_iter()
+    .map(|(name, result)| ResetStep {
+        name: name.to_string(),
+        ok: result.is_ok(),
+        error: result.err(),
+    })
+    .collect();
+
+    let result = SessionReset { steps, automatic };
+    // Frontend clears form drafts and returns home on this event
+    events::publish(app, "session-reset", &result);
+    result
+}
+
+/// Spawn the idle watcher that resets the session once per idle period
+pub fn start_session_reset(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<SessionState>();
+        let idle_reset_secs = state.config.lock().expect("session config lock").idle_reset_secs;
+        let idle = attract::idle_time(&app);
+
+        let mut reset_done = state.reset_done.lock().expect("session reset lock");
+        if idle_reset_secs == 0 || idle < Duration::from_secs(idle_reset_secs) {
+            *reset_done = false;
+        } else if !*reset_done {
+            reset(&app, true);
+            *reset_done = true;
+        }
+        drop(reset_done);
+        std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Wipe all per-user data now
+#[tauri::command]
+pub fn reset_session(app: AppHandle, state: State<'_, SessionState>) -> SessionReset {
+    let mut reset_done = state.reset_done.lock().expect("session reset lock");
+    let result = reset(&app, false);
+    *reset_done = true;
+    result
+}
+
+/// Get the session reset settings
+#[tauri::command]
+pub fn get_session_config(state: State<'_, SessionState>) -> SessionConfig {
+    state.config.lock().expect("session config lock").clone()
+}
+
+/// Update the session reset settings (supervisor)
+#[tauri::command]
+pub fn set_session_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, SessionState>,
+    config: SessionConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Supervisor)?;
+    store::save(&app, SESSION_FILE, &config)?;
+    *state.config.lock().expect("session config lock") = config;
+    Ok(())
+}
@@ -0,0 +1,744 @@
+//! SIP intercom
+//!
+//! Lets a help-point kiosk call the front desk with one tap, and take calls
+//! from it. The SIP and audio work is done by baresip, run in the
+//! background with a configuration written here and driven over its
+//! `ctrl_tcp` socket: netstring-framed JSON commands in, registration and
+//! call events out. The events keep a status that is published as
+//! `call-state` whenever it changes. baresip is restarted if it exits.
+//!
+//! The account's password is kept in the keyring. Visitors may only call
+//! the configured help desk; other addresses need an operator.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{events, keyring, store};
+
+const SIP_FILE: &str = "sip.json";
+
+const PASSWORD_KEY: &str = "sip-password";
+
+/// baresip's configuration directory, in the data directory
+const BARESIP_DIR: &str = "baresip";
+
+const CONTROL_ADDRESS: &str = "127.0.0.1:4444";
+
+/// Where distributions install baresip's modules
+const MODULE_DIRS: &[&str] = &[
+    "/usr/lib/baresip/modules",
+    "/usr/lib/x86_64-linux-gnu/baresip/modules",
+    "/usr/lib/aarch64-linux-gnu/baresip/modules",
+    "/usr/lib/arm-linux-gnueabihf/baresip/modules",
+    "/usr/local/lib/baresip/modules",
+];
+
+/// Attempts, a quarter second apart, at reaching a starting baresip
+const CONNECT_ATTEMPTS: u32 = 20;
+
+/// Wait before restarting baresip after it exited
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Longest control message accepted
+const MAX_MESSAGE: usize = 64 * 1024;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SipTransport {
+    Udp,
+    Tcp,
+    Tls,
+}
+
+impl SipTransport {
+    fn as_str(self) -> &'static str {
+        match self {
+            SipTransport::Udp => "udp",
+            SipTransport::Tcp => "tcp",
+            SipTransport::Tls => "tls",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SipAccount {
+    /// Address of record, e.g. `sip:helppoint-3@pbx.example.com`
+    pub uri: String,
+    /// Authentication user when it differs from the address's user
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Blank when read back; an empty password keeps the stored one
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub outbound_proxy: Option<String>,
+    pub transport: SipTransport,
+    /// Seconds between re-registrations
+    pub register_interval_secs: u32,
+    /// Answer incoming calls straight away, as an intercom does
+    #[serde(default)]
+    pub auto_answer: bool,
+}
+
+/// Which devices call audio uses, as ids from `list_audio_inputs` and
+/// `list_audio_outputs`; the defaults when unset
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CallAudio {
+    pub input: Option<String>,
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SipConfig {
+    pub account: Option<SipAccount>,
+    /// What `sip_call` dials with no address, and all a visitor may dial
+    pub help_desk_uri: String,
+    pub audio: CallAudio,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Registration {
+    /// No account, or baresip is not running
+    Unregistered,
+    Registering,
+    Registered,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CallDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CallPhase {
+    /// Incoming and not yet answered
+    Offered,
+    /// Outgoing and not yet ringing
+    Calling,
+    /// Outgoing and ringing at the other end
+    Ringing,
+    Established,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallInfo {
+    pub id: String,
+    pub peer: String,
+    pub direction: CallDirection,
+    pub phase: CallPhase,
+    pub started_at: i64,
+    pub answered_at: Option<i64>,
+}
+
+/// Payload of `call-state`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SipStatus {
+    pub registration: Registration,
+    pub account: Option<String>,
+    pub call: Option<CallInfo>,
+    /// Why the last call ended, e.g. "486 Busy Here"
+    pub last_call_ended: Option<String>,
+    pub error: Option<String>,
+}
+
+impl Default for SipStatus {
+    fn default() -> Self {
+        SipStatus {
+            registration: Registration::Unregistered,
+            account: None,
+            call: None,
+            last_call_ended: None,
+            error: None,
+        }
+    }
+}
+
+pub struct SipState {
+    config: Mutex<SipConfig>,
+    status: Mutex<SipStatus>,
+    baresip: Mutex<Option<Child>>,
+    control: Mutex<Option<TcpStream>>,
+    /// Bumped on every restart so an old supervisor stops
+    generation: AtomicU64,
+    next_token: AtomicU64,
+}
+
+impl SipState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut config: SipConfig = store::load(app, SIP_FILE);
+        if let Some(account) = config.account.as_mut() {
+            account.password = keyring::get(app, PASSWORD_KEY).ok().flatten().unwrap_or_default();
+        }
+        SipState {
+            config: Mutex::new(config),
+            status: Mutex::new(SipStatus::default()),
+            baresip: Mutex::new(None),
+            control: Mutex::new(None),
+            generation: AtomicU64::new(0),
+            next_token: AtomicU64::new(0),
+        }
+    }
+}
+
+// ============================================================================
+// baresip
+// ============================================================================
+
+/// A device id as baresip's `<module>,<device>`
+fn audio_device(id: Option<&str>) -> String {
+    match id.and_then(|id| id.split_once(':')) {
+        Some((module, device)) => format!("{},{}", module, device),
+        None => "pulse,default".to_string(),
+    }
+}
+
+/// The baresip module a device id needs
+fn audio_driver(id: Option<&str>) -> &str {
+    id.and_then(|id| id.split_once(':'))
+        .map_or("pulse", |(module, _)| module)
+}
+
+fn baresip_config(audio: &CallAudio) -> String {
+    let mut config = String::new();
+    if let Some(dir) = MODULE_DIRS
+        .iter()
+        .find(|dir| Path::new(dir).join("ctrl_tcp.so").exists())
+    {
+        config.push_str(&format!("module_path\t\t{}\n", dir));
+    }
+    let output = audio_device(audio.output.as_deref());
+    config.push_str(&format!("audio_player\t\t{}\n", output));
+    config.push_str(&format!("audio_alert\t\t{}\n", output));
+    config.push_str(&format!("audio_source\t\t{}\n", audio_device(audio.input.as_deref())));
+    let mut drivers = vec![
+        audio_driver(audio.input.as_deref()),
+        audio_driver(audio.output.as_deref()),
+    ];
+    drivers.dedup();
+    for module in drivers
+        .iter()
+        .map(|driver| format!("{}.so", driver))
+        .chain(["g711.so".to_string()])
+    {
+        config.push_str(&format!("module\t\t\t{}\n", module));
+    }
+    for module in ["account.so", "menu.so", "ctrl_tcp.so"] {
+        config.push_str(&format!("module_app\t\t{}\n", module));
+    }
+    config.push_str(&format!("ctrl_tcp_listen\t\t{}\n", CONTROL_ADDRESS));
+    config
+}
+
+/// The account as a line of baresip's `accounts` file
+fn account_line(account: &SipAccount) -> String {
+    let mut address = account.uri.clone();
+    if account.transport != SipTransport::Udp {
+        address.push_str(&format!(";transport={}", account.transport.as_str()));
+    }
+    let mut line = match &account.display_name {
+        Some(name) if !name.is_empty() => format!("\"{}\" <{}>", name, address),
+        _ => format!("<{}>", address),
+    };
+    if let Some(username) = account.username.as_deref().filter(|username| !username.is_empty()) {
+        line.push_str(&format!(";auth_user={}", username));
+    }
+    if !account.password.is_empty() {
+        line.push_str(&format!(";auth_pass={}", account.password));
+    }
+    if let Some(proxy) = account.outbound_proxy.as_deref().filter(|proxy| !proxy.is_empty()) {
+        line.push_str(&format!(";outbound=\"{};lr\"", proxy));
+    }
+    line.push_str(&format!(";regint={}", account.register_interval_secs));
+    line.push_str(if account.auto_answer {
+        ";answermode=auto"
+    } else {
+        ";answermode=manual"
+    });
+    line
+}
+
+fn write_files(dir: &Path, config: &SipConfig, account: &SipAccount) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    // The accounts file holds the password
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700)).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let write = |name: &str, contents: String| {
+        let path = dir.join(name);
+        fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    write("config", baresip_config(&config.audio))?;
+    write("accounts", format!("{}\n", account_line(account)))
+}
+
+/// Save the settings, without the password
+fn save(app: &AppHandle, config: &SipConfig) -> Result<(), String> {
+    let mut saved = config.clone();
+    if let Some(account) = saved.account.as_mut() {
+        account.password = String::new();
+    }
+    store::save(app, SIP_FILE, &saved)
+}
+
+/// One netstring, `<length>:<bytes>,`
+fn read_netstring(reader: &mut impl BufRead) -> io::Result<Vec<u8>> {
+    let mut length = Vec::new();
+    reader.read_until(b':', &mut length)?;
+    if length.pop() != Some(b':') {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    let length: usize = String::from_utf8_lossy(&length)
+        .trim()
+        .parse()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Bad netstring length"))?;
+    if length > MAX_MESSAGE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Control message too long"));
+    }
+    let mut message = vec![0u8; length + 1];
+    reader.read_exact(&mut message)?;
+    if message.pop() != Some(b',') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Bad netstring end"));
+    }
+    Ok(message)
+}
+
+/// Bring the status up to date with a baresip event; returns whether it
+/// changed
+fn apply_event(status: &mut SipStatus, event: &Value) -> bool {
+    let before = status.clone();
+    let text = |key: &str| event.get(key).and_then(Value::as_str).unwrap_or("").to_string();
+    let kind = text("type");
+    let now = Local::now().timestamp();
+    match kind.as_str() {
+        "REGISTERING" => status.registration = Registration::Registering,
+        "REGISTER_OK" => {
+            status.registration = Registration::Registered;
+            status.error = None;
+        }
+        "REGISTER_FAIL" => {
+            status.registration = Registration::Failed;
+            status.error = Some(format!("Registration failed: {}", text("param")));
+        }
+        "UNREGISTERING" => status.registration = Registration::Unregistered,
+        "CALL_INCOMING" | "CALL_OUTGOING" if status.call.is_none() => {
+            let incoming = kind == "CALL_INCOMING";
+            status.call = Some(CallInfo {
+                id: text("id"),
+                peer: text("peeruri"),
+                direction: if incoming {
+                    CallDirection::Incoming
+                } else {
+                    CallDirection::Outgoing
+                },
+                phase: if incoming {
+                    CallPhase::Offered
+                } else {
+                    CallPhase::Calling
+                },
+                started_at: now,
+                answered_at: None,
+            });
+        }
+        "CALL_RINGING" | "CALL_PROGRESS" | "CALL_ESTABLISHED" | "CALL_CLOSED" => {
+            let id = text("id");
+            let Some(call) = status.call.as_mut().filter(|call| call.id == id || id.is_empty()) else {
+                return false;
+            };
+            match kind.as_str() {
+                "CALL_CLOSED" => {
+                    let reason = text("param");
+                    status.last_call_ended = Some(if reason.is_empty() {
+                        "Call ended".to_string()
+                    } else {
+                        reason
+                    });
+                    status.call = None;
+                }
+                "CALL_ESTABLISHED" => {
+                    call.phase = CallPhase::Established;
+                    call.answered_at = Some(now);
+                }
+                _ if call.direction == CallDirection::Outgoing => call.phase = CallPhase::Ringing,
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+    *status != before
+}
+
+fn publish_status(app: &AppHandle, status: &SipStatus) {
+    events::publish(app, "call-state", status);
+}
+
+fn set_status(app: &AppHandle, change: impl FnOnce(&mut SipStatus)) {
+    let state = app.state::<SipState>();
+    let mut status = state.status.lock().expect("sip status lock");
+    let before = status.clone();
+    change(&mut status);
+    if *status != before {
+        publish_status(app, &status);
+    }
+}
+
+fn frame(command: &str, params: &str, token: u64) -> String {
+    let message = json!({ "command": command, "params": params, "token": token.to_string() }).to_string();
+    format!("{}:{},", message.len(), message)
+}
+
+/// Ask a baresip left over from an earlier run to quit, so the new one
+/// gets the control port
+fn quit_stale() {
+    if let Ok(mut stream) = TcpStream::connect(CONTROL_ADDRESS) {
+        if stream.write_all(frame("quit", "", 0).as_bytes()).is_ok() {
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+}
+
+fn connect() -> Result<TcpStream, String> {
+    let mut last_error = String::new();
+    for _ in 0..CONNECT_ATTEMPTS {
+        match TcpStream::connect(CONTROL_ADDRESS) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = e.to_string(),
+        }
+        std::thread::sleep(Duration::from_millis(250));
+    }
+    Err(format!("baresip's control socket did not open: {}", last_error))
+}
+
+/// Run baresip and follow its events until it exits
+fn run_baresip(app: &AppHandle, state: &SipState, config: &SipConfig, account: &SipAccount) -> Result<(), String> {
+    let dir = store::data_path(app, BARESIP_DIR)?;
+    write_files(&dir, config, account)?;
+    quit_stale();
+    let child = Command::new("baresip")
+        .arg("-f")
+        .arg(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run baresip: {}", e))?;
+    *state.baresip.lock().expect("baresip lock") = Some(child);
+
+    let stream = connect()?;
+    *state.control.lock().expect("sip control lock") = Some(stream.try_clone().map_err(|e| e.to_string())?);
+    set_status(app, |status| {
+        status.registration = Registration::Registering;
+        status.account = Some(account.uri.clone());
+        status.error = None;
+    });
+    let mut reader = BufReader::new(stream);
+    while let Ok(message) = read_netstring(&mut reader) {
+        let Ok(event) = serde_json::from_slice::<Value>(&message) else {
+            continue;
+        };
+        if event.get("event").and_then(Value::as_bool) != Some(true) {
+            continue;
+        }
+        let mut status = state.status.lock().expect("sip status lock");
+        if apply_event(&mut status, &event) {
+            publish_status(app, &status);
+        }
+    }
+    Err("baresip exited".to_string())
+}
+
+fn stop_baresip(state: &SipState) {
+    state.control.lock().expect("sip control lock").take();
+    if let Some(mut child) = state.baresip.lock().expect("baresip lock").take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Keep baresip running for the configured account until the next restart
+fn supervise(app: AppHandle, generation: u64) {
+    let state = app.state::<SipState>();
+    let current = || state.generation.load(Ordering::SeqCst) == generation;
+    while current() {
+        let config = state.config.lock().expect("sip config lock").clone();
+        let Some(account) = config.account.clone() else { return };
+        let result = run_baresip(&app, &state, &config, &account);
+        if !current() {
+            return;
+        }
+        stop_baresip(&state);
+        set_status(&app, |status| {
+            status.registration = Registration::Unregistered;
+            status.call = None;
+            status.error = result.err();
+        });
+        std::thread::sleep(RETRY_DELAY);
+    }
+}
+
+/// Stop baresip and, with an account configured, start it afresh
+fn restart(app: &AppHandle, state: &SipState) {
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    stop_baresip(state);
+    let has_account = state.config.lock().expect("sip config lock").account.is_some();
+    set_status(app, |status| *status = SipStatus::default());
+    if has_account {
+        let app = app.clone();
+        std::thread::spawn(move || supervise(app, generation));
+    }
+}
+
+/// Register the saved account in the background
+pub fn start_sip(app: AppHandle) {
+    let state = app.state::<SipState>();
+    restart(&app, &state);
+}
+
+fn send(state: &SipState, command: &str, params: &str) -> Result<(), String> {
+    let mut control = state.control.lock().expect("sip control lock");
+    let stream = control.as_mut().ok_or("The intercom is not running")?;
+    let token = state.next_token.fetch_add(1, Ordering::SeqCst);
+    stream
+        .write_all(frame(command, params, token).as_bytes())
+        .map_err(|e| format!("Failed to reach baresip: {}", e))
+}
+
+/// A SIP address, with `sip:` added to a bare `user@host`
+fn normalize_uri(uri: &str) -> Result<String, String> {
+    let uri = uri.trim();
+    let uri = if uri.starts_with("sip:") || uri.starts_with("sips:") {
+        uri.to_string()
+    } else {
+        format!("sip:{}", uri)
+    };
+    let host = uri
+        .split_once(':')
+        .map_or("", |(_, rest)| rest)
+        .rsplit('@')
+        .next()
+        .unwrap_or("");
+    let bad = |c: char| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>' | '"');
+    if host.is_empty() || uri.chars().any(bad) {
+        return Err(format!("Not a SIP address: {}", uri));
+    }
+    Ok(uri)
+}
+
+fn validate(account: &SipAccount) -> Result<(), String> {
+    normalize_uri(&account.uri)?;
+    let plain = |value: &str| {
+        !value
+            .chars()
+            .any(|c| c.is_control() || matches!(c, ';' | '"' | '<' | '>'))
+    };
+    let fields = [
+        account.username.as_deref(),
+        account.display_name.as_deref(),
+        account.outbound_proxy.as_deref(),
+    ];
+    if !fields.iter().flatten().all(|value| plain(value)) || !plain(&account.password) {
+        return Err("Account fields cannot contain ; \" < > or control characters".into());
+    }
+    if !(60..=86400).contains(&account.register_interval_secs) {
+        return Err("Re-registration must be every 60-86400 seconds".into());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// The intercom settings (password blanked)
+#[tauri::command]
+pub fn get_sip_config(state: State<'_, SipState>) -> SipConfig {
+    let mut config = state.config.lock().expect("sip config lock").clone();
+    if let Some(account) = config.account.as_mut() {
+        account.password = String::new();
+    }
+    config
+}
+
+/// Registration and the current call
+#[tauri::command]
+pub fn get_sip_status(state: State<'_, SipState>) -> SipStatus {
+    state.status.lock().expect("sip status lock").clone()
+}
+
+/// Register with an account, replacing any other (admin)
+#[tauri::command]
+pub fn sip_register(
+    app: AppHandle,
+    state: State<'_, SipState>,
+    auth: State<'_, AuthState>,
+    mut account: SipAccount,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    account.uri = normalize_uri(&account.uri).map_err(KioskError::invalid)?;
+    validate(&account).map_err(KioskError::invalid)?;
+    {
+        let mut config = state.config.lock().expect("sip config lock");
+        if account.password.is_empty() {
+            account.password = config
+                .account
+                .as_ref()
+                .map(|current| current.password.clone())
+                .unwrap_or_default();
+        } else {
+            keyring::put(&app, PASSWORD_KEY, &account.password)?;
+        }
+        let mut updated = config.clone();
+        updated.account = Some(account);
+        save(&app, &updated)?;
+        *config = updated;
+    }
+    restart(&app, &state);
+    Ok(())
+}
+
+/// Forget the account and stop the intercom (admin)
+#[tauri::command]
+pub fn sip_unregister(
+    app: AppHandle,
+    state: State<'_, SipState>,
+    auth: State<'_, AuthState>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    {
+        let mut config = state.config.lock().expect("sip config lock");
+        let mut updated = config.clone();
+        updated.account = None;
+        save(&app, &updated)?;
+        *config = updated;
+    }
+    keyring::remove(&app, PASSWORD_KEY)?;
+    restart(&app, &state);
+    Ok(())
+}
+
+/// Set the address the help button calls (admin)
+#[tauri::command]
+pub fn set_help_desk(
+    app: AppHandle,
+    state: State<'_, SipState>,
+    auth: State<'_, AuthState>,
+    uri: String,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let uri = normalize_uri(&uri).map_err(KioskError::invalid)?;
+    let mut config = state.config.lock().expect("sip config lock");
+    let mut updated = config.clone();
+    updated.help_desk_uri = uri;
+    save(&app, &updated)?;
+    *config = updated;
+    Ok(())
+}
+
+/// Route call audio to a microphone and speaker; takes effect on the next
+/// call
+#[tauri::command]
+pub fn set_call_audio(
+    app: AppHandle,
+    state: State<'_, SipState>,
+    auth: State<'_, AuthState>,
+    audio: CallAudio,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    let known = |id: &Option<String>| {
+        id.as_deref().map_or(true, |id| {
+            let (module, device) = id.split_once(':').unwrap_or(("", ""));
+            matches!(module, "pulse" | "alsa") && !device.is_empty() && !device.chars().any(char::is_whitespace)
+        })
+    };
+    if !known(&audio.input) || !known(&audio.output) {
+        return Err(KioskError::invalid("Audio devices must be ids from the device lists"));
+    }
+    {
+        let mut config = state.config.lock().expect("sip config lock");
+        let mut updated = config.clone();
+        updated.audio = audio;
+        save(&app, &updated)?;
+        *config = updated;
+    }
+    // baresip reads the devices at start-up; switch the running one too
+    let audio = state.config.lock().expect("sip config lock").audio.clone();
+    let _ = send(&state, "ausrc", &audio_device(audio.input.as_deref()));
+    let _ = send(&state, "auplay", &audio_device(audio.output.as_deref()));
+    Ok(())
+}
+
+/// Call an address, or the help desk when none is given. Visitors may
+/// only call the help desk.
+#[tauri::command]
+pub fn sip_call(state: State<'_, SipState>, auth: State<'_, AuthState>, uri: Option<String>) -> Result<(), KioskError> {
+    let help_desk = state.config.lock().expect("sip config lock").help_desk_uri.clone();
+    let uri = match uri {
+        Some(uri) => normalize_uri(&uri).map_err(KioskError::invalid)?,
+        None if help_desk.is_empty() => return Err(KioskError::invalid("No help desk is configured")),
+        None => help_desk.clone(),
+    };
+    if uri != help_desk {
+        auth::require(&auth, Role::Operator)?;
+    }
+    {
+        let status = state.status.lock().expect("sip status lock");
+        if status.registration != Registration::Registered {
+            return Err(KioskError::new(ErrorKind::Failed, "The intercom is not registered"));
+        }
+        if status.call.is_some() {
+            return Err(KioskError::invalid("A call is already in progress"));
+        }
+    }
+    send(&state, "dial", &uri)?;
+    Ok(())
+}
+
+/// Answer the incoming call
+#[tauri::command]
+pub fn sip_answer(state: State<'_, SipState>) -> Result<(), KioskError> {
+    let offered = state
+        .status
+        .lock()
+        .expect("sip status lock")
+        .call
+        .as_ref()
+        .is_some_and(|call| call.phase == CallPhase::Offered);
+    if !offered {
+        return Err(KioskError::invalid("No call is waiting to be answered"));
+    }
+    send(&state, "accept", "")?;
+    Ok(())
+}
+
+/// End the current call, or turn down an incoming one
+#[tauri::command]
+pub fn sip_hangup(state: State<'_, SipState>) -> Result<(), KioskError> {
+    if state.status.lock().expect("sip status lock").call.is_none() {
+        return Err(KioskError::invalid("No call is in progress"));
+    }
+    send(&state, "hangup", "")?;
+    Ok(())
+}
@@ -0,0 +1,754 @@
+//! Klondike solitaire
+//!
+//! The deal and every move live in the backend: the frontend sends moves,
+//! the backend checks them against the rules and answers with what the
+//! player may see. Deals come from a seed, so a seed replays the same game.
+//! With `solvable` deals are tried until a bounded search finds a way to
+//! win, up to a fixed number of deals; `guaranteed` says whether one was.
+//!
+//! Scoring follows the Windows rules: 5 for a card from the waste to the
+//! tableau or for turning a card over, 10 for a card onto a foundation,
+//! minus 15 for one taken back off, and minus 100 (draw one) or 20 (draw
+//! three) for going through the stock again, never below zero. A win adds
+//! a time bonus of 700000 / seconds once 30 seconds have passed and goes
+//! on the leaderboard. Undo puts back the board and score before the move.
+//!
+//! Cards are written rank then suit: `AC`, `10H`, `QS`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, State};
+
+use crate::auth::AuthState;
+use crate::error::KioskError;
+use crate::games::{self, Rng, ScoreOrder, ScoreResult};
+
+const GAME: &str = "solitaire";
+
+/// Games kept in memory; the least recently played goes first
+const MAX_GAMES: usize = 8;
+
+const MAX_UNDO: usize = 500;
+
+/// Deals tried for a game that can be won
+const SOLVABLE_ATTEMPTS: usize = 30;
+
+/// Positions the search may visit for one deal
+const SOLVER_NODES: usize = 50_000;
+
+const SUITS: [char; 4] = ['C', 'D', 'H', 'S'];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SolitaireOptions {
+    /// Cards turned per draw, 1 or 3 (default 1)
+    pub draw: Option<u8>,
+    /// Replays a deal; random when unset
+    pub seed: Option<u32>,
+    /// Deal a game that can be won
+    pub solvable: bool,
+    pub player: Option<String>,
+}
+
+/// A move; piles are 0-6 from the left, suits are `C`, `D`, `H` or `S`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SolitaireMove {
+    /// Turn cards from the stock, or the waste back over when it is empty
+    Draw,
+    WasteToTableau { pile: usize },
+    WasteToFoundation,
+    TableauToFoundation { pile: usize },
+    /// Move the top `count` face-up cards of a pile
+    TableauToTableau { from: usize, to: usize, count: usize },
+    FoundationToTableau { suit: char, pile: usize },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableauPile {
+    /// Face-down cards under the face-up ones
+    pub hidden: usize,
+    /// Face-up cards, bottom first
+    pub cards: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SolitaireView {
+    pub id: String,
+    pub draw: u8,
+    pub seed: u32,
+    /// Cards left in the stock
+    pub stock: usize,
+    /// Up to the last `draw` cards turned, the playable one last
+    pub waste: Vec<String>,
+    pub waste_count: usize,
+    /// Top card of each foundation, in `C`, `D`, `H`, `S` order
+    pub foundations: Vec<Option<String>>,
+    pub tableau: Vec<TableauPile>,
+    pub score: i64,
+    pub moves: u32,
+    pub can_undo: bool,
+    pub won: bool,
+    pub elapsed_secs: f64,
+    /// Whether the deal was checked to be winnable
+    pub guaranteed: bool,
+    /// Where a won game landed on the leaderboard
+    pub result: Option<ScoreResult>,
+}
+
+/// A card is 0-51: suit `card / 13` in `SUITS` order, rank `card % 13 + 1`
+type Card = u8;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Pile {
+    cards: Vec<Card>,
+    hidden: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Board {
+    /// Top card last
+    stock: Vec<Card>,
+    waste: Vec<Card>,
+    /// Cards on each suit's foundation, which is also its top rank
+    foundations: [u8; 4],
+    tableau: [Pile; 7],
+}
+
+struct Game {
+    draw: u8,
+    seed: u32,
+    guaranteed: bool,
+    player: String,
+    board: Board,
+    score: i64,
+    moves: u32,
+    history: Vec<(Board, i64)>,
+    started: Instant,
+    finished_secs: Option<f64>,
+    result: Option<ScoreResult>,
+    last_played: Instant,
+}
+
+#[derive(Default)]
+pub struct SolitaireState(Mutex<HashMap<String, Game>>);
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+fn suit(card: Card) -> usize {
+    (card / 13) as usize
+}
+
+fn rank(card: Card) -> u8 {
+    card % 13 + 1
+}
+
+fn red(card: Card) -> bool {
+    matches!(suit(card), 1 | 2)
+}
+
+fn card_name(card: Card) -> String {
+    let rank = match rank(card) {
+        1 => "A".to_string(),
+        11 => "J".to_string(),
+        12 => "Q".to_string(),
+        13 => "K".to_string(),
+        n => n.to_string(),
+    };
+    format!("{}{}", rank, SUITS[suit(card)])
+}
+
+impl Pile {
+    fn top(&self) -> Option<Card> {
+        self.cards.last().copied()
+    }
+
+    /// Whether `card` may go on this pile
+    fn takes(&self, card: Card) -> bool {
+        match self.top() {
+            Some(top) => rank(top) == rank(card) + 1 && red(top) != red(card),
+            None => rank(card) == 13,
+        }
+    }
+
+    fn face_up(&self) -> usize {
+        self.cards.len() - self.hidden
+    }
+
+    /// Turn the top card over if it is face down; true if one was
+    fn turn_over(&mut self) -> bool {
+        if self.hidden > 0 && self.hidden == self.cards.len() {
+            self.hidden -= 1;
+            return true;
+        }
+        false
+    }
+}
+
+impl Board {
+    fn deal(seed: u32) -> Self {
+        let mut deck: Vec<Card> = (0..52).collect();
+        Rng::new(seed).shuffle(&mut deck);
+        let tableau = std::array::from_fn(|i| {
+            let cards: Vec<Card> = deck.drain(deck.len() - (i + 1)..).collect();
+            Pile { cards, hidden: i }
+        });
+        Board {
+            stock: deck,
+            waste: Vec::new(),
+            foundations: [0; 4],
+            tableau,
+        }
+    }
+
+    fn won(&self) -> bool {
+        self.foundations.iter().all(|&count| count == 13)
+    }
+
+    fn fits_foundation(&self, card: Card) -> bool {
+        self.foundations[suit(card)] + 1 == rank(card)
+    }
+
+    fn pile(&mut self, pile: usize) -> Result<&mut Pile, String> {
+        self.tableau.get_mut(pile).ok_or_else(|| format!("There is no pile {}", pile))
+    }
+
+    /// Play a move and return the points it scores
+    fn apply(&mut self, play: &SolitaireMove, draw: u8) -> Result<i64, String> {
+        match *play {
+            SolitaireMove::Draw => {
+                if self.stock.is_empty() {
+                    if self.waste.is_empty() {
+                        return Err("The stock and waste are empty".into());
+                    }
+                    self.stock = self.waste.drain(..).rev().collect();
+                    return Ok(if draw == 1 { -100 } else { -20 });
+                }
+                for _ in 0..draw {
+                    let Some(card) = self.stock.pop() else { break };
+                    self.waste.push(card);
+                }
+                Ok(0)
+            }
+            SolitaireMove::WasteToTableau { pile } => {
+                let card = *self.waste.last().ok_or("The waste is empty")?;
+                let target = self.pile(pile)?;
+                if !target.takes(card) {
+                    return Err(format!("{} cannot go on pile {}", card_name(card), pile));
+                }
+                target.cards.push(card);
+                self.waste.pop();
+                Ok(5)
+            }
+            SolitaireMove::WasteToFoundation => {
+                let card = *self.waste.last().ok_or("The waste is empty")?;
+                if !self.fits_foundation(card) {
+                    return Err(format!("{} cannot go on its foundation", card_name(card)));
+                }
+                self.foundations[suit(card)] += 1;
+                self.waste.pop();
+                Ok(10)
+            }
+            SolitaireMove::TableauToFoundation { pile } => {
+                let card = self.pile(pile)?.top().ok_or("The pile is empty")?;
+                if !self.fits_foundation(card) {
+                    return Err(format!("{} cannot go on its foundation", card_name(card)));
+                }
+                self.foundations[suit(card)] += 1;
+                let source = self.pile(pile)?;
+                source.cards.pop();
+                Ok(if source.turn_over() { 15 } else { 10 })
+            }
+            SolitaireMove::TableauToTableau { from, to, count } => {
+                if from == to {
+                    return Err("The cards are already on that pile".into());
+                }
+                let source = self.pile(from)?;
+                if count == 0 || count > source.face_up() {
+                    return Err(format!("Pile {} has no {} face-up cards to move", from, count));
+                }
+                let start = source.cards.len() - count;
+                let card = source.cards[start];
+                if !self.pile(to)?.takes(card) {
+                    return Err(format!("{} cannot go on pile {}", card_name(card), to));
+                }
+                let moved: Vec<Card> = self.pile(from)?.cards.drain(start..).collect();
+                self.pile(to)?.cards.extend(moved);
+                Ok(if self.pile(from)?.turn_over() { 5 } else { 0 })
+            }
+            SolitaireMove::FoundationToTableau { suit: name, pile } => {
+                let index = SUITS
+                    .iter()
+                    .position(|&s| s == name.to_ascii_uppercase())
+                    .ok_or_else(|| format!("There is no suit {}", name))?;
+                let count = self.foundations[index];
+                if count == 0 {
+                    return Err(format!("The {} foundation is empty", SUITS[index]));
+                }
+                let card = (index * 13) as u8 + count - 1;
+                let target = self.pile(pile)?;
+                if !target.takes(card) {
+                    return Err(format!("{} cannot go on pile {}", card_name(card), pile));
+                }
+                target.cards.push(card);
+                self.foundations[index] -= 1;
+                Ok(-15)
+            }
+        }
+    }
+
+    /// Moves worth searching, most promising first. Cards are not taken
+    /// back off foundations, and runs are only split to free a card for a
+    /// foundation.
+    fn candidate_moves(&self) -> Vec<SolitaireMove> {
+        let mut moves = Vec::new();
+        if self.waste.last().is_some_and(|&card| self.fits_foundation(card)) {
+            moves.push(SolitaireMove::WasteToFoundation);
+        }
+        for (pile, cards) in self.tableau.iter().enumerate() {
+            if cards.top().is_some_and(|card| self.fits_foundation(card)) {
+                moves.push(SolitaireMove::TableauToFoundation { pile });
+            }
+        }
+        for (from, source) in self.tableau.iter().enumerate() {
+            for count in 1..=source.face_up() {
+                let start = source.cards.len() - count;
+                let whole_run = start == source.hidden;
+                // A king already at the bottom of a pile has nowhere better to go
+                if whole_run && source.hidden == 0 && rank(source.cards[0]) == 13 {
+                    continue;
+                }
+                let frees_card = !whole_run && self.fits_foundation(source.cards[start - 1]);
+                if !whole_run && !frees_card {
+                    continue;
+                }
+                for (to, target) in self.tableau.iter().enumerate() {
+                    if to != from && target.takes(source.cards[start]) {
+                        moves.push(SolitaireMove::TableauToTableau { from, to, count });
+                    }
+                }
+            }
+        }
+        if let Some(&card) = self.waste.last() {
+            for (pile, target) in self.tableau.iter().enumerate() {
+                if target.takes(card) {
+                    moves.push(SolitaireMove::WasteToTableau { pile });
+                }
+            }
+        }
+        if !self.stock.is_empty() || !self.waste.is_empty() {
+            moves.push(SolitaireMove::Draw);
+        }
+        moves
+    }
+
+    /// Everything is turned over with nothing left to draw, which always
+    /// plays out
+    fn solved(&self) -> bool {
+        self.stock.is_empty() && self.waste.is_empty() && self.tableau.iter().all(|pile| pile.hidden == 0)
+    }
+}
+
+/// Search for a win within `SOLVER_NODES` positions. A win found is real;
+/// giving up does not mean the deal cannot be won.
+fn winnable(board: &Board, draw: u8) -> bool {
+    let mut seen = HashSet::new();
+    let mut pending = vec![board.clone()];
+    while let Some(board) = pending.pop() {
+        if board.solved() {
+            return true;
+        }
+        if !seen.insert(board.clone()) {
+            continue;
+        }
+        if seen.len() >= SOLVER_NODES {
+            return false;
+        }
+        // Pushed in reverse so the most promising move is searched first
+        for play in board.candidate_moves().iter().rev() {
+            let mut next = board.clone();
+            if next.apply(play, draw).is_ok() && !seen.contains(&next) {
+                pending.push(next);
+            }
+        }
+    }
+    false
+}
+
+impl Game {
+    fn elapsed(&self) -> f64 {
+        self.finished_secs.unwrap_or_else(|| self.started.elapsed().as_secs_f64())
+    }
+
+    fn play(&mut self, play: &SolitaireMove) -> Result<(), KioskError> {
+        if self.board.won() {
+            return Err(KioskError::invalid("The game is already won"));
+        }
+        let before = self.board.clone();
+        let points = self.board.apply(play, self.draw).map_err(KioskError::invalid)?;
+        self.history.push((before, self.score));
+        if self.history.len() > MAX_UNDO {
+            self.history.remove(0);
+        }
+        self.score = (self.score + points).max(0);
+        self.moves += 1;
+
+        if self.board.won() {
+            let secs = self.elapsed();
+            self.finished_secs = Some(secs);
+            if secs >= 30.0 {
+                self.score += (700_000.0 / secs) as i64;
+            }
+        }
+        Ok(())
+    }
+
+    /// Put a game just won on the leaderboard
+    fn record_result(&mut self, app: &AppHandle) {
+        if self.board.won() && self.result.is_none() {
+            let variant = if self.draw == 1 { "draw1" } else { "draw3" };
+            self.result = games::record_score(app, GAME, variant, &self.player, self.score as f64, ScoreOrder::Higher)
+                .ok();
+        }
+    }
+
+    fn undo(&mut self) -> Result<(), KioskError> {
+        if self.board.won() {
+            return Err(KioskError::invalid("The game is already won"));
+        }
+        let (board, score) = self.history.pop().ok_or_else(|| KioskError::invalid("There is nothing to undo"))?;
+        self.board = board;
+        self.score = score;
+        self.moves += 1;
+        Ok(())
+    }
+
+    fn view(&self, id: &str) -> SolitaireView {
+        let board = &self.board;
+        let shown = board.waste.len().saturating_sub(self.draw as usize);
+        SolitaireView {
+            id: id.to_string(),
+            draw: self.draw,
+            seed: self.seed,
+            stock: board.stock.len(),
+            waste: board.waste[shown..].iter().map(|&card| card_name(card)).collect(),
+            waste_count: board.waste.len(),
+            foundations: (0..4)
+                .map(|index| {
+                    let count = board.foundations[index];
+                    (count > 0).then(|| card_name((index * 13) as u8 + count - 1))
+                })
+                .collect(),
+            tableau: board
+                .tableau
+                .iter()
+                .map(|pile| TableauPile {
+                    hidden: pile.hidden,
+                    cards: pile.cards[pile.hidden..].iter().map(|&card| card_name(card)).collect(),
+                })
+                .collect(),
+            score: self.score,
+            moves: self.moves,
+            can_undo: !self.history.is_empty() && !board.won(),
+            won: board.won(),
+            elapsed_secs: self.elapsed(),
+            guaranteed: self.guaranteed,
+            result: self.result.clone(),
+        }
+    }
+}
+
+/// Run `change` on a game and return its view
+fn with_game(
+    state: &SolitaireState,
+    id: &str,
+    change: impl FnOnce(&mut Game) -> Result<(), KioskError>,
+) -> Result<SolitaireView, KioskError> {
+    let mut games = state.0.lock().expect("solitaire lock");
+    let game = games
+        .get_mut(id)
+        .ok_or_else(|| KioskError::not_found(format!("No game {}", id)))?;
+    change(game)?;
+    game.last_played = Instant::now();
+    Ok(game.view(id))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Deal a game. A solvable deal can take a few searches, so this runs off
+/// the main thread.
+#[tauri::command(async)]
+pub fn new_solitaire(
+    auth: State<'_, AuthState>,
+    state: State<'_, SolitaireState>,
+    options: Option<SolitaireOptions>,
+) -> Result<SolitaireView, KioskError> {
+    let options = options.unwrap_or_default();
+    let draw = options.draw.unwrap_or(1);
+    if draw != 1 && draw != 3 {
+        return Err(KioskError::invalid("Draw one or three cards"));
+    }
+
+    let mut seed = options.seed.unwrap_or_else(games::random_seed);
+    let mut board = Board::deal(seed);
+    let mut guaranteed = false;
+    if options.solvable {
+        for _ in 0..SOLVABLE_ATTEMPTS {
+            if winnable(&board, draw) {
+                guaranteed = true;
+                break;
+            }
+            seed = Rng::new(seed).next_u64() as u32;
+            board = Board::deal(seed);
+        }
+    }
+
+    let game = Game {
+        draw,
+        seed,
+        guaranteed,
+        player: games::player_name(&auth, options.player),
+        board,
+        score: 0,
+        moves: 0,
+        history: Vec::new(),
+        started: Instant::now(),
+        finished_secs: None,
+        result: None,
+        last_played: Instant::now(),
+    };
+    let id = uuid::Uuid::new_v4().to_string();
+    let view = game.view(&id);
+
+    let mut games = state.0.lock().expect("solitaire lock");
+    while games.len() >= MAX_GAMES {
+        let Some(oldest) = games
+            .iter()
+            .min_by_key(|(_, game)| game.last_played)
+            .map(|(id, _)| id.clone())
+        else {
+            break;
+        };
+        games.remove(&oldest);
+    }
+    games.insert(id, game);
+    Ok(view)
+}
+
+#[tauri::command]
+pub fn get_solitaire(state: State<'_, SolitaireState>, id: String) -> Result<SolitaireView, KioskError> {
+    with_game(&state, &id, |_| Ok(()))
+}
+
+/// Play a move; an illegal one is refused and changes nothing
+#[tauri::command]
+pub fn solitaire_move(
+    app: AppHandle,
+    state: State<'_, SolitaireState>,
+    id: String,
+    play: SolitaireMove,
+) -> Result<SolitaireView, KioskError> {
+    with_game(&state, &id, |game| {
+        game.play(&play)?;
+        game.record_result(&app);
+        Ok(())
+    })
+}
+
+/// Take back the last move
+#[tauri::command]
+pub fn solitaire_undo(state: State<'_, SolitaireState>, id: String) -> Result<SolitaireView, KioskError> {
+    with_game(&state, &id, Game::undo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AC: Card = 0;
+    const TWO_C: Card = 1;
+    const KC: Card = 12;
+    const FIVE_D: Card = 17;
+    const KD: Card = 25;
+    const QH: Card = 37;
+    const KH: Card = 38;
+    const QS: Card = 50;
+    const KS: Card = 51;
+
+    fn pile(cards: &[Card], hidden: usize) -> Pile {
+        Pile {
+            cards: cards.to_vec(),
+            hidden,
+        }
+    }
+
+    fn board() -> Board {
+        Board {
+            stock: Vec::new(),
+            waste: Vec::new(),
+            foundations: [0; 4],
+            tableau: std::array::from_fn(|_| pile(&[], 0)),
+        }
+    }
+
+    fn game(board: Board) -> Game {
+        Game {
+            draw: 1,
+            seed: 0,
+            guaranteed: false,
+            player: String::new(),
+            board,
+            score: 0,
+            moves: 0,
+            history: Vec::new(),
+            started: Instant::now(),
+            finished_secs: None,
+            result: None,
+            last_played: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn a_seed_replays_its_deal() {
+        let deal = Board::deal(42);
+        assert_eq!(deal, Board::deal(42));
+        assert_ne!(deal, Board::deal(43));
+
+        assert_eq!(deal.stock.len(), 24);
+        for (i, pile) in deal.tableau.iter().enumerate() {
+            assert_eq!((pile.cards.len(), pile.hidden), (i + 1, i));
+        }
+        let mut cards: Vec<Card> = deal.stock.clone();
+        cards.extend(deal.tableau.iter().flat_map(|pile| pile.cards.iter().copied()));
+        cards.sort_unstable();
+        assert_eq!(cards, (0..52).collect::<Vec<Card>>());
+    }
+
+    #[test]
+    fn card_names() {
+        assert_eq!(card_name(AC), "AC");
+        assert_eq!(card_name(FIVE_D), "5D");
+        assert_eq!(card_name(QH), "QH");
+        assert_eq!(card_name(KS), "KS");
+    }
+
+    #[test]
+    fn tableau_takes_alternating_colours_down_to_kings_on_empty_piles() {
+        let mut board = board();
+        board.tableau[0] = pile(&[KS], 0);
+        board.waste = vec![QS];
+        assert!(board.apply(&SolitaireMove::WasteToTableau { pile: 0 }, 1).is_err());
+        board.waste = vec![QH];
+        assert_eq!(board.apply(&SolitaireMove::WasteToTableau { pile: 0 }, 1), Ok(5));
+        assert_eq!(board.tableau[0].cards, vec![KS, QH]);
+
+        board.waste = vec![QS];
+        assert!(board.apply(&SolitaireMove::WasteToTableau { pile: 1 }, 1).is_err());
+        assert!(board.apply(&SolitaireMove::WasteToTableau { pile: 7 }, 1).is_err());
+
+        assert!(board.apply(&SolitaireMove::TableauToTableau { from: 0, to: 0, count: 1 }, 1).is_err());
+        assert!(board.apply(&SolitaireMove::TableauToTableau { from: 0, to: 1, count: 3 }, 1).is_err());
+        assert!(board.apply(&SolitaireMove::TableauToTableau { from: 0, to: 1, count: 1 }, 1).is_err());
+        assert_eq!(board.apply(&SolitaireMove::TableauToTableau { from: 0, to: 1, count: 2 }, 1), Ok(0));
+        assert_eq!(board.tableau[1].cards, vec![KS, QH]);
+        assert!(board.tableau[0].cards.is_empty());
+    }
+
+    #[test]
+    fn foundations_build_up_by_suit() {
+        let mut board = board();
+        board.waste = vec![TWO_C];
+        assert!(board.apply(&SolitaireMove::WasteToFoundation, 1).is_err());
+        board.waste = vec![TWO_C, AC];
+        assert_eq!(board.apply(&SolitaireMove::WasteToFoundation, 1), Ok(10));
+        assert_eq!(board.apply(&SolitaireMove::WasteToFoundation, 1), Ok(10));
+        assert_eq!(board.foundations, [2, 0, 0, 0]);
+
+        // Turning the card underneath over scores too
+        board.tableau[3] = pile(&[FIVE_D, KD], 1);
+        board.foundations[1] = 12;
+        assert_eq!(board.apply(&SolitaireMove::TableauToFoundation { pile: 3 }, 1), Ok(15));
+        assert_eq!(board.tableau[3].hidden, 0);
+
+        board.tableau[4] = pile(&[KS], 0);
+        board.foundations[3] = 12;
+        assert_eq!(
+            board.apply(&SolitaireMove::FoundationToTableau { suit: 's', pile: 4 }, 1),
+            Err("QS cannot go on pile 4".to_string())
+        );
+        board.tableau[4] = pile(&[KD], 0);
+        assert_eq!(board.apply(&SolitaireMove::FoundationToTableau { suit: 's', pile: 4 }, 1), Ok(-15));
+        assert_eq!(board.foundations[3], 11);
+        assert!(board.apply(&SolitaireMove::FoundationToTableau { suit: 'h', pile: 5 }, 1).is_err());
+    }
+
+    #[test]
+    fn drawing_turns_the_stock_then_recycles_the_waste() {
+        let mut board = board();
+        board.stock = vec![AC, TWO_C, FIVE_D, KD];
+        assert_eq!(board.apply(&SolitaireMove::Draw, 3), Ok(0));
+        assert_eq!(board.waste, vec![KD, FIVE_D, TWO_C]);
+        assert_eq!(board.apply(&SolitaireMove::Draw, 3), Ok(0));
+        assert_eq!(board.apply(&SolitaireMove::Draw, 3), Ok(-20));
+        assert_eq!(board.stock, vec![AC, TWO_C, FIVE_D, KD]);
+        assert!(board.waste.is_empty());
+        assert_eq!(board.apply(&SolitaireMove::Draw, 1), Ok(0));
+        assert_eq!(board.apply(&SolitaireMove::Draw, 1), Ok(0));
+        assert_eq!(board.waste, vec![KD, FIVE_D]);
+
+        let mut empty = self::board();
+        assert!(empty.apply(&SolitaireMove::Draw, 1).is_err());
+    }
+
+    #[test]
+    fn winnable_finds_a_win_or_gives_up() {
+        let mut board = board();
+        board.foundations = [12; 4];
+        board.tableau[0] = pile(&[KS, KD], 1);
+        board.stock = vec![KH, KC];
+        assert!(winnable(&board, 1));
+
+        // The ace is buried under the two with nothing to move it onto
+        let mut stuck = self::board();
+        stuck.tableau[0] = pile(&[AC, TWO_C], 1);
+        assert!(!winnable(&stuck, 1));
+    }
+
+    #[test]
+    fn undo_puts_back_the_board_and_score() {
+        let mut board = board();
+        board.tableau[0] = pile(&[KS], 0);
+        board.waste = vec![QH];
+        let mut game = game(board.clone());
+
+        assert!(game.undo().is_err());
+        game.play(&SolitaireMove::WasteToTableau { pile: 0 }).unwrap();
+        assert_eq!(game.score, 5);
+        assert!(game.play(&SolitaireMove::WasteToFoundation).is_err());
+        assert_eq!(game.moves, 1);
+
+        game.undo().unwrap();
+        assert_eq!(game.board, board);
+        assert_eq!(game.score, 0);
+        assert!(game.undo().is_err());
+    }
+
+    #[test]
+    fn score_never_goes_below_zero() {
+        let mut board = board();
+        board.waste = vec![AC];
+        let mut game = game(board);
+        game.play(&SolitaireMove::Draw).unwrap();
+        assert_eq!(game.score, 0);
+    }
+}
@@ -0,0 +1,291 @@
+//! Offline speech recognition
+//!
+//! Listens on the microphone and publishes `speech-recognized` for each
+//! utterance, so accessibility kiosks can be driven hands-free. Recognition
+//! runs locally with Vosk; audio comes from ALSA's `arecord` as 16 kHz mono
+//! PCM. A grammar limits recognition to a fixed set of phrases, which is
+//! both faster and far more accurate for command words than free dictation.
+//! Needs libvosk and a Vosk model directory (see alphacephei.com/vosk/models).
+
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+use vosk::{DecodingState, Model, Recognizer};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::lazy::{self, Subsystem};
+use crate::{events, store};
+
+const CONFIG_FILE: &str = "speech.json";
+
+/// Model directory in the app data directory when none is configured
+const DEFAULT_MODEL_DIR: &str = "speech-model";
+
+const SAMPLE_RATE: u32 = 16000;
+
+/// Audio fed to the recognizer at a time (100 ms of 16-bit samples)
+const CHUNK_BYTES: usize = SAMPLE_RATE as usize / 10 * 2;
+
+/// Vosk's token for speech outside the grammar
+const UNKNOWN: &str = "[unk]";
+
+const MAX_PHRASES: usize = 500;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeechConfig {
+    /// Vosk model directory; `speech-model` in the data directory when empty
+    pub model: String,
+    /// ALSA capture device; the default device when empty
+    pub device: String,
+}
+
+/// Payload of `speech-recognized`
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeechRecognized {
+    pub text: String,
+    /// Whether the text is one of the grammar's phrases
+    pub in_grammar: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeechStatus {
+    pub listening: bool,
+    /// Phrases recognition is limited to; empty for free dictation
+    pub grammar: Vec<String>,
+    pub model_loaded: bool,
+    pub error: Option<String>,
+}
+
+pub struct SpeechState {
+    config: Mutex<SpeechConfig>,
+    model: Mutex<Option<Arc<Model>>>,
+    recorder: Mutex<Option<Child>>,
+    grammar: Mutex<Vec<String>>,
+    error: Mutex<Option<String>>,
+    /// Bumped on every start and stop so an old listener goes quiet
+    generation: AtomicU64,
+}
+
+impl Subsystem for SpeechState {
+    const NAME: &'static str = "speech";
+
+    fn load(app: &AppHandle) -> Self {
+        SpeechState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            model: Mutex::new(None),
+            recorder: Mutex::new(None),
+            grammar: Mutex::new(Vec::new()),
+            error: Mutex::new(None),
+            generation: AtomicU64::new(0),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The configured model, loaded once and kept for later sessions
+fn model(app: &AppHandle, state: &SpeechState) -> Result<Arc<Model>, String> {
+    let mut cached = state.model.lock().expect("speech model lock");
+    if let Some(model) = cached.as_ref() {
+        return Ok(model.clone());
+    }
+    let configured = state.config.lock().expect("speech config lock").model.clone();
+    let path = if configured.is_empty() {
+        store::data_path(app, DEFAULT_MODEL_DIR)?
+    } else {
+        configured.into()
+    };
+    if !path.is_dir() {
+        return Err(format!("No speech model at {}", path.display()));
+    }
+    let model = Arc::new(
+        Model::new(path.to_string_lossy()).ok_or_else(|| format!("Cannot load the speech model at {}", path.display()))?,
+    );
+    *cached = Some(model.clone());
+    Ok(model)
+}
+
+fn normalize_grammar(grammar: Vec<String>) -> Result<Vec<String>, String> {
+    let mut phrases: Vec<String> = grammar
+        .iter()
+        .map(|phrase| phrase.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+        .filter(|phrase| !phrase.is_empty())
+        .collect();
+    phrases.dedup();
+    if phrases.len() > MAX_PHRASES {
+        return Err(format!("A grammar can have at most {} phrases", MAX_PHRASES));
+    }
+    if phrases.iter().any(|phrase| phrase.contains('"') || phrase.contains('\\')) {
+        return Err("Grammar phrases cannot contain quotes or backslashes".to_string());
+    }
+    Ok(phrases)
+}
+
+fn start_recorder(device: &str) -> Result<Child, String> {
+    let mut command = Command::new("arecord");
+    command.args(["-q", "-t", "raw", "-f", "S16_LE", "-c", "1", "-r", &SAMPLE_RATE.to_string()]);
+    if !device.is_empty() {
+        command.args(["-D", device]);
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run arecord (install alsa-utils): {}", e))
+}
+
+fn stop_recorder(state: &SpeechState) {
+    if let Some(mut child) = state.recorder.lock().expect("speech recorder lock").take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+fn status(state: &SpeechState) -> SpeechStatus {
+    let listening = state
+        .recorder
+        .lock()
+        .expect("speech recorder lock")
+        .as_mut()
+        .is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+    SpeechStatus {
+        listening,
+        grammar: state.grammar.lock().expect("speech grammar lock").clone(),
+        model_loaded: state.model.lock().expect("speech model lock").is_some(),
+        error: state.error.lock().expect("speech error lock").clone(),
+    }
+}
+
+/// Publish the recognizer's finished utterance, if it heard anything
+fn publish_result(app: &AppHandle, recognizer: &mut Recognizer, grammar: &[String]) {
+    let Some(result) = recognizer.result().single() else {
+        return;
+    };
+    let text = result.text.replace(UNKNOWN, " ").split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return;
+    }
+    let in_grammar = grammar.contains(&text);
+    events::publish(app, "speech-recognized", &SpeechRecognized { text, in_grammar });
+}
+
+/// Feed the recorder's audio to the recognizer until it stops or is replaced
+fn listen(app: AppHandle, generation: u64, mut audio: impl Read, mut recognizer: Recognizer, grammar: Vec<String>) {
+    let current = || {
+        lazy::get::<SpeechState>(&app).is_ok_and(|state| state.generation.load(Ordering::SeqCst) == generation)
+    };
+    let mut buffer = vec![0u8; CHUNK_BYTES];
+    let mut samples = Vec::with_capacity(CHUNK_BYTES / 2);
+    while current() {
+        if audio.read_exact(&mut buffer).is_err() {
+            break;
+        }
+        samples.clear();
+        samples.extend(buffer.chunks_exact(2).map(|pair| i16::from_le_bytes([pair[0], pair[1]])));
+        match recognizer.accept_waveform(&samples) {
+            Ok(DecodingState::Finalized) => publish_result(&app, &mut recognizer, &grammar),
+            Ok(_) => {}
+            Err(e) => {
+                if let Ok(state) = lazy::get::<SpeechState>(&app) {
+                    *state.error.lock().expect("speech error lock") = Some(e.to_string());
+                }
+                break;
+            }
+        }
+    }
+    if !current() {
+        return;
+    }
+    // The microphone went away rather than being stopped
+    if let Ok(state) = lazy::get::<SpeechState>(&app) {
+        stop_recorder(state);
+        let mut error = state.error.lock().expect("speech error lock");
+        error.get_or_insert_with(|| "The microphone stopped".to_string());
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start listening for speech, limited to `grammar`'s phrases when given,
+/// replacing any earlier session
+#[tauri::command]
+pub fn start_listening(app: AppHandle, grammar: Option<Vec<String>>) -> Result<SpeechStatus, KioskError> {
+    let state = lazy::get::<SpeechState>(&app)?;
+    let grammar = normalize_grammar(grammar.unwrap_or_default())?;
+    let model = model(&app, state).map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+
+    let recognizer = if grammar.is_empty() {
+        Recognizer::new(&model, SAMPLE_RATE as f32)
+    } else {
+        let mut phrases = grammar.clone();
+        phrases.push(UNKNOWN.to_string());
+        Recognizer::new_with_grammar(&model, SAMPLE_RATE as f32, &phrases)
+    }
+    .ok_or_else(|| KioskError::new(ErrorKind::Failed, "Cannot start the speech recognizer"))?;
+
+    let generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    stop_recorder(state);
+    let device = state.config.lock().expect("speech config lock").device.clone();
+    let mut child = start_recorder(&device).map_err(|e| KioskError::new(ErrorKind::Failed, e))?;
+    let audio = child.stdout.take().ok_or_else(|| KioskError::new(ErrorKind::Failed, "arecord stdout unavailable"))?;
+    *state.recorder.lock().expect("speech recorder lock") = Some(child);
+    *state.grammar.lock().expect("speech grammar lock") = grammar.clone();
+    *state.error.lock().expect("speech error lock") = None;
+
+    let listening = status(state);
+    std::thread::spawn(move || listen(app, generation, audio, recognizer, grammar));
+    Ok(listening)
+}
+
+/// Stop listening
+#[tauri::command]
+pub fn stop_listening(app: AppHandle) -> Result<(), KioskError> {
+    let state = lazy::get::<SpeechState>(&app)?;
+    state.generation.fetch_add(1, Ordering::SeqCst);
+    stop_recorder(state);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_speech_status(app: AppHandle) -> Result<SpeechStatus, KioskError> {
+    Ok(status(lazy::get::<SpeechState>(&app)?))
+}
+
+#[tauri::command]
+pub fn get_speech_config(app: AppHandle) -> Result<SpeechConfig, KioskError> {
+    let state = lazy::get::<SpeechState>(&app)?;
+    Ok(state.config.lock().expect("speech config lock").clone())
+}
+
+/// Change the model and microphone (admin); takes effect on the next
+/// `start_listening`
+#[tauri::command]
+pub fn set_speech_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    config: SpeechConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let state = lazy::get::<SpeechState>(&app)?;
+    let mut current = state.config.lock().expect("speech config lock");
+    store::save(&app, CONFIG_FILE, &config)?;
+    if config.model != current.model {
+        *state.model.lock().expect("speech model lock") = None;
+    }
+    *current = config;
+    Ok(())
+}
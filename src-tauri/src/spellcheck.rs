@@ -0,0 +1,259 @@
+//! Offline spell checking
+//!
+//! Drives `hunspell` in pipe mode for the Notepad/WordPad clones and form
+//! inputs. Dictionaries come from the system hunspell directory or are
+//! downloaded into the app data directory with `install_dictionary`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::http::ResponseExt;
+use crate::{http, store};
+
+/// System directories hunspell dictionaries are installed into
+const SYSTEM_DICT_DIRS: &[&str] = &["/usr/share/hunspell", "/usr/share/myspell/dicts"];
+
+/// App data subdirectory for downloaded dictionaries
+const DICT_DIR: &str = "dictionaries";
+
+/// LibreOffice dictionary repository, laid out as `<language>/<lang>.{aff,dic}`
+const DICT_BASE_URL: &str = "https://raw.githubusercontent.com/LibreOffice/dictionaries/master";
+
+/// Largest dictionary file accepted from the download
+const MAX_DICT_BYTES: u64 = 20 * 1024 * 1024;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+/// A misspelled word found in checked text
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Misspelling {
+    pub word: String,
+    /// Character offset of the word in the checked text
+    pub offset: usize,
+    pub suggestions: Vec<String>,
+}
+
+/// An installed dictionary
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DictionaryInfo {
+    pub lang: String,
+    pub path: String,
+    pub user_installed: bool,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Reject anything that isn't a plain language tag like `en_US`
+fn validate_lang(lang: &str) -> Result<(), String> {
+    let valid = !lang.is_empty()
+        && lang.len() <= 16
+        && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid language: {}", lang))
+    }
+}
+
+fn user_dict_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = store::data_path(app, DICT_DIR)?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Find the dictionary base path (without extension) hunspell should load
+fn find_dictionary(app: &AppHandle, lang: &str) -> Result<PathBuf, String> {
+    validate_lang(lang)?;
+
+    let user_dir = user_dict_dir(app)?;
+    std::iter::once(user_dir.as_path())
+        .chain(SYSTEM_DICT_DIRS.iter().map(Path::new))
+        .map(|dir| dir.join(lang))
+        .find(|base| base.with_extension("dic").exists())
+        .ok_or_else(|| format!("No dictionary installed for {}", lang))
+}
+
+/// Run hunspell in pipe mode over `lines`, returning its raw output
+fn run_hunspell(dictionary: &Path, lines: &[&str]) -> Result<String, String> {
+    let mut child = Command::new("hunspell")
+        .arg("-a")
+        .arg("-d")
+        .arg(dictionary)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run hunspell: {}", e))?;
+
+    // '^' stops hunspell interpreting a line as a pipe-mode command
+    let input: String = lines.iter().map(|line| format!("^{}\n", line)).collect();
+    let mut stdin = child.stdin.take().ok_or("hunspell stdin unavailable")?;
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let mut output = String::new();
+    child
+        .stdout
+        .take()
+        .ok_or("hunspell stdout unavailable")?
+        .read_to_string(&mut output)
+        .map_err(|e| e.to_string())?;
+
+    writer
+        .join()
+        .map_err(|_| "hunspell writer panicked".to_string())?
+        .map_err(|e| e.to_string())?;
+    child.wait().map_err(|e| e.to_string())?;
+
+    Ok(output)
+}
+
+/// Parse a `&`/`#` pipe-mode result line into the word and its suggestions
+fn parse_result(line: &str) -> Option<(String, Vec<String>)> {
+    let mut parts = line.splitn(2, ": ");
+    let head = parts.next()?;
+    let mut fields = head.split_whitespace();
+
+    match fields.next()? {
+        "&" => {
+            let word = fields.next()?.to_string();
+            let suggestions = parts
+                .next()
+                .map(|list| list.split(", ").map(str::to_string).collect())
+                .unwrap_or_default();
+            Some((word, suggestions))
+        }
+        "#" => Some((fields.next()?.to_string(), Vec::new())),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Check `text`, returning each misspelled word with suggestions
+#[tauri::command]
+pub fn spellcheck(app: AppHandle, text: String, lang: String) -> Result<Vec<Misspelling>, KioskError> {
+    let dictionary = find_dictionary(&app, &lang)?;
+    let lines: Vec<&str> = text.split('\n').collect();
+    let output = run_hunspell(&dictionary, &lines)?;
+
+    // Skip the version banner; each input line's results end with a blank line
+    let mut results = output.split('\n').skip(1);
+    let mut misspellings = Vec::new();
+    let mut line_start = 0;
+
+    for line in &lines {
+        let mut search_from = 0;
+
+        for result in results.by_ref() {
+            if result.is_empty() {
+                break;
+            }
+
+            if let Some((word, suggestions)) = parse_result(result) {
+                // Locate the word in the original line rather than trusting pipe-mode offsets
+                if let Some(pos) = line[search_from..].find(&word) {
+                    let byte_pos = search_from + pos;
+                    search_from = byte_pos + word.len();
+                    misspellings.push(Misspelling {
+                        offset: line_start + line[..byte_pos].chars().count(),
+                        word,
+                        suggestions,
+                    });
+                }
+            }
+        }
+
+        line_start += line.chars().count() + 1;
+    }
+
+    Ok(misspellings)
+}
+
+/// Get spelling suggestions for a single word
+#[tauri::command]
+pub fn suggest(app: AppHandle, word: String, lang: String) -> Result<Vec<String>, KioskError> {
+    let dictionary = find_dictionary(&app, &lang)?;
+    let word = word.split_whitespace().next().unwrap_or_default();
+    let output = run_hunspell(&dictionary, &[word])?;
+
+    Ok(output
+        .lines()
+        .skip(1)
+        .find_map(parse_result)
+        .map(|(_, suggestions)| suggestions)
+        .unwrap_or_default())
+}
+
+/// List dictionaries available to the spell checker
+#[tauri::command]
+pub fn list_dictionaries(app: AppHandle) -> Result<Vec<DictionaryInfo>, KioskError> {
+    let user_dir = user_dict_dir(&app)?;
+    let mut dictionaries = Vec::new();
+
+    for dir in std::iter::once(user_dir.as_path()).chain(SYSTEM_DICT_DIRS.iter().map(Path::new)) {
+        let Ok(entries) = fs::read_dir(dir) else { continue };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "dic") {
+                let lang = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                if dictionaries.iter().any(|d: &DictionaryInfo| d.lang == lang) {
+                    continue;
+                }
+
+                dictionaries.push(DictionaryInfo {
+                    lang,
+                    path: path.to_string_lossy().to_string(),
+                    user_installed: dir == user_dir,
+                });
+            }
+        }
+    }
+
+    dictionaries.sort_by(|a, b| a.lang.cmp(&b.lang));
+    Ok(dictionaries)
+}
+
+/// Download and install a hunspell dictionary such as `en_GB` or `fr_FR`
+#[tauri::command(async)]
+pub fn install_dictionary(app: AppHandle, lang: String) -> Result<DictionaryInfo, KioskError> {
+    validate_lang(&lang)?;
+
+    let language = lang.split(['_', '-']).next().unwrap_or(&lang);
+    let dir = user_dict_dir(&app)?;
+    let agent = http::agent();
+
+    for ext in ["aff", "dic"] {
+        let url = format!("{}/{}/{}.{}", DICT_BASE_URL, language, lang, ext);
+        let response = agent
+            .get(&url)
+            .call()
+            .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+
+        let mut data = Vec::new();
+        response
+            .into_throttled_reader()
+            .take(MAX_DICT_BYTES)
+            .read_to_end(&mut data)
+            .map_err(|e| e.to_string())?;
+
+        fs::write(dir.join(format!("{}.{}", lang, ext)), data).map_err(|e| e.to_string())?;
+    }
+
+    Ok(DictionaryInfo {
+        path: dir.join(format!("{}.dic", lang)).to_string_lossy().to_string(),
+        lang,
+        user_installed: true,
+    })
+}
@@ -0,0 +1,196 @@
+//! Stats history
+//!
+//! Samples CPU, memory and network throughput once a second into fixed-size
+//! ring buffers at three resolutions (1 s for 5 minutes, 5 s for an hour,
+//! 1 min for a day), so graphs can ask for a window at the resolution they
+//! draw instead of keeping raw samples in the frontend. Lite mode keeps no
+//! history and does not sample.
+
+use chrono::Local;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{config, SharedSystem};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// (resolution in seconds, samples kept)
+const TIERS: [(u64, usize); 3] = [(1, 300), (5, 720), (60, 1440)];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct StatsSample {
+    /// Unix time of the end of the sample
+    pub time: i64,
+    /// Percent of all cores
+    pub cpu: f32,
+    /// Percent of RAM in use
+    pub memory: f32,
+    /// Bytes per second over all interfaces
+    pub rx_rate: f64,
+    pub tx_rate: f64,
+}
+
+struct Tier {
+    resolution: u64,
+    capacity: usize,
+    samples: VecDeque<StatsSample>,
+    /// Sum and count of the samples for the bucket being filled
+    pending: (StatsSample, u64),
+}
+
+pub struct StatsHistory(Mutex<Vec<Tier>>);
+
+impl StatsHistory {
+    pub fn load(app: &AppHandle) -> Self {
+        if config::lite_mode(app) {
+            return StatsHistory(Mutex::new(Vec::new()));
+        }
+        let tiers = TIERS
+            .iter()
+            .map(|&(resolution, capacity)| Tier {
+                resolution,
+                capacity,
+                samples: VecDeque::with_capacity(capacity),
+                pending: (StatsSample::default(), 0),
+            })
+            .collect();
+        StatsHistory(Mutex::new(tiers))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn add(sum: &mut StatsSample, sample: &StatsSample) {
+    sum.time = sample.time;
+    sum.cpu += sample.cpu;
+    sum.memory += sample.memory;
+    sum.rx_rate += sample.rx_rate;
+    sum.tx_rate += sample.tx_rate;
+}
+
+fn average(sum: &StatsSample, count: u64) -> StatsSample {
+    let n = count.max(1) as f64;
+    StatsSample {
+        time: sum.time,
+        cpu: (sum.cpu as f64 / n) as f32,
+        memory: (sum.memory as f64 / n) as f32,
+        rx_rate: sum.rx_rate / n,
+        tx_rate: sum.tx_rate / n,
+    }
+}
+
+fn record(history: &StatsHistory, sample: StatsSample) {
+    let mut tiers = history.0.lock().expect("stats history lock");
+    for tier in tiers.iter_mut() {
+        add(&mut tier.pending.0, &sample);
+        tier.pending.1 += 1;
+        if tier.pending.1 < tier.resolution {
+            continue;
+        }
+        if tier.samples.len() == tier.capacity {
+            tier.samples.pop_front();
+        }
+        tier.samples.push_back(average(&tier.pending.0, tier.pending.1));
+        tier.pending = (StatsSample::default(), 0);
+    }
+}
+
+fn network_totals(system: &SharedSystem) -> (u64, u64) {
+    let mut networks = system.networks.lock().expect("network state lock");
+    networks.refresh();
+    networks.iter().fold((0, 0), |(rx, tx), (_, data)| {
+        (rx + data.total_received(), tx + data.total_transmitted())
+    })
+}
+
+/// Spawn the sampler that fills the history
+pub fn start_stats(app: AppHandle) {
+    if config::lite_mode(&app) {
+        return;
+    }
+    std::thread::spawn(move || {
+        let system = app.state::<SharedSystem>();
+        let history = app.state::<StatsHistory>();
+        let mut last_totals = network_totals(&system);
+
+        loop {
+            std::thread::sleep(SAMPLE_INTERVAL);
+            let (cpu, memory) = {
+                let mut sys = system.system.lock().expect("system state lock");
+                sys.refresh_cpu_usage();
+                sys.refresh_memory();
+                let total = sys.total_memory().max(1) as f32;
+                (sys.global_cpu_usage(), sys.used_memory() as f32 / total * 100.0)
+            };
+            let totals = network_totals(&system);
+            let seconds = SAMPLE_INTERVAL.as_secs_f64();
+            record(
+                &history,
+                StatsSample {
+                    time: Local::now().timestamp(),
+                    cpu,
+                    memory,
+                    // Counters can go backwards when an interface is removed
+                    rx_rate: totals.0.saturating_sub(last_totals.0) as f64 / seconds,
+                    tx_rate: totals.1.saturating_sub(last_totals.1) as f64 / seconds,
+                },
+            );
+            last_totals = totals;
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Stats for the last `window` seconds, averaged into `resolution`-second
+/// points, oldest first
+#[tauri::command]
+pub fn get_stats_history(state: State<'_, StatsHistory>, window: u64, resolution: u64) -> Vec<StatsSample> {
+    let resolution = resolution.max(1);
+    let tiers = state.0.lock().expect("stats history lock");
+    // The coarsest tier that is still fine enough, unless it cannot cover the window
+    let tier = tiers
+        .iter()
+        .rev()
+        .find(|tier| tier.resolution <= resolution && tier.resolution * tier.capacity as u64 >= window)
+        .or_else(|| tiers.iter().find(|tier| tier.resolution * tier.capacity as u64 >= window))
+        .or_else(|| tiers.last());
+    let Some(tier) = tier else {
+        return Vec::new();
+    };
+
+    let since = Local::now().timestamp() - window as i64;
+    let bucket = resolution as i64;
+    let mut points: Vec<StatsSample> = Vec::new();
+    let mut current: Option<(i64, StatsSample, u64)> = None;
+    for sample in tier.samples.iter().filter(|sample| sample.time > since) {
+        let key = sample.time.div_euclid(bucket);
+        match &mut current {
+            Some((current_key, sum, count)) if *current_key == key => {
+                add(sum, sample);
+                *count += 1;
+            }
+            _ => {
+                if let Some((_, sum, count)) = current.take() {
+                    points.push(average(&sum, count));
+                }
+                current = Some((key, *sample, 1));
+            }
+        }
+    }
+    if let Some((_, sum, count)) = current {
+        points.push(average(&sum, count));
+    }
+    points
+}
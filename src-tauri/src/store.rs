@@ -0,0 +1,45 @@
+//! JSON persistence helpers
+//!
+//! Backend modules keep their state as small JSON documents in the app data
+//! directory. Writes go through a temporary file and a rename so a power pull
+//! mid-write never leaves a truncated document behind.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Resolve a file inside the app data directory, creating the directory if needed
+pub fn data_path(app: &AppHandle, file: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(file))
+}
+
+/// Load a JSON document, falling back to the default when missing or unreadable
+pub fn load<T: DeserializeOwned + Default>(app: &AppHandle, file: &str) -> T {
+    data_path(app, file).map(|path| read_json(&path)).unwrap_or_default()
+}
+
+/// Save a JSON document atomically
+pub fn save<T: Serialize>(app: &AppHandle, file: &str, value: &T) -> Result<(), String> {
+    write_json(&data_path(app, file)?, value)
+}
+
+/// `load` for a path, for callers without an app handle
+pub(crate) fn read_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// `save` for a path, for callers without an app handle
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+
+    fs::write(&tmp, text).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
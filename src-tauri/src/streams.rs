@@ -0,0 +1,737 @@
+//! Camera streams
+//!
+//! Security-desk kiosks show live feeds from IP cameras and recorders. The
+//! streams, RTSP or HLS, are configured here and checked in the background
+//! with ffprobe; whether each is online, what it carries and how long it
+//! takes to answer is published as `stream-status`. Snapshots are grabbed
+//! with ffmpeg.
+//!
+//! Webviews cannot play RTSP, so a stream can be relayed: ffmpeg re-muxes
+//! it, without re-encoding, to a short rolling HLS playlist in the data
+//! directory, served to the frontend over the `stream://` protocol. Relays
+//! that exit are restarted. H.264 cameras play everywhere; H.265 depends on
+//! the webview. Stream passwords are kept in the keyring.
+//!
+//! Streams are a lazy subsystem (`streams` flag): nothing is probed until a
+//! command needs them, unless a relay is set to always run.
+
+use base64::Engine;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::http::{Request, Response, StatusCode};
+use tauri::{AppHandle, State, UriSchemeContext};
+use url::Url;
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::lazy::{self, Subsystem};
+use crate::{events, keyring, store, vfs};
+
+const STREAMS_FILE: &str = "streams.json";
+
+/// Relay playlists and segments, one directory per stream, in the data directory
+const RELAY_DIR: &str = "streams";
+
+const PLAYLIST: &str = "index.m3u8";
+
+/// ffmpeg's messages from a relay, for reporting why it exited
+const RELAY_LOG: &str = "relay.log";
+
+/// Target segment length; cameras' keyframe interval can stretch it
+const SEGMENT_SECONDS: &str = "1";
+
+const PLAYLIST_SEGMENTS: &str = "4";
+
+/// Probes and snapshots are abandoned after this
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often relays are looked after
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Streams are probed every this many supervision rounds
+const PROBE_ROUNDS: u64 = 6;
+
+/// Wait before restarting a relay that exited
+const RELAY_RETRY: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RtspTransport {
+    /// Interleaved in the RTSP connection; gets through firewalls and NAT
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl RtspTransport {
+    fn as_str(self) -> &'static str {
+        match self {
+            RtspTransport::Tcp => "tcp",
+            RtspTransport::Udp => "udp",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraStream {
+    /// Empty to add a new stream
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// `rtsp://`, `rtsps://` or an HLS playlist over `http(s)://`, without credentials
+    pub url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Blank when read back; an empty password keeps the stored one
+    #[serde(default)]
+    pub password: String,
+    #[serde(default)]
+    pub transport: RtspTransport,
+    /// Keep a relay running from startup
+    #[serde(default)]
+    pub relay: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Availability {
+    Unknown,
+    Online,
+    Offline,
+}
+
+/// What a stream carries, from ffprobe
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamInfo {
+    pub video_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fps: Option<f64>,
+    pub audio_codec: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayStatus {
+    /// For the player, e.g. `stream://localhost/<id>/index.m3u8`
+    pub playlist_url: String,
+    pub running: bool,
+    /// Whether the playlist has been written yet
+    pub ready: bool,
+    pub started_at: i64,
+    pub restarts: u32,
+    /// Why it last exited
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamStatus {
+    pub id: String,
+    pub availability: Availability,
+    pub info: Option<StreamInfo>,
+    /// Time to connect and read the stream's headers
+    pub latency_ms: Option<u64>,
+    pub checked_at: Option<i64>,
+    /// When it last went online or offline
+    pub since: Option<i64>,
+    pub error: Option<String>,
+    pub relay: Option<RelayStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamSnapshot {
+    pub data_url: String,
+    /// Where it was saved, when a path was given
+    pub path: Option<String>,
+    /// Time to connect and decode the first frame
+    pub latency_ms: u64,
+    pub taken_at: i64,
+}
+
+struct Relay {
+    child: Option<Child>,
+    started: Instant,
+    started_at: i64,
+    restarts: u32,
+    error: Option<String>,
+}
+
+pub struct StreamsState {
+    streams: Mutex<Vec<CameraStream>>,
+    status: Mutex<HashMap<String, StreamStatus>>,
+    relays: Mutex<HashMap<String, Relay>>,
+}
+
+impl Subsystem for StreamsState {
+    const NAME: &'static str = "streams";
+
+    fn load(app: &AppHandle) -> Self {
+        let mut streams: Vec<CameraStream> = store::load(app, STREAMS_FILE);
+        for stream in streams.iter_mut() {
+            stream.password = keyring::get(app, &password_key(&stream.id)).ok().flatten().unwrap_or_default();
+        }
+        StreamsState {
+            streams: Mutex::new(streams),
+            status: Mutex::new(HashMap::new()),
+            relays: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn start(app: AppHandle) {
+        start_streams(app);
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn password_key(id: &str) -> String {
+    format!("streams/{}", id)
+}
+
+/// Save streams with passwords sealed in the keyring rather than the JSON file
+fn save(app: &AppHandle, streams: &[CameraStream]) -> Result<(), String> {
+    for stream in streams.iter().filter(|stream| !stream.password.is_empty()) {
+        keyring::put(app, &password_key(&stream.id), &stream.password)?;
+    }
+    let stripped: Vec<CameraStream> = streams
+        .iter()
+        .cloned()
+        .map(|stream| CameraStream {
+            password: String::new(),
+            ..stream
+        })
+        .collect();
+    store::save(app, STREAMS_FILE, &stripped)
+}
+
+fn find(state: &StreamsState, id: &str) -> Result<CameraStream, KioskError> {
+    let streams = state.streams.lock().expect("streams lock");
+    streams
+        .iter()
+        .find(|stream| stream.id == id)
+        .cloned()
+        .ok_or_else(|| KioskError::not_found(format!("Stream not found: {}", id)))
+}
+
+fn validate(stream: &CameraStream) -> Result<(), KioskError> {
+    if stream.name.trim().is_empty() {
+        return Err(KioskError::invalid("The stream needs a name"));
+    }
+    let url = Url::parse(stream.url.trim()).map_err(|e| KioskError::invalid(format!("Invalid URL: {}", e)))?;
+    if !matches!(url.scheme(), "rtsp" | "rtsps" | "http" | "https") {
+        return Err(KioskError::invalid("Stream URLs must be rtsp://, rtsps://, http:// or https://"));
+    }
+    if url.host_str().map_or(true, str::is_empty) {
+        return Err(KioskError::invalid("The stream URL has no host"));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(KioskError::invalid("Give the credentials as the username and password, not in the URL"));
+    }
+    Ok(())
+}
+
+/// The URL ffmpeg opens, with the credentials in it
+fn source_url(stream: &CameraStream) -> Result<Url, String> {
+    let mut url = Url::parse(stream.url.trim()).map_err(|e| format!("Invalid URL: {}", e))?;
+    if let Some(username) = stream.username.as_deref().filter(|username| !username.is_empty()) {
+        url.set_username(username).map_err(|_| "The stream URL cannot take a username".to_string())?;
+        if !stream.password.is_empty() {
+            url.set_password(Some(&stream.password)).map_err(|_| "The stream URL cannot take a password".to_string())?;
+        }
+    }
+    Ok(url)
+}
+
+fn input_args(stream: &CameraStream) -> Result<Vec<String>, String> {
+    let url = source_url(stream)?;
+    let mut args = Vec::new();
+    if url.scheme().starts_with("rtsp") {
+        args.extend(["-rtsp_transport".to_string(), stream.transport.as_str().to_string()]);
+    }
+    args.extend(["-i".to_string(), url.to_string()]);
+    Ok(args)
+}
+
+/// ffmpeg echoes the URL in its errors; keep the password out of them
+fn redact(message: &str, stream: &CameraStream) -> String {
+    let encoded = source_url(stream).ok().and_then(|url| url.password().map(str::to_string));
+    let mut message = message.to_string();
+    for secret in [Some(stream.password.clone()), encoded].into_iter().flatten().filter(|s| !s.is_empty()) {
+        message = message.replace(&secret, "***");
+    }
+    message
+}
+
+/// The last thing ffmpeg complained about
+fn last_error(text: &str, stream: &CameraStream) -> Option<String> {
+    text.lines().rev().map(str::trim).find(|line| !line.is_empty()).map(|line| redact(line, stream))
+}
+
+/// Run an ffmpeg tool against a stream, giving up after `CONNECT_TIMEOUT`
+fn run(program: &str, args: &[String], stream: &CameraStream) -> Result<Output, String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    while matches!(child.try_wait(), Ok(None)) {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err("Timed out connecting to the stream".to_string());
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let error = last_error(&String::from_utf8_lossy(&output.stderr), stream);
+        return Err(error.unwrap_or_else(|| format!("{} failed", program)));
+    }
+    Ok(output)
+}
+
+/// ffprobe's `25/1` frame rates; `0/0` when unknown
+fn frame_rate(value: &Value) -> Option<f64> {
+    let (numerator, denominator) = value.as_str()?.split_once('/')?;
+    let (numerator, denominator): (f64, f64) = (numerator.parse().ok()?, denominator.parse().ok()?);
+    (numerator > 0.0 && denominator > 0.0).then(|| (numerator / denominator * 100.0).round() / 100.0)
+}
+
+fn parse_probe(json: &str) -> StreamInfo {
+    let probe: Value = serde_json::from_str(json).unwrap_or_default();
+    let streams = probe["streams"].as_array().cloned().unwrap_or_default();
+    let of_type = |kind: &str| streams.iter().find(|stream| stream["codec_type"] == kind);
+    let mut info = StreamInfo::default();
+    if let Some(video) = of_type("video") {
+        info.video_codec = video["codec_name"].as_str().map(str::to_string);
+        info.width = video["width"].as_u64().map(|width| width as u32);
+        info.height = video["height"].as_u64().map(|height| height as u32);
+        info.fps = frame_rate(&video["avg_frame_rate"]).or_else(|| frame_rate(&video["r_frame_rate"]));
+    }
+    info.audio_codec = of_type("audio").and_then(|audio| audio["codec_name"].as_str().map(str::to_string));
+    info
+}
+
+/// Connect to a stream and read what it carries, timing it
+fn probe(stream: &CameraStream) -> (Result<StreamInfo, String>, Duration) {
+    let started = Instant::now();
+    let result = input_args(stream).and_then(|input| {
+        let mut args: Vec<String> = ["-v", "error"].iter().map(|arg| arg.to_string()).collect();
+        args.extend(input);
+        args.extend(["-show_entries", "stream=codec_type,codec_name,width,height,avg_frame_rate,r_frame_rate"]
+            .iter()
+            .map(|arg| arg.to_string()));
+        args.extend(["-of".to_string(), "json".to_string()]);
+        run("ffprobe", &args, stream)
+    });
+    let result = result.and_then(|output| {
+        let info = parse_probe(&String::from_utf8_lossy(&output.stdout));
+        match info.video_codec {
+            Some(_) => Ok(info),
+            None => Err("The stream has no video".to_string()),
+        }
+    });
+    (result, started.elapsed())
+}
+
+/// Record a probe's outcome, publishing `stream-status` when the stream
+/// goes online or offline
+fn record(app: &AppHandle, state: &StreamsState, id: &str, result: Result<StreamInfo, String>, elapsed: Duration) {
+    let now = Local::now().timestamp();
+    let status = {
+        let mut statuses = state.status.lock().expect("stream status lock");
+        let status = statuses.entry(id.to_string()).or_insert_with(|| unknown(id));
+        let availability = if result.is_ok() { Availability::Online } else { Availability::Offline };
+        let changed = status.availability != availability;
+        if changed {
+            status.since = Some(now);
+        }
+        status.availability = availability;
+        status.checked_at = Some(now);
+        match result {
+            Ok(info) => {
+                status.info = Some(info);
+                status.latency_ms = Some(elapsed.as_millis() as u64);
+                status.error = None;
+            }
+            Err(e) => {
+                status.latency_ms = None;
+                status.error = Some(e);
+            }
+        }
+        changed.then(|| status.clone())
+    };
+    if let Some(status) = status {
+        events::publish(app, "stream-status", with_relay(app, state, status));
+    }
+}
+
+fn unknown(id: &str) -> StreamStatus {
+    StreamStatus {
+        id: id.to_string(),
+        availability: Availability::Unknown,
+        info: None,
+        latency_ms: None,
+        checked_at: None,
+        since: None,
+        error: None,
+        relay: None,
+    }
+}
+
+fn probe_all(app: &AppHandle, state: &StreamsState) {
+    let streams = state.streams.lock().expect("streams lock").clone();
+    // Probed side by side so one dead camera does not hold up the rest
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let probes: Vec<_> = streams.iter().map(|stream| scope.spawn(move || probe(stream))).collect();
+        probes.into_iter().map(|probe| probe.join()).collect()
+    });
+    for (stream, result) in streams.iter().zip(results) {
+        if let Ok((result, elapsed)) = result {
+            record(app, state, &stream.id, result, elapsed);
+        }
+    }
+}
+
+// ============================================================================
+// Relays
+// ============================================================================
+
+fn relay_dir(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    store::data_path(app, &format!("{}/{}", RELAY_DIR, id))
+}
+
+fn spawn_relay(app: &AppHandle, stream: &CameraStream) -> Result<Child, String> {
+    let dir = relay_dir(app, &stream.id)?;
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let log = fs::File::create(dir.join(RELAY_LOG)).map_err(|e| e.to_string())?;
+    let segments = dir.join("segment-%05d.ts").to_string_lossy().to_string();
+    let playlist = dir.join(PLAYLIST).to_string_lossy().to_string();
+    Command::new("ffmpeg")
+        .args(["-loglevel", "error", "-nostdin"])
+        .args(input_args(stream)?)
+        // Video is copied as it is; audio, when there is any, becomes AAC
+        .args(["-map", "0:v:0", "-map", "0:a:0?", "-c:v", "copy", "-c:a", "aac", "-f", "hls"])
+        .args(["-hls_time", SEGMENT_SECONDS, "-hls_list_size", PLAYLIST_SEGMENTS])
+        .args(["-hls_flags", "delete_segments+omit_endlist", "-hls_segment_filename", &segments, &playlist])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(log)
+        .spawn()
+        .map_err(|e| format!("Failed to run ffmpeg: {}", e))
+}
+
+fn relay_status(app: &AppHandle, id: &str, relay: &mut Relay) -> RelayStatus {
+    let running = relay.child.as_mut().is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+    let ready = running && relay_dir(app, id).is_ok_and(|dir| dir.join(PLAYLIST).exists());
+    RelayStatus {
+        playlist_url: format!("stream://localhost/{}/{}", id, PLAYLIST),
+        running,
+        ready,
+        started_at: relay.started_at,
+        restarts: relay.restarts,
+        error: relay.error.clone(),
+    }
+}
+
+fn with_relay(app: &AppHandle, state: &StreamsState, mut status: StreamStatus) -> StreamStatus {
+    let mut relays = state.relays.lock().expect("stream relays lock");
+    status.relay = relays.get_mut(&status.id).map(|relay| relay_status(app, &status.id, relay));
+    status
+}
+
+fn stop_relay(app: &AppHandle, state: &StreamsState, id: &str) {
+    let relay = state.relays.lock().expect("stream relays lock").remove(id);
+    if let Some(mut child) = relay.and_then(|relay| relay.child) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    if let Ok(dir) = relay_dir(app, id) {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
+
+/// Start relays configured to always run, and restart any that exited
+fn supervise(app: &AppHandle, state: &StreamsState) {
+    let streams = state.streams.lock().expect("streams lock").clone();
+    let mut relays = state.relays.lock().expect("stream relays lock");
+    for stream in streams.iter().filter(|stream| stream.relay) {
+        relays.entry(stream.id.clone()).or_insert_with(|| Relay {
+            child: None,
+            // Due a start straight away
+            started: Instant::now() - RELAY_RETRY,
+            started_at: 0,
+            restarts: 0,
+            error: None,
+        });
+    }
+
+    let mut changed = Vec::new();
+    for (id, relay) in relays.iter_mut() {
+        let Some(stream) = streams.iter().find(|stream| &stream.id == id) else { continue };
+        if let Some(child) = relay.child.as_mut() {
+            if matches!(child.try_wait(), Ok(None)) {
+                continue;
+            }
+            let log = relay_dir(app, id).ok().and_then(|dir| fs::read_to_string(dir.join(RELAY_LOG)).ok());
+            let error = log.and_then(|log| last_error(&log, stream));
+            relay.error = Some(error.unwrap_or_else(|| "ffmpeg exited".to_string()));
+            relay.child = None;
+            relay.restarts += 1;
+            changed.push(id.clone());
+        }
+        if relay.started.elapsed() < RELAY_RETRY {
+            continue;
+        }
+        relay.started = Instant::now();
+        relay.started_at = Local::now().timestamp();
+        match spawn_relay(app, stream) {
+            Ok(child) => relay.child = Some(child),
+            Err(e) => relay.error = Some(e),
+        }
+    }
+    drop(relays);
+
+    for id in changed {
+        let status = state.status.lock().expect("stream status lock").get(&id).cloned();
+        events::publish(app, "stream-status", with_relay(app, state, status.unwrap_or_else(|| unknown(&id))));
+    }
+}
+
+/// Check streams and look after relays in the background
+fn start_streams(app: AppHandle) {
+    std::thread::spawn(move || {
+        let Some(state) = lazy::loaded::<StreamsState>(&app) else { return };
+        for round in 0u64.. {
+            if round % PROBE_ROUNDS == 0 {
+                probe_all(&app, state);
+            }
+            supervise(&app, state);
+            std::thread::sleep(SUPERVISE_INTERVAL);
+        }
+    });
+}
+
+/// Load streams at startup when a relay is set to always run
+pub fn start_on_launch(app: &AppHandle) {
+    let streams: Vec<CameraStream> = store::load(app, STREAMS_FILE);
+    if streams.iter().any(|stream| stream.relay) {
+        let _ = lazy::get::<StreamsState>(app);
+    }
+}
+
+// ============================================================================
+// Protocol
+// ============================================================================
+
+fn respond(status: StatusCode, mime: &str, body: Vec<u8>) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", mime)
+        .header("Cache-Control", "no-cache")
+        // Players fetch segments from the app's own origin
+        .header("Access-Control-Allow-Origin", "*")
+        .body(body)
+        .expect("valid stream response")
+}
+
+/// Serve relay playlists and segments: `stream://localhost/<id>/index.m3u8`
+pub fn protocol(ctx: UriSchemeContext<'_, tauri::Wry>, request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let not_found = || respond(StatusCode::NOT_FOUND, "text/plain", b"Stream not found".to_vec());
+    let requested = request.uri().path().trim_start_matches('/').to_string();
+    let relative = Path::new(&requested);
+    let parts: Vec<Component> = relative.components().collect();
+    // Only "<id>/<file>"; no "..", roots or deeper paths
+    if parts.len() != 2 || !parts.iter().all(|part| matches!(part, Component::Normal(_))) {
+        return respond(StatusCode::FORBIDDEN, "text/plain", b"Forbidden".to_vec());
+    }
+    let mime = match relative.extension().and_then(|extension| extension.to_str()) {
+        Some("m3u8") => "application/vnd.apple.mpegurl",
+        Some("ts") => "video/mp2t",
+        _ => return not_found(),
+    };
+    let Ok(root) = store::data_path(ctx.app_handle(), RELAY_DIR) else { return not_found() };
+    match fs::read(root.join(relative)) {
+        Ok(data) => respond(StatusCode::OK, mime, data),
+        Err(_) => not_found(),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List the configured streams; passwords are blank
+#[tauri::command]
+pub fn list_streams(app: AppHandle) -> Result<Vec<CameraStream>, KioskError> {
+    let state = lazy::get::<StreamsState>(&app)?;
+    Ok(state
+        .streams
+        .lock()
+        .expect("streams lock")
+        .iter()
+        .cloned()
+        .map(|stream| CameraStream {
+            password: String::new(),
+            ..stream
+        })
+        .collect())
+}
+
+/// Add or update a stream, returning its id. An empty password keeps the
+/// stored one.
+#[tauri::command]
+pub fn save_stream(app: AppHandle, auth: State<'_, AuthState>, mut stream: CameraStream) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let state = lazy::get::<StreamsState>(&app)?;
+    validate(&stream)?;
+    stream.name = stream.name.trim().to_string();
+    stream.url = stream.url.trim().to_string();
+
+    let mut streams = state.streams.lock().expect("streams lock");
+    let restart = match streams.iter_mut().find(|existing| existing.id == stream.id && !stream.id.is_empty()) {
+        Some(existing) => {
+            if stream.password.is_empty() {
+                stream.password = std::mem::take(&mut existing.password);
+            }
+            let moved = existing.url != stream.url || existing.username != stream.username;
+            let restart = moved || existing.transport != stream.transport || existing.relay != stream.relay;
+            *existing = stream.clone();
+            restart
+        }
+        None => {
+            stream.id = uuid::Uuid::new_v4().to_string();
+            streams.push(stream.clone());
+            false
+        }
+    };
+    save(&app, &streams)?;
+    drop(streams);
+
+    if restart {
+        stop_relay(&app, state, &stream.id);
+    }
+    state.status.lock().expect("stream status lock").remove(&stream.id);
+    Ok(stream.id)
+}
+
+/// Remove a stream, stopping its relay
+#[tauri::command]
+pub fn delete_stream(app: AppHandle, auth: State<'_, AuthState>, id: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let state = lazy::get::<StreamsState>(&app)?;
+    let mut streams = state.streams.lock().expect("streams lock");
+    streams.retain(|stream| stream.id != id);
+    save(&app, &streams)?;
+    drop(streams);
+
+    stop_relay(&app, state, &id);
+    state.status.lock().expect("stream status lock").remove(&id);
+    keyring::remove(&app, &password_key(&id))?;
+    Ok(())
+}
+
+/// Every stream's last known status, in the configured order
+#[tauri::command]
+pub fn get_stream_status(app: AppHandle) -> Result<Vec<StreamStatus>, KioskError> {
+    let state = lazy::get::<StreamsState>(&app)?;
+    let ids: Vec<String> = state.streams.lock().expect("streams lock").iter().map(|stream| stream.id.clone()).collect();
+    let statuses = state.status.lock().expect("stream status lock").clone();
+    Ok(ids
+        .iter()
+        .map(|id| with_relay(&app, state, statuses.get(id).cloned().unwrap_or_else(|| unknown(id))))
+        .collect())
+}
+
+/// Check a stream now rather than waiting for the next round
+#[tauri::command(async)]
+pub fn probe_stream(app: AppHandle, id: String) -> Result<StreamStatus, KioskError> {
+    let state = lazy::get::<StreamsState>(&app)?;
+    let stream = find(state, &id)?;
+    let (result, elapsed) = probe(&stream);
+    record(&app, state, &id, result, elapsed);
+    let status = state.status.lock().expect("stream status lock").get(&id).cloned();
+    Ok(with_relay(&app, state, status.unwrap_or_else(|| unknown(&id))))
+}
+
+/// Grab a JPEG frame from a stream, and save it to a virtual path if one is
+/// given (operators only)
+#[tauri::command(async)]
+pub fn stream_snapshot(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    id: String,
+    path: Option<String>,
+) -> Result<StreamSnapshot, KioskError> {
+    if path.is_some() {
+        auth::require(&auth, Role::Operator)?;
+    }
+    let stream = find(lazy::get::<StreamsState>(&app)?, &id)?;
+    let started = Instant::now();
+    let mut args: Vec<String> = ["-loglevel", "error", "-nostdin"].iter().map(|arg| arg.to_string()).collect();
+    args.extend(input_args(&stream)?);
+    let output = ["-frames:v", "1", "-f", "image2pipe", "-c:v", "mjpeg", "-q:v", "3", "-"];
+    args.extend(output.iter().map(|arg| arg.to_string()));
+    let jpeg = run("ffmpeg", &args, &stream)?.stdout;
+    if jpeg.is_empty() {
+        return Err("The stream gave no picture".to_string().into());
+    }
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    if let Some(path) = &path {
+        fs::write(vfs::resolve_write(&app, path, jpeg.len() as u64)?, &jpeg).map_err(|e| e.to_string())?;
+        vfs::changed(&app, path, "write");
+    }
+    Ok(StreamSnapshot {
+        data_url: format!("data:image/jpeg;base64,{}", base64::engine::general_purpose::STANDARD.encode(&jpeg)),
+        path,
+        latency_ms,
+        taken_at: Local::now().timestamp(),
+    })
+}
+
+/// Start relaying a stream to HLS for the webview, if it is not already;
+/// the playlist is ready once `ready` is set
+#[tauri::command]
+pub fn start_stream_relay(app: AppHandle, id: String) -> Result<RelayStatus, KioskError> {
+    let state = lazy::get::<StreamsState>(&app)?;
+    let stream = find(state, &id)?;
+    let mut relays = state.relays.lock().expect("stream relays lock");
+    if let Some(relay) = relays.get_mut(&id) {
+        return Ok(relay_status(&app, &id, relay));
+    }
+    let child = spawn_relay(&app, &stream)?;
+    let relay = relays.entry(id.clone()).or_insert(Relay {
+        child: Some(child),
+        started: Instant::now(),
+        started_at: Local::now().timestamp(),
+        restarts: 0,
+        error: None,
+    });
+    Ok(relay_status(&app, &id, relay))
+}
+
+/// Stop relaying a stream; relays configured to always run start again
+#[tauri::command]
+pub fn stop_stream_relay(app: AppHandle, id: String) -> Result<(), KioskError> {
+    let state = lazy::get::<StreamsState>(&app)?;
+    find(state, &id)?;
+    stop_relay(&app, state, &id);
+    Ok(())
+}
@@ -0,0 +1,441 @@
+//! Structured config files
+//!
+//! Reads and writes JSON and YAML files for the admin panel's config
+//! editor. The text is edited as a whole and written back exactly as given,
+//! so comments and layout in YAML survive, but only after it parses and
+//! passes the file's schema; `validate_structured_file` reports the same
+//! problems while the admin is still typing. Files are replaced atomically,
+//! keeping their permissions.
+//!
+//! Paths are virtual, or absolute for system files on the admin's list in
+//! `config-files.json`. The schema comes from that list or from a
+//! `<file>.schema.json` next to the file. Schemas use a subset of JSON
+//! Schema: `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `items`, `minItems`, `maxItems`, `minimum`,
+//! `maximum`, `minLength` and `maxLength`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{recents, store};
+use crate::vfs::{self, Access};
+
+const CONFIG_FILES_FILE: &str = "config-files.json";
+
+/// Files over this size are not opened in the editor
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Validation stops after this many problems
+const MAX_ERRORS: usize = 100;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StructuredFormat {
+    Json,
+    Yaml,
+}
+
+/// A system file the admin panel may edit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFileEntry {
+    /// Absolute path
+    pub path: String,
+    #[serde(default)]
+    pub label: String,
+    /// JSON Schema the contents must pass
+    #[serde(default)]
+    pub schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigFilesPolicy {
+    pub files: Vec<ConfigFileEntry>,
+}
+
+/// A parse error or schema violation
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuredError {
+    /// JSON Pointer to the offending value; empty for the whole document
+    pub pointer: String,
+    pub message: String,
+    /// 1-based position of a parse error
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuredFile {
+    pub path: String,
+    pub format: StructuredFormat,
+    /// The text as on disk
+    pub content: String,
+    /// The parsed document; null when it does not parse
+    pub value: Value,
+    pub schema: Option<Value>,
+    pub errors: Vec<StructuredError>,
+}
+
+pub struct ConfigFilesState(Mutex<ConfigFilesPolicy>);
+
+impl ConfigFilesState {
+    pub fn load(app: &AppHandle) -> Self {
+        ConfigFilesState(Mutex::new(store::load(app, CONFIG_FILES_FILE)))
+    }
+}
+
+// ============================================================================
+// Schema Validation
+// ============================================================================
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_i64() || number.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, wanted: &str) -> bool {
+    let actual = type_name(value);
+    actual == wanted
+        || (wanted == "number" && actual == "integer")
+        || (wanted == "integer" && value.as_f64().is_some_and(|number| number.fract() == 0.0))
+}
+
+/// Escape a key for a JSON Pointer
+fn pointer_part(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+fn validate(value: &Value, schema: &Value, pointer: &str, errors: &mut Vec<StructuredError>) {
+    if errors.len() >= MAX_ERRORS {
+        return;
+    }
+    let Some(schema) = schema.as_object() else {
+        if schema == &Value::Bool(false) {
+            errors.push(schema_error(pointer, "No value is allowed here".to_string()));
+        }
+        return;
+    };
+    let mut fail = |message: String| errors.push(schema_error(pointer, message));
+
+    if let Some(wanted) = schema.get("type") {
+        let types: Vec<&str> = match wanted {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            fail(format!("Expected {}, found {}", types.join(" or "), type_name(value)));
+            return;
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            let options: Vec<String> = options.iter().map(Value::to_string).collect();
+            fail(format!("Must be one of {}", options.join(", ")));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            fail(format!("Must be {}", expected));
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+            if number < minimum {
+                fail(format!("Must be at least {}", minimum));
+            }
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+            if number > maximum {
+                fail(format!("Must be at most {}", maximum));
+            }
+        }
+    }
+    if let Some(text) = value.as_str() {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                fail(format!("Must be at least {} characters", min));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                fail(format!("Must be at most {} characters", max));
+            }
+        }
+    }
+
+    if let Some(items) = value.as_array() {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if count < min {
+                fail(format!("Needs at least {} items", min));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if count > max {
+                fail(format!("Allows at most {} items", max));
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (index, item) in items.iter().enumerate() {
+                validate(item, item_schema, &format!("{}/{}", pointer, index), errors);
+            }
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(key) = required.as_str().filter(|key| !object.contains_key(*key)) {
+                errors.push(schema_error(pointer, format!("Missing required key \"{}\"", key)));
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, item) in object {
+            let child = format!("{}/{}", pointer, pointer_part(key));
+            match (properties.and_then(|properties| properties.get(key)), schema.get("additionalProperties")) {
+                (Some(property), _) => validate(item, property, &child, errors),
+                (None, Some(Value::Bool(false))) => {
+                    errors.push(schema_error(&child, format!("Unknown key \"{}\"", key)));
+                }
+                (None, Some(additional)) => validate(item, additional, &child, errors),
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+fn schema_error(pointer: &str, message: String) -> StructuredError {
+    StructuredError {
+        pointer: pointer.to_string(),
+        message,
+        line: None,
+        column: None,
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn format_of(path: &Path) -> StructuredFormat {
+    let extension = path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("yaml") | Some("yml") => StructuredFormat::Yaml,
+        _ => StructuredFormat::Json,
+    }
+}
+
+fn parse(content: &str, format: StructuredFormat) -> Result<Value, StructuredError> {
+    match format {
+        StructuredFormat::Json => serde_json::from_str(content).map_err(|e| StructuredError {
+            pointer: String::new(),
+            message: e.to_string(),
+            line: Some(e.line()),
+            column: Some(e.column()),
+        }),
+        StructuredFormat::Yaml => serde_yaml::from_str(content).map_err(|e| StructuredError {
+            pointer: String::new(),
+            message: e.to_string(),
+            line: e.location().map(|location| location.line()),
+            column: e.location().map(|location| location.column()),
+        }),
+    }
+}
+
+/// Parse and validate, returning the document (null when it does not
+/// parse) and its problems
+fn check(content: &str, format: StructuredFormat, schema: Option<&Value>) -> (Value, Vec<StructuredError>) {
+    match parse(content, format) {
+        Ok(value) => {
+            let mut errors = Vec::new();
+            if let Some(schema) = schema {
+                validate(&value, schema, "", &mut errors);
+            }
+            (value, errors)
+        }
+        Err(error) => (Value::Null, vec![error]),
+    }
+}
+
+/// The real path of a virtual path, or of an absolute path on the admin's
+/// list, and the schema that applies
+fn resolve(
+    app: &AppHandle,
+    auth: &AuthState,
+    policy: &ConfigFilesState,
+    path: &str,
+    access: Access,
+) -> Result<(PathBuf, Option<Value>), KioskError> {
+    let (real, schema) = if Path::new(path).is_absolute() {
+        auth::require(auth, Role::Admin)?;
+        let policy = policy.0.lock().expect("config files lock");
+        let entry = policy
+            .files
+            .iter()
+            .find(|entry| Path::new(&entry.path) == Path::new(path))
+            .ok_or_else(|| KioskError::new(ErrorKind::Denied, format!("{} is not an editable config file", path)))?;
+        (PathBuf::from(path), entry.schema.clone())
+    } else {
+        (vfs::resolve(app, path, access)?, None)
+    };
+    if schema.is_some() {
+        return Ok((real, schema));
+    }
+    let name = real.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let sibling = real.with_file_name(format!("{}.schema.json", name));
+    let schema = match fs::read_to_string(&sibling) {
+        Ok(text) => Some(
+            serde_json::from_str(&text)
+                .map_err(|e| KioskError::invalid(format!("Invalid schema {}: {}", sibling.display(), e)))?,
+        ),
+        Err(_) => None,
+    };
+    Ok((real, schema))
+}
+
+/// Replace a file through a temporary copy, keeping its permissions
+fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let tmp = path.with_file_name(format!(".{}.tmp", name));
+    fs::write(&tmp, content).map_err(|e| e.to_string())?;
+    if let Ok(metadata) = fs::metadata(path) {
+        let _ = fs::set_permissions(&tmp, metadata.permissions());
+    }
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        e.to_string()
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Open a JSON or YAML file with its schema and any problems it has
+#[tauri::command]
+pub fn read_structured_file(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    policy: State<'_, ConfigFilesState>,
+    path: String,
+) -> Result<StructuredFile, KioskError> {
+    let (real, schema) = resolve(&app, &auth, &policy, &path, Access::Read)?;
+    if fs::metadata(&real)?.len() > MAX_FILE_BYTES {
+        return Err(KioskError::invalid("The file is too large to edit"));
+    }
+    let content = fs::read_to_string(&real)?;
+    let format = format_of(&real);
+    let (value, errors) = check(&content, format, schema.as_ref());
+    if !Path::new(&path).is_absolute() {
+        let _ = recents::record(&app, "editor", &path);
+    }
+    Ok(StructuredFile {
+        path,
+        format,
+        content,
+        value,
+        schema,
+        errors,
+    })
+}
+
+/// Problems `content` would have as the file at `path`, without writing it
+#[tauri::command]
+pub fn validate_structured_file(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    policy: State<'_, ConfigFilesState>,
+    path: String,
+    content: String,
+) -> Result<Vec<StructuredError>, KioskError> {
+    let (real, schema) = resolve(&app, &auth, &policy, &path, Access::Read)?;
+    Ok(check(&content, format_of(&real), schema.as_ref()).1)
+}
+
+/// Write `content` as is once it parses and passes the schema
+#[tauri::command]
+pub fn write_structured_file(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    policy: State<'_, ConfigFilesState>,
+    path: String,
+    content: String,
+) -> Result<StructuredFile, KioskError> {
+    let (real, schema) = resolve(&app, &auth, &policy, &path, Access::Write)?;
+    let format = format_of(&real);
+    let (value, errors) = check(&content, format, schema.as_ref());
+    if let Some(first) = errors.first() {
+        let at = if first.pointer.is_empty() {
+            String::new()
+        } else {
+            format!(" at {}", first.pointer)
+        };
+        return Err(KioskError::invalid(format!(
+            "Not saved, {} problem(s): {}{}",
+            errors.len(),
+            first.message,
+            at
+        )));
+    }
+    if !Path::new(&path).is_absolute() {
+        vfs::resolve_write(&app, &path, content.len() as u64)?;
+    }
+    write_atomic(&real, &content)?;
+    if !Path::new(&path).is_absolute() {
+        let _ = recents::record(&app, "editor", &path);
+    }
+    Ok(StructuredFile {
+        path,
+        format,
+        content,
+        value,
+        schema,
+        errors,
+    })
+}
+
+#[tauri::command]
+pub fn get_config_files(
+    auth: State<'_, AuthState>,
+    policy: State<'_, ConfigFilesState>,
+) -> Result<ConfigFilesPolicy, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    Ok(policy.0.lock().expect("config files lock").clone())
+}
+
+/// Replace the list of system files the editor may open (admin)
+#[tauri::command]
+pub fn set_config_files(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    policy: State<'_, ConfigFilesState>,
+    files: Vec<ConfigFileEntry>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if let Some(entry) = files.iter().find(|entry| !Path::new(&entry.path).is_absolute()) {
+        return Err(KioskError::invalid(format!("Not an absolute path: {}", entry.path)));
+    }
+    let updated = ConfigFilesPolicy { files };
+    store::save(&app, CONFIG_FILES_FILE, &updated)?;
+    *policy.0.lock().expect("config files lock") = updated;
+    Ok(())
+}
@@ -0,0 +1,439 @@
+//! App supervisor
+//!
+//! Launches helper apps as child processes and watches them: stdout and
+//! stderr go to a per-app log in the data directory, exits are published as
+//! `app-exited` (and `app-crashed` for failures, which the rules engine can
+//! act on), and apps with auto-restart enabled are started again with an
+//! increasing delay up to a restart limit.
+//!
+//! Launching, removing and auto-restart settings are for admins. Only
+//! programs on an allow-list of absolute paths (`app-programs.json`), kept
+//! by admins, can be launched, and loader variables (`LD_*`, `PATH`) are
+//! dropped from the environment an app is given.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::{events, store};
+
+const PROGRAMS_FILE: &str = "app-programs.json";
+
+const LOG_DIR: &str = "app-logs";
+
+/// Logs are rotated to `<id>.log.1` past this size
+const LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Restart delay doubles per consecutive crash up to this
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchSpec {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Start the app again when it exits with a failure
+    #[serde(default)]
+    pub auto_restart: bool,
+    /// Consecutive automatic restarts before giving up
+    #[serde(default = "default_restart_limit")]
+    pub restart_limit: u32,
+    #[serde(default = "default_restart_delay")]
+    pub restart_delay_secs: u64,
+}
+
+fn default_restart_limit() -> u32 {
+    5
+}
+
+fn default_restart_delay() -> u64 {
+    2
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppProcess {
+    pub id: String,
+    pub spec: LaunchSpec,
+    pub pid: Option<u32>,
+    pub running: bool,
+    /// Automatic restarts since the last manual start
+    pub restarts: u32,
+    pub last_exit_code: Option<i32>,
+    pub last_signal: Option<i32>,
+    pub error: Option<String>,
+    pub started_at: Option<i64>,
+}
+
+/// Payload of `app-exited` and `app-crashed`
+#[derive(Debug, Clone, Serialize)]
+pub struct AppExit {
+    pub id: String,
+    pub name: String,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    pub will_restart: bool,
+}
+
+struct Managed {
+    status: Mutex<AppProcess>,
+    /// Set by `stop_app`; the monitor exits instead of restarting
+    stop: AtomicBool,
+    /// Set by `restart_app`; the next exit restarts immediately
+    restart: AtomicBool,
+}
+
+pub struct Supervisor {
+    apps: Mutex<HashMap<String, Arc<Managed>>>,
+    /// Programs apps may be launched from
+    programs: Mutex<Vec<String>>,
+}
+
+impl Supervisor {
+    pub fn load(app: &AppHandle) -> Self {
+        Supervisor {
+            apps: Mutex::new(HashMap::new()),
+            programs: Mutex::new(store::load(app, PROGRAMS_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn log_file(id: &str) -> String {
+    format!("{}/{}.log", LOG_DIR, id)
+}
+
+fn open_log(app: &AppHandle, id: &str) -> Result<File, String> {
+    fs::create_dir_all(store::data_path(app, LOG_DIR)?).map_err(|e| e.to_string())?;
+    let path = store::data_path(app, &log_file(id))?;
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() > LOG_MAX_BYTES) {
+        let _ = fs::rename(&path, path.with_extension("log.1"));
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| e.to_string())
+}
+
+/// Copy a child's output stream into the log line by line
+fn capture(stream: impl Read + Send + 'static, log: Arc<Mutex<File>>, tag: &'static str) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            let mut log = log.lock().expect("app log lock");
+            let _ = writeln!(log, "{} [{}] {}", Local::now().format("%Y-%m-%d %H:%M:%S"), tag, line);
+        }
+    });
+}
+
+fn program_allowed(app: &AppHandle, program: &str) -> bool {
+    let state = app.state::<Supervisor>();
+    let programs = state.programs.lock().expect("app programs lock");
+    programs.iter().any(|allowed| allowed == program)
+}
+
+/// Variables that change which code the dynamic loader or a shell runs
+fn loader_var(name: &str) -> bool {
+    name.starts_with("LD_") || name == "PATH"
+}
+
+fn spawn_child(app: &AppHandle, id: &str, spec: &LaunchSpec) -> Result<Child, String> {
+    // Checked again on every restart, in case the program was taken off the list
+    if !program_allowed(app, &spec.command) {
+        return Err(format!("{} is not an allowed app program", spec.command));
+    }
+    let mut command = Command::new(&spec.command);
+    command
+        .args(&spec.args)
+        .envs(spec.env.iter().filter(|(name, _)| !loader_var(name)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Own process group so stopping the app also stops its children
+        .process_group(0);
+    if let Some(cwd) = &spec.cwd {
+        command.current_dir(cwd);
+    }
+    let mut child = command.spawn().map_err(|e| format!("{}: {}", spec.command, e))?;
+
+    let log = Arc::new(Mutex::new(open_log(app, id)?));
+    if let Some(stdout) = child.stdout.take() {
+        capture(stdout, log.clone(), "out");
+    }
+    if let Some(stderr) = child.stderr.take() {
+        capture(stderr, log, "err");
+    }
+    Ok(child)
+}
+
+fn signal_group(pid: u32, signal: i32) {
+    // SAFETY: kill has no memory safety requirements; a negative pid targets the group
+    unsafe { libc::kill(-(pid as i32), signal) };
+}
+
+/// Run an app until it is stopped or stays down, restarting it as configured
+fn monitor(app: AppHandle, managed: Arc<Managed>) {
+    loop {
+        let (id, spec) = {
+            let status = managed.status.lock().expect("app status lock");
+            (status.id.clone(), status.spec.clone())
+        };
+
+        let mut child = match spawn_child(&app, &id, &spec) {
+            Ok(child) => child,
+            Err(e) => {
+                let mut status = managed.status.lock().expect("app status lock");
+                status.running = false;
+                status.error = Some(e);
+                return;
+            }
+        };
+        {
+            let mut status = managed.status.lock().expect("app status lock");
+            status.pid = Some(child.id());
+            status.running = true;
+            status.error = None;
+            status.started_at = Some(Local::now().timestamp());
+        }
+        events::publish(&app, "app-started", &id);
+
+        let exit = child.wait();
+        let (code, signal) = match &exit {
+            Ok(exit) => (exit.code(), exit.signal()),
+            Err(_) => (None, None),
+        };
+        let failed = !exit.as_ref().is_ok_and(|exit| exit.success());
+        let stopping = managed.stop.load(Ordering::Relaxed);
+        let restart_requested = managed.restart.swap(false, Ordering::Relaxed);
+
+        let (restarts, will_restart) = {
+            let mut status = managed.status.lock().expect("app status lock");
+            status.pid = None;
+            status.running = false;
+            status.last_exit_code = code;
+            status.last_signal = signal;
+            let will_restart = !stopping
+                && (restart_requested || (failed && spec.auto_restart && status.restarts < spec.restart_limit));
+            if will_restart && !restart_requested {
+                status.restarts += 1;
+            }
+            (status.restarts, will_restart)
+        };
+
+        let exited = AppExit {
+            id,
+            name: spec.name.clone(),
+            code,
+            signal,
+            will_restart,
+        };
+        events::publish(&app, "app-exited", &exited);
+        if failed && !stopping && !restart_requested {
+            events::publish(&app, "app-crashed", &exited);
+        }
+
+        if !will_restart {
+            return;
+        }
+        if !restart_requested {
+            let delay = Duration::from_secs(spec.restart_delay_secs << restarts.saturating_sub(1).min(6));
+            std::thread::sleep(delay.min(MAX_RESTART_DELAY));
+            if managed.stop.load(Ordering::Relaxed) {
+                return;
+            }
+        }
+    }
+}
+
+fn start(app: &AppHandle, managed: &Arc<Managed>) {
+    managed.stop.store(false, Ordering::Relaxed);
+    managed.restart.store(false, Ordering::Relaxed);
+    let app = app.clone();
+    let managed = managed.clone();
+    std::thread::spawn(move || monitor(app, managed));
+}
+
+fn find(state: &Supervisor, id: &str) -> Result<Arc<Managed>, KioskError> {
+    state
+        .apps
+        .lock()
+        .expect("supervisor lock")
+        .get(id)
+        .cloned()
+        .ok_or_else(|| KioskError::not_found(format!("App not found: {}", id)))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Launch an allow-listed program under supervision (admin)
+#[tauri::command]
+pub fn launch_app(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, Supervisor>,
+    mut spec: LaunchSpec,
+) -> Result<String, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if spec.command.trim().is_empty() {
+        return Err(KioskError::invalid("Command is required"));
+    }
+    if !program_allowed(&app, &spec.command) {
+        return Err(KioskError::new(
+            ErrorKind::Denied,
+            format!("{} is not an allowed app program", spec.command),
+        ));
+    }
+    spec.env.retain(|name, _| !loader_var(name));
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let managed = Arc::new(Managed {
+        status: Mutex::new(AppProcess {
+            id: id.clone(),
+            spec,
+            pid: None,
+            running: false,
+            restarts: 0,
+            last_exit_code: None,
+            last_signal: None,
+            error: None,
+            started_at: None,
+        }),
+        stop: AtomicBool::new(false),
+        restart: AtomicBool::new(false),
+    });
+    state.apps.lock().expect("supervisor lock").insert(id.clone(), managed.clone());
+    start(&app, &managed);
+    Ok(id)
+}
+
+/// List supervised apps
+#[tauri::command]
+pub fn list_apps(state: State<'_, Supervisor>) -> Vec<AppProcess> {
+    let apps = state.apps.lock().expect("supervisor lock");
+    let mut list: Vec<AppProcess> = apps
+        .values()
+        .map(|managed| managed.status.lock().expect("app status lock").clone())
+        .collect();
+    list.sort_by(|a, b| a.spec.name.cmp(&b.spec.name));
+    list
+}
+
+/// Stop an app (SIGTERM to its process group); it is not restarted
+#[tauri::command]
+pub fn stop_app(state: State<'_, Supervisor>, id: String) -> Result<(), KioskError> {
+    let managed = find(&state, &id)?;
+    managed.stop.store(true, Ordering::Relaxed);
+    if let Some(pid) = managed.status.lock().expect("app status lock").pid {
+        signal_group(pid, libc::SIGTERM);
+    }
+    Ok(())
+}
+
+/// Restart an app now, or start it again if it has stopped
+#[tauri::command]
+pub fn restart_app(app: AppHandle, state: State<'_, Supervisor>, id: String) -> Result<(), KioskError> {
+    let managed = find(&state, &id)?;
+    let mut status = managed.status.lock().expect("app status lock");
+    status.restarts = 0;
+    match status.pid {
+        Some(pid) => {
+            managed.restart.store(true, Ordering::Relaxed);
+            signal_group(pid, libc::SIGTERM);
+        }
+        None => {
+            drop(status);
+            start(&app, &managed);
+        }
+    }
+    Ok(())
+}
+
+/// Turn automatic restart on or off for an app (admin)
+#[tauri::command]
+pub fn set_app_auto_restart(
+    auth: State<'_, AuthState>,
+    state: State<'_, Supervisor>,
+    id: String,
+    enabled: bool,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let managed = find(&state, &id)?;
+    managed.status.lock().expect("app status lock").spec.auto_restart = enabled;
+    Ok(())
+}
+
+/// Remove a stopped app from the list (admin)
+#[tauri::command]
+pub fn remove_app(auth: State<'_, AuthState>, state: State<'_, Supervisor>, id: String) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let managed = find(&state, &id)?;
+    if managed.status.lock().expect("app status lock").running {
+        return Err("Stop the app before removing it".into());
+    }
+    state.apps.lock().expect("supervisor lock").remove(&id);
+    Ok(())
+}
+
+/// Programs apps may be launched from
+#[tauri::command]
+pub fn get_app_programs(state: State<'_, Supervisor>) -> Vec<String> {
+    state.programs.lock().expect("app programs lock").clone()
+}
+
+/// Replace the programs apps may be launched from; each must be an absolute
+/// path to an existing file (admin)
+#[tauri::command]
+pub fn set_app_programs(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, Supervisor>,
+    programs: Vec<String>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    for program in &programs {
+        let path = std::path::Path::new(program);
+        if !path.is_absolute() || !path.is_file() {
+            return Err(KioskError::invalid(format!("{} is not the absolute path of a program", program)));
+        }
+    }
+    let mut allowed = state.programs.lock().expect("app programs lock");
+    store::save(&app, PROGRAMS_FILE, &programs)?;
+    *allowed = programs;
+    Ok(())
+}
+
+/// The end of an app's captured output
+#[tauri::command]
+pub fn get_app_log(app: AppHandle, id: String, max_bytes: Option<u64>) -> Result<String, KioskError> {
+    let path = store::data_path(&app, &log_file(&id))?;
+    let mut file = File::open(&path)?;
+    let len = file.metadata()?.len();
+    let max_bytes = max_bytes.unwrap_or(64 * 1024);
+    file.seek(SeekFrom::Start(len.saturating_sub(max_bytes)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
@@ -0,0 +1,286 @@
+//! Support bundles
+//!
+//! `generate_support_bundle` collects everything a support ticket needs into
+//! one zip: runtime config, the module JSON documents with secrets redacted,
+//! supervised app logs, the system journal, event history, crash reports,
+//! the hardware profile, recent stats and a screenshot. Anything that cannot
+//! be collected is listed in the bundle's manifest instead of failing it.
+
+use chrono::Local;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager, Window};
+
+use crate::jobs::{self, JobHandle};
+use crate::vfs::{self, Access};
+use crate::zip::ZipWriter;
+use crate::{config, events, stats, store};
+
+/// Default directory for bundles inside the app data directory
+const BUNDLE_DIR: &str = "support";
+
+/// Bundles kept in the default directory
+const BUNDLES_KEPT: usize = 5;
+
+/// Never copied, redacted or not
+const EXCLUDED_FILES: &[&str] = &["secrets.json", "keyring.key"];
+
+const APP_LOG_DIR: &str = "app-logs";
+
+const CRASH_DIR: &str = "/var/crash";
+
+/// Larger files are truncated to their last this many bytes
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+
+const JOURNAL_LINES: &str = "5000";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SupportBundle {
+    pub path: String,
+    pub size: u64,
+    pub files: Vec<String>,
+    /// Sections that could not be collected, with the reason
+    pub skipped: Vec<String>,
+    pub created_at: i64,
+}
+
+struct Bundle<'a> {
+    zip: ZipWriter<BufWriter<File>>,
+    job: &'a JobHandle,
+    files: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl Bundle<'_> {
+    fn add(&mut self, name: &str, data: &[u8]) -> Result<(), String> {
+        self.job.check()?;
+        self.zip.add(name, data)?;
+        self.files.push(name.to_string());
+        Ok(())
+    }
+
+    fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<(), String> {
+        let text = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+        self.add(name, &text)
+    }
+
+    /// Add a section, noting it in the manifest when it cannot be collected
+    fn section(&mut self, name: &str, data: Result<Vec<u8>, String>) -> Result<(), String> {
+        match data {
+            Ok(data) => self.add(name, &data),
+            Err(e) => {
+                self.skipped.push(format!("{}: {}", name, e));
+                Ok(())
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Collectors
+// ============================================================================
+
+/// Replace values under sensitive-looking keys, at any depth
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if config::is_sensitive(key) && !value.is_null() {
+                    *value = Value::String(config::REDACTED.to_string());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Read a file, keeping only the tail of large ones
+fn read_tail(path: &Path) -> Result<Vec<u8>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    if len > MAX_FILE_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_FILE_BYTES)).map_err(|e| e.to_string())?;
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes)
+}
+
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()).collect())
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn command_output(program: &str, args: &[&str]) -> Result<Vec<u8>, String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(output.stdout)
+}
+
+/// PNG of the whole screen via grim on Wayland or ImageMagick on X11
+pub(crate) fn screenshot() -> Result<Vec<u8>, String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        command_output("grim", &["-t", "png", "-"])
+    } else {
+        command_output("import", &["-window", "root", "png:-"])
+    }
+}
+
+fn add_config(app: &AppHandle, bundle: &mut Bundle) -> Result<(), String> {
+    bundle.add_json("runtime-config.json", &config::runtime_config(app))?;
+
+    let dir = store::data_path(app, "")?;
+    for path in files_in(&dir) {
+        let name = file_name(&path);
+        if !name.ends_with(".json") || EXCLUDED_FILES.contains(&name.as_str()) {
+            continue;
+        }
+        let document = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()))
+            .and_then(|mut value| {
+                redact(&mut value);
+                serde_json::to_vec_pretty(&value).map_err(|e| e.to_string())
+            });
+        bundle.section(&format!("config/{}", name), document)?;
+    }
+    Ok(())
+}
+
+fn add_logs(app: &AppHandle, bundle: &mut Bundle) -> Result<(), String> {
+    for path in files_in(&store::data_path(app, APP_LOG_DIR)?) {
+        bundle.section(&format!("logs/apps/{}", file_name(&path)), read_tail(&path))?;
+    }
+    let history = events::dump_event_history(app.state(), None, None);
+    bundle.add_json("logs/events.json", &history)?;
+    bundle.section(
+        "logs/journal.txt",
+        command_output("journalctl", &["-b", "--no-pager", "-n", JOURNAL_LINES]),
+    )
+}
+
+fn add_crashes(app: &AppHandle, bundle: &mut Bundle) -> Result<(), String> {
+    let crashes = events::dump_event_history(app.state(), Some("app-crashed".to_string()), None);
+    bundle.add_json("crashes/app-crashes.json", &crashes)?;
+    for path in files_in(Path::new(CRASH_DIR)) {
+        bundle.section(&format!("crashes/{}", file_name(&path)), read_tail(&path))?;
+    }
+    bundle.section("crashes/coredumps.txt", command_output("coredumpctl", &["list", "--no-pager"]))
+}
+
+fn add_system(app: &AppHandle, bundle: &mut Bundle) -> Result<(), String> {
+    bundle.add_json("hardware.json", &crate::get_hardware_profile(app.state()))?;
+    bundle.add_json("stats/last-hour.json", &stats::get_stats_history(app.state(), 3600, 5))?;
+    bundle.add_json("stats/last-day.json", &stats::get_stats_history(app.state(), 86400, 60))
+}
+
+/// Delete the oldest bundles in the default directory beyond the limit
+fn prune(dir: &Path) {
+    let bundles: Vec<PathBuf> = files_in(dir)
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .collect();
+    // Names embed the creation time, so sorted order is oldest first
+    for path in bundles.iter().take(bundles.len().saturating_sub(BUNDLES_KEPT)) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn generate(app: &AppHandle, job: &JobHandle, destination: Option<String>) -> Result<SupportBundle, String> {
+    let default_dir = store::data_path(app, BUNDLE_DIR)?;
+    let dir = match destination {
+        Some(destination) => vfs::resolve(app, &destination, Access::Write).map_err(|e| e.to_string())?,
+        None => default_dir.clone(),
+    };
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let now = Local::now();
+    let host = sysinfo::System::host_name().unwrap_or_else(|| "kiosk".to_string());
+    let path = dir.join(format!("kiosk-support-{}-{}.zip", host, now.format("%Y%m%d-%H%M%S")));
+    let file = File::create(&path).map_err(|e| e.to_string())?;
+
+    let mut bundle = Bundle {
+        zip: ZipWriter::new(BufWriter::new(file)),
+        job,
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    let result = (|| {
+        job.progress(0.0, Some("Configuration".to_string()));
+        add_config(app, &mut bundle)?;
+        job.progress(0.2, Some("Logs".to_string()));
+        add_logs(app, &mut bundle)?;
+        job.progress(0.5, Some("Crash reports".to_string()));
+        add_crashes(app, &mut bundle)?;
+        job.progress(0.6, Some("System".to_string()));
+        add_system(app, &mut bundle)?;
+        job.progress(0.8, Some("Screenshot".to_string()));
+        bundle.section("screenshot.png", screenshot())?;
+
+        let manifest = serde_json::json!({
+            "created_at": now.to_rfc3339(),
+            "host": host,
+            "version": env!("CARGO_PKG_VERSION"),
+            "files": bundle.files,
+            "skipped": bundle.skipped,
+        });
+        bundle.add_json("manifest.json", &manifest)
+    })();
+
+    let Bundle { zip, files, skipped, .. } = bundle;
+    let finished = result.and_then(|_| zip.finish());
+    if let Err(e) = finished {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+
+    if dir == default_dir {
+        prune(&dir);
+    }
+    Ok(SupportBundle {
+        size: fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0),
+        path: vfs::to_virtual(app, &path).unwrap_or_else(|| path.display().to_string()),
+        files,
+        skipped,
+        created_at: now.timestamp(),
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Build a support bundle as a job. It is written to `destination` (such as
+/// a USB drive) or the app's support directory; the job result is a
+/// `SupportBundle`.
+#[tauri::command]
+pub fn generate_support_bundle(app: AppHandle, window: Window, destination: Option<String>) -> String {
+    let worker = app.clone();
+    jobs::spawn(&app, Some(window.label()), "support-bundle", move |job| {
+        generate(&worker, job, destination)
+    })
+}
@@ -0,0 +1,530 @@
+//! System information report
+//!
+//! Builds the full report behind the "System Information" accessory: system
+//! summary, processor, memory (with DIMMs when DMI tables are readable),
+//! disks, network adapters and attached peripherals. `get_system_report`
+//! returns it as JSON, plain text or a standalone HTML page for saving or
+//! printing.
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use sysinfo::System;
+use tauri::{AppHandle, Manager};
+
+use crate::error::KioskError;
+use crate::{DriveInfo, HardwareProfile, SharedSystem};
+
+const USB_DEVICES: &str = "/sys/bus/usb/devices";
+const INPUT_DEVICES: &str = "/proc/bus/input/devices";
+const DRM_CONNECTORS: &str = "/sys/class/drm";
+const SOUND_CARDS: &str = "/proc/asound/cards";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Json,
+    Text,
+    Html,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SystemSummary {
+    #[serde(flatten)]
+    pub profile: HardwareProfile,
+    pub kernel: Option<String>,
+    pub architecture: Option<String>,
+    pub boot_time: i64,
+    pub uptime_secs: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProcessorInfo {
+    pub brand: String,
+    pub vendor: String,
+    pub physical_cores: Option<usize>,
+    pub logical_cores: usize,
+    pub frequency_mhz: u64,
+}
+
+/// A memory module from DMI type 17
+#[derive(Debug, Default, Serialize)]
+pub struct MemoryModule {
+    pub locator: String,
+    pub size: String,
+    pub kind: String,
+    pub speed: String,
+    pub manufacturer: String,
+    pub part_number: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemoryInfo {
+    pub total: u64,
+    pub available: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+    /// Empty when DMI tables are missing (Raspberry Pi) or not readable
+    pub modules: Vec<MemoryModule>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NetworkAdapter {
+    pub name: String,
+    pub mac_address: String,
+    pub addresses: Vec<String>,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Peripheral {
+    /// usb, input, display or audio
+    pub class: String,
+    pub name: String,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SystemReport {
+    pub generated_at: i64,
+    pub system: SystemSummary,
+    pub processor: ProcessorInfo,
+    pub memory: MemoryInfo,
+    pub disks: Vec<DriveInfo>,
+    pub network: Vec<NetworkAdapter>,
+    pub peripherals: Vec<Peripheral>,
+}
+
+/// A titled table, the common shape the text and HTML renderers work from
+struct Section {
+    title: &'static str,
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+// ============================================================================
+// Collectors
+// ============================================================================
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|text| text.trim().to_string())
+}
+
+/// Memory modules from `dmidecode`, which needs root on most systems
+fn memory_modules() -> Vec<MemoryModule> {
+    let Ok(output) = Command::new("dmidecode").args(["-t", "17"]).output() else {
+        return Vec::new();
+    };
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut modules = Vec::new();
+    let mut current: Option<MemoryModule> = None;
+    for line in text.lines() {
+        if line.starts_with("Memory Device") {
+            modules.extend(current.take());
+            current = Some(MemoryModule::default());
+            continue;
+        }
+        let (Some(module), Some((key, value))) = (current.as_mut(), line.trim().split_once(": ")) else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key {
+            "Locator" => module.locator = value,
+            "Size" => module.size = value,
+            "Type" => module.kind = value,
+            "Speed" => module.speed = value,
+            "Manufacturer" => module.manufacturer = value,
+            "Part Number" => module.part_number = value,
+            _ => {}
+        }
+    }
+    modules.extend(current);
+    // Empty slots report no module installed
+    modules.retain(|module| !module.size.is_empty() && !module.size.starts_with("No Module"));
+    modules
+}
+
+fn usb_devices() -> Vec<Peripheral> {
+    let Ok(entries) = fs::read_dir(USB_DEVICES) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<Peripheral> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let vendor = read_trimmed(&path.join("idVendor"))?;
+            let product_id = read_trimmed(&path.join("idProduct"))?;
+            let manufacturer = read_trimmed(&path.join("manufacturer")).unwrap_or_default();
+            let product = read_trimmed(&path.join("product")).unwrap_or_else(|| "USB device".to_string());
+            Some(Peripheral {
+                class: "usb".to_string(),
+                name: format!("{} {}", manufacturer, product).trim().to_string(),
+                detail: format!("{}:{} at {}", vendor, product_id, entry.file_name().to_string_lossy()),
+            })
+        })
+        .collect();
+    devices.sort_by(|a, b| a.detail.cmp(&b.detail));
+    devices
+}
+
+fn input_devices() -> Vec<Peripheral> {
+    let text = fs::read_to_string(INPUT_DEVICES).unwrap_or_default();
+    text.split("\n\n")
+        .filter_map(|block| {
+            let field = |prefix: &str| {
+                block
+                    .lines()
+                    .find_map(|line| line.strip_prefix(prefix))
+                    .map(|value| value.trim().trim_matches('"').to_string())
+            };
+            Some(Peripheral {
+                class: "input".to_string(),
+                name: field("N: Name=")?,
+                detail: field("H: Handlers=").unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+fn displays() -> Vec<Peripheral> {
+    let Ok(entries) = fs::read_dir(DRM_CONNECTORS) else {
+        return Vec::new();
+    };
+    let mut displays: Vec<Peripheral> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let status = read_trimmed(&entry.path().join("status"))?;
+            // card0-HDMI-A-1 -> HDMI-A-1
+            let name = entry.file_name().to_string_lossy().to_string();
+            let connector = name.split_once('-').map_or(name.as_str(), |(_, connector)| connector).to_string();
+            let mode = fs::read_to_string(entry.path().join("modes"))
+                .ok()
+                .and_then(|modes| modes.lines().next().map(str::to_string));
+            Some(Peripheral {
+                class: "display".to_string(),
+                name: connector,
+                detail: mode.map_or(status.clone(), |mode| format!("{}, {}", status, mode)),
+            })
+        })
+        .collect();
+    displays.sort_by(|a, b| a.name.cmp(&b.name));
+    displays
+}
+
+fn sound_cards() -> Vec<Peripheral> {
+    // " 0 [vc4hdmi0       ]: vc4-hdmi - vc4-hdmi-0"
+    let text = fs::read_to_string(SOUND_CARDS).unwrap_or_default();
+    text.lines()
+        .filter_map(|line| {
+            let (index, rest) = line.trim().split_once(' ')?;
+            index.parse::<u32>().ok()?;
+            let (_, description) = rest.split_once("]: ")?;
+            Some(Peripheral {
+                class: "audio".to_string(),
+                name: description.trim().to_string(),
+                detail: format!("card {}", index),
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn collect(app: &AppHandle) -> SystemReport {
+    let profile = crate::get_hardware_profile(app.state());
+    let disks = crate::list_drives(app.state());
+    let shared = app.state::<SharedSystem>();
+
+    let (processor, memory) = {
+        let mut sys = shared.system.lock().expect("system state lock");
+        sys.refresh_cpu_all();
+        sys.refresh_memory();
+        let cpus = sys.cpus();
+        let processor = ProcessorInfo {
+            brand: cpus.first().map(|cpu| cpu.brand().to_string()).unwrap_or_default(),
+            vendor: cpus.first().map(|cpu| cpu.vendor_id().to_string()).unwrap_or_default(),
+            physical_cores: sys.physical_core_count(),
+            logical_cores: cpus.len(),
+            frequency_mhz: cpus.iter().map(|cpu| cpu.frequency()).max().unwrap_or(0),
+        };
+        let memory = MemoryInfo {
+            total: sys.total_memory(),
+            available: sys.available_memory(),
+            swap_total: sys.total_swap(),
+            swap_used: sys.used_swap(),
+            modules: memory_modules(),
+        };
+        (processor, memory)
+    };
+
+    let network = {
+        let mut networks = shared.networks.lock().expect("network state lock");
+        networks.refresh_list();
+        let mut adapters: Vec<NetworkAdapter> = networks
+            .iter()
+            .map(|(name, data)| NetworkAdapter {
+                name: name.clone(),
+                mac_address: data.mac_address().to_string(),
+                addresses: data
+                    .ip_networks()
+                    .iter()
+                    .map(|network| format!("{}/{}", network.addr, network.prefix))
+                    .collect(),
+                total_received: data.total_received(),
+                total_transmitted: data.total_transmitted(),
+            })
+            .collect();
+        adapters.sort_by(|a, b| a.name.cmp(&b.name));
+        adapters
+    };
+
+    let mut peripherals = usb_devices();
+    peripherals.extend(input_devices());
+    peripherals.extend(displays());
+    peripherals.extend(sound_cards());
+
+    SystemReport {
+        generated_at: Local::now().timestamp(),
+        system: SystemSummary {
+            profile,
+            kernel: System::kernel_version(),
+            architecture: System::cpu_arch(),
+            boot_time: System::boot_time() as i64,
+            uptime_secs: System::uptime(),
+        },
+        processor,
+        memory,
+        disks,
+        network,
+        peripherals,
+    }
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+fn mb(bytes: u64) -> String {
+    format!("{} MB", bytes / 1024 / 1024)
+}
+
+fn time_text(timestamp: i64) -> String {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn key_values(title: &'static str, items: Vec<(&str, String)>) -> Section {
+    Section {
+        title,
+        headers: vec!["Item", "Value"],
+        rows: items.into_iter().map(|(key, value)| vec![key.to_string(), value]).collect(),
+    }
+}
+
+fn sections(report: &SystemReport) -> Vec<Section> {
+    let system = &report.system;
+    let unknown = || "Unknown".to_string();
+    let uptime = system.uptime_secs;
+    vec![
+        key_values(
+            "System Summary",
+            vec![
+                ("OS Name", system.profile.os_name.clone()),
+                ("Version", system.profile.os_version.clone()),
+                ("Kernel", system.kernel.clone().unwrap_or_else(unknown)),
+                ("System Name", system.profile.hostname.clone()),
+                ("System Model", system.profile.model.clone()),
+                ("System Type", system.architecture.clone().unwrap_or_else(unknown)),
+                ("Boot Time", time_text(system.boot_time)),
+                ("Up Time", format!("{}d {}h {}m", uptime / 86400, uptime % 86400 / 3600, uptime % 3600 / 60)),
+            ],
+        ),
+        key_values(
+            "Processor",
+            vec![
+                ("Name", report.processor.brand.clone()),
+                ("Vendor", report.processor.vendor.clone()),
+                (
+                    "Physical Cores",
+                    report.processor.physical_cores.map_or_else(unknown, |cores| cores.to_string()),
+                ),
+                ("Logical Processors", report.processor.logical_cores.to_string()),
+                ("Speed", format!("{} MHz", report.processor.frequency_mhz)),
+            ],
+        ),
+        key_values(
+            "Memory",
+            vec![
+                ("Total Physical Memory", mb(report.memory.total)),
+                ("Available Physical Memory", mb(report.memory.available)),
+                ("Total Swap", mb(report.memory.swap_total)),
+                ("Swap In Use", mb(report.memory.swap_used)),
+            ],
+        ),
+        Section {
+            title: "Memory Modules",
+            headers: vec!["Locator", "Size", "Type", "Speed", "Manufacturer", "Part Number"],
+            rows: report
+                .memory
+                .modules
+                .iter()
+                .map(|module| {
+                    vec![
+                        module.locator.clone(),
+                        module.size.clone(),
+                        module.kind.clone(),
+                        module.speed.clone(),
+                        module.manufacturer.clone(),
+                        module.part_number.clone(),
+                    ]
+                })
+                .collect(),
+        },
+        Section {
+            title: "Disks",
+            headers: vec!["Name", "Mount Point", "Size", "Free", "Removable"],
+            rows: report
+                .disks
+                .iter()
+                .map(|disk| {
+                    vec![
+                        disk.name.clone(),
+                        disk.mount_point.clone(),
+                        mb(disk.total_space),
+                        mb(disk.available_space),
+                        if disk.is_removable { "Yes" } else { "No" }.to_string(),
+                    ]
+                })
+                .collect(),
+        },
+        Section {
+            title: "Network Adapters",
+            headers: vec!["Name", "MAC Address", "Addresses", "Received", "Sent"],
+            rows: report
+                .network
+                .iter()
+                .map(|adapter| {
+                    vec![
+                        adapter.name.clone(),
+                        adapter.mac_address.clone(),
+                        adapter.addresses.join(", "),
+                        mb(adapter.total_received),
+                        mb(adapter.total_transmitted),
+                    ]
+                })
+                .collect(),
+        },
+        Section {
+            title: "Peripherals",
+            headers: vec!["Class", "Name", "Detail"],
+            rows: report
+                .peripherals
+                .iter()
+                .map(|device| vec![device.class.clone(), device.name.clone(), device.detail.clone()])
+                .collect(),
+        },
+    ]
+}
+
+fn render_text(report: &SystemReport) -> String {
+    let mut out = format!("System Information - {}\n", time_text(report.generated_at));
+    for section in sections(report) {
+        out.push_str(&format!("\n[{}]\n", section.title));
+        if section.rows.is_empty() {
+            out.push_str("(none)\n");
+            continue;
+        }
+        // Pad each column to its widest cell
+        let widths: Vec<usize> = (0..section.headers.len())
+            .map(|column| {
+                section
+                    .rows
+                    .iter()
+                    .map(|row| row[column].chars().count())
+                    .chain(std::iter::once(section.headers[column].len()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |cells: Vec<&str>| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+            format!("{}\n", padded.join("  ").trim_end())
+        };
+        out.push_str(&line(section.headers.clone()));
+        for row in &section.rows {
+            out.push_str(&line(row.iter().map(String::as_str).collect()));
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(report: &SystemReport) -> String {
+    let title = format!("System Information - {}", escape_html(&report.system.profile.hostname));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: Tahoma, sans-serif; font-size: 12px; }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 16px; }}\n\
+         th, td {{ border: 1px solid #808080; padding: 2px 8px; text-align: left; }}\n\
+         th {{ background: #d4d0c8; }}\n\
+         </style>\n</head>\n<body>\n<h1>{}</h1>\n<p>Generated {}</p>\n",
+        title,
+        title,
+        time_text(report.generated_at)
+    );
+    for section in sections(report) {
+        out.push_str(&format!("<h2>{}</h2>\n", section.title));
+        if section.rows.is_empty() {
+            out.push_str("<p>(none)</p>\n");
+            continue;
+        }
+        out.push_str("<table>\n<tr>");
+        for header in &section.headers {
+            out.push_str(&format!("<th>{}</th>", header));
+        }
+        out.push_str("</tr>\n");
+        for row in &section.rows {
+            out.push_str("<tr>");
+            for cell in row {
+                out.push_str(&format!("<td>{}</td>", escape_html(cell)));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Full system report as `json`, `text` or a standalone `html` page
+#[tauri::command]
+pub fn get_system_report(app: AppHandle, format: ReportFormat) -> Result<String, KioskError> {
+    let report = collect(&app);
+    Ok(match format {
+        ReportFormat::Json => serde_json::to_string_pretty(&report)?,
+        ReportFormat::Text => render_text(&report),
+        ReportFormat::Html => render_html(&report),
+    })
+}
@@ -0,0 +1,819 @@
+//! Tabular files
+//!
+//! Reads CSV, TSV and Excel (XLSX) files into pages of JSON rows with a
+//! type inferred for each column, and writes rows back out in any of the
+//! three formats, so the spreadsheet accessory and data imports do not
+//! parse workbooks in the webview. XLSX is read and written with the
+//! built-in zip support: cell values, shared strings and date formats are
+//! understood; formulas are read as their cached values and formatting is
+//! not kept.
+//!
+//! A column whose filled cells are all numbers, whole numbers, `true` or
+//! `false`, or ISO dates gets that type and typed values; anything mixed is
+//! text, with every cell given as it was written.
+
+use chrono::{Duration as DateDuration, NaiveDate, NaiveDateTime};
+use quick_xml::events::{BytesStart, Event};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+use crate::{recents, zip};
+
+/// Files over this size are refused, since a workbook is read whole
+const MAX_FILE_BYTES: u64 = 64 * 1024 * 1024;
+
+const DEFAULT_PAGE_ROWS: usize = 200;
+
+const MAX_PAGE_ROWS: usize = 5000;
+
+/// Built-in Excel number formats that show a date or time
+const DATE_FORMAT_IDS: &[u32] = &[14, 15, 16, 17, 18, 19, 20, 21, 22, 45, 46, 47];
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TabularFormat {
+    Csv,
+    Tsv,
+    Xlsx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    /// No filled cells
+    Empty,
+    Boolean,
+    Integer,
+    Number,
+    /// "YYYY-MM-DD", or "YYYY-MM-DDTHH:MM:SS" with a time
+    Date,
+    Text,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TabularOptions {
+    /// Taken from the file extension when unset
+    pub format: Option<TabularFormat>,
+    /// CSV field separator; guessed from the first line when unset
+    pub delimiter: Option<char>,
+    /// Whether the first row names the columns
+    pub header: bool,
+    /// Worksheet name; the first sheet when unset
+    pub sheet: Option<String>,
+    /// First data row of the page
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Give every cell as text instead of inferring column types
+    pub raw: bool,
+}
+
+impl Default for TabularOptions {
+    fn default() -> Self {
+        TabularOptions {
+            format: None,
+            delimiter: None,
+            header: true,
+            sheet: None,
+            offset: 0,
+            limit: None,
+            raw: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TabularColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub column_type: ColumnType,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TabularPage {
+    pub format: TabularFormat,
+    /// Worksheet names; empty for CSV and TSV
+    pub sheets: Vec<String>,
+    pub sheet: Option<String>,
+    pub columns: Vec<TabularColumn>,
+    /// Cells are null when empty
+    pub rows: Vec<Vec<Value>>,
+    pub offset: usize,
+    /// Data rows in the whole file or sheet
+    pub total_rows: usize,
+}
+
+/// Rows to write; cells may be strings, numbers, booleans or null
+#[derive(Debug, Clone, Deserialize)]
+pub struct TabularData {
+    #[serde(default)]
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TabularWriteOptions {
+    /// Taken from the file extension when unset
+    pub format: Option<TabularFormat>,
+    /// CSV field separator; `,` by default
+    pub delimiter: Option<char>,
+    /// Worksheet name for XLSX
+    pub sheet: Option<String>,
+}
+
+/// A cell as read, before the column's type is known
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Empty,
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Date(String),
+}
+
+type Rows = Vec<Vec<Cell>>;
+
+// ============================================================================
+// Type Inference
+// ============================================================================
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+impl Cell {
+    fn text(&self) -> String {
+        match self {
+            Cell::Empty => String::new(),
+            Cell::Text(text) | Cell::Date(text) => text.clone(),
+            Cell::Number(value) => format_number(*value),
+            Cell::Bool(value) => if *value { "TRUE" } else { "FALSE" }.to_string(),
+        }
+    }
+
+    /// What a cell's text looks like; numbers with leading zeros (codes,
+    /// phone numbers) stay text
+    fn inferred(&self) -> Cell {
+        let Cell::Text(text) = self else { return self.clone() };
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return Cell::Empty;
+        }
+        if trimmed.eq_ignore_ascii_case("true") || trimmed.eq_ignore_ascii_case("false") {
+            return Cell::Bool(trimmed.eq_ignore_ascii_case("true"));
+        }
+        let digits = trimmed.trim_start_matches(['-', '+']);
+        let leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+        if !leading_zero && digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            if let Ok(value) = trimmed.parse::<f64>() {
+                if value.is_finite() {
+                    return Cell::Number(value);
+                }
+            }
+        }
+        if NaiveDate::parse_from_str(trimmed, "%Y-%m-%d").is_ok()
+            || NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%dT%H:%M:%S").is_ok()
+            || NaiveDateTime::parse_from_str(trimmed, "%Y-%m-%d %H:%M:%S").is_ok()
+        {
+            return Cell::Date(trimmed.replace(' ', "T"));
+        }
+        self.clone()
+    }
+
+    fn kind(&self) -> ColumnType {
+        match self {
+            Cell::Empty => ColumnType::Empty,
+            Cell::Text(_) => ColumnType::Text,
+            Cell::Number(value) if value.fract() == 0.0 && value.abs() < 9e15 => ColumnType::Integer,
+            Cell::Number(_) => ColumnType::Number,
+            Cell::Bool(_) => ColumnType::Boolean,
+            Cell::Date(_) => ColumnType::Date,
+        }
+    }
+
+    /// The cell as JSON; text columns keep every cell as written
+    fn value(&self, column_type: ColumnType) -> Value {
+        if column_type == ColumnType::Text {
+            return match self {
+                Cell::Empty => Value::Null,
+                cell => Value::String(cell.text()),
+            };
+        }
+        match (self.inferred(), column_type) {
+            (Cell::Empty, _) => Value::Null,
+            (Cell::Number(value), ColumnType::Integer) => Value::from(value as i64),
+            (Cell::Number(value), _) => Value::from(value),
+            (Cell::Bool(value), _) => Value::Bool(value),
+            (cell, _) => Value::String(cell.text()),
+        }
+    }
+}
+
+/// The type all filled cells of a column share
+fn column_type(rows: &Rows, column: usize) -> ColumnType {
+    rows.iter()
+        .filter_map(|row| row.get(column))
+        .map(|cell| cell.inferred().kind())
+        .fold(ColumnType::Empty, |so_far, kind| match (so_far, kind) {
+            (so_far, ColumnType::Empty) => so_far,
+            (ColumnType::Empty, kind) => kind,
+            (ColumnType::Integer, ColumnType::Number) | (ColumnType::Number, ColumnType::Integer) => ColumnType::Number,
+            (so_far, kind) if so_far == kind => so_far,
+            _ => ColumnType::Text,
+        })
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+/// The likeliest separator from the first line of a file
+fn guess_delimiter(text: &str) -> char {
+    let first = text.lines().next().unwrap_or_default();
+    [',', ';', '\t', '|']
+        .into_iter()
+        .max_by_key(|&delimiter| first.matches(delimiter).count())
+        .filter(|&delimiter| first.contains(delimiter))
+        .unwrap_or(',')
+}
+
+/// Split delimited text into rows, with RFC 4180 quoting
+fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+fn csv_field(value: &str, delimiter: char) -> String {
+    if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(number) => number.to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn write_delimited(data: &TabularData, delimiter: char) -> String {
+    let header = (!data.columns.is_empty()).then(|| data.columns.iter().map(|name| csv_field(name, delimiter)));
+    let mut text = String::new();
+    if let Some(header) = header {
+        text.push_str(&header.collect::<Vec<_>>().join(&delimiter.to_string()));
+        text.push_str("\r\n");
+    }
+    for row in &data.rows {
+        let fields: Vec<String> = row.iter().map(|value| csv_field(&value_text(value), delimiter)).collect();
+        text.push_str(&fields.join(&delimiter.to_string()));
+        text.push_str("\r\n");
+    }
+    text
+}
+
+// ============================================================================
+// XLSX
+// ============================================================================
+
+fn attribute(element: &BytesStart, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.as_ref() == name.as_bytes())
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+fn entry<'a>(entries: &'a HashMap<String, Vec<u8>>, name: &str) -> Result<&'a str, String> {
+    let data = entries.get(name).ok_or_else(|| format!("Not an Excel workbook: {} is missing", name))?;
+    std::str::from_utf8(data).map_err(|_| format!("{} is not UTF-8", name))
+}
+
+/// Sheet names and their part paths, in workbook order
+fn workbook_sheets(entries: &HashMap<String, Vec<u8>>) -> Result<Vec<(String, String)>, String> {
+    let mut targets = HashMap::new();
+    let mut reader = quick_xml::Reader::from_str(entry(entries, "xl/_rels/workbook.xml.rels")?);
+    loop {
+        match reader.read_event().map_err(|e| format!("Invalid workbook: {}", e))? {
+            Event::Start(element) | Event::Empty(element) if element.local_name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (attribute(&element, "Id"), attribute(&element, "Target")) {
+                    let path = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    targets.insert(id, path);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let mut sheets = Vec::new();
+    let mut reader = quick_xml::Reader::from_str(entry(entries, "xl/workbook.xml")?);
+    loop {
+        match reader.read_event().map_err(|e| format!("Invalid workbook: {}", e))? {
+            Event::Start(element) | Event::Empty(element) if element.local_name().as_ref() == b"sheet" => {
+                let name = attribute(&element, "name").unwrap_or_default();
+                if let Some(path) = attribute(&element, "r:id").and_then(|id| targets.get(&id)) {
+                    sheets.push((name, path.clone()));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(sheets)
+}
+
+fn shared_strings(entries: &HashMap<String, Vec<u8>>) -> Result<Vec<String>, String> {
+    let Ok(xml) = entry(entries, "xl/sharedStrings.xml") else { return Ok(Vec::new()) };
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    // Phonetic runs repeat the text as a reading guide
+    let mut in_phonetic = false;
+    let mut reader = quick_xml::Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(|e| format!("Invalid shared strings: {}", e))? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"si" => current.clear(),
+                b"t" => in_text = true,
+                b"rPh" => in_phonetic = true,
+                _ => {}
+            },
+            Event::End(element) => match element.local_name().as_ref() {
+                b"si" => strings.push(std::mem::take(&mut current)),
+                b"t" => in_text = false,
+                b"rPh" => in_phonetic = false,
+                _ => {}
+            },
+            Event::Empty(element) if element.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Text(text) if in_text && !in_phonetic => {
+                current.push_str(&text.unescape().map_err(|e| e.to_string())?);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(strings)
+}
+
+/// Whether a custom number format shows a date, ignoring quoted text,
+/// colours and locale tags
+fn is_date_format(code: &str) -> bool {
+    let mut plain = String::new();
+    let mut skip_until = None;
+    for c in code.chars() {
+        match (skip_until, c) {
+            (Some(end), c) if c == end => skip_until = None,
+            (Some(_), _) => {}
+            (None, '"') => skip_until = Some('"'),
+            (None, '[') => skip_until = Some(']'),
+            (None, c) => plain.push(c.to_ascii_lowercase()),
+        }
+    }
+    plain != "general" && (plain.contains('y') || plain.contains('d') || plain.contains("h:"))
+}
+
+/// For each cell style index, whether it formats numbers as dates
+fn date_styles(entries: &HashMap<String, Vec<u8>>) -> Result<Vec<bool>, String> {
+    let Ok(xml) = entry(entries, "xl/styles.xml") else { return Ok(Vec::new()) };
+    let mut custom = HashMap::new();
+    let mut styles = Vec::new();
+    let mut in_cell_xfs = false;
+    let mut reader = quick_xml::Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(|e| format!("Invalid styles: {}", e))? {
+            Event::Start(element) | Event::Empty(element) => match element.local_name().as_ref() {
+                b"numFmt" => {
+                    let code = attribute(&element, "formatCode");
+                    if let (Some(id), Some(code)) = (attribute(&element, "numFmtId"), code) {
+                        custom.insert(id.parse::<u32>().unwrap_or_default(), is_date_format(&code));
+                    }
+                }
+                b"cellXfs" => in_cell_xfs = true,
+                b"xf" if in_cell_xfs => {
+                    let id = attribute(&element, "numFmtId")
+                        .and_then(|id| id.parse::<u32>().ok())
+                        .unwrap_or_default();
+                    styles.push(DATE_FORMAT_IDS.contains(&id) || custom.get(&id).copied().unwrap_or(false));
+                }
+                _ => {}
+            },
+            Event::End(element) if element.local_name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(styles)
+}
+
+/// An Excel serial date (1900 system) as ISO text
+fn serial_date(serial: f64) -> Option<String> {
+    let epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_hms_opt(0, 0, 0)?;
+    let time = epoch + DateDuration::milliseconds((serial * 86_400_000.0).round() as i64);
+    Some(if serial.fract() == 0.0 {
+        time.format("%Y-%m-%d").to_string()
+    } else {
+        time.format("%Y-%m-%dT%H:%M:%S").to_string()
+    })
+}
+
+/// Zero-based column of a reference such as "AB12"
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: String = reference.chars().take_while(|c| c.is_ascii_alphabetic()).collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let number = letters
+        .bytes()
+        .fold(0usize, |number, letter| number * 26 + (letter.to_ascii_uppercase() - b'A' + 1) as usize);
+    Some(number - 1)
+}
+
+/// One worksheet's cells as rows
+fn read_sheet(xml: &str, strings: &[String], dates: &[bool]) -> Result<Rows, String> {
+    let mut rows: Rows = Vec::new();
+    let mut row: Vec<Cell> = Vec::new();
+    // The cell being read: column, type, style and value text
+    let mut cell: Option<(usize, String, usize, String)> = None;
+    let mut in_value = false;
+    let mut reader = quick_xml::Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(|e| format!("Invalid worksheet: {}", e))? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"row" => {
+                    // Rows may be skipped when empty
+                    let number = attribute(&element, "r").and_then(|r| r.parse::<usize>().ok());
+                    if let Some(number) = number {
+                        while rows.len() + 1 < number {
+                            rows.push(Vec::new());
+                        }
+                    }
+                    row.clear();
+                }
+                b"c" => {
+                    let column = attribute(&element, "r")
+                        .and_then(|r| column_index(&r))
+                        .unwrap_or(row.len());
+                    let kind = attribute(&element, "t").unwrap_or_else(|| "n".to_string());
+                    let style = attribute(&element, "s")
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or_default();
+                    cell = Some((column, kind, style, String::new()));
+                }
+                b"v" | b"t" => in_value = true,
+                _ => {}
+            },
+            Event::Empty(element) if element.local_name().as_ref() == b"row" => rows.push(Vec::new()),
+            Event::Text(text) if in_value => {
+                if let Some((_, _, _, value)) = cell.as_mut() {
+                    value.push_str(&text.unescape().map_err(|e| e.to_string())?);
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"v" | b"t" => in_value = false,
+                b"c" => {
+                    if let Some((column, kind, style, value)) = cell.take() {
+                        let parsed = match kind.as_str() {
+                            "s" => value
+                                .trim()
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|index| strings.get(index))
+                                .map_or(Cell::Empty, |text| Cell::Text(text.clone())),
+                            "b" => Cell::Bool(value.trim() == "1"),
+                            "str" | "inlineStr" | "e" => Cell::Text(value),
+                            _ => match value.trim().parse::<f64>() {
+                                Ok(number) if dates.get(style).copied().unwrap_or(false) => {
+                                    serial_date(number).map_or(Cell::Number(number), Cell::Date)
+                                }
+                                Ok(number) => Cell::Number(number),
+                                Err(_) => Cell::Empty,
+                            },
+                        };
+                        if row.len() <= column {
+                            row.resize(column + 1, Cell::Empty);
+                        }
+                        row[column] = parsed;
+                    }
+                }
+                b"row" => rows.push(std::mem::take(&mut row)),
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(rows)
+}
+
+/// Sheet names and the chosen sheet's name and rows
+fn read_xlsx(data: &[u8], sheet: Option<&str>) -> Result<(Vec<String>, String, Rows), String> {
+    let entries: HashMap<String, Vec<u8>> = zip::read_entries(data)?.into_iter().collect();
+    let sheets = workbook_sheets(&entries)?;
+    let (name, path) = match sheet {
+        Some(wanted) => sheets
+            .iter()
+            .find(|(name, _)| name == wanted)
+            .ok_or_else(|| format!("No sheet named {}", wanted))?,
+        None => sheets.first().ok_or("The workbook has no sheets")?,
+    };
+    let rows = read_sheet(entry(&entries, path)?, &shared_strings(&entries)?, &date_styles(&entries)?)?;
+    Ok((sheets.iter().map(|(name, _)| name.clone()).collect(), name.clone(), rows))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn column_letters(mut index: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    letters.reverse();
+    String::from_utf8(letters).unwrap_or_default()
+}
+
+fn xlsx_cell(reference: &str, value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(value) => format!("<c r=\"{}\" t=\"b\"><v>{}</v></c>", reference, u8::from(*value)),
+        Value::Number(number) => format!("<c r=\"{}\"><v>{}</v></c>", reference, number),
+        other => format!(
+            "<c r=\"{}\" t=\"inlineStr\"><is><t xml:space=\"preserve\">{}</t></is></c>",
+            reference,
+            escape_xml(&value_text(other))
+        ),
+    }
+}
+
+fn write_xlsx(data: &TabularData, sheet: &str) -> Result<Vec<u8>, String> {
+    let header = (!data.columns.is_empty())
+        .then(|| data.columns.iter().map(|name| Value::String(name.clone())).collect());
+    let mut rows_xml = String::new();
+    for (index, row) in header.iter().chain(&data.rows).enumerate() {
+        let cells: String = row
+            .iter()
+            .enumerate()
+            .map(|(column, value)| xlsx_cell(&format!("{}{}", column_letters(column), index + 1), value))
+            .collect();
+        rows_xml.push_str(&format!("<row r=\"{}\">{}</row>", index + 1, cells));
+    }
+
+    let main = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+    let relationships = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+    let package = "http://schemas.openxmlformats.org/package/2006";
+    let parts = [
+        (
+            "[Content_Types].xml",
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+                 <Types xmlns=\"{package}/content-types\">\
+                 <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+                 <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+                 <Override PartName=\"/xl/workbook.xml\" \
+                 ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+                 <Override PartName=\"/xl/worksheets/sheet1.xml\" \
+                 ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+                 </Types>"
+            ),
+        ),
+        (
+            "_rels/.rels",
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+                 <Relationships xmlns=\"{package}/relationships\">\
+                 <Relationship Id=\"rId1\" Type=\"{relationships}/officeDocument\" Target=\"xl/workbook.xml\"/>\
+                 </Relationships>"
+            ),
+        ),
+        (
+            "xl/workbook.xml",
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+                 <workbook xmlns=\"{main}\" xmlns:r=\"{relationships}\">\
+                 <sheets><sheet name=\"{}\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>",
+                escape_xml(sheet)
+            ),
+        ),
+        (
+            "xl/_rels/workbook.xml.rels",
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+                 <Relationships xmlns=\"{package}/relationships\">\
+                 <Relationship Id=\"rId1\" Type=\"{relationships}/worksheet\" Target=\"worksheets/sheet1.xml\"/>\
+                 </Relationships>"
+            ),
+        ),
+        (
+            "xl/worksheets/sheet1.xml",
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\
+                 <worksheet xmlns=\"{main}\"><sheetData>{rows_xml}</sheetData></worksheet>"
+            ),
+        ),
+    ];
+
+    let mut writer = zip::ZipWriter::new(Vec::new());
+    for (name, xml) in parts {
+        writer.add(name, xml.as_bytes())?;
+    }
+    writer.finish()
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn format_of(path: &str, format: Option<TabularFormat>) -> Result<TabularFormat, KioskError> {
+    if let Some(format) = format {
+        return Ok(format);
+    }
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("csv") | Some("txt") => Ok(TabularFormat::Csv),
+        Some("tsv") | Some("tab") => Ok(TabularFormat::Tsv),
+        Some("xlsx") | Some("xlsm") => Ok(TabularFormat::Xlsx),
+        _ => Err(KioskError::invalid(format!("Unknown tabular file type: {}", path))),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Read a page of rows from a CSV, TSV or XLSX file at a virtual path
+#[tauri::command]
+pub fn parse_tabular_file(
+    app: AppHandle,
+    path: String,
+    options: Option<TabularOptions>,
+) -> Result<TabularPage, KioskError> {
+    let options = options.unwrap_or_default();
+    let format = format_of(&path, options.format)?;
+    let real = vfs::resolve(&app, &path, Access::Read)?;
+    let size = fs::metadata(&real)?.len();
+    if size > MAX_FILE_BYTES {
+        return Err(KioskError::invalid(format!(
+            "The file is over the {} MB limit",
+            MAX_FILE_BYTES / (1024 * 1024)
+        )));
+    }
+    let data = fs::read(&real)?;
+    if options.offset == 0 {
+        let _ = recents::record(&app, "spreadsheet", &path);
+    }
+
+    let (sheets, sheet, mut rows) = match format {
+        TabularFormat::Xlsx => {
+            let (sheets, sheet, rows) = read_xlsx(&data, options.sheet.as_deref()).map_err(KioskError::invalid)?;
+            (sheets, Some(sheet), rows)
+        }
+        TabularFormat::Csv | TabularFormat::Tsv => {
+            let text = String::from_utf8_lossy(&data);
+            let delimiter = match (options.delimiter, format) {
+                (Some(delimiter), _) => delimiter,
+                (None, TabularFormat::Tsv) => '\t',
+                (None, _) => guess_delimiter(&text),
+            };
+            let rows = parse_delimited(&text, delimiter)
+                .into_iter()
+                .map(|row| row.into_iter().map(Cell::Text).collect())
+                .collect();
+            (Vec::new(), None, rows)
+        }
+    };
+
+    let header = if options.header && !rows.is_empty() {
+        Some(rows.remove(0))
+    } else {
+        None
+    };
+    // Trailing blank lines are not rows
+    while rows.last().is_some_and(|row| row.iter().all(|cell| cell.text().is_empty())) {
+        rows.pop();
+    }
+    let width = rows.iter().chain(&header).map(Vec::len).max().unwrap_or(0);
+    let columns: Vec<TabularColumn> = (0..width)
+        .map(|index| TabularColumn {
+            name: header
+                .as_ref()
+                .and_then(|header| header.get(index))
+                .map(Cell::text)
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| column_letters(index)),
+            column_type: if options.raw {
+                ColumnType::Text
+            } else {
+                column_type(&rows, index)
+            },
+        })
+        .collect();
+
+    let limit = options.limit.unwrap_or(DEFAULT_PAGE_ROWS).clamp(1, MAX_PAGE_ROWS);
+    let page = rows
+        .iter()
+        .skip(options.offset)
+        .take(limit)
+        .map(|row| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(index, column)| row.get(index).map_or(Value::Null, |cell| cell.value(column.column_type)))
+                .collect()
+        })
+        .collect();
+    Ok(TabularPage {
+        format,
+        sheets,
+        sheet,
+        columns,
+        rows: page,
+        offset: options.offset,
+        total_rows: rows.len(),
+    })
+}
+
+/// Write rows to a virtual path as CSV, TSV or XLSX, returning the number
+/// of data rows
+#[tauri::command]
+pub fn write_tabular_file(
+    app: AppHandle,
+    path: String,
+    data: TabularData,
+    options: Option<TabularWriteOptions>,
+) -> Result<usize, KioskError> {
+    let options = options.unwrap_or_default();
+    let bytes = match format_of(&path, options.format)? {
+        TabularFormat::Csv => write_delimited(&data, options.delimiter.unwrap_or(',')).into_bytes(),
+        TabularFormat::Tsv => write_delimited(&data, options.delimiter.unwrap_or('\t')).into_bytes(),
+        TabularFormat::Xlsx => write_xlsx(&data, options.sheet.as_deref().unwrap_or("Sheet1"))?,
+    };
+    fs::write(vfs::resolve_write(&app, &path, bytes.len() as u64)?, bytes)?;
+    let _ = recents::record(&app, "spreadsheet", &path);
+    Ok(data.rows.len())
+}
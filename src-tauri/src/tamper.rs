@@ -0,0 +1,313 @@
+//! Tamper detection
+//!
+//! For unattended public units: watches a case switch on a GPIO input, USB
+//! devices coming and going, and keyboards plugged in that are not on the
+//! allowed list. Each finding is published as `tamper-detected`, appended to
+//! `tamper.log` in the log folder (so support bundles carry it) and kept in
+//! `tamper.json`; optionally a camera photo is taken and the screen locked.
+//! USB changes come from the rules engine's hardware watcher; devices
+//! present at startup are the baseline.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::lock::{self, LockState};
+use crate::{config, events, rules, store};
+
+const CONFIG_FILE: &str = "tamper-config.json";
+
+const TAMPER_FILE: &str = "tamper.json";
+
+const LOG_FILE: &str = "tamper.log";
+
+/// Folder under the app data directory for tamper photos
+const PHOTO_DIR: &str = "tamper";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Findings kept in `tamper.json`
+const EVENTS_KEPT: usize = 200;
+
+const USB_DEVICES_DIR: &str = "/sys/bus/usb/devices";
+
+/// USB interface class and protocol of a boot keyboard
+const HID_CLASS: &str = "03";
+const KEYBOARD_PROTOCOL: &str = "01";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseSwitch {
+    /// GPIO input (sysfs numbering)
+    pub pin: u32,
+    /// Value the pin reads with the case open
+    pub open_value: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TamperConfig {
+    pub enabled: bool,
+    pub case_switch: Option<CaseSwitch>,
+    /// Report any USB device plugged in or removed
+    pub watch_usb: bool,
+    /// Keyboards expected on the unit, as "vendor:product" ids (hex); others
+    /// are reported even when `watch_usb` is off
+    pub allowed_keyboards: Vec<String>,
+    /// Take a photo with `camera` on each finding
+    pub capture_photo: bool,
+    pub camera: String,
+    /// Lock the screen on each finding (needs a lock PIN)
+    pub lock_screen: bool,
+}
+
+impl Default for TamperConfig {
+    fn default() -> Self {
+        TamperConfig {
+            enabled: false,
+            case_switch: None,
+            watch_usb: true,
+            allowed_keyboards: Vec::new(),
+            capture_photo: false,
+            camera: "/dev/video0".to_string(),
+            lock_screen: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperKind {
+    CaseOpened,
+    UsbInserted,
+    UsbRemoved,
+    KeyboardAttached,
+}
+
+/// Payload of `tamper-detected`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TamperEvent {
+    pub time: i64,
+    pub kind: TamperKind,
+    pub detail: String,
+    /// Photo taken at the time, under the app data directory
+    #[serde(default)]
+    pub photo: Option<String>,
+}
+
+pub struct TamperState {
+    config: Mutex<TamperConfig>,
+    history: Mutex<Vec<TamperEvent>>,
+}
+
+impl TamperState {
+    pub fn load(app: &AppHandle) -> Self {
+        TamperState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            history: Mutex::new(store::load(app, TAMPER_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Whether a USB device (sysfs name) has a keyboard interface
+fn is_keyboard(device: &str) -> bool {
+    let Ok(entries) = fs::read_dir(USB_DEVICES_DIR) else { return false };
+    let prefix = format!("{}:", device);
+    entries.flatten().any(|entry| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let read = |file: &str| fs::read_to_string(entry.path().join(file)).map(|text| text.trim().to_string());
+        name.starts_with(&prefix)
+            && read("bInterfaceClass").is_ok_and(|class| class == HID_CLASS)
+            && read("bInterfaceProtocol").is_ok_and(|protocol| protocol == KEYBOARD_PROTOCOL)
+    })
+}
+
+fn describe(device: &Value) -> String {
+    let field = |name: &str| device.get(name).and_then(Value::as_str).unwrap_or_default().to_string();
+    let ids = format!("{}:{}", field("vendor_id"), field("product_id"));
+    match [field("manufacturer"), field("product")].join(" ").trim() {
+        "" => format!("{} at {}", ids, field("path")),
+        name => format!("{} ({}) at {}", name, ids, field("path")),
+    }
+}
+
+/// Classify a USB change from the rules watcher, if it counts as tampering
+fn usb_finding(config: &TamperConfig, topic: &str, device: &Value) -> Option<(TamperKind, String)> {
+    let field = |name: &str| device.get(name).and_then(Value::as_str).unwrap_or_default().to_lowercase();
+    if topic == "usb-inserted" {
+        let ids = format!("{}:{}", field("vendor_id"), field("product_id"));
+        let allowed = config.allowed_keyboards.iter().any(|allowed| allowed.trim().to_lowercase() == ids);
+        if !allowed && is_keyboard(&field("path")) {
+            return Some((TamperKind::KeyboardAttached, describe(device)));
+        }
+    }
+    if !config.watch_usb {
+        return None;
+    }
+    match topic {
+        "usb-inserted" => Some((TamperKind::UsbInserted, describe(device))),
+        "usb-removed" => Some((TamperKind::UsbRemoved, describe(device))),
+        _ => None,
+    }
+}
+
+/// Grab one frame from the camera as a JPEG
+fn take_photo(app: &AppHandle, camera: &str, time: i64) -> Result<String, String> {
+    let path = store::data_path(app, &format!("{}/{}.jpg", PHOTO_DIR, time))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let status = Command::new("ffmpeg")
+        .args(["-loglevel", "error", "-f", "v4l2", "-i", camera, "-frames:v", "1", "-y"])
+        .arg(&path)
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|e| format!("Could not run ffmpeg: {}", e))?;
+    if !status.success() {
+        return Err(format!("Could not take a photo with {}", camera));
+    }
+    Ok(path.display().to_string())
+}
+
+fn log(app: &AppHandle, event: &TamperEvent) -> Result<(), String> {
+    let dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(LOG_FILE))
+        .map_err(|e| e.to_string())?;
+    let time = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let kind = serde_json::to_value(event.kind).unwrap_or_default();
+    writeln!(file, "{} {} {}", time, kind.as_str().unwrap_or_default(), event.detail).map_err(|e| e.to_string())
+}
+
+/// Record a finding and carry out the configured responses
+fn report(app: &AppHandle, kind: TamperKind, detail: String) {
+    let config = app.state::<TamperState>().config.lock().expect("tamper config lock").clone();
+    if config.lock_screen {
+        let _ = lock::lock(app, &app.state::<LockState>(), true);
+    }
+    let time = Local::now().timestamp();
+    let photo = config
+        .capture_photo
+        .then(|| take_photo(app, &config.camera, time).ok())
+        .flatten();
+    let event = TamperEvent {
+        time,
+        kind,
+        detail,
+        photo,
+    };
+    let _ = log(app, &event);
+
+    let state = app.state::<TamperState>();
+    let mut history = state.history.lock().expect("tamper history lock");
+    history.push(event.clone());
+    let excess = history.len().saturating_sub(EVENTS_KEPT);
+    history.drain(..excess);
+    let _ = store::save(app, TAMPER_FILE, &*history);
+    drop(history);
+    events::publish(app, "tamper-detected", event);
+}
+
+/// Listen for USB changes and spawn the case switch watcher
+pub fn start_tamper(app: AppHandle) {
+    events::listen(&app, |app, event| {
+        let config = app.state::<TamperState>().config.lock().expect("tamper config lock").clone();
+        if !config.enabled {
+            return;
+        }
+        if let Some((kind, detail)) = usb_finding(&config, &event.topic, &event.payload) {
+            // A photo takes a moment; keep it off the publisher's thread
+            let app = app.clone();
+            std::thread::spawn(move || report(&app, kind, detail));
+        }
+    });
+
+    std::thread::spawn(move || {
+        let mut was_open = None;
+        loop {
+            let config = app.state::<TamperState>().config.lock().expect("tamper config lock").clone();
+            let open = config
+                .case_switch
+                .filter(|_| config.enabled)
+                .and_then(|switch| Some(rules::gpio_value(switch.pin)? == switch.open_value));
+            // Only a change to open counts, so a unit started open reports once
+            if open == Some(true) && was_open != Some(true) {
+                report(&app, TamperKind::CaseOpened, "Case switch opened".to_string());
+            }
+            was_open = open;
+            std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_tamper_config(state: State<'_, TamperState>) -> TamperConfig {
+    state.config.lock().expect("tamper config lock").clone()
+}
+
+/// Update tamper detection (admin)
+#[tauri::command]
+pub fn set_tamper_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, TamperState>,
+    config: TamperConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    for ids in &config.allowed_keyboards {
+        let valid = ids
+            .split_once(':')
+            .is_some_and(|(vendor, product)| [vendor, product].iter().all(|id| u16::from_str_radix(id.trim(), 16).is_ok()));
+        if !valid {
+            return Err(KioskError::invalid(format!("Not a vendor:product id pair: {}", ids)));
+        }
+    }
+    store::save(&app, CONFIG_FILE, &config)?;
+    *state.config.lock().expect("tamper config lock") = config;
+    Ok(())
+}
+
+/// Recorded findings, newest first
+#[tauri::command]
+pub fn list_tamper_events(state: State<'_, TamperState>, limit: Option<usize>) -> Vec<TamperEvent> {
+    let history = state.history.lock().expect("tamper history lock");
+    history.iter().rev().take(limit.unwrap_or(usize::MAX)).cloned().collect()
+}
+
+/// Forget recorded findings and delete their photos (admin)
+#[tauri::command]
+pub fn clear_tamper_events(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, TamperState>,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut history = state.history.lock().expect("tamper history lock");
+    for photo in history.iter().filter_map(|event| event.photo.as_ref()) {
+        let _ = fs::remove_file(photo);
+    }
+    history.clear();
+    Ok(store::save(&app, TAMPER_FILE, &*history)?)
+}
@@ -0,0 +1,404 @@
+//! Document templates
+//!
+//! Renders MiniJinja templates from the `templates` folder in the app data
+//! directory as HTML, plain text or PDF, for receipts, badges, reports and
+//! signage slides. Templates are named by their path under the folder;
+//! `.html` templates escape values automatically and may `extends` or
+//! `include` each other. PDFs are printed from the HTML with `wkhtmltopdf`,
+//! or headless Chromium when that is missing. The folder is watched, so
+//! edited templates are used without a restart and `templates-changed` is
+//! published.
+//!
+//! Besides the MiniJinja built-ins, templates have `date` (a timestamp or
+//! RFC 3339 string in the kiosk's date style: short, medium, long or time),
+//! `currency` (an amount in an ISO 4217 currency) and `number` filters
+//! formatted for the kiosk's locale.
+
+use base64::Engine;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use minijinja::{Environment, Value};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::i18n::{self, DateStyle};
+use crate::{config, events, formatting, recents, session, store, vfs};
+
+const TEMPLATES_DIR: &str = "templates";
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Extensions of templates that render to HTML
+const HTML_EXTENSIONS: &[&str] = &["html", "htm"];
+
+/// Tags that end a line when HTML is turned into text
+const BLOCK_TAGS: &[&str] = &["br", "p", "div", "tr", "li", "h1", "h2", "h3", "h4", "h5", "h6", "table", "hr"];
+
+/// Names the temporary files of concurrent PDF renders apart
+static PDF_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateOutput {
+    #[default]
+    Html,
+    Text,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedTemplate {
+    pub output: TemplateOutput,
+    /// The document; base64 for PDFs
+    pub content: String,
+    /// Virtual path it was saved to, when asked for
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateInfo {
+    /// Path under the templates folder, with `/` separators
+    pub name: String,
+    pub html: bool,
+    pub size: u64,
+    /// Last modified, RFC 3339
+    pub modified: Option<String>,
+}
+
+/// Payload of `templates-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplatesChanged {
+    pub templates: Vec<String>,
+}
+
+pub struct TemplatesState {
+    dir: PathBuf,
+    env: Mutex<Environment<'static>>,
+}
+
+impl TemplatesState {
+    pub fn load(app: &AppHandle) -> Self {
+        let dir = store::data_path(app, TEMPLATES_DIR).unwrap_or_else(|_| std::env::temp_dir().join(TEMPLATES_DIR));
+        let _ = fs::create_dir_all(&dir);
+        TemplatesState {
+            env: Mutex::new(environment(app, &dir)),
+            dir,
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn filter_error(message: impl Into<String>) -> minijinja::Error {
+    minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, message.into())
+}
+
+fn date_style(style: Option<&str>) -> Result<DateStyle, minijinja::Error> {
+    match style.unwrap_or("medium") {
+        "short" => Ok(DateStyle::Short),
+        "medium" => Ok(DateStyle::Medium),
+        "long" => Ok(DateStyle::Long),
+        "time" => Ok(DateStyle::Time),
+        other => Err(filter_error(format!("Unknown date style: {}", other))),
+    }
+}
+
+/// A Unix timestamp in seconds, an RFC 3339 time or a "YYYY-MM-DD" date
+fn parse_time(value: &Value) -> Result<DateTime<Local>, minijinja::Error> {
+    if let Some(text) = value.as_str() {
+        if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+            return Ok(time.with_timezone(&Local));
+        }
+        return NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .and_then(|time| Local.from_local_datetime(&time).earliest())
+            .ok_or_else(|| filter_error(format!("Not a date: {}", text)));
+    }
+    i64::try_from(value.clone())
+        .ok()
+        .and_then(|secs| Local.timestamp_opt(secs, 0).single())
+        .ok_or_else(|| filter_error(format!("Not a date: {}", value)))
+}
+
+fn environment(app: &AppHandle, dir: &Path) -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_loader(minijinja::path_loader(dir));
+    env.add_filter("date", |value: Value, style: Option<String>| {
+        Ok(i18n::format_date(&parse_time(&value)?, date_style(style.as_deref())?))
+    });
+    let handle = app.clone();
+    env.add_filter("currency", move |amount: f64, currency: String| {
+        formatting::format_currency(handle.clone(), amount, currency, None).map_err(|e| filter_error(e.message))
+    });
+    let handle = app.clone();
+    env.add_filter("number", move |value: f64, digits: Option<u32>| {
+        formatting::format_number(handle.clone(), value, digits, None).map_err(|e| filter_error(e.message))
+    });
+    env
+}
+
+fn is_html(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| HTML_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known)))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Readable text of an HTML document: tags dropped, block elements on
+/// their own lines, scripts and styles left out
+fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        rest = &rest[start + end + 1..];
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        if (name == "script" || name == "style") && !tag.starts_with('/') {
+            let close = format!("</{}", name);
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .map_or("", |at| &rest[at..]);
+        } else if BLOCK_TAGS.contains(&name.as_str()) && !text.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&#x2f;", "/")
+        .replace("&amp;", "&");
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let mut result = String::new();
+    for line in lines {
+        // Collapse runs of blank lines left by nested blocks
+        if line.is_empty() && (result.is_empty() || result.ends_with("\n\n")) {
+            continue;
+        }
+        result.push_str(&line);
+        result.push('\n');
+    }
+    result.trim_end().to_string()
+}
+
+/// Print an HTML document to PDF with whichever converter is installed
+pub(crate) fn html_to_pdf(html: &str) -> Result<Vec<u8>, String> {
+    let dir = session::session_temp_dir();
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let stem = format!("template-{}-{}", std::process::id(), PDF_COUNTER.fetch_add(1, Ordering::Relaxed));
+    let input = dir.join(format!("{}.html", stem));
+    let output = dir.join(format!("{}.pdf", stem));
+    fs::write(&input, html).map_err(|e| e.to_string())?;
+
+    let input_arg = input.display().to_string();
+    let output_arg = output.display().to_string();
+    let print_to_pdf = format!("--print-to-pdf={}", output_arg);
+    let url = format!("file://{}", input_arg);
+    let converters: [(&str, Vec<&str>); 3] = [
+        ("wkhtmltopdf", vec!["--quiet", &input_arg, &output_arg]),
+        ("chromium", vec!["--headless", "--disable-gpu", "--no-pdf-header-footer", &print_to_pdf, &url]),
+        ("chromium-browser", vec!["--headless", "--disable-gpu", "--no-pdf-header-footer", &print_to_pdf, &url]),
+    ];
+    let mut result = Err("No PDF converter available (install wkhtmltopdf or chromium)".to_string());
+    for (program, args) in converters {
+        let status = Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if status.success() && output.exists() => {
+                result = fs::read(&output).map_err(|e| e.to_string());
+                break;
+            }
+            Ok(_) => result = Err(format!("{} failed to print the template", program)),
+            Err(_) => {}
+        }
+    }
+    let _ = fs::remove_file(&input);
+    let _ = fs::remove_file(&output);
+    result
+}
+
+fn render(state: &TemplatesState, name: &str, data: &impl Serialize) -> Result<String, KioskError> {
+    let env = state.env.lock().expect("templates lock");
+    let template = env.get_template(name).map_err(|e| match e.kind() {
+        minijinja::ErrorKind::TemplateNotFound => KioskError::not_found(format!("No template named {}", name)),
+        _ => KioskError::invalid(format!("Template {}: {:#}", name, e)),
+    })?;
+    template
+        .render(data)
+        .map_err(|e| KioskError::invalid(format!("Template {}: {:#}", name, e)))
+}
+
+/// Render the first of `names` found in the templates folder, or `None`
+/// when there is none of them
+pub(crate) fn render_first(
+    app: &AppHandle,
+    names: &[&str],
+    data: &impl Serialize,
+) -> Result<Option<String>, KioskError> {
+    let state = app.state::<TemplatesState>();
+    match names.iter().find(|name| state.dir.join(name).is_file()) {
+        Some(name) => render(&state, name, data).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Every template under the folder with its modification time
+fn scan(dir: &Path) -> Vec<(String, fs::Metadata)> {
+    fn walk(dir: &Path, prefix: &str, found: &mut Vec<(String, fs::Metadata)>) {
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else { continue };
+            let path = format!("{}{}", prefix, name);
+            if metadata.is_dir() {
+                walk(&entry.path(), &format!("{}/", path), found);
+            } else {
+                found.push((path, metadata));
+            }
+        }
+    }
+    let mut found = Vec::new();
+    walk(dir, "", &mut found);
+    found.sort_by(|a, b| a.0.cmp(&b.0));
+    found
+}
+
+fn fingerprint(dir: &Path) -> Vec<(String, u64, Option<SystemTime>)> {
+    scan(dir)
+        .into_iter()
+        .map(|(name, metadata)| (name, metadata.len(), metadata.modified().ok()))
+        .collect()
+}
+
+/// Watch the templates folder, dropping cached templates when it changes
+pub fn start_templates(app: AppHandle) {
+    std::thread::spawn(move || {
+        let state = app.state::<TemplatesState>();
+        let mut last = fingerprint(&state.dir);
+        loop {
+            std::thread::sleep(config::poll_interval(&app, WATCH_INTERVAL));
+            let now = fingerprint(&state.dir);
+            if now == last {
+                continue;
+            }
+            state.env.lock().expect("templates lock").clear_templates();
+            let templates = now.iter().map(|(name, _, _)| name.clone()).collect();
+            last = now;
+            events::publish(&app, "templates-changed", TemplatesChanged { templates });
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Templates in the templates folder
+#[tauri::command]
+pub fn list_templates(state: State<'_, TemplatesState>) -> Vec<TemplateInfo> {
+    scan(&state.dir)
+        .into_iter()
+        .map(|(name, metadata)| TemplateInfo {
+            html: is_html(&name),
+            size: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .map(|time| DateTime::<Local>::from(time).to_rfc3339()),
+            name,
+        })
+        .collect()
+}
+
+/// Render template `name` with `data` as HTML, plain text or PDF,
+/// optionally saving it to the virtual path `path`
+#[tauri::command]
+pub fn render_template(
+    app: AppHandle,
+    state: State<'_, TemplatesState>,
+    name: String,
+    data: serde_json::Value,
+    output: Option<TemplateOutput>,
+    path: Option<String>,
+) -> Result<RenderedTemplate, KioskError> {
+    let output = output.unwrap_or_default();
+    let rendered = render(&state, &name, &data)?;
+
+    let html = is_html(&name);
+    let bytes = match output {
+        TemplateOutput::Html if html => rendered.into_bytes(),
+        TemplateOutput::Html => format!("<pre>{}</pre>", escape_html(&rendered)).into_bytes(),
+        TemplateOutput::Text if html => html_to_text(&rendered).into_bytes(),
+        TemplateOutput::Text => rendered.into_bytes(),
+        TemplateOutput::Pdf => {
+            let document = if html {
+                rendered
+            } else {
+                format!("<!DOCTYPE html><meta charset=\"utf-8\"><pre>{}</pre>", escape_html(&rendered))
+            };
+            html_to_pdf(&document)?
+        }
+    };
+
+    let saved = match path {
+        Some(path) => {
+            let real = vfs::resolve_write(&app, &path, bytes.len() as u64)?;
+            fs::write(&real, &bytes)?;
+            let _ = recents::record(&app, "templates", &path);
+            Some(path)
+        }
+        None => None,
+    };
+    let content = if output == TemplateOutput::Pdf {
+        base64::engine::general_purpose::STANDARD.encode(&bytes)
+    } else {
+        String::from_utf8(bytes).unwrap_or_default()
+    };
+    Ok(RenderedTemplate {
+        output,
+        content,
+        path: saved,
+    })
+}
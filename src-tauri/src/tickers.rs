@@ -0,0 +1,289 @@
+//! Stock and crypto tickers
+//!
+//! Polls quotes for the taskbar ticker: stocks from Yahoo Finance's chart
+//! endpoint and crypto from CoinGecko, neither of which needs an API key.
+//! Requests are spaced out to stay under the providers' rate limits, and the
+//! last good quote is kept (flagged stale) whenever a fetch fails.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::http::ResponseExt;
+use crate::{events, http, metering, store};
+
+const CONFIG_FILE: &str = "tickers.json";
+const CACHE_FILE: &str = "tickers_cache.json";
+
+const YAHOO_CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+const COINGECKO_PRICE_URL: &str = "https://api.coingecko.com/api/v3/simple/price";
+
+/// Minimum gap between two provider requests
+const REQUEST_SPACING: Duration = Duration::from_millis(1500);
+
+/// Lower bound for the configured poll interval
+const MIN_POLL_SECONDS: u64 = 60;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TickerKind {
+    Stock,
+    Crypto,
+}
+
+/// A symbol shown on the ticker. Crypto symbols are CoinGecko ids ("bitcoin").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerSymbol {
+    pub symbol: String,
+    pub kind: TickerKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickerConfig {
+    pub symbols: Vec<TickerSymbol>,
+    /// Quote currency for crypto prices
+    pub currency: String,
+    pub poll_seconds: u64,
+}
+
+impl Default for TickerConfig {
+    fn default() -> Self {
+        TickerConfig {
+            symbols: Vec::new(),
+            currency: "usd".to_string(),
+            poll_seconds: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    pub symbol: String,
+    pub kind: TickerKind,
+    pub price: f64,
+    pub change_percent: Option<f64>,
+    pub currency: String,
+    pub updated_at: i64,
+    /// The last fetch failed and this is the previous value
+    #[serde(default)]
+    pub stale: bool,
+}
+
+pub struct TickersState {
+    config: Mutex<TickerConfig>,
+    quotes: Mutex<HashMap<String, Quote>>,
+    last_request: Mutex<Option<Instant>>,
+}
+
+impl TickersState {
+    pub fn load(app: &AppHandle) -> Self {
+        TickersState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            quotes: Mutex::new(store::load(app, CACHE_FILE)),
+            last_request: Mutex::new(None),
+        }
+    }
+
+    /// Sleep until the next provider request is allowed
+    fn throttle(&self) {
+        let mut last = self.last_request.lock().expect("ticker throttle lock");
+        if let Some(elapsed) = last.map(|at| at.elapsed()) {
+            if elapsed < REQUEST_SPACING {
+                std::thread::sleep(REQUEST_SPACING - elapsed);
+            }
+        }
+        *last = Some(Instant::now());
+    }
+}
+
+#[derive(Deserialize)]
+struct ChartResponse {
+    chart: ChartBody,
+}
+
+#[derive(Deserialize)]
+struct ChartBody {
+    result: Option<Vec<ChartResult>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartResult {
+    meta: ChartMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChartMeta {
+    regular_market_price: f64,
+    chart_previous_close: Option<f64>,
+    currency: Option<String>,
+}
+
+// ============================================================================
+// Fetching
+// ============================================================================
+
+fn fetch_stock(state: &TickersState, symbol: &str) -> Result<Quote, String> {
+    state.throttle();
+    let response: ChartResponse = http::agent()
+        .get(&format!("{}/{}", YAHOO_CHART_URL, symbol))
+        .query("range", "1d")
+        .query("interval", "1d")
+        .call()
+        .map_err(|e| format!("Quote request for {} failed: {}", symbol, e))?
+        .into_throttled_json()
+        .map_err(|e| e.to_string())?;
+
+    let meta = response
+        .chart
+        .result
+        .and_then(|results| results.into_iter().next())
+        .map(|result| result.meta)
+        .ok_or_else(|| format!("Unknown symbol: {}", symbol))?;
+
+    Ok(Quote {
+        symbol: symbol.to_string(),
+        kind: TickerKind::Stock,
+        price: meta.regular_market_price,
+        change_percent: meta
+            .chart_previous_close
+            .filter(|close| *close != 0.0)
+            .map(|close| (meta.regular_market_price - close) / close * 100.0),
+        currency: meta.currency.unwrap_or_default(),
+        updated_at: Local::now().timestamp(),
+        stale: false,
+    })
+}
+
+/// Fetch every crypto id in one request
+fn fetch_crypto(state: &TickersState, ids: &[String], currency: &str) -> Result<Vec<Quote>, String> {
+    state.throttle();
+    let response: HashMap<String, HashMap<String, f64>> = http::agent()
+        .get(COINGECKO_PRICE_URL)
+        .query("ids", &ids.join(","))
+        .query("vs_currencies", currency)
+        .query("include_24hr_change", "true")
+        .call()
+        .map_err(|e| format!("Crypto price request failed: {}", e))?
+        .into_throttled_json()
+        .map_err(|e| e.to_string())?;
+
+    let now = Local::now().timestamp();
+    Ok(ids
+        .iter()
+        .filter_map(|id| {
+            let prices = response.get(id)?;
+            Some(Quote {
+                symbol: id.clone(),
+                kind: TickerKind::Crypto,
+                price: *prices.get(currency)?,
+                change_percent: prices.get(&format!("{}_24h_change", currency)).copied(),
+                currency: currency.to_uppercase(),
+                updated_at: now,
+                stale: false,
+            })
+        })
+        .collect())
+}
+
+/// Refresh all configured quotes, keeping old values for anything that failed
+fn poll(app: &AppHandle, state: &TickersState) -> Result<(), String> {
+    let config = state.config.lock().expect("ticker config lock").clone();
+    let mut fresh: Vec<Quote> = Vec::new();
+
+    for ticker in config.symbols.iter().filter(|ticker| ticker.kind == TickerKind::Stock) {
+        if let Ok(quote) = fetch_stock(state, &ticker.symbol) {
+            fresh.push(quote);
+        }
+    }
+
+    let crypto: Vec<String> = config
+        .symbols
+        .iter()
+        .filter(|ticker| ticker.kind == TickerKind::Crypto)
+        .map(|ticker| ticker.symbol.to_lowercase())
+        .collect();
+    if !crypto.is_empty() {
+        if let Ok(quotes) = fetch_crypto(state, &crypto, &config.currency.to_lowercase()) {
+            fresh.extend(quotes);
+        }
+    }
+
+    let mut quotes = state.quotes.lock().expect("ticker quotes lock");
+    for quote in quotes.values_mut() {
+        quote.stale = true;
+    }
+    for quote in fresh {
+        quotes.insert(quote.symbol.clone(), quote);
+    }
+    store::save(app, CACHE_FILE, &*quotes)?;
+
+    events::publish(app, "quotes-updated", quotes.len());
+    Ok(())
+}
+
+/// Spawn the background quote poller
+pub fn start_tickers(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<TickersState>();
+        let (configured, poll_seconds) = {
+            let config = state.config.lock().expect("ticker config lock");
+            (!config.symbols.is_empty(), config.poll_seconds.max(MIN_POLL_SECONDS))
+        };
+        if configured && metering::background_allowed(&app) {
+            let _ = poll(&app, &state);
+        }
+        std::thread::sleep(Duration::from_secs(poll_seconds));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the latest quotes in configured order. Served from cache; never blocks on the network.
+#[tauri::command]
+pub fn get_quotes(state: State<'_, TickersState>) -> Vec<Quote> {
+    let config = state.config.lock().expect("ticker config lock");
+    let quotes = state.quotes.lock().expect("ticker quotes lock");
+    config
+        .symbols
+        .iter()
+        .filter_map(|ticker| {
+            let key = match ticker.kind {
+                TickerKind::Stock => ticker.symbol.clone(),
+                TickerKind::Crypto => ticker.symbol.to_lowercase(),
+            };
+            quotes.get(&key).cloned()
+        })
+        .collect()
+}
+
+/// Get the ticker symbols and poll interval
+#[tauri::command]
+pub fn get_ticker_config(state: State<'_, TickersState>) -> TickerConfig {
+    state.config.lock().expect("ticker config lock").clone()
+}
+
+/// Update the ticker symbols and poll interval
+#[tauri::command]
+pub fn set_ticker_config(app: AppHandle, state: State<'_, TickersState>, mut config: TickerConfig) -> Result<(), KioskError> {
+    config.poll_seconds = config.poll_seconds.max(MIN_POLL_SECONDS);
+    config.symbols.retain(|ticker| !ticker.symbol.trim().is_empty());
+    for ticker in &mut config.symbols {
+        ticker.symbol = ticker.symbol.trim().to_string();
+    }
+
+    let mut current = state.config.lock().expect("ticker config lock");
+    *current = config;
+    Ok(store::save(&app, CONFIG_FILE, &*current)?)
+}
@@ -0,0 +1,576 @@
+//! Ticket printing
+//!
+//! A persistent print queue for ESC/POS ticket and wristband printers. Jobs
+//! survive restarts, wait out paper-outs and jams, and are retried with a
+//! delay; printer state changes are reported as `ticket-printer-status`
+//! events and job changes as `ticket-job` events.
+//!
+//! Templates are plain text with `{{field}}` placeholders and directive lines
+//! starting with `!`: `!left`, `!center`, `!right`, `!bold on|off`,
+//! `!size <1-8>`, `!barcode <data>` (Code 128), `!qr <data>`, `!feed <lines>`
+//! and `!cut`. A cut is added at the end when the template has none.
+//!
+//! The printer is set up by admins, and a local printer must be a printer
+//! or serial character device (`/dev/usb/lp*`, `/dev/lp*`, `/dev/tty*`).
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::os::unix::fs::FileTypeExt;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::lazy::{self, Subsystem};
+use crate::{config, events, store};
+
+const CONFIG_FILE: &str = "ticket_printer.json";
+const QUEUE_FILE: &str = "ticket_queue.json";
+
+const WORKER_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Device nodes a local printer may be opened at
+const DEVICE_PREFIXES: &[&str] = &["/dev/usb/lp", "/dev/lp", "/dev/tty"];
+const STATUS_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// Finished jobs kept for the queue view
+const MAX_FINISHED_JOBS: usize = 50;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PrinterConnection {
+    /// Character device such as /dev/usb/lp0
+    Device { path: String },
+    /// Raw TCP, usually port 9100
+    Tcp { host: String, port: u16 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TicketPrinterConfig {
+    pub connection: PrinterConnection,
+    pub max_retries: u32,
+    pub retry_delay_secs: i64,
+}
+
+impl Default for TicketPrinterConfig {
+    fn default() -> Self {
+        TicketPrinterConfig {
+            connection: PrinterConnection::Device {
+                path: "/dev/usb/lp0".to_string(),
+            },
+            max_retries: 5,
+            retry_delay_secs: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaperStatus {
+    Ok,
+    NearEnd,
+    Out,
+    Unknown,
+}
+
+/// Printer state, emitted as `ticket-printer-status` when it changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TicketPrinterStatus {
+    pub online: bool,
+    pub paper: PaperStatus,
+    pub cover_open: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketJobStatus {
+    Queued,
+    Printed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TicketJob {
+    pub id: String,
+    pub status: TicketJobStatus,
+    pub attempts: u32,
+    pub created_at: i64,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    /// Rendered ESC/POS bytes
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    data: Vec<u8>,
+}
+
+pub struct TicketState {
+    config: Mutex<TicketPrinterConfig>,
+    queue: Mutex<Vec<TicketJob>>,
+    status: Mutex<Option<TicketPrinterStatus>>,
+}
+
+impl Subsystem for TicketState {
+    const NAME: &'static str = "tickets";
+
+    fn load(app: &AppHandle) -> Self {
+        TicketState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            queue: Mutex::new(store::load(app, QUEUE_FILE)),
+            status: Mutex::new(None),
+        }
+    }
+
+    fn start(app: AppHandle) {
+        start_ticket_queue(app);
+    }
+}
+
+// ============================================================================
+// ESC/POS Rendering
+// ============================================================================
+
+const ESC: u8 = 0x1B;
+const GS: u8 = 0x1D;
+const DLE: u8 = 0x10;
+const EOT: u8 = 0x04;
+
+fn fill_placeholders(line: &str, data: &HashMap<String, String>) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        let key = rest[start + 2..start + end].trim();
+        out.push_str(data.get(key).map(String::as_str).unwrap_or_default());
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Printable ASCII for the printer's default code page
+fn text_bytes(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| if (' '..='~').contains(&c) { c as u8 } else { b'?' })
+        .collect()
+}
+
+fn code128(data: &str) -> Vec<u8> {
+    let mut payload = b"{B".to_vec();
+    payload.extend(text_bytes(data).into_iter().take(253));
+    let mut bytes = vec![
+        GS, b'h', 80, // height in dots
+        GS, b'w', 2, // module width
+        GS, b'H', 2, // human-readable text below
+        GS, b'k', 73, payload.len() as u8,
+    ];
+    bytes.extend(payload);
+    bytes.push(b'\n');
+    bytes
+}
+
+fn qr_code(data: &str) -> Vec<u8> {
+    let payload = data.as_bytes();
+    let stored = (payload.len() + 3) as u16;
+    let mut bytes = vec![
+        GS, b'(', b'k', 4, 0, 49, 65, 50, 0, // model 2
+        GS, b'(', b'k', 3, 0, 49, 67, 6, // module size
+        GS, b'(', b'k', 3, 0, 49, 69, 49, // error correction M
+        GS, b'(', b'k', (stored & 0xFF) as u8, (stored >> 8) as u8, 49, 80, 48,
+    ];
+    bytes.extend(payload);
+    bytes.extend([GS, b'(', b'k', 3, 0, 49, 81, 48, b'\n']);
+    bytes
+}
+
+/// Render a ticket template to ESC/POS commands
+fn render(template: &str, data: &HashMap<String, String>) -> Result<Vec<u8>, String> {
+    let mut out = vec![ESC, b'@'];
+    let mut cut = false;
+
+    for raw in template.lines() {
+        let line = fill_placeholders(raw, data);
+        let Some(directive) = line.strip_prefix('!') else {
+            out.extend(text_bytes(&line));
+            out.push(b'\n');
+            continue;
+        };
+
+        let (name, argument) = directive.split_once(' ').unwrap_or((directive, ""));
+        let argument = argument.trim();
+        match name.to_lowercase().as_str() {
+            "left" => out.extend([ESC, b'a', 0]),
+            "center" => out.extend([ESC, b'a', 1]),
+            "right" => out.extend([ESC, b'a', 2]),
+            "bold" => out.extend([ESC, b'E', (argument != "off") as u8]),
+            "size" => {
+                let size: u8 = argument.parse().map_err(|_| format!("Invalid size: {}", argument))?;
+                let scale = size.clamp(1, 8) - 1;
+                out.extend([GS, b'!', (scale << 4) | scale]);
+            }
+            "barcode" => out.extend(code128(argument)),
+            "qr" => out.extend(qr_code(argument)),
+            "feed" => out.extend([ESC, b'd', argument.parse().unwrap_or(1)]),
+            "cut" => {
+                out.extend([GS, b'V', 66, 0]);
+                cut = true;
+            }
+            other => return Err(format!("Unknown ticket directive: !{}", other)),
+        }
+    }
+
+    if !cut {
+        out.extend([GS, b'V', 66, 0]);
+    }
+    Ok(out)
+}
+
+// ============================================================================
+// Printer I/O
+// ============================================================================
+
+trait PrinterPort: Read + Write {}
+impl<T: Read + Write> PrinterPort for T {}
+
+/// Fail unless `path` names a printer or serial device node; one that is
+/// not plugged in yet passes, but never a regular file
+fn check_device(path: &str) -> Result<(), String> {
+    let named = DEVICE_PREFIXES.iter().any(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric()))
+    });
+    if !named {
+        return Err(format!("{} is not a printer or serial device", path));
+    }
+    match fs::metadata(path) {
+        Ok(metadata) if !metadata.file_type().is_char_device() => Err(format!("{} is not a device", path)),
+        _ => Ok(()),
+    }
+}
+
+fn open(connection: &PrinterConnection) -> Result<Box<dyn PrinterPort>, String> {
+    match connection {
+        PrinterConnection::Device { path } => {
+            // Checked again, since the config file may predate the rule
+            check_device(path)?;
+            let file = OpenOptions::new()
+                .write(true)
+                .open(path)
+                .map_err(|e| format!("Cannot open {}: {}", path, e))?;
+            if !file.metadata().is_ok_and(|metadata| metadata.file_type().is_char_device()) {
+                return Err(format!("{} is not a device", path));
+            }
+            Ok(Box::new(file))
+        }
+        PrinterConnection::Tcp { host, port } => {
+            let stream = TcpStream::connect((host.as_str(), *port)).map_err(|e| format!("Cannot reach printer: {}", e))?;
+            stream.set_read_timeout(Some(STATUS_TIMEOUT)).map_err(|e| e.to_string())?;
+            Ok(Box::new(stream))
+        }
+    }
+}
+
+/// Ask for one real-time status byte (DLE EOT n)
+fn status_byte(port: &mut dyn PrinterPort, kind: u8) -> Option<u8> {
+    port.write_all(&[DLE, EOT, kind]).ok()?;
+    let mut byte = [0u8; 1];
+    match port.read(&mut byte) {
+        Ok(1) => Some(byte[0]),
+        _ => None,
+    }
+}
+
+/// Paper and cover state from DLE EOT over a network connection
+fn network_status(connection: &PrinterConnection) -> TicketPrinterStatus {
+    let mut port = match open(connection) {
+        Ok(port) => port,
+        Err(error) => return offline(error),
+    };
+
+    // Printers that don't answer status requests are assumed ready
+    let offline_cause = status_byte(port.as_mut(), 2);
+    let paper = match status_byte(port.as_mut(), 4) {
+        Some(byte) if byte & 0x60 != 0 => PaperStatus::Out,
+        Some(byte) if byte & 0x0C != 0 => PaperStatus::NearEnd,
+        Some(_) => PaperStatus::Ok,
+        None => PaperStatus::Unknown,
+    };
+
+    TicketPrinterStatus {
+        online: true,
+        paper,
+        cover_open: offline_cause.is_some_and(|byte| byte & 0x04 != 0),
+        error: None,
+    }
+}
+
+/// Paper state from the usblp driver; reading a device that never answers
+/// DLE EOT would block forever, so the kernel's LPGETSTATUS is used instead
+#[cfg(target_os = "linux")]
+fn device_status(path: &str) -> TicketPrinterStatus {
+    use std::os::unix::io::AsRawFd;
+
+    const LPGETSTATUS: libc::c_ulong = 0x060b;
+    const LP_NOPA: libc::c_int = 0x20;
+
+    if let Err(e) = check_device(path) {
+        return offline(e);
+    }
+    let file = match OpenOptions::new().write(true).open(path) {
+        Ok(file) => file,
+        Err(e) => return offline(format!("Cannot open {}: {}", path, e)),
+    };
+    let mut status: libc::c_int = 0;
+    // SAFETY: LPGETSTATUS writes a single int through the pointer
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), LPGETSTATUS as _, &mut status) };
+
+    TicketPrinterStatus {
+        online: true,
+        paper: match (result, status & LP_NOPA) {
+            (0, 0) => PaperStatus::Ok,
+            (0, _) => PaperStatus::Out,
+            _ => PaperStatus::Unknown,
+        },
+        cover_open: false,
+        error: None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn device_status(path: &str) -> TicketPrinterStatus {
+    if let Err(e) = check_device(path) {
+        return offline(e);
+    }
+    match OpenOptions::new().write(true).open(path) {
+        Ok(_) => TicketPrinterStatus {
+            online: true,
+            paper: PaperStatus::Unknown,
+            cover_open: false,
+            error: None,
+        },
+        Err(e) => offline(format!("Cannot open {}: {}", path, e)),
+    }
+}
+
+fn offline(error: String) -> TicketPrinterStatus {
+    TicketPrinterStatus {
+        online: false,
+        paper: PaperStatus::Unknown,
+        cover_open: false,
+        error: Some(error),
+    }
+}
+
+fn query_status(connection: &PrinterConnection) -> TicketPrinterStatus {
+    match connection {
+        PrinterConnection::Device { path } => device_status(path),
+        PrinterConnection::Tcp { .. } => network_status(connection),
+    }
+}
+
+/// Record the printer status, emitting an event when it changes
+fn update_status(app: &AppHandle, state: &TicketState, status: TicketPrinterStatus) {
+    let mut current = state.status.lock().expect("ticket status lock");
+    if current.as_ref() != Some(&status) {
+        events::publish(app, "ticket-printer-status", &status);
+        *current = Some(status);
+    }
+}
+
+fn save_queue(app: &AppHandle, queue: &mut Vec<TicketJob>) -> Result<(), String> {
+    // Keep every pending job but only the most recent finished ones
+    let finished = queue.iter().filter(|job| job.status != TicketJobStatus::Queued).count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+    queue.retain(|job| {
+        let drop = excess > 0 && job.status != TicketJobStatus::Queued;
+        if drop {
+            excess -= 1;
+        }
+        !drop
+    });
+    store::save(app, QUEUE_FILE, &*queue)
+}
+
+/// Try to print the oldest due job
+fn process(app: &AppHandle, state: &TicketState) -> Result<(), String> {
+    let now = Local::now().timestamp();
+    let Some(job) = state
+        .queue
+        .lock()
+        .expect("ticket queue lock")
+        .iter()
+        .find(|job| job.status == TicketJobStatus::Queued && job.next_attempt_at <= now)
+        .cloned()
+    else {
+        return Ok(());
+    };
+
+    let config = state.config.lock().expect("ticket config lock").clone();
+    let status = query_status(&config.connection);
+    let blocked = match (&status.error, status.paper, status.cover_open) {
+        (Some(error), _, _) => Some(error.clone()),
+        (None, PaperStatus::Out, _) => Some("Out of paper".to_string()),
+        (None, _, true) => Some("Printer cover open".to_string()),
+        _ => None,
+    };
+    update_status(app, state, status);
+
+    let result = match blocked {
+        Some(reason) => Err(reason),
+        None => open(&config.connection).and_then(|mut port| {
+            port.write_all(&job.data).and_then(|_| port.flush()).map_err(|e| e.to_string())
+        }),
+    };
+
+    let mut queue = state.queue.lock().expect("ticket queue lock");
+    let Some(entry) = queue.iter_mut().find(|entry| entry.id == job.id) else {
+        return Ok(());
+    };
+    match result {
+        Ok(()) => {
+            entry.status = TicketJobStatus::Printed;
+            entry.last_error = None;
+            entry.data.clear();
+        }
+        // Paper-outs don't use up retries; the job waits for a refill
+        Err(error) if error == "Out of paper" || error == "Printer cover open" => {
+            entry.next_attempt_at = now + config.retry_delay_secs;
+            entry.last_error = Some(error);
+        }
+        Err(error) => {
+            entry.attempts += 1;
+            entry.next_attempt_at = now + config.retry_delay_secs * entry.attempts as i64;
+            entry.last_error = Some(error);
+            if entry.attempts > config.max_retries {
+                entry.status = TicketJobStatus::Failed;
+            }
+        }
+    }
+    events::publish(app, "ticket-job", &*entry);
+    save_queue(app, &mut queue)
+}
+
+/// Spawn the worker that drains the ticket queue
+fn start_ticket_queue(app: AppHandle) {
+    std::thread::spawn(move || {
+        let Ok(state) = lazy::get::<TicketState>(&app) else {
+            return;
+        };
+        loop {
+            let _ = process(&app, state);
+            std::thread::sleep(config::poll_interval(&app, WORKER_INTERVAL));
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Render a ticket template with data and queue it for printing
+#[tauri::command]
+pub fn print_ticket(
+    app: AppHandle,
+    template: String,
+    data: HashMap<String, String>,
+) -> Result<TicketJob, KioskError> {
+    let state = lazy::get::<TicketState>(&app)?;
+    let now = Local::now().timestamp();
+    let job = TicketJob {
+        id: uuid::Uuid::new_v4().to_string(),
+        status: TicketJobStatus::Queued,
+        attempts: 0,
+        created_at: now,
+        next_attempt_at: now,
+        last_error: None,
+        data: render(&template, &data)?,
+    };
+
+    let mut queue = state.queue.lock().expect("ticket queue lock");
+    queue.push(job.clone());
+    save_queue(&app, &mut queue)?;
+    events::publish(&app, "ticket-job", &job);
+    Ok(job)
+}
+
+/// List queued and recently finished ticket jobs
+#[tauri::command]
+pub fn list_ticket_jobs(app: AppHandle) -> Result<Vec<TicketJob>, KioskError> {
+    let state = lazy::get::<TicketState>(&app)?;
+    Ok(state.queue.lock().expect("ticket queue lock").clone())
+}
+
+/// Put a failed job back in the queue
+#[tauri::command]
+pub fn retry_ticket_job(app: AppHandle, id: String) -> Result<(), KioskError> {
+    let state = lazy::get::<TicketState>(&app)?;
+    let mut queue = state.queue.lock().expect("ticket queue lock");
+    let job = queue
+        .iter_mut()
+        .find(|job| job.id == id && job.status == TicketJobStatus::Failed && !job.data.is_empty())
+        .ok_or_else(|| format!("No failed ticket job {}", id))?;
+    job.status = TicketJobStatus::Queued;
+    job.attempts = 0;
+    job.next_attempt_at = Local::now().timestamp();
+    Ok(save_queue(&app, &mut queue)?)
+}
+
+/// Remove a job from the queue
+#[tauri::command]
+pub fn cancel_ticket_job(app: AppHandle, id: String) -> Result<(), KioskError> {
+    let state = lazy::get::<TicketState>(&app)?;
+    let mut queue = state.queue.lock().expect("ticket queue lock");
+    let before = queue.len();
+    queue.retain(|job| job.id != id);
+    if queue.len() == before {
+        return Err(KioskError::not_found(format!("Ticket job not found: {}", id)));
+    }
+    Ok(save_queue(&app, &mut queue)?)
+}
+
+/// Query the ticket printer's paper and cover sensors
+#[tauri::command]
+pub fn get_ticket_printer_status(app: AppHandle) -> Result<TicketPrinterStatus, KioskError> {
+    let state = lazy::get::<TicketState>(&app)?;
+    let connection = state.config.lock().expect("ticket config lock").connection.clone();
+    let status = query_status(&connection);
+    update_status(&app, state, status.clone());
+    Ok(status)
+}
+
+/// Get the ticket printer settings
+#[tauri::command]
+pub fn get_ticket_printer_config(app: AppHandle) -> Result<TicketPrinterConfig, KioskError> {
+    let state = lazy::get::<TicketState>(&app)?;
+    Ok(state.config.lock().expect("ticket config lock").clone())
+}
+
+/// Update the ticket printer settings (admin)
+#[tauri::command]
+pub fn set_ticket_printer_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    config: TicketPrinterConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if let PrinterConnection::Device { path } = &config.connection {
+        check_device(path).map_err(KioskError::invalid)?;
+    }
+    let state = lazy::get::<TicketState>(&app)?;
+    let mut current = state.config.lock().expect("ticket config lock");
+    *current = config;
+    Ok(store::save(&app, CONFIG_FILE, &*current)?)
+}
@@ -0,0 +1,439 @@
+//! Timers and alarms
+//!
+//! Countdown timers and time-of-day alarms for the Clock accessory and for
+//! operational reminders such as "sanitize the station every 2 hours". A
+//! timer publishes `timer-expired` when it runs out; an alarm publishes
+//! `alarm-fired` at its time, once or on chosen weekdays, optionally
+//! repeating every few minutes until a time of day. Both are kept in
+//! `timers.json`, so they survive a restart: a timer that ran out while the
+//! kiosk was off fires as soon as it starts again, while alarms missed in
+//! that time are skipped to their next occurrence.
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveTime, TimeZone, Weekday};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::{events, store};
+
+const TIMERS_FILE: &str = "timers.json";
+
+const TICK: Duration = Duration::from_millis(500);
+
+const MAX_TIMER_SECS: u64 = 7 * 24 * 60 * 60;
+
+const MAX_TIMERS: usize = 50;
+
+const MAX_ALARMS: usize = 100;
+
+/// How far ahead to look for an alarm's next occurrence
+const LOOKAHEAD_DAYS: i64 = 8;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timer {
+    pub id: String,
+    pub label: String,
+    pub duration_secs: u64,
+    /// Unix time in milliseconds the timer runs out; `None` while paused
+    pub ends_at_ms: Option<i64>,
+    /// Time left, kept up to date while paused
+    pub remaining_ms: i64,
+}
+
+/// When an alarm repeats; an alarm without one rings once
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlarmRecurrence {
+    /// Weekdays ("mon" to "sun"); every day when empty
+    pub days: Vec<String>,
+    /// Ring again every this many minutes after the alarm time
+    pub every_minutes: Option<u32>,
+    /// Last time of day a repeating alarm rings, "HH:MM"; the end of the
+    /// day when unset
+    pub until: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alarm {
+    pub id: String,
+    pub label: String,
+    /// "HH:MM"
+    pub time: String,
+    pub recurrence: Option<AlarmRecurrence>,
+    pub enabled: bool,
+    /// Unix time it next rings
+    pub next_at: Option<i64>,
+    /// The next ring is a snooze rather than the schedule
+    #[serde(default)]
+    pub snoozed: bool,
+}
+
+/// Payload of `timer-expired`
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerExpired {
+    pub id: String,
+    pub label: String,
+    pub duration_secs: u64,
+    /// How long ago it ran out, when that was while the kiosk was off
+    pub late_secs: u64,
+}
+
+/// Payload of `alarm-fired`
+#[derive(Debug, Clone, Serialize)]
+pub struct AlarmFired {
+    pub id: String,
+    pub label: String,
+    /// Unix time it was due
+    pub at: i64,
+    pub snoozed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Stored {
+    timers: Vec<Timer>,
+    alarms: Vec<Alarm>,
+}
+
+pub struct TimersState(Mutex<Stored>);
+
+impl TimersState {
+    pub fn load(app: &AppHandle) -> Self {
+        let mut stored: Stored = store::load(app, TIMERS_FILE);
+        let now = Local::now();
+        for alarm in stored.alarms.iter_mut().filter(|alarm| alarm.enabled) {
+            alarm.snoozed = false;
+            alarm.next_at = next_ring(alarm, now).map(|at| at.timestamp());
+        }
+        TimersState(Mutex::new(stored))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn now_ms() -> i64 {
+    Local::now().timestamp_millis()
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| format!("Not a time (HH:MM): {}", time))
+}
+
+fn parse_days(days: &[String]) -> Result<Vec<Weekday>, String> {
+    days.iter()
+        .map(|day| day.parse::<Weekday>().map_err(|_| format!("Not a weekday: {}", day)))
+        .collect()
+}
+
+fn validate_alarm(time: &str, recurrence: Option<&AlarmRecurrence>) -> Result<(), String> {
+    parse_time(time)?;
+    if let Some(recurrence) = recurrence {
+        parse_days(&recurrence.days)?;
+        if recurrence.every_minutes == Some(0) {
+            return Err("An alarm cannot repeat every 0 minutes".to_string());
+        }
+        if let Some(until) = &recurrence.until {
+            if parse_time(until)? < parse_time(time)? {
+                return Err("A repeating alarm must stop after it starts".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The first time after `after` the alarm's schedule rings
+fn next_ring(alarm: &Alarm, after: DateTime<Local>) -> Option<DateTime<Local>> {
+    let time = parse_time(&alarm.time).ok()?;
+    let recurrence = alarm.recurrence.clone().unwrap_or_default();
+    let days = parse_days(&recurrence.days).ok()?;
+    let until = match &recurrence.until {
+        Some(until) => parse_time(until).ok()?,
+        None => NaiveTime::from_hms_opt(23, 59, 59)?,
+    };
+    let step = recurrence.every_minutes.map(|minutes| ChronoDuration::minutes(minutes as i64));
+
+    for offset in 0..=LOOKAHEAD_DAYS {
+        let date = after.date_naive() + ChronoDuration::days(offset);
+        if !days.is_empty() && !days.contains(&date.weekday()) {
+            continue;
+        }
+        let mut at = date.and_time(time);
+        loop {
+            let local = Local.from_local_datetime(&at).earliest();
+            if let Some(local) = local.filter(|local| *local > after) {
+                return Some(local);
+            }
+            let Some(step) = step else { break };
+            at += step;
+            if at.date() != date || at.time() > until {
+                break;
+            }
+        }
+    }
+    None
+}
+
+/// Fire due timers and alarms
+fn tick(app: &AppHandle) {
+    let state = app.state::<TimersState>();
+    let mut stored = state.0.lock().expect("timers lock");
+    let now = Local::now();
+    let now_ms = now.timestamp_millis();
+
+    let (expired, running): (Vec<Timer>, Vec<Timer>) = std::mem::take(&mut stored.timers)
+        .into_iter()
+        .partition(|timer| timer.ends_at_ms.is_some_and(|ends| ends <= now_ms));
+    stored.timers = running;
+
+    let mut fired = Vec::new();
+    for alarm in stored.alarms.iter_mut().filter(|alarm| alarm.enabled) {
+        let Some(at) = alarm.next_at.filter(|at| *at <= now.timestamp()) else {
+            continue;
+        };
+        fired.push(AlarmFired {
+            id: alarm.id.clone(),
+            label: alarm.label.clone(),
+            at,
+            snoozed: alarm.snoozed,
+        });
+        alarm.snoozed = false;
+        alarm.next_at = match alarm.recurrence {
+            Some(_) => next_ring(alarm, now).map(|at| at.timestamp()),
+            None => None,
+        };
+        alarm.enabled = alarm.next_at.is_some();
+    }
+
+    if expired.is_empty() && fired.is_empty() {
+        return;
+    }
+    let _ = store::save(app, TIMERS_FILE, &*stored);
+    drop(stored);
+
+    for timer in expired {
+        let late_ms = now_ms - timer.ends_at_ms.unwrap_or(now_ms);
+        events::publish(
+            app,
+            "timer-expired",
+            &TimerExpired {
+                id: timer.id,
+                label: timer.label,
+                duration_secs: timer.duration_secs,
+                // Ticks are frequent, so only a restart makes one seconds late
+                late_secs: (late_ms / 1000).max(0) as u64,
+            },
+        );
+    }
+    for alarm in fired {
+        events::publish(app, "alarm-fired", &alarm);
+    }
+}
+
+/// Fire timers and alarms in the background
+pub fn start_timers(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        tick(&app);
+        std::thread::sleep(TICK);
+    });
+}
+
+fn save(app: &AppHandle, stored: &Stored) -> Result<(), KioskError> {
+    Ok(store::save(app, TIMERS_FILE, stored)?)
+}
+
+/// Timers with their time left brought up to date
+fn current_timers(stored: &Stored) -> Vec<Timer> {
+    let now = now_ms();
+    let mut timers = stored.timers.clone();
+    for timer in timers.iter_mut() {
+        if let Some(ends) = timer.ends_at_ms {
+            timer.remaining_ms = (ends - now).max(0);
+        }
+    }
+    timers
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Start a countdown of `duration_secs`
+#[tauri::command]
+pub fn create_timer(
+    app: AppHandle,
+    state: State<'_, TimersState>,
+    duration_secs: u64,
+    label: Option<String>,
+) -> Result<Timer, KioskError> {
+    if duration_secs == 0 || duration_secs > MAX_TIMER_SECS {
+        return Err(KioskError::invalid(format!("A timer runs from 1 to {} seconds", MAX_TIMER_SECS)));
+    }
+    let mut stored = state.0.lock().expect("timers lock");
+    if stored.timers.len() >= MAX_TIMERS {
+        return Err(KioskError::invalid(format!("At most {} timers can run at once", MAX_TIMERS)));
+    }
+    let remaining_ms = duration_secs as i64 * 1000;
+    let timer = Timer {
+        id: uuid::Uuid::new_v4().to_string(),
+        label: label.unwrap_or_default(),
+        duration_secs,
+        ends_at_ms: Some(now_ms() + remaining_ms),
+        remaining_ms,
+    };
+    stored.timers.push(timer.clone());
+    save(&app, &stored)?;
+    Ok(timer)
+}
+
+/// Timers that have not run out yet
+#[tauri::command]
+pub fn list_timers(state: State<'_, TimersState>) -> Vec<Timer> {
+    current_timers(&state.0.lock().expect("timers lock"))
+}
+
+/// Pause or resume a timer
+#[tauri::command]
+pub fn set_timer_paused(
+    app: AppHandle,
+    state: State<'_, TimersState>,
+    id: String,
+    paused: bool,
+) -> Result<Timer, KioskError> {
+    let mut stored = state.0.lock().expect("timers lock");
+    let now = now_ms();
+    let timer = stored
+        .timers
+        .iter_mut()
+        .find(|timer| timer.id == id)
+        .ok_or_else(|| KioskError::not_found(format!("Timer not found: {}", id)))?;
+    match (paused, timer.ends_at_ms) {
+        (true, Some(ends)) => {
+            timer.remaining_ms = (ends - now).max(0);
+            timer.ends_at_ms = None;
+        }
+        (false, None) => timer.ends_at_ms = Some(now + timer.remaining_ms),
+        _ => {}
+    }
+    let timer = timer.clone();
+    save(&app, &stored)?;
+    Ok(timer)
+}
+
+/// Stop a timer without it firing
+#[tauri::command]
+pub fn cancel_timer(app: AppHandle, state: State<'_, TimersState>, id: String) -> Result<(), KioskError> {
+    let mut stored = state.0.lock().expect("timers lock");
+    let before = stored.timers.len();
+    stored.timers.retain(|timer| timer.id != id);
+    if stored.timers.len() == before {
+        return Err(KioskError::not_found(format!("Timer not found: {}", id)));
+    }
+    save(&app, &stored)
+}
+
+/// Set an alarm for `time` ("HH:MM"), ringing once unless `recurrence`
+/// says when it repeats
+#[tauri::command]
+pub fn create_alarm(
+    app: AppHandle,
+    state: State<'_, TimersState>,
+    time: String,
+    recurrence: Option<AlarmRecurrence>,
+    label: Option<String>,
+) -> Result<Alarm, KioskError> {
+    validate_alarm(&time, recurrence.as_ref()).map_err(KioskError::invalid)?;
+    let mut stored = state.0.lock().expect("timers lock");
+    if stored.alarms.len() >= MAX_ALARMS {
+        return Err(KioskError::invalid(format!("At most {} alarms can be set", MAX_ALARMS)));
+    }
+    let mut alarm = Alarm {
+        id: uuid::Uuid::new_v4().to_string(),
+        label: label.unwrap_or_default(),
+        time,
+        recurrence,
+        enabled: true,
+        next_at: None,
+        snoozed: false,
+    };
+    alarm.next_at = next_ring(&alarm, Local::now()).map(|at| at.timestamp());
+    stored.alarms.push(alarm.clone());
+    save(&app, &stored)?;
+    Ok(alarm)
+}
+
+#[tauri::command]
+pub fn list_alarms(state: State<'_, TimersState>) -> Vec<Alarm> {
+    state.0.lock().expect("timers lock").alarms.clone()
+}
+
+/// Switch an alarm on or off; a one-off alarm switched on rings at its
+/// time's next occurrence
+#[tauri::command]
+pub fn set_alarm_enabled(
+    app: AppHandle,
+    state: State<'_, TimersState>,
+    id: String,
+    enabled: bool,
+) -> Result<Alarm, KioskError> {
+    let mut stored = state.0.lock().expect("timers lock");
+    let alarm = stored
+        .alarms
+        .iter_mut()
+        .find(|alarm| alarm.id == id)
+        .ok_or_else(|| KioskError::not_found(format!("Alarm not found: {}", id)))?;
+    alarm.enabled = enabled;
+    alarm.snoozed = false;
+    alarm.next_at = if enabled {
+        next_ring(alarm, Local::now()).map(|at| at.timestamp())
+    } else {
+        None
+    };
+    let alarm = alarm.clone();
+    save(&app, &stored)?;
+    Ok(alarm)
+}
+
+/// Ring an alarm again in `minutes` (default 10)
+#[tauri::command]
+pub fn snooze_alarm(
+    app: AppHandle,
+    state: State<'_, TimersState>,
+    id: String,
+    minutes: Option<u32>,
+) -> Result<Alarm, KioskError> {
+    let minutes = minutes.unwrap_or(10);
+    if minutes == 0 || minutes > 24 * 60 {
+        return Err(KioskError::invalid("Snooze for 1 minute to 24 hours"));
+    }
+    let mut stored = state.0.lock().expect("timers lock");
+    let alarm = stored
+        .alarms
+        .iter_mut()
+        .find(|alarm| alarm.id == id)
+        .ok_or_else(|| KioskError::not_found(format!("Alarm not found: {}", id)))?;
+    alarm.enabled = true;
+    alarm.snoozed = true;
+    alarm.next_at = Some((Local::now() + ChronoDuration::minutes(minutes as i64)).timestamp());
+    let alarm = alarm.clone();
+    save(&app, &stored)?;
+    Ok(alarm)
+}
+
+#[tauri::command]
+pub fn delete_alarm(app: AppHandle, state: State<'_, TimersState>, id: String) -> Result<(), KioskError> {
+    let mut stored = state.0.lock().expect("timers lock");
+    let before = stored.alarms.len();
+    stored.alarms.retain(|alarm| alarm.id != id);
+    if stored.alarms.len() == before {
+        return Err(KioskError::not_found(format!("Alarm not found: {}", id)));
+    }
+    save(&app, &stored)
+}
@@ -0,0 +1,241 @@
+//! One-time unlock codes
+//!
+//! Time-based one-time passwords (RFC 6238, HMAC-SHA1) so support staff can
+//! read an operator a 6-digit code over the phone instead of sharing a
+//! static admin PIN. The kiosk holds the provisioning secret in the keyring;
+//! support holds the same secret in an authenticator app or runs
+//! `kiosk-cli totp SECRET`. A code signs in with `login` under the
+//! `totp` provider, with the role set here, and each code works only once,
+//! across restarts too.
+
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use chrono::Local;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{keyring, store};
+
+const TOTP_FILE: &str = "totp.json";
+
+/// Last accepted time step, kept apart from the config an admin saves
+const STEP_FILE: &str = "totp-step.json";
+
+const SECRET_KEY: &str = "auth/totp";
+
+/// Generated secrets are 160 bits, the HMAC-SHA1 block recommendation
+const SECRET_BYTES: usize = 20;
+
+/// Imported secrets shorter than this are refused
+const MIN_SECRET_BYTES: usize = 10;
+
+pub(crate) const DEFAULT_DIGITS: u32 = 6;
+
+pub(crate) const DEFAULT_PERIOD_SECS: u64 = 30;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TotpConfig {
+    pub enabled: bool,
+    /// Role a code signs in with
+    pub role: Role,
+    pub digits: u32,
+    pub period_secs: u64,
+    /// Periods either side of now also accepted, for clock drift
+    pub skew: u32,
+}
+
+impl Default for TotpConfig {
+    fn default() -> Self {
+        TotpConfig {
+            enabled: false,
+            role: Role::Admin,
+            digits: DEFAULT_DIGITS,
+            period_secs: DEFAULT_PERIOD_SECS,
+            skew: 1,
+        }
+    }
+}
+
+/// A new secret for support's authenticator
+#[derive(Debug, Clone, Serialize)]
+pub struct TotpProvisioning {
+    /// Base32, as authenticator apps take it
+    pub secret: String,
+    /// `otpauth://` URI for a QR code
+    pub uri: String,
+}
+
+pub struct TotpState {
+    config: Mutex<TotpConfig>,
+    /// Time step of the last code accepted, so it cannot be replayed
+    last_step: Mutex<Option<u64>>,
+}
+
+impl TotpState {
+    pub fn load(app: &AppHandle) -> Self {
+        TotpState {
+            config: Mutex::new(store::load(app, TOTP_FILE)),
+            last_step: Mutex::new(store::load(app, STEP_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(5) {
+        let mut buffer = [0u8; 5];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+        let bits = buffer.iter().fold(0u64, |acc, &byte| acc << 8 | byte as u64);
+        let chars = (chunk.len() * 8 + 4) / 5;
+        for index in 0..chars {
+            text.push(BASE32_ALPHABET[(bits >> (35 - index * 5) & 31) as usize] as char);
+        }
+    }
+    text
+}
+
+/// Decode base32, ignoring case, spaces, dashes and padding
+fn decode_base32(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut bits, mut count) = (0u32, 0u32);
+    for c in text.chars().filter(|c| !matches!(c, ' ' | '-' | '=')) {
+        let value = BASE32_ALPHABET.iter().position(|&a| a as char == c.to_ascii_uppercase())?;
+        bits = bits << 5 | value as u32;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// The code for one time step (RFC 4226 dynamic truncation)
+fn code_at(secret: &[u8], step: u64, digits: u32) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    format!("{:0width$}", value as u64 % 10u64.pow(digits), width = digits as usize)
+}
+
+/// The current code for a base32 secret and how many seconds it has left
+pub(crate) fn current_code(secret: &str, digits: u32, period_secs: u64) -> Result<(String, u64), String> {
+    let secret = decode_base32(secret).filter(|bytes| !bytes.is_empty()).ok_or("Not a base32 secret")?;
+    let now = Local::now().timestamp().max(0) as u64;
+    Ok((code_at(&secret, now / period_secs, digits), period_secs - now % period_secs))
+}
+
+/// Check a code against the provisioned secret, using it up if it matches
+pub(crate) fn verify(app: &AppHandle, code: &str) -> Result<Role, String> {
+    let state = app.state::<TotpState>();
+    let config = state.config.lock().expect("totp config lock").clone();
+    if !config.enabled {
+        return Err("One-time codes are not enabled".to_string());
+    }
+    let secret = keyring::get(app, SECRET_KEY)?
+        .and_then(|secret| decode_base32(&secret))
+        .ok_or("No one-time code secret is provisioned")?;
+
+    let code = code.trim().replace(' ', "");
+    let now = Local::now().timestamp().max(0) as u64 / config.period_secs;
+    let mut last_step = state.last_step.lock().expect("totp step lock");
+    let matched = (now.saturating_sub(config.skew as u64)..=now + config.skew as u64)
+        .filter(|step| last_step.map_or(true, |last| *step > last))
+        .find(|step| code_at(&secret, *step, config.digits) == code)
+        .ok_or("Invalid or used code")?;
+    store::save(app, STEP_FILE, &Some(matched))?;
+    *last_step = Some(matched);
+    Ok(config.role)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_totp_config(state: State<'_, TotpState>) -> TotpConfig {
+    state.config.lock().expect("totp config lock").clone()
+}
+
+/// Update one-time code settings (admin)
+#[tauri::command]
+pub fn set_totp_config(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, TotpState>,
+    config: TotpConfig,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    if !(6..=8).contains(&config.digits) {
+        return Err(KioskError::invalid("Codes have 6 to 8 digits"));
+    }
+    if !(15..=300).contains(&config.period_secs) {
+        return Err(KioskError::invalid("A code lasts 15 to 300 seconds"));
+    }
+    if config.skew > 10 {
+        return Err(KioskError::invalid("Accept at most 10 periods of clock drift"));
+    }
+    store::save(&app, TOTP_FILE, &config)?;
+    *state.config.lock().expect("totp config lock") = config;
+    Ok(())
+}
+
+/// Store support's provisioning secret (base32), or generate one when
+/// none is given (admin)
+#[tauri::command]
+pub fn provision_totp(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    state: State<'_, TotpState>,
+    secret: Option<String>,
+) -> Result<TotpProvisioning, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let bytes = match secret {
+        Some(secret) => decode_base32(&secret)
+            .filter(|bytes| bytes.len() >= MIN_SECRET_BYTES)
+            .ok_or_else(|| KioskError::invalid(format!("A secret is base32 of at least {} bytes", MIN_SECRET_BYTES)))?,
+        None => {
+            let mut bytes = vec![0u8; SECRET_BYTES];
+            OsRng.fill_bytes(&mut bytes);
+            bytes
+        }
+    };
+    let secret = encode_base32(&bytes);
+    keyring::put(&app, SECRET_KEY, &secret)?;
+    store::save(&app, STEP_FILE, &None::<u64>)?;
+    *state.last_step.lock().expect("totp step lock") = None;
+
+    let config = state.config.lock().expect("totp config lock").clone();
+    let host = sysinfo::System::host_name().unwrap_or_else(|| "kiosk".to_string());
+    let uri = format!(
+        "otpauth://totp/Kiosk:{}?secret={}&issuer=Kiosk&digits={}&period={}",
+        host, secret, config.digits, config.period_secs
+    );
+    Ok(TotpProvisioning { secret, uri })
+}
+
+/// Forget the provisioning secret, so no code is accepted (admin)
+#[tauri::command]
+pub fn clear_totp(app: AppHandle, auth: State<'_, AuthState>) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    Ok(keyring::remove(&app, SECRET_KEY)?)
+}
@@ -0,0 +1,783 @@
+//! Virtual filesystem roots
+//!
+//! Maps the locations the desktop shows (Desktop, My Documents, Downloads,
+//! removable drives, mounted network shares and the Recycle Bin) to real
+//! directories for the current platform. File commands take and return
+//! virtual paths such as `documents/Letters/cv.txt` or `removable-e/photos`,
+//! whose first segment is a root id, so the frontend never builds or sees a
+//! platform path. Deleting moves to the Recycle Bin, which uses the
+//! freedesktop.org trash layout (`files/` plus `info/*.trashinfo`). Copies,
+//! moves and deletes run as jobs, since a folder or a move to another drive
+//! can take a while; a cancelled copy removes what it had copied.
+//!
+//! Every path is checked on the way in: `..` segments are refused, symlinks
+//! are resolved and must stay inside their root, and the scope policy
+//! (`fs-scope.json`) says which roots each role may read and change. A
+//! walk-up user with no operator signed in gets the `public` scope.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State, Window};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::{ErrorKind, KioskError};
+use crate::jobs::{self, JobHandle};
+use crate::{events, quota, recents, store};
+
+const SCOPE_FILE: &str = "fs-scope.json";
+
+/// Largest file `read_text_file` will load
+const MAX_TEXT_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Filesystem types listed as network shares
+const NETWORK_FS: &[&str] = &["cifs", "smb3", "smbfs", "nfs", "nfs4", "fuse.sshfs", "davfs"];
+
+/// First letter handed out to removable drives, after C: and a D: CD-ROM
+const FIRST_REMOVABLE_LETTER: u8 = b'E';
+
+const TRASH_ID: &str = "trash";
+
+const COPY_CHUNK: usize = 1024 * 1024;
+
+/// Least time between `job-progress` reports of a copy, move or delete
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RootKind {
+    Desktop,
+    Documents,
+    Downloads,
+    Removable,
+    Network,
+    Trash,
+}
+
+/// A top-level location shown in My Computer and the folder tree
+#[derive(Debug, Clone, Serialize)]
+pub struct VirtualRoot {
+    /// First segment of virtual paths under this root
+    pub id: String,
+    /// Display name, e.g. "My Documents" or "Removable (E:)"
+    pub name: String,
+    pub kind: RootKind,
+    pub total_space: Option<u64>,
+    pub available_space: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileEntry {
+    pub name: String,
+    /// Virtual path
+    pub path: String,
+    pub is_dir: bool,
+    pub size: u64,
+    /// Unix time of the last modification
+    pub modified: Option<i64>,
+    pub hidden: bool,
+}
+
+pub(crate) struct Root {
+    pub info: VirtualRoot,
+    pub path: PathBuf,
+}
+
+/// Bytes done of a copy, move or delete job, reported as its progress
+struct Progress<'a> {
+    job: &'a JobHandle,
+    total: u64,
+    done: u64,
+    reported: Instant,
+}
+
+impl<'a> Progress<'a> {
+    fn new(job: &'a JobHandle, total: u64) -> Self {
+        Progress {
+            job,
+            total,
+            done: 0,
+            reported: Instant::now(),
+        }
+    }
+
+    fn check(&self) -> io::Result<()> {
+        self.job.check().map_err(|e| io::Error::new(io::ErrorKind::Interrupted, e))
+    }
+
+    fn add(&mut self, bytes: u64) {
+        self.done += bytes;
+        if self.total > 0 && self.reported.elapsed() >= PROGRESS_INTERVAL {
+            self.reported = Instant::now();
+            self.job.progress(self.done as f64 / self.total as f64, None);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Read,
+    Write,
+}
+
+/// Roots one kind of user may reach, by id; `prefix*` and `*` match several.
+/// Roots that can be changed can also be read.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScopeRule {
+    pub read: Vec<String>,
+    pub write: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsScopePolicy {
+    /// Walk-up users, with no operator signed in
+    pub public: ScopeRule,
+    pub operator: ScopeRule,
+    pub supervisor: ScopeRule,
+    pub admin: ScopeRule,
+}
+
+impl Default for FsScopePolicy {
+    fn default() -> Self {
+        let rule = |read: &[&str], write: &[&str]| ScopeRule {
+            read: read.iter().map(|id| id.to_string()).collect(),
+            write: write.iter().map(|id| id.to_string()).collect(),
+        };
+        let staff = rule(&["desktop"], &["documents", "downloads", "removable-*", "share-*", "trash"]);
+        FsScopePolicy {
+            public: rule(&["desktop"], &["documents", "downloads", "removable-*", "trash"]),
+            operator: staff.clone(),
+            supervisor: staff,
+            admin: rule(&[], &["*"]),
+        }
+    }
+}
+
+impl FsScopePolicy {
+    fn rule(&self, role: Option<Role>) -> &ScopeRule {
+        match role {
+            None => &self.public,
+            Some(Role::Operator) => &self.operator,
+            Some(Role::Supervisor) => &self.supervisor,
+            Some(Role::Admin) => &self.admin,
+        }
+    }
+}
+
+pub struct ScopeState(Mutex<FsScopePolicy>);
+
+impl ScopeState {
+    pub fn load(app: &AppHandle) -> Self {
+        ScopeState(Mutex::new(store::load(app, SCOPE_FILE)))
+    }
+}
+
+// ============================================================================
+// Roots
+// ============================================================================
+
+fn root(id: &str, name: &str, kind: RootKind, path: PathBuf) -> Root {
+    Root {
+        info: VirtualRoot {
+            id: id.to_string(),
+            name: name.to_string(),
+            kind,
+            total_space: None,
+            available_space: None,
+        },
+        path,
+    }
+}
+
+/// Directory holding the Recycle Bin's `files` and `info` folders
+fn trash_dir(app: &AppHandle) -> Option<PathBuf> {
+    let base = if cfg!(target_os = "linux") {
+        app.path().data_dir().ok()?
+    } else {
+        app.path().app_data_dir().ok()?
+    };
+    Some(base.join("Trash"))
+}
+
+/// Lowercase letters, digits and dashes, for root ids built from names
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+/// Mounted network shares: (mount point, display name)
+fn network_mounts() -> Vec<(PathBuf, String)> {
+    let Ok(mounts) = fs::read_to_string("/proc/mounts") else {
+        return Vec::new();
+    };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let source = fields.next()?;
+            // Spaces in mount points are escaped as \040
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            if !NETWORK_FS.contains(&fs_type) {
+                return None;
+            }
+            // "//server/share" or "server:/export/share"
+            let source = source.trim_start_matches('/');
+            let (server, share) = source
+                .split_once(['/', ':'])
+                .map(|(server, share)| (server, share.trim_matches('/')))?;
+            let share = share.rsplit('/').next().filter(|share| !share.is_empty()).unwrap_or(server);
+            Some((PathBuf::from(mount_point), format!("{} on {}", share, server)))
+        })
+        .collect()
+}
+
+/// Every root on this kiosk; the fixed folders are created if missing
+pub(crate) fn roots(app: &AppHandle) -> Vec<Root> {
+    let paths = app.path();
+    let mut roots = Vec::new();
+    let fixed = [
+        ("desktop", "Desktop", RootKind::Desktop, paths.desktop_dir()),
+        ("documents", "My Documents", RootKind::Documents, paths.document_dir()),
+        ("downloads", "Downloads", RootKind::Downloads, paths.download_dir()),
+    ];
+    for (id, name, kind, path) in fixed {
+        if let Ok(path) = path {
+            let _ = fs::create_dir_all(&path);
+            roots.push(root(id, name, kind, path));
+        }
+    }
+
+    let mut removable: Vec<crate::DriveInfo> = crate::list_drives(app.state())
+        .into_iter()
+        .filter(|drive| drive.is_removable)
+        .collect();
+    removable.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    for (index, drive) in removable.into_iter().enumerate() {
+        // Windows mounts drives at their letter; elsewhere hand letters out in order
+        let letter = drive
+            .mount_point
+            .chars()
+            .next()
+            .filter(|c| cfg!(windows) && c.is_ascii_alphabetic())
+            .map(|c| c.to_ascii_uppercase())
+            .unwrap_or((FIRST_REMOVABLE_LETTER + index.min(21) as u8) as char);
+        let label = if drive.name.is_empty() || cfg!(windows) { "Removable".to_string() } else { drive.name.clone() };
+        let mut entry = root(
+            &format!("removable-{}", letter.to_ascii_lowercase()),
+            &format!("{} ({}:)", label, letter),
+            RootKind::Removable,
+            PathBuf::from(&drive.mount_point),
+        );
+        entry.info.total_space = Some(drive.total_space);
+        entry.info.available_space = Some(drive.available_space);
+        roots.push(entry);
+    }
+
+    for (path, name) in network_mounts() {
+        roots.push(root(&format!("share-{}", slug(&name)), &name, RootKind::Network, path));
+    }
+
+    if let Some(trash) = trash_dir(app) {
+        let files = trash.join("files");
+        let _ = fs::create_dir_all(&files);
+        let _ = fs::create_dir_all(trash.join("info"));
+        roots.push(root(TRASH_ID, "Recycle Bin", RootKind::Trash, files));
+    }
+    roots
+}
+
+// ============================================================================
+// Path Mapping
+// ============================================================================
+
+/// Split a virtual path into its root id and plain segments, rejecting `..`
+fn split(virtual_path: &str) -> Result<(&str, Vec<&str>), String> {
+    let mut parts = virtual_path.split(['/', '\\']).filter(|part| !part.is_empty() && *part != ".");
+    let id = parts.next().ok_or("No location given")?;
+    let segments: Vec<&str> = parts.collect();
+    let plain = segments.iter().all(|segment| {
+        let mut components = Path::new(segment).components();
+        matches!(components.next(), Some(Component::Normal(_))) && components.next().is_none()
+    });
+    if !plain {
+        return Err(format!("Invalid path: {}", virtual_path));
+    }
+    Ok((id, segments))
+}
+
+fn matches_any(patterns: &[String], id: &str) -> bool {
+    patterns.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(prefix) => id.starts_with(prefix),
+        None => pattern == id,
+    })
+}
+
+fn permitted(app: &AppHandle, root: &VirtualRoot, access: Access) -> bool {
+    let role = auth::current_role(&app.state::<AuthState>());
+    let state = app.state::<ScopeState>();
+    let policy = state.0.lock().expect("fs scope lock");
+    let rule = policy.rule(role);
+    matches_any(&rule.write, &root.id) || (access == Access::Read && matches_any(&rule.read, &root.id))
+}
+
+/// Resolve symlinks in all but the last segment, and fail unless the result,
+/// and the target of a final symlink, stay inside `base`
+fn contain(base: &Path, path: &Path) -> Result<PathBuf, KioskError> {
+    let escape = || KioskError::new(ErrorKind::Denied, "Path leads outside its location");
+    if path == base {
+        return Ok(path.to_path_buf());
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(escape());
+    };
+    let real = parent.canonicalize()?.join(name);
+    if !real.starts_with(base) {
+        return Err(escape());
+    }
+    if fs::symlink_metadata(&real).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        // A dangling link cannot be checked, so it is refused too
+        let target = real.canonicalize().map_err(|_| escape())?;
+        if !target.starts_with(base) {
+            return Err(escape());
+        }
+    }
+    Ok(real)
+}
+
+/// The root a virtual path is under, with its path canonicalized, and the
+/// real path it names, checked against the caller's scope
+pub(crate) fn resolve_in(app: &AppHandle, virtual_path: &str, access: Access) -> Result<(Root, PathBuf), KioskError> {
+    let (id, segments) = split(virtual_path).map_err(KioskError::invalid)?;
+    let mut root = roots(app)
+        .into_iter()
+        .find(|root| root.info.id == id)
+        .ok_or_else(|| KioskError::not_found(format!("Unknown location: {}", id)))?;
+    if !permitted(app, &root.info, access) {
+        let message = match access {
+            Access::Read => format!("{} is not available", root.info.name),
+            Access::Write => format!("{} is read-only", root.info.name),
+        };
+        return Err(KioskError::new(ErrorKind::Denied, message));
+    }
+
+    root.path = root.path.canonicalize()?;
+    let path = segments.iter().fold(root.path.clone(), |path, segment| path.join(segment));
+    let path = contain(&root.path, &path)?;
+    Ok((root, path))
+}
+
+/// The real path a virtual path names, checked against the caller's scope
+pub(crate) fn resolve(app: &AppHandle, virtual_path: &str, access: Access) -> Result<PathBuf, KioskError> {
+    resolve_in(app, virtual_path, access).map(|(_, path)| path)
+}
+
+/// Resolve a file about to be written with `size` bytes, checking the
+/// scope and that replacing it keeps the root within its quota
+pub(crate) fn resolve_write(app: &AppHandle, virtual_path: &str, size: u64) -> Result<PathBuf, KioskError> {
+    let (root, path) = resolve_in(app, virtual_path, Access::Write)?;
+    require_item(&root, &path)?;
+    let existing = fs::metadata(&path).map_or(0, |metadata| metadata.len());
+    quota::check(app, &root, size.saturating_sub(existing))?;
+    Ok(path)
+}
+
+fn join_virtual(id: &str, relative: &Path) -> String {
+    relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().to_string())
+        .fold(id.to_string(), |path, part| format!("{}/{}", path, part))
+}
+
+/// The root a real path lies under, with the path relative to it
+fn locate(app: &AppHandle, path: &Path) -> Option<(PathBuf, Root)> {
+    roots(app)
+        .into_iter()
+        .filter_map(|mut root| {
+            // Resolved paths are canonical, the root's may not be
+            let relative = match path.strip_prefix(&root.path) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => {
+                    root.path = root.path.canonicalize().ok()?;
+                    path.strip_prefix(&root.path).ok()?.to_path_buf()
+                }
+            };
+            Some((relative, root))
+        })
+        // The deepest root wins, e.g. a drive mounted inside the home folder
+        .max_by_key(|(_, root)| root.path.components().count())
+}
+
+/// The virtual path of a real path, when it lies under a root
+pub(crate) fn to_virtual(app: &AppHandle, path: &Path) -> Option<String> {
+    locate(app, path).map(|(relative, root)| join_virtual(&root.info.id, &relative))
+}
+
+/// The root a real path lies under
+pub(crate) fn root_of(app: &AppHandle, path: &Path) -> Option<Root> {
+    locate(app, path).map(|(_, root)| root)
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn entry(name: String, path: String, metadata: &fs::Metadata) -> FileEntry {
+    FileEntry {
+        hidden: name.starts_with('.'),
+        name,
+        path,
+        is_dir: metadata.is_dir(),
+        size: if metadata.is_dir() { 0 } else { metadata.len() },
+        modified: metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|age| age.as_secs() as i64),
+    }
+}
+
+fn copy_file(from: &Path, to: &Path, progress: &mut Progress) -> io::Result<()> {
+    let mut source = File::open(from)?;
+    let mut target = File::create(to)?;
+    let mut buffer = vec![0u8; COPY_CHUNK];
+    loop {
+        progress.check()?;
+        let read = source.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        target.write_all(&buffer[..read])?;
+        progress.add(read as u64);
+    }
+    fs::set_permissions(to, source.metadata()?.permissions())
+}
+
+fn copy_recursive(from: &Path, to: &Path, progress: &mut Progress) -> io::Result<()> {
+    progress.check()?;
+    if fs::symlink_metadata(from)?.is_dir() {
+        fs::create_dir(to)?;
+        for item in fs::read_dir(from)? {
+            let item = item?;
+            // Links inside a folder could point outside the caller's scope
+            if item.file_type()?.is_symlink() {
+                continue;
+            }
+            copy_recursive(&item.path(), &to.join(item.file_name()), progress)?;
+        }
+        Ok(())
+    } else {
+        copy_file(from, to, progress)
+    }
+}
+
+/// Copy a file or folder, removing the partial copy when it fails or is cancelled
+fn copy_tree(from: &Path, to: &Path, progress: &mut Progress) -> io::Result<()> {
+    let result = copy_recursive(from, to, progress);
+    if result.is_err() {
+        let _ = remove_recursive(to);
+    }
+    result
+}
+
+/// Empty the fixed folders and Recycle Bin walk-up users may change, so the
+/// next user finds nothing the last one left
+pub(crate) fn clear_public_folders(app: &AppHandle) -> Result<(), String> {
+    let public = app.state::<ScopeState>().0.lock().expect("fs scope lock").public.write.clone();
+    let mut failed = Vec::new();
+    for root in roots(app) {
+        let fixed = matches!(
+            root.info.kind,
+            RootKind::Desktop | RootKind::Documents | RootKind::Downloads | RootKind::Trash
+        );
+        if !fixed || !matches_any(&public, &root.info.id) {
+            continue;
+        }
+        let mut dirs = vec![root.path.clone()];
+        if root.info.kind == RootKind::Trash {
+            dirs.extend(root.path.parent().map(|trash| trash.join("info")));
+        }
+        for entry in dirs.iter().filter_map(|dir| fs::read_dir(dir).ok()).flatten().flatten() {
+            if remove_recursive(&entry.path()).is_err() {
+                failed.push(format!("{}/{}", root.info.id, entry.file_name().to_string_lossy()));
+            }
+        }
+    }
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Could not remove {}", failed.join(", ")))
+    }
+}
+
+fn remove_recursive(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Delete a file or folder, checking for cancellation between items
+fn remove_tree(path: &Path, progress: &mut Progress) -> io::Result<()> {
+    progress.check()?;
+    let metadata = fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        fs::remove_file(path)?;
+        progress.add(metadata.len());
+        return Ok(());
+    }
+    for item in fs::read_dir(path)? {
+        remove_tree(&item?.path(), progress)?;
+    }
+    fs::remove_dir(path)
+}
+
+/// Rename, falling back to copy and delete across filesystems. Only the
+/// copy can be cancelled, so a move never leaves half a folder behind.
+fn move_path(from: &Path, to: &Path, job: &JobHandle) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let mut progress = Progress::new(job, quota::measure(from));
+    copy_tree(from, to, &mut progress)?;
+    remove_recursive(from)
+}
+
+fn check_free(path: &Path) -> Result<(), KioskError> {
+    if path.exists() {
+        return Err(KioskError::invalid(format!(
+            "{} already exists",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        )));
+    }
+    Ok(())
+}
+
+/// Root-relative paths name something inside a root, never the root itself
+fn require_item(root: &Root, path: &Path) -> Result<(), KioskError> {
+    if path == root.path {
+        return Err(KioskError::invalid(format!("{} cannot be changed", root.info.name)));
+    }
+    Ok(())
+}
+
+/// Percent-encode a path for a `.trashinfo` file
+fn trash_encode(path: &Path) -> String {
+    path.to_string_lossy()
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Move a file or folder into the Recycle Bin under a free name
+fn move_to_trash(app: &AppHandle, path: &Path, job: &JobHandle) -> Result<(), KioskError> {
+    let trash = trash_dir(app).ok_or("The Recycle Bin is not available")?;
+    let name = path.file_name().ok_or("Invalid path")?.to_string_lossy().to_string();
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem.to_string(), format!(".{}", extension)),
+        _ => (name.clone(), String::new()),
+    };
+    let taken = |name: &str| {
+        trash.join("files").join(name).exists() || trash.join("info").join(format!("{}.trashinfo", name)).exists()
+    };
+    let mut target = name;
+    let mut copy = 1;
+    while taken(&target) {
+        copy += 1;
+        target = format!("{} ({}){}", stem, copy, extension);
+    }
+
+    let info = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        trash_encode(path),
+        Local::now().format("%Y-%m-%dT%H:%M:%S")
+    );
+    let info_path = trash.join("info").join(format!("{}.trashinfo", target));
+    fs::write(&info_path, info)?;
+    if let Err(e) = move_path(path, &trash.join("files").join(&target), job) {
+        let _ = fs::remove_file(&info_path);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+pub(crate) fn changed(app: &AppHandle, path: &str, action: &str) {
+    events::publish(app, "fs-changed", serde_json::json!({ "path": path, "action": action }));
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List the top-level locations the caller may open
+#[tauri::command]
+pub fn list_virtual_roots(app: AppHandle) -> Vec<VirtualRoot> {
+    roots(&app)
+        .into_iter()
+        .map(|root| root.info)
+        .filter(|root| permitted(&app, root, Access::Read))
+        .collect()
+}
+
+/// List a folder, folders first
+#[tauri::command]
+pub fn read_directory(app: AppHandle, path: String) -> Result<Vec<FileEntry>, KioskError> {
+    let (root, dir) = resolve_in(&app, &path, Access::Read)?;
+    let base = dir.strip_prefix(&root.path).unwrap_or(Path::new("")).to_path_buf();
+    let mut entries: Vec<FileEntry> = fs::read_dir(&dir)?
+        .flatten()
+        .filter_map(|item| {
+            let name = item.file_name().to_string_lossy().to_string();
+            // Follows symlinks so links to folders list as folders
+            let metadata = fs::metadata(item.path()).ok()?;
+            let path = join_virtual(&root.info.id, &base.join(&name));
+            Some(entry(name, path, &metadata))
+        })
+        .collect();
+    entries.sort_by_key(|entry| (!entry.is_dir, entry.name.to_lowercase()));
+    Ok(entries)
+}
+
+/// Read a text file
+#[tauri::command]
+pub fn read_text_file(app: AppHandle, path: String) -> Result<String, KioskError> {
+    let file = resolve(&app, &path, Access::Read)?;
+    if fs::metadata(&file)?.len() > MAX_TEXT_BYTES {
+        return Err(KioskError::invalid("File is too large to open"));
+    }
+    let data = fs::read(&file)?;
+    let _ = recents::record(&app, "notepad", &path);
+    Ok(String::from_utf8_lossy(&data).to_string())
+}
+
+/// Create or replace a text file
+#[tauri::command]
+pub fn write_text_file(app: AppHandle, path: String, contents: String) -> Result<(), KioskError> {
+    let file = resolve_write(&app, &path, contents.len() as u64)?;
+    fs::write(&file, contents)?;
+    changed(&app, &path, "write");
+    let _ = recents::record(&app, "notepad", &path);
+    Ok(())
+}
+
+/// Create a folder
+#[tauri::command]
+pub fn create_directory(app: AppHandle, path: String) -> Result<(), KioskError> {
+    let (root, dir) = resolve_in(&app, &path, Access::Write)?;
+    require_item(&root, &dir)?;
+    check_free(&dir)?;
+    fs::create_dir(&dir)?;
+    changed(&app, &path, "create");
+    Ok(())
+}
+
+/// Copy a file or folder to a new path, as a job
+#[tauri::command]
+pub fn copy_path(app: AppHandle, window: Window, source: String, destination: String) -> Result<String, KioskError> {
+    let from = resolve(&app, &source, Access::Read)?;
+    let (root, to) = resolve_in(&app, &destination, Access::Write)?;
+    require_item(&root, &to)?;
+    check_free(&to)?;
+    if to.starts_with(&from) {
+        return Err(KioskError::invalid("Cannot copy a folder into itself"));
+    }
+    let worker = app.clone();
+    Ok(jobs::spawn(&app, Some(window.label()), "copy", move |job| {
+        let size = quota::measure(&from);
+        quota::check(&worker, &root, size).map_err(|e| e.to_string())?;
+        copy_tree(&from, &to, &mut Progress::new(job, size)).map_err(|e| e.to_string())?;
+        changed(&worker, &destination, "create");
+        Ok(())
+    }))
+}
+
+/// Move or rename a file or folder, as a job
+#[tauri::command]
+pub fn rename_path(app: AppHandle, window: Window, source: String, destination: String) -> Result<String, KioskError> {
+    let (source_root, from) = resolve_in(&app, &source, Access::Write)?;
+    require_item(&source_root, &from)?;
+    let (root, to) = resolve_in(&app, &destination, Access::Write)?;
+    require_item(&root, &to)?;
+    check_free(&to)?;
+    if to.starts_with(&from) {
+        return Err(KioskError::invalid("Cannot move a folder into itself"));
+    }
+    let worker = app.clone();
+    Ok(jobs::spawn(&app, Some(window.label()), "move", move |job| {
+        if root.info.id != source_root.info.id {
+            quota::check(&worker, &root, quota::measure(&from)).map_err(|e| e.to_string())?;
+        }
+        move_path(&from, &to, job).map_err(|e| e.to_string())?;
+        changed(&worker, &source, "delete");
+        changed(&worker, &destination, "create");
+        Ok(())
+    }))
+}
+
+/// Move a file or folder to the Recycle Bin, or delete it for good when it
+/// is already there or `permanent` is set, as a job. A cancelled permanent
+/// delete keeps what it had not reached yet.
+#[tauri::command]
+pub fn delete_path(
+    app: AppHandle,
+    window: Window,
+    path: String,
+    permanent: Option<bool>,
+) -> Result<String, KioskError> {
+    let (root, target) = resolve_in(&app, &path, Access::Write)?;
+    require_item(&root, &target)?;
+    fs::symlink_metadata(&target)?;
+
+    let worker = app.clone();
+    Ok(jobs::spawn(&app, Some(window.label()), "delete", move |job| {
+        let result = if root.info.kind == RootKind::Trash || permanent.unwrap_or(false) {
+            remove_tree(&target, &mut Progress::new(job, quota::measure(&target))).map_err(|e| e.to_string())
+        } else {
+            move_to_trash(&worker, &target, job).map_err(|e| e.to_string())
+        };
+        // Top-level items in the bin have an info file alongside
+        if result.is_ok() && root.info.kind == RootKind::Trash && target.parent() == Some(root.path.as_path()) {
+            if let (Some(trash), Some(name)) = (trash_dir(&worker), target.file_name()) {
+                let _ = fs::remove_file(trash.join("info").join(format!("{}.trashinfo", name.to_string_lossy())));
+            }
+        }
+        // Even a stopped delete may have removed some of it
+        changed(&worker, &path, "delete");
+        result
+    }))
+}
+
+/// Get the roots each role may read and change
+#[tauri::command]
+pub fn get_fs_scope(state: State<'_, ScopeState>) -> FsScopePolicy {
+    state.0.lock().expect("fs scope lock").clone()
+}
+
+/// Update the roots each role may read and change (admin)
+#[tauri::command]
+pub fn set_fs_scope(
+    app: AppHandle,
+    state: State<'_, ScopeState>,
+    auth: State<'_, AuthState>,
+    policy: FsScopePolicy,
+) -> Result<(), KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    store::save(&app, SCOPE_FILE, &policy)?;
+    *state.0.lock().expect("fs scope lock") = policy;
+    Ok(())
+}
@@ -0,0 +1,478 @@
+//! Wayfinding
+//!
+//! Building map data (floors, walkable graph and points of interest) loaded
+//! from a JSON or GeoJSON bundle, with shortest-path routing between points
+//! of interest. Accessible routing avoids stairs, escalators and any edge
+//! marked as not step-free.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::error::KioskError;
+use crate::vfs::{self, Access};
+use crate::store;
+
+const MAP_FILE: &str = "wayfinding.json";
+
+/// Fixed cost in metres for changing floors, on top of the edge length
+const STAIRS_PENALTY: f64 = 15.0;
+const ESCALATOR_PENALTY: f64 = 10.0;
+const ELEVATOR_PENALTY: f64 = 25.0;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Floor {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub level: i32,
+}
+
+/// A point on the walkable graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapNode {
+    pub id: String,
+    pub floor: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EdgeKind {
+    #[default]
+    Walk,
+    Ramp,
+    Stairs,
+    Escalator,
+    Elevator,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapEdge {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub kind: EdgeKind,
+    /// Explicit step-free flag; defaults from the edge kind
+    #[serde(default)]
+    pub accessible: Option<bool>,
+    #[serde(default)]
+    pub one_way: bool,
+    /// Cost override in metres
+    #[serde(default)]
+    pub weight: Option<f64>,
+}
+
+/// A named destination attached to a graph node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Poi {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub category: Option<String>,
+    pub node: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MapData {
+    pub floors: Vec<Floor>,
+    pub nodes: Vec<MapNode>,
+    pub edges: Vec<MapEdge>,
+    pub pois: Vec<Poi>,
+    /// Coordinates are longitude/latitude rather than metres
+    #[serde(default)]
+    pub geographic: bool,
+}
+
+/// Map summary for the frontend (graph edges omitted)
+#[derive(Debug, Clone, Serialize)]
+pub struct MapInfo {
+    pub floors: Vec<Floor>,
+    pub nodes: Vec<MapNode>,
+    pub pois: Vec<Poi>,
+    pub geographic: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RouteOptions {
+    /// Step-free route only
+    #[serde(default)]
+    pub accessible: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteStep {
+    pub instruction: String,
+    pub floor: String,
+    pub distance: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Route {
+    /// Walking distance in metres
+    pub distance: f64,
+    pub nodes: Vec<MapNode>,
+    pub steps: Vec<RouteStep>,
+}
+
+pub struct MapState(Mutex<MapData>);
+
+impl MapState {
+    pub fn load(app: &AppHandle) -> Self {
+        MapState(Mutex::new(store::load(app, MAP_FILE)))
+    }
+}
+
+// ============================================================================
+// Bundle Parsing
+// ============================================================================
+
+fn string_prop(properties: &Value, key: &str) -> Option<String> {
+    match properties.get(key)? {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+fn point(coordinates: &Value) -> Option<(f64, f64)> {
+    Some((coordinates.get(0)?.as_f64()?, coordinates.get(1)?.as_f64()?))
+}
+
+/// Convert a GeoJSON FeatureCollection into map data.
+///
+/// Point features are graph nodes (`id`, `floor`); a `name` property also
+/// makes them points of interest. LineString features are edges between the
+/// nodes named in their `from`/`to` properties. Floors come from a top-level
+/// `floors` member when present, otherwise from the node floor ids.
+fn parse_geojson(value: &Value) -> Result<MapData, String> {
+    let features = value
+        .get("features")
+        .and_then(Value::as_array)
+        .ok_or("GeoJSON bundle has no features")?;
+    let mut map = MapData {
+        geographic: true,
+        ..MapData::default()
+    };
+
+    for feature in features {
+        let properties = feature.get("properties").unwrap_or(&Value::Null);
+        let geometry = feature.get("geometry").unwrap_or(&Value::Null);
+        match geometry.get("type").and_then(Value::as_str) {
+            Some("Point") => {
+                let Some((x, y)) = geometry.get("coordinates").and_then(point) else { continue };
+                let Some(id) = string_prop(properties, "id").or_else(|| string_prop(feature, "id")) else {
+                    continue;
+                };
+                let floor = string_prop(properties, "floor").unwrap_or_default();
+                if let Some(name) = string_prop(properties, "name") {
+                    map.pois.push(Poi {
+                        id: id.clone(),
+                        name,
+                        category: string_prop(properties, "category"),
+                        node: id.clone(),
+                    });
+                }
+                map.nodes.push(MapNode { id, floor, x, y });
+            }
+            Some("LineString") => {
+                let (Some(from), Some(to)) = (string_prop(properties, "from"), string_prop(properties, "to")) else {
+                    continue;
+                };
+                let edge = serde_json::json!({
+                    "from": from,
+                    "to": to,
+                    "kind": properties.get("kind").or_else(|| properties.get("type")),
+                    "accessible": properties.get("accessible"),
+                    "one_way": properties.get("one_way").and_then(Value::as_bool).unwrap_or(false),
+                    "weight": properties.get("weight"),
+                });
+                map.edges.push(serde_json::from_value(edge).map_err(|e| e.to_string())?);
+            }
+            _ => {}
+        }
+    }
+
+    map.floors = match value.get("floors") {
+        Some(floors) => serde_json::from_value(floors.clone()).map_err(|e| e.to_string())?,
+        None => {
+            let mut ids: Vec<String> = map.nodes.iter().map(|node| node.floor.clone()).collect();
+            ids.sort();
+            ids.dedup();
+            ids.into_iter()
+                .enumerate()
+                .map(|(level, id)| Floor {
+                    name: id.clone(),
+                    id,
+                    level: level as i32,
+                })
+                .collect()
+        }
+    };
+    Ok(map)
+}
+
+fn validate(map: &MapData) -> Result<(), String> {
+    let nodes: HashMap<&str, &MapNode> = map.nodes.iter().map(|node| (node.id.as_str(), node)).collect();
+    if nodes.len() != map.nodes.len() {
+        return Err("Duplicate node ids in map bundle".to_string());
+    }
+    for edge in &map.edges {
+        for end in [&edge.from, &edge.to] {
+            if !nodes.contains_key(end.as_str()) {
+                return Err(format!("Edge references unknown node '{}'", end));
+            }
+        }
+    }
+    for poi in &map.pois {
+        if !nodes.contains_key(poi.node.as_str()) {
+            return Err(format!("Point of interest '{}' references unknown node '{}'", poi.id, poi.node));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Routing
+// ============================================================================
+
+fn distance(map: &MapData, a: &MapNode, b: &MapNode) -> f64 {
+    if map.geographic {
+        // Equirectangular approximation; fine at building scale
+        let latitude = ((a.y + b.y) / 2.0).to_radians();
+        let dx = (b.x - a.x) * latitude.cos() * 111_320.0;
+        let dy = (b.y - a.y) * 110_540.0;
+        (dx * dx + dy * dy).sqrt()
+    } else {
+        ((b.x - a.x).powi(2) + (b.y - a.y).powi(2)).sqrt()
+    }
+}
+
+fn is_accessible(edge: &MapEdge) -> bool {
+    edge.accessible
+        .unwrap_or(!matches!(edge.kind, EdgeKind::Stairs | EdgeKind::Escalator))
+}
+
+fn edge_cost(map: &MapData, edge: &MapEdge, a: &MapNode, b: &MapNode) -> f64 {
+    if let Some(weight) = edge.weight {
+        return weight;
+    }
+    let penalty = match edge.kind {
+        EdgeKind::Stairs => STAIRS_PENALTY,
+        EdgeKind::Escalator => ESCALATOR_PENALTY,
+        EdgeKind::Elevator => ELEVATOR_PENALTY,
+        EdgeKind::Walk | EdgeKind::Ramp => 0.0,
+    };
+    distance(map, a, b) + penalty
+}
+
+/// Resolve a point of interest or node id to a node index
+fn resolve(map: &MapData, index: &HashMap<&str, usize>, id: &str) -> Result<usize, String> {
+    let node = map
+        .pois
+        .iter()
+        .find(|poi| poi.id == id)
+        .map(|poi| poi.node.as_str())
+        .unwrap_or(id);
+    index.get(node).copied().ok_or_else(|| format!("Unknown location '{}'", id))
+}
+
+fn map_info(map: &MapData) -> MapInfo {
+    MapInfo {
+        floors: map.floors.clone(),
+        nodes: map.nodes.clone(),
+        pois: map.pois.clone(),
+        geographic: map.geographic,
+    }
+}
+
+fn floor_name(map: &MapData, id: &str) -> String {
+    map.floors
+        .iter()
+        .find(|floor| floor.id == id)
+        .map(|floor| floor.name.clone())
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Dijkstra over the node graph; returns the node path and the edges taken
+fn shortest_path(
+    map: &MapData,
+    from: usize,
+    to: usize,
+    options: &RouteOptions,
+) -> Option<Vec<(usize, Option<EdgeKind>)>> {
+    let index: HashMap<&str, usize> = map.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let mut adjacency: Vec<Vec<(usize, f64, EdgeKind)>> = vec![Vec::new(); map.nodes.len()];
+    for edge in map.edges.iter().filter(|edge| !options.accessible || is_accessible(edge)) {
+        let (a, b) = (index[edge.from.as_str()], index[edge.to.as_str()]);
+        let cost = edge_cost(map, edge, &map.nodes[a], &map.nodes[b]);
+        adjacency[a].push((b, cost, edge.kind));
+        if !edge.one_way {
+            adjacency[b].push((a, cost, edge.kind));
+        }
+    }
+
+    let mut best = vec![f64::INFINITY; map.nodes.len()];
+    let mut previous: Vec<Option<(usize, EdgeKind)>> = vec![None; map.nodes.len()];
+    let mut heap = BinaryHeap::new();
+    best[from] = 0.0;
+    // Costs are kept in millimetres so the heap can order them as integers
+    heap.push(Reverse((0u64, from)));
+
+    while let Some(Reverse((cost, node))) = heap.pop() {
+        let cost = cost as f64 / 1000.0;
+        if node == to {
+            break;
+        }
+        if cost > best[node] {
+            continue;
+        }
+        for &(next, weight, kind) in &adjacency[node] {
+            let candidate = cost + weight;
+            if candidate < best[next] {
+                best[next] = candidate;
+                previous[next] = Some((node, kind));
+                heap.push(Reverse(((candidate * 1000.0) as u64, next)));
+            }
+        }
+    }
+
+    if !best[to].is_finite() {
+        return None;
+    }
+    // Each entry carries the kind of edge used to leave it
+    let mut path = vec![(to, None)];
+    let mut current = to;
+    while let Some((node, kind)) = previous[current] {
+        path.push((node, Some(kind)));
+        current = node;
+    }
+    path.reverse();
+    Some(path)
+}
+
+fn describe(map: &MapData, path: &[(usize, Option<EdgeKind>)], destination: &str) -> Route {
+    let mut steps: Vec<RouteStep> = Vec::new();
+    let mut walked = 0.0;
+    let mut total = 0.0;
+
+    for pair in path.windows(2) {
+        let (a, kind) = pair[0];
+        let b = pair[1].0;
+        let (from, to) = (&map.nodes[a], &map.nodes[b]);
+        let length = distance(map, from, to);
+        total += length;
+
+        if from.floor == to.floor {
+            walked += length;
+            continue;
+        }
+        if walked > 0.0 {
+            steps.push(RouteStep {
+                instruction: format!("Walk {:.0} m", walked),
+                floor: from.floor.clone(),
+                distance: walked,
+            });
+            walked = 0.0;
+        }
+        let via = match kind.unwrap_or_default() {
+            EdgeKind::Elevator => "the elevator",
+            EdgeKind::Escalator => "the escalator",
+            EdgeKind::Stairs => "the stairs",
+            EdgeKind::Ramp => "the ramp",
+            EdgeKind::Walk => "the connector",
+        };
+        steps.push(RouteStep {
+            instruction: format!("Take {} to {}", via, floor_name(map, &to.floor)),
+            floor: from.floor.clone(),
+            distance: length,
+        });
+    }
+
+    let last = path.last().map(|(node, _)| &map.nodes[*node]);
+    let floor = last.map(|node| node.floor.clone()).unwrap_or_default();
+    if walked > 0.0 {
+        steps.push(RouteStep {
+            instruction: format!("Walk {:.0} m", walked),
+            floor: floor.clone(),
+            distance: walked,
+        });
+    }
+    steps.push(RouteStep {
+        instruction: format!("Arrive at {}", destination),
+        floor,
+        distance: 0.0,
+    });
+
+    Route {
+        distance: total,
+        nodes: path.iter().map(|(node, _)| map.nodes[*node].clone()).collect(),
+        steps,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Load a map bundle (native JSON or GeoJSON) and make it the active map
+#[tauri::command]
+pub fn load_map_bundle(app: AppHandle, state: State<'_, MapState>, path: String) -> Result<MapInfo, KioskError> {
+    let text = std::fs::read_to_string(vfs::resolve(&app, &path, Access::Read)?).map_err(|e| e.to_string())?;
+    let value: Value = serde_json::from_str(&text).map_err(|e| format!("Invalid map bundle: {}", e))?;
+    let map = if value.get("type").and_then(Value::as_str) == Some("FeatureCollection") {
+        parse_geojson(&value)?
+    } else {
+        serde_json::from_value(value).map_err(|e| format!("Invalid map bundle: {}", e))?
+    };
+    validate(&map)?;
+
+    store::save(&app, MAP_FILE, &map)?;
+    let mut current = state.0.lock().expect("map lock");
+    *current = map;
+    Ok(map_info(&current))
+}
+
+/// Get floors, nodes and points of interest of the active map
+#[tauri::command]
+pub fn get_map_info(state: State<'_, MapState>) -> MapInfo {
+    map_info(&state.0.lock().expect("map lock"))
+}
+
+/// Find the shortest route between two points of interest (or node ids)
+#[tauri::command]
+pub fn find_route(
+    state: State<'_, MapState>,
+    from: String,
+    to: String,
+    options: Option<RouteOptions>,
+) -> Result<Route, KioskError> {
+    let map = state.0.lock().expect("map lock");
+    let options = options.unwrap_or_default();
+    let index: HashMap<&str, usize> = map.nodes.iter().enumerate().map(|(i, node)| (node.id.as_str(), i)).collect();
+    let start = resolve(&map, &index, &from)?;
+    let end = resolve(&map, &index, &to)?;
+
+    let path = shortest_path(&map, start, end, &options).ok_or_else(|| {
+        if options.accessible {
+            "No step-free route available".to_string()
+        } else {
+            "No route available".to_string()
+        }
+    })?;
+    let destination = map
+        .pois
+        .iter()
+        .find(|poi| poi.id == to)
+        .map(|poi| poi.name.clone())
+        .unwrap_or(to);
+    Ok(describe(&map, &path, &destination))
+}
@@ -0,0 +1,343 @@
+//! Weather
+//!
+//! Current conditions and a daily forecast for the desktop weather widget.
+//! Data comes from Open-Meteo (or any server speaking its forecast API), is
+//! cached on disk, and refreshed in the background with a `weather-updated`
+//! event so the widget never blocks on the network.
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::KioskError;
+use crate::http::ResponseExt;
+use crate::{config, events, http, metering, store};
+
+const CONFIG_FILE: &str = "weather.json";
+const CACHE_FILE: &str = "weather_cache.json";
+
+const OPEN_METEO_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+/// How often the background loop checks whether the cache is due a refresh
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeatherUnits {
+    Metric,
+    Imperial,
+}
+
+/// Location and provider settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherConfig {
+    pub location_name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub units: WeatherUnits,
+    /// Forecast endpoint; defaults to the public Open-Meteo API
+    pub provider_url: String,
+    pub refresh_minutes: u64,
+    pub forecast_days: u32,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        WeatherConfig {
+            location_name: "Greenwich".to_string(),
+            latitude: 51.4769,
+            longitude: 0.0,
+            units: WeatherUnits::Metric,
+            provider_url: OPEN_METEO_URL.to_string(),
+            refresh_minutes: 30,
+            forecast_days: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrentWeather {
+    pub temperature: f64,
+    pub feels_like: f64,
+    pub humidity: f64,
+    pub wind_speed: f64,
+    /// WMO weather interpretation code
+    pub code: u32,
+    pub description: String,
+    pub is_day: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyForecast {
+    /// Local midnight of the day (unix seconds)
+    pub date: i64,
+    pub code: u32,
+    pub description: String,
+    pub temp_max: f64,
+    pub temp_min: f64,
+    pub precipitation_chance: Option<f64>,
+}
+
+/// Cached weather, flagged stale when the last refresh failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherReport {
+    pub location_name: String,
+    pub units: WeatherUnits,
+    pub current: CurrentWeather,
+    pub daily: Vec<DailyForecast>,
+    pub fetched_at: i64,
+    #[serde(default)]
+    pub stale: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WeatherCache {
+    report: Option<WeatherReport>,
+    last_error: Option<String>,
+}
+
+pub struct WeatherState {
+    config: Mutex<WeatherConfig>,
+    cache: Mutex<WeatherCache>,
+}
+
+impl WeatherState {
+    pub fn load(app: &AppHandle) -> Self {
+        WeatherState {
+            config: Mutex::new(store::load(app, CONFIG_FILE)),
+            cache: Mutex::new(store::load(app, CACHE_FILE)),
+        }
+    }
+}
+
+/// Open-Meteo forecast response (only the fields we request)
+#[derive(Deserialize)]
+struct ForecastResponse {
+    current: ForecastCurrent,
+    daily: ForecastDaily,
+}
+
+#[derive(Deserialize)]
+struct ForecastCurrent {
+    temperature_2m: f64,
+    apparent_temperature: f64,
+    relative_humidity_2m: f64,
+    wind_speed_10m: f64,
+    weather_code: u32,
+    is_day: u8,
+}
+
+#[derive(Deserialize)]
+struct ForecastDaily {
+    time: Vec<i64>,
+    weather_code: Vec<u32>,
+    temperature_2m_max: Vec<f64>,
+    temperature_2m_min: Vec<f64>,
+    #[serde(default)]
+    precipitation_probability_max: Vec<Option<f64>>,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Describe a WMO weather interpretation code
+fn describe(code: u32) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 | 53 | 55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 | 63 => "Rain",
+        65 => "Heavy rain",
+        66 | 67 => "Freezing rain",
+        71 | 73 => "Snow",
+        75 => "Heavy snow",
+        77 => "Snow grains",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown",
+    }
+}
+
+fn fetch(config: &WeatherConfig) -> Result<WeatherReport, String> {
+    let (temperature_unit, wind_unit) = match config.units {
+        WeatherUnits::Metric => ("celsius", "kmh"),
+        WeatherUnits::Imperial => ("fahrenheit", "mph"),
+    };
+
+    let response: ForecastResponse = http::agent()
+        .get(&config.provider_url)
+        .query("latitude", &config.latitude.to_string())
+        .query("longitude", &config.longitude.to_string())
+        .query(
+            "current",
+            "temperature_2m,apparent_temperature,relative_humidity_2m,wind_speed_10m,weather_code,is_day",
+        )
+        .query(
+            "daily",
+            "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max",
+        )
+        .query("temperature_unit", temperature_unit)
+        .query("wind_speed_unit", wind_unit)
+        .query("forecast_days", &config.forecast_days.to_string())
+        .query("timezone", "auto")
+        .query("timeformat", "unixtime")
+        .call()
+        .map_err(|e| format!("Weather request failed: {}", e))?
+        .into_throttled_json()
+        .map_err(|e| format!("Invalid weather response: {}", e))?;
+
+    let current = response.current;
+    let daily = response.daily;
+    let forecast = daily
+        .time
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &date)| {
+            let code = *daily.weather_code.get(i)?;
+            Some(DailyForecast {
+                date,
+                code,
+                description: describe(code).to_string(),
+                temp_max: *daily.temperature_2m_max.get(i)?,
+                temp_min: *daily.temperature_2m_min.get(i)?,
+                precipitation_chance: daily.precipitation_probability_max.get(i).copied().flatten(),
+            })
+        })
+        .collect();
+
+    Ok(WeatherReport {
+        location_name: config.location_name.clone(),
+        units: config.units,
+        current: CurrentWeather {
+            temperature: current.temperature_2m,
+            feels_like: current.apparent_temperature,
+            humidity: current.relative_humidity_2m,
+            wind_speed: current.wind_speed_10m,
+            code: current.weather_code,
+            description: describe(current.weather_code).to_string(),
+            is_day: current.is_day != 0,
+        },
+        daily: forecast,
+        fetched_at: Local::now().timestamp(),
+        stale: false,
+    })
+}
+
+/// Fetch fresh weather into the cache and notify the widget
+fn refresh(app: &AppHandle, state: &WeatherState) -> Result<WeatherReport, String> {
+    let config = state.config.lock().expect("weather config lock").clone();
+    let result = fetch(&config);
+
+    let mut cache = state.cache.lock().expect("weather cache lock");
+    match &result {
+        Ok(report) => {
+            cache.report = Some(report.clone());
+            cache.last_error = None;
+        }
+        Err(error) => cache.last_error = Some(error.clone()),
+    }
+    store::save(app, CACHE_FILE, &*cache)?;
+
+    let report = result?;
+    events::publish(app, "weather-updated", &report);
+    Ok(report)
+}
+
+/// The forecast's location as (latitude, longitude)
+pub(crate) fn configured_position(app: &AppHandle) -> Option<(f64, f64)> {
+    let state = app.try_state::<WeatherState>()?;
+    let config = state.config.lock().expect("weather config lock");
+    Some((config.latitude, config.longitude))
+}
+
+/// Spawn the background loop that refreshes the weather when it goes stale
+pub fn start_weather(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<WeatherState>();
+        let refresh_secs = state.config.lock().expect("weather config lock").refresh_minutes.max(5) as i64 * 60;
+        let fetched_at = state
+            .cache
+            .lock()
+            .expect("weather cache lock")
+            .report
+            .as_ref()
+            .map(|report| report.fetched_at);
+
+        let stale = fetched_at.map_or(true, |fetched_at| Local::now().timestamp() - fetched_at >= refresh_secs);
+        if stale && metering::background_allowed(&app) {
+            let _ = refresh(&app, &state);
+        }
+        std::thread::sleep(config::poll_interval(&app, POLL_INTERVAL));
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the cached weather, fetching it first if nothing is cached yet
+#[tauri::command(async)]
+pub fn get_weather(app: AppHandle, state: State<'_, WeatherState>) -> Result<WeatherReport, KioskError> {
+    {
+        let cache = state.cache.lock().expect("weather cache lock");
+        if let Some(report) = &cache.report {
+            return Ok(WeatherReport {
+                stale: cache.last_error.is_some(),
+                ..report.clone()
+            });
+        }
+    }
+    Ok(refresh(&app, &state)?)
+}
+
+/// Fetch fresh weather now
+#[tauri::command(async)]
+pub fn refresh_weather(app: AppHandle, state: State<'_, WeatherState>) -> Result<WeatherReport, KioskError> {
+    Ok(refresh(&app, &state)?)
+}
+
+/// Get the weather location and provider settings
+#[tauri::command]
+pub fn get_weather_config(state: State<'_, WeatherState>) -> WeatherConfig {
+    state.config.lock().expect("weather config lock").clone()
+}
+
+/// Update the weather settings and drop the cached report
+#[tauri::command]
+pub fn set_weather_config(
+    app: AppHandle,
+    state: State<'_, WeatherState>,
+    config: WeatherConfig,
+) -> Result<(), KioskError> {
+    if !(-90.0..=90.0).contains(&config.latitude) || !(-180.0..=180.0).contains(&config.longitude) {
+        return Err(KioskError::invalid("Latitude or longitude out of range"));
+    }
+    if !config.provider_url.starts_with("http://") && !config.provider_url.starts_with("https://") {
+        return Err(KioskError::invalid("Provider URL must start with http:// or https://"));
+    }
+    if !(1..=16).contains(&config.forecast_days) {
+        return Err(KioskError::invalid("Forecast must cover 1 to 16 days"));
+    }
+
+    let mut current = state.config.lock().expect("weather config lock");
+    *current = config;
+    store::save(&app, CONFIG_FILE, &*current)?;
+    drop(current);
+
+    // Force the background loop to fetch for the new location
+    let mut cache = state.cache.lock().expect("weather cache lock");
+    *cache = WeatherCache::default();
+    Ok(store::save(&app, CACHE_FILE, &*cache)?)
+}
@@ -0,0 +1,262 @@
+//! Wake-on-LAN
+//!
+//! Sends magic packets so an operator kiosk can wake the display kiosks in
+//! the morning, and turns WoL on for this kiosk's own wired NIC so the fleet
+//! server can wake it for overnight updates. The NIC setting is applied with
+//! ethtool, which does not survive a reboot, so it is stored in `wol.json`
+//! and applied again at startup.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::auth::{self, AuthState, Role};
+use crate::error::KioskError;
+use crate::{events, overlay, store};
+
+const WOL_FILE: &str = "wol.json";
+
+const NET_DIR: &str = "/sys/class/net";
+
+/// The discard port, which WoL senders use by convention
+const DEFAULT_PORT: u16 = 9;
+
+/// Packets are sent a few times since UDP broadcast may drop one
+const REPEATS: usize = 3;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WolConfig {
+    enabled: bool,
+    /// NIC to enable WoL on; the first wired interface when unset
+    #[serde(default)]
+    interface: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WolStatus {
+    pub interface: String,
+    pub mac: String,
+    /// The NIC supports waking on magic packets
+    pub supported: bool,
+    /// Magic packet wake is on in the NIC right now
+    pub enabled: bool,
+    /// Stored setting, applied again at every start
+    pub configured: bool,
+    /// Why the NIC could not be queried, if it could not
+    pub error: Option<String>,
+}
+
+/// Payload of `wol-sent`
+#[derive(Debug, Clone, Serialize)]
+pub struct WolSent {
+    pub mac: String,
+    pub broadcast: String,
+    pub port: u16,
+}
+
+pub struct WolState {
+    config: Mutex<WolConfig>,
+}
+
+impl WolState {
+    pub fn load(app: &AppHandle) -> Self {
+        WolState {
+            config: Mutex::new(store::load(app, WOL_FILE)),
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Parse `aa:bb:cc:dd:ee:ff`, `aa-bb-cc-dd-ee-ff` or `aabbccddeeff`
+fn parse_mac(mac: &str) -> Result<[u8; 6], String> {
+    let hex: String = mac.trim().chars().filter(|c| !matches!(c, ':' | '-' | '.')).collect();
+    if hex.len() != 12 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Not a MAC address: {}", mac));
+    }
+    let mut bytes = [0u8; 6];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|e| e.to_string())?;
+    }
+    Ok(bytes)
+}
+
+fn format_mac(bytes: &[u8; 6]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(":")
+}
+
+/// Six 0xFF bytes followed by the MAC sixteen times
+fn magic_packet(mac: &[u8; 6]) -> Vec<u8> {
+    let mut packet = vec![0xFF; 6];
+    for _ in 0..16 {
+        packet.extend_from_slice(mac);
+    }
+    packet
+}
+
+fn send_packet(mac: &[u8; 6], broadcast: Ipv4Addr, port: u16) -> Result<(), String> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|e| e.to_string())?;
+    socket.set_broadcast(true).map_err(|e| e.to_string())?;
+    let packet = magic_packet(mac);
+    for _ in 0..REPEATS {
+        socket.send_to(&packet, (broadcast, port)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// First physical wired interface: has a device, is not wireless
+fn default_interface() -> Option<String> {
+    let mut names: Vec<String> = fs::read_dir(NET_DIR)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names.into_iter().find(|name| {
+        let dir = Path::new(NET_DIR).join(name);
+        dir.join("device").exists() && !dir.join("wireless").exists() && !dir.join("phy80211").exists()
+    })
+}
+
+fn interface_name(requested: Option<String>, config: &WolConfig) -> Result<String, String> {
+    let name = requested
+        .or_else(|| config.interface.clone())
+        .or_else(default_interface)
+        .ok_or_else(|| "No wired network interface".to_string())?;
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid || !Path::new(NET_DIR).join(&name).exists() {
+        return Err(format!("No network interface {}", name));
+    }
+    Ok(name)
+}
+
+/// The `Supports Wake-on` and `Wake-on` letters from ethtool's report
+fn wake_modes(interface: &str) -> Result<(String, String), String> {
+    let report = overlay::privileged("ethtool", &[interface])?;
+    let field = |label: &str| {
+        report
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(label))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+    Ok((field("Supports Wake-on:"), field("Wake-on:")))
+}
+
+fn apply(interface: &str, enabled: bool) -> Result<(), String> {
+    overlay::privileged("ethtool", &["-s", interface, "wol", if enabled { "g" } else { "d" }]).map(|_| ())
+}
+
+fn status(interface: String, configured: bool) -> WolStatus {
+    let mac = fs::read_to_string(Path::new(NET_DIR).join(&interface).join("address"))
+        .map(|mac| mac.trim().to_string())
+        .unwrap_or_default();
+    let (modes, error) = match wake_modes(&interface) {
+        Ok(modes) => (modes, None),
+        Err(e) => (Default::default(), Some(e)),
+    };
+    WolStatus {
+        interface,
+        mac,
+        supported: modes.0.contains('g'),
+        enabled: modes.1.contains('g'),
+        configured,
+        error,
+    }
+}
+
+/// Turn WoL back on at startup if it was configured
+pub fn start_wol(app: AppHandle) {
+    let config = app.state::<WolState>().config.lock().expect("wol lock").clone();
+    if !config.enabled {
+        return;
+    }
+    std::thread::spawn(move || {
+        if let Ok(interface) = interface_name(None, &config) {
+            let _ = apply(&interface, true);
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Send a magic packet to wake the machine with the given MAC, to the
+/// limited broadcast address unless a subnet broadcast is given
+#[tauri::command]
+pub fn send_wol(
+    app: AppHandle,
+    auth: State<'_, AuthState>,
+    mac: String,
+    broadcast: Option<String>,
+    port: Option<u16>,
+) -> Result<WolSent, KioskError> {
+    auth::require(&auth, Role::Operator)?;
+    let bytes = parse_mac(&mac).map_err(KioskError::invalid)?;
+    let address: Ipv4Addr = match broadcast.as_deref() {
+        Some(address) => address
+            .trim()
+            .parse()
+            .map_err(|_| KioskError::invalid(format!("Not an IPv4 address: {}", address)))?,
+        None => Ipv4Addr::BROADCAST,
+    };
+    let port = port.unwrap_or(DEFAULT_PORT);
+    send_packet(&bytes, address, port)?;
+
+    let sent = WolSent {
+        mac: format_mac(&bytes),
+        broadcast: address.to_string(),
+        port,
+    };
+    events::publish(&app, "wol-sent", &sent);
+    Ok(sent)
+}
+
+/// Wake-on-LAN support and setting of this kiosk's wired NIC
+#[tauri::command]
+pub fn get_wol_status(state: State<'_, WolState>, interface: Option<String>) -> Result<WolStatus, KioskError> {
+    let config = state.config.lock().expect("wol lock").clone();
+    let interface = interface_name(interface, &config).map_err(KioskError::not_found)?;
+    Ok(status(interface, config.enabled))
+}
+
+/// Turn waking on magic packets on or off for this kiosk's NIC and keep the
+/// setting across restarts (admin)
+#[tauri::command]
+pub fn set_wol_enabled(
+    app: AppHandle,
+    state: State<'_, WolState>,
+    auth: State<'_, AuthState>,
+    enabled: bool,
+    interface: Option<String>,
+) -> Result<WolStatus, KioskError> {
+    auth::require(&auth, Role::Admin)?;
+    let mut config = state.config.lock().expect("wol lock");
+    let name = interface_name(interface.clone(), &config).map_err(KioskError::not_found)?;
+    let (supported, _) = wake_modes(&name)?;
+    if enabled && !supported.contains('g') {
+        return Err(KioskError::invalid(format!("{} cannot wake on magic packets", name)));
+    }
+    apply(&name, enabled)?;
+
+    config.enabled = enabled;
+    if interface.is_some() {
+        config.interface = Some(name.clone());
+    }
+    store::save(&app, WOL_FILE, &*config)?;
+    drop(config);
+
+    let status = status(name, enabled);
+    events::publish(&app, "wol-changed", &status);
+    Ok(status)
+}
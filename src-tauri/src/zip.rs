@@ -0,0 +1,220 @@
+//! Minimal zip writer and reader
+//!
+//! Writes deflated entries and a central directory, enough for archives
+//! that other tools open (support bundles, backups), and reads back stored
+//! or deflated entries with their CRCs checked. Each entry is handled in
+//! memory, so it is meant for files of a few megabytes, and archives are
+//! limited to 4 GiB (no zip64).
+
+use chrono::{Datelike, Local, NaiveDateTime, Timelike};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY: u32 = 0x0605_4b50;
+
+const VERSION: u16 = 20;
+/// General purpose flag bit 11: names are UTF-8
+const FLAG_UTF8: u16 = 0x0800;
+const METHOD_STORE: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+/// Unix `-rw-r--r--` in the high half of the external attributes
+const UNIX_FILE_MODE: u32 = 0o100644 << 16;
+
+// ============================================================================
+// Data Structures
+// ============================================================================
+
+struct Entry {
+    name: String,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+}
+
+pub(crate) struct ZipWriter<W: Write> {
+    out: W,
+    offset: u64,
+    entries: Vec<Entry>,
+    /// MS-DOS (time, date) stamped on every entry
+    modified: (u16, u16),
+}
+
+// ============================================================================
+// Writer
+// ============================================================================
+
+fn dos_time(time: NaiveDateTime) -> (u16, u16) {
+    let dos_time = ((time.hour() << 11) | (time.minute() << 5) | (time.second() / 2)) as u16;
+    let dos_date = (((time.year().clamp(1980, 2107) - 1980) as u32) << 9) | (time.month() << 5) | time.day();
+    (dos_time, dos_date as u16)
+}
+
+fn to_u32(value: u64) -> Result<u32, String> {
+    u32::try_from(value).map_err(|_| "Archive is larger than 4 GiB".to_string())
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub(crate) fn new(out: W) -> Self {
+        ZipWriter {
+            out,
+            offset: 0,
+            entries: Vec::new(),
+            modified: dos_time(Local::now().naive_local()),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.out.write_all(bytes).map_err(|e| e.to_string())?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Add a file; `name` uses `/` separators
+    pub(crate) fn add(&mut self, name: &str, data: &[u8]) -> Result<(), String> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).map_err(|e| e.to_string())?;
+        let compressed = encoder.finish().map_err(|e| e.to_string())?;
+
+        let entry = Entry {
+            name: name.trim_start_matches('/').to_string(),
+            crc: crc32fast::hash(data),
+            compressed_size: to_u32(compressed.len() as u64)?,
+            size: to_u32(data.len() as u64)?,
+            offset: to_u32(self.offset)?,
+        };
+
+        let mut header = Vec::with_capacity(30 + entry.name.len());
+        header.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+        header.extend_from_slice(&METHOD_DEFLATE.to_le_bytes());
+        header.extend_from_slice(&self.modified.0.to_le_bytes());
+        header.extend_from_slice(&self.modified.1.to_le_bytes());
+        header.extend_from_slice(&entry.crc.to_le_bytes());
+        header.extend_from_slice(&entry.compressed_size.to_le_bytes());
+        header.extend_from_slice(&entry.size.to_le_bytes());
+        header.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(entry.name.as_bytes());
+
+        self.write(&header)?;
+        self.write(&compressed)?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Write the central directory and return the underlying writer
+    pub(crate) fn finish(mut self) -> Result<W, String> {
+        let directory_offset = to_u32(self.offset)?;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+            directory.extend_from_slice(&VERSION.to_le_bytes());
+            directory.extend_from_slice(&VERSION.to_le_bytes());
+            directory.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+            directory.extend_from_slice(&METHOD_DEFLATE.to_le_bytes());
+            directory.extend_from_slice(&self.modified.0.to_le_bytes());
+            directory.extend_from_slice(&self.modified.1.to_le_bytes());
+            directory.extend_from_slice(&entry.crc.to_le_bytes());
+            directory.extend_from_slice(&entry.compressed_size.to_le_bytes());
+            directory.extend_from_slice(&entry.size.to_le_bytes());
+            directory.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            // Extra field, comment, disk number and internal attributes
+            directory.extend_from_slice(&[0u8; 8]);
+            directory.extend_from_slice(&UNIX_FILE_MODE.to_le_bytes());
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+        let directory_size = to_u32(directory.len() as u64)?;
+        let count = u16::try_from(self.entries.len()).map_err(|_| "Too many entries".to_string())?;
+        self.write(&directory)?;
+
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&END_OF_DIRECTORY.to_le_bytes());
+        end.extend_from_slice(&[0u8; 4]);
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&count.to_le_bytes());
+        end.extend_from_slice(&directory_size.to_le_bytes());
+        end.extend_from_slice(&directory_offset.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes());
+        self.write(&end)?;
+
+        self.out.flush().map_err(|e| e.to_string())?;
+        Ok(self.out)
+    }
+}
+
+// ============================================================================
+// Reader
+// ============================================================================
+
+fn u16_at(data: &[u8], at: usize) -> Result<u16, String> {
+    data.get(at..at + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| "Archive is truncated".to_string())
+}
+
+fn u32_at(data: &[u8], at: usize) -> Result<u32, String> {
+    data.get(at..at + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or_else(|| "Archive is truncated".to_string())
+}
+
+/// Every file in an archive as (name, contents), failing on anything
+/// corrupt: bad headers, unsupported compression, or a size or CRC mismatch
+pub(crate) fn read_entries(data: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    // The end record is the last 22 bytes unless the archive has a comment
+    let end = (0..data.len().saturating_sub(21))
+        .rev()
+        .find(|&at| u32_at(data, at) == Ok(END_OF_DIRECTORY))
+        .ok_or_else(|| "Not a zip archive".to_string())?;
+    let count = u16_at(data, end + 10)? as usize;
+    let mut at = u32_at(data, end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(data, at)? != CENTRAL_HEADER {
+            return Err("Archive directory is corrupt".to_string());
+        }
+        let method = u16_at(data, at + 10)?;
+        let crc = u32_at(data, at + 16)?;
+        let compressed_size = u32_at(data, at + 20)? as usize;
+        let size = u32_at(data, at + 24)? as usize;
+        let name_len = u16_at(data, at + 28)? as usize;
+        let extra_len = u16_at(data, at + 30)? as usize;
+        let comment_len = u16_at(data, at + 32)? as usize;
+        let offset = u32_at(data, at + 42)? as usize;
+        let name = data.get(at + 46..at + 46 + name_len).ok_or_else(|| "Archive is truncated".to_string())?;
+        let name = String::from_utf8_lossy(name).to_string();
+        at += 46 + name_len + extra_len + comment_len;
+
+        if name.ends_with('/') {
+            continue;
+        }
+        if u32_at(data, offset)? != LOCAL_HEADER {
+            return Err(format!("{}: bad entry header", name));
+        }
+        let start = offset + 30 + u16_at(data, offset + 26)? as usize + u16_at(data, offset + 28)? as usize;
+        let stored = data.get(start..start + compressed_size).ok_or_else(|| format!("{}: truncated", name))?;
+        let contents = match method {
+            METHOD_STORE => stored.to_vec(),
+            METHOD_DEFLATE => {
+                let mut contents = Vec::with_capacity(size);
+                // One byte past the declared size is enough to catch a mismatch
+                DeflateDecoder::new(stored).take(size as u64 + 1).read_to_end(&mut contents).map_err(|e| format!("{}: {}", name, e))?;
+                contents
+            }
+            other => return Err(format!("{}: unsupported compression method {}", name, other)),
+        };
+        if contents.len() != size || crc32fast::hash(&contents) != crc {
+            return Err(format!("{}: checksum mismatch", name));
+        }
+        entries.push((name, contents));
+    }
+    Ok(entries)
+}
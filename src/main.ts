@@ -6,7 +6,7 @@
  */
 
 import { Desktop } from './components/Desktop';
-import { isTauri } from './utils/api';
+import { isTauri, reportFirstPaint } from './utils/api';
 
 // Import WinBox CSS
 import 'winbox/dist/css/winbox.min.css';
@@ -44,6 +44,11 @@ async function init(): Promise<void> {
 
     if (!isTauri()) {
       console.log('Kiosk: Running in browser mode (no Tauri backend)');
+    } else {
+      // The second frame callback runs after the desktop has been painted
+      requestAnimationFrame(() => requestAnimationFrame(() => {
+        reportFirstPaint().catch((error) => console.warn('Kiosk: Boot timing not recorded:', error));
+      }));
     }
 
     // Emit ready event
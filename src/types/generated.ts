@@ -0,0 +1,4175 @@
+// Generated from the backend sources by src-tauri/build/tsgen.rs; do not edit.
+
+// accessibility
+
+export interface StickyKeys {
+  enabled: boolean;
+  /** A modifier pressed twice stays down until pressed again */
+  lock_on_double_press: boolean;
+  /** Pressing a modifier together with another key turns sticky keys off */
+  off_when_two_pressed: boolean;
+}
+
+export interface FilterKeys {
+  enabled: boolean;
+  /** How long a key is held before it counts; 0 for no wait */
+  slow_keys_ms: number;
+  /**
+   * A key pressed again within this long of its release is ignored; 0
+   * to take every press
+   */
+  bounce_keys_ms: number;
+}
+
+export interface MouseKeys {
+  enabled: boolean;
+  /** Top pointer speed in pixels per second */
+  max_speed: number;
+  /** How long a key is held before the pointer reaches top speed */
+  acceleration_ms: number;
+}
+
+export interface AccessibilitySettings {
+  sticky_keys: StickyKeys;
+  filter_keys: FilterKeys;
+  /** Announce Caps Lock, Num Lock and Scroll Lock */
+  toggle_keys: boolean;
+  mouse_keys: MouseKeys;
+  /** The keyboard shortcuts that turn the features on and off */
+  shortcuts: boolean;
+}
+
+/** Payload of `sticky-keys` */
+export interface StickyModifiers {
+  /** Held for the next key */
+  latched: string[];
+  /** Held until pressed again */
+  locked: string[];
+}
+
+export interface AccessibilityStatus {
+  /** Keyboards are being watched */
+  running: boolean;
+  /** Keyboards grabbed and passed through the virtual device */
+  grabbed: string[];
+  sticky: StickyModifiers;
+  /** Why the keyboards can't be watched or grabbed */
+  error: string | null;
+}
+
+// approvals
+
+export interface DualAuthConfig {
+  enabled: boolean;
+  /** Actions from `list_protected_actions` that need a second admin */
+  actions: string[];
+}
+
+export interface ProtectedAction {
+  action: string;
+  description: string;
+  /** Needs a second admin under the current settings */
+  protected: boolean;
+}
+
+export interface Approval {
+  id: string;
+  action: string;
+  requested_by: string;
+  requested_at: number;
+  /** Unix time the request, or the approval, lapses */
+  expires_at: number;
+  approved_by: string | null;
+}
+
+export type AuditEvent =
+  | 'requested'
+  | 'approved'
+  | 'rejected'
+  | 'cancelled'
+  | 'used';
+
+export interface AuditEntry {
+  time: number;
+  event: AuditEvent;
+  action: string;
+  approval_id: string;
+  /** Operator who did this step */
+  operator: string;
+  detail?: string | null;
+}
+
+// attract
+
+export type AttractKind =
+  | 'image'
+  | 'video'
+  | 'url';
+
+/** One entry in the attract playlist */
+export interface AttractItem {
+  kind: AttractKind;
+  /** File path or URL */
+  source: string;
+  /** Seconds on screen before moving on */
+  dwell_secs: number;
+}
+
+export interface AttractConfig {
+  enabled: boolean;
+  /** Seconds without activity before the loop starts */
+  idle_timeout_secs: number;
+  items: AttractItem[];
+}
+
+/** Payload of the `attract-state` event */
+export interface AttractStatus {
+  active: boolean;
+  index: number;
+  item: AttractItem | null;
+  /** Set when the loop was interrupted and the home screen should show */
+  return_home: boolean;
+}
+
+// auth
+
+/** Operator roles, lowest to highest */
+export type Role =
+  | 'operator'
+  | 'supervisor'
+  | 'admin';
+
+export type AuthProvider =
+  | 'local'
+  | 'ldap'
+  | 'cached'
+  | 'totp';
+
+export interface LocalAccount {
+  username: string;
+  display_name: string;
+  role: Role;
+  pin_hash: string;
+  salt: string;
+}
+
+/** A local account as shown to the frontend */
+export interface OperatorAccount {
+  username: string;
+  display_name: string;
+  role: Role;
+  /** New PIN; omitted when editing keeps the current one */
+  pin?: string | null;
+}
+
+/** Directory group granting a role */
+export interface GroupRole {
+  /** Group DN or common name */
+  group: string;
+  role: Role;
+}
+
+export interface LdapConfig {
+  enabled: boolean;
+  host: string;
+  port: number;
+  security: LdapSecurity;
+  /** Search base, e.g. "dc=corp,dc=example,dc=com" */
+  base_dn: string;
+  /** Attribute holding the login name */
+  user_attribute: string;
+  /**
+   * Bind name built from the login, e.g. "{username}@corp.example.com";
+   * used when no service account is configured
+   */
+  bind_template: string;
+  /** Service account used to look users up before binding as them */
+  bind_dn?: string;
+  bind_password?: string;
+  group_roles: GroupRole[];
+  /** Days a cached directory login stays valid offline; 0 disables caching */
+  offline_cache_days: number;
+}
+
+export interface CachedLogin {
+  username: string;
+  display_name: string;
+  role: Role;
+  hash: string;
+  salt: string;
+  cached_at: number;
+}
+
+/** The signed-in operator */
+export interface OperatorSession {
+  username: string;
+  display_name: string;
+  role: Role;
+  provider: AuthProvider;
+  logged_in_at: number;
+}
+
+// avcheck
+
+export interface CameraCheck {
+  device: string | null;
+  passed: boolean;
+  frames: number;
+  fps: number;
+  /** Time to the first frame */
+  startup_ms: number | null;
+  /** Mean gray level, 0 to 255 */
+  brightness: number;
+  /** Mean change between frames, in gray levels */
+  motion: number;
+  issues: string[];
+}
+
+export interface MicrophoneCheck {
+  device: string | null;
+  passed: boolean;
+  /** Background level, in dBFS */
+  ambient_db: number | null;
+  /** Loudest sample during the test, in dBFS */
+  peak_db: number | null;
+  clipping: boolean;
+  issues: string[];
+}
+
+export interface SpeakerCheck {
+  device: string | null;
+  passed: boolean;
+  /** The test tone's level at the microphone, in dBFS */
+  tone_db: number | null;
+  /** How far the tone stood out from the background */
+  above_ambient_db: number | null;
+  issues: string[];
+}
+
+/** Result of a `test_av_devices` job */
+export interface AvReport {
+  camera: CameraCheck;
+  microphone: MicrophoneCheck;
+  speaker: SpeakerCheck;
+  passed: boolean;
+  tested_at: number;
+}
+
+// backup
+
+export interface BackupEntry {
+  /** Path relative to the data directory, `/` separated */
+  name: string;
+  size: number;
+  sha256: string;
+}
+
+export interface BackupManifest {
+  created_at: string;
+  host: string;
+  version: string;
+  files: BackupEntry[];
+  /** The keyring export, when the backup was given a passphrase */
+  secrets?: BackupEntry | null;
+}
+
+export interface BackupResult {
+  path: string;
+  size: number;
+  files: number;
+  /** Keyring secrets carried under the passphrase */
+  secrets: number;
+  /** SHA-256 of the archive, also written beside it */
+  sha256: string;
+  created_at: number;
+}
+
+export interface RestoreResult {
+  files: number;
+  /** Keyring secrets sealed to this device */
+  secrets: number;
+  /** Host and time the backup was taken on */
+  host: string;
+  created_at: string;
+}
+
+// badges
+
+/** Badge size and styling. Defaults to a landscape CR80 card. */
+export interface BadgeTemplate {
+  width_mm: number;
+  height_mm: number;
+  dpi: number;
+  header_text: string;
+  /** Header band colour as "#rrggbb" */
+  accent_color: string;
+  /** Label printer queue; the system default when unset */
+  printer: string | null;
+}
+
+/** Visitor details printed on the badge */
+export interface BadgeData {
+  name: string;
+  subtitle?: string;
+  /** Image file path or `data:` URL (e.g. a webcam capture) */
+  photo?: string | null;
+  /** Text encoded in the QR code, e.g. a visit id */
+  qr_data?: string | null;
+  template?: BadgeTemplate;
+}
+
+export type BadgeFormat =
+  | 'png'
+  | 'pdf';
+
+/** A rendered badge */
+export interface BadgePreview {
+  /** PNG or PDF as a `data:` URL */
+  data_url: string;
+  width: number;
+  height: number;
+}
+
+// bandwidth
+
+/** "HH:MM" to "HH:MM"; an end at or before the start runs past midnight */
+export interface TimeWindow {
+  start: string;
+  end: string;
+}
+
+/** When the limit applies */
+export type BandwidthSchedule =
+  | { type: 'always' }
+  | { type: 'open_hours' }
+  | { type: 'windows'; windows: TimeWindow[]; };
+
+export interface BandwidthConfig {
+  /** Kilobits per second; 0 for no limit */
+  kbps: number;
+  schedule: BandwidthSchedule;
+}
+
+export interface BandwidthStatus {
+  config: BandwidthConfig;
+  /** Limit in force now, in kilobits per second */
+  active_kbps: number | null;
+}
+
+/** Payload of `bandwidth-limit-changed` */
+export interface BandwidthLimitChanged {
+  active_kbps: number | null;
+}
+
+// benchmark
+
+export interface FrameRate {
+  average_fps: number;
+  min_fps: number;
+  frames: number;
+}
+
+export interface BenchmarkResult {
+  /** Job id the result was produced by */
+  id: string;
+  model: string;
+  cpu_single_mbps: number;
+  cpu_multi_mbps: number;
+  threads: number;
+  memory_mbps: number;
+  disk_write_mbps: number;
+  disk_read_mbps: number;
+  frame_rate?: FrameRate | null;
+  score: number;
+  run_at: number;
+}
+
+// bitmap
+
+export type BitmapFormat =
+  | 'bmp'
+  | 'ico'
+  | 'cur'
+  | 'ani';
+
+export interface Hotspot {
+  x: number;
+  y: number;
+}
+
+export interface BitmapImage {
+  width: number;
+  height: number;
+  /** Bits per pixel as stored */
+  bits: number;
+  /** "#rrggbb" colours of an indexed image */
+  palette: string[];
+  hotspot: Hotspot | null;
+  /** PNG as a `data:` URL */
+  data_url: string;
+}
+
+export interface AniStep {
+  /** Index into the frames */
+  frame: number;
+  jiffies: number;
+}
+
+export interface Animation {
+  /**
+   * Frames in the order shown; when writing, each frame once at the
+   * default rate if empty
+   */
+  steps: AniStep[];
+  title: string | null;
+  author: string | null;
+}
+
+export interface BitmapFile {
+  format: BitmapFormat;
+  /** The BMP, each size of an icon, or each frame of an animation */
+  images: BitmapImage[];
+  animation: Animation | null;
+}
+
+export interface BitmapSource {
+  /** PNG or JPEG as a `data:` URL */
+  data_url: string;
+  /** Cursor hotspot; the top-left corner when unset */
+  hotspot?: Hotspot | null;
+}
+
+export interface BitmapWriteOptions {
+  /** Taken from the file extension when unset */
+  format?: BitmapFormat | null;
+  /** 1, 4, 8, 24 or 32; the fewest that keep every colour when unset */
+  bits?: number | null;
+  /** "#rrggbb" colours for an indexed image */
+  palette?: string[];
+  /** Steps and details of an ANI */
+  animation?: Animation | null;
+}
+
+// boot
+
+export interface BootMark {
+  name: string;
+  /** Milliseconds since the process started */
+  at_ms: number;
+  /** Milliseconds since the previous mark */
+  delta_ms: number;
+}
+
+export interface BootRecord {
+  time: number;
+  total_ms: number;
+  regression?: string | null;
+}
+
+export interface BootConfig {
+  /** Target for process start to first paint */
+  budget_ms: number;
+  history?: BootRecord[];
+}
+
+export interface BootTimeline {
+  marks: BootMark[];
+  /** Start to first paint, once the frontend has reported it */
+  total_ms: number | null;
+  budget_ms: number;
+  median_ms: number | null;
+  history: BootRecord[];
+}
+
+// brightness
+
+export type LightSensor =
+  | { type: 'auto' }
+  | { type: 'iio'; device: string; }
+  | { type: 'bh1750'; bus: number; address: number; };
+
+export interface CurvePoint {
+  lux: number;
+  /** Backlight level in percent */
+  brightness: number;
+}
+
+export interface BrightnessConfig {
+  auto: boolean;
+  sensor: LightSensor;
+  /** Interpolated on a log lux scale, flat beyond the ends */
+  curve: CurvePoint[];
+  /** Floor so the screen never goes fully dark */
+  min_brightness: number;
+}
+
+export interface LightLevel {
+  /** Smoothed illuminance; `None` without a working sensor */
+  lux: number | null;
+  sensor: string | null;
+  /** Current backlight level in percent */
+  brightness: number | null;
+  auto: boolean;
+  error: string | null;
+}
+
+/** Payload of `brightness-changed` */
+export interface BrightnessChanged {
+  brightness: number;
+  lux: number | null;
+  auto: boolean;
+}
+
+// bundles
+
+export interface BundleConfig {
+  /** Refuse bundles without a valid signature */
+  lockdown: boolean;
+  /** Trusted public keys (32 bytes, base64 or hex) */
+  trusted_keys: string[];
+}
+
+export interface BundleVerification {
+  /** A signature file was present and verified */
+  signed: boolean;
+  /** Fingerprint of the key that verified the signature */
+  key_fingerprint: string | null;
+  /** SHA-256 of the bundle, hex */
+  digest: string;
+}
+
+// business
+
+/** Open from `open` to `close`, as "HH:MM" */
+export interface Period {
+  open: string;
+  close: string;
+}
+
+/** Different hours on one date, or closed when `hours` is empty */
+export interface SpecialDay {
+  /** "YYYY-MM-DD" */
+  date: string;
+  name?: string;
+  hours?: Period[];
+}
+
+export interface BusinessHours {
+  /** Monday first; an empty day is closed */
+  days: Period[][];
+  /** Holiday region such as `de`; no public holidays when unset */
+  region: string | null;
+  open_on_holidays: boolean;
+  special_days: SpecialDay[];
+}
+
+export interface Region {
+  name: string;
+  holidays: HolidayRule[];
+}
+
+export type Observed =
+  | 'actual'
+  | 'nearest_weekday'
+  | 'next_weekday';
+
+export interface HolidayRule {
+  name: string;
+  date: string;
+  observed?: Observed;
+  /** First year the holiday was kept */
+  since?: number | null;
+}
+
+export interface HolidayDate {
+  /** "YYYY-MM-DD" the holiday is kept on */
+  date: string;
+  name: string;
+  /** Moved off a weekend */
+  observed: boolean;
+}
+
+export interface HolidayRegion {
+  code: string;
+  name: string;
+}
+
+export interface OpenStatus {
+  open: boolean;
+  /** Holiday or special day name when today is one */
+  day_name: string | null;
+  /** Unix time the current opening period ends */
+  closes_at: number | null;
+  /** Unix time of the next opening when closed */
+  opens_at: number | null;
+}
+
+/** Payload of `business-open-changed` */
+export interface BusinessOpenChanged {
+  open: boolean;
+  opens_at: number | null;
+}
+
+// calculator
+
+/** Calculator mode, matching the View menu of the accessory */
+export type CalcMode =
+  | 'standard'
+  | 'scientific'
+  | 'programmer';
+
+/** Result of evaluating an expression */
+export interface CalcResult {
+  value: string;
+  /** Unit of the result when a conversion was requested */
+  unit: string | null;
+  /** Radix views, filled in programmer mode */
+  hex: string | null;
+  oct: string | null;
+  bin: string | null;
+}
+
+// calendar
+
+export type Frequency =
+  | 'daily'
+  | 'weekly'
+  | 'monthly'
+  | 'yearly';
+
+/** A recurrence rule, a subset of iCalendar RRULE */
+export interface Recurrence {
+  frequency: Frequency;
+  interval?: number;
+  /** Stop after this many occurrences */
+  count: number | null;
+  /** Stop after this timestamp */
+  until: number | null;
+  /** Weekdays for weekly rules ("MO", "TU", ...) */
+  by_day?: string[];
+}
+
+/** Editable event fields */
+export interface EventInput {
+  title: string;
+  location?: string;
+  description?: string;
+  start: number;
+  end: number;
+  all_day?: boolean;
+  recurrence: Recurrence | null;
+  /** Minutes before the start to raise a reminder */
+  reminder_minutes: number | null;
+}
+
+/** A stored event */
+export interface CalendarEvent extends EventInput {
+  id: string;
+  /** iCalendar UID, preserved across import/export */
+  uid: string;
+}
+
+/** One concrete occurrence of an event within a queried range */
+export interface EventOccurrence {
+  event_id: string;
+  title: string;
+  location: string;
+  start: number;
+  end: number;
+  all_day: boolean;
+}
+
+/** Payload of the `calendar-reminder` event */
+export interface ReminderPayload {
+  event_id: string;
+  title: string;
+  location: string;
+  start: number;
+  minutes_before: number;
+}
+
+// cash
+
+export interface HopperConfig {
+  address: number;
+  /** Value of each coin the hopper pays out, in minor units */
+  coin_value: number;
+}
+
+export interface CashConfig {
+  port: string;
+  baud_rate: number;
+  /** Most ccTalk adapters echo transmitted bytes back on the shared line */
+  echo: boolean;
+  coin_acceptor: number | null;
+  bill_validator: number | null;
+  /** Hold bills in escrow until `accept_escrow`/`return_escrow` */
+  escrow_bills: boolean;
+  /**
+   * Value of each coin/bill position (position 1 first), in minor units.
+   * Empty lists are filled from the device's coin/bill ids.
+   */
+  coin_values: number[];
+  bill_values: number[];
+  hoppers: HopperConfig[];
+}
+
+export type CashKind =
+  | 'coin'
+  | 'bill';
+
+/** Emitted as `cash-inserted` when money is credited */
+export interface CashInserted {
+  kind: CashKind;
+  value: number;
+  total: number;
+}
+
+/** Money credited in the current session, plus any bill held in escrow */
+export interface CashSession {
+  accepting: boolean;
+  inserted: number;
+  escrow: number | null;
+  /** Price of the current sale, set with `set_sale_total` */
+  sale_total: number;
+  /** Change already paid out this session */
+  dispensed: number;
+}
+
+export interface ChangeResult {
+  requested: number;
+  dispensed: number;
+  shortfall: number;
+  /** Why the payout stopped early, when the bus failed partway */
+  error: string | null;
+}
+
+// cec
+
+export type TvPower =
+  | 'on'
+  | 'standby'
+  | 'turning_on'
+  | 'turning_off'
+  | 'unknown';
+
+/** Payload of `tv-state` */
+export interface TvState {
+  /** A CEC adapter was found */
+  available: boolean;
+  power: TvPower;
+  /** HDMI input last selected through the kiosk; 0 means the kiosk itself */
+  input: number | null;
+  error: string | null;
+}
+
+// certs
+
+export type CertKind =
+  | 'ca'
+  | 'client';
+
+export interface CertificateInfo {
+  id: string;
+  kind: CertKind;
+  subject: string;
+  issuer: string;
+  not_before: number;
+  not_after: number;
+  /** SHA-256 of the leaf certificate, colon-separated hex */
+  fingerprint: string;
+  /** Number of certificates in the file (chain or bundle) */
+  count: number;
+  /** The client certificate currently presented to servers */
+  active?: boolean;
+}
+
+export interface CertStore {
+  certificates: CertificateInfo[];
+}
+
+// charmap
+
+/** A named range of code points */
+export interface UnicodeBlock {
+  name: string;
+  first: number;
+  last: number;
+}
+
+/** A code point the font can render */
+export interface Glyph {
+  code_point: number;
+  character: string;
+  label: string;
+}
+
+// checksum
+
+export type HashAlgorithm =
+  | 'md5'
+  | 'sha1'
+  | 'sha256';
+
+/** Payload of `hash-progress` */
+export interface HashProgress {
+  path: string;
+  processed: number;
+  total: number;
+}
+
+/** One file listed in a JSON manifest */
+export interface ManifestFile {
+  path: string;
+  hash: string;
+  algorithm?: HashAlgorithm | null;
+  size?: number | null;
+}
+
+export interface JsonManifest {
+  files: ManifestFile[];
+}
+
+export type EntryStatus =
+  | 'ok'
+  | 'mismatch'
+  | 'size_mismatch'
+  | 'missing';
+
+export interface ManifestEntry {
+  path: string;
+  algorithm: HashAlgorithm;
+  expected: string;
+  actual: string | null;
+  status: EntryStatus;
+}
+
+export interface ManifestReport {
+  /** True when every listed file is present and matches */
+  ok: boolean;
+  entries: ManifestEntry[];
+}
+
+// cleanup
+
+/** Reclaimable space in one category */
+export interface CleanupCategory {
+  id: string;
+  name: string;
+  description: string;
+  files: number;
+  bytes: number;
+}
+
+export interface CleanupSchedule {
+  enabled: boolean;
+  interval_hours: number;
+  /** Category ids to clean */
+  categories: string[];
+  last_run?: number | null;
+}
+
+/** Payload of `cleanup-progress` */
+export interface CleanupProgress {
+  category: string;
+  processed: number;
+  total: number;
+  freed: number;
+}
+
+/** Payload of `cleanup-finished` */
+export interface CleanupResult {
+  freed: number;
+  deleted: number;
+  failed: number;
+  scheduled: boolean;
+}
+
+// config
+
+export interface FeatureFlag {
+  name: string;
+  enabled: boolean;
+  /** Forced by a `KIOSK_FEATURE_*` environment variable */
+  from_env: boolean;
+}
+
+export interface BuildInfo {
+  name: string;
+  version: string;
+  identifier: string;
+  tauri_version: string;
+  rust_version: string;
+  profile: string;
+  target_os: string;
+  target_arch: string;
+  /** Optional subsystems compiled in */
+  features: string[];
+}
+
+export interface ProcessInfo {
+  pid: number;
+  executable: string | null;
+  working_dir: string | null;
+  args: string[];
+}
+
+export interface RuntimeConfig {
+  paths: Record<string, string>;
+  feature_flags: FeatureFlag[];
+  environment: Record<string, string>;
+  build: BuildInfo;
+  process: ProcessInfo;
+  plugins: string[];
+}
+
+// contacts
+
+/** A phone number with its vCard type (work, home, cell, ...) */
+export interface ContactPhone {
+  kind: string;
+  number: string;
+}
+
+/** Editable contact fields */
+export interface ContactInput {
+  display_name: string;
+  first_name: string;
+  last_name: string;
+  company: string;
+  title: string;
+  emails: string[];
+  phones: ContactPhone[];
+  address: string;
+  notes: string;
+}
+
+/** A stored contact */
+export interface Contact extends ContactInput {
+  id: string;
+  created_at: number;
+  updated_at: number;
+}
+
+// control
+
+/** Payload of `kiosk-message` */
+export interface KioskMessage {
+  title: string;
+  body: string;
+  /** Seconds to show the message; 0 keeps it until dismissed */
+  timeout_secs: number;
+  /** Where the message came from, e.g. "dbus" */
+  source: string;
+}
+
+export interface ControlStatus {
+  /** "system" or "session" once the name is claimed */
+  bus: string | null;
+  name: string;
+  /** Why the name could not be claimed, if it could not */
+  error: string | null;
+}
+
+// demo
+
+export interface DemoRoute {
+  /** "GET", "POST", ... or "*" for any */
+  method: string;
+  /** `/api/items/:id`; a trailing `*` matches the rest of the path */
+  path: string;
+  /** Fixture under the folder; `{name}` is replaced by a captured segment */
+  file: string | null;
+  /** Inline JSON answer, used when there is no file */
+  body: unknown;
+  status: number;
+  headers: Record<string, string>;
+  /** Overrides the server-wide latency */
+  latency_ms: number | null;
+}
+
+export interface DemoServerConfig {
+  enabled: boolean;
+  port: number;
+  /** Fixture folder; relative paths are under the app data directory */
+  folder: string;
+  routes: DemoRoute[];
+  /** Delay before every answer */
+  latency_ms: number;
+}
+
+export interface DemoServerStatus {
+  running: boolean;
+  /** Base URL while running, e.g. "http://127.0.0.1:8787" */
+  url: string | null;
+  folder: string;
+  requests: number;
+  /** Why the server could not start */
+  error: string | null;
+}
+
+// devices
+
+export type DeviceStatus =
+  | 'ok'
+  | 'warning'
+  | 'disabled'
+  | 'disconnected';
+
+export interface DeviceNode {
+  id: string;
+  name: string;
+  /**
+   * Icon category: computer, usb, display, sound, keyboard, mouse, hid,
+   * camera, network, storage, system or other
+   */
+  class: string;
+  vendor: string | null;
+  /** `vvvv:pppp` for USB and PCI devices */
+  hardware_id: string | null;
+  driver: string | null;
+  status: DeviceStatus;
+  children: DeviceNode[];
+}
+
+// downloads
+
+export type DownloadState =
+  | 'queued'
+  | 'active'
+  | 'paused'
+  | 'completed'
+  | 'failed';
+
+/** Payload of `download-progress` and `download-finished` */
+export interface Download {
+  id: string;
+  url: string;
+  /** Where the file ends up */
+  path: string;
+  /** The same place as a virtual path, when it is under a root */
+  virtual_path: string | null;
+  /** Who asked for it, e.g. "browser" or "content" */
+  source: string;
+  /** Expected SHA-256, hex */
+  sha256: string | null;
+  /** Non-essential: held back while the data cap is used up */
+  background: boolean;
+  max_kbps: number | null;
+  state: DownloadState;
+  received_bytes: number;
+  /** Unknown until the server says */
+  total_bytes: number | null;
+  error: string | null;
+  created_at: number;
+  finished_at: number | null;
+}
+
+// error
+
+export type ErrorKind =
+  | 'invalid'
+  | 'not_found'
+  | 'denied'
+  | 'rate_limited'
+  | 'io'
+  | 'failed';
+
+export interface KioskError {
+  kind: ErrorKind;
+  /** Sent in the kiosk's language when the catalog translates it */
+  message: string;
+}
+
+// events
+
+export interface EventRecord {
+  seq: number;
+  topic: string;
+  payload: unknown;
+  time: number;
+}
+
+export interface EventSubscription {
+  id: string;
+  /** Frontend event name the subscription is delivered on */
+  channel: string;
+  /** History matching the topics, oldest first */
+  replay: EventRecord[];
+}
+
+// feeds
+
+/** A subscribed feed and the outcome of its last refresh */
+export interface Feed {
+  url: string;
+  title: string;
+  last_fetched: number | null;
+  last_error: string | null;
+}
+
+/** A cached feed item */
+export interface FeedItem {
+  id: string;
+  feed_url: string;
+  feed_title: string;
+  title: string;
+  link: string;
+  /** Sanitized HTML summary */
+  summary: string;
+  published: number | null;
+}
+
+export interface FeedCache {
+  feeds: Feed[];
+  items: FeedItem[];
+}
+
+// fetch
+
+/** How the cache is used, as in the browser's `fetch` */
+export type CacheMode =
+  | 'default'
+  | 'no_store'
+  | 'reload'
+  | 'no_cache'
+  | 'force_cache';
+
+export interface FetchRequest {
+  url?: string;
+  /** GET when empty */
+  method?: string;
+  headers?: Record<string, string>;
+  body?: string | null;
+  /** Cookie jar to send and store cookies with; none when absent */
+  jar?: string | null;
+  cache?: CacheMode;
+  /** Extra attempts after a network error or a 429/502/503/504 */
+  retries?: number;
+  timeout_ms?: number | null;
+}
+
+export interface FetchResponse {
+  status: number;
+  status_text: string;
+  /** Final URL after redirects */
+  url: string;
+  /** Lower-case names; repeated headers are joined with ", " */
+  headers: Record<string, string>;
+  body: string;
+  /** The body is base64 because it is not text */
+  base64: boolean;
+  from_cache: boolean;
+}
+
+export interface FetchConfig {
+  /** Proxy for every backend request, e.g. "http://proxy:3128" */
+  proxy: string | null;
+  /**
+   * Hosts `fetch_url` may reach; "*.example.com" covers subdomains.
+   * Any public host when empty; local and private addresses only when
+   * listed here.
+   */
+  allowed_hosts: string[];
+}
+
+// fonts
+
+/** An installed font face */
+export interface FontInfo {
+  family: string;
+  style: string;
+  file: string;
+  user_installed: boolean;
+}
+
+// footfall
+
+export interface DayCounts {
+  visitors: number[];
+  /** Total seconds visitors stayed, by the hour they arrived */
+  dwell_secs: number[];
+  /** Visitors whose departure was seen, for the dwell average */
+  departures: number[];
+}
+
+export interface FootfallDay {
+  /** "YYYY-MM-DD" */
+  date: string;
+  total: number;
+  /** Visitors per hour, midnight first */
+  hours: number[];
+  average_dwell_secs: number | null;
+}
+
+export interface FootfallStats {
+  from: string;
+  to: string;
+  total: number;
+  days: FootfallDay[];
+  /** Visitors per hour of day over the whole range */
+  by_hour: number[];
+  /** Busiest hour of day, if anyone came */
+  peak_hour: number | null;
+  average_dwell_secs: number | null;
+}
+
+// formatting
+
+/** A measurement in the region's unit */
+export interface Measurement {
+  /** Ready to display, such as `5,2 km` */
+  text: string;
+  value: number;
+  /** Unit the value ended up in */
+  unit: string;
+  symbol: string;
+}
+
+// games
+
+/** Which end of the leaderboard is best */
+export type ScoreOrder =
+  | 'higher'
+  | 'lower';
+
+/** A finished game's score */
+export interface ScoreSubmission {
+  game: string;
+  score: number;
+  /** The logged-in operator, or "Guest", when not given */
+  player?: string | null;
+  /**
+   * Leaderboard within the game, such as a difficulty; the default one
+   * when not given
+   */
+  variant?: string | null;
+  /** Sets the leaderboard's order; kept from earlier scores when not given */
+  order?: ScoreOrder | null;
+}
+
+export interface HighScore {
+  player: string;
+  score: number;
+  achieved_at: number;
+}
+
+export interface Leaderboard {
+  game: string;
+  variant: string;
+  order: ScoreOrder;
+  entries: HighScore[];
+}
+
+/** Where a submitted score landed */
+export interface ScoreResult {
+  /** 1-based place on the leaderboard; `None` when it did not make it */
+  rank: number | null;
+  /** Whether it is the new best */
+  best: boolean;
+  /** The player's own best on this leaderboard */
+  personal_best: number | null;
+}
+
+/** Payload of `game-high-score` */
+export interface GameHighScore {
+  game: string;
+  variant: string;
+  player: string;
+  score: number;
+}
+
+export interface SavedGame {
+  game: string;
+  player: string;
+  /** Whatever the game needs to resume */
+  state: unknown;
+  saved_at: number;
+}
+
+export interface Board {
+  order: ScoreOrder;
+  entries: HighScore[];
+}
+
+export interface GamesData {
+  /** Keyed by game, then variant */
+  boards: Record<string, Record<string, Board>>;
+  /** Keyed by game, then player */
+  saves: Record<string, Record<string, SavedGame>>;
+}
+
+// help
+
+/** A help page listed in the contents pane */
+export interface HelpTopic {
+  /** Path relative to the help root, e.g. "desktop.md" */
+  path: string;
+  title: string;
+}
+
+/** A ranked search hit */
+export interface HelpResult {
+  path: string;
+  title: string;
+  snippet: string;
+  score: number;
+}
+
+// hours
+
+/** On and off times as "HH:MM" */
+export interface DayHours {
+  on: string;
+  off: string;
+}
+
+export interface Holiday {
+  /** "YYYY-MM-DD" */
+  date: string;
+  name?: string;
+  /** Special hours for the day; closed all day when absent */
+  hours?: DayHours | null;
+}
+
+/** Which ways of turning the display off are used */
+export interface DisplayOutputs {
+  dpms: boolean;
+  cec: boolean;
+  backlight: boolean;
+}
+
+export interface Schedule {
+  enabled: boolean;
+  /** Monday first; a day without hours stays off */
+  days: (DayHours | null)[];
+  holidays: Holiday[];
+  outputs: DisplayOutputs;
+}
+
+/** Payload of `display-override` */
+export interface DisplayOverride {
+  /** Forced state, or `None` once the schedule is back in charge */
+  on: boolean | null;
+  /** Unix time the override ends */
+  until: number | null;
+}
+
+/** Payload of `display-power` */
+export interface DisplayPower {
+  on: boolean;
+  /** "schedule" or "override" */
+  reason: string;
+  /** Outputs that could not be switched */
+  errors: string[];
+}
+
+export interface DisplayState {
+  /** Last state applied to the display, if the schedule has acted yet */
+  on: boolean | null;
+  scheduled_on: boolean;
+  override_on: boolean | null;
+  override_until: number | null;
+  /** Unix time of the next scheduled change */
+  next_change: number | null;
+}
+
+// i18n
+
+export interface DateNames {
+  date_short?: string;
+  date_medium?: string;
+  date_long?: string;
+  time?: string;
+  months?: string[];
+  months_short?: string[];
+  /** Monday first */
+  weekdays?: string[];
+  weekdays_short?: string[];
+}
+
+/** How numbers, prices and measurements are written */
+export interface NumberFormat {
+  decimal?: string;
+  group?: string;
+  /**
+   * Integer digits needed before grouping starts beyond the first group;
+   * 2 leaves 4-digit numbers ungrouped as in Spanish
+   */
+  min_grouping?: number;
+  /** Where the price goes (`#`) relative to the currency symbol (`¤`) */
+  currency?: string;
+  measurement_system?: MeasurementSystem;
+}
+
+export type MeasurementSystem =
+  | 'metric'
+  | 'us'
+  | 'uk';
+
+export interface Catalog {
+  name?: string;
+  dates?: DateNames;
+  numbers?: NumberFormat;
+  strings?: Record<string, string>;
+  errors?: Record<string, string>;
+}
+
+export interface LocaleSetting {
+  locale: string | null;
+  /** Customized formats, over the locale's own */
+  formats: FormatOverrides;
+  /** ISO 4217 code of the kiosk's currency */
+  currency: string | null;
+}
+
+export interface FormatOverrides {
+  decimal: string | null;
+  group: string | null;
+  currency: string | null;
+  measurement_system: MeasurementSystem | null;
+  date_short: string | null;
+  date_long: string | null;
+  time: string | null;
+}
+
+/** The formats a locale writes numbers, prices and dates in */
+export interface RegionalFormats {
+  decimal_separator: string;
+  group_separator: string;
+  /** Where the price goes (`#`) relative to the currency symbol (`¤`) */
+  currency_format: string;
+  measurement_system: MeasurementSystem;
+  /** strftime patterns */
+  date_short: string;
+  date_long: string;
+  time: string;
+}
+
+export interface LocaleInfo {
+  /** BCP 47 style code, such as `de` or `de-AT` */
+  code: string;
+  name: string;
+  /** Shipped with the kiosk rather than added in the data directory */
+  bundled: boolean;
+}
+
+/** Payload of `locale-changed` */
+export interface LocaleChanged {
+  locale: string;
+  name: string;
+}
+
+// icons
+
+export type IconSource =
+  | 'ico'
+  | 'executable'
+  | 'image'
+  | 'svg';
+
+export interface ExtractedIcon {
+  source: IconSource;
+  width: number;
+  height: number;
+  /** PNG, or the SVG, as a `data:` URL */
+  data_url: string;
+  /** The file the icon came from, when a .desktop entry pointed elsewhere */
+  file: string | null;
+}
+
+// jobs
+
+export type JobState =
+  | 'running'
+  | 'completed'
+  | 'failed'
+  | 'cancelled';
+
+/** Payload of `job-progress` and `job-finished` */
+export interface JobStatus {
+  id: string;
+  kind: string;
+  state: JobState;
+  /** 0.0 to 1.0 when the job can tell */
+  progress: number | null;
+  message: string | null;
+  result: unknown;
+  error: string | null;
+  /** Label of the window that started the job */
+  window: string | null;
+  started_at: number;
+  finished_at: number | null;
+}
+
+// keyboard
+
+export interface KeyboardLayout {
+  /** `layout` or `layout(variant)` */
+  id: string;
+  layout: string;
+  variant: string | null;
+  name: string;
+}
+
+/** One user's layouts */
+export interface KeyboardSettings {
+  /** Layouts offered by the indicator, in the user's order */
+  layouts: string[];
+  /** The layout in use */
+  active: string;
+}
+
+export interface KeyboardData {
+  /** The kiosk's own, used when nobody is signed in */
+  kiosk: KeyboardSettings;
+  /** Operators' own, by username */
+  operators: Record<string, KeyboardSettings>;
+}
+
+export interface LayoutChanged extends KeyboardSettings {
+  operator: string | null;
+}
+
+// keyring
+
+/** Every secret sealed under a passphrase rather than the device key */
+export interface SecretsExport {
+  /** Base64 PBKDF2 salt */
+  salt: string;
+  /** Sealed values by key name */
+  secrets: Record<string, string>;
+}
+
+// lan
+
+export interface LanIdentity {
+  kiosk_id: string;
+}
+
+/** A message between kiosks */
+export interface LanMessage {
+  from: string;
+  topic: string;
+  payload: unknown;
+}
+
+// ldap
+
+/** Transport encryption for the directory connection */
+export type LdapSecurity =
+  | 'tls'
+  | 'starttls'
+  | 'none';
+
+// lib
+
+/** System statistics for the system monitor */
+export interface SystemStats {
+  cpu_usage: number;
+  total_memory: number;
+  used_memory: number;
+  available_memory: number;
+  cpu_count: number;
+}
+
+/** Hardware profile information */
+export interface HardwareProfile {
+  model: string;
+  ram_mb: number;
+  os_name: string;
+  os_version: string;
+  hostname: string;
+}
+
+/** Date and time information for the taskbar clock */
+export interface DateTimeInfo {
+  time_12h: string;
+  time_24h: string;
+  /** Time in the kiosk language's usual format */
+  time: string;
+  date_short: string;
+  date_long: string;
+  day_of_week: string;
+  timestamp: number;
+}
+
+/** Drive information for file manager */
+export interface DriveInfo {
+  name: string;
+  mount_point: string;
+  total_space: number;
+  available_space: number;
+  is_removable: boolean;
+}
+
+/** Traffic counters for one network interface */
+export interface NetworkStats {
+  name: string;
+  /** Bytes since the previous call */
+  received: number;
+  transmitted: number;
+  total_received: number;
+  total_transmitted: number;
+}
+
+// location
+
+export type LocationSource =
+  | { type: 'none' }
+  | { type: 'gpsd'; host: string; port: number; }
+  | { type: 'serial'; port: string; baud: number; }
+  | { type: 'wifi'; url: string; }
+  | { type: 'fixed'; latitude: number; longitude: number; };
+
+export interface Geofence {
+  latitude: number;
+  longitude: number;
+  radius_m: number;
+}
+
+export interface LocationConfig {
+  source: LocationSource;
+  /** Seconds between Wi-Fi lookups */
+  wifi_interval_secs: number;
+  geofence: Geofence | null;
+}
+
+/** Payload of `location-changed` */
+export interface Location {
+  latitude: number;
+  longitude: number;
+  /** Estimated horizontal error */
+  accuracy_m: number | null;
+  altitude_m: number | null;
+  /** gpsd, serial, wifi or fixed */
+  source: string;
+  /** Unix time of the fix */
+  time: number;
+}
+
+export interface GeofenceStatus {
+  enabled: boolean;
+  /** Unknown until there is a fix */
+  inside: boolean | null;
+  /** Distance from the geofence centre */
+  distance_m: number | null;
+}
+
+/** Payload of `geofence-left` and `geofence-returned` */
+export interface GeofenceCrossed {
+  location: Location;
+  distance_m: number;
+  radius_m: number;
+}
+
+// lock
+
+export interface LockConfig {
+  /** Seconds idle before the screen locks itself; 0 disables it */
+  auto_lock_secs: number;
+  /** Wrong PINs allowed before unlocking is delayed */
+  max_attempts: number;
+  /** First delay after `max_attempts`; doubled with each further failure */
+  lockout_secs: number;
+  max_lockout_secs: number;
+}
+
+export interface LockStatus {
+  locked: boolean;
+  pin_set: boolean;
+  /** Wrong PINs since the last unlock */
+  failures: number;
+  /** Milliseconds until the next unlock attempt is accepted */
+  retry_in_ms: number | null;
+}
+
+/** Payload of `screen-locked` */
+export interface ScreenLocked {
+  /** Locked by the idle timer rather than on request */
+  automatic: boolean;
+}
+
+export interface LockData {
+  config: LockConfig;
+  pin_hash: string | null;
+  pin_salt: string;
+  locked: boolean;
+  /** Wrong PINs since the last unlock */
+  failures: number;
+  /** Wall-clock time (ms) before which unlocking is refused */
+  retry_at: number | null;
+}
+
+// macros
+
+/** One evdev event */
+export interface MacroEvent {
+  /** Milliseconds since the recording started */
+  at_ms: number;
+  /** Device node, such as `/dev/input/event2` */
+  device: string;
+  kind: number;
+  code: number;
+  value: number;
+}
+
+export interface Macro {
+  name: string;
+  created_at: number;
+  duration_ms: number;
+  events: MacroEvent[];
+}
+
+/** A macro without its events, for listing */
+export interface MacroInfo {
+  name: string;
+  created_at: number;
+  duration_ms: number;
+  events: number;
+}
+
+export interface MacroStatus {
+  /** Name of the macro being recorded */
+  recording: string | null;
+  /** Name of the macro being played */
+  playing: string | null;
+}
+
+/** Payload of `macro-finished` */
+export interface MacroFinished {
+  name: string;
+  /** Why playback stopped early */
+  error: string | null;
+}
+
+// magnifier
+
+export interface MagnifierSettings {
+  zoom: number;
+  /** Lens size in pixels */
+  width: number;
+  height: number;
+  /** Frames per second */
+  fps: number;
+  /** Smooth the scaled image rather than showing square pixels */
+  smoothing: boolean;
+  /**
+   * Keep the lens beside the pointer; otherwise it stays in the top
+   * right corner
+   */
+  follow_pointer: boolean;
+}
+
+export interface MagnifierStatus {
+  running: boolean;
+  zoom: number;
+  /** Why the last frame could not be shown */
+  error: string | null;
+}
+
+// mail
+
+/** Connection security for IMAP/SMTP */
+export type MailSecurity =
+  | 'tls'
+  | 'starttls'
+  | 'none';
+
+/** A configured mailbox */
+export interface EmailAccount {
+  id?: string;
+  name: string;
+  email: string;
+  username: string;
+  password?: string;
+  imap_host: string;
+  imap_port: number;
+  imap_security?: MailSecurity;
+  smtp_host: string;
+  smtp_port: number;
+  smtp_security?: MailSecurity;
+}
+
+/** Message list entry */
+export interface MessageSummary {
+  uid: number;
+  subject: string;
+  from: string;
+  date: number;
+  size: number;
+  seen: boolean;
+}
+
+/** A full message ready for the preview pane */
+export interface EmailMessage {
+  uid: number;
+  subject: string;
+  from: string;
+  to: string[];
+  date: number;
+  text: string;
+  html: string | null;
+  attachments: string[];
+}
+
+/** An outgoing message */
+export interface EmailDraft {
+  account_id: string;
+  to: string[];
+  cc?: string[];
+  subject: string;
+  body: string;
+}
+
+/** Result of syncing one account */
+export interface SyncSummary {
+  folders: string[];
+  messages: number;
+  unread: number;
+  synced_at: number;
+}
+
+/** Cached folder listing for one account */
+export interface MailCache {
+  folders: string[];
+  messages: Record<string, MessageSummary[]>;
+}
+
+// metering
+
+export interface ByteCounts {
+  rx_bytes: number;
+  tx_bytes: number;
+}
+
+export interface DataUsageConfig {
+  /** Interface name prefixes that count toward the cap */
+  metered: string[];
+  /** Monthly allowance on metered interfaces; no cap when unset */
+  monthly_cap_mb: number | null;
+  /** Day of the month the allowance renews (1-28) */
+  billing_day: number;
+  /** Percentage of the cap that triggers `data-cap-warning` */
+  warn_percent: number;
+}
+
+export interface UsageDay {
+  /** "YYYY-MM-DD" */
+  date: string;
+  interfaces: Record<string, ByteCounts>;
+}
+
+export interface DataUsage {
+  from: string;
+  to: string;
+  days: UsageDay[];
+  /** Per interface over the whole range */
+  totals: Record<string, ByteCounts>;
+}
+
+/** Payload of `data-cap-warning` and `data-cap-exceeded` */
+export interface DataCapStatus {
+  cap_bytes: number | null;
+  /** Metered bytes this billing period */
+  used_bytes: number;
+  /** First day of the billing period, "YYYY-MM-DD" */
+  period_start: string;
+  exceeded: boolean;
+}
+
+// microphone
+
+export interface AudioDevice {
+  /** `pulse:<source or sink>` or `alsa:<device>` */
+  id: string;
+  name: string;
+  default: boolean;
+}
+
+export type AudioFormat =
+  | 'wav'
+  | 'flac'
+  | 'ogg'
+  | 'opus'
+  | 'mp3';
+
+/** Payload of `audio-level`, both 0 to 1 of full scale */
+export interface AudioLevel {
+  peak: number;
+  rms: number;
+}
+
+export interface AudioRecording {
+  /** Virtual path of the file */
+  path: string;
+  format: AudioFormat;
+  size: number;
+  duration_secs: number;
+  /** Why it stopped: "stopped", "time limit", "storage full" or "capture ended" */
+  reason: string;
+  created_at: number;
+}
+
+export interface AudioRecordingStatus {
+  path: string;
+  format: AudioFormat;
+  device: string | null;
+  elapsed_secs: number;
+}
+
+// middleware
+
+export interface CommandMetrics {
+  command: string;
+  calls: number;
+  rate_limited: number;
+  total_ms: number;
+  max_ms: number;
+  last_ms: number;
+}
+
+// minesweeper
+
+export type MinesweeperDifficulty =
+  | 'beginner'
+  | 'intermediate'
+  | 'expert'
+  | 'custom';
+
+export interface MinesweeperOptions {
+  difficulty?: MinesweeperDifficulty;
+  /** Size and mines of a custom board */
+  width?: number | null;
+  height?: number | null;
+  mines?: number | null;
+  /** Replays a board; random when unset */
+  seed?: number | null;
+  /** Deal a board that never needs a guess */
+  no_guess?: boolean;
+  player?: string | null;
+}
+
+export type MinesweeperStatus =
+  | 'playing'
+  | 'won'
+  | 'lost';
+
+export interface MinesweeperView {
+  id: string;
+  difficulty: MinesweeperDifficulty;
+  width: number;
+  height: number;
+  mines: number;
+  seed: number;
+  /** Mines less flags placed */
+  mines_left: number;
+  rows: string[];
+  status: MinesweeperStatus;
+  /** From the first reveal */
+  elapsed_secs: number;
+  /** Whether the board was checked to need no guessing */
+  guaranteed: boolean;
+  /** Where a won game landed on the leaderboard */
+  score: ScoreResult | null;
+}
+
+// mock
+
+/**
+ * Scripted answer for one command. `sequence` is played one call at a time,
+ * repeating its last entry; otherwise `error` or `result` is returned.
+ */
+export interface Fixture {
+  result?: unknown;
+  sequence?: unknown[];
+  error?: string | null;
+  delay_ms?: number;
+}
+
+export interface Fixtures {
+  commands?: Record<string, Fixture>;
+  /** Input values by sysfs pin number */
+  gpio?: Record<string, number>;
+}
+
+export interface MockStatus {
+  enabled: boolean;
+  fixtures: string | null;
+  /** Commands answered from fixtures */
+  commands: string[];
+  /** Why the fixture file could not be read, if it could not */
+  error: string | null;
+}
+
+// modem
+
+export interface ModemConfig {
+  apn: string;
+  username: string;
+  /** Write-only: returned empty, and left unchanged when set empty */
+  password: string;
+  allow_roaming: boolean;
+  /** Dial whenever the modem is registered but not connected */
+  auto_connect: boolean;
+}
+
+/** Counters of the live data connection */
+export interface ModemConnection {
+  /** Network interface the bearer brought up, e.g. `wwan0` */
+  interface: string;
+  rx_bytes: number;
+  tx_bytes: number;
+  duration_secs: number;
+}
+
+/** Payload of `modem-state-changed` */
+export interface ModemStatus {
+  present: boolean;
+  manufacturer: string;
+  model: string;
+  imei: string;
+  /** ModemManager state, e.g. "registered", "connected" */
+  state: string;
+  signal_percent: number;
+  /** Best access technology in use: "2G", "3G", "LTE" or "5G" */
+  technology: string;
+  operator: string;
+  /** "home", "roaming", "searching", "denied", "idle" or "unknown" */
+  registration: string;
+  own_number: string | null;
+  connection: ModemConnection | null;
+}
+
+/** Payload of `sms-received` */
+export interface SmsMessage {
+  /** ModemManager object path, for `delete_sms` */
+  id: string;
+  number: string;
+  text: string;
+  timestamp: string;
+  incoming: boolean;
+}
+
+// monotonic
+
+export interface MonotonicTime {
+  /** Milliseconds on the monotonic clock; only differences are meaningful */
+  monotonic_ms: number;
+  /** Like `monotonic_ms`, but also counting time suspended */
+  boottime_ms: number;
+  /** Wall clock Unix time in milliseconds, read at the same moment */
+  wall_ms: number;
+}
+
+/** Payload of `interval-tick:<id>` */
+export interface IntervalTick {
+  id: string;
+  /** Ticks since the interval started, from 1 */
+  seq: number;
+  monotonic_ms: number;
+  /** Milliseconds since the interval started */
+  elapsed_ms: number;
+  /** Ticks skipped because the previous one ran late */
+  missed: number;
+}
+
+/** Payload of `clock-jumped` */
+export interface ClockJumped {
+  /** How far the wall clock moved, positive when forwards */
+  offset_ms: number;
+  wall_ms: number;
+}
+
+// nightlight
+
+export type ColorSchedule =
+  | { type: 'off' }
+  | { type: 'always' }
+  | { type: 'sun' }
+  | { type: 'times'; start: string; end: string; };
+
+export type ColorFilter =
+  | 'none'
+  | 'grayscale'
+  | 'protanopia'
+  | 'deuteranopia'
+  | 'tritanopia';
+
+export interface ColorSettings {
+  /** Temperature at night */
+  kelvin: number;
+  schedule: ColorSchedule;
+  filter: ColorFilter;
+}
+
+export interface ColorStatus {
+  /** Temperature on screen now */
+  kelvin: number;
+  filter: ColorFilter;
+  /** How far into the night the schedule is, 0 (day) to 1 */
+  night: number;
+  /** What applied it: "hyprland", "ctm", "gammastep", "redshift" */
+  backend: string | null;
+  error: string | null;
+}
+
+// oauth
+
+export interface OAuthProvider {
+  id: string;
+  name: string;
+  device_authorization_url: string;
+  token_url: string;
+  client_id: string;
+  /** Only some providers (e.g. Google) require a secret for device clients */
+  client_secret?: string;
+  scope: string;
+}
+
+/** What the operator needs to approve the kiosk */
+export interface DeviceAuthPrompt {
+  provider: string;
+  user_code: string;
+  verification_uri: string;
+  /** URI with the code embedded, suitable for a QR code */
+  verification_uri_complete: string | null;
+  expires_at: number;
+}
+
+export type DeviceAuthStatus =
+  | 'pending'
+  | 'authorized'
+  | 'denied'
+  | 'expired'
+  | 'cancelled'
+  | 'error';
+
+/** Payload of `oauth-status` */
+export interface OAuthStatusEvent {
+  provider: string;
+  status: DeviceAuthStatus;
+  error: string | null;
+}
+
+/** Provider as listed for the frontend */
+export interface OAuthConnection {
+  provider: OAuthProvider;
+  /** A refresh token is stored */
+  connected: boolean;
+  pending: boolean;
+}
+
+export interface DeviceCodeResponse {
+  device_code: string;
+  user_code: string;
+  /** Google still uses the draft name `verification_url` */
+  verification_uri: string;
+  verification_uri_complete?: string | null;
+  expires_in: number;
+  interval?: number | null;
+}
+
+export interface TokenResponse {
+  access_token: string;
+  expires_in?: number | null;
+  refresh_token?: string | null;
+}
+
+export interface TokenError {
+  error: string;
+  error_description?: string | null;
+}
+
+// overlay
+
+export interface OverlayStatus {
+  /** raspi-config is installed, so the overlay can be switched */
+  supported: boolean;
+  /** The root filesystem is an overlay now */
+  active: boolean;
+  /** The overlay will be on after the next boot */
+  enabled_next_boot: boolean;
+  /** The boot partition is mounted read-only */
+  boot_read_only: boolean;
+  /** The change only happens at the next boot */
+  reboot_pending: boolean;
+}
+
+export interface CommitResult {
+  /** Folders copied to the SD card */
+  committed: string[];
+}
+
+// payments
+
+export type TerminalConnection =
+  | { type: 'tcp'; host: string; port: number; }
+  | { type: 'serial'; path: string; baud_rate: number; };
+
+export type TerminalDriver =
+  | 'unconfigured'
+  | 'zvt'
+  | 'simulator';
+
+export interface PaymentConfig {
+  driver: TerminalDriver;
+  connection: TerminalConnection;
+  /** ISO 4217 numeric currency code, e.g. 978 for EUR */
+  currency_code: number;
+}
+
+export type PaymentStatus =
+  | 'pending'
+  | 'processing'
+  | 'approved'
+  | 'declined'
+  | 'cancelled'
+  | 'error';
+
+/** Progress of a payment, emitted as `payment-status` */
+export interface PaymentUpdate {
+  id: string;
+  /** Amount in minor units (cents) */
+  amount: number;
+  status: PaymentStatus;
+  message: string;
+}
+
+// photoframe
+
+export type PhotoTransition =
+  | 'cut'
+  | 'fade'
+  | 'slide'
+  | 'zoom';
+
+export type PhotoFit =
+  | 'contain'
+  | 'cover';
+
+export interface PhotoFrameConfig {
+  /** Virtual paths of the folders to show, scanned with their subfolders */
+  folders: string[];
+  /** Also show photos on USB drives while they are plugged in */
+  removable: boolean;
+  /** Seconds each photo is shown */
+  interval_secs: number;
+  transition: PhotoTransition;
+  transition_ms: number;
+  shuffle: boolean;
+  fit: PhotoFit;
+  /** Start the photo frame when the kiosk starts */
+  autostart: boolean;
+}
+
+/** Payload of the `show-photo` event */
+export interface PhotoSlide {
+  seq: number;
+  /** The scaled photo, e.g. `photo://localhost/12.jpg` */
+  url: string;
+  /** Virtual path of the original */
+  path: string;
+  name: string;
+  width: number;
+  height: number;
+  index: number;
+  count: number;
+  transition: PhotoTransition;
+  transition_ms: number;
+  interval_secs: number;
+}
+
+export interface PhotoFrameStatus {
+  running: boolean;
+  count: number;
+  current: PhotoSlide | null;
+  error: string | null;
+}
+
+// pointer
+
+export interface PointerSettings {
+  /** Xcursor theme id; the session's own when unset */
+  cursor_theme: string | null;
+  /** Nominal cursor size in pixels */
+  cursor_size: number;
+  /** Pointer speed from -1 (slowest) to 1 (fastest) */
+  speed: number;
+  accel_profile: AccelProfile;
+  /** Longest gap between the clicks of a double-click */
+  double_click_ms: number;
+  /** Swap the primary and secondary buttons */
+  left_handed: boolean;
+  /** Content follows the fingers or wheel, as on a touchscreen */
+  natural_scroll: boolean;
+}
+
+/** How libinput turns device movement into pointer movement */
+export type AccelProfile =
+  | 'adaptive'
+  | 'flat';
+
+export interface CursorTheme {
+  /** Folder name, used to select the theme */
+  id: string;
+  name: string;
+  comment: string | null;
+  path: string;
+  /** Installed from the control panel, so it can be removed */
+  user_installed: boolean;
+}
+
+// printing
+
+/** A CUPS print queue */
+export interface PrinterInfo {
+  name: string;
+  is_default: boolean;
+}
+
+export type PaperSize =
+  | 'letter'
+  | 'a4';
+
+/** Page setup, defaulting to Notepad's */
+export interface PrintOptions {
+  /** CUPS queue; the system default when unset */
+  printer: string | null;
+  /** Document name used for `&f` and the job title */
+  title: string;
+  /**
+   * Header and footer templates: `&f` file, `&p` page, `&d` date, `&t` time,
+   * `&l` `&c` `&r` align the following text, `&&` a literal ampersand
+   */
+  header: string;
+  footer: string;
+  /** Margins in inches */
+  margin_left: number;
+  margin_right: number;
+  margin_top: number;
+  margin_bottom: number;
+  font_family: string;
+  font_size: number;
+  paper: PaperSize;
+  word_wrap: boolean;
+  copies: number;
+}
+
+/** A submitted print job */
+export interface PrintJob {
+  /** CUPS request id, e.g. "Office-42" */
+  job_id: string | null;
+  pages: number;
+}
+
+// proximity
+
+export type ProximitySource =
+  | { type: 'gpio'; pin: number; active_high: boolean; }
+  | { type: 'serial'; port: string; baud: number; threshold: number; };
+
+export interface ProximityConfig {
+  enabled: boolean;
+  source: ProximitySource;
+  /** How long someone must be seen before they count as arrived */
+  approach_ms: number;
+  /** How long no one must be seen before they count as gone */
+  leave_secs: number;
+  wake_screen: boolean;
+  exit_attract: boolean;
+}
+
+/** Payload of `person-approached` */
+export interface PersonApproached {
+  /** Last distance from a range finder, in the sensor's units */
+  distance: number | null;
+}
+
+/** Payload of `person-left` */
+export interface PersonLeft {
+  /** How long they were there */
+  present_secs: number;
+}
+
+export interface ProximityStatus {
+  enabled: boolean;
+  present: boolean;
+  /** Raw sensor state before debouncing */
+  detecting: boolean;
+  distance: number | null;
+  error: string | null;
+}
+
+// queue
+
+/** An issued queue number */
+export interface QueueTicket {
+  /** Display number, e.g. "A012" */
+  number: string;
+  service: string;
+  issued_at: number;
+}
+
+/** The customer currently called to a counter */
+export interface Serving {
+  counter: string;
+  ticket: QueueTicket;
+  called_at: number;
+}
+
+/** Full queue state, persisted and shared with peer kiosks */
+export interface QueueSnapshot {
+  /** Day the counters belong to ("YYYY-MM-DD") */
+  date: string;
+  waiting: QueueTicket[];
+  serving: Serving[];
+  /** Last number issued per service */
+  counters: Record<string, number>;
+  updated_at: number;
+}
+
+// quota
+
+/** Limit in bytes per root id */
+export interface QuotaConfig {
+  limits: Record<string, number>;
+}
+
+export interface QuotaStatus {
+  root: string;
+  name: string;
+  used_bytes: number;
+  limit_bytes: number;
+  exceeded: boolean;
+}
+
+// recents
+
+/** A single recently used document */
+export interface RecentEntry {
+  path: string;
+  name: string;
+  app: string;
+  last_used: number;
+}
+
+// recording
+
+export interface Recording {
+  path: string;
+  size: number;
+  duration_secs: number;
+  /** Why it stopped: "stopped", "time limit", "size limit" or "recorder exited" */
+  reason: string;
+  created_at: number;
+}
+
+export interface RecordingStatus {
+  path: string;
+  elapsed_secs: number;
+  max_duration_secs: number;
+  size: number;
+}
+
+// regional
+
+export interface RegionalSettings {
+  locale: string;
+  formats: RegionalFormats;
+  /** ISO 4217 code of the kiosk's currency, such as `EUR` */
+  currency: string | null;
+  /** The layouts used when nobody is signed in */
+  keyboard: KeyboardSettings;
+}
+
+// reports
+
+export type ReportType =
+  | 'footfall'
+  | 'uptime'
+  | 'data_usage';
+
+export type ReportFileFormat =
+  | 'csv'
+  | 'pdf';
+
+/** Days covered, inclusive ("YYYY-MM-DD"); today when neither is given */
+export interface ReportRange {
+  from: string | null;
+  to: string | null;
+}
+
+export interface ReportEmail {
+  account_id: string;
+  to: string[];
+}
+
+/** Where a finished report goes; any combination */
+export interface ReportDelivery {
+  /** Virtual folder to save the file in */
+  folder: string | null;
+  email: ReportEmail | null;
+  /** URL the file is PUT to; one ending in `/` gets the file name added */
+  upload_url: string | null;
+}
+
+export type ReportFrequency =
+  | 'daily'
+  | 'weekly'
+  | 'monthly';
+
+export interface ReportSchedule {
+  id: string;
+  report: ReportType;
+  format?: ReportFileFormat;
+  frequency: ReportFrequency;
+  /** "HH:MM" the report is made at */
+  time: string;
+  delivery: ReportDelivery;
+  enabled?: boolean;
+}
+
+export interface ReportsConfig {
+  schedules: ReportSchedule[];
+  /** Date each schedule last ran, so a restart does not repeat it */
+  last_runs: Record<string, string>;
+}
+
+export interface GeneratedReport {
+  report: ReportType;
+  format: ReportFileFormat;
+  from: string;
+  to: string;
+  file_name: string;
+  rows: number;
+  /** CSV text, or the PDF as base64 */
+  content: string;
+  /** Virtual path it was saved to */
+  path: string | null;
+  emailed: boolean;
+  uploaded: boolean;
+}
+
+/** Payload of `report-finished` */
+export interface ReportFinished {
+  schedule_id: string;
+  report: ReportType;
+  from: string;
+  to: string;
+  path: string | null;
+  error: string | null;
+}
+
+export interface UptimeDay {
+  running_secs: number;
+  starts: number;
+}
+
+/** A report's contents before it is written out, also handed to templates */
+export interface ReportTable {
+  title: string;
+  report: ReportType;
+  from: string;
+  to: string;
+  generated_at: string;
+  columns: string[];
+  rows: string[][];
+  /** Label and value pairs shown under the table */
+  summary: ([string, string])[];
+}
+
+// reset
+
+export interface ResetToken {
+  token: string;
+  expires_in_secs: number;
+}
+
+/** Payload of `factory-reset-progress` */
+export interface ResetProgress {
+  step: string;
+  index: number;
+  total: number;
+}
+
+// rooms
+
+/** CalDAV connection and opening hours for the room */
+export interface RoomConfig {
+  room_name: string;
+  /** Calendar collection URL, e.g. https://dav.example.com/calendars/room-1/ */
+  calendar_url: string;
+  username: string;
+  password?: string;
+  /** Opening hours as "HH:MM" */
+  open_time: string;
+  close_time: string;
+  slot_minutes: number;
+}
+
+/** A bookable time slot */
+export interface RoomSlot {
+  start: number;
+  end: number;
+  busy: boolean;
+  /** Title of the booking occupying the slot */
+  title: string | null;
+}
+
+/** Availability answer, flagged when served from a stale cache */
+export interface RoomAvailability {
+  room_name: string;
+  slots: RoomSlot[];
+  last_synced: number | null;
+  offline: boolean;
+}
+
+/** Requested booking window */
+export interface SlotRequest {
+  start: number;
+  end: number;
+}
+
+/** Booking details entered on the panel */
+export interface BookingDetails {
+  title: string;
+  organizer?: string;
+}
+
+export interface RoomCache {
+  events: CalendarEvent[];
+  last_synced: number | null;
+  last_error: string | null;
+}
+
+// rules
+
+export type Trigger =
+  | { type: 'usb_inserted'; vendor_id?: string | null; product_id?: string | null; }
+  | { type: 'temperature_above'; celsius: number; sensor?: string | null; }
+  | { type: 'gpio_changed'; pin: number; value: number; }
+  | { type: 'app_crashed'; name?: string | null; }
+  | { type: 'event'; topic: string; field?: string | null; equals?: unknown; };
+
+export type Action =
+  | { type: 'webhook'; url: string; }
+  | { type: 'notification'; title: string; message: string; }
+  | { type: 'script'; program: string; args?: string[]; }
+  | { type: 'reboot' };
+
+export interface Rule {
+  id?: string;
+  name: string;
+  enabled: boolean;
+  trigger: Trigger;
+  actions: Action[];
+  /** Minimum seconds between firings */
+  cooldown_secs?: number;
+}
+
+/** Payload of `rule-fired` */
+export interface RuleFired {
+  rule_id: string;
+  rule_name: string;
+  topic: string;
+  payload: unknown;
+  errors: string[];
+  fired_at: number;
+}
+
+// services
+
+/** Units the applet may control; a trailing `*` matches a prefix */
+export interface ServicePolicy {
+  manageable: string[];
+}
+
+export interface ServiceInfo {
+  name: string;
+  description: string;
+  /** loaded, not-found, masked... */
+  load_state: string;
+  /** active, inactive, failed, activating... */
+  active_state: string;
+  /** running, dead, exited... */
+  sub_state: string;
+  manageable: boolean;
+}
+
+export interface ServiceStatus extends ServiceInfo {
+  /** enabled, disabled, static, masked... */
+  unit_file_state: string;
+  main_pid: number | null;
+  /** Unix time the unit last became active */
+  active_since: number | null;
+}
+
+export type ServiceAction =
+  | 'start'
+  | 'stop'
+  | 'restart'
+  | 'enable'
+  | 'disable';
+
+// session
+
+export interface SessionConfig {
+  /** Seconds of inactivity before an automatic reset; 0 disables it */
+  idle_reset_secs: number;
+}
+
+/** Outcome of one reset step */
+export interface ResetStep {
+  name: string;
+  ok: boolean;
+  error: string | null;
+}
+
+/** Result of a session reset, also the payload of `session-reset` */
+export interface SessionReset {
+  steps: ResetStep[];
+  /** True when triggered by the idle timer */
+  automatic: boolean;
+}
+
+// sip
+
+export type SipTransport =
+  | 'udp'
+  | 'tcp'
+  | 'tls';
+
+export interface SipAccount {
+  /** Address of record, e.g. `sip:helppoint-3@pbx.example.com` */
+  uri: string;
+  /** Authentication user when it differs from the address's user */
+  username?: string | null;
+  /** Blank when read back; an empty password keeps the stored one */
+  password?: string;
+  display_name?: string | null;
+  outbound_proxy?: string | null;
+  transport: SipTransport;
+  /** Seconds between re-registrations */
+  register_interval_secs: number;
+  /** Answer incoming calls straight away, as an intercom does */
+  auto_answer?: boolean;
+}
+
+/**
+ * Which devices call audio uses, as ids from `list_audio_inputs` and
+ * `list_audio_outputs`; the defaults when unset
+ */
+export interface CallAudio {
+  input: string | null;
+  output: string | null;
+}
+
+export interface SipConfig {
+  account: SipAccount | null;
+  /** What `sip_call` dials with no address, and all a visitor may dial */
+  help_desk_uri: string;
+  audio: CallAudio;
+}
+
+export type Registration =
+  | 'unregistered'
+  | 'registering'
+  | 'registered'
+  | 'failed';
+
+export type CallDirection =
+  | 'incoming'
+  | 'outgoing';
+
+export type CallPhase =
+  | 'offered'
+  | 'calling'
+  | 'ringing'
+  | 'established';
+
+export interface CallInfo {
+  id: string;
+  peer: string;
+  direction: CallDirection;
+  phase: CallPhase;
+  started_at: number;
+  answered_at: number | null;
+}
+
+/** Payload of `call-state` */
+export interface SipStatus {
+  registration: Registration;
+  account: string | null;
+  call: CallInfo | null;
+  /** Why the last call ended, e.g. "486 Busy Here" */
+  last_call_ended: string | null;
+  error: string | null;
+}
+
+// solitaire
+
+export interface SolitaireOptions {
+  /** Cards turned per draw, 1 or 3 (default 1) */
+  draw?: number | null;
+  /** Replays a deal; random when unset */
+  seed?: number | null;
+  /** Deal a game that can be won */
+  solvable?: boolean;
+  player?: string | null;
+}
+
+/** A move; piles are 0-6 from the left, suits are `C`, `D`, `H` or `S` */
+export type SolitaireMove =
+  | { type: 'draw' }
+  | { type: 'waste_to_tableau'; pile: number; }
+  | { type: 'waste_to_foundation' }
+  | { type: 'tableau_to_foundation'; pile: number; }
+  | { type: 'tableau_to_tableau'; from: number; to: number; count: number; }
+  | { type: 'foundation_to_tableau'; suit: string; pile: number; };
+
+export interface TableauPile {
+  /** Face-down cards under the face-up ones */
+  hidden: number;
+  /** Face-up cards, bottom first */
+  cards: string[];
+}
+
+export interface SolitaireView {
+  id: string;
+  draw: number;
+  seed: number;
+  /** Cards left in the stock */
+  stock: number;
+  /** Up to the last `draw` cards turned, the playable one last */
+  waste: string[];
+  waste_count: number;
+  /** Top card of each foundation, in `C`, `D`, `H`, `S` order */
+  foundations: (string | null)[];
+  tableau: TableauPile[];
+  score: number;
+  moves: number;
+  can_undo: boolean;
+  won: boolean;
+  elapsed_secs: number;
+  /** Whether the deal was checked to be winnable */
+  guaranteed: boolean;
+  /** Where a won game landed on the leaderboard */
+  result: ScoreResult | null;
+}
+
+// speech
+
+export interface SpeechConfig {
+  /** Vosk model directory; `speech-model` in the data directory when empty */
+  model: string;
+  /** ALSA capture device; the default device when empty */
+  device: string;
+}
+
+/** Payload of `speech-recognized` */
+export interface SpeechRecognized {
+  text: string;
+  /** Whether the text is one of the grammar's phrases */
+  in_grammar: boolean;
+}
+
+export interface SpeechStatus {
+  listening: boolean;
+  /** Phrases recognition is limited to; empty for free dictation */
+  grammar: string[];
+  model_loaded: boolean;
+  error: string | null;
+}
+
+// spellcheck
+
+/** A misspelled word found in checked text */
+export interface Misspelling {
+  word: string;
+  /** Character offset of the word in the checked text */
+  offset: number;
+  suggestions: string[];
+}
+
+/** An installed dictionary */
+export interface DictionaryInfo {
+  lang: string;
+  path: string;
+  user_installed: boolean;
+}
+
+// stats
+
+export interface StatsSample {
+  /** Unix time of the end of the sample */
+  time: number;
+  /** Percent of all cores */
+  cpu: number;
+  /** Percent of RAM in use */
+  memory: number;
+  /** Bytes per second over all interfaces */
+  rx_rate: number;
+  tx_rate: number;
+}
+
+// streams
+
+export type RtspTransport =
+  | 'tcp'
+  | 'udp';
+
+export interface CameraStream {
+  /** Empty to add a new stream */
+  id?: string;
+  name: string;
+  /** `rtsp://`, `rtsps://` or an HLS playlist over `http(s)://`, without credentials */
+  url: string;
+  username?: string | null;
+  /** Blank when read back; an empty password keeps the stored one */
+  password?: string;
+  transport?: RtspTransport;
+  /** Keep a relay running from startup */
+  relay?: boolean;
+}
+
+export type Availability =
+  | 'unknown'
+  | 'online'
+  | 'offline';
+
+/** What a stream carries, from ffprobe */
+export interface StreamInfo {
+  video_codec: string | null;
+  width: number | null;
+  height: number | null;
+  fps: number | null;
+  audio_codec: string | null;
+}
+
+export interface RelayStatus {
+  /** For the player, e.g. `stream://localhost/<id>/index.m3u8` */
+  playlist_url: string;
+  running: boolean;
+  /** Whether the playlist has been written yet */
+  ready: boolean;
+  started_at: number;
+  restarts: number;
+  /** Why it last exited */
+  error: string | null;
+}
+
+export interface StreamStatus {
+  id: string;
+  availability: Availability;
+  info: StreamInfo | null;
+  /** Time to connect and read the stream's headers */
+  latency_ms: number | null;
+  checked_at: number | null;
+  /** When it last went online or offline */
+  since: number | null;
+  error: string | null;
+  relay: RelayStatus | null;
+}
+
+export interface StreamSnapshot {
+  data_url: string;
+  /** Where it was saved, when a path was given */
+  path: string | null;
+  /** Time to connect and decode the first frame */
+  latency_ms: number;
+  taken_at: number;
+}
+
+// structured
+
+export type StructuredFormat =
+  | 'json'
+  | 'yaml';
+
+/** A system file the admin panel may edit */
+export interface ConfigFileEntry {
+  /** Absolute path */
+  path: string;
+  label?: string;
+  /** JSON Schema the contents must pass */
+  schema?: unknown;
+}
+
+export interface ConfigFilesPolicy {
+  files: ConfigFileEntry[];
+}
+
+/** A parse error or schema violation */
+export interface StructuredError {
+  /** JSON Pointer to the offending value; empty for the whole document */
+  pointer: string;
+  message: string;
+  /** 1-based position of a parse error */
+  line: number | null;
+  column: number | null;
+}
+
+export interface StructuredFile {
+  path: string;
+  format: StructuredFormat;
+  /** The text as on disk */
+  content: string;
+  /** The parsed document; null when it does not parse */
+  value: unknown;
+  schema: unknown;
+  errors: StructuredError[];
+}
+
+// supervisor
+
+export interface LaunchSpec {
+  name: string;
+  command: string;
+  args?: string[];
+  cwd?: string | null;
+  env?: Record<string, string>;
+  /** Start the app again when it exits with a failure */
+  auto_restart?: boolean;
+  /** Consecutive automatic restarts before giving up */
+  restart_limit?: number;
+  restart_delay_secs?: number;
+}
+
+export interface AppProcess {
+  id: string;
+  spec: LaunchSpec;
+  pid: number | null;
+  running: boolean;
+  /** Automatic restarts since the last manual start */
+  restarts: number;
+  last_exit_code: number | null;
+  last_signal: number | null;
+  error: string | null;
+  started_at: number | null;
+}
+
+/** Payload of `app-exited` and `app-crashed` */
+export interface AppExit {
+  id: string;
+  name: string;
+  code: number | null;
+  signal: number | null;
+  will_restart: boolean;
+}
+
+// support
+
+export interface SupportBundle {
+  path: string;
+  size: number;
+  files: string[];
+  /** Sections that could not be collected, with the reason */
+  skipped: string[];
+  created_at: number;
+}
+
+// sysreport
+
+export type ReportFormat =
+  | 'json'
+  | 'text'
+  | 'html';
+
+export interface SystemSummary extends HardwareProfile {
+  kernel: string | null;
+  architecture: string | null;
+  boot_time: number;
+  uptime_secs: number;
+}
+
+export interface ProcessorInfo {
+  brand: string;
+  vendor: string;
+  physical_cores: number | null;
+  logical_cores: number;
+  frequency_mhz: number;
+}
+
+/** A memory module from DMI type 17 */
+export interface MemoryModule {
+  locator: string;
+  size: string;
+  kind: string;
+  speed: string;
+  manufacturer: string;
+  part_number: string;
+}
+
+export interface MemoryInfo {
+  total: number;
+  available: number;
+  swap_total: number;
+  swap_used: number;
+  /** Empty when DMI tables are missing (Raspberry Pi) or not readable */
+  modules: MemoryModule[];
+}
+
+export interface NetworkAdapter {
+  name: string;
+  mac_address: string;
+  addresses: string[];
+  total_received: number;
+  total_transmitted: number;
+}
+
+export interface Peripheral {
+  /** usb, input, display or audio */
+  class: string;
+  name: string;
+  detail: string;
+}
+
+export interface SystemReport {
+  generated_at: number;
+  system: SystemSummary;
+  processor: ProcessorInfo;
+  memory: MemoryInfo;
+  disks: DriveInfo[];
+  network: NetworkAdapter[];
+  peripherals: Peripheral[];
+}
+
+// tabular
+
+export type TabularFormat =
+  | 'csv'
+  | 'tsv'
+  | 'xlsx';
+
+export type ColumnType =
+  | 'empty'
+  | 'boolean'
+  | 'integer'
+  | 'number'
+  | 'date'
+  | 'text';
+
+export interface TabularOptions {
+  /** Taken from the file extension when unset */
+  format?: TabularFormat | null;
+  /** CSV field separator; guessed from the first line when unset */
+  delimiter?: string | null;
+  /** Whether the first row names the columns */
+  header?: boolean;
+  /** Worksheet name; the first sheet when unset */
+  sheet?: string | null;
+  /** First data row of the page */
+  offset?: number;
+  limit?: number | null;
+  /** Give every cell as text instead of inferring column types */
+  raw?: boolean;
+}
+
+export interface TabularColumn {
+  name: string;
+  type: ColumnType;
+}
+
+export interface TabularPage {
+  format: TabularFormat;
+  /** Worksheet names; empty for CSV and TSV */
+  sheets: string[];
+  sheet: string | null;
+  columns: TabularColumn[];
+  /** Cells are null when empty */
+  rows: unknown[][];
+  offset: number;
+  /** Data rows in the whole file or sheet */
+  total_rows: number;
+}
+
+/** Rows to write; cells may be strings, numbers, booleans or null */
+export interface TabularData {
+  columns?: string[];
+  rows: unknown[][];
+}
+
+export interface TabularWriteOptions {
+  /** Taken from the file extension when unset */
+  format?: TabularFormat | null;
+  /** CSV field separator; `,` by default */
+  delimiter?: string | null;
+  /** Worksheet name for XLSX */
+  sheet?: string | null;
+}
+
+// tamper
+
+export interface CaseSwitch {
+  /** GPIO input (sysfs numbering) */
+  pin: number;
+  /** Value the pin reads with the case open */
+  open_value: number;
+}
+
+export interface TamperConfig {
+  enabled: boolean;
+  case_switch: CaseSwitch | null;
+  /** Report any USB device plugged in or removed */
+  watch_usb: boolean;
+  /**
+   * Keyboards expected on the unit, as "vendor:product" ids (hex); others
+   * are reported even when `watch_usb` is off
+   */
+  allowed_keyboards: string[];
+  /** Take a photo with `camera` on each finding */
+  capture_photo: boolean;
+  camera: string;
+  /** Lock the screen on each finding (needs a lock PIN) */
+  lock_screen: boolean;
+}
+
+export type TamperKind =
+  | 'case_opened'
+  | 'usb_inserted'
+  | 'usb_removed'
+  | 'keyboard_attached';
+
+/** Payload of `tamper-detected` */
+export interface TamperEvent {
+  time: number;
+  kind: TamperKind;
+  detail: string;
+  /** Photo taken at the time, under the app data directory */
+  photo?: string | null;
+}
+
+// templates
+
+export type TemplateOutput =
+  | 'html'
+  | 'text'
+  | 'pdf';
+
+export interface RenderedTemplate {
+  output: TemplateOutput;
+  /** The document; base64 for PDFs */
+  content: string;
+  /** Virtual path it was saved to, when asked for */
+  path: string | null;
+}
+
+export interface TemplateInfo {
+  /** Path under the templates folder, with `/` separators */
+  name: string;
+  html: boolean;
+  size: number;
+  /** Last modified, RFC 3339 */
+  modified: string | null;
+}
+
+/** Payload of `templates-changed` */
+export interface TemplatesChanged {
+  templates: string[];
+}
+
+// tickers
+
+export type TickerKind =
+  | 'stock'
+  | 'crypto';
+
+/** A symbol shown on the ticker. Crypto symbols are CoinGecko ids ("bitcoin"). */
+export interface TickerSymbol {
+  symbol: string;
+  kind: TickerKind;
+}
+
+export interface TickerConfig {
+  symbols: TickerSymbol[];
+  /** Quote currency for crypto prices */
+  currency: string;
+  poll_seconds: number;
+}
+
+export interface Quote {
+  symbol: string;
+  kind: TickerKind;
+  price: number;
+  change_percent: number | null;
+  currency: string;
+  updated_at: number;
+  /** The last fetch failed and this is the previous value */
+  stale?: boolean;
+}
+
+export interface ChartResponse {
+  chart: ChartBody;
+}
+
+export interface ChartBody {
+  result?: ChartResult[] | null;
+}
+
+export interface ChartResult {
+  meta: ChartMeta;
+}
+
+export interface ChartMeta {
+  regularMarketPrice: number;
+  chartPreviousClose?: number | null;
+  currency?: string | null;
+}
+
+// tickets
+
+export type PrinterConnection =
+  | { type: 'device'; path: string; }
+  | { type: 'tcp'; host: string; port: number; };
+
+export interface TicketPrinterConfig {
+  connection: PrinterConnection;
+  max_retries: number;
+  retry_delay_secs: number;
+}
+
+export type PaperStatus =
+  | 'ok'
+  | 'near_end'
+  | 'out'
+  | 'unknown';
+
+/** Printer state, emitted as `ticket-printer-status` when it changes */
+export interface TicketPrinterStatus {
+  online: boolean;
+  paper: PaperStatus;
+  cover_open: boolean;
+  error: string | null;
+}
+
+export type TicketJobStatus =
+  | 'queued'
+  | 'printed'
+  | 'failed';
+
+export interface TicketJob {
+  id: string;
+  status: TicketJobStatus;
+  attempts: number;
+  created_at: number;
+  next_attempt_at: number;
+  last_error: string | null;
+  /** Rendered ESC/POS bytes */
+  data?: number[];
+}
+
+// timers
+
+export interface Timer {
+  id: string;
+  label: string;
+  duration_secs: number;
+  /** Unix time in milliseconds the timer runs out; `None` while paused */
+  ends_at_ms: number | null;
+  /** Time left, kept up to date while paused */
+  remaining_ms: number;
+}
+
+/** When an alarm repeats; an alarm without one rings once */
+export interface AlarmRecurrence {
+  /** Weekdays ("mon" to "sun"); every day when empty */
+  days: string[];
+  /** Ring again every this many minutes after the alarm time */
+  every_minutes: number | null;
+  /**
+   * Last time of day a repeating alarm rings, "HH:MM"; the end of the
+   * day when unset
+   */
+  until: string | null;
+}
+
+export interface Alarm {
+  id: string;
+  label: string;
+  /** "HH:MM" */
+  time: string;
+  recurrence: AlarmRecurrence | null;
+  enabled: boolean;
+  /** Unix time it next rings */
+  next_at: number | null;
+  /** The next ring is a snooze rather than the schedule */
+  snoozed?: boolean;
+}
+
+/** Payload of `timer-expired` */
+export interface TimerExpired {
+  id: string;
+  label: string;
+  duration_secs: number;
+  /** How long ago it ran out, when that was while the kiosk was off */
+  late_secs: number;
+}
+
+/** Payload of `alarm-fired` */
+export interface AlarmFired {
+  id: string;
+  label: string;
+  /** Unix time it was due */
+  at: number;
+  snoozed: boolean;
+}
+
+export interface Stored {
+  timers: Timer[];
+  alarms: Alarm[];
+}
+
+// totp
+
+export interface TotpConfig {
+  enabled: boolean;
+  /** Role a code signs in with */
+  role: Role;
+  digits: number;
+  period_secs: number;
+  /** Periods either side of now also accepted, for clock drift */
+  skew: number;
+}
+
+/** A new secret for support's authenticator */
+export interface TotpProvisioning {
+  /** Base32, as authenticator apps take it */
+  secret: string;
+  /** `otpauth://` URI for a QR code */
+  uri: string;
+}
+
+// vfs
+
+export type RootKind =
+  | 'desktop'
+  | 'documents'
+  | 'downloads'
+  | 'removable'
+  | 'network'
+  | 'trash';
+
+/** A top-level location shown in My Computer and the folder tree */
+export interface VirtualRoot {
+  /** First segment of virtual paths under this root */
+  id: string;
+  /** Display name, e.g. "My Documents" or "Removable (E:)" */
+  name: string;
+  kind: RootKind;
+  total_space: number | null;
+  available_space: number | null;
+}
+
+export interface FileEntry {
+  name: string;
+  /** Virtual path */
+  path: string;
+  is_dir: boolean;
+  size: number;
+  /** Unix time of the last modification */
+  modified: number | null;
+  hidden: boolean;
+}
+
+/**
+ * Roots one kind of user may reach, by id; `prefix*` and `*` match several.
+ * Roots that can be changed can also be read.
+ */
+export interface ScopeRule {
+  read: string[];
+  write: string[];
+}
+
+export interface FsScopePolicy {
+  /** Walk-up users, with no operator signed in */
+  public: ScopeRule;
+  operator: ScopeRule;
+  supervisor: ScopeRule;
+  admin: ScopeRule;
+}
+
+// wayfinding
+
+export interface Floor {
+  id: string;
+  name: string;
+  level?: number;
+}
+
+/** A point on the walkable graph */
+export interface MapNode {
+  id: string;
+  floor: string;
+  x: number;
+  y: number;
+}
+
+export type EdgeKind =
+  | 'walk'
+  | 'ramp'
+  | 'stairs'
+  | 'escalator'
+  | 'elevator';
+
+export interface MapEdge {
+  from: string;
+  to: string;
+  kind?: EdgeKind;
+  /** Explicit step-free flag; defaults from the edge kind */
+  accessible?: boolean | null;
+  one_way?: boolean;
+  /** Cost override in metres */
+  weight?: number | null;
+}
+
+/** A named destination attached to a graph node */
+export interface Poi {
+  id: string;
+  name: string;
+  category?: string | null;
+  node: string;
+}
+
+export interface MapData {
+  floors: Floor[];
+  nodes: MapNode[];
+  edges: MapEdge[];
+  pois: Poi[];
+  /** Coordinates are longitude/latitude rather than metres */
+  geographic?: boolean;
+}
+
+/** Map summary for the frontend (graph edges omitted) */
+export interface MapInfo {
+  floors: Floor[];
+  nodes: MapNode[];
+  pois: Poi[];
+  geographic: boolean;
+}
+
+export interface RouteOptions {
+  /** Step-free route only */
+  accessible?: boolean;
+}
+
+export interface RouteStep {
+  instruction: string;
+  floor: string;
+  distance: number;
+}
+
+export interface Route {
+  /** Walking distance in metres */
+  distance: number;
+  nodes: MapNode[];
+  steps: RouteStep[];
+}
+
+// weather
+
+export type WeatherUnits =
+  | 'metric'
+  | 'imperial';
+
+/** Location and provider settings */
+export interface WeatherConfig {
+  location_name: string;
+  latitude: number;
+  longitude: number;
+  units: WeatherUnits;
+  /** Forecast endpoint; defaults to the public Open-Meteo API */
+  provider_url: string;
+  refresh_minutes: number;
+  forecast_days: number;
+}
+
+export interface CurrentWeather {
+  temperature: number;
+  feels_like: number;
+  humidity: number;
+  wind_speed: number;
+  /** WMO weather interpretation code */
+  code: number;
+  description: string;
+  is_day: boolean;
+}
+
+export interface DailyForecast {
+  /** Local midnight of the day (unix seconds) */
+  date: number;
+  code: number;
+  description: string;
+  temp_max: number;
+  temp_min: number;
+  precipitation_chance: number | null;
+}
+
+/** Cached weather, flagged stale when the last refresh failed */
+export interface WeatherReport {
+  location_name: string;
+  units: WeatherUnits;
+  current: CurrentWeather;
+  daily: DailyForecast[];
+  fetched_at: number;
+  stale?: boolean;
+}
+
+export interface WeatherCache {
+  report: WeatherReport | null;
+  last_error: string | null;
+}
+
+/** Open-Meteo forecast response (only the fields we request) */
+export interface ForecastResponse {
+  current: ForecastCurrent;
+  daily: ForecastDaily;
+}
+
+export interface ForecastCurrent {
+  temperature_2m: number;
+  apparent_temperature: number;
+  relative_humidity_2m: number;
+  wind_speed_10m: number;
+  weather_code: number;
+  is_day: number;
+}
+
+export interface ForecastDaily {
+  time: number[];
+  weather_code: number[];
+  temperature_2m_max: number[];
+  temperature_2m_min: number[];
+  precipitation_probability_max?: (number | null)[];
+}
+
+// wol
+
+export interface WolConfig {
+  enabled: boolean;
+  /** NIC to enable WoL on; the first wired interface when unset */
+  interface?: string | null;
+}
+
+export interface WolStatus {
+  interface: string;
+  mac: string;
+  /** The NIC supports waking on magic packets */
+  supported: boolean;
+  /** Magic packet wake is on in the NIC right now */
+  enabled: boolean;
+  /** Stored setting, applied again at every start */
+  configured: boolean;
+  /** Why the NIC could not be queried, if it could not */
+  error: string | null;
+}
+
+/** Payload of `wol-sent` */
+export interface WolSent {
+  mac: string;
+  broadcast: string;
+  port: number;
+}
+
+/** Every backend command: its arguments and the value it resolves to */
+export interface Commands {
+  get_accessibility_settings: { args: Record<string, never>; result: AccessibilitySettings };
+  set_accessibility_settings: { args: { settings: AccessibilitySettings }; result: void };
+  get_accessibility_status: { args: Record<string, never>; result: AccessibilityStatus };
+  list_protected_actions: { args: Record<string, never>; result: ProtectedAction[] };
+  get_dual_auth_config: { args: Record<string, never>; result: DualAuthConfig };
+  set_dual_auth_config: { args: { config: DualAuthConfig; approvalId?: string | null }; result: void };
+  request_approval: { args: { action: string }; result: Approval };
+  approve_action: { args: { id: string; username: string; secret: string; provider?: AuthProvider | null }; result: Approval };
+  cancel_approval: { args: { id: string }; result: void };
+  list_pending_approvals: { args: Record<string, never>; result: Approval[] };
+  get_dual_auth_audit: { args: { limit?: number | null }; result: AuditEntry[] };
+  get_attract_config: { args: Record<string, never>; result: AttractConfig };
+  configure_attract_loop: { args: { items: AttractItem[]; idleTimeoutSecs?: number | null; enabled?: boolean | null }; result: void };
+  report_activity: { args: Record<string, never>; result: void };
+  get_attract_state: { args: Record<string, never>; result: AttractStatus };
+  login: { args: { username: string; secret: string; provider?: AuthProvider | null }; result: OperatorSession };
+  logout: { args: Record<string, never>; result: void };
+  get_current_operator: { args: Record<string, never>; result: OperatorSession | null };
+  list_operator_accounts: { args: Record<string, never>; result: OperatorAccount[] };
+  save_operator_account: { args: { account: OperatorAccount; setupToken?: string | null }; result: void };
+  delete_operator_account: { args: { username: string }; result: void };
+  get_ldap_config: { args: Record<string, never>; result: LdapConfig };
+  set_ldap_config: { args: { config: LdapConfig }; result: void };
+  test_av_devices: { args: { camera?: string | null; input?: string | null; output?: string | null }; result: string };
+  backup_to_drive: { args: { mountPoint: string; passphrase?: string | null }; result: string };
+  restore_from_drive: { args: { path: string; passphrase?: string | null }; result: string };
+  preview_badge: { args: { data: BadgeData; format?: BadgeFormat | null }; result: BadgePreview };
+  print_badge: { args: { data: BadgeData }; result: string | null };
+  get_bandwidth_limit: { args: Record<string, never>; result: BandwidthStatus };
+  set_bandwidth_limit: { args: { kbps: number; schedule: BandwidthSchedule }; result: BandwidthStatus };
+  run_benchmark: { args: Record<string, never>; result: string };
+  report_frame_rate: { args: { id: string; frameRate: FrameRate }; result: void };
+  list_benchmark_results: { args: Record<string, never>; result: BenchmarkResult[] };
+  read_bitmap_file: { args: { path: string }; result: BitmapFile };
+  write_bitmap_file: { args: { path: string; images: BitmapSource[]; options?: BitmapWriteOptions | null }; result: number };
+  report_first_paint: { args: Record<string, never>; result: void };
+  get_boot_timeline: { args: Record<string, never>; result: BootTimeline };
+  set_boot_budget: { args: { budgetMs: number }; result: void };
+  get_light_level: { args: Record<string, never>; result: LightLevel };
+  get_brightness_config: { args: Record<string, never>; result: BrightnessConfig };
+  set_brightness_config: { args: { config: BrightnessConfig }; result: void };
+  set_auto_brightness: { args: { enabled: boolean }; result: void };
+  set_brightness: { args: { brightness: number }; result: void };
+  verify_bundle: { args: { path: string; pubkey?: string | null }; result: string };
+  get_bundle_config: { args: Record<string, never>; result: BundleConfig };
+  set_bundle_config: { args: { config: BundleConfig; approvalId?: string | null }; result: void };
+  get_business_hours: { args: Record<string, never>; result: BusinessHours };
+  set_business_hours: { args: { hours: BusinessHours }; result: OpenStatus };
+  is_open_now: { args: Record<string, never>; result: OpenStatus };
+  next_opening: { args: Record<string, never>; result: number | null };
+  get_holidays: { args: { year: number; region?: string | null }; result: HolidayDate[] };
+  list_holiday_regions: { args: Record<string, never>; result: HolidayRegion[] };
+  evaluate_expression: { args: { expr: string; mode: CalcMode }; result: CalcResult };
+  list_events: { args: Record<string, never>; result: CalendarEvent[] };
+  get_events: { args: { from: number; to: number }; result: EventOccurrence[] };
+  create_event: { args: { event: EventInput }; result: CalendarEvent };
+  update_event: { args: { id: string; event: EventInput }; result: CalendarEvent };
+  delete_event: { args: { id: string }; result: void };
+  import_ics: { args: { path: string }; result: number };
+  export_ics: { args: { path: string }; result: number };
+  get_cash_config: { args: Record<string, never>; result: CashConfig };
+  set_cash_config: { args: { config: CashConfig }; result: void };
+  set_cash_accepting: { args: { enabled: boolean }; result: void };
+  get_inserted_amount: { args: Record<string, never>; result: CashSession };
+  set_sale_total: { args: { total: number }; result: CashSession };
+  reset_inserted_amount: { args: Record<string, never>; result: number };
+  accept_escrow: { args: Record<string, never>; result: number | null };
+  return_escrow: { args: Record<string, never>; result: number | null };
+  dispense_change: { args: { amount: number }; result: ChangeResult };
+  tv_power_on: { args: Record<string, never>; result: TvState };
+  tv_power_off: { args: Record<string, never>; result: TvState };
+  set_tv_input: { args: { input: number }; result: TvState };
+  get_tv_state: { args: Record<string, never>; result: TvState };
+  import_certificate: { args: { path: string }; result: CertificateInfo };
+  list_certificates: { args: Record<string, never>; result: CertificateInfo[] };
+  remove_certificate: { args: { id: string }; result: void };
+  set_client_certificate: { args: { id?: string | null }; result: void };
+  get_unicode_blocks: { args: Record<string, never>; result: UnicodeBlock[] };
+  get_glyphs: { args: { block: string; font: string }; result: Glyph[] };
+  hash_file: { args: { path: string; algorithm: HashAlgorithm }; result: string };
+  verify_manifest: { args: { path: string }; result: string };
+  scan_cleanup: { args: Record<string, never>; result: CleanupCategory[] };
+  run_cleanup: { args: { categories: string[] }; result: string };
+  get_cleanup_schedule: { args: Record<string, never>; result: CleanupSchedule };
+  set_cleanup_schedule: { args: { schedule: CleanupSchedule }; result: void };
+  get_runtime_config: { args: Record<string, never>; result: RuntimeConfig };
+  set_feature_flag: { args: { name: string; value: boolean }; result: FeatureFlag[] };
+  list_contacts: { args: Record<string, never>; result: Contact[] };
+  get_contact: { args: { id: string }; result: Contact };
+  create_contact: { args: { contact: ContactInput }; result: Contact };
+  update_contact: { args: { id: string; contact: ContactInput }; result: Contact };
+  delete_contact: { args: { id: string }; result: void };
+  search_contacts: { args: { query: string }; result: Contact[] };
+  import_vcard: { args: { path: string }; result: number };
+  export_vcard: { args: { path: string; ids?: string[] | null }; result: number };
+  get_dbus_control_status: { args: Record<string, never>; result: ControlStatus };
+  get_demo_server_config: { args: Record<string, never>; result: DemoServerConfig };
+  set_demo_server_config: { args: { config: DemoServerConfig }; result: void };
+  get_demo_server_status: { args: Record<string, never>; result: DemoServerStatus };
+  get_device_tree: { args: Record<string, never>; result: DeviceNode };
+  queue_download: { args: { url: string; path?: string | null; sha256?: string | null; background?: boolean | null; maxKbps?: number | null }; result: Download };
+  list_downloads: { args: Record<string, never>; result: Download[] };
+  pause_download: { args: { id: string }; result: Download };
+  resume_download: { args: { id: string }; result: Download };
+  cancel_download: { args: { id: string }; result: void };
+  subscribe_events: { args: { topics: string[]; replay?: boolean | null }; result: EventSubscription };
+  unsubscribe_events: { args: { id: string }; result: void };
+  dump_event_history: { args: { topic?: string | null; limit?: number | null }; result: EventRecord[] };
+  list_feeds: { args: Record<string, never>; result: Feed[] };
+  add_feed: { args: { url: string }; result: Feed };
+  remove_feed: { args: { url: string }; result: void };
+  refresh_feeds: { args: Record<string, never>; result: Feed[] };
+  get_feed_items: { args: { limit?: number | null }; result: FeedItem[] };
+  fetch_url: { args: { request: FetchRequest }; result: FetchResponse };
+  clear_fetch_cookies: { args: { jar?: string | null }; result: void };
+  get_fetch_config: { args: Record<string, never>; result: FetchConfig };
+  set_fetch_config: { args: { config: FetchConfig }; result: void };
+  list_fonts: { args: Record<string, never>; result: FontInfo[] };
+  install_font: { args: { path: string }; result: FontInfo };
+  remove_font: { args: { name: string }; result: number };
+  record_footfall: { args: { count: number }; result: void };
+  get_footfall_stats: { args: { from?: string | null; to?: string | null }; result: FootfallStats };
+  export_footfall_csv: { args: { path: string; from?: string | null; to?: string | null }; result: number };
+  format_number: { args: { value: number; digits?: number | null; locale?: string | null }; result: string };
+  format_currency: { args: { amount: number; currency: string; locale?: string | null }; result: string };
+  format_measurement: { args: { value: number; unit: string; locale?: string | null; digits?: number | null; convert?: boolean | null }; result: Measurement };
+  submit_score: { args: { submission: ScoreSubmission }; result: ScoreResult };
+  get_leaderboard: { args: { game: string; variant?: string | null; limit?: number | null }; result: Leaderboard };
+  clear_leaderboard: { args: { game: string; variant?: string | null }; result: void };
+  save_game_state: { args: { game: string; gameState: unknown; player?: string | null }; result: SavedGame };
+  load_game_state: { args: { game: string; player?: string | null }; result: SavedGame | null };
+  delete_game_state: { args: { game: string; player?: string | null }; result: void };
+  list_help_topics: { args: Record<string, never>; result: HelpTopic[] };
+  search_help: { args: { query: string; limit?: number | null }; result: HelpResult[] };
+  get_schedule: { args: Record<string, never>; result: Schedule };
+  set_schedule: { args: { schedule: Schedule }; result: DisplayState };
+  set_display_override: { args: { on?: boolean | null; until?: number | null }; result: DisplayState };
+  get_display_state: { args: Record<string, never>; result: DisplayState };
+  list_locales: { args: Record<string, never>; result: LocaleInfo[] };
+  get_locale: { args: Record<string, never>; result: string };
+  get_strings: { args: { locale?: string | null }; result: Record<string, string> };
+  set_locale: { args: { locale: string }; result: LocaleInfo };
+  extract_icon: { args: { path: string; size?: number | null }; result: ExtractedIcon };
+  get_job_status: { args: { id: string }; result: JobStatus };
+  cancel_job: { args: { id: string }; result: void };
+  list_jobs: { args: Record<string, never>; result: JobStatus[] };
+  list_keyboard_layouts: { args: Record<string, never>; result: KeyboardLayout[] };
+  get_keyboard_layout: { args: Record<string, never>; result: KeyboardSettings };
+  set_keyboard_layout: { args: { layout: string }; result: KeyboardSettings };
+  next_keyboard_layout: { args: Record<string, never>; result: KeyboardSettings };
+  remove_keyboard_layout: { args: { layout: string }; result: KeyboardSettings };
+  store_secret: { args: { key: string; value: string }; result: void };
+  get_secret: { args: { key: string }; result: string | null };
+  delete_secret: { args: { key: string }; result: void };
+  get_kiosk_id: { args: Record<string, never>; result: string };
+  get_system_stats: { args: Record<string, never>; result: SystemStats };
+  get_hardware_profile: { args: Record<string, never>; result: HardwareProfile };
+  get_datetime: { args: Record<string, never>; result: DateTimeInfo };
+  list_drives: { args: Record<string, never>; result: DriveInfo[] };
+  get_network_stats: { args: Record<string, never>; result: NetworkStats[] };
+  greet: { args: { name: string }; result: string };
+  get_location: { args: Record<string, never>; result: Location };
+  get_geofence_status: { args: Record<string, never>; result: GeofenceStatus };
+  get_location_config: { args: Record<string, never>; result: LocationConfig };
+  set_location_config: { args: { config: LocationConfig }; result: void };
+  lock_screen: { args: Record<string, never>; result: LockStatus };
+  unlock: { args: { pin: string }; result: LockStatus };
+  get_lock_status: { args: Record<string, never>; result: LockStatus };
+  get_lock_config: { args: Record<string, never>; result: LockConfig };
+  set_lock_config: { args: { config: LockConfig }; result: void };
+  set_lock_pin: { args: { pin?: string | null }; result: LockStatus };
+  record_macro: { args: { name: string }; result: void };
+  stop_macro_recording: { args: Record<string, never>; result: MacroInfo };
+  play_macro: { args: { name: string; looped?: boolean | null }; result: void };
+  stop_macro: { args: Record<string, never>; result: void };
+  list_macros: { args: Record<string, never>; result: MacroInfo[] };
+  delete_macro: { args: { name: string }; result: void };
+  get_macro_status: { args: Record<string, never>; result: MacroStatus };
+  start_magnifier: { args: Record<string, never>; result: MagnifierStatus };
+  stop_magnifier: { args: Record<string, never>; result: MagnifierStatus };
+  get_magnifier_status: { args: Record<string, never>; result: MagnifierStatus };
+  get_magnifier_settings: { args: Record<string, never>; result: MagnifierSettings };
+  set_magnifier_settings: { args: { settings: MagnifierSettings }; result: void };
+  set_magnifier_zoom: { args: { zoom: number }; result: number };
+  magnifier_zoom_in: { args: Record<string, never>; result: number };
+  magnifier_zoom_out: { args: Record<string, never>; result: number };
+  list_email_accounts: { args: Record<string, never>; result: EmailAccount[] };
+  save_email_account: { args: { account: EmailAccount }; result: string };
+  delete_email_account: { args: { id: string }; result: void };
+  sync_email: { args: { accountId: string; folders?: string[] | null }; result: SyncSummary };
+  list_email_folders: { args: { accountId: string }; result: string[] };
+  list_messages: { args: { accountId: string; folder: string; offset?: number | null; limit?: number | null }; result: MessageSummary[] };
+  read_message: { args: { accountId: string; folder: string; uid: number }; result: EmailMessage };
+  send_email: { args: { draft: EmailDraft }; result: void };
+  get_data_usage: { args: { from?: string | null; to?: string | null }; result: DataUsage };
+  get_data_cap_status: { args: Record<string, never>; result: DataCapStatus };
+  get_data_usage_config: { args: Record<string, never>; result: DataUsageConfig };
+  set_data_usage_config: { args: { config: DataUsageConfig }; result: DataCapStatus };
+  list_audio_inputs: { args: Record<string, never>; result: AudioDevice[] };
+  list_audio_outputs: { args: Record<string, never>; result: AudioDevice[] };
+  start_recording: { args: { path: string; format: AudioFormat; device?: string | null }; result: AudioRecordingStatus };
+  stop_recording: { args: Record<string, never>; result: AudioRecording };
+  get_audio_recording_status: { args: Record<string, never>; result: AudioRecordingStatus | null };
+  get_command_metrics: { args: Record<string, never>; result: CommandMetrics[] };
+  reset_command_metrics: { args: Record<string, never>; result: void };
+  new_minesweeper: { args: { options?: MinesweeperOptions | null }; result: MinesweeperView };
+  get_minesweeper: { args: { id: string }; result: MinesweeperView };
+  minesweeper_reveal: { args: { id: string; x: number; y: number }; result: MinesweeperView };
+  minesweeper_flag: { args: { id: string; x: number; y: number }; result: MinesweeperView };
+  minesweeper_chord: { args: { id: string; x: number; y: number }; result: MinesweeperView };
+  get_mock_status: { args: Record<string, never>; result: MockStatus };
+  reset_mock_sequences: { args: Record<string, never>; result: void };
+  get_modem_status: { args: Record<string, never>; result: ModemStatus };
+  get_modem_config: { args: Record<string, never>; result: ModemConfig };
+  set_modem_config: { args: { config: ModemConfig }; result: void };
+  connect_modem: { args: Record<string, never>; result: ModemStatus };
+  disconnect_modem: { args: Record<string, never>; result: ModemStatus };
+  send_sms: { args: { number: string; text: string }; result: void };
+  list_sms: { args: Record<string, never>; result: SmsMessage[] };
+  delete_sms: { args: { id: string }; result: void };
+  get_monotonic_time: { args: Record<string, never>; result: MonotonicTime };
+  start_interval: { args: { intervalMs: number }; result: string };
+  stop_interval: { args: { id: string }; result: void };
+  get_color_settings: { args: Record<string, never>; result: ColorSettings };
+  get_color_status: { args: Record<string, never>; result: ColorStatus };
+  set_color_temperature: { args: { kelvin: number; schedule: ColorSchedule }; result: ColorStatus };
+  set_color_filter: { args: { filter: ColorFilter }; result: ColorStatus };
+  list_oauth_providers: { args: Record<string, never>; result: OAuthConnection[] };
+  save_oauth_provider: { args: { provider: OAuthProvider }; result: void };
+  delete_oauth_provider: { args: { id: string }; result: void };
+  start_device_auth: { args: { provider: string }; result: DeviceAuthPrompt };
+  cancel_device_auth: { args: { provider: string }; result: void };
+  get_access_token: { args: { provider: string }; result: string };
+  sign_out_provider: { args: { provider: string }; result: void };
+  get_overlay_status: { args: Record<string, never>; result: OverlayStatus };
+  enable_overlay_fs: { args: Record<string, never>; result: OverlayStatus };
+  disable_overlay_fs: { args: Record<string, never>; result: OverlayStatus };
+  commit_changes: { args: { paths?: string[] | null }; result: CommitResult };
+  get_payment_config: { args: Record<string, never>; result: PaymentConfig };
+  set_payment_config: { args: { config: PaymentConfig }; result: void };
+  start_payment: { args: { amount: number }; result: PaymentUpdate };
+  cancel_payment: { args: Record<string, never>; result: void };
+  get_payment_status: { args: Record<string, never>; result: PaymentUpdate | null };
+  get_photo_frame_config: { args: Record<string, never>; result: PhotoFrameConfig };
+  set_photo_frame_config: { args: { config: PhotoFrameConfig }; result: void };
+  start_photo_frame: { args: Record<string, never>; result: void };
+  stop_photo_frame: { args: Record<string, never>; result: void };
+  next_photo: { args: Record<string, never>; result: void };
+  previous_photo: { args: Record<string, never>; result: void };
+  get_photo_frame_status: { args: Record<string, never>; result: PhotoFrameStatus };
+  list_cursor_themes: { args: Record<string, never>; result: CursorTheme[] };
+  get_pointer_settings: { args: Record<string, never>; result: PointerSettings };
+  set_pointer_settings: { args: { settings: PointerSettings }; result: string[] };
+  install_cursor_theme: { args: { folder: string; name?: string | null }; result: CursorTheme };
+  remove_cursor_theme: { args: { id: string }; result: void };
+  list_printers: { args: Record<string, never>; result: PrinterInfo[] };
+  print_text: { args: { content: string; options?: PrintOptions | null }; result: PrintJob };
+  get_proximity_config: { args: Record<string, never>; result: ProximityConfig };
+  set_proximity_config: { args: { config: ProximityConfig }; result: void };
+  get_proximity_state: { args: Record<string, never>; result: ProximityStatus };
+  take_number: { args: { service: string }; result: QueueTicket };
+  call_next: { args: { counter: string; service?: string | null }; result: Serving | null };
+  get_queue_state: { args: Record<string, never>; result: QueueSnapshot };
+  reset_queue: { args: Record<string, never>; result: void };
+  get_quota_status: { args: Record<string, never>; result: QuotaStatus[] };
+  get_quota_config: { args: Record<string, never>; result: QuotaConfig };
+  set_quota: { args: { root: string; limitBytes?: number | null }; result: void };
+  add_recent: { args: { app: string; path: string }; result: void };
+  get_recents: { args: { app?: string | null; limit?: number | null }; result: RecentEntry[] };
+  clear_recents: { args: { app?: string | null }; result: void };
+  start_screen_recording: { args: { maxDuration?: number | null }; result: RecordingStatus };
+  stop_screen_recording: { args: Record<string, never>; result: Recording };
+  get_screen_recording_status: { args: Record<string, never>; result: RecordingStatus | null };
+  get_regional_settings: { args: { locale?: string | null }; result: RegionalSettings };
+  set_regional_settings: { args: { settings: RegionalSettings }; result: RegionalSettings };
+  generate_report: { args: { report: ReportType; range?: ReportRange | null; format?: ReportFileFormat | null; delivery?: ReportDelivery | null }; result: GeneratedReport };
+  get_report_schedules: { args: Record<string, never>; result: ReportSchedule[] };
+  set_report_schedules: { args: { schedules: ReportSchedule[] }; result: void };
+  request_factory_reset: { args: Record<string, never>; result: ResetToken };
+  factory_reset: { args: { confirmToken: string; approvalId?: string | null }; result: string };
+  get_room_config: { args: Record<string, never>; result: RoomConfig };
+  set_room_config: { args: { config: RoomConfig }; result: void };
+  sync_room_calendar: { args: Record<string, never>; result: number };
+  get_room_availability: { args: { from: number; to: number }; result: RoomAvailability };
+  book_slot: { args: { slot: SlotRequest; details: BookingDetails }; result: CalendarEvent };
+  list_rules: { args: Record<string, never>; result: Rule[] };
+  save_rule: { args: { rule: Rule }; result: string };
+  delete_rule: { args: { id: string }; result: void };
+  test_rule: { args: { id: string }; result: void };
+  get_rule_scripts: { args: Record<string, never>; result: string[] };
+  set_rule_scripts: { args: { programs: string[] }; result: void };
+  list_services: { args: Record<string, never>; result: ServiceInfo[] };
+  get_service_status: { args: { name: string }; result: ServiceStatus };
+  start_service: { args: { name: string }; result: void };
+  stop_service: { args: { name: string }; result: void };
+  restart_service: { args: { name: string }; result: void };
+  enable_service: { args: { name: string }; result: void };
+  disable_service: { args: { name: string }; result: void };
+  get_service_policy: { args: Record<string, never>; result: ServicePolicy };
+  set_service_policy: { args: { policy: ServicePolicy }; result: void };
+  reset_session: { args: Record<string, never>; result: SessionReset };
+  get_session_config: { args: Record<string, never>; result: SessionConfig };
+  set_session_config: { args: { config: SessionConfig }; result: void };
+  get_sip_config: { args: Record<string, never>; result: SipConfig };
+  get_sip_status: { args: Record<string, never>; result: SipStatus };
+  sip_register: { args: { account: SipAccount }; result: void };
+  sip_unregister: { args: Record<string, never>; result: void };
+  set_help_desk: { args: { uri: string }; result: void };
+  set_call_audio: { args: { audio: CallAudio }; result: void };
+  sip_call: { args: { uri?: string | null }; result: void };
+  sip_answer: { args: Record<string, never>; result: void };
+  sip_hangup: { args: Record<string, never>; result: void };
+  new_solitaire: { args: { options?: SolitaireOptions | null }; result: SolitaireView };
+  get_solitaire: { args: { id: string }; result: SolitaireView };
+  solitaire_move: { args: { id: string; play: SolitaireMove }; result: SolitaireView };
+  solitaire_undo: { args: { id: string }; result: SolitaireView };
+  start_listening: { args: { grammar?: string[] | null }; result: SpeechStatus };
+  stop_listening: { args: Record<string, never>; result: void };
+  get_speech_status: { args: Record<string, never>; result: SpeechStatus };
+  get_speech_config: { args: Record<string, never>; result: SpeechConfig };
+  set_speech_config: { args: { config: SpeechConfig }; result: void };
+  spellcheck: { args: { text: string; lang: string }; result: Misspelling[] };
+  suggest: { args: { word: string; lang: string }; result: string[] };
+  list_dictionaries: { args: Record<string, never>; result: DictionaryInfo[] };
+  install_dictionary: { args: { lang: string }; result: DictionaryInfo };
+  get_stats_history: { args: { window: number; resolution: number }; result: StatsSample[] };
+  list_streams: { args: Record<string, never>; result: CameraStream[] };
+  save_stream: { args: { stream: CameraStream }; result: string };
+  delete_stream: { args: { id: string }; result: void };
+  get_stream_status: { args: Record<string, never>; result: StreamStatus[] };
+  probe_stream: { args: { id: string }; result: StreamStatus };
+  stream_snapshot: { args: { id: string; path?: string | null }; result: StreamSnapshot };
+  start_stream_relay: { args: { id: string }; result: RelayStatus };
+  stop_stream_relay: { args: { id: string }; result: void };
+  read_structured_file: { args: { path: string }; result: StructuredFile };
+  validate_structured_file: { args: { path: string; content: string }; result: StructuredError[] };
+  write_structured_file: { args: { path: string; content: string }; result: StructuredFile };
+  get_config_files: { args: Record<string, never>; result: ConfigFilesPolicy };
+  set_config_files: { args: { files: ConfigFileEntry[] }; result: void };
+  launch_app: { args: { spec: LaunchSpec }; result: string };
+  list_apps: { args: Record<string, never>; result: AppProcess[] };
+  stop_app: { args: { id: string }; result: void };
+  restart_app: { args: { id: string }; result: void };
+  set_app_auto_restart: { args: { id: string; enabled: boolean }; result: void };
+  remove_app: { args: { id: string }; result: void };
+  get_app_programs: { args: Record<string, never>; result: string[] };
+  set_app_programs: { args: { programs: string[] }; result: void };
+  get_app_log: { args: { id: string; maxBytes?: number | null }; result: string };
+  generate_support_bundle: { args: { destination?: string | null }; result: string };
+  get_system_report: { args: { format: ReportFormat }; result: string };
+  parse_tabular_file: { args: { path: string; options?: TabularOptions | null }; result: TabularPage };
+  write_tabular_file: { args: { path: string; data: TabularData; options?: TabularWriteOptions | null }; result: number };
+  get_tamper_config: { args: Record<string, never>; result: TamperConfig };
+  set_tamper_config: { args: { config: TamperConfig }; result: void };
+  list_tamper_events: { args: { limit?: number | null }; result: TamperEvent[] };
+  clear_tamper_events: { args: Record<string, never>; result: void };
+  list_templates: { args: Record<string, never>; result: TemplateInfo[] };
+  render_template: { args: { name: string; data: unknown; output?: TemplateOutput | null; path?: string | null }; result: RenderedTemplate };
+  get_quotes: { args: Record<string, never>; result: Quote[] };
+  get_ticker_config: { args: Record<string, never>; result: TickerConfig };
+  set_ticker_config: { args: { config: TickerConfig }; result: void };
+  print_ticket: { args: { template: string; data: Record<string, string> }; result: TicketJob };
+  list_ticket_jobs: { args: Record<string, never>; result: TicketJob[] };
+  retry_ticket_job: { args: { id: string }; result: void };
+  cancel_ticket_job: { args: { id: string }; result: void };
+  get_ticket_printer_status: { args: Record<string, never>; result: TicketPrinterStatus };
+  get_ticket_printer_config: { args: Record<string, never>; result: TicketPrinterConfig };
+  set_ticket_printer_config: { args: { config: TicketPrinterConfig }; result: void };
+  create_timer: { args: { durationSecs: number; label?: string | null }; result: Timer };
+  list_timers: { args: Record<string, never>; result: Timer[] };
+  set_timer_paused: { args: { id: string; paused: boolean }; result: Timer };
+  cancel_timer: { args: { id: string }; result: void };
+  create_alarm: { args: { time: string; recurrence?: AlarmRecurrence | null; label?: string | null }; result: Alarm };
+  list_alarms: { args: Record<string, never>; result: Alarm[] };
+  set_alarm_enabled: { args: { id: string; enabled: boolean }; result: Alarm };
+  snooze_alarm: { args: { id: string; minutes?: number | null }; result: Alarm };
+  delete_alarm: { args: { id: string }; result: void };
+  get_totp_config: { args: Record<string, never>; result: TotpConfig };
+  set_totp_config: { args: { config: TotpConfig }; result: void };
+  provision_totp: { args: { secret?: string | null }; result: TotpProvisioning };
+  clear_totp: { args: Record<string, never>; result: void };
+  list_virtual_roots: { args: Record<string, never>; result: VirtualRoot[] };
+  read_directory: { args: { path: string }; result: FileEntry[] };
+  read_text_file: { args: { path: string }; result: string };
+  write_text_file: { args: { path: string; contents: string }; result: void };
+  create_directory: { args: { path: string }; result: void };
+  copy_path: { args: { source: string; destination: string }; result: string };
+  rename_path: { args: { source: string; destination: string }; result: string };
+  delete_path: { args: { path: string; permanent?: boolean | null }; result: string };
+  get_fs_scope: { args: Record<string, never>; result: FsScopePolicy };
+  set_fs_scope: { args: { policy: FsScopePolicy }; result: void };
+  load_map_bundle: { args: { path: string }; result: MapInfo };
+  get_map_info: { args: Record<string, never>; result: MapInfo };
+  find_route: { args: { from: string; to: string; options?: RouteOptions | null }; result: Route };
+  get_weather: { args: Record<string, never>; result: WeatherReport };
+  refresh_weather: { args: Record<string, never>; result: WeatherReport };
+  get_weather_config: { args: Record<string, never>; result: WeatherConfig };
+  set_weather_config: { args: { config: WeatherConfig }; result: void };
+  send_wol: { args: { mac: string; broadcast?: string | null; port?: number | null }; result: WolSent };
+  get_wol_status: { args: { interface?: string | null }; result: WolStatus };
+  set_wol_enabled: { args: { enabled: boolean; interface?: string | null }; result: WolStatus };
+}
+
+/** Every event topic the backend publishes, with its payload */
+export interface Events {
+  'accessibility-changed': AccessibilitySettings;
+  'alarm-fired': AlarmFired;
+  'app-crashed': AppExit;
+  'app-exited': AppExit;
+  'app-started': unknown;
+  'attract-state': AttractStatus;
+  'audio-level': AudioLevel;
+  'audio-recording-finished': AudioRecording;
+  'audio-recording-started': AudioRecordingStatus;
+  'backup-created': BackupResult;
+  'backup-restored': RestoreResult;
+  'bandwidth-limit-changed': BandwidthLimitChanged;
+  'benchmark-started': string;
+  'boot-regression': unknown;
+  'brightness-changed': BrightnessChanged;
+  'business-open-changed': BusinessOpenChanged;
+  'calendar-reminder': ReminderPayload;
+  'call-state': SipStatus;
+  'cash-escrow': unknown;
+  'cash-inserted': CashInserted;
+  'cleanup-finished': CleanupResult;
+  'cleanup-progress': CleanupProgress;
+  'clock-jumped': ClockJumped;
+  'color-changed': unknown;
+  'command-slow': unknown;
+  'data-cap-exceeded': DataCapStatus;
+  'data-cap-warning': DataCapStatus;
+  'display-override': DisplayOverride;
+  'display-power': DisplayPower;
+  'download-finished': Download;
+  'download-progress': Download;
+  'dual-auth-audit': AuditEntry;
+  'factory-reset-progress': ResetProgress;
+  'feature-flag-changed': unknown;
+  'fs-changed': unknown;
+  'game-high-score': GameHighScore;
+  'geofence-left': GeofenceCrossed;
+  'geofence-returned': GeofenceCrossed;
+  'gpio-changed': unknown;
+  'hash-progress': HashProgress;
+  'interval-tick:<id>': IntervalTick;
+  'job-finished': JobStatus;
+  'job-progress': JobStatus;
+  'keyboard-layout-changed': LayoutChanged;
+  'kiosk-message': KioskMessage;
+  'lan-message': LanMessage;
+  'locale-changed': LocaleChanged;
+  'location-changed': Location;
+  'macro-finished': MacroFinished;
+  'macro-playing': string;
+  'macro-recording': string;
+  'macro-saved': MacroInfo;
+  'magnifier-changed': unknown;
+  'modem-state-changed': ModemStatus;
+  'oauth-status': OAuthStatusEvent;
+  'operator-changed': OperatorSession | null;
+  'overlay-changed': unknown;
+  'overlay-committed': string[];
+  'payment-status': PaymentUpdate;
+  'person-approached': PersonApproached;
+  'person-left': PersonLeft;
+  'photo-frame-stopped': unknown;
+  'queue-updated': unknown;
+  'quota-exceeded': unknown;
+  'quotes-updated': unknown;
+  'report-finished': ReportFinished;
+  'room-calendar-synced': unknown;
+  'rule-fired': RuleFired;
+  'rule-notification': unknown;
+  'screen-locked': ScreenLocked;
+  'screen-recording-finished': Recording;
+  'screen-recording-started': RecordingStatus;
+  'screen-unlocked': unknown;
+  'service-changed': unknown;
+  'session-reset': SessionReset;
+  'show-photo': PhotoSlide;
+  'sms-received': SmsMessage;
+  'speech-recognized': SpeechRecognized;
+  'sticky-keys': StickyModifiers;
+  'stream-status': StreamStatus;
+  'subsystem-started': unknown;
+  'tamper-detected': TamperEvent;
+  'templates-changed': TemplatesChanged;
+  'ticket-job': TicketJob;
+  'ticket-printer-status': TicketPrinterStatus;
+  'timer-expired': TimerExpired;
+  'toggle-key': unknown;
+  'tv-state': TvState;
+  'unlock-failed': unknown;
+  'usb-inserted': unknown;
+  'usb-removed': unknown;
+  'weather-updated': unknown;
+  'wol-changed': unknown;
+  'wol-sent': WolSent;
+}
@@ -4,40 +4,34 @@
  */
 
 // ============================================================================
-// System Types (from Rust backend)
+// Backend Types
 // ============================================================================
 
-export interface SystemStats {
-  cpu_usage: number;
-  total_memory: number;
-  used_memory: number;
-  available_memory: number;
-  cpu_count: number;
-}
+// Every Serde type, and the Commands and Events maps, are generated from the
+// backend sources at build time (src-tauri/build/tsgen.rs); only types with
+// no Rust counterpart are written here.
+export type * from './generated';
 
-export interface HardwareProfile {
-  model: string;
-  ram_mb: number;
-  os_name: string;
-  os_version: string;
-  hostname: string;
-}
+// ============================================================================
+// Downloads Types
+// ============================================================================
 
-export interface DateTimeInfo {
-  time_12h: string;
-  time_24h: string;
-  date_short: string;
-  date_long: string;
-  day_of_week: string;
-  timestamp: number;
+export interface QueueDownloadOptions {
+  /** Virtual path; defaults to Downloads under the URL's file name */
+  path?: string;
+  sha256?: string;
+  background?: boolean;
+  maxKbps?: number;
 }
 
-export interface DriveInfo {
-  name: string;
-  mount_point: string;
-  total_space: number;
-  available_space: number;
-  is_removable: boolean;
+// ============================================================================
+// Accessibility Types
+// ============================================================================
+
+/** Payload of `toggle-key` */
+export interface ToggleKey {
+  key: 'caps_lock' | 'num_lock' | 'scroll_lock';
+  on: boolean;
 }
 
 // ============================================================================
@@ -112,15 +106,6 @@ export type MenuAction = string;
 // TODO: Future Types
 // ============================================================================
 
-// TODO: Add file browser types
-// export interface FileEntry {
-//   name: string;
-//   path: string;
-//   isDirectory: boolean;
-//   size: number;
-//   modified: number;
-// }
-
 // TODO: Add terminal types
 // export interface TerminalSession {
 //   id: string;
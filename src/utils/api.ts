@@ -3,8 +3,224 @@
  * Wrapper around Tauri invoke commands
  */
 
-import { invoke } from '@tauri-apps/api/core';
-import type { SystemStats, HardwareProfile, DateTimeInfo, DriveInfo } from '../types';
+import { convertFileSrc, invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type {
+  SystemStats,
+  HardwareProfile,
+  DateTimeInfo,
+  DriveInfo,
+  NetworkStats,
+  RecentEntry,
+  FontInfo,
+  UnicodeBlock,
+  Glyph,
+  Misspelling,
+  DictionaryInfo,
+  CalcMode,
+  CalcResult,
+  ContactInput,
+  Contact,
+  EmailAccount,
+  MessageSummary,
+  EmailMessage,
+  EmailDraft,
+  SyncSummary,
+  CalendarEvent,
+  EventInput,
+  EventOccurrence,
+  RoomConfig,
+  RoomAvailability,
+  SlotRequest,
+  BookingDetails,
+  Feed,
+  FeedItem,
+  WeatherConfig,
+  WeatherReport,
+  TickerConfig,
+  Quote,
+  HelpTopic,
+  HelpResult,
+  PrinterInfo,
+  PrintOptions,
+  PrintJob,
+  BadgeData,
+  BadgeFormat,
+  BadgePreview,
+  PaymentConfig,
+  PaymentUpdate,
+  CashConfig,
+  CashSession,
+  ChangeResult,
+  TicketPrinterConfig,
+  TicketPrinterStatus,
+  TicketJob,
+  QueueTicket,
+  Serving,
+  QueueSnapshot,
+  MapInfo,
+  RouteOptions,
+  Route,
+  AttractItem,
+  AttractConfig,
+  AttractStatus,
+  SessionConfig,
+  SessionReset,
+  CleanupCategory,
+  CleanupSchedule,
+  HashAlgorithm,
+  BundleConfig,
+  CertificateInfo,
+  AuthProvider,
+  OperatorAccount,
+  OperatorSession,
+  LdapConfig,
+  OAuthProvider,
+  OAuthConnection,
+  DeviceAuthPrompt,
+  Rule,
+  EventRecord,
+  EventSubscription,
+  KioskError,
+  CommandMetrics,
+  JobStatus,
+  FrameRate,
+  BenchmarkResult,
+  StatsSample,
+  LaunchSpec,
+  AppProcess,
+  RuntimeConfig,
+  FeatureFlag,
+  ReportFormat,
+  DeviceNode,
+  ServiceInfo,
+  ServiceStatus,
+  ServicePolicy,
+  BootTimeline,
+  VirtualRoot,
+  FileEntry,
+  FsScopePolicy,
+  QuotaConfig,
+  QuotaStatus,
+  OverlayStatus,
+  CommitResult,
+  ResetToken,
+  Recording,
+  RecordingStatus,
+  MacroInfo,
+  MacroStatus,
+  Commands,
+  Events,
+  MockStatus,
+  ControlStatus,
+  WolStatus,
+  WolSent,
+  TvState,
+  Schedule,
+  DisplayState,
+  LightLevel,
+  BrightnessConfig,
+  ProximityConfig,
+  ProximityStatus,
+  FootfallStats,
+  SpeechConfig,
+  SpeechStatus,
+  LocaleInfo,
+  Measurement,
+  BusinessHours,
+  HolidayDate,
+  HolidayRegion,
+  OpenStatus,
+  Timer,
+  AlarmRecurrence,
+  Alarm,
+  MonotonicTime,
+  LockConfig,
+  LockStatus,
+  DualAuthConfig,
+  ProtectedAction,
+  Approval,
+  AuditEntry,
+  TotpConfig,
+  TotpProvisioning,
+  TamperConfig,
+  TamperEvent,
+  Location,
+  GeofenceStatus,
+  LocationConfig,
+  ModemConfig,
+  ModemStatus,
+  SmsMessage,
+  DataUsage,
+  DataCapStatus,
+  DataUsageConfig,
+  Download,
+  QueueDownloadOptions,
+  BandwidthSchedule,
+  BandwidthStatus,
+  FetchRequest,
+  FetchResponse,
+  FetchConfig,
+  DemoServerConfig,
+  DemoServerStatus,
+  TemplateOutput,
+  RenderedTemplate,
+  TemplateInfo,
+  ReportType,
+  ReportFileFormat,
+  ReportRange,
+  ReportDelivery,
+  ReportSchedule,
+  GeneratedReport,
+  TabularOptions,
+  TabularPage,
+  TabularData,
+  TabularWriteOptions,
+  StructuredFile,
+  StructuredError,
+  ConfigFilesPolicy,
+  ConfigFileEntry,
+  ScoreSubmission,
+  ScoreResult,
+  Leaderboard,
+  SavedGame,
+  MinesweeperOptions,
+  MinesweeperView,
+  SolitaireOptions,
+  SolitaireMove,
+  SolitaireView,
+  BitmapFile,
+  BitmapSource,
+  BitmapWriteOptions,
+  ExtractedIcon,
+  PointerSettings,
+  CursorTheme,
+  KeyboardLayout,
+  KeyboardSettings,
+  RegionalSettings,
+  AccessibilitySettings,
+  AccessibilityStatus,
+  MagnifierSettings,
+  MagnifierStatus,
+  ColorSettings,
+  ColorStatus,
+  ColorSchedule,
+  ColorFilter,
+  AudioDevice,
+  AudioFormat,
+  AudioRecording,
+  AudioRecordingStatus,
+  SipAccount,
+  SipConfig,
+  SipStatus,
+  CallAudio,
+  CameraStream,
+  StreamStatus,
+  StreamSnapshot,
+  RelayStatus,
+  PhotoFrameConfig,
+  PhotoFrameStatus,
+} from '../types';
 
 // ============================================================================
 // System Commands
@@ -38,11 +254,3313 @@ export async function listDrives(): Promise<DriveInfo[]> {
   return invoke<DriveInfo[]>('list_drives');
 }
 
+/**
+ * Get per-interface network traffic counters
+ */
+export async function getNetworkStats(): Promise<NetworkStats[]> {
+  return invoke<NetworkStats[]>('get_network_stats');
+}
+
 /**
  * Test backend connectivity
  */
-export async function greet(name: string): Promise<string> {
-  return invoke<string>('greet', { name });
+export async function greet(name: string): Promise<string> {
+  return invoke<string>('greet', { name });
+}
+
+// ============================================================================
+// Recent Documents
+// ============================================================================
+
+/**
+ * Record a document opened or saved by an app
+ */
+export async function addRecent(app: string, path: string): Promise<void> {
+  return invoke<void>('add_recent', { app, path });
+}
+
+/**
+ * Get recent documents, optionally limited to one app's jump list
+ */
+export async function getRecents(app?: string, limit?: number): Promise<RecentEntry[]> {
+  return invoke<RecentEntry[]>('get_recents', { app, limit });
+}
+
+/**
+ * Clear recent documents for one app, or all of them
+ */
+export async function clearRecents(app?: string): Promise<void> {
+  return invoke<void>('clear_recents', { app });
+}
+
+// ============================================================================
+// Fonts
+// ============================================================================
+
+/**
+ * List installed font faces
+ */
+export async function listFonts(): Promise<FontInfo[]> {
+  return invoke<FontInfo[]>('list_fonts');
+}
+
+/**
+ * Install a font file for the kiosk user
+ */
+export async function installFont(path: string): Promise<FontInfo> {
+  return invoke<FontInfo>('install_font', { path });
+}
+
+/**
+ * Remove a user-installed font family, returning the number of files deleted
+ */
+export async function removeFont(name: string): Promise<number> {
+  return invoke<number>('remove_font', { name });
+}
+
+// ============================================================================
+// Character Map
+// ============================================================================
+
+/**
+ * List Unicode blocks for the Character Map subset dropdown
+ */
+export async function getUnicodeBlocks(): Promise<UnicodeBlock[]> {
+  return invoke<UnicodeBlock[]>('get_unicode_blocks');
+}
+
+/**
+ * Get the glyphs in a block that a font actually covers
+ */
+export async function getGlyphs(block: string, font: string): Promise<Glyph[]> {
+  return invoke<Glyph[]>('get_glyphs', { block, font });
+}
+
+// ============================================================================
+// Spell Check
+// ============================================================================
+
+/**
+ * Spell check text, returning misspelled words with their character offsets
+ */
+export async function spellcheck(text: string, lang: string): Promise<Misspelling[]> {
+  return invoke<Misspelling[]>('spellcheck', { text, lang });
+}
+
+/**
+ * Get spelling suggestions for a word
+ */
+export async function suggest(word: string, lang: string): Promise<string[]> {
+  return invoke<string[]>('suggest', { word, lang });
+}
+
+/**
+ * List installed spell-check dictionaries
+ */
+export async function listDictionaries(): Promise<DictionaryInfo[]> {
+  return invoke<DictionaryInfo[]>('list_dictionaries');
+}
+
+/**
+ * Download and install a dictionary (e.g. 'en_GB')
+ */
+export async function installDictionary(lang: string): Promise<DictionaryInfo> {
+  return invoke<DictionaryInfo>('install_dictionary', { lang });
+}
+
+// ============================================================================
+// Calculator
+// ============================================================================
+
+/**
+ * Evaluate a calculator expression (e.g. 'sqrt(2)', '0xFF & 0b1010', '12 km to mi')
+ */
+export async function evaluateExpression(expr: string, mode: CalcMode): Promise<CalcResult> {
+  return invoke<CalcResult>('evaluate_expression', { expr, mode });
+}
+
+// ============================================================================
+// Address Book
+// ============================================================================
+
+/**
+ * List all contacts sorted by display name
+ */
+export async function listContacts(): Promise<Contact[]> {
+  return invoke<Contact[]>('list_contacts');
+}
+
+/**
+ * Get a single contact
+ */
+export async function getContact(id: string): Promise<Contact> {
+  return invoke<Contact>('get_contact', { id });
+}
+
+/**
+ * Create a contact
+ */
+export async function createContact(contact: ContactInput): Promise<Contact> {
+  return invoke<Contact>('create_contact', { contact });
+}
+
+/**
+ * Replace a contact's fields
+ */
+export async function updateContact(id: string, contact: ContactInput): Promise<Contact> {
+  return invoke<Contact>('update_contact', { id, contact });
+}
+
+/**
+ * Delete a contact
+ */
+export async function deleteContact(id: string): Promise<void> {
+  return invoke<void>('delete_contact', { id });
+}
+
+/**
+ * Search contacts by name, company, email, or phone
+ */
+export async function searchContacts(query: string): Promise<Contact[]> {
+  return invoke<Contact[]>('search_contacts', { query });
+}
+
+/**
+ * Import contacts from a .vcf file, returning the number added
+ */
+export async function importVcard(path: string): Promise<number> {
+  return invoke<number>('import_vcard', { path });
+}
+
+/**
+ * Export contacts (all, or the given ids) to a .vcf file
+ */
+export async function exportVcard(path: string, ids?: string[]): Promise<number> {
+  return invoke<number>('export_vcard', { path, ids });
+}
+
+// ============================================================================
+// Email
+// ============================================================================
+
+/**
+ * List configured email accounts (passwords are blanked)
+ */
+export async function listEmailAccounts(): Promise<EmailAccount[]> {
+  return invoke<EmailAccount[]>('list_email_accounts');
+}
+
+/**
+ * Create or update an email account, returning its id (admin). A blank
+ * password keeps the stored one unless a server, port or the username
+ * changed. Plaintext (`none`) is only allowed to localhost.
+ */
+export async function saveEmailAccount(account: EmailAccount): Promise<string> {
+  return invoke<string>('save_email_account', { account });
+}
+
+/**
+ * Remove an email account and its local cache (admin)
+ */
+export async function deleteEmailAccount(id: string): Promise<void> {
+  return invoke<void>('delete_email_account', { id });
+}
+
+/**
+ * Sync folders and recent headers from the IMAP server (defaults to INBOX)
+ */
+export async function syncEmail(accountId: string, folders?: string[]): Promise<SyncSummary> {
+  return invoke<SyncSummary>('sync_email', { accountId, folders });
+}
+
+/**
+ * Get cached folder names for an account
+ */
+export async function listEmailFolders(accountId: string): Promise<string[]> {
+  return invoke<string[]>('list_email_folders', { accountId });
+}
+
+/**
+ * Get the cached message list for a folder
+ */
+export async function listMessages(
+  accountId: string,
+  folder: string,
+  offset?: number,
+  limit?: number
+): Promise<MessageSummary[]> {
+  return invoke<MessageSummary[]>('list_messages', { accountId, folder, offset, limit });
+}
+
+/**
+ * Read a message, downloading it on first open
+ */
+export async function readMessage(accountId: string, folder: string, uid: number): Promise<EmailMessage> {
+  return invoke<EmailMessage>('read_message', { accountId, folder, uid });
+}
+
+/**
+ * Send a plain-text email
+ */
+export async function sendEmail(draft: EmailDraft): Promise<void> {
+  return invoke<void>('send_email', { draft });
+}
+
+// ============================================================================
+// Calendar
+// ============================================================================
+
+/**
+ * List stored calendar events (recurrences not expanded)
+ */
+export async function listEvents(): Promise<CalendarEvent[]> {
+  return invoke<CalendarEvent[]>('list_events');
+}
+
+/**
+ * Get event occurrences overlapping a time range (unix seconds)
+ */
+export async function getEvents(from: number, to: number): Promise<EventOccurrence[]> {
+  return invoke<EventOccurrence[]>('get_events', { from, to });
+}
+
+/**
+ * Create a calendar event
+ */
+export async function createEvent(event: EventInput): Promise<CalendarEvent> {
+  return invoke<CalendarEvent>('create_event', { event });
+}
+
+/**
+ * Replace a calendar event's fields
+ */
+export async function updateEvent(id: string, event: EventInput): Promise<CalendarEvent> {
+  return invoke<CalendarEvent>('update_event', { id, event });
+}
+
+/**
+ * Delete a calendar event
+ */
+export async function deleteEvent(id: string): Promise<void> {
+  return invoke<void>('delete_event', { id });
+}
+
+/**
+ * Import events from an .ics file, returning the number read
+ */
+export async function importIcs(path: string): Promise<number> {
+  return invoke<number>('import_ics', { path });
+}
+
+/**
+ * Export all events to an .ics file
+ */
+export async function exportIcs(path: string): Promise<number> {
+  return invoke<number>('export_ics', { path });
+}
+
+// ============================================================================
+// Room Booking
+// ============================================================================
+
+/**
+ * Get the room's CalDAV connection and opening hours (password blanked)
+ */
+export async function getRoomConfig(): Promise<RoomConfig> {
+  return invoke<RoomConfig>('get_room_config');
+}
+
+/**
+ * Save the room configuration (admin); an empty password keeps the stored
+ * one unless the calendar URL or username changed
+ */
+export async function setRoomConfig(config: RoomConfig): Promise<void> {
+  return invoke('set_room_config', { config });
+}
+
+/**
+ * Sync the room calendar now, returning the number of bookings fetched
+ */
+export async function syncRoomCalendar(): Promise<number> {
+  return invoke<number>('sync_room_calendar');
+}
+
+/**
+ * Get free/busy slots within opening hours for a time range (unix seconds)
+ */
+export async function getRoomAvailability(from: number, to: number): Promise<RoomAvailability> {
+  return invoke<RoomAvailability>('get_room_availability', { from, to });
+}
+
+/**
+ * Book a slot on the room calendar
+ */
+export async function bookSlot(slot: SlotRequest, details: BookingDetails): Promise<CalendarEvent> {
+  return invoke<CalendarEvent>('book_slot', { slot, details });
+}
+
+// ============================================================================
+// News Feeds
+// ============================================================================
+
+/**
+ * List subscribed news feeds
+ */
+export async function listFeeds(): Promise<Feed[]> {
+  return invoke<Feed[]>('list_feeds');
+}
+
+/**
+ * Subscribe to an RSS or Atom feed
+ */
+export async function addFeed(url: string): Promise<Feed> {
+  return invoke<Feed>('add_feed', { url });
+}
+
+/**
+ * Unsubscribe from a feed
+ */
+export async function removeFeed(url: string): Promise<void> {
+  return invoke('remove_feed', { url });
+}
+
+/**
+ * Refresh all feeds; failed feeds keep their cached items
+ */
+export async function refreshFeeds(): Promise<Feed[]> {
+  return invoke<Feed[]>('refresh_feeds');
+}
+
+/**
+ * Get the newest cached feed items for the news ticker
+ */
+export async function getFeedItems(limit?: number): Promise<FeedItem[]> {
+  return invoke<FeedItem[]>('get_feed_items', { limit });
+}
+
+// ============================================================================
+// Weather
+// ============================================================================
+
+/**
+ * Get cached weather (refreshed in the background; listen for `weather-updated`)
+ */
+export async function getWeather(): Promise<WeatherReport> {
+  return invoke<WeatherReport>('get_weather');
+}
+
+/**
+ * Fetch fresh weather now
+ */
+export async function refreshWeather(): Promise<WeatherReport> {
+  return invoke<WeatherReport>('refresh_weather');
+}
+
+/**
+ * Get the weather location and provider settings
+ */
+export async function getWeatherConfig(): Promise<WeatherConfig> {
+  return invoke<WeatherConfig>('get_weather_config');
+}
+
+/**
+ * Update the weather location and provider settings
+ */
+export async function setWeatherConfig(config: WeatherConfig): Promise<void> {
+  return invoke('set_weather_config', { config });
+}
+
+// ============================================================================
+// Tickers
+// ============================================================================
+
+/**
+ * Get the latest cached quotes (listen for `quotes-updated` to refresh)
+ */
+export async function getQuotes(): Promise<Quote[]> {
+  return invoke<Quote[]>('get_quotes');
+}
+
+/**
+ * Get the ticker symbols and poll interval
+ */
+export async function getTickerConfig(): Promise<TickerConfig> {
+  return invoke<TickerConfig>('get_ticker_config');
+}
+
+/**
+ * Update the ticker symbols and poll interval
+ */
+export async function setTickerConfig(config: TickerConfig): Promise<void> {
+  return invoke('set_ticker_config', { config });
+}
+
+// ============================================================================
+// Help
+// ============================================================================
+
+/**
+ * List bundled help pages for the contents pane
+ */
+export async function listHelpTopics(): Promise<HelpTopic[]> {
+  return invoke<HelpTopic[]>('list_help_topics');
+}
+
+/**
+ * Search help pages, best matches first
+ */
+export async function searchHelp(query: string, limit?: number): Promise<HelpResult[]> {
+  return invoke<HelpResult[]>('search_help', { query, limit });
+}
+
+/**
+ * URL of a help page served over the help:// protocol
+ */
+export function helpUrl(path: string): string {
+  return convertFileSrc(path, 'help');
+}
+
+// ============================================================================
+// Printing
+// ============================================================================
+
+/**
+ * List CUPS printers
+ */
+export async function listPrinters(): Promise<PrinterInfo[]> {
+  return invoke<PrinterInfo[]>('list_printers');
+}
+
+/**
+ * Print plain text or RTF with Notepad-style page setup
+ */
+export async function printText(content: string, options?: PrintOptions): Promise<PrintJob> {
+  return invoke<PrintJob>('print_text', { content, options });
+}
+
+// ============================================================================
+// Badges
+// ============================================================================
+
+/**
+ * Render a visitor badge for preview, as a PNG or a one-page PDF
+ */
+export async function previewBadge(data: BadgeData, format?: BadgeFormat): Promise<BadgePreview> {
+  return invoke<BadgePreview>('preview_badge', { data, format });
+}
+
+/**
+ * Print a visitor badge on the label printer, returning the CUPS job id
+ */
+export async function printBadge(data: BadgeData): Promise<string | null> {
+  return invoke<string | null>('print_badge', { data });
+}
+
+// ============================================================================
+// Payments
+// ============================================================================
+
+/**
+ * Get the payment terminal settings
+ */
+export async function getPaymentConfig(): Promise<PaymentConfig> {
+  return invoke<PaymentConfig>('get_payment_config');
+}
+
+/**
+ * Update the payment terminal settings (admin)
+ */
+export async function setPaymentConfig(config: PaymentConfig): Promise<void> {
+  return invoke('set_payment_config', { config });
+}
+
+/**
+ * Start a card payment in minor units (listen for `payment-status`)
+ */
+export async function startPayment(amount: number): Promise<PaymentUpdate> {
+  return invoke<PaymentUpdate>('start_payment', { amount });
+}
+
+/**
+ * Abort the running card payment
+ */
+export async function cancelPayment(): Promise<void> {
+  return invoke('cancel_payment');
+}
+
+/**
+ * Get the state of the current or most recent payment
+ */
+export async function getPaymentStatus(): Promise<PaymentUpdate | null> {
+  return invoke<PaymentUpdate | null>('get_payment_status');
+}
+
+// ============================================================================
+// Cash Handling
+// ============================================================================
+
+/**
+ * Get the cash hardware settings
+ */
+export async function getCashConfig(): Promise<CashConfig> {
+  return invoke<CashConfig>('get_cash_config');
+}
+
+/**
+ * Update the cash hardware settings (supervisor)
+ */
+export async function setCashConfig(config: CashConfig): Promise<void> {
+  return invoke('set_cash_config', { config });
+}
+
+/**
+ * Start or stop accepting coins and bills (listen for `cash-inserted` and
+ * `cash-escrow`; supervisor)
+ */
+export async function setCashAccepting(enabled: boolean): Promise<void> {
+  return invoke('set_cash_accepting', { enabled });
+}
+
+/**
+ * Get the money inserted so far and any bill in escrow
+ */
+export async function getInsertedAmount(): Promise<CashSession> {
+  return invoke<CashSession>('get_inserted_amount');
+}
+
+/**
+ * Set the price of the current sale, which change is worked out against
+ */
+export async function setSaleTotal(total: number): Promise<CashSession> {
+  return invoke<CashSession>('set_sale_total', { total });
+}
+
+/**
+ * Finish a sale, returning the inserted total and resetting it (supervisor)
+ */
+export async function resetInsertedAmount(): Promise<number> {
+  return invoke<number>('reset_inserted_amount');
+}
+
+/**
+ * Stack the bill held in escrow
+ */
+export async function acceptEscrow(): Promise<number | null> {
+  return invoke<number | null>('accept_escrow');
+}
+
+/**
+ * Return the bill held in escrow
+ */
+export async function returnEscrow(): Promise<number | null> {
+  return invoke<number | null>('return_escrow');
+}
+
+/**
+ * Pay out change in minor units from the hoppers. More than the change due
+ * (inserted less the sale total and change paid) needs an operator; a
+ * failure partway returns what was paid with `error` set
+ */
+export async function dispenseChange(amount: number): Promise<ChangeResult> {
+  return invoke<ChangeResult>('dispense_change', { amount });
+}
+
+// ============================================================================
+// Ticket Printing
+// ============================================================================
+
+/**
+ * Queue a ticket rendered from a template with {{field}} placeholders and ! directives
+ */
+export async function printTicket(template: string, data: Record<string, string>): Promise<TicketJob> {
+  return invoke<TicketJob>('print_ticket', { template, data });
+}
+
+/**
+ * List queued and recently finished ticket jobs
+ */
+export async function listTicketJobs(): Promise<TicketJob[]> {
+  return invoke<TicketJob[]>('list_ticket_jobs');
+}
+
+/**
+ * Requeue a failed ticket job
+ */
+export async function retryTicketJob(id: string): Promise<void> {
+  return invoke('retry_ticket_job', { id });
+}
+
+/**
+ * Remove a ticket job from the queue
+ */
+export async function cancelTicketJob(id: string): Promise<void> {
+  return invoke('cancel_ticket_job', { id });
+}
+
+/**
+ * Query the ticket printer's paper and cover sensors
+ */
+export async function getTicketPrinterStatus(): Promise<TicketPrinterStatus> {
+  return invoke<TicketPrinterStatus>('get_ticket_printer_status');
+}
+
+/**
+ * Get the ticket printer settings
+ */
+export async function getTicketPrinterConfig(): Promise<TicketPrinterConfig> {
+  return invoke<TicketPrinterConfig>('get_ticket_printer_config');
+}
+
+/**
+ * Update the ticket printer settings (admin); a local printer is a
+ * /dev/usb/lp*, /dev/lp* or /dev/tty* device
+ */
+export async function setTicketPrinterConfig(config: TicketPrinterConfig): Promise<void> {
+  return invoke('set_ticket_printer_config', { config });
+}
+
+// ============================================================================
+// Queue
+// ============================================================================
+
+/**
+ * Issue the next queue number for a service
+ */
+export async function takeNumber(service: string): Promise<QueueTicket> {
+  return invoke('take_number', { service });
+}
+
+/**
+ * Call the longest-waiting customer to a counter, optionally for one service
+ */
+export async function callNext(counter: string, service?: string): Promise<Serving | null> {
+  return invoke('call_next', { counter, service });
+}
+
+/**
+ * Get waiting customers and who is being served where
+ */
+export async function getQueueState(): Promise<QueueSnapshot> {
+  return invoke('get_queue_state');
+}
+
+/**
+ * Clear the queue and restart numbering
+ */
+export async function resetQueue(): Promise<void> {
+  return invoke('reset_queue');
+}
+
+/**
+ * Get this kiosk's id on the LAN bridge
+ */
+export async function getKioskId(): Promise<string> {
+  return invoke('get_kiosk_id');
+}
+
+// ============================================================================
+// Wayfinding
+// ============================================================================
+
+/**
+ * Load a building map bundle (JSON or GeoJSON) and make it the active map
+ */
+export async function loadMapBundle(path: string): Promise<MapInfo> {
+  return invoke('load_map_bundle', { path });
+}
+
+/**
+ * Get floors, nodes and points of interest of the active map
+ */
+export async function getMapInfo(): Promise<MapInfo> {
+  return invoke('get_map_info');
+}
+
+/**
+ * Find the shortest route between two points of interest
+ */
+export async function findRoute(from: string, to: string, options?: RouteOptions): Promise<Route> {
+  return invoke('find_route', { from, to, options });
+}
+
+// ============================================================================
+// Attract Loop
+// ============================================================================
+
+/**
+ * Get the attract loop settings
+ */
+export async function getAttractConfig(): Promise<AttractConfig> {
+  return invoke('get_attract_config');
+}
+
+/**
+ * Set the attract playlist, and optionally the idle timeout and enabled flag
+ */
+export async function configureAttractLoop(
+  items: AttractItem[],
+  idleTimeoutSecs?: number,
+  enabled?: boolean
+): Promise<void> {
+  return invoke('configure_attract_loop', { items, idleTimeoutSecs, enabled });
+}
+
+/**
+ * Record user activity; interrupts the attract loop if it is playing
+ */
+export async function reportActivity(): Promise<void> {
+  return invoke('report_activity');
+}
+
+/**
+ * Get whether the attract loop is playing and what is on screen
+ */
+export async function getAttractState(): Promise<AttractStatus> {
+  return invoke('get_attract_state');
+}
+
+// ============================================================================
+// Session
+// ============================================================================
+
+/**
+ * Wipe clipboard, browsing data, temp files, recents, the folders walk-up
+ * users may write to and shares mounted since startup, and sign the
+ * operator out
+ */
+export async function resetSession(): Promise<SessionReset> {
+  return invoke('reset_session');
+}
+
+/**
+ * Get the session reset settings
+ */
+export async function getSessionConfig(): Promise<SessionConfig> {
+  return invoke('get_session_config');
+}
+
+/**
+ * Update the session reset settings (supervisor)
+ */
+export async function setSessionConfig(config: SessionConfig): Promise<void> {
+  return invoke('set_session_config', { config });
+}
+
+// ============================================================================
+// Disk Cleanup
+// ============================================================================
+
+/**
+ * Report reclaimable disk space per cleanup category
+ */
+export async function scanCleanup(): Promise<CleanupCategory[]> {
+  return invoke('scan_cleanup');
+}
+
+/**
+ * Start cleaning the selected categories as a job; listen for cleanup-progress and cleanup-finished.
+ * Returns the job id.
+ */
+export async function runCleanup(categories: string[]): Promise<string> {
+  return invoke('run_cleanup', { categories });
+}
+
+/**
+ * Get the cleanup schedule
+ */
+export async function getCleanupSchedule(): Promise<CleanupSchedule> {
+  return invoke('get_cleanup_schedule');
+}
+
+/**
+ * Update the cleanup schedule
+ */
+export async function setCleanupSchedule(schedule: CleanupSchedule): Promise<void> {
+  return invoke('set_cleanup_schedule', { schedule });
+}
+
+// ============================================================================
+// Checksum
+// ============================================================================
+
+/**
+ * Start hashing a file as a job; large files report hash-progress events.
+ * Returns the job id; the hex digest is the job result.
+ */
+export async function hashFile(path: string, algorithm: HashAlgorithm): Promise<string> {
+  return invoke('hash_file', { path, algorithm });
+}
+
+/**
+ * Start verifying every file listed in a checksum manifest (sha256sum-style or JSON).
+ * Returns the job id; the job result is a ManifestReport.
+ */
+export async function verifyManifest(path: string): Promise<string> {
+  return invoke('verify_manifest', { path });
+}
+
+// ============================================================================
+// Signed Bundle
+// ============================================================================
+
+/**
+ * Start verifying a bundle's ed25519 signature (<path>.sig) against a key or the trusted keys.
+ * Returns the job id; the job result is a BundleVerification.
+ */
+export async function verifyBundle(path: string, pubkey?: string): Promise<string> {
+  return invoke('verify_bundle', { path, pubkey });
+}
+
+/**
+ * Get the bundle signing policy
+ */
+export async function getBundleConfig(): Promise<BundleConfig> {
+  return invoke('get_bundle_config');
+}
+
+/**
+ * Update the bundle signing policy; turning lockdown off may need an approval
+ */
+export async function setBundleConfig(config: BundleConfig, approvalId?: string): Promise<void> {
+  return invoke('set_bundle_config', { config, approvalId });
+}
+
+// ============================================================================
+// Secrets
+// ============================================================================
+
+/**
+ * Encrypt and store a secret (Wi-Fi PSK, share credentials, API token, ...); admin only.
+ * Keys live in their own namespace, apart from the backend's secrets.
+ */
+export async function storeSecret(key: string, value: string): Promise<void> {
+  return invoke('store_secret', { key, value });
+}
+
+/**
+ * Decrypt a stored secret (admin); null when none is stored under the key
+ */
+export async function getSecret(key: string): Promise<string | null> {
+  return invoke('get_secret', { key });
+}
+
+/**
+ * Delete a stored secret (admin)
+ */
+export async function deleteSecret(key: string): Promise<void> {
+  return invoke('delete_secret', { key });
+}
+
+// ============================================================================
+// Certificate
+// ============================================================================
+
+/**
+ * Import a CA bundle, or a client certificate PEM containing its private key
+ */
+export async function importCertificate(path: string): Promise<CertificateInfo> {
+  return invoke('import_certificate', { path });
+}
+
+/**
+ * List installed CA and client certificates
+ */
+export async function listCertificates(): Promise<CertificateInfo[]> {
+  return invoke('list_certificates');
+}
+
+/**
+ * Remove an installed certificate and its key
+ */
+export async function removeCertificate(id: string): Promise<void> {
+  return invoke('remove_certificate', { id });
+}
+
+/**
+ * Choose the client certificate presented to servers, or null for none
+ */
+export async function setClientCertificate(id: string | null): Promise<void> {
+  return invoke('set_client_certificate', { id });
+}
+
+// ============================================================================
+// Operator Auth
+// ============================================================================
+
+/**
+ * Sign an operator in with a local PIN, directory password or one-time code
+ */
+export async function login(
+  username: string,
+  secret: string,
+  provider?: AuthProvider
+): Promise<OperatorSession> {
+  return invoke('login', { username, secret, provider });
+}
+
+/**
+ * Sign the current operator out
+ */
+export async function logout(): Promise<void> {
+  return invoke('logout');
+}
+
+/**
+ * Get the signed-in operator, if any
+ */
+export async function getCurrentOperator(): Promise<OperatorSession | null> {
+  return invoke('get_current_operator');
+}
+
+/**
+ * List local PIN accounts
+ */
+export async function listOperatorAccounts(): Promise<OperatorAccount[]> {
+  return invoke('list_operator_accounts');
+}
+
+/**
+ * Create or update a local PIN account (admin); the first account takes the
+ * setup token printed by `kiosk-cli setup-token` instead
+ */
+export async function saveOperatorAccount(account: OperatorAccount, setupToken?: string): Promise<void> {
+  return invoke('save_operator_account', { account, setupToken });
+}
+
+/**
+ * Delete a local PIN account
+ */
+export async function deleteOperatorAccount(username: string): Promise<void> {
+  return invoke('delete_operator_account', { username });
+}
+
+/**
+ * Get the LDAP/Active Directory settings (service password blanked)
+ */
+export async function getLdapConfig(): Promise<LdapConfig> {
+  return invoke('get_ldap_config');
+}
+
+/**
+ * Update the LDAP/Active Directory settings; an empty password keeps the stored one.
+ * Needs a signed-in admin.
+ */
+export async function setLdapConfig(config: LdapConfig): Promise<void> {
+  return invoke('set_ldap_config', { config });
+}
+
+// ============================================================================
+// OAuth
+// ============================================================================
+
+/**
+ * List OAuth providers and whether each is connected
+ */
+export async function listOAuthProviders(): Promise<OAuthConnection[]> {
+  return invoke('list_oauth_providers');
+}
+
+/**
+ * Create or update an OAuth provider (admin); an empty client secret keeps
+ * the stored one unless an endpoint or the client id changed
+ */
+export async function saveOAuthProvider(provider: OAuthProvider): Promise<void> {
+  return invoke('save_oauth_provider', { provider });
+}
+
+/**
+ * Remove an OAuth provider and its stored tokens (admin)
+ */
+export async function deleteOAuthProvider(id: string): Promise<void> {
+  return invoke('delete_oauth_provider', { id });
+}
+
+/**
+ * Start device sign-in; listen for oauth-status to learn the outcome
+ */
+export async function startDeviceAuth(provider: string): Promise<DeviceAuthPrompt> {
+  return invoke('start_device_auth', { provider });
+}
+
+/**
+ * Abandon a device sign-in in progress
+ */
+export async function cancelDeviceAuth(provider: string): Promise<void> {
+  return invoke('cancel_device_auth', { provider });
+}
+
+/**
+ * Get a valid access token, refreshing it if needed
+ */
+export async function getAccessToken(provider: string): Promise<string> {
+  return invoke('get_access_token', { provider });
+}
+
+/**
+ * Forget the stored tokens for a provider
+ */
+export async function signOutProvider(provider: string): Promise<void> {
+  return invoke('sign_out_provider', { provider });
+}
+
+// ============================================================================
+// Rules
+// ============================================================================
+
+/**
+ * List configured event rules
+ */
+export async function listRules(): Promise<Rule[]> {
+  return invoke('list_rules');
+}
+
+/**
+ * Create or update a rule (admin); an empty id creates a new one. Returns the id.
+ * Script actions may only run programs on the allow-list.
+ */
+export async function saveRule(rule: Rule): Promise<string> {
+  return invoke('save_rule', { rule });
+}
+
+/**
+ * Delete a rule (admin)
+ */
+export async function deleteRule(id: string): Promise<void> {
+  return invoke('delete_rule', { id });
+}
+
+/**
+ * Run a rule's actions now (admin); listen for rule-fired to see the result
+ */
+export async function testRule(id: string): Promise<void> {
+  return invoke('test_rule', { id });
+}
+
+/**
+ * Programs rule Script actions may run
+ */
+export async function getRuleScripts(): Promise<string[]> {
+  return invoke<string[]>('get_rule_scripts');
+}
+
+/**
+ * Replace the programs rule Script actions may run; absolute paths only (admin)
+ */
+export async function setRuleScripts(programs: string[]): Promise<void> {
+  return invoke<void>('set_rule_scripts', { programs });
+}
+
+// ============================================================================
+// Event Bus
+// ============================================================================
+
+/**
+ * Subscribe to backend event topics (exact, `prefix*` or `*`).
+ * Events arrive as EventRecord on the returned channel; replay holds recent history.
+ */
+export async function subscribeEvents(topics: string[], replay?: boolean): Promise<EventSubscription> {
+  return invoke('subscribe_events', { topics, replay });
+}
+
+/**
+ * Stop an event subscription
+ */
+export async function unsubscribeEvents(id: string): Promise<void> {
+  return invoke('unsubscribe_events', { id });
+}
+
+/**
+ * Recent backend events, oldest first, for inspection
+ */
+export async function dumpEventHistory(topic?: string, limit?: number): Promise<EventRecord[]> {
+  return invoke('dump_event_history', { topic, limit });
+}
+
+// ============================================================================
+// Commands
+// ============================================================================
+
+/**
+ * Check whether a rejected command value is a backend error envelope
+ */
+export function isKioskError(error: unknown): error is KioskError {
+  return typeof error === 'object' && error !== null && 'kind' in error && 'message' in error;
+}
+
+/**
+ * Per-command call counts and timings, slowest total first
+ */
+export async function getCommandMetrics(): Promise<CommandMetrics[]> {
+  return invoke('get_command_metrics');
+}
+
+/**
+ * Clear collected command metrics
+ */
+export async function resetCommandMetrics(): Promise<void> {
+  return invoke('reset_command_metrics');
+}
+
+// ============================================================================
+// Jobs
+// ============================================================================
+
+/**
+ * Get a background job's state, progress and result (also sent as job-finished)
+ */
+export async function getJobStatus<T = unknown>(id: string): Promise<JobStatus & { result: T | null }> {
+  return invoke('get_job_status', { id });
+}
+
+/**
+ * Ask a running job to stop
+ */
+export async function cancelJob(id: string): Promise<void> {
+  return invoke('cancel_job', { id });
+}
+
+/**
+ * List known background jobs, newest first
+ */
+export async function listJobs(): Promise<JobStatus[]> {
+  return invoke('list_jobs');
+}
+
+// ============================================================================
+// Benchmark
+// ============================================================================
+
+/**
+ * Start the performance self-test as a job; the job result is a BenchmarkResult.
+ * Measure the frame rate meanwhile with measureFrameRate and send it with reportFrameRate.
+ */
+export async function runBenchmark(): Promise<string> {
+  return invoke('run_benchmark');
+}
+
+/**
+ * Attach a frame rate measurement to a benchmark run
+ */
+export async function reportFrameRate(id: string, frameRate: FrameRate): Promise<void> {
+  return invoke('report_frame_rate', { id, frameRate });
+}
+
+/**
+ * Saved benchmark results, oldest first
+ */
+export async function listBenchmarkResults(): Promise<BenchmarkResult[]> {
+  return invoke('list_benchmark_results');
+}
+
+/**
+ * Measure the webview's frame rate with requestAnimationFrame
+ */
+export function measureFrameRate(durationMs: number = 5000): Promise<FrameRate> {
+  return new Promise((resolve) => {
+    const start = performance.now();
+    let last = start;
+    let frames = 0;
+    let longest = 0;
+
+    const tick = (now: number) => {
+      frames++;
+      longest = Math.max(longest, now - last);
+      last = now;
+      if (now - start < durationMs) {
+        requestAnimationFrame(tick);
+        return;
+      }
+      const seconds = (now - start) / 1000;
+      resolve({
+        average_fps: seconds > 0 ? frames / seconds : 0,
+        min_fps: longest > 0 ? 1000 / longest : 0,
+        frames,
+      });
+    };
+    requestAnimationFrame(tick);
+  });
+}
+
+// ============================================================================
+// Stats History
+// ============================================================================
+
+/**
+ * Stats for the last `window` seconds, averaged into `resolution`-second points (up to 24 hours)
+ */
+export async function getStatsHistory(window: number, resolution: number): Promise<StatsSample[]> {
+  return invoke('get_stats_history', { window, resolution });
+}
+
+// ============================================================================
+// App Supervisor
+// ============================================================================
+
+/**
+ * Launch an allow-listed program under supervision, returning its id.
+ * Output is captured to its log and exits are published as `app-exited` /
+ * `app-crashed`. `LD_*` and `PATH` are dropped from `env` (admin)
+ */
+export async function launchApp(spec: LaunchSpec): Promise<string> {
+  return invoke('launch_app', { spec });
+}
+
+/**
+ * List supervised apps
+ */
+export async function listApps(): Promise<AppProcess[]> {
+  return invoke('list_apps');
+}
+
+/**
+ * Stop an app; it is not restarted
+ */
+export async function stopApp(id: string): Promise<void> {
+  return invoke('stop_app', { id });
+}
+
+/**
+ * Restart an app now, or start it again if it has stopped
+ */
+export async function restartApp(id: string): Promise<void> {
+  return invoke('restart_app', { id });
+}
+
+/**
+ * Turn automatic restart on or off for an app (admin)
+ */
+export async function setAppAutoRestart(id: string, enabled: boolean): Promise<void> {
+  return invoke('set_app_auto_restart', { id, enabled });
+}
+
+/**
+ * Remove a stopped app from the list (admin)
+ */
+export async function removeApp(id: string): Promise<void> {
+  return invoke('remove_app', { id });
+}
+
+/**
+ * The end of an app's captured stdout/stderr (64 KiB by default)
+ */
+export async function getAppLog(id: string, maxBytes?: number): Promise<string> {
+  return invoke('get_app_log', { id, maxBytes });
+}
+
+/**
+ * Programs apps may be launched from
+ */
+export async function getAppPrograms(): Promise<string[]> {
+  return invoke<string[]>('get_app_programs');
+}
+
+/**
+ * Replace the programs apps may be launched from; absolute paths of
+ * existing files (admin)
+ */
+export async function setAppPrograms(programs: string[]): Promise<void> {
+  return invoke<void>('set_app_programs', { programs });
+}
+
+// ============================================================================
+// Runtime Config
+// ============================================================================
+
+/**
+ * Effective runtime configuration for diagnostics
+ */
+export async function getRuntimeConfig(): Promise<RuntimeConfig> {
+  return invoke('get_runtime_config');
+}
+
+/**
+ * Set a feature flag and persist it, returning all flags. An environment
+ * override still wins. Needs a signed-in admin.
+ */
+export async function setFeatureFlag(name: string, value: boolean): Promise<FeatureFlag[]> {
+  return invoke('set_feature_flag', { name, value });
+}
+
+// ============================================================================
+// Support Bundle
+// ============================================================================
+
+/**
+ * Build a zip of logs, redacted config, crash reports, hardware profile,
+ * stats history and a screenshot. Returns a job id; the job result is a
+ * `SupportBundle`. Written to `destination` (e.g. a USB drive) or the app's
+ * support directory.
+ */
+export async function generateSupportBundle(destination?: string): Promise<string> {
+  return invoke('generate_support_bundle', { destination });
+}
+
+// ============================================================================
+// System Report
+// ============================================================================
+
+/**
+ * Full system report as JSON (a `SystemReport`), plain text or a standalone HTML page
+ */
+export async function getSystemReport(format: ReportFormat): Promise<string> {
+  return invoke('get_system_report', { format });
+}
+
+// ============================================================================
+// Device Tree
+// ============================================================================
+
+/**
+ * Devices grouped by category under the computer, Device Manager style
+ */
+export async function getDeviceTree(): Promise<DeviceNode> {
+  return invoke('get_device_tree');
+}
+
+// ============================================================================
+// Services
+// ============================================================================
+
+/**
+ * All loaded systemd service units
+ */
+export async function listServices(): Promise<ServiceInfo[]> {
+  return invoke('list_services');
+}
+
+/**
+ * Detailed state of one service (`ssh` or `ssh.service`)
+ */
+export async function getServiceStatus(name: string): Promise<ServiceStatus> {
+  return invoke('get_service_status', { name });
+}
+
+/**
+ * Start a manageable service (supervisor login required)
+ */
+export async function startService(name: string): Promise<void> {
+  return invoke('start_service', { name });
+}
+
+/**
+ * Stop a manageable service (supervisor login required)
+ */
+export async function stopService(name: string): Promise<void> {
+  return invoke('stop_service', { name });
+}
+
+/**
+ * Restart a manageable service (supervisor login required)
+ */
+export async function restartService(name: string): Promise<void> {
+  return invoke('restart_service', { name });
+}
+
+/**
+ * Enable a manageable service at boot (admin login required)
+ */
+export async function enableService(name: string): Promise<void> {
+  return invoke('enable_service', { name });
+}
+
+/**
+ * Stop a manageable service starting at boot (admin login required)
+ */
+export async function disableService(name: string): Promise<void> {
+  return invoke('disable_service', { name });
+}
+
+/**
+ * Get the list of manageable services
+ */
+export async function getServicePolicy(): Promise<ServicePolicy> {
+  return invoke('get_service_policy');
+}
+
+/**
+ * Update the list of manageable services (admin login required)
+ */
+export async function setServicePolicy(policy: ServicePolicy): Promise<void> {
+  return invoke('set_service_policy', { policy });
+}
+
+// ============================================================================
+// Boot Timeline
+// ============================================================================
+
+/**
+ * Tell the backend the desktop has painted, closing the boot timeline
+ */
+export async function reportFirstPaint(): Promise<void> {
+  return invoke('report_first_paint');
+}
+
+/**
+ * This boot's startup marks with recent boot totals
+ */
+export async function getBootTimeline(): Promise<BootTimeline> {
+  return invoke('get_boot_timeline');
+}
+
+/**
+ * Set the start-to-first-paint budget in milliseconds
+ */
+export async function setBootBudget(budgetMs: number): Promise<void> {
+  return invoke('set_boot_budget', { budgetMs });
+}
+
+// ============================================================================
+// Files
+// ============================================================================
+
+/**
+ * List the top-level locations (Desktop, My Documents, drives, shares, Recycle Bin)
+ */
+export async function listVirtualRoots(): Promise<VirtualRoot[]> {
+  return invoke<VirtualRoot[]>('list_virtual_roots');
+}
+
+/**
+ * List a folder by virtual path, folders first
+ */
+export async function readDirectory(path: string): Promise<FileEntry[]> {
+  return invoke<FileEntry[]>('read_directory', { path });
+}
+
+/**
+ * Read a text file
+ */
+export async function readTextFile(path: string): Promise<string> {
+  return invoke<string>('read_text_file', { path });
+}
+
+/**
+ * Create or replace a text file
+ */
+export async function writeTextFile(path: string, contents: string): Promise<void> {
+  return invoke('write_text_file', { path, contents });
+}
+
+/**
+ * Create a folder
+ */
+export async function createDirectory(path: string): Promise<void> {
+  return invoke('create_directory', { path });
+}
+
+/**
+ * Copy a file or folder to a new path, as a cancellable job; returns its id
+ */
+export async function copyPath(source: string, destination: string): Promise<string> {
+  return invoke<string>('copy_path', { source, destination });
+}
+
+/**
+ * Move or rename a file or folder, as a job; returns its id
+ */
+export async function renamePath(source: string, destination: string): Promise<string> {
+  return invoke<string>('rename_path', { source, destination });
+}
+
+/**
+ * Move to the Recycle Bin, or delete for good when already there or `permanent`,
+ * as a job; returns its id
+ */
+export async function deletePath(path: string, permanent?: boolean): Promise<string> {
+  return invoke<string>('delete_path', { path, permanent });
+}
+
+/**
+ * Get the roots each role may read and change
+ */
+export async function getFsScope(): Promise<FsScopePolicy> {
+  return invoke<FsScopePolicy>('get_fs_scope');
+}
+
+/**
+ * Update the roots each role may read and change (admin)
+ */
+export async function setFsScope(policy: FsScopePolicy): Promise<void> {
+  return invoke('set_fs_scope', { policy });
+}
+
+// ============================================================================
+// Storage Quota
+// ============================================================================
+
+/**
+ * Usage of every root with a quota
+ */
+export async function getQuotaStatus(): Promise<QuotaStatus[]> {
+  return invoke<QuotaStatus[]>('get_quota_status');
+}
+
+/**
+ * Get the configured quota limits
+ */
+export async function getQuotaConfig(): Promise<QuotaConfig> {
+  return invoke<QuotaConfig>('get_quota_config');
+}
+
+/**
+ * Set, or remove with null, the limit for a root (admin)
+ */
+export async function setQuota(root: string, limitBytes: number | null): Promise<void> {
+  return invoke('set_quota', { root, limitBytes });
+}
+
+// ============================================================================
+// Read-only Root
+// ============================================================================
+
+/**
+ * Whether the read-only root is active now and after the next boot
+ */
+export async function getOverlayStatus(): Promise<OverlayStatus> {
+  return invoke<OverlayStatus>('get_overlay_status');
+}
+
+/**
+ * Make the root filesystem read-only from the next boot (admin)
+ */
+export async function enableOverlayFs(): Promise<OverlayStatus> {
+  return invoke<OverlayStatus>('enable_overlay_fs');
+}
+
+/**
+ * Make the root filesystem writable again from the next boot (admin)
+ */
+export async function disableOverlayFs(): Promise<OverlayStatus> {
+  return invoke<OverlayStatus>('disable_overlay_fs');
+}
+
+/**
+ * Persist the kiosk's data, and the given virtual folders, to the SD card (admin)
+ */
+export async function commitChanges(paths?: string[]): Promise<CommitResult> {
+  return invoke<CommitResult>('commit_changes', { paths: paths ?? null });
+}
+
+// ============================================================================
+// Factory Reset
+// ============================================================================
+
+/**
+ * Issue the token factoryReset must be called with (admin)
+ */
+export async function requestFactoryReset(): Promise<ResetToken> {
+  return invoke<ResetToken>('request_factory_reset');
+}
+
+/**
+ * Wipe the kiosk back to its defaults and reboot; returns the job id (admin,
+ * with a second admin's approval under dual authorization)
+ */
+export async function factoryReset(confirmToken: string, approvalId?: string): Promise<string> {
+  return invoke<string>('factory_reset', { confirmToken, approvalId });
+}
+
+// ============================================================================
+// Backup
+// ============================================================================
+
+/**
+ * Back up the app data to a drive or folder; returns the job id, whose
+ * result is a BackupResult. With a passphrase (8+ characters) the keyring
+ * secrets are carried too, sealed under it (admin)
+ */
+export async function backupToDrive(mountPoint: string, passphrase?: string): Promise<string> {
+  return invoke<string>('backup_to_drive', { mountPoint, passphrase: passphrase ?? null });
+}
+
+/**
+ * Verify a backup, replace the app data with it and restart; returns the
+ * job id, whose result is a RestoreResult. The backup's passphrase brings
+ * its secrets along; without it they have to be entered again (admin)
+ */
+export async function restoreFromDrive(path: string, passphrase?: string): Promise<string> {
+  return invoke<string>('restore_from_drive', { path, passphrase: passphrase ?? null });
+}
+
+// ============================================================================
+// Screen Recording
+// ============================================================================
+
+/**
+ * Start recording the screen for up to maxDuration seconds (default 60, at most 600)
+ */
+export async function startScreenRecording(maxDuration?: number): Promise<RecordingStatus> {
+  return invoke<RecordingStatus>('start_screen_recording', { maxDuration: maxDuration ?? null });
+}
+
+/**
+ * Stop the running recording and return the finished file
+ */
+export async function stopScreenRecording(): Promise<Recording> {
+  return invoke<Recording>('stop_screen_recording');
+}
+
+/**
+ * Get the running recording, if any
+ */
+export async function getScreenRecordingStatus(): Promise<RecordingStatus | null> {
+  return invoke<RecordingStatus | null>('get_screen_recording_status');
+}
+
+// ============================================================================
+// Input Macros
+// ============================================================================
+
+/**
+ * Start recording input into a macro (admin)
+ */
+export async function recordMacro(name: string): Promise<void> {
+  return invoke('record_macro', { name });
+}
+
+/**
+ * Stop recording and save the macro (admin)
+ */
+export async function stopMacroRecording(): Promise<MacroInfo> {
+  return invoke<MacroInfo>('stop_macro_recording');
+}
+
+/**
+ * Replay a macro, repeating it until stopped when loop is set (admin)
+ */
+export async function playMacro(name: string, loop = false): Promise<void> {
+  return invoke('play_macro', { name, looped: loop });
+}
+
+/**
+ * Stop a playing macro
+ */
+export async function stopMacro(): Promise<void> {
+  return invoke('stop_macro');
+}
+
+/**
+ * List saved macros
+ */
+export async function listMacros(): Promise<MacroInfo[]> {
+  return invoke<MacroInfo[]>('list_macros');
+}
+
+/**
+ * Delete a saved macro (admin)
+ */
+export async function deleteMacro(name: string): Promise<void> {
+  return invoke('delete_macro', { name });
+}
+
+/**
+ * Get what is being recorded or played
+ */
+export async function getMacroStatus(): Promise<MacroStatus> {
+  return invoke<MacroStatus>('get_macro_status');
+}
+
+// ============================================================================
+// Generated Bindings
+// ============================================================================
+
+/**
+ * Invoke any backend command with its generated argument and result types
+ */
+export async function invokeCommand<K extends keyof Commands>(
+  command: K,
+  ...args: Commands[K]['args'] extends Record<string, never> ? [] : [Commands[K]['args']]
+): Promise<Commands[K]['result']> {
+  return invoke<Commands[K]['result']>(command, args[0]);
+}
+
+/**
+ * Listen for a backend event with its generated payload type; resolves to
+ * the function that stops listening
+ */
+export async function onEvent<K extends keyof Events>(
+  topic: K,
+  handler: (payload: Events[K]) => void
+): Promise<UnlistenFn> {
+  return listen<Events[K]>(topic, (event) => handler(event.payload));
+}
+
+// ============================================================================
+// Mock Mode
+// ============================================================================
+
+/**
+ * Whether the backend runs with --mock, and which commands are scripted
+ */
+export async function getMockStatus(): Promise<MockStatus> {
+  return invoke<MockStatus>('get_mock_status');
+}
+
+/**
+ * Start scripted fixture sequences over from their first entry
+ */
+export async function resetMockSequences(): Promise<void> {
+  return invoke('reset_mock_sequences');
+}
+
+// ============================================================================
+// D-Bus Control
+// ============================================================================
+
+/**
+ * Which bus the org.kiosk.Control interface is exported on, if any
+ */
+export async function getDbusControlStatus(): Promise<ControlStatus> {
+  return invoke<ControlStatus>('get_dbus_control_status');
+}
+
+// ============================================================================
+// Wake-on-LAN
+// ============================================================================
+
+/**
+ * Wake a machine by MAC address, optionally via a subnet broadcast address
+ */
+export async function sendWol(mac: string, broadcast?: string, port?: number): Promise<WolSent> {
+  return invoke<WolSent>('send_wol', { mac, broadcast, port });
+}
+
+/**
+ * Wake-on-LAN support and setting of this kiosk's wired NIC
+ */
+export async function getWolStatus(iface?: string): Promise<WolStatus> {
+  return invoke<WolStatus>('get_wol_status', { interface: iface });
+}
+
+/**
+ * Turn Wake-on-LAN on or off for this kiosk's NIC (admin)
+ */
+export async function setWolEnabled(enabled: boolean, iface?: string): Promise<WolStatus> {
+  return invoke<WolStatus>('set_wol_enabled', { enabled, interface: iface });
+}
+
+// ============================================================================
+// HDMI-CEC
+// ============================================================================
+
+/**
+ * Wake the TV over HDMI-CEC and make the kiosk its active source
+ */
+export async function tvPowerOn(): Promise<TvState> {
+  return invoke<TvState>('tv_power_on');
+}
+
+/**
+ * Put the TV into standby
+ */
+export async function tvPowerOff(): Promise<TvState> {
+  return invoke<TvState>('tv_power_off');
+}
+
+/**
+ * Switch the TV to an HDMI input (1-15), or to the kiosk itself with 0
+ */
+export async function setTvInput(input: number): Promise<TvState> {
+  return invoke<TvState>('set_tv_input', { input });
+}
+
+/**
+ * Ask the TV for its power status now
+ */
+export async function getTvState(): Promise<TvState> {
+  return invoke<TvState>('get_tv_state');
+}
+
+// ============================================================================
+// Operating Hours
+// ============================================================================
+
+/**
+ * Get the display operating hours
+ */
+export async function getSchedule(): Promise<Schedule> {
+  return invoke<Schedule>('get_schedule');
+}
+
+/**
+ * Replace the operating hours and apply them right away (admin)
+ */
+export async function setSchedule(schedule: Schedule): Promise<DisplayState> {
+  return invoke<DisplayState>('set_schedule', { schedule });
+}
+
+/**
+ * Force the display on or off until a unix time (default: the next
+ * scheduled change); null hands control back to the schedule
+ */
+export async function setDisplayOverride(on: boolean | null, until?: number): Promise<DisplayState> {
+  return invoke<DisplayState>('set_display_override', { on, until });
+}
+
+/**
+ * Current, scheduled and overridden display state
+ */
+export async function getDisplayState(): Promise<DisplayState> {
+  return invoke<DisplayState>('get_display_state');
+}
+
+// ============================================================================
+// Auto-brightness
+// ============================================================================
+
+/**
+ * Smoothed ambient light, the sensor and the backlight level
+ */
+export async function getLightLevel(): Promise<LightLevel> {
+  return invoke<LightLevel>('get_light_level');
+}
+
+/**
+ * Get the light sensor, brightness curve and floor
+ */
+export async function getBrightnessConfig(): Promise<BrightnessConfig> {
+  return invoke<BrightnessConfig>('get_brightness_config');
+}
+
+/**
+ * Replace the light sensor, brightness curve and floor (admin)
+ */
+export async function setBrightnessConfig(config: BrightnessConfig): Promise<void> {
+  return invoke('set_brightness_config', { config });
+}
+
+/**
+ * Turn the auto-brightness controller on or off
+ */
+export async function setAutoBrightness(enabled: boolean): Promise<void> {
+  return invoke('set_auto_brightness', { enabled });
+}
+
+/**
+ * Set the backlight by hand (0-100); turns auto-brightness off
+ */
+export async function setBrightness(brightness: number): Promise<void> {
+  return invoke('set_brightness', { brightness });
+}
+
+// ============================================================================
+// Proximity Sensor
+// ============================================================================
+
+/**
+ * Get the proximity sensor and debounce settings
+ */
+export async function getProximityConfig(): Promise<ProximityConfig> {
+  return invoke<ProximityConfig>('get_proximity_config');
+}
+
+/**
+ * Replace the proximity sensor and debounce settings (admin)
+ */
+export async function setProximityConfig(config: ProximityConfig): Promise<void> {
+  return invoke('set_proximity_config', { config });
+}
+
+/**
+ * Whether someone is in front of the kiosk
+ */
+export async function getProximityState(): Promise<ProximityStatus> {
+  return invoke<ProximityStatus>('get_proximity_state');
+}
+
+// ============================================================================
+// Footfall
+// ============================================================================
+
+/**
+ * Visitor counts from `from` to `to` (inclusive, "YYYY-MM-DD"); today when
+ * neither is given
+ */
+export async function getFootfallStats(from?: string, to?: string): Promise<FootfallStats> {
+  return invoke<FootfallStats>('get_footfall_stats', { from, to });
+}
+
+/**
+ * Write hourly counts for a range as CSV to a virtual path, returning the
+ * number of rows
+ */
+export async function exportFootfallCsv(path: string, from?: string, to?: string): Promise<number> {
+  return invoke<number>('export_footfall_csv', { path, from, to });
+}
+
+/**
+ * Add visitors counted by another source, such as a camera
+ */
+export async function recordFootfall(count: number): Promise<void> {
+  return invoke('record_footfall', { count });
+}
+
+// ============================================================================
+// Speech Recognition
+// ============================================================================
+
+/**
+ * Start listening for speech, limited to the grammar's phrases when given
+ * (needs a build with the `speech` feature)
+ */
+export async function startListening(grammar?: string[]): Promise<SpeechStatus> {
+  return invoke<SpeechStatus>('start_listening', { grammar });
+}
+
+/**
+ * Stop listening
+ */
+export async function stopListening(): Promise<void> {
+  return invoke('stop_listening');
+}
+
+/**
+ * Get whether the kiosk is listening and for which phrases
+ */
+export async function getSpeechStatus(): Promise<SpeechStatus> {
+  return invoke<SpeechStatus>('get_speech_status');
+}
+
+/**
+ * Get the speech model and microphone settings
+ */
+export async function getSpeechConfig(): Promise<SpeechConfig> {
+  return invoke<SpeechConfig>('get_speech_config');
+}
+
+/**
+ * Change the speech model and microphone (admin)
+ */
+export async function setSpeechConfig(config: SpeechConfig): Promise<void> {
+  return invoke('set_speech_config', { config });
+}
+
+// ============================================================================
+// Localization
+// ============================================================================
+
+/**
+ * List locales with a catalog, bundled or added in the data directory
+ */
+export async function listLocales(): Promise<LocaleInfo[]> {
+  return invoke<LocaleInfo[]>('list_locales');
+}
+
+/**
+ * Get the kiosk's current locale
+ */
+export async function getLocale(): Promise<string> {
+  return invoke<string>('get_locale');
+}
+
+/**
+ * Get the UI strings for a locale, or the current one, with fallbacks filled in
+ */
+export async function getStrings(locale?: string): Promise<Record<string, string>> {
+  return invoke<Record<string, string>>('get_strings', { locale });
+}
+
+/**
+ * Switch the kiosk's language and remember it; customized formats are dropped
+ */
+export async function setLocale(locale: string): Promise<LocaleInfo> {
+  return invoke<LocaleInfo>('set_locale', { locale });
+}
+
+// ============================================================================
+// Formatting
+// ============================================================================
+
+/**
+ * Format a number with the separators of a locale, or the kiosk's locale
+ */
+export async function formatNumber(value: number, digits?: number, locale?: string): Promise<string> {
+  return invoke<string>('format_number', { value, digits, locale });
+}
+
+/**
+ * Format a price in an ISO 4217 currency such as `EUR` for a locale, or the kiosk's locale
+ */
+export async function formatCurrency(amount: number, currency: string, locale?: string): Promise<string> {
+  return invoke<string>('format_currency', { amount, currency, locale });
+}
+
+/**
+ * Format a measurement for a locale, converted to the region's usual unit unless `convert` is false
+ */
+export async function formatMeasurement(
+  value: number,
+  unit: string,
+  locale?: string,
+  digits?: number,
+  convert?: boolean
+): Promise<Measurement> {
+  return invoke<Measurement>('format_measurement', { value, unit, locale, digits, convert });
+}
+
+// ============================================================================
+// Business Hours
+// ============================================================================
+
+/**
+ * Get the business hours, holiday region and special days
+ */
+export async function getBusinessHours(): Promise<BusinessHours> {
+  return invoke<BusinessHours>('get_business_hours');
+}
+
+/**
+ * Replace the business hours, holiday region and special days (admin)
+ */
+export async function setBusinessHours(hours: BusinessHours): Promise<OpenStatus> {
+  return invoke<OpenStatus>('set_business_hours', { hours });
+}
+
+/**
+ * Whether the premises are open, and until or from when
+ */
+export async function isOpenNow(): Promise<OpenStatus> {
+  return invoke<OpenStatus>('is_open_now');
+}
+
+/**
+ * Unix time of the next opening, or null if nothing opens in the next two months
+ */
+export async function nextOpening(): Promise<number | null> {
+  return invoke<number | null>('next_opening');
+}
+
+/**
+ * Public holidays of a region, or the configured region, in a year
+ */
+export async function getHolidays(year: number, region?: string): Promise<HolidayDate[]> {
+  return invoke<HolidayDate[]>('get_holidays', { year, region });
+}
+
+/**
+ * List holiday regions with bundled or added rules
+ */
+export async function listHolidayRegions(): Promise<HolidayRegion[]> {
+  return invoke<HolidayRegion[]>('list_holiday_regions');
+}
+
+// ============================================================================
+// Timers and Alarms
+// ============================================================================
+
+/**
+ * Start a countdown timer
+ */
+export async function createTimer(durationSecs: number, label?: string): Promise<Timer> {
+  return invoke<Timer>('create_timer', { durationSecs, label });
+}
+
+/**
+ * List timers that have not run out yet
+ */
+export async function listTimers(): Promise<Timer[]> {
+  return invoke<Timer[]>('list_timers');
+}
+
+/**
+ * Pause or resume a timer
+ */
+export async function setTimerPaused(id: string, paused: boolean): Promise<Timer> {
+  return invoke<Timer>('set_timer_paused', { id, paused });
+}
+
+/**
+ * Stop a timer without it firing
+ */
+export async function cancelTimer(id: string): Promise<void> {
+  return invoke<void>('cancel_timer', { id });
+}
+
+/**
+ * Set an alarm for "HH:MM", ringing once unless a recurrence is given
+ */
+export async function createAlarm(
+  time: string,
+  recurrence?: AlarmRecurrence,
+  label?: string
+): Promise<Alarm> {
+  return invoke<Alarm>('create_alarm', { time, recurrence, label });
+}
+
+/**
+ * List all alarms
+ */
+export async function listAlarms(): Promise<Alarm[]> {
+  return invoke<Alarm[]>('list_alarms');
+}
+
+/**
+ * Switch an alarm on or off
+ */
+export async function setAlarmEnabled(id: string, enabled: boolean): Promise<Alarm> {
+  return invoke<Alarm>('set_alarm_enabled', { id, enabled });
+}
+
+/**
+ * Ring an alarm again in a few minutes (default 10)
+ */
+export async function snoozeAlarm(id: string, minutes?: number): Promise<Alarm> {
+  return invoke<Alarm>('snooze_alarm', { id, minutes });
+}
+
+/**
+ * Delete an alarm
+ */
+export async function deleteAlarm(id: string): Promise<void> {
+  return invoke<void>('delete_alarm', { id });
+}
+
+// ============================================================================
+// Monotonic Timing
+// ============================================================================
+
+/**
+ * Read the monotonic clock, with the wall clock for reference
+ */
+export async function getMonotonicTime(): Promise<MonotonicTime> {
+  return invoke<MonotonicTime>('get_monotonic_time');
+}
+
+/**
+ * Emit `interval-tick:<id>` on a monotonic schedule, returning the id
+ */
+export async function startInterval(intervalMs: number): Promise<string> {
+  return invoke<string>('start_interval', { intervalMs });
+}
+
+/**
+ * Stop an interval started with startInterval
+ */
+export async function stopInterval(id: string): Promise<void> {
+  return invoke<void>('stop_interval', { id });
+}
+
+// ============================================================================
+// Screen Lock
+// ============================================================================
+
+/**
+ * Lock the screen now
+ */
+export async function lockScreen(): Promise<LockStatus> {
+  return invoke<LockStatus>('lock_screen');
+}
+
+/**
+ * Unlock the screen with the PIN
+ */
+export async function unlock(pin: string): Promise<LockStatus> {
+  return invoke<LockStatus>('unlock', { pin });
+}
+
+/**
+ * Get whether the screen is locked and how long until the next attempt
+ */
+export async function getLockStatus(): Promise<LockStatus> {
+  return invoke<LockStatus>('get_lock_status');
+}
+
+/**
+ * Get the auto-lock and lockout settings
+ */
+export async function getLockConfig(): Promise<LockConfig> {
+  return invoke<LockConfig>('get_lock_config');
+}
+
+/**
+ * Update the auto-lock and lockout settings (admin)
+ */
+export async function setLockConfig(config: LockConfig): Promise<void> {
+  return invoke<void>('set_lock_config', { config });
+}
+
+/**
+ * Set the lock PIN, or clear it to turn the lock off (admin)
+ */
+export async function setLockPin(pin: string | null): Promise<LockStatus> {
+  return invoke<LockStatus>('set_lock_pin', { pin });
+}
+
+// ============================================================================
+// Dual Authorization
+// ============================================================================
+
+/**
+ * List the actions dual authorization can cover and whether each is covered
+ */
+export async function listProtectedActions(): Promise<ProtectedAction[]> {
+  return invoke<ProtectedAction[]>('list_protected_actions');
+}
+
+/**
+ * Get the dual authorization settings
+ */
+export async function getDualAuthConfig(): Promise<DualAuthConfig> {
+  return invoke<DualAuthConfig>('get_dual_auth_config');
+}
+
+/**
+ * Update dual authorization (admin); weakening it needs an approval
+ */
+export async function setDualAuthConfig(config: DualAuthConfig, approvalId?: string): Promise<void> {
+  return invoke<void>('set_dual_auth_config', { config, approvalId });
+}
+
+/**
+ * Ask for a second admin's approval of a protected action (admin)
+ */
+export async function requestApproval(action: string): Promise<Approval> {
+  return invoke<Approval>('request_approval', { action });
+}
+
+/**
+ * Approve a request as a second admin, with that admin's own credentials
+ * or a one-time code from support
+ */
+export async function approveAction(
+  id: string,
+  username: string,
+  secret: string,
+  provider?: AuthProvider
+): Promise<Approval> {
+  return invoke<Approval>('approve_action', { id, username, secret, provider });
+}
+
+/**
+ * Withdraw a request or an unused approval (admin)
+ */
+export async function cancelApproval(id: string): Promise<void> {
+  return invoke<void>('cancel_approval', { id });
+}
+
+/**
+ * List open requests and unused approvals
+ */
+export async function listPendingApprovals(): Promise<Approval[]> {
+  return invoke<Approval[]>('list_pending_approvals');
+}
+
+/**
+ * Get the dual authorization audit trail, newest first (admin)
+ */
+export async function getDualAuthAudit(limit?: number): Promise<AuditEntry[]> {
+  return invoke<AuditEntry[]>('get_dual_auth_audit', { limit });
+}
+
+// ============================================================================
+// One-Time Codes
+// ============================================================================
+
+/**
+ * Get the one-time code settings
+ */
+export async function getTotpConfig(): Promise<TotpConfig> {
+  return invoke<TotpConfig>('get_totp_config');
+}
+
+/**
+ * Update the one-time code settings (admin)
+ */
+export async function setTotpConfig(config: TotpConfig): Promise<void> {
+  return invoke<void>('set_totp_config', { config });
+}
+
+/**
+ * Store support's provisioning secret, or generate one when none is given (admin)
+ */
+export async function provisionTotp(secret?: string): Promise<TotpProvisioning> {
+  return invoke<TotpProvisioning>('provision_totp', { secret });
+}
+
+/**
+ * Forget the provisioning secret so no code is accepted (admin)
+ */
+export async function clearTotp(): Promise<void> {
+  return invoke<void>('clear_totp');
+}
+
+// ============================================================================
+// Tamper Detection
+// ============================================================================
+
+/**
+ * Get the tamper detection settings
+ */
+export async function getTamperConfig(): Promise<TamperConfig> {
+  return invoke<TamperConfig>('get_tamper_config');
+}
+
+/**
+ * Update tamper detection (admin)
+ */
+export async function setTamperConfig(config: TamperConfig): Promise<void> {
+  return invoke<void>('set_tamper_config', { config });
+}
+
+/**
+ * List recorded tamper findings, newest first
+ */
+export async function listTamperEvents(limit?: number): Promise<TamperEvent[]> {
+  return invoke<TamperEvent[]>('list_tamper_events', { limit });
+}
+
+/**
+ * Forget recorded findings and delete their photos (admin)
+ */
+export async function clearTamperEvents(): Promise<void> {
+  return invoke<void>('clear_tamper_events');
+}
+
+// ============================================================================
+// Location
+// ============================================================================
+
+/**
+ * Get the latest position
+ */
+export async function getLocation(): Promise<Location> {
+  return invoke<Location>('get_location');
+}
+
+/**
+ * Get whether the unit is inside its geofence
+ */
+export async function getGeofenceStatus(): Promise<GeofenceStatus> {
+  return invoke<GeofenceStatus>('get_geofence_status');
+}
+
+/**
+ * Get the location source and geofence
+ */
+export async function getLocationConfig(): Promise<LocationConfig> {
+  return invoke<LocationConfig>('get_location_config');
+}
+
+/**
+ * Update the location source and geofence (admin)
+ */
+export async function setLocationConfig(config: LocationConfig): Promise<void> {
+  return invoke<void>('set_location_config', { config });
+}
+
+// ============================================================================
+// Modem
+// ============================================================================
+
+/**
+ * Get modem, signal and connection details
+ */
+export async function getModemStatus(): Promise<ModemStatus> {
+  return invoke<ModemStatus>('get_modem_status');
+}
+
+/**
+ * Get the APN and dialing settings
+ */
+export async function getModemConfig(): Promise<ModemConfig> {
+  return invoke<ModemConfig>('get_modem_config');
+}
+
+/**
+ * Update the APN and dialing settings (admin)
+ */
+export async function setModemConfig(config: ModemConfig): Promise<void> {
+  return invoke<void>('set_modem_config', { config });
+}
+
+/**
+ * Bring up mobile data (supervisor)
+ */
+export async function connectModem(): Promise<ModemStatus> {
+  return invoke<ModemStatus>('connect_modem');
+}
+
+/**
+ * Drop mobile data (supervisor)
+ */
+export async function disconnectModem(): Promise<ModemStatus> {
+  return invoke<ModemStatus>('disconnect_modem');
+}
+
+/**
+ * Send a text message (supervisor)
+ */
+export async function sendSms(number: string, text: string): Promise<void> {
+  return invoke<void>('send_sms', { number, text });
+}
+
+/**
+ * List messages stored on the modem (supervisor)
+ */
+export async function listSms(): Promise<SmsMessage[]> {
+  return invoke<SmsMessage[]>('list_sms');
+}
+
+/**
+ * Delete a stored message (supervisor)
+ */
+export async function deleteSms(id: string): Promise<void> {
+  return invoke<void>('delete_sms', { id });
+}
+
+// ============================================================================
+// Data Usage
+// ============================================================================
+
+/**
+ * Get bytes per interface per day (inclusive "YYYY-MM-DD" range; today by default)
+ */
+export async function getDataUsage(from?: string, to?: string): Promise<DataUsage> {
+  return invoke<DataUsage>('get_data_usage', { from, to });
+}
+
+/**
+ * Get metered usage against the cap this billing period
+ */
+export async function getDataCapStatus(): Promise<DataCapStatus> {
+  return invoke<DataCapStatus>('get_data_cap_status');
+}
+
+/**
+ * Get the metered interfaces and monthly cap
+ */
+export async function getDataUsageConfig(): Promise<DataUsageConfig> {
+  return invoke<DataUsageConfig>('get_data_usage_config');
+}
+
+/**
+ * Update the metered interfaces and monthly cap (admin)
+ */
+export async function setDataUsageConfig(config: DataUsageConfig): Promise<DataCapStatus> {
+  return invoke<DataCapStatus>('set_data_usage_config', { config });
+}
+
+// ============================================================================
+// Downloads
+// ============================================================================
+
+/**
+ * Queue a download, resumed automatically after pauses, failures and restarts;
+ * local and private addresses are refused and the file is held to its folder's quota
+ */
+export async function queueDownload(url: string, options: QueueDownloadOptions = {}): Promise<Download> {
+  return invoke<Download>('queue_download', { url, ...options });
+}
+
+/**
+ * List every download, oldest first
+ */
+export async function listDownloads(): Promise<Download[]> {
+  return invoke<Download[]>('list_downloads');
+}
+
+/**
+ * Pause a queued or running download, keeping what has arrived
+ */
+export async function pauseDownload(id: string): Promise<Download> {
+  return invoke<Download>('pause_download', { id });
+}
+
+/**
+ * Continue a paused or failed download
+ */
+export async function resumeDownload(id: string): Promise<Download> {
+  return invoke<Download>('resume_download', { id });
+}
+
+/**
+ * Remove a download from the list, deleting any partial file
+ */
+export async function cancelDownload(id: string): Promise<void> {
+  return invoke<void>('cancel_download', { id });
+}
+
+// ============================================================================
+// Bandwidth
+// ============================================================================
+
+/**
+ * Get the bandwidth limit and whether it is in force now
+ */
+export async function getBandwidthLimit(): Promise<BandwidthStatus> {
+  return invoke<BandwidthStatus>('get_bandwidth_limit');
+}
+
+/**
+ * Limit backend transfers to `kbps` (0 for no limit) when the schedule says so (admin)
+ */
+export async function setBandwidthLimit(kbps: number, schedule: BandwidthSchedule): Promise<BandwidthStatus> {
+  return invoke<BandwidthStatus>('set_bandwidth_limit', { kbps, schedule });
+}
+
+// ============================================================================
+// Fetch
+// ============================================================================
+
+/**
+ * Make an HTTP request from the backend, free of CORS and with the kiosk's network settings
+ */
+export async function fetchUrl(request: FetchRequest): Promise<FetchResponse> {
+  return invoke<FetchResponse>('fetch_url', { request });
+}
+
+/**
+ * Empty one cookie jar, or all of them
+ */
+export async function clearFetchCookies(jar?: string): Promise<void> {
+  return invoke<void>('clear_fetch_cookies', { jar });
+}
+
+/**
+ * Get the proxy and allowed hosts
+ */
+export async function getFetchConfig(): Promise<FetchConfig> {
+  return invoke<FetchConfig>('get_fetch_config');
+}
+
+/**
+ * Update the proxy and allowed hosts (admin)
+ */
+export async function setFetchConfig(config: FetchConfig): Promise<void> {
+  return invoke<void>('set_fetch_config', { config });
+}
+
+// ============================================================================
+// Demo Server
+// ============================================================================
+
+/**
+ * Get the demo fixture server settings
+ */
+export async function getDemoServerConfig(): Promise<DemoServerConfig> {
+  return invoke<DemoServerConfig>('get_demo_server_config');
+}
+
+/**
+ * Update the demo fixture server and restart it (admin)
+ */
+export async function setDemoServerConfig(config: DemoServerConfig): Promise<void> {
+  return invoke<void>('set_demo_server_config', { config });
+}
+
+/**
+ * Get whether the demo fixture server is running and where
+ */
+export async function getDemoServerStatus(): Promise<DemoServerStatus> {
+  return invoke<DemoServerStatus>('get_demo_server_status');
+}
+
+// ============================================================================
+// Template
+// ============================================================================
+
+/**
+ * Render a template from the templates folder as HTML, plain text or PDF,
+ * optionally saving it to a virtual path
+ */
+export async function renderTemplate(
+  name: string,
+  data: unknown,
+  output?: TemplateOutput,
+  path?: string
+): Promise<RenderedTemplate> {
+  return invoke<RenderedTemplate>('render_template', { name, data, output, path });
+}
+
+/**
+ * List the templates in the templates folder
+ */
+export async function listTemplates(): Promise<TemplateInfo[]> {
+  return invoke<TemplateInfo[]>('list_templates');
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+/**
+ * Make a report over a range of days (today by default) and save, email or
+ * upload it; without a delivery it is only returned (supervisor)
+ */
+export async function generateReport(
+  report: ReportType,
+  range?: ReportRange,
+  format?: ReportFileFormat,
+  delivery?: ReportDelivery
+): Promise<GeneratedReport> {
+  return invoke<GeneratedReport>('generate_report', { report, range, format, delivery });
+}
+
+/**
+ * Get the scheduled reports
+ */
+export async function getReportSchedules(): Promise<ReportSchedule[]> {
+  return invoke<ReportSchedule[]>('get_report_schedules');
+}
+
+/**
+ * Replace the scheduled reports (admin)
+ */
+export async function setReportSchedules(schedules: ReportSchedule[]): Promise<void> {
+  return invoke<void>('set_report_schedules', { schedules });
+}
+
+// ============================================================================
+// Tabular File
+// ============================================================================
+
+/**
+ * Read a page of rows from a CSV, TSV or XLSX file, with column types inferred
+ */
+export async function parseTabularFile(path: string, options?: TabularOptions): Promise<TabularPage> {
+  return invoke<TabularPage>('parse_tabular_file', { path, options });
+}
+
+/**
+ * Write rows as CSV, TSV or XLSX, returning the number of data rows
+ */
+export async function writeTabularFile(
+  path: string,
+  data: TabularData,
+  options?: TabularWriteOptions
+): Promise<number> {
+  return invoke<number>('write_tabular_file', { path, data, options });
+}
+
+// ============================================================================
+// Structured File
+// ============================================================================
+
+/**
+ * Open a JSON or YAML file with its schema and any problems it has
+ */
+export async function readStructuredFile(path: string): Promise<StructuredFile> {
+  return invoke<StructuredFile>('read_structured_file', { path });
+}
+
+/**
+ * Check edited text against the file's format and schema without saving
+ */
+export async function validateStructuredFile(path: string, content: string): Promise<StructuredError[]> {
+  return invoke<StructuredError[]>('validate_structured_file', { path, content });
+}
+
+/**
+ * Save edited text as is, once it parses and passes the schema
+ */
+export async function writeStructuredFile(path: string, content: string): Promise<StructuredFile> {
+  return invoke<StructuredFile>('write_structured_file', { path, content });
+}
+
+/**
+ * Get the system config files the editor may open (admin)
+ */
+export async function getConfigFiles(): Promise<ConfigFilesPolicy> {
+  return invoke<ConfigFilesPolicy>('get_config_files');
+}
+
+/**
+ * Replace the system config files the editor may open (admin)
+ */
+export async function setConfigFiles(files: ConfigFileEntry[]): Promise<void> {
+  return invoke<void>('set_config_files', { files });
+}
+
+// ============================================================================
+// Game
+// ============================================================================
+
+/**
+ * Record a finished game's score on its leaderboard
+ */
+export async function submitScore(submission: ScoreSubmission): Promise<ScoreResult> {
+  return invoke<ScoreResult>('submit_score', { submission });
+}
+
+/**
+ * Get the best scores for a game, best first
+ */
+export async function getLeaderboard(game: string, variant?: string, limit?: number): Promise<Leaderboard> {
+  return invoke<Leaderboard>('get_leaderboard', { game, variant, limit });
+}
+
+/**
+ * Clear a game's leaderboards, or one variant's (supervisor)
+ */
+export async function clearLeaderboard(game: string, variant?: string): Promise<void> {
+  return invoke<void>('clear_leaderboard', { game, variant });
+}
+
+/**
+ * Keep a game in progress so the player can resume it later
+ */
+export async function saveGameState(game: string, gameState: unknown, player?: string): Promise<SavedGame> {
+  return invoke<SavedGame>('save_game_state', { game, gameState, player });
+}
+
+/**
+ * Get the player's saved game, if any
+ */
+export async function loadGameState(game: string, player?: string): Promise<SavedGame | null> {
+  return invoke<SavedGame | null>('load_game_state', { game, player });
+}
+
+/**
+ * Drop the player's saved game
+ */
+export async function deleteGameState(game: string, player?: string): Promise<void> {
+  return invoke<void>('delete_game_state', { game, player });
+}
+
+/**
+ * Start a Minesweeper game; mines are laid on the first reveal
+ */
+export async function newMinesweeper(options?: MinesweeperOptions): Promise<MinesweeperView> {
+  return invoke<MinesweeperView>('new_minesweeper', { options });
+}
+
+export async function getMinesweeper(id: string): Promise<MinesweeperView> {
+  return invoke<MinesweeperView>('get_minesweeper', { id });
+}
+
+/**
+ * Open a cell; a mine ends the game
+ */
+export async function minesweeperReveal(id: string, x: number, y: number): Promise<MinesweeperView> {
+  return invoke<MinesweeperView>('minesweeper_reveal', { id, x, y });
+}
+
+/**
+ * Put a flag on a covered cell, or take it off
+ */
+export async function minesweeperFlag(id: string, x: number, y: number): Promise<MinesweeperView> {
+  return invoke<MinesweeperView>('minesweeper_flag', { id, x, y });
+}
+
+/**
+ * Open the neighbours of a number whose mines are all flagged
+ */
+export async function minesweeperChord(id: string, x: number, y: number): Promise<MinesweeperView> {
+  return invoke<MinesweeperView>('minesweeper_chord', { id, x, y });
+}
+
+/**
+ * Deal a Klondike solitaire game
+ */
+export async function newSolitaire(options?: SolitaireOptions): Promise<SolitaireView> {
+  return invoke<SolitaireView>('new_solitaire', { options });
+}
+
+export async function getSolitaire(id: string): Promise<SolitaireView> {
+  return invoke<SolitaireView>('get_solitaire', { id });
+}
+
+/**
+ * Play a move; an illegal one is refused and changes nothing
+ */
+export async function solitaireMove(id: string, play: SolitaireMove): Promise<SolitaireView> {
+  return invoke<SolitaireView>('solitaire_move', { id, play });
+}
+
+/**
+ * Take back the last move
+ */
+export async function solitaireUndo(id: string): Promise<SolitaireView> {
+  return invoke<SolitaireView>('solitaire_undo', { id });
+}
+
+// ============================================================================
+// Bitmap
+// ============================================================================
+
+/**
+ * Read a BMP, ICO, CUR or ANI file
+ */
+export async function readBitmapFile(path: string): Promise<BitmapFile> {
+  return invoke<BitmapFile>('read_bitmap_file', { path });
+}
+
+/**
+ * Write images as a BMP (the first image), an icon or cursor with every
+ * image as a size, or an animated cursor with every image as a frame;
+ * returns the bytes written
+ */
+export async function writeBitmapFile(
+  path: string,
+  images: BitmapSource[],
+  options?: BitmapWriteOptions
+): Promise<number> {
+  return invoke<number>('write_bitmap_file', { path, images, options });
+}
+
+// ============================================================================
+// Icon
+// ============================================================================
+
+/**
+ * Get an icon for a file, or for a .desktop entry in the system's
+ * applications folders, `size` pixels square (32 when not given)
+ */
+export async function extractIcon(path: string, size?: number): Promise<ExtractedIcon> {
+  return invoke<ExtractedIcon>('extract_icon', { path, size });
+}
+
+// ============================================================================
+// Pointer
+// ============================================================================
+
+/**
+ * List the cursor themes installed system-wide and for the user
+ */
+export async function listCursorThemes(): Promise<CursorTheme[]> {
+  return invoke<CursorTheme[]>('list_cursor_themes');
+}
+
+/**
+ * Get the saved pointer settings
+ */
+export async function getPointerSettings(): Promise<PointerSettings> {
+  return invoke<PointerSettings>('get_pointer_settings');
+}
+
+/**
+ * Save and apply the cursor and mouse/touchpad settings, returning the parts of the session that took them
+ */
+export async function setPointerSettings(settings: PointerSettings): Promise<string[]> {
+  return invoke<string[]>('set_pointer_settings', { settings });
+}
+
+/**
+ * Convert a folder of Windows .cur and .ani files into a cursor theme
+ */
+export async function installCursorTheme(folder: string, name?: string): Promise<CursorTheme> {
+  return invoke<CursorTheme>('install_cursor_theme', { folder, name });
+}
+
+/**
+ * Remove a cursor theme installed from the control panel
+ */
+export async function removeCursorTheme(id: string): Promise<void> {
+  return invoke<void>('remove_cursor_theme', { id });
+}
+
+// ============================================================================
+// Keyboard
+// ============================================================================
+
+/**
+ * List every keyboard layout and variant the system knows
+ */
+export async function listKeyboardLayouts(): Promise<KeyboardLayout[]> {
+  return invoke<KeyboardLayout[]>('list_keyboard_layouts');
+}
+
+/**
+ * Get the signed-in operator's keyboard layouts, or the kiosk's own
+ */
+export async function getKeyboardLayout(): Promise<KeyboardSettings> {
+  return invoke<KeyboardSettings>('get_keyboard_layout');
+}
+
+/**
+ * Switch to a keyboard layout, such as `de` or `de(nodeadkeys)`, and add it to the user's layouts
+ */
+export async function setKeyboardLayout(layout: string): Promise<KeyboardSettings> {
+  return invoke<KeyboardSettings>('set_keyboard_layout', { layout });
+}
+
+/**
+ * Switch to the user's next keyboard layout
+ */
+export async function nextKeyboardLayout(): Promise<KeyboardSettings> {
+  return invoke<KeyboardSettings>('next_keyboard_layout');
+}
+
+/**
+ * Drop a keyboard layout from the user's layouts
+ */
+export async function removeKeyboardLayout(layout: string): Promise<KeyboardSettings> {
+  return invoke<KeyboardSettings>('remove_keyboard_layout', { layout });
+}
+
+// ============================================================================
+// Regional Settings
+// ============================================================================
+
+/**
+ * Get the kiosk's regional settings, or with `locale`, that locale's own formats in place of the kiosk's
+ */
+export async function getRegionalSettings(locale?: string): Promise<RegionalSettings> {
+  return invoke<RegionalSettings>('get_regional_settings', { locale });
+}
+
+/**
+ * Check and save all the regional settings at once
+ */
+export async function setRegionalSettings(settings: RegionalSettings): Promise<RegionalSettings> {
+  return invoke<RegionalSettings>('set_regional_settings', { settings });
+}
+
+// ============================================================================
+// Accessibility
+// ============================================================================
+
+/**
+ * Get the sticky, filter, toggle and mouse keys settings
+ */
+export async function getAccessibilitySettings(): Promise<AccessibilitySettings> {
+  return invoke<AccessibilitySettings>('get_accessibility_settings');
+}
+
+/**
+ * Save the keyboard accessibility settings and start or stop watching the keyboards to suit
+ */
+export async function setAccessibilitySettings(settings: AccessibilitySettings): Promise<void> {
+  return invoke<void>('set_accessibility_settings', { settings });
+}
+
+/**
+ * Get whether the keyboards are watched and grabbed, and the sticky modifiers
+ */
+export async function getAccessibilityStatus(): Promise<AccessibilityStatus> {
+  return invoke<AccessibilityStatus>('get_accessibility_status');
+}
+
+// ============================================================================
+// Magnifier
+// ============================================================================
+
+/**
+ * Open the lens and start following the pointer
+ */
+export async function startMagnifier(): Promise<MagnifierStatus> {
+  return invoke<MagnifierStatus>('start_magnifier');
+}
+
+/**
+ * Close the lens
+ */
+export async function stopMagnifier(): Promise<MagnifierStatus> {
+  return invoke<MagnifierStatus>('stop_magnifier');
+}
+
+/**
+ * Get whether the lens is open, its zoom and any capture error
+ */
+export async function getMagnifierStatus(): Promise<MagnifierStatus> {
+  return invoke<MagnifierStatus>('get_magnifier_status');
+}
+
+/**
+ * Get the lens settings
+ */
+export async function getMagnifierSettings(): Promise<MagnifierSettings> {
+  return invoke<MagnifierSettings>('get_magnifier_settings');
+}
+
+/**
+ * Save the lens settings; a running lens picks them up with its next frame
+ */
+export async function setMagnifierSettings(settings: MagnifierSettings): Promise<void> {
+  return invoke<void>('set_magnifier_settings', { settings });
+}
+
+/**
+ * Set the zoom, from 1 to 16 times; resolves to the zoom applied
+ */
+export async function setMagnifierZoom(zoom: number): Promise<number> {
+  return invoke<number>('set_magnifier_zoom', { zoom });
+}
+
+/**
+ * Step up to the next zoom level
+ */
+export async function magnifierZoomIn(): Promise<number> {
+  return invoke<number>('magnifier_zoom_in');
+}
+
+/**
+ * Step down to the previous zoom level
+ */
+export async function magnifierZoomOut(): Promise<number> {
+  return invoke<number>('magnifier_zoom_out');
+}
+
+// ============================================================================
+// Night Light
+// ============================================================================
+
+/**
+ * Get the night temperature, its schedule and the colour filter
+ */
+export async function getColorSettings(): Promise<ColorSettings> {
+  return invoke<ColorSettings>('get_color_settings');
+}
+
+/**
+ * Get the temperature and filter on screen now
+ */
+export async function getColorStatus(): Promise<ColorStatus> {
+  return invoke<ColorStatus>('get_color_status');
+}
+
+/**
+ * Set the night temperature in kelvin (1000-6500) and when it applies
+ */
+export async function setColorTemperature(kelvin: number, schedule: ColorSchedule): Promise<ColorStatus> {
+  return invoke<ColorStatus>('set_color_temperature', { kelvin, schedule });
+}
+
+/**
+ * Set the colour filter, which applies day and night
+ */
+export async function setColorFilter(filter: ColorFilter): Promise<ColorStatus> {
+  return invoke<ColorStatus>('set_color_filter', { filter });
+}
+
+// ============================================================================
+// Microphone
+// ============================================================================
+
+/**
+ * List microphones and other capture devices
+ */
+export async function listAudioInputs(): Promise<AudioDevice[]> {
+  return invoke<AudioDevice[]>('list_audio_inputs');
+}
+
+/**
+ * List speakers and other playback devices
+ */
+export async function listAudioOutputs(): Promise<AudioDevice[]> {
+  return invoke<AudioDevice[]>('list_audio_outputs');
+}
+
+/**
+ * Start recording to a virtual path such as `documents/Note.ogg`, whose extension must suit the format,
+ * from an input from listAudioInputs or the default one; levels arrive as `audio-level` events
+ */
+export async function startRecording(
+  path: string,
+  format: AudioFormat,
+  device?: string
+): Promise<AudioRecordingStatus> {
+  return invoke<AudioRecordingStatus>('start_recording', { path, format, device });
+}
+
+/**
+ * Stop the recording and return the finished file
+ */
+export async function stopRecording(): Promise<AudioRecording> {
+  return invoke<AudioRecording>('stop_recording');
+}
+
+/**
+ * Get the running recording, if any
+ */
+export async function getAudioRecordingStatus(): Promise<AudioRecordingStatus | null> {
+  return invoke<AudioRecordingStatus | null>('get_audio_recording_status');
+}
+
+// ============================================================================
+// Intercom
+// ============================================================================
+
+/**
+ * Get the intercom settings (password blanked)
+ */
+export async function getSipConfig(): Promise<SipConfig> {
+  return invoke<SipConfig>('get_sip_config');
+}
+
+/**
+ * Get the registration and the current call; changes arrive as `call-state` events
+ */
+export async function getSipStatus(): Promise<SipStatus> {
+  return invoke<SipStatus>('get_sip_status');
+}
+
+/**
+ * Register with an account, replacing any other (admin)
+ */
+export async function sipRegister(account: SipAccount): Promise<void> {
+  return invoke<void>('sip_register', { account });
+}
+
+/**
+ * Forget the account and stop the intercom (admin)
+ */
+export async function sipUnregister(): Promise<void> {
+  return invoke<void>('sip_unregister');
+}
+
+/**
+ * Set the address the help button calls (admin)
+ */
+export async function setHelpDesk(uri: string): Promise<void> {
+  return invoke<void>('set_help_desk', { uri });
+}
+
+/**
+ * Route call audio to a microphone and speaker from the device lists
+ */
+export async function setCallAudio(audio: CallAudio): Promise<void> {
+  return invoke<void>('set_call_audio', { audio });
+}
+
+/**
+ * Call an address, or the help desk when none is given; visitors may only call the help desk
+ */
+export async function sipCall(uri?: string): Promise<void> {
+  return invoke<void>('sip_call', { uri });
+}
+
+/**
+ * Answer the incoming call
+ */
+export async function sipAnswer(): Promise<void> {
+  return invoke<void>('sip_answer');
+}
+
+/**
+ * End the current call, or turn down an incoming one
+ */
+export async function sipHangup(): Promise<void> {
+  return invoke<void>('sip_hangup');
+}
+
+// ============================================================================
+// AV Self-Test
+// ============================================================================
+
+/**
+ * Start checking the camera (a /dev/video node), microphone and speaker end to end; defaults are used
+ * for any not given. Returns the job id; the job result is an AvReport.
+ */
+export async function testAvDevices(
+  camera?: string | null,
+  input?: string | null,
+  output?: string | null
+): Promise<string> {
+  return invoke('test_av_devices', { camera, input, output });
+}
+
+// ============================================================================
+// Camera Streams
+// ============================================================================
+
+/**
+ * List the configured camera streams; passwords are blank
+ */
+export async function listStreams(): Promise<CameraStream[]> {
+  return invoke<CameraStream[]>('list_streams');
+}
+
+/**
+ * Add or update a camera stream (admin), returning its id. An empty password keeps the stored one.
+ */
+export async function saveStream(stream: CameraStream): Promise<string> {
+  return invoke<string>('save_stream', { stream });
+}
+
+/**
+ * Remove a camera stream (admin), stopping its relay
+ */
+export async function deleteStream(id: string): Promise<void> {
+  return invoke<void>('delete_stream', { id });
+}
+
+/**
+ * Every stream's last known status; changes arrive as stream-status events
+ */
+export async function getStreamStatus(): Promise<StreamStatus[]> {
+  return invoke<StreamStatus[]>('get_stream_status');
+}
+
+/**
+ * Check a stream now rather than waiting for the next round
+ */
+export async function probeStream(id: string): Promise<StreamStatus> {
+  return invoke<StreamStatus>('probe_stream', { id });
+}
+
+/**
+ * Grab a JPEG frame from a stream, saving it to a virtual path if one is given (operators only)
+ */
+export async function streamSnapshot(id: string, path?: string | null): Promise<StreamSnapshot> {
+  return invoke<StreamSnapshot>('stream_snapshot', { id, path });
+}
+
+/**
+ * Start relaying a stream to HLS for the webview; play `playlist_url` once `ready` is set
+ */
+export async function startStreamRelay(id: string): Promise<RelayStatus> {
+  return invoke<RelayStatus>('start_stream_relay', { id });
+}
+
+/**
+ * Stop relaying a stream; relays configured to always run start again
+ */
+export async function stopStreamRelay(id: string): Promise<void> {
+  return invoke<void>('stop_stream_relay', { id });
+}
+
+// ============================================================================
+// Photo Frame
+// ============================================================================
+
+/**
+ * Get the photo frame settings
+ */
+export async function getPhotoFrameConfig(): Promise<PhotoFrameConfig> {
+  return invoke<PhotoFrameConfig>('get_photo_frame_config');
+}
+
+/**
+ * Change the photo frame settings (operator); a running frame picks them up from the next photo
+ */
+export async function setPhotoFrameConfig(config: PhotoFrameConfig): Promise<void> {
+  return invoke<void>('set_photo_frame_config', { config });
+}
+
+/**
+ * Start the slideshow (operator); show-photo events follow
+ */
+export async function startPhotoFrame(): Promise<void> {
+  return invoke<void>('start_photo_frame');
+}
+
+/**
+ * Stop the slideshow (operator); photo-frame-stopped follows
+ */
+export async function stopPhotoFrame(): Promise<void> {
+  return invoke<void>('stop_photo_frame');
+}
+
+/**
+ * Show the next photo now
+ */
+export async function nextPhoto(): Promise<void> {
+  return invoke<void>('next_photo');
+}
+
+/**
+ * Go back to the previous photo
+ */
+export async function previousPhoto(): Promise<void> {
+  return invoke<void>('previous_photo');
+}
+
+/**
+ * Whether the photo frame is running, how many photos it has and which is showing
+ */
+export async function getPhotoFrameStatus(): Promise<PhotoFrameStatus> {
+  return invoke<PhotoFrameStatus>('get_photo_frame_status');
 }
 
 // ============================================================================
@@ -105,11 +3623,6 @@ export function getLocalDateTime(): DateTimeInfo {
 // TODO: Future API Functions
 // ============================================================================
 
-// TODO: Add file operations
-// export async function readDirectory(path: string): Promise<FileEntry[]> { }
-// export async function copyFile(src: string, dest: string): Promise<void> { }
-// export async function deleteFile(path: string): Promise<void> { }
-
 // TODO: Add terminal operations
 // export async function spawnTerminal(cols: number, rows: number): Promise<TerminalSession> { }
 // export async function writeTerminal(id: string, data: string): Promise<void> { }